@@ -0,0 +1,139 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use criterion::{black_box, Criterion};
+use s2n_quic_core::{
+    endpoint::limits::{
+        prefix::{Prefix, PrefixPolicy},
+        ConnectionAttempt, Limiter, Outcome,
+    },
+    inet::{IpV4Address, IpV6Address, SocketAddress, SocketAddressV4, SocketAddressV6},
+};
+
+pub fn benchmarks(c: &mut Criterion) {
+    limits(c);
+    prefix_policy(c);
+}
+
+const HANDSHAKE_LIMIT: usize = 100;
+
+struct HandshakeLimit;
+
+impl Limiter for HandshakeLimit {
+    #[inline]
+    fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+        handshake_limit(info)
+    }
+}
+
+#[inline]
+fn handshake_limit(info: &ConnectionAttempt) -> Outcome {
+    if info.inflight_handshakes > HANDSHAKE_LIMIT {
+        Outcome::retry()
+    } else {
+        Outcome::allow()
+    }
+}
+
+#[inline(never)]
+fn on_connection_attempt<L: Limiter>(limiter: &mut L, info: &ConnectionAttempt) -> Outcome {
+    limiter.on_connection_attempt(info)
+}
+
+fn limits(c: &mut Criterion) {
+    let mut group = c.benchmark_group("endpoint/limits");
+
+    let builder = ConnectionAttempt::builder().with_inflight_handshakes(HANDSHAKE_LIMIT + 1);
+    let attempt = builder.build();
+
+    group.bench_function("struct", |b| {
+        let mut limiter = HandshakeLimit;
+        b.iter(|| on_connection_attempt(&mut limiter, black_box(&attempt)))
+    });
+
+    group.bench_function("closure", |b| {
+        let limit = HANDSHAKE_LIMIT;
+        let mut limiter = move |info: &ConnectionAttempt| {
+            if info.inflight_handshakes > limit {
+                Outcome::retry()
+            } else {
+                Outcome::allow()
+            }
+        };
+        b.iter(|| on_connection_attempt(&mut limiter, black_box(&attempt)))
+    });
+
+    group.bench_function("boxed", |b| {
+        let mut limiter: Box<dyn Limiter> = Box::new(HandshakeLimit);
+        b.iter(|| on_connection_attempt(&mut limiter, black_box(&attempt)))
+    });
+
+    group.finish();
+}
+
+/// The number of prefixes added to each list for each address family
+const PREFIX_COUNT: u16 = 1000;
+
+fn prefix_policy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("endpoint/limits/prefix");
+
+    let mut builder = PrefixPolicy::builder().with_deny_outcome(Outcome::close());
+    for i in 0..PREFIX_COUNT {
+        let [a, b] = i.to_be_bytes();
+        // deny a /16 with an allowed /24 inside it
+        builder = builder
+            .with_deny(Prefix::new(IpV4Address::new([a.wrapping_add(10), b, 0, 0]), 16).unwrap())
+            .with_allow(Prefix::new(IpV4Address::new([a.wrapping_add(10), b, 1, 0]), 24).unwrap());
+
+        let deny = IpV6Address::from([0x2001, 0xdb8, i, 0, 0, 0, 0, 0]);
+        let allow = IpV6Address::from([0x2001, 0xdb8, i, 1, 0, 0, 0, 0]);
+        builder = builder
+            .with_deny(Prefix::new(deny, 48).unwrap())
+            .with_allow(Prefix::new(allow, 64).unwrap());
+    }
+    let mut policy = builder.build();
+
+    let addresses: [(&str, SocketAddress); 6] = [
+        (
+            "ipv4/allowed",
+            SocketAddressV4::new([10, 100, 1, 1], 443).into(),
+        ),
+        (
+            "ipv4/denied",
+            SocketAddressV4::new([10, 100, 2, 1], 443).into(),
+        ),
+        (
+            "ipv4/unmatched",
+            SocketAddressV4::new([203, 0, 113, 1], 443).into(),
+        ),
+        (
+            "ipv6/allowed",
+            SocketAddressV6::new(IpV6Address::from([0x2001, 0xdb8, 100, 1, 0, 0, 0, 1]), 443)
+                .into(),
+        ),
+        (
+            "ipv6/denied",
+            SocketAddressV6::new(IpV6Address::from([0x2001, 0xdb8, 100, 2, 0, 0, 0, 1]), 443)
+                .into(),
+        ),
+        (
+            "ipv6/mapped",
+            SocketAddressV6::new(
+                IpV6Address::from([0, 0, 0, 0, 0, 0xffff, 0x0a64, 0x0101]),
+                443,
+            )
+            .into(),
+        ),
+    ];
+
+    for (name, address) in addresses {
+        let builder = ConnectionAttempt::builder().with_remote_address(address);
+        let attempt = builder.build();
+
+        group.bench_function(name, |b| {
+            b.iter(|| on_connection_attempt(&mut policy, black_box(&attempt)))
+        });
+    }
+
+    group.finish();
+}
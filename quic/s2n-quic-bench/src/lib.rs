@@ -5,20 +5,24 @@ use criterion::Criterion;
 
 mod buffer;
 mod crypto;
+mod endpoint;
 mod frame;
 mod inet;
 mod packet;
 mod sync;
+mod timer;
 mod varint;
 mod xdp;
 
 pub fn benchmarks(c: &mut Criterion) {
     buffer::benchmarks(c);
     crypto::benchmarks(c);
+    endpoint::benchmarks(c);
     frame::benchmarks(c);
     inet::benchmarks(c);
     packet::benchmarks(c);
     sync::benchmarks(c);
+    timer::benchmarks(c);
     varint::benchmarks(c);
     xdp::benchmarks(c);
 }
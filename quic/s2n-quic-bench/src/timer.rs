@@ -0,0 +1,143 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use criterion::{black_box, BenchmarkId, Criterion, Throughput};
+use s2n_quic_core::time::{clock::testing, wheel::Wheel, Duration, Timestamp};
+use std::collections::BTreeSet;
+
+/// The number of simulated connections
+const CONNECTIONS: usize = 50_000;
+/// The number of timers each connection keeps armed (loss detection, idle and pacing)
+const TIMERS_PER_CONNECTION: usize = 3;
+const TIMERS: usize = CONNECTIONS * TIMERS_PER_CONNECTION;
+
+const GRANULARITY: Duration = Duration::from_millis(1);
+
+pub fn benchmarks(c: &mut Criterion) {
+    let epoch = testing::now();
+    let delays = delays();
+
+    let mut group = c.benchmark_group("timer");
+    group.throughput(Throughput::Elements(TIMERS as u64));
+
+    group.bench_with_input(
+        BenchmarkId::new("wheel/rearm", TIMERS),
+        &delays,
+        |b, delays| {
+            let mut wheel = Wheel::new(epoch, GRANULARITY);
+            let keys: Vec<_> = (0..TIMERS).map(|timer| wheel.insert(timer)).collect();
+            let mut now = epoch;
+            b.iter(|| {
+                now += GRANULARITY;
+                for (key, delay) in keys.iter().zip(delays) {
+                    wheel.arm(*key, now + *delay);
+                }
+                black_box(wheel.next_expiration())
+            })
+        },
+    );
+
+    group.bench_with_input(
+        BenchmarkId::new("btree/rearm", TIMERS),
+        &delays,
+        |b, delays| {
+            let mut tree = BTreeSet::new();
+            let mut deadlines = vec![None; TIMERS];
+            let mut now = epoch;
+            b.iter(|| {
+                now += GRANULARITY;
+                for (timer, delay) in delays.iter().enumerate() {
+                    rearm_btree(&mut tree, &mut deadlines, timer, now + *delay);
+                }
+                black_box(tree.iter().next().copied())
+            })
+        },
+    );
+
+    group.bench_with_input(
+        BenchmarkId::new("wheel/expire", TIMERS),
+        &delays,
+        |b, delays| {
+            let mut wheel = Wheel::new(epoch, GRANULARITY);
+            let keys: Vec<_> = (0..TIMERS).map(|timer| wheel.insert(timer)).collect();
+            let mut now = epoch;
+            b.iter(|| {
+                for (key, delay) in keys.iter().zip(delays) {
+                    wheel.arm(*key, now + *delay);
+                }
+                now += MAX_DELAY;
+                let mut expired = 0;
+                while let Some(key) = wheel.poll_expired(now) {
+                    black_box(key);
+                    expired += 1;
+                }
+                assert_eq!(expired, TIMERS);
+            })
+        },
+    );
+
+    group.bench_with_input(
+        BenchmarkId::new("btree/expire", TIMERS),
+        &delays,
+        |b, delays| {
+            let mut tree = BTreeSet::new();
+            let mut deadlines = vec![None; TIMERS];
+            let mut now = epoch;
+            b.iter(|| {
+                for (timer, delay) in delays.iter().enumerate() {
+                    rearm_btree(&mut tree, &mut deadlines, timer, now + *delay);
+                }
+                now += MAX_DELAY;
+                let mut expired = 0;
+                while let Some(&(deadline, timer)) = tree.iter().next() {
+                    if deadline > now {
+                        break;
+                    }
+                    tree.remove(&(deadline, timer));
+                    deadlines[timer] = None;
+                    black_box(timer);
+                    expired += 1;
+                }
+                assert_eq!(expired, TIMERS);
+            })
+        },
+    );
+
+    group.finish();
+}
+
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Returns a deterministic spread of delays, modeling short loss detection and pacing timers
+/// along with long idle timers
+fn delays() -> Vec<Duration> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    (0..TIMERS)
+        .map(|timer| {
+            // xorshift
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+
+            let max = match timer % TIMERS_PER_CONNECTION {
+                0 => 500,
+                1 => MAX_DELAY.as_millis() as u64,
+                _ => 10,
+            };
+            Duration::from_millis(state % max + 1)
+        })
+        .collect()
+}
+
+/// Re-arms a timer in a set ordered by deadline, as the connection container does today
+fn rearm_btree(
+    tree: &mut BTreeSet<(Timestamp, usize)>,
+    deadlines: &mut [Option<Timestamp>],
+    timer: usize,
+    deadline: Timestamp,
+) {
+    if let Some(prev) = deadlines[timer].replace(deadline) {
+        tree.remove(&(prev, timer));
+    }
+    tree.insert((deadline, timer));
+}
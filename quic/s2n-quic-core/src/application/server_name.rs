@@ -36,6 +36,61 @@ impl ServerName {
         self.0
     }
 
+    /// Returns `true` if the name can be sent in the TLS `server_name` extension
+    ///
+    /// As required by [RFC 6066](https://www.rfc-editor.org/rfc/rfc6066#section-3), the name
+    /// must be a fully qualified DNS host name without a trailing dot. IP literals are rejected
+    /// since they are not permitted in the extension. See [`Self::is_ip_address`].
+    ///
+    /// ```rust
+    /// # use s2n_quic_core::application::ServerName;
+    /// assert!(ServerName::from("example.com").is_valid_host_name());
+    /// assert!(!ServerName::from("example.com.").is_valid_host_name());
+    /// assert!(!ServerName::from("127.0.0.1").is_valid_host_name());
+    /// ```
+    pub fn is_valid_host_name(&self) -> bool {
+        let name = self.as_str();
+
+        if name.is_empty() || name.len() > 253 {
+            return false;
+        }
+
+        let is_valid_label = |label: &str| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        };
+
+        if !name.split('.').all(is_valid_label) {
+            return false;
+        }
+
+        // an all-numeric final label is an IPv4 literal rather than a host name. IPv6 literals
+        // are already rejected since `:` isn't a valid label character.
+        let last = name.rsplit('.').next().unwrap_or(name);
+        !last.bytes().all(|b| b.is_ascii_digit())
+    }
+
+    /// Returns `true` if the name is an IPv4 or IPv6 address literal
+    ///
+    /// Such names can't be sent in the TLS `server_name` extension, but the peer's certificate
+    /// can still be verified against them.
+    ///
+    /// ```rust
+    /// # use s2n_quic_core::application::ServerName;
+    /// assert!(ServerName::from("127.0.0.1").is_ip_address());
+    /// assert!(ServerName::from("2001:db8::1").is_ip_address());
+    /// assert!(!ServerName::from("example.com").is_ip_address());
+    /// ```
+    #[inline]
+    pub fn is_ip_address(&self) -> bool {
+        self.as_str().parse::<crate::inet::IpAddress>().is_ok()
+    }
+
     #[inline]
     fn as_str(&self) -> &str {
         // Safety: the byte array is validated as a valid UTF-8 string
@@ -73,3 +128,56 @@ impl core::ops::Deref for ServerName {
         self.as_str()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_name_validation() {
+        for name in [
+            "localhost",
+            "example.com",
+            "a-b.example.com",
+            "xn--bcher-kva.example",
+        ] {
+            assert!(ServerName::from(name).is_valid_host_name(), "{name}");
+        }
+
+        for name in [
+            "",
+            "example.com.",
+            ".example.com",
+            "a..example.com",
+            "-a.example.com",
+            "a-.example.com",
+            "exa mple.com",
+            "127.0.0.1",
+            "::1",
+            "[::1]",
+            "2001:db8::1",
+        ] {
+            assert!(!ServerName::from(name).is_valid_host_name(), "{name:?}");
+        }
+
+        let long_label = "a".repeat(64);
+        assert!(!ServerName::from(long_label.as_str()).is_valid_host_name());
+    }
+
+    #[test]
+    fn ip_address_detection() {
+        for name in [
+            "127.0.0.1",
+            "192.0.2.255",
+            "::1",
+            "2001:db8::1",
+            "::ffff:192.0.2.1",
+        ] {
+            assert!(ServerName::from(name).is_ip_address(), "{name}");
+        }
+
+        for name in ["", "localhost", "1.2.3", "256.0.0.1", "[::1]", "127.0.0.1."] {
+            assert!(!ServerName::from(name).is_ip_address(), "{name:?}");
+        }
+    }
+}
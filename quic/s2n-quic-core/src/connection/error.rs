@@ -372,7 +372,17 @@ pub fn as_frame<'a, F: connection::close::Formatter>(
 
             Some((early, one_rtt))
         }
-        Error::MaxHandshakeDurationExceeded { .. } => None,
+        Error::MaxHandshakeDurationExceeded { .. } => {
+            // let the peer know the handshake was abandoned instead of leaving it to time out.
+            // Connections on clients don't send this frame and close silently instead.
+            let error = transport::Error::CONNECTION_REFUSED
+                .with_reason("the handshake took too long to complete");
+
+            let early = formatter.format_early_transport_error(context, error);
+            let one_rtt = formatter.format_transport_error(context, error);
+
+            Some((early, one_rtt))
+        }
         Error::ImmediateClose { .. } => None,
         Error::EndpointClosing { .. } => None,
         Error::Unspecified { .. } => {
@@ -9,8 +9,9 @@ use crate::{
         AckDelayExponent, ActiveConnectionIdLimit, InitialFlowControlLimits, InitialMaxData,
         InitialMaxStreamDataBidiLocal, InitialMaxStreamDataBidiRemote, InitialMaxStreamDataUni,
         InitialMaxStreamsBidi, InitialMaxStreamsUni, InitialStreamLimits, MaxAckDelay,
-        MaxDatagramFrameSize, MaxIdleTimeout, TransportParameters,
+        MaxDatagramFrameSize, MaxIdleTimeout, MinAckDelay, TransportParameters,
     },
+    varint::VarInt,
 };
 use core::{convert::TryInto, time::Duration};
 use s2n_codec::decoder_invariant;
@@ -57,6 +58,9 @@ pub struct Limits {
     pub(crate) max_open_remote_bidirectional_streams: InitialMaxStreamsBidi,
     pub(crate) max_open_remote_unidirectional_streams: InitialMaxStreamsUni,
     pub(crate) max_ack_delay: MaxAckDelay,
+    pub(crate) min_ack_delay: Option<MinAckDelay>,
+    pub(crate) requested_ack_eliciting_threshold: Option<VarInt>,
+    pub(crate) requested_max_ack_delay: Option<Duration>,
     pub(crate) ack_delay_exponent: AckDelayExponent,
     pub(crate) max_active_connection_ids: ActiveConnectionIdLimit,
     pub(crate) ack_elicitation_interval: u8,
@@ -66,6 +70,7 @@ pub struct Limits {
     pub(crate) max_keep_alive_period: Duration,
     pub(crate) max_datagram_frame_size: MaxDatagramFrameSize,
     pub(crate) initial_round_trip_time: Duration,
+    pub(crate) grease: bool,
 }
 
 impl Default for Limits {
@@ -101,6 +106,9 @@ impl Limits {
             max_open_remote_bidirectional_streams: InitialMaxStreamsBidi::RECOMMENDED,
             max_open_remote_unidirectional_streams: InitialMaxStreamsUni::RECOMMENDED,
             max_ack_delay: MaxAckDelay::RECOMMENDED,
+            min_ack_delay: None,
+            requested_ack_eliciting_threshold: None,
+            requested_max_ack_delay: None,
             ack_delay_exponent: AckDelayExponent::RECOMMENDED,
             max_active_connection_ids: ActiveConnectionIdLimit::RECOMMENDED,
             ack_elicitation_interval: ack::Settings::RECOMMENDED.ack_elicitation_interval,
@@ -110,6 +118,7 @@ impl Limits {
             max_keep_alive_period: MAX_KEEP_ALIVE_PERIOD_DEFAULT,
             max_datagram_frame_size: MaxDatagramFrameSize::DEFAULT,
             initial_round_trip_time: recovery::DEFAULT_INITIAL_RTT,
+            grease: true,
         }
     }
 
@@ -207,6 +216,42 @@ impl Limits {
         u64
     );
     setter!(with_max_ack_delay, max_ack_delay, Duration);
+
+    /// Enables the ACK frequency extension by advertising the minimum amount of time the
+    /// endpoint is willing to delay acknowledgements.
+    ///
+    /// Peers supporting the extension may then request acknowledgements to be sent less
+    /// frequently. The advertised value is capped at the configured `max_ack_delay`.
+    pub fn with_min_ack_delay(mut self, value: Duration) -> Result<Self, ValidationError> {
+        self.min_ack_delay = Some(value.try_into()?);
+        Ok(self)
+    }
+
+    /// Sets the number of ack-eliciting packets the peer is requested to receive before
+    /// immediately sending an acknowledgement.
+    ///
+    /// The request is only sent to peers supporting the ACK frequency extension.
+    pub fn with_requested_ack_eliciting_threshold(
+        mut self,
+        value: u64,
+    ) -> Result<Self, ValidationError> {
+        self.requested_ack_eliciting_threshold = Some(value.try_into()?);
+        Ok(self)
+    }
+
+    /// Sets the maximum amount of time the peer is requested to delay sending acknowledgements.
+    ///
+    /// The request is only sent to peers supporting the ACK frequency extension. The
+    /// requested value is raised to the peer's advertised `min_ack_delay`, if needed.
+    pub fn with_requested_max_ack_delay(
+        mut self,
+        value: Duration,
+    ) -> Result<Self, ValidationError> {
+        // the delay is encoded in microseconds
+        let _: VarInt = value.as_micros().try_into()?;
+        self.requested_max_ack_delay = Some(value);
+        Ok(self)
+    }
     setter!(
         with_max_active_connection_ids,
         max_active_connection_ids,
@@ -257,6 +302,18 @@ impl Limits {
         Ok(self)
     }
 
+    /// Enables or disables greasing, which is enabled by default
+    ///
+    /// When enabled, the endpoint includes a reserved transport parameter with a random
+    /// identifier and value, and advertises a random reserved version in the
+    /// version_information transport parameter. This ensures peers ignore values they
+    /// don't support, as required by the specification. The random values are generated
+    /// with the endpoint's random provider.
+    pub fn with_grease(mut self, enabled: bool) -> Result<Self, ValidationError> {
+        self.grease = enabled;
+        Ok(self)
+    }
+
     // internal APIs
 
     #[doc(hidden)]
@@ -277,6 +334,31 @@ impl Limits {
         }
     }
 
+    #[doc(hidden)]
+    #[inline]
+    pub fn min_ack_delay(&self) -> Option<MinAckDelay> {
+        let min_ack_delay = self.min_ack_delay?;
+        let max_ack_delay = self.max_ack_delay.as_duration();
+
+        if min_ack_delay.as_duration() <= max_ack_delay {
+            Some(min_ack_delay)
+        } else {
+            max_ack_delay.try_into().ok()
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn requested_ack_eliciting_threshold(&self) -> Option<VarInt> {
+        self.requested_ack_eliciting_threshold
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn requested_max_ack_delay(&self) -> Option<Duration> {
+        self.requested_max_ack_delay
+    }
+
     #[doc(hidden)]
     #[inline]
     pub const fn initial_flow_control_limits(&self) -> InitialFlowControlLimits {
@@ -335,6 +417,12 @@ impl Limits {
     pub fn initial_round_trip_time(&self) -> Duration {
         self.initial_round_trip_time
     }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn grease(&self) -> bool {
+        self.grease
+    }
 }
 
 /// Creates limits for a given connection
@@ -369,4 +457,29 @@ mod tests {
         assert!(limits.with_bidirectional_remote_data_window(data).is_ok());
         assert!(limits.with_unidirectional_data_window(data).is_ok());
     }
+
+    // The advertised min_ack_delay can't exceed the max_ack_delay
+    #[test]
+    fn min_ack_delay_test() {
+        let limits = Limits::default();
+        assert_eq!(limits.min_ack_delay(), None);
+
+        let limits = limits.with_min_ack_delay(Duration::from_millis(1)).unwrap();
+        assert_eq!(
+            limits.min_ack_delay().unwrap().as_duration(),
+            Duration::from_millis(1)
+        );
+
+        let limits = limits
+            .with_min_ack_delay(Duration::from_millis(100))
+            .unwrap();
+        assert_eq!(
+            limits.min_ack_delay().unwrap().as_duration(),
+            MaxAckDelay::RECOMMENDED.as_duration()
+        );
+
+        assert!(limits
+            .with_min_ack_delay(Duration::from_secs(1000))
+            .is_err());
+    }
 }
@@ -64,6 +64,11 @@ impl<'a> ProtectedPayload<'a> {
         self.buffer.len()
     }
 
+    /// Returns the bytes of the entire packet, including the header
+    pub fn as_bytes(&self) -> &[u8] {
+        self.buffer.peek().into_less_safe_slice()
+    }
+
     /// Returns `true` if the payload is empty
     pub fn is_empty(&self) -> bool {
         self.buffer.is_empty()
@@ -1,7 +1,7 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::crypto::CryptoError;
+use crate::{crypto::CryptoError, packet::version::QUIC_VERSION_1};
 use hex_literal::hex;
 
 pub const INTEGRITY_TAG_LEN: usize = 16;
@@ -10,6 +10,28 @@ pub type IntegrityTag = [u8; INTEGRITY_TAG_LEN];
 pub trait RetryKey {
     fn generate_tag(payload: &[u8]) -> IntegrityTag;
     fn validate(payload: &[u8], tag: IntegrityTag) -> Result<(), CryptoError>;
+
+    /// Generates the integrity tag for a Retry packet with the given QUIC version
+    ///
+    /// Returns `None` if the implementation doesn't support the version.
+    #[inline]
+    fn generate_tag_for_version(version: u32, payload: &[u8]) -> Option<IntegrityTag> {
+        (version == QUIC_VERSION_1).then(|| Self::generate_tag(payload))
+    }
+
+    /// Validates the integrity tag for a Retry packet with the given QUIC version
+    #[inline]
+    fn validate_for_version(
+        version: u32,
+        payload: &[u8],
+        tag: IntegrityTag,
+    ) -> Result<(), CryptoError> {
+        if version == QUIC_VERSION_1 {
+            Self::validate(payload, tag)
+        } else {
+            Err(CryptoError::DECRYPT_ERROR)
+        }
+    }
 }
 
 //= https://www.rfc-editor.org/rfc/rfc9001#section-5.8
@@ -26,6 +48,16 @@ pub const SECRET_KEY_BYTES: [u8; 16] = hex!("be0c690b9f66575a1d766b54e368c84e");
 
 pub const NONCE_BYTES: [u8; 12] = hex!("461599d35d632bf2239825bb");
 
+// https://www.rfc-editor.org/rfc/rfc9369#section-3.3.3
+// The key and nonce used for the Retry Integrity Tag (Section 5.8 of [QUIC-TLS])
+// change to:
+//
+// key = 0x8fb4b01b56ac48e260fbcbcead7ccc92
+// nonce = 0xd86969bc2d7c6d9990efb04a
+
+pub const SECRET_KEY_BYTES_V2: [u8; 16] = hex!("8fb4b01b56ac48e260fbcbcead7ccc92");
+pub const NONCE_BYTES_V2: [u8; 12] = hex!("d86969bc2d7c6d9990efb04a");
+
 pub mod example {
     use super::*;
 
@@ -77,3 +109,31 @@ pub mod example {
 
     pub const TOKEN_LEN: usize = 5;
 }
+
+pub mod example_v2 {
+    use super::*;
+
+    pub const PACKET_LEN: usize = 36;
+
+    // https://www.rfc-editor.org/rfc/rfc9369#appendix-A.4
+    // This shows a Retry packet that might be sent in response to the Initial
+    // packet in Appendix A.2. The integrity check includes the client-chosen
+    // connection ID value of 0x8394c8f03e515708, but that value is not included
+    // in the final Retry packet:
+    //
+    // cf6b3343cf0008f067a5502a4262b574 6f6b656ec8646ce8bfe33952d9555436
+    // 65dcc7b6
+    pub const PACKET: [u8; PACKET_LEN] = hex!(
+        "
+        cf6b3343cf0008f067a5502a4262b574 6f6b656ec8646ce8bfe33952d9555436
+        65dcc7b6
+        "
+    );
+
+    pub const PSEUDO_PACKET: [u8; 29] =
+        hex!("088394c8f03e515708 cf6b3343cf 00 08f067a5502a4262b5 746f6b656e");
+
+    pub const EXPECTED_TAG: [u8; 16] = hex!("c8646ce8bfe33952d955543665dcc7b6");
+
+    pub const VERSION: u32 = 0x6b33_43cf;
+}
@@ -6,6 +6,7 @@ pub use bytes::{Bytes, BytesMut};
 use core::{convert::TryFrom, fmt::Debug};
 use zerocopy::{AsBytes, FromBytes, FromZeroes, Unaligned};
 
+pub mod handshake;
 #[cfg(any(test, feature = "testing"))]
 pub mod testing;
 
@@ -32,6 +33,57 @@ impl TlsExportError {
     }
 }
 
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ChainError {
+    #[non_exhaustive]
+    Failure,
+}
+
+impl ChainError {
+    pub fn failure() -> Self {
+        ChainError::Failure
+    }
+}
+
+/// A key exchange group negotiated in the TLS handshake
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct KeyExchangeGroup<'a> {
+    /// The name of the group, e.g. `x25519` or `x25519_kyber-512-r3`
+    pub name: &'a str,
+    /// Whether the group is a hybrid post-quantum group
+    pub is_post_quantum: bool,
+}
+
+impl<'a> KeyExchangeGroup<'a> {
+    pub fn new(name: &'a str, is_post_quantum: bool) -> Self {
+        Self {
+            name,
+            is_post_quantum,
+        }
+    }
+}
+
+/// Counters describing the TLS handshake
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HandshakeStats {
+    /// The number of TLS handshake bytes sent to the peer
+    pub bytes_sent: u64,
+    /// The number of TLS handshake bytes received from the peer
+    pub bytes_received: u64,
+}
+
+impl HandshakeStats {
+    pub fn new(bytes_sent: u64, bytes_received: u64) -> Self {
+        Self {
+            bytes_sent,
+            bytes_received,
+        }
+    }
+}
+
 pub trait TlsSession: Send {
     /// See <https://datatracker.ietf.org/doc/html/rfc5705> and <https://www.rfc-editor.org/rfc/rfc8446>.
     fn tls_exporter(
@@ -42,6 +94,41 @@ pub trait TlsSession: Send {
     ) -> Result<(), TlsExportError>;
 
     fn cipher_suite(&self) -> CipherSuite;
+
+    /// Returns the key exchange group negotiated for the handshake
+    ///
+    /// The group is known once the server's key share is selected. Providers which can't report
+    /// the group keep the default, which returns `None`.
+    fn key_exchange_group(&self) -> Option<KeyExchangeGroup> {
+        None
+    }
+
+    /// Returns the type of handshake that was performed
+    ///
+    /// The type is only known once the handshake completes, since a HelloRetryRequest or client
+    /// authentication may occur at any point before then. Providers which can't report the type
+    /// keep the default, which returns `None`.
+    fn handshake_type(&self) -> Option<handshake::HandshakeType> {
+        None
+    }
+
+    /// Returns the number of handshake bytes exchanged with the peer
+    ///
+    /// The counters stop at handshake completion, so post-handshake messages such as session
+    /// tickets aren't included. Providers which don't count handshake bytes keep the default,
+    /// which returns `None`.
+    fn handshake_stats(&self) -> Option<HandshakeStats> {
+        None
+    }
+
+    /// Returns the DER-encoded certificate chain presented by the peer, starting with the leaf
+    ///
+    /// An empty chain is returned if the peer didn't present a certificate. Providers which
+    /// can't read the chain keep the default, which returns an error.
+    #[cfg(feature = "alloc")]
+    fn peer_cert_chain_der(&self) -> Result<alloc::vec::Vec<alloc::vec::Vec<u8>>, ChainError> {
+        Err(ChainError::failure())
+    }
 }
 
 //= https://www.rfc-editor.org/rfc/rfc9000#section-4
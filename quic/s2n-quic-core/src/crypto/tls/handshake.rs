@@ -0,0 +1,19 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Describes a completed TLS handshake
+//!
+//! The types are kept in their own module since [`super::HandshakeType`] already names the TLS
+//! handshake message types.
+
+/// Describes the type of TLS handshake that was performed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HandshakeType {
+    /// The session was resumed from a previous connection
+    pub resumed: bool,
+    /// The server requested the client to send a new ClientHello
+    pub hello_retry_request: bool,
+    /// The client authenticated itself with a certificate
+    pub client_auth: bool,
+}
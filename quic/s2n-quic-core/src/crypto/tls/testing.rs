@@ -45,6 +45,10 @@ pub mod certificates {
     pem!(UNTRUSTED_CERT_PEM, "untrusted_cert.pem");
     pem!(UNTRUSTED_KEY_PEM, "untrusted_key.pem");
 
+    // An OCSP response reporting CERT_PEM as good, signed by its own key and valid from 2000
+    // until 2500.
+    der!(OCSP_RESPONSE_DER, "ocsp.der");
+
     // Certificates and Keys used specifically for testing the mTLS (mutual TLS) feature.
     //
     // The MTLS_CA_CERT is the root CA cert and used to sign both MTLS_SERVER_CERT and
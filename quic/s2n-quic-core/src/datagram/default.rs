@@ -256,6 +256,7 @@ pub struct Sender {
     smoothed_packet_size: f64,
     waker: Option<Waker>,
     max_datagram_payload: u64,
+    max_datagram_size: usize,
     error: Option<connection::Error>,
 }
 
@@ -426,6 +427,15 @@ impl Sender {
             7.0 / 8.0 * self.smoothed_packet_size + 1.0 / 8.0 * capacity as f64;
     }
 
+    /// Returns the size of the largest datagram that can currently be sent to the peer.
+    ///
+    /// This is updated as packets are transmitted to track changes in the path MTU. Before
+    /// the first packet is transmitted, the peer's transport parameter limit is returned.
+    /// Datagrams which are larger than this value are dropped when they are transmitted.
+    pub fn max_datagram_size(&self) -> usize {
+        self.max_datagram_size
+    }
+
     /// Returns the largest packet space for datagrams seen during this connection.
     ///
     /// Should be used to determine an appropriate datagram size that can be sent in
@@ -453,6 +463,8 @@ impl Sender {
 
 impl super::Sender for Sender {
     fn on_transmit<P: Packet>(&mut self, packet: &mut P) {
+        self.max_datagram_size = packet.max_datagram_size();
+
         // Cede space to stream data when datagrams are not prioritized
         if packet.has_pending_streams() && !packet.datagrams_prioritized() {
             return;
@@ -540,6 +552,7 @@ impl SenderBuilder {
             queue: VecDeque::with_capacity(self.queue_capacity),
             capacity: self.queue_capacity,
             max_datagram_payload: self.max_datagram_payload,
+            max_datagram_size: self.max_datagram_payload.try_into().unwrap_or(usize::MAX),
             max_packet_space: 0,
             min_packet_space: 0,
             smoothed_packet_size: 0.0,
@@ -780,6 +793,29 @@ mod tests {
         assert!(!default_sender.queue.is_empty());
     }
 
+    #[test]
+    // Check that the max datagram size follows the packet space on the current path
+    fn max_datagram_size_test() {
+        let conn_info = ConnectionInfo::new(100, noop_waker());
+        let mut default_sender = Sender::builder()
+            .with_connection_info(&conn_info)
+            .build()
+            .unwrap();
+
+        // The peer's limit is used before any packets are transmitted
+        assert_eq!(default_sender.max_datagram_size(), 100);
+
+        for capacity in [50, 80, 20] {
+            let mut packet = MockPacket {
+                remaining_capacity: capacity,
+                has_pending_streams: true,
+                datagrams_prioritized: false,
+            };
+            crate::datagram::Sender::on_transmit(&mut default_sender, &mut packet);
+            assert_eq!(default_sender.max_datagram_size(), capacity);
+        }
+    }
+
     fn fake_receive_context() -> crate::datagram::ReceiveContext<'static> {
         crate::datagram::ReceiveContext {
             path: crate::event::api::Path {
@@ -913,6 +949,10 @@ mod tests {
             self.remaining_capacity
         }
 
+        fn max_datagram_size(&self) -> usize {
+            self.remaining_capacity
+        }
+
         fn write_datagram(&mut self, data: &[u8]) -> Result<(), WriteError> {
             self.write_datagram_vectored(&[data])
         }
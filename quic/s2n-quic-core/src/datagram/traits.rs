@@ -103,6 +103,13 @@ pub trait Packet {
     /// Returns the remaining space in the packet left to write datagrams
     fn remaining_capacity(&self) -> usize;
 
+    /// Returns the size of the largest datagram that can be written to a packet
+    ///
+    /// This is limited by both the current path MTU and the peer's `max_datagram_frame_size`
+    /// transport parameter, so the value may change as the MTU is discovered over the
+    /// lifetime of the connection.
+    fn max_datagram_size(&self) -> usize;
+
     /// Writes a single datagram to a packet. This function should be called
     /// per datagram.
     fn write_datagram(&mut self, data: &[u8]) -> Result<(), WriteError>;
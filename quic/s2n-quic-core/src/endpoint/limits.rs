@@ -2,9 +2,45 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    connection,
     event::{api::SocketAddress, IntoEvent, Timestamp},
     inet,
+    path::MINIMUM_MTU,
+    transport,
+    varint::VarInt,
 };
+use core::{
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// The default maximum number of delayed responses the endpoint holds for each response type
+///
+/// Each delayed response holds an encoded packet of up to 1200 bytes along with an index entry, so
+/// the default limit uses up to roughly 1.3MB for Retry packets and another 1.3MB for
+/// CONNECTION_CLOSE packets.
+pub const DEFAULT_MAX_DELAYED_RESPONSES: usize = 1024;
+
+/// The default maximum number of connection attempts the endpoint holds while waiting for the
+/// limiter to decide on their outcome
+///
+/// Each pending attempt holds a copy of its datagram, including any packets coalesced with the
+/// Initial packet, so the default limit uses up to roughly 1.5MB with a 1500 byte MTU.
+pub const DEFAULT_MAX_PENDING_ATTEMPTS: usize = 1024;
+
+/// The default amount of time a connection attempt waits for the limiter to decide on its outcome
+/// before it is dropped
+pub const DEFAULT_PENDING_ATTEMPT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The default length of the sliding window the endpoint measures the connection attempt rate
+/// over
+pub const DEFAULT_ATTEMPT_RATE_WINDOW: Duration = Duration::from_secs(1);
+
+#[cfg(feature = "alloc")]
+pub mod prefix;
+
+/// The version number of QUIC version 1, defined in RFC 9000
+const QUIC_VERSION_1: u32 = 0x1;
 
 /// Outcome describes how the library should proceed on a connection attempt. The implementor will
 /// use information from the ConnectionAttempt object to determine how the library should handle
@@ -22,19 +58,30 @@ pub enum Outcome {
     ///
     /// Use `Outcome::retry()` to construct this variant
     #[non_exhaustive]
-    Retry,
+    Retry {
+        /// How long to wait before sending the Retry packet
+        delay: Duration,
+    },
 
     /// Silently drop the connection attempt
     ///
     /// Use `Outcome::drop()` to construct this variant
     #[non_exhaustive]
-    Drop,
+    Drop {
+        /// Why the attempt was dropped, reported in the endpoint's datagram dropped event
+        reason: Option<&'static str>,
+    },
 
     /// Cleanly close the connection
     ///
     /// Use `Outcome::close()` to construct this variant
     #[non_exhaustive]
-    Close,
+    Close {
+        /// The error sent to the peer in a CONNECTION_CLOSE frame
+        error: transport::Error,
+        /// How long to wait before sending the CONNECTION_CLOSE frame
+        delay: Duration,
+    },
 }
 
 impl Outcome {
@@ -45,17 +92,156 @@ impl Outcome {
 
     /// Defer the connection by sending a Retry packet
     pub fn retry() -> Self {
-        Self::Retry
+        Self::Retry {
+            delay: Duration::ZERO,
+        }
     }
 
     /// Silently drop the connection attempt
     pub fn drop() -> Self {
-        Self::Drop
+        Self::Drop { reason: None }
     }
 
     /// Cleanly close the connection
+    ///
+    /// The peer is sent a CONNECTION_CLOSE frame with the `CONNECTION_REFUSED` error code, which
+    /// can be changed with [`Self::with_error_code`] and [`Self::with_reason`].
     pub fn close() -> Self {
-        Self::Close
+        Self::Close {
+            error: transport::Error::CONNECTION_REFUSED,
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Delays sending the Retry or CONNECTION_CLOSE packet to the peer
+    ///
+    /// Retransmitted Initial packets received during the delay are dropped rather than
+    /// producing additional responses. If the endpoint is already holding
+    /// [`Limiter::max_delayed_responses`] responses, the response is dropped instead. This has no
+    /// effect on other outcomes.
+    #[must_use]
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        match &mut self {
+            Self::Retry { delay: value } | Self::Close { delay: value, .. } => *value = delay,
+            _ => {}
+        }
+        self
+    }
+
+    /// Sets the transport error code sent to the peer when closing the connection
+    ///
+    /// Application error codes can't be used, since the connection is refused before the
+    /// application protocol has been negotiated. This has no effect on other outcomes.
+    #[must_use]
+    pub fn with_error_code(mut self, code: VarInt) -> Self {
+        if let Self::Close { error, .. } = &mut self {
+            *error = transport::Error::new(code).with_reason(error.reason);
+        }
+        self
+    }
+
+    /// Sets the reason for refusing the connection attempt
+    ///
+    /// When closing the connection, the reason phrase is sent to the peer in the
+    /// CONNECTION_CLOSE frame and long reasons are truncated to fit in a single packet. When
+    /// dropping the attempt, the reason is never sent to the peer but is included in the
+    /// endpoint's datagram dropped event, which allows operators to tell apart attempts dropped
+    /// for different reasons. This has no effect on other outcomes.
+    ///
+    /// ```rust
+    /// # use s2n_quic_core::endpoint::limits::Outcome;
+    /// let outcome = Outcome::drop().with_reason("denylisted prefix");
+    /// assert_eq!(outcome.reason(), Some("denylisted prefix"));
+    /// ```
+    #[must_use]
+    pub fn with_reason(mut self, reason: &'static str) -> Self {
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-19.19
+        //# This SHOULD be a UTF-8 encoded
+        //# string [RFC3629], though the frame does not carry information, such
+        //# as language tags, that would aid comprehension by any entity other
+        //# than the one that created the text.
+        // The reason is a `str`, so it is always valid UTF-8
+        match &mut self {
+            Self::Close { error, .. } => *error = error.with_reason(reason),
+            Self::Drop { reason: value } => *value = Some(reason),
+            _ => {}
+        }
+        self
+    }
+
+    /// Returns `true` if the connection is allowed to continue
+    #[inline]
+    pub fn is_allow(&self) -> bool {
+        matches!(self, Self::Allow { .. })
+    }
+
+    /// Returns `true` if the connection is deferred with a Retry packet
+    #[inline]
+    pub fn is_retry(&self) -> bool {
+        matches!(self, Self::Retry { .. })
+    }
+
+    /// Returns `true` if the connection attempt is silently dropped
+    #[inline]
+    pub fn is_drop(&self) -> bool {
+        matches!(self, Self::Drop { .. })
+    }
+
+    /// Returns `true` if the connection is closed with a CONNECTION_CLOSE frame
+    #[inline]
+    pub fn is_close(&self) -> bool {
+        matches!(self, Self::Close { .. })
+    }
+
+    /// Returns the reason set with [`Self::with_reason`], if any
+    #[inline]
+    pub fn reason(&self) -> Option<&'static str> {
+        match self {
+            Self::Drop { reason } => *reason,
+            Self::Close { error, .. } if !error.reason.is_empty() => Some(error.reason),
+            _ => None,
+        }
+    }
+
+    /// Returns how long the response to the peer is delayed
+    #[inline]
+    pub fn delay(&self) -> Duration {
+        match self {
+            Self::Retry { delay } | Self::Close { delay, .. } => *delay,
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+/// Identifies a connection attempt passed to a [`Limiter`]
+///
+/// The endpoint assigns each attempt a new id, which stays the same while the attempt is polled
+/// with [`Limiter::poll_on_connection_attempt`]. Limiters deciding asynchronously can use it to
+/// match decisions from an external service to the attempts waiting for them, and to release
+/// any state held for attempts reported to [`Limiter::on_connection_attempt_cancelled`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AttemptId(u64);
+
+impl AttemptId {
+    /// Creates an attempt id from its numeric value
+    #[inline]
+    pub const fn new(id: u64) -> Self {
+        Self(id)
+    }
+
+    /// Returns the numeric value of the attempt id
+    #[inline]
+    pub const fn as_u64(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the current id and advances to the next one
+    #[doc(hidden)]
+    #[inline]
+    pub fn next(&mut self) -> Self {
+        let id = *self;
+        self.0 = self.0.wrapping_add(1);
+        id
     }
 }
 
@@ -65,15 +251,115 @@ impl Outcome {
 #[non_exhaustive]
 #[derive(Debug)]
 pub struct ConnectionAttempt<'a> {
+    /// Identifies the attempt while its outcome is being decided
+    ///
+    /// The id is the same each time a pending attempt is passed to
+    /// [`Limiter::poll_on_connection_attempt`]. Retransmitted Initial packets which arrive once
+    /// the attempt has been decided, and attempts resent in response to a Retry packet, are
+    /// assigned a new id.
+    pub attempt_id: AttemptId,
+
     /// Number of handshakes the have begun but not completed
     pub inflight_handshakes: usize,
 
-    /// Number of open connections
+    /// Number of connections currently held by the endpoint, including inflight handshakes
+    ///
+    /// A connection continues to be counted while it is closing or draining and stops being
+    /// counted once it has been finalized and removed from the endpoint. Use
+    /// [`Self::established_connections`] to exclude inflight handshakes.
     pub connection_count: usize,
 
     /// The unverified address of the connecting peer
     /// This address comes from the datagram
     pub remote_address: SocketAddress<'a>,
+
+    /// The local address the datagram was received on
+    ///
+    /// If the endpoint is bound to an unspecified address, such as `0.0.0.0`, this is the
+    /// concrete destination address of the datagram, as long as the IO provider supports
+    /// reporting it (e.g. via `IP_PKTINFO`).
+    pub local_address: SocketAddress<'a>,
+
+    /// The destination connection ID of the client's first Initial packet
+    ///
+    /// This is chosen by the client and isn't authenticated, but stays the same for any
+    /// retransmissions of the Initial packet. It can be used to detect replayed Initial packets,
+    /// correlate attempts with external flow logs, or consistently hash decisions.
+    ///
+    /// ```rust
+    /// # use s2n_quic_core::endpoint::limits::{ConnectionAttempt, Limiter, Outcome};
+    /// /// Sends a Retry packet to a consistent share of the attempts
+    /// struct RetryShare {
+    ///     percent: u32,
+    /// }
+    ///
+    /// impl Limiter for RetryShare {
+    ///     fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+    ///         // retransmitted Initial packets hash to the same value
+    ///         let hash = info
+    ///             .original_destination_connection_id
+    ///             .iter()
+    ///             .fold(0u32, |hash, byte| hash.wrapping_mul(31).wrapping_add(*byte as u32));
+    ///
+    ///         if hash % 100 < self.percent {
+    ///             Outcome::retry()
+    ///         } else {
+    ///             Outcome::allow()
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub original_destination_connection_id: &'a [u8],
+
+    /// Whether the Initial packet carried an address validation token, and whether the token was
+    /// valid
+    ///
+    /// Clients returning with a valid token have proven they can receive packets at their
+    /// address, so they should usually be allowed even while the endpoint is under load. The
+    /// endpoint never sends a Retry packet in response to a valid token and allows the attempt
+    /// instead, since this would send the client into a Retry loop.
+    pub token_status: TokenStatus,
+
+    /// The UDP payload length of the datagram which carried the attempt
+    ///
+    /// This is the length of the entire datagram, including any packets coalesced with the
+    /// Initial packet and any padding. Clients are required to expand datagrams carrying Initial
+    /// packets to at least 1200 bytes, so the endpoint discards smaller attempts whatever the
+    /// limiter decides.
+    pub datagram_len: usize,
+
+    /// The version field from the long header of the Initial packet
+    ///
+    /// Servers also pass Initial packets with versions they don't support to the limiter,
+    /// before deciding whether to respond with a Version Negotiation packet. For these attempts,
+    /// returning [`Outcome::drop`] suppresses the Version Negotiation packet, while any other
+    /// outcome lets the endpoint respond as usual, since the client can't be sent a Retry or
+    /// CONNECTION_CLOSE in a version the endpoint doesn't understand. These attempts are decided
+    /// with [`Limiter::on_connection_attempt`], as the endpoint holds no state for them.
+    pub quic_version: u32,
+
+    /// The number of connection attempts per second the endpoint received recently, including
+    /// this one
+    ///
+    /// The rate is measured over the sliding window returned by
+    /// [`Limiter::attempt_rate_window`], and includes every attempt passed to the limiter,
+    /// whatever its outcome. It can be used to only defer attempts with Retry packets while the
+    /// endpoint is under load, rather than duplicating the measurement in each limiter.
+    pub recent_attempt_rate: f32,
+
+    /// Whether the client sent 0-RTT data along with the attempt
+    ///
+    /// This is `true` if the client coalesced a 0-RTT packet with its Initial packet. Clients may
+    /// also send 0-RTT packets in separate datagrams, so this can be `false` even though early
+    /// data was offered. The endpoint doesn't accept 0-RTT data yet, so early data is always
+    /// rejected and the client sends it again once the handshake completes.
+    pub early_data_offered: bool,
+
+    /// The time the datagram containing the attempt was received, according to the endpoint's
+    /// clock
+    ///
+    /// This should be preferred over `Instant::now()` so limiters behave deterministically
+    /// when the endpoint is driven by a simulated or mock clock.
     pub timestamp: Timestamp,
 }
 
@@ -83,17 +369,466 @@ impl<'a> ConnectionAttempt<'a> {
         inflight_handshakes: usize,
         connection_count: usize,
         remote_address: &'a inet::SocketAddress,
+        local_address: &'a inet::SocketAddress,
+        original_destination_connection_id: &'a [u8],
         timestamp: Timestamp,
     ) -> Self {
         Self {
+            attempt_id: AttemptId::default(),
             inflight_handshakes,
             connection_count,
+            remote_address: remote_address.into_event(),
+            local_address: local_address.into_event(),
+            original_destination_connection_id,
+            token_status: TokenStatus::None,
+            datagram_len: MINIMUM_MTU as usize,
+            quic_version: QUIC_VERSION_1,
+            recent_attempt_rate: 0.0,
+            early_data_offered: false,
+            timestamp,
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn with_attempt_id(mut self, attempt_id: AttemptId) -> Self {
+        self.attempt_id = attempt_id;
+        self
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn with_token_status(mut self, token_status: TokenStatus) -> Self {
+        self.token_status = token_status;
+        self
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn with_datagram_len(mut self, datagram_len: usize) -> Self {
+        self.datagram_len = datagram_len;
+        self
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn with_quic_version(mut self, quic_version: u32) -> Self {
+        self.quic_version = quic_version;
+        self
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn with_recent_attempt_rate(mut self, recent_attempt_rate: f32) -> Self {
+        self.recent_attempt_rate = recent_attempt_rate;
+        self
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn with_early_data_offered(mut self, early_data_offered: bool) -> Self {
+        self.early_data_offered = early_data_offered;
+        self
+    }
+
+    /// Returns a builder for connection attempts, which can be used to test [`Limiter`]
+    /// implementations without an endpoint
+    ///
+    /// Any fields which aren't set use a default value, so tests keep compiling as fields are
+    /// added to [`ConnectionAttempt`].
+    ///
+    /// ```rust
+    /// # use s2n_quic_core::{endpoint::limits::{ConnectionAttempt, TokenStatus}, inet::SocketAddressV4};
+    ///
+    /// let builder = ConnectionAttempt::builder()
+    ///     .with_inflight_handshakes(10)
+    ///     .with_remote_address(SocketAddressV4::new([192, 0, 2, 1], 443))
+    ///     .with_token_status(TokenStatus::RetryToken { valid: true });
+    /// let info = builder.build();
+    ///
+    /// assert_eq!(info.inflight_handshakes, 10);
+    /// assert_eq!(info.remote_address.port(), 443);
+    /// ```
+    #[inline]
+    pub fn builder() -> ConnectionAttemptBuilder {
+        ConnectionAttemptBuilder::default()
+    }
+
+    /// Returns the number of connections that have completed their handshake and been handed
+    /// to the application, but have not yet been finalized
+    #[inline]
+    pub fn established_connections(&self) -> usize {
+        self.connection_count
+            .saturating_sub(self.inflight_handshakes)
+    }
+}
+
+/// Builds [`ConnectionAttempt`]s for testing [`Limiter`] implementations
+///
+/// See [`ConnectionAttempt::builder`].
+#[derive(Clone, Debug)]
+pub struct ConnectionAttemptBuilder {
+    attempt_id: AttemptId,
+    inflight_handshakes: usize,
+    connection_count: usize,
+    remote_address: inet::SocketAddress,
+    local_address: inet::SocketAddress,
+    original_destination_connection_id: connection::UnboundedId,
+    token_status: TokenStatus,
+    datagram_len: usize,
+    quic_version: u32,
+    recent_attempt_rate: f32,
+    early_data_offered: bool,
+    timestamp: crate::time::Timestamp,
+}
+
+impl Default for ConnectionAttemptBuilder {
+    fn default() -> Self {
+        Self {
+            attempt_id: AttemptId::default(),
+            inflight_handshakes: 0,
+            connection_count: 0,
+            remote_address: Default::default(),
+            local_address: Default::default(),
+            original_destination_connection_id: connection::UnboundedId::try_from_bytes(&[0; 8])
+                .expect("8 bytes is a valid connection ID length"),
+            token_status: TokenStatus::None,
+            datagram_len: MINIMUM_MTU as usize,
+            quic_version: QUIC_VERSION_1,
+            recent_attempt_rate: 0.0,
+            early_data_offered: false,
+            // Safety: the builder has no clock of its own, so attempts are received at the start
+            // of the clock unless a timestamp from a clock is provided
+            timestamp: unsafe { crate::time::Timestamp::from_duration(Duration::ZERO) },
+        }
+    }
+}
+
+impl ConnectionAttemptBuilder {
+    /// Sets the id of the attempt, defaulting to `0`
+    #[inline]
+    pub fn with_attempt_id(mut self, attempt_id: AttemptId) -> Self {
+        self.attempt_id = attempt_id;
+        self
+    }
+
+    /// Sets the number of handshakes in progress, defaulting to `0`
+    #[inline]
+    pub fn with_inflight_handshakes(mut self, inflight_handshakes: usize) -> Self {
+        self.inflight_handshakes = inflight_handshakes;
+        self
+    }
+
+    /// Sets the number of connections held by the endpoint, defaulting to `0`
+    ///
+    /// The count is raised to at least the number of inflight handshakes when building the
+    /// attempt, since the endpoint counts inflight handshakes as connections.
+    #[inline]
+    pub fn with_connection_count(mut self, connection_count: usize) -> Self {
+        self.connection_count = connection_count;
+        self
+    }
+
+    /// Sets the address of the peer, defaulting to `0.0.0.0:0`
+    #[inline]
+    pub fn with_remote_address<A: Into<inet::SocketAddress>>(mut self, address: A) -> Self {
+        self.remote_address = address.into();
+        self
+    }
+
+    /// Sets the local address the attempt was received on, defaulting to `0.0.0.0:0`
+    #[inline]
+    pub fn with_local_address<A: Into<inet::SocketAddress>>(mut self, address: A) -> Self {
+        self.local_address = address.into();
+        self
+    }
+
+    /// Sets the destination connection ID of the client's first Initial packet, defaulting to
+    /// 8 zero bytes
+    ///
+    /// # Panics
+    ///
+    /// Panics if the connection ID is longer than 20 bytes.
+    #[inline]
+    pub fn with_original_destination_connection_id(mut self, id: &[u8]) -> Self {
+        self.original_destination_connection_id = connection::UnboundedId::try_from_bytes(id)
+            .expect("connection IDs can't be longer than 20 bytes");
+        self
+    }
+
+    /// Sets the status of the token presented by the attempt, defaulting to
+    /// [`TokenStatus::None`]
+    #[inline]
+    pub fn with_token_status(mut self, token_status: TokenStatus) -> Self {
+        self.token_status = token_status;
+        self
+    }
+
+    /// Sets the UDP payload length of the datagram which carried the attempt, defaulting to
+    /// `1200`
+    #[inline]
+    pub fn with_datagram_len(mut self, datagram_len: usize) -> Self {
+        self.datagram_len = datagram_len;
+        self
+    }
+
+    /// Sets the version of the Initial packet, defaulting to QUIC version 1
+    #[inline]
+    pub fn with_quic_version(mut self, quic_version: u32) -> Self {
+        self.quic_version = quic_version;
+        self
+    }
+
+    /// Sets the number of attempts per second the endpoint received recently, defaulting to
+    /// `0.0`
+    #[inline]
+    pub fn with_recent_attempt_rate(mut self, recent_attempt_rate: f32) -> Self {
+        self.recent_attempt_rate = recent_attempt_rate;
+        self
+    }
+
+    /// Sets whether the client sent 0-RTT data along with the attempt, defaulting to `false`
+    #[inline]
+    pub fn with_early_data_offered(mut self, early_data_offered: bool) -> Self {
+        self.early_data_offered = early_data_offered;
+        self
+    }
+
+    /// Sets the time the attempt was received, defaulting to the start of the clock
+    ///
+    /// The timestamp should come from a [`Clock`](crate::time::Clock), such as
+    /// `s2n_quic_core::time::testing::now()` with the `testing` feature.
+    #[inline]
+    pub fn with_timestamp(mut self, timestamp: crate::time::Timestamp) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Builds the connection attempt, which borrows the addresses from the builder
+    #[inline]
+    pub fn build(&self) -> ConnectionAttempt {
+        ConnectionAttempt::new(
+            self.inflight_handshakes,
+            self.connection_count.max(self.inflight_handshakes),
+            &self.remote_address,
+            &self.local_address,
+            self.original_destination_connection_id.as_bytes(),
+            self.timestamp.into_event(),
+        )
+        .with_attempt_id(self.attempt_id)
+        .with_token_status(self.token_status)
+        .with_datagram_len(self.datagram_len)
+        .with_quic_version(self.quic_version)
+        .with_recent_attempt_rate(self.recent_attempt_rate)
+        .with_early_data_offered(self.early_data_offered)
+    }
+}
+
+/// The address validation token presented by a connection attempt
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TokenStatus {
+    /// The Initial packet didn't carry a token
+    #[default]
+    None,
+
+    /// The Initial packet carried a token which was provided in a Retry packet
+    RetryToken { valid: bool },
+
+    /// The Initial packet carried a token which was provided in a NEW_TOKEN frame
+    NewToken { valid: bool },
+}
+
+impl TokenStatus {
+    /// Returns `true` if the attempt presented a valid token of either kind
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        matches!(
+            self,
+            Self::RetryToken { valid: true } | Self::NewToken { valid: true }
+        )
+    }
+
+    /// Returns `true` if the attempt presented a token which failed validation
+    #[inline]
+    pub fn is_invalid(&self) -> bool {
+        matches!(
+            self,
+            Self::RetryToken { valid: false } | Self::NewToken { valid: false }
+        )
+    }
+}
+
+/// Information about a connection which the endpoint created for an allowed attempt
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct ConnectionOpenInfo<'a> {
+    /// The address of the peer
+    ///
+    /// The handshake hasn't completed yet, so the address hasn't been validated unless the
+    /// client presented a valid Retry token.
+    pub remote_address: SocketAddress<'a>,
+
+    /// The time the endpoint created the connection
+    pub timestamp: Timestamp,
+}
+
+impl<'a> ConnectionOpenInfo<'a> {
+    #[doc(hidden)]
+    pub fn new(remote_address: &'a inet::SocketAddress, timestamp: Timestamp) -> Self {
+        Self {
             remote_address: remote_address.into_event(),
             timestamp,
         }
     }
 }
 
+/// Information about a connection which completed its handshake and was handed to the
+/// application
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct HandshakeInfo<'a> {
+    /// The address of the peer
+    ///
+    /// The address has been validated by the handshake, so it can be trusted more than the
+    /// address of a [`ConnectionAttempt`].
+    pub remote_address: SocketAddress<'a>,
+
+    /// The time the endpoint reported the completed handshake to the limiter
+    pub timestamp: Timestamp,
+}
+
+impl<'a> HandshakeInfo<'a> {
+    #[doc(hidden)]
+    pub fn new(remote_address: &'a inet::SocketAddress, timestamp: Timestamp) -> Self {
+        Self {
+            remote_address: remote_address.into_event(),
+            timestamp,
+        }
+    }
+}
+
+/// Information about a connection which was finalized and removed from the endpoint
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct ConnectionCloseInfo<'a> {
+    /// The address of the peer
+    pub remote_address: SocketAddress<'a>,
+
+    /// Whether the connection completed its handshake
+    ///
+    /// This is `true` if and only if [`Limiter::on_handshake_completed`] was called for the
+    /// connection.
+    pub handshake_completed: bool,
+
+    /// The reason the connection was closed, if any
+    pub error: Option<connection::Error>,
+
+    /// The time the endpoint reported the closed connection to the limiter
+    pub timestamp: Timestamp,
+}
+
+impl<'a> ConnectionCloseInfo<'a> {
+    #[doc(hidden)]
+    pub fn new(
+        remote_address: &'a inet::SocketAddress,
+        handshake_completed: bool,
+        error: Option<connection::Error>,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            remote_address: remote_address.into_event(),
+            handshake_completed,
+            error,
+            timestamp,
+        }
+    }
+}
+
+/// Information about a connection once the server name requested by the client is known
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct ServerNameAttempt<'a> {
+    /// The server name (SNI) the client requested in its ClientHello
+    pub server_name: &'a str,
+
+    /// The address of the peer
+    ///
+    /// The handshake hasn't completed yet, so the address hasn't been validated unless the
+    /// client presented a valid Retry token.
+    pub remote_address: SocketAddress<'a>,
+
+    /// The local address the connection was accepted on
+    pub local_address: SocketAddress<'a>,
+
+    /// Number of handshakes that have begun but not completed, including this connection
+    pub inflight_handshakes: usize,
+
+    /// Number of connections currently held by the endpoint, including inflight handshakes
+    pub connection_count: usize,
+
+    /// The time the endpoint reported the server name to the limiter
+    pub timestamp: Timestamp,
+}
+
+impl<'a> ServerNameAttempt<'a> {
+    #[doc(hidden)]
+    pub fn new(
+        server_name: &'a str,
+        remote_address: &'a inet::SocketAddress,
+        local_address: &'a inet::SocketAddress,
+        inflight_handshakes: usize,
+        connection_count: usize,
+        timestamp: Timestamp,
+    ) -> Self {
+        Self {
+            server_name,
+            remote_address: remote_address.into_event(),
+            local_address: local_address.into_event(),
+            inflight_handshakes,
+            connection_count,
+            timestamp,
+        }
+    }
+}
+
+/// Decides how the endpoint proceeds with each connection attempt
+///
+/// # Testing
+///
+/// Limiters can be unit tested without an endpoint by building attempts with
+/// [`ConnectionAttempt::builder`]:
+///
+/// ```rust
+/// # use s2n_quic_core::endpoint::limits::{ConnectionAttempt, Limiter, Outcome, TokenStatus};
+/// struct HandshakeLimit(usize);
+///
+/// impl Limiter for HandshakeLimit {
+///     fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+///         if info.inflight_handshakes >= self.0 && !info.token_status.is_valid() {
+///             Outcome::retry()
+///         } else {
+///             Outcome::allow()
+///         }
+///     }
+/// }
+///
+/// let cases = [
+///     // (inflight handshakes, token status, expected outcome)
+///     (0, TokenStatus::None, Outcome::allow()),
+///     (10, TokenStatus::None, Outcome::retry()),
+///     (10, TokenStatus::RetryToken { valid: true }, Outcome::allow()),
+///     (10, TokenStatus::RetryToken { valid: false }, Outcome::retry()),
+/// ];
+///
+/// let mut limiter = HandshakeLimit(10);
+/// for (inflight_handshakes, token_status, expected) in cases {
+///     let attempt = ConnectionAttempt::builder()
+///         .with_inflight_handshakes(inflight_handshakes)
+///         .with_token_status(token_status);
+///     assert_eq!(limiter.on_connection_attempt(&attempt.build()), expected);
+/// }
+/// ```
 pub trait Limiter: 'static + Send {
     /// This trait is used to determine the outcome of connection attempts on an endpoint. The
     /// implementor returns an Outcome based on the ConnectionAttempt, or other information that the
@@ -103,14 +838,40 @@ pub trait Limiter: 'static + Send {
     /// # mod s2n_quic { pub mod provider { pub mod endpoint_limits { pub use s2n_quic_core::endpoint::limits::*; } } }
     /// use s2n_quic::provider::endpoint_limits::{Limiter, ConnectionAttempt, Outcome};
     ///
+    /// use core::time::Duration;
+    ///
     /// struct MyEndpointLimits {
     ///    handshake_limit: usize,
-    ///    delay: core::time::Duration,
+    ///    public_port: u16,
+    ///    public_handshake_limit: usize,
+    ///    max_attempts_per_second: f64,
+    ///    attempts_per_second: f64,
+    ///    last_attempt: Option<Duration>,
     /// }
     ///
     /// impl Limiter for MyEndpointLimits {
     ///    fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
-    ///        if info.inflight_handshakes > self.handshake_limit {
+    ///        // estimate the attempt rate from the interval since the previous attempt
+    ///        let now = info.timestamp.duration_since_start();
+    ///        if let Some(last_attempt) = self.last_attempt.replace(now) {
+    ///            let interval = now.saturating_sub(last_attempt).as_secs_f64();
+    ///            if interval > 0.0 {
+    ///                self.attempts_per_second = 0.9 * self.attempts_per_second + 0.1 / interval;
+    ///            }
+    ///        }
+    ///
+    ///        if self.attempts_per_second > self.max_attempts_per_second {
+    ///            return Outcome::retry();
+    ///        }
+    ///
+    ///        // apply stricter limits to connections arriving on the public interface
+    ///        let handshake_limit = if info.local_address.port() == self.public_port {
+    ///            self.public_handshake_limit
+    ///        } else {
+    ///            self.handshake_limit
+    ///        };
+    ///
+    ///        if info.inflight_handshakes > handshake_limit {
     ///            Outcome::retry()
     ///        } else {
     ///            Outcome::allow()
@@ -119,4 +880,823 @@ pub trait Limiter: 'static + Send {
     /// }
     /// ```
     fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome;
+
+    /// Polls for the outcome of a connection attempt
+    ///
+    /// This allows limiters to consult an external service, e.g. a shared reputation database,
+    /// before deciding on the outcome. While the decision isn't available, the limiter returns
+    /// `Poll::Pending` and wakes the endpoint with `cx` once it is. The endpoint holds the
+    /// attempt and polls it again with the same `info`, including the same
+    /// [`ConnectionAttempt::attempt_id`], until it is ready, dropping any retransmitted Initial
+    /// packets for the attempt in the meantime. Attempts which wait longer than
+    /// [`Self::pending_attempt_timeout`] are dropped, reported to
+    /// [`Self::on_connection_attempt_cancelled`] and aren't polled again.
+    ///
+    /// The default implementation returns the outcome of [`Self::on_connection_attempt`], which
+    /// is only called by the endpoint through this method.
+    ///
+    /// ```rust
+    /// # use s2n_quic_core::endpoint::limits::{AttemptId, ConnectionAttempt, Limiter, Outcome};
+    /// use core::task::{Context, Poll, Waker};
+    /// use std::{
+    ///     collections::{HashMap, HashSet},
+    ///     sync::{mpsc, Arc, Mutex},
+    /// };
+    ///
+    /// /// Decisions shared between the limiter and the service making them
+    /// #[derive(Default)]
+    /// struct Decisions {
+    ///     /// Attempts the endpoint is waiting on
+    ///     waiting: HashSet<AttemptId>,
+    ///     outcomes: HashMap<AttemptId, Outcome>,
+    ///     waker: Option<Waker>,
+    /// }
+    ///
+    /// impl Decisions {
+    ///     /// Called by the service once it has decided on an attempt
+    ///     fn decide(&mut self, attempt_id: AttemptId, outcome: Outcome) {
+    ///         // the endpoint may have stopped waiting for the attempt in the meantime
+    ///         if self.waiting.contains(&attempt_id) {
+    ///             self.outcomes.insert(attempt_id, outcome);
+    ///             if let Some(waker) = self.waker.take() {
+    ///                 waker.wake();
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// struct ExternalLimiter {
+    ///     decisions: Arc<Mutex<Decisions>>,
+    ///     requests: mpsc::Sender<AttemptId>,
+    /// }
+    ///
+    /// impl Limiter for ExternalLimiter {
+    ///     fn on_connection_attempt(&mut self, _info: &ConnectionAttempt) -> Outcome {
+    ///         // the endpoint only calls `poll_on_connection_attempt`
+    ///         Outcome::drop()
+    ///     }
+    ///
+    ///     fn poll_on_connection_attempt(
+    ///         &mut self,
+    ///         cx: &mut Context,
+    ///         info: &ConnectionAttempt,
+    ///     ) -> Poll<Outcome> {
+    ///         let mut decisions = self.decisions.lock().unwrap();
+    ///
+    ///         if let Some(outcome) = decisions.outcomes.remove(&info.attempt_id) {
+    ///             decisions.waiting.remove(&info.attempt_id);
+    ///             return Poll::Ready(outcome);
+    ///         }
+    ///
+    ///         // ask the service about the attempt the first time it is polled
+    ///         if decisions.waiting.insert(info.attempt_id)
+    ///             && self.requests.send(info.attempt_id).is_err()
+    ///         {
+    ///             decisions.waiting.remove(&info.attempt_id);
+    ///             return Poll::Ready(Outcome::drop());
+    ///         }
+    ///
+    ///         // the service wakes the endpoint once it has decided
+    ///         decisions.waker = Some(cx.waker().clone());
+    ///         Poll::Pending
+    ///     }
+    ///
+    ///     fn on_connection_attempt_cancelled(&mut self, attempt_id: AttemptId) {
+    ///         let mut decisions = self.decisions.lock().unwrap();
+    ///         decisions.waiting.remove(&attempt_id);
+    ///         decisions.outcomes.remove(&attempt_id);
+    ///     }
+    /// }
+    /// ```
+    #[inline]
+    fn poll_on_connection_attempt(
+        &mut self,
+        cx: &mut Context,
+        info: &ConnectionAttempt,
+    ) -> Poll<Outcome> {
+        let _ = cx;
+        Poll::Ready(self.on_connection_attempt(info))
+    }
+
+    /// Called when the endpoint stops waiting for an attempt which
+    /// [`Self::poll_on_connection_attempt`] returned `Poll::Pending` for
+    ///
+    /// This happens when the attempt waits longer than [`Self::pending_attempt_timeout`], or
+    /// arrives while [`Self::max_pending_attempts`] are already held. The attempt is dropped
+    /// and isn't polled again, so any state held for it can be released.
+    ///
+    /// The default implementation does nothing.
+    #[inline]
+    fn on_connection_attempt_cancelled(&mut self, attempt_id: AttemptId) {
+        let _ = attempt_id;
+    }
+
+    /// The maximum number of connection attempts the endpoint holds while
+    /// [`Self::poll_on_connection_attempt`] is pending
+    ///
+    /// Attempts arriving while the limit is reached are dropped.
+    fn max_pending_attempts(&self) -> usize {
+        DEFAULT_MAX_PENDING_ATTEMPTS
+    }
+
+    /// How long a connection attempt waits for [`Self::poll_on_connection_attempt`] before it is
+    /// dropped
+    fn pending_attempt_timeout(&self) -> Duration {
+        DEFAULT_PENDING_ATTEMPT_TIMEOUT
+    }
+
+    /// The maximum number of delayed Retry or CONNECTION_CLOSE responses the endpoint holds at
+    /// once
+    ///
+    /// See [`Outcome::with_delay`]. The limit applies separately to each type of response. Each
+    /// held response uses up to 1200 bytes for the encoded packet, plus an index entry of
+    /// roughly 64 bytes used to detect retransmitted Initial packets, so the limit should be sized
+    /// with the memory it allows in mind. Responses beyond the limit are dropped and reported with
+    /// the `EndpointResponseDropped` event.
+    fn max_delayed_responses(&self) -> usize {
+        DEFAULT_MAX_DELAYED_RESPONSES
+    }
+
+    /// The longest time a connection may spend completing its handshake before the endpoint
+    /// abandons it
+    ///
+    /// Abandoned connections are closed with a
+    /// [`MaxHandshakeDurationExceeded`](connection::Error::MaxHandshakeDurationExceeded) error,
+    /// which is reported to [`Self::on_connection_closed`], and the peer is sent a
+    /// CONNECTION_CLOSE frame with the `CONNECTION_REFUSED` error code. If the connection limits
+    /// also set a maximum handshake duration, the shorter of the two is used.
+    ///
+    /// Returns `None` by default, leaving the duration to the connection limits.
+    fn max_handshake_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    /// The length of the sliding window the endpoint measures
+    /// [`ConnectionAttempt::recent_attempt_rate`] over
+    ///
+    /// Shorter windows react to bursts of attempts more quickly, while longer windows smooth
+    /// them out. Defaults to [`DEFAULT_ATTEMPT_RATE_WINDOW`].
+    fn attempt_rate_window(&self) -> Duration {
+        DEFAULT_ATTEMPT_RATE_WINDOW
+    }
+
+    /// Decides whether a connection continues once the server name requested by the client is
+    /// known
+    ///
+    /// Some abuse targets a hostname rather than coming from a single address, but the server
+    /// name (SNI) isn't available to [`Self::on_connection_attempt`] since it's carried in the
+    /// ClientHello. This is called once the ClientHello has been parsed, allowing limiters to
+    /// close connections to a hostname which is under attack or over its quota. Connections
+    /// from clients which don't send a server name aren't passed to this method.
+    ///
+    /// The connection has already been created at this point, so only [`Outcome::allow`] and
+    /// [`Outcome::close`] can be returned, and the delay of a close outcome is ignored.
+    /// Returning any other outcome is a bug which panics in debug builds, and otherwise allows
+    /// the connection to continue.
+    ///
+    /// The default implementation allows every connection.
+    ///
+    /// ```rust
+    /// # use s2n_quic_core::endpoint::limits::{ConnectionAttempt, Limiter, Outcome, ServerNameAttempt};
+    /// struct HostnameDenylist(Vec<&'static str>);
+    ///
+    /// impl Limiter for HostnameDenylist {
+    ///     fn on_connection_attempt(&mut self, _info: &ConnectionAttempt) -> Outcome {
+    ///         Outcome::allow()
+    ///     }
+    ///
+    ///     fn on_server_name(&mut self, info: &ServerNameAttempt) -> Outcome {
+    ///         if self.0.contains(&info.server_name) {
+    ///             Outcome::close().with_reason("hostname unavailable")
+    ///         } else {
+    ///             Outcome::allow()
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    #[inline]
+    fn on_server_name(&mut self, info: &ServerNameAttempt) -> Outcome {
+        let _ = info;
+        Outcome::allow()
+    }
+
+    /// Called when the endpoint creates a connection for an allowed attempt
+    ///
+    /// This is called before the next attempt is passed to [`Self::on_connection_attempt`], so
+    /// limiters counting connections see every connection they allowed. Each connection which is
+    /// opened is later passed to [`Self::on_connection_closed`], whether or not it completes its
+    /// handshake. Attempts which were allowed but failed before a connection was created, e.g.
+    /// because another limiter refused them, aren't reported.
+    #[inline]
+    fn on_connection_opened(&mut self, info: &ConnectionOpenInfo) {
+        let _ = info;
+    }
+
+    /// Called when a connection completes its handshake and is handed to the application
+    ///
+    /// Along with [`Self::on_connection_closed`], this allows limiters to track the connections
+    /// held by the endpoint, e.g. to cap the number of connections from each peer. Note that
+    /// connections which were sent a Retry packet are passed to [`Self::on_connection_attempt`]
+    /// again when the client responds with the token, but only report their lifecycle once.
+    #[inline]
+    fn on_handshake_completed(&mut self, info: &HandshakeInfo) {
+        let _ = info;
+    }
+
+    /// Called when a connection has been finalized and removed from the endpoint
+    ///
+    /// This is called for every connection, including connections which never completed their
+    /// handshake, which is indicated by [`ConnectionCloseInfo::handshake_completed`].
+    #[inline]
+    fn on_connection_closed(&mut self, info: &ConnectionCloseInfo) {
+        let _ = info;
+    }
+
+    /// Runs `other` after this limiter, returning the most restrictive outcome
+    ///
+    /// See the [`Limiter`] implementation for tuples for details on how the outcomes are
+    /// combined.
+    ///
+    /// ```rust
+    /// # use s2n_quic_core::endpoint::limits::{ConnectionAttempt, Limiter, Outcome};
+    /// struct AllowList;
+    ///
+    /// impl Limiter for AllowList {
+    ///     fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+    ///         if info.remote_address.port() == 443 {
+    ///             Outcome::allow()
+    ///         } else {
+    ///             Outcome::drop().with_reason("not allow-listed")
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// struct GlobalCap(usize);
+    ///
+    /// impl Limiter for GlobalCap {
+    ///     fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+    ///         if info.connection_count >= self.0 {
+    ///             Outcome::close()
+    ///         } else {
+    ///             Outcome::allow()
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let limiter = AllowList.and_then(GlobalCap(10_000));
+    /// ```
+    fn and_then<L: Limiter>(self, other: L) -> (Self, L)
+    where
+        Self: Sized,
+    {
+        (self, other)
+    }
+}
+
+impl Outcome {
+    /// Returns how restrictive the outcome is, with higher values being more restrictive
+    #[inline]
+    fn restrictiveness(&self) -> u8 {
+        match self {
+            Self::Allow { .. } => 0,
+            Self::Retry { .. } => 1,
+            Self::Close { .. } => 2,
+            Self::Drop { .. } => 3,
+        }
+    }
+}
+
+/// Limiter is implemented for closures, for limiters which only need to decide on each attempt
+///
+/// ```rust
+/// # use s2n_quic_core::endpoint::limits::{ConnectionAttempt, Limiter, Outcome};
+/// let mut limiter = |attempt: &ConnectionAttempt| {
+///     if attempt.inflight_handshakes > 100 {
+///         Outcome::retry()
+///     } else {
+///         Outcome::allow()
+///     }
+/// };
+///
+/// let attempt = ConnectionAttempt::builder().with_inflight_handshakes(101);
+/// assert_eq!(limiter.on_connection_attempt(&attempt.build()), Outcome::retry());
+/// ```
+///
+/// The argument type needs to be annotated so the closure accepts attempts with any lifetime.
+/// Closures use the default for every other method, so limiters which track connections or
+/// decide asynchronously need to implement the trait on a type instead.
+impl<F> Limiter for F
+where
+    F: 'static + Send + FnMut(&ConnectionAttempt) -> Outcome,
+{
+    #[inline]
+    fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+        (self)(info)
+    }
+}
+
+macro_rules! impl_box {
+    ($($ty:tt)*) => {
+        /// Limiter is implemented for boxed limiters, so the limiter can be chosen at runtime
+        ///
+        /// ```rust
+        /// # use s2n_quic_core::endpoint::limits::{ConnectionAttempt, Limiter, Outcome};
+        /// fn limiter(max_handshakes: Option<usize>) -> Box<dyn Limiter> {
+        ///     match max_handshakes {
+        ///         Some(limit) => Box::new(move |attempt: &ConnectionAttempt| {
+        ///             if attempt.inflight_handshakes >= limit {
+        ///                 Outcome::retry()
+        ///             } else {
+        ///                 Outcome::allow()
+        ///             }
+        ///         }),
+        ///         None => Box::new(|_: &ConnectionAttempt| Outcome::allow()),
+        ///     }
+        /// }
+        ///
+        /// let attempt = ConnectionAttempt::builder().with_inflight_handshakes(10);
+        /// assert_eq!(limiter(Some(10)).on_connection_attempt(&attempt.build()), Outcome::retry());
+        /// assert_eq!(limiter(None).on_connection_attempt(&attempt.build()), Outcome::allow());
+        /// ```
+        #[cfg(feature = "alloc")]
+        impl Limiter for alloc::boxed::Box<$($ty)*> {
+            #[inline]
+            fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+                (**self).on_connection_attempt(info)
+            }
+
+            #[inline]
+            fn poll_on_connection_attempt(
+                &mut self,
+                cx: &mut Context,
+                info: &ConnectionAttempt,
+            ) -> Poll<Outcome> {
+                (**self).poll_on_connection_attempt(cx, info)
+            }
+
+            #[inline]
+            fn on_connection_attempt_cancelled(&mut self, attempt_id: AttemptId) {
+                (**self).on_connection_attempt_cancelled(attempt_id)
+            }
+
+            #[inline]
+            fn max_pending_attempts(&self) -> usize {
+                (**self).max_pending_attempts()
+            }
+
+            #[inline]
+            fn pending_attempt_timeout(&self) -> Duration {
+                (**self).pending_attempt_timeout()
+            }
+
+            #[inline]
+            fn max_delayed_responses(&self) -> usize {
+                (**self).max_delayed_responses()
+            }
+
+            #[inline]
+            fn max_handshake_duration(&self) -> Option<Duration> {
+                (**self).max_handshake_duration()
+            }
+
+            #[inline]
+            fn attempt_rate_window(&self) -> Duration {
+                (**self).attempt_rate_window()
+            }
+
+            #[inline]
+            fn on_server_name(&mut self, info: &ServerNameAttempt) -> Outcome {
+                (**self).on_server_name(info)
+            }
+
+            #[inline]
+            fn on_connection_opened(&mut self, info: &ConnectionOpenInfo) {
+                (**self).on_connection_opened(info)
+            }
+
+            #[inline]
+            fn on_handshake_completed(&mut self, info: &HandshakeInfo) {
+                (**self).on_handshake_completed(info)
+            }
+
+            #[inline]
+            fn on_connection_closed(&mut self, info: &ConnectionCloseInfo) {
+                (**self).on_connection_closed(info)
+            }
+        }
+    };
+}
+
+impl_box!(dyn Limiter);
+impl_box!(dyn Limiter + Send);
+
+macro_rules! impl_tuple {
+    ($($ty:ident $idx:tt),*) => {
+        /// Limiter is implemented for tuples to make it easy to compose multiple limiters.
+        ///
+        /// The limiters are called in order and the most restrictive outcome wins, in the order
+        /// `Drop`, `Close`, `Retry` then `Allow`. If several limiters return equally restrictive
+        /// outcomes, the first is used. Once a limiter returns `Drop`, the remaining limiters
+        /// aren't called. The limit on delayed responses, the maximum handshake duration and the
+        /// attempt rate window are the smallest of the limiters. Outcomes from
+        /// [`Limiter::on_server_name`] are combined the same way, calling every limiter.
+        /// Every limiter is notified of connection lifecycle changes.
+        ///
+        /// The limiters are consulted with [`Limiter::on_connection_attempt`], so limiters which
+        /// return pending decisions from [`Limiter::poll_on_connection_attempt`] can't be
+        /// combined.
+        impl<$($ty: Limiter),*> Limiter for ($($ty,)*) {
+            #[inline]
+            fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+                let mut outcome = Outcome::allow();
+                $(
+                    if outcome.is_drop() {
+                        return outcome;
+                    }
+
+                    let next = self.$idx.on_connection_attempt(info);
+                    if next.restrictiveness() > outcome.restrictiveness() {
+                        outcome = next;
+                    }
+                )*
+                outcome
+            }
+
+            #[inline]
+            fn on_server_name(&mut self, info: &ServerNameAttempt) -> Outcome {
+                let mut outcome = Outcome::allow();
+                $(
+                    let next = self.$idx.on_server_name(info);
+                    if next.restrictiveness() > outcome.restrictiveness() {
+                        outcome = next;
+                    }
+                )*
+                outcome
+            }
+
+            #[inline]
+            fn on_connection_opened(&mut self, info: &ConnectionOpenInfo) {
+                $(
+                    self.$idx.on_connection_opened(info);
+                )*
+            }
+
+            #[inline]
+            fn on_handshake_completed(&mut self, info: &HandshakeInfo) {
+                $(
+                    self.$idx.on_handshake_completed(info);
+                )*
+            }
+
+            #[inline]
+            fn on_connection_closed(&mut self, info: &ConnectionCloseInfo) {
+                $(
+                    self.$idx.on_connection_closed(info);
+                )*
+            }
+
+            #[inline]
+            fn max_delayed_responses(&self) -> usize {
+                let mut limit = usize::MAX;
+                $(
+                    limit = limit.min(self.$idx.max_delayed_responses());
+                )*
+                limit
+            }
+
+            #[inline]
+            fn max_handshake_duration(&self) -> Option<Duration> {
+                let mut limit: Option<Duration> = None;
+                $(
+                    if let Some(duration) = self.$idx.max_handshake_duration() {
+                        limit = Some(limit.map_or(duration, |limit| limit.min(duration)));
+                    }
+                )*
+                limit
+            }
+
+            #[inline]
+            fn attempt_rate_window(&self) -> Duration {
+                let mut window = Duration::MAX;
+                $(
+                    window = window.min(self.$idx.attempt_rate_window());
+                )*
+                window
+            }
+        }
+    };
+}
+
+impl_tuple!(A 0, B 1);
+impl_tuple!(A 0, B 1, C 2);
+impl_tuple!(A 0, B 1, C 2, D 3);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        event::IntoEvent,
+        inet::SocketAddress,
+        time::{testing::Clock, Clock as _},
+    };
+
+    /// Returns a fixed outcome and counts the number of times it was called
+    struct Fixed {
+        outcome: Outcome,
+        calls: usize,
+    }
+
+    impl Fixed {
+        fn new(outcome: Outcome) -> Self {
+            Self { outcome, calls: 0 }
+        }
+    }
+
+    impl Limiter for Fixed {
+        fn on_connection_attempt(&mut self, _info: &ConnectionAttempt) -> Outcome {
+            self.calls += 1;
+            self.outcome.clone()
+        }
+    }
+
+    fn attempt<L: Limiter>(limiter: &mut L) -> Outcome {
+        let address = SocketAddress::default();
+        let clock = Clock::default();
+        let info =
+            ConnectionAttempt::new(0, 0, &address, &address, &[], clock.get_time().into_event());
+        limiter.on_connection_attempt(&info)
+    }
+
+    #[test]
+    fn tuple_precedence_test() {
+        // ordered from least to most restrictive
+        let outcomes = [
+            Outcome::allow(),
+            Outcome::retry(),
+            Outcome::close(),
+            Outcome::drop(),
+        ];
+
+        for (a_index, a) in outcomes.iter().enumerate() {
+            for (b_index, b) in outcomes.iter().enumerate() {
+                let expected = if a_index >= b_index { a } else { b };
+                let mut limiter = (Fixed::new(a.clone()), Fixed::new(b.clone()));
+                assert_eq!(&attempt(&mut limiter), expected, "{a:?} {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn tuple_drop_short_circuit_test() {
+        let mut limiter = Fixed::new(Outcome::allow())
+            .and_then(Fixed::new(Outcome::drop().with_reason("first")))
+            .and_then(Fixed::new(Outcome::drop().with_reason("second")));
+        assert_eq!(attempt(&mut limiter), Outcome::drop().with_reason("first"));
+        assert_eq!(limiter.0 .0.calls, 1);
+        assert_eq!(limiter.0 .1.calls, 1);
+        assert_eq!(limiter.1.calls, 0);
+
+        let mut limiter = (
+            Fixed::new(Outcome::drop()),
+            Fixed::new(Outcome::close()),
+            Fixed::new(Outcome::retry()),
+        );
+        assert_eq!(attempt(&mut limiter), Outcome::drop());
+        assert_eq!(limiter.1.calls, 0);
+        assert_eq!(limiter.2.calls, 0);
+    }
+
+    #[test]
+    fn tuple_first_equal_outcome_test() {
+        let mut limiter = (
+            Fixed::new(Outcome::close().with_reason("first")),
+            Fixed::new(Outcome::retry()),
+            Fixed::new(Outcome::close().with_reason("second")),
+        );
+        assert_eq!(attempt(&mut limiter), Outcome::close().with_reason("first"));
+        assert_eq!(limiter.2.calls, 1);
+    }
+
+    /// Allows every attempt with a fixed maximum handshake duration
+    struct HandshakeDuration(Option<Duration>);
+
+    impl Limiter for HandshakeDuration {
+        fn on_connection_attempt(&mut self, _info: &ConnectionAttempt) -> Outcome {
+            Outcome::allow()
+        }
+
+        fn max_handshake_duration(&self) -> Option<Duration> {
+            self.0
+        }
+    }
+
+    #[test]
+    fn tuple_max_handshake_duration_test() {
+        let secs = |secs| HandshakeDuration(Some(Duration::from_secs(secs)));
+
+        assert_eq!(
+            (HandshakeDuration(None), HandshakeDuration(None)).max_handshake_duration(),
+            None
+        );
+        assert_eq!(
+            (HandshakeDuration(None), secs(5)).max_handshake_duration(),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            (secs(10), HandshakeDuration(None), secs(3)).max_handshake_duration(),
+            Some(Duration::from_secs(3))
+        );
+    }
+
+    struct RateWindow(Duration);
+
+    impl Limiter for RateWindow {
+        fn on_connection_attempt(&mut self, _info: &ConnectionAttempt) -> Outcome {
+            Outcome::allow()
+        }
+
+        fn attempt_rate_window(&self) -> Duration {
+            self.0
+        }
+    }
+
+    #[test]
+    fn tuple_attempt_rate_window_test() {
+        let secs = |secs| RateWindow(Duration::from_secs(secs));
+
+        assert_eq!(
+            (secs(10), secs(5)).attempt_rate_window(),
+            Duration::from_secs(5)
+        );
+        assert_eq!(
+            (secs(10), Fixed::new(Outcome::allow())).attempt_rate_window(),
+            DEFAULT_ATTEMPT_RATE_WINDOW
+        );
+    }
+
+    /// Closes connections to a single server name
+    struct ServerNameDeny(&'static str);
+
+    impl Limiter for ServerNameDeny {
+        fn on_connection_attempt(&mut self, _info: &ConnectionAttempt) -> Outcome {
+            Outcome::allow()
+        }
+
+        fn on_server_name(&mut self, info: &ServerNameAttempt) -> Outcome {
+            if info.server_name == self.0 {
+                Outcome::close().with_reason(self.0)
+            } else {
+                Outcome::allow()
+            }
+        }
+    }
+
+    #[test]
+    fn tuple_server_name_test() {
+        let address = SocketAddress::default();
+        let clock = Clock::default();
+        let server_name = |limiter: &mut dyn Limiter, server_name| {
+            let info = ServerNameAttempt::new(
+                server_name,
+                &address,
+                &address,
+                1,
+                1,
+                clock.get_time().into_event(),
+            );
+            limiter.on_server_name(&info)
+        };
+
+        // limiters allow every server name by default
+        let mut limiter = Fixed::new(Outcome::close());
+        assert_eq!(server_name(&mut limiter, "example.com"), Outcome::allow());
+
+        let mut limiter = (
+            Fixed::new(Outcome::allow()),
+            ServerNameDeny("a.example.com"),
+            ServerNameDeny("b.example.com"),
+        );
+        assert_eq!(server_name(&mut limiter, "example.com"), Outcome::allow());
+        assert_eq!(
+            server_name(&mut limiter, "b.example.com"),
+            Outcome::close().with_reason("b.example.com")
+        );
+    }
+
+    #[test]
+    fn closure_test() {
+        let mut calls = 0;
+        let mut limiter = move |info: &ConnectionAttempt| {
+            calls += 1;
+            if calls > 1 || info.inflight_handshakes > 0 {
+                Outcome::close()
+            } else {
+                Outcome::allow()
+            }
+        };
+
+        assert_eq!(attempt(&mut limiter), Outcome::allow());
+        assert_eq!(attempt(&mut limiter), Outcome::close());
+
+        // closures can be composed with other limiters
+        let mut limiter =
+            Fixed::new(Outcome::retry()).and_then(|_: &ConnectionAttempt| Outcome::allow());
+        assert_eq!(attempt(&mut limiter), Outcome::retry());
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn boxed_test() {
+        use alloc::boxed::Box;
+
+        let mut limiter: Box<dyn Limiter> =
+            Box::new(HandshakeDuration(Some(Duration::from_secs(5))));
+        assert_eq!(attempt(&mut limiter), Outcome::allow());
+        assert_eq!(
+            limiter.max_handshake_duration(),
+            Some(Duration::from_secs(5))
+        );
+
+        // boxed limiters can be chosen at runtime and composed with other limiters
+        let limiters: [Box<dyn Limiter + Send>; 2] = [
+            Box::new(Fixed::new(Outcome::drop())),
+            Box::new(|_: &ConnectionAttempt| Outcome::close()),
+        ];
+        let [first, second] = limiters;
+        let mut limiter = second.and_then(first);
+        assert_eq!(attempt(&mut limiter), Outcome::drop());
+    }
+
+    #[test]
+    fn builder_test() {
+        let builder = ConnectionAttempt::builder();
+        let info = builder.build();
+        assert_eq!(info.attempt_id, AttemptId::new(0));
+        assert_eq!(info.inflight_handshakes, 0);
+        assert_eq!(info.connection_count, 0);
+        assert_eq!(info.original_destination_connection_id, &[0; 8]);
+        assert_eq!(info.token_status, TokenStatus::None);
+        assert_eq!(info.datagram_len, 1200);
+        assert_eq!(info.quic_version, 1);
+        assert_eq!(info.recent_attempt_rate, 0.0);
+        assert!(!info.early_data_offered);
+
+        let remote_address = SocketAddress::default();
+        let timestamp = crate::time::testing::now() + Duration::from_secs(5);
+        let builder = ConnectionAttempt::builder()
+            .with_attempt_id(AttemptId::new(7))
+            .with_inflight_handshakes(3)
+            .with_connection_count(1)
+            .with_remote_address(remote_address)
+            .with_original_destination_connection_id(&[1, 2, 3])
+            .with_token_status(TokenStatus::NewToken { valid: false })
+            .with_datagram_len(1000)
+            .with_quic_version(0xff00_001d)
+            .with_recent_attempt_rate(250.0)
+            .with_early_data_offered(true)
+            .with_timestamp(timestamp);
+        let info = builder.build();
+        assert_eq!(info.attempt_id.as_u64(), 7);
+        assert_eq!(info.inflight_handshakes, 3);
+        // inflight handshakes are counted as connections
+        assert_eq!(info.connection_count, 3);
+        assert_eq!(info.original_destination_connection_id, &[1, 2, 3]);
+        assert!(info.token_status.is_invalid());
+        assert_eq!(info.datagram_len, 1000);
+        assert_eq!(info.quic_version, 0xff00_001d);
+        assert_eq!(info.recent_attempt_rate, 250.0);
+        assert!(info.early_data_offered);
+        assert_eq!(
+            info.timestamp.duration_since_start(),
+            timestamp.into_event().duration_since_start()
+        );
+    }
+
+    #[test]
+    fn outcome_accessors_test() {
+        assert!(Outcome::allow().is_allow());
+        assert!(Outcome::retry().is_retry());
+        assert!(Outcome::close().is_close());
+        assert!(Outcome::drop().is_drop());
+
+        assert_eq!(Outcome::drop().reason(), None);
+        assert_eq!(
+            Outcome::drop().with_reason("over handshake limit").reason(),
+            Some("over handshake limit")
+        );
+        assert_eq!(Outcome::close().reason(), None);
+        assert_eq!(
+            Outcome::close().with_reason("server busy").reason(),
+            Some("server busy")
+        );
+        // reasons are ignored by outcomes that don't use them
+        assert_eq!(Outcome::allow().with_reason("ignored"), Outcome::allow());
+
+        let delay = Duration::from_secs(1);
+        assert_eq!(Outcome::retry().with_delay(delay).delay(), delay);
+        assert_eq!(Outcome::close().with_delay(delay).delay(), delay);
+        assert_eq!(Outcome::drop().with_delay(delay).delay(), Duration::ZERO);
+    }
 }
@@ -0,0 +1,540 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A [`Limiter`] which decides on connection attempts based on the prefix of the peer's address
+//!
+//! ```rust
+//! # use s2n_quic_core::{
+//! #     endpoint::limits::{prefix::PrefixPolicy, ConnectionAttempt, Limiter, Outcome},
+//! #     inet::{IpNet, SocketAddress},
+//! # };
+//! let mut policy = PrefixPolicy::builder()
+//!     // refuse attempts from the documentation range with CONNECTION_CLOSE
+//!     .with_deny("192.0.2.0/24".parse::<IpNet>().unwrap())
+//!     .with_deny_outcome(Outcome::close())
+//!     // except for a single host
+//!     .with_allow("192.0.2.1/32".parse::<IpNet>().unwrap())
+//!     .build();
+//!
+//! let address: SocketAddress = "192.0.2.2:443".parse().unwrap();
+//! let attempt = ConnectionAttempt::builder().with_remote_address(address);
+//! assert!(policy.on_connection_attempt(&attempt.build()).is_close());
+//! ```
+
+use super::{ConnectionAttempt, Limiter, Outcome};
+use crate::{
+    event::api::SocketAddress,
+    inet::{IpAddress, IpNet},
+};
+use alloc::vec::Vec;
+
+/// The length of an IPv4-mapped IPv6 prefix, `::ffff:0:0/96`
+const IPV4_MAPPED_PREFIX_LEN: u8 = 96;
+
+/// The error returned when a prefix length is longer than its address
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PrefixError(&'static str);
+
+impl core::fmt::Display for PrefixError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PrefixError {}
+
+/// An address prefix in CIDR notation, such as `10.0.0.0/8` or `2001:db8::/32`
+///
+/// Bits of the address after the prefix length are ignored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Prefix {
+    address: IpAddress,
+    len: u8,
+}
+
+impl Prefix {
+    /// Creates a prefix matching the first `len` bits of `address`
+    ///
+    /// IPv4-mapped IPv6 prefixes, such as `::ffff:10.0.0.0/104`, are converted to the
+    /// equivalent IPv4 prefix. Returns an error if `len` is longer than the address.
+    pub fn new<A: Into<IpAddress>>(address: A, len: u8) -> Result<Self, PrefixError> {
+        match address.into() {
+            IpAddress::Ipv4(address) => {
+                if len > 32 {
+                    return Err(PrefixError("IPv4 prefixes can't be longer than 32 bits"));
+                }
+                Ok(Self {
+                    address: address.into(),
+                    len,
+                })
+            }
+            IpAddress::Ipv6(address) => {
+                if len > 128 {
+                    return Err(PrefixError("IPv6 prefixes can't be longer than 128 bits"));
+                }
+
+                let is_mapped = address.segments()[..6] == [0, 0, 0, 0, 0, 0xffff];
+                if is_mapped && len >= IPV4_MAPPED_PREFIX_LEN {
+                    if let IpAddress::Ipv4(address) = address.unmap() {
+                        return Ok(Self {
+                            address: address.into(),
+                            len: len - IPV4_MAPPED_PREFIX_LEN,
+                        });
+                    }
+                }
+
+                Ok(Self {
+                    address: address.into(),
+                    len,
+                })
+            }
+        }
+    }
+
+    /// Returns the address of the prefix
+    #[inline]
+    pub fn address(&self) -> IpAddress {
+        self.address
+    }
+
+    /// Returns the number of leading bits matched by the prefix
+    #[inline]
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u8 {
+        self.len
+    }
+}
+
+impl From<IpNet> for Prefix {
+    /// Converts the network into a prefix, converting IPv4-mapped IPv6 networks to the equivalent
+    /// IPv4 prefix
+    #[inline]
+    fn from(net: IpNet) -> Self {
+        Self::new(net.address(), net.len()).expect("network lengths are always valid")
+    }
+}
+
+/// Prefixes are serialized in CIDR notation
+#[cfg(feature = "serde")]
+impl serde::Serialize for Prefix {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let net = IpNet::new(self.address, self.len).expect("prefix lengths are always valid");
+        serde::Serialize::serialize(&net, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Prefix {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <IpNet as serde::Deserialize>::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// The list a prefix was added to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum List {
+    Allow,
+    Deny,
+}
+
+/// A node in a binary trie, indexing its children in the trie's node list
+#[derive(Clone, Copy, Debug, Default)]
+struct Node {
+    /// The index of the child for a `0` and `1` bit. The root is never a child, so `0` means the
+    /// node has no child.
+    children: [u32; 2],
+    list: Option<List>,
+}
+
+/// A binary trie which finds the longest prefix matching an address in a single pass over its
+/// bits
+#[derive(Clone, Debug)]
+struct Trie {
+    nodes: Vec<Node>,
+}
+
+impl Default for Trie {
+    fn default() -> Self {
+        Self {
+            nodes: alloc::vec![Node::default()],
+        }
+    }
+}
+
+#[inline]
+fn bit(bytes: &[u8], index: usize) -> usize {
+    ((bytes[index / 8] >> (7 - index % 8)) & 1) as usize
+}
+
+impl Trie {
+    fn insert(&mut self, bytes: &[u8], len: u8, list: List) {
+        let mut index = 0;
+
+        for bit_index in 0..len as usize {
+            let bit = bit(bytes, bit_index);
+            let child = self.nodes[index].children[bit];
+            index = if child == 0 {
+                let child = self.nodes.len();
+                self.nodes.push(Node::default());
+                self.nodes[index].children[bit] = child as u32;
+                child
+            } else {
+                child as usize
+            };
+        }
+
+        let node = &mut self.nodes[index];
+        // the deny list wins if a prefix is in both lists
+        if node.list != Some(List::Deny) {
+            node.list = Some(list);
+        }
+    }
+
+    #[inline]
+    fn longest_match(&self, bytes: &[u8]) -> Option<List> {
+        let mut node = &self.nodes[0];
+        let mut list = node.list;
+
+        for bit_index in 0..bytes.len() * 8 {
+            let child = node.children[bit(bytes, bit_index)];
+            if child == 0 {
+                break;
+            }
+            node = &self.nodes[child as usize];
+            list = node.list.or(list);
+        }
+
+        list
+    }
+}
+
+/// Builds a [`PrefixPolicy`]
+///
+/// See [`PrefixPolicy::builder`].
+#[derive(Clone, Debug)]
+pub struct Builder {
+    ipv4: Trie,
+    ipv6: Trie,
+    allow_outcome: Outcome,
+    deny_outcome: Outcome,
+    default_outcome: Outcome,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self {
+            ipv4: Trie::default(),
+            ipv6: Trie::default(),
+            allow_outcome: Outcome::allow(),
+            deny_outcome: Outcome::drop(),
+            default_outcome: Outcome::allow(),
+        }
+    }
+}
+
+impl Builder {
+    /// Adds a prefix to the allow list
+    #[inline]
+    pub fn with_allow<P: Into<Prefix>>(mut self, prefix: P) -> Self {
+        self.insert(prefix.into(), List::Allow);
+        self
+    }
+
+    /// Adds a prefix to the deny list
+    #[inline]
+    pub fn with_deny<P: Into<Prefix>>(mut self, prefix: P) -> Self {
+        self.insert(prefix.into(), List::Deny);
+        self
+    }
+
+    /// Sets the outcome for attempts from the allow list, defaulting to [`Outcome::allow`]
+    #[inline]
+    pub fn with_allow_outcome(mut self, outcome: Outcome) -> Self {
+        self.allow_outcome = outcome;
+        self
+    }
+
+    /// Sets the outcome for attempts from the deny list, defaulting to [`Outcome::drop`]
+    ///
+    /// Use [`Outcome::close`] to let denied clients know the connection was refused instead of
+    /// leaving them to time out.
+    #[inline]
+    pub fn with_deny_outcome(mut self, outcome: Outcome) -> Self {
+        self.deny_outcome = outcome;
+        self
+    }
+
+    /// Sets the outcome for attempts which don't match any prefix, defaulting to
+    /// [`Outcome::allow`]
+    ///
+    /// Setting this to [`Outcome::drop`] turns the allow list into the only addresses which can
+    /// connect.
+    #[inline]
+    pub fn with_default_outcome(mut self, outcome: Outcome) -> Self {
+        self.default_outcome = outcome;
+        self
+    }
+
+    /// Builds the policy
+    #[inline]
+    pub fn build(self) -> PrefixPolicy {
+        let Self {
+            ipv4,
+            ipv6,
+            allow_outcome,
+            deny_outcome,
+            default_outcome,
+        } = self;
+
+        PrefixPolicy {
+            ipv4,
+            ipv6,
+            allow_outcome,
+            deny_outcome,
+            default_outcome,
+        }
+    }
+
+    fn insert(&mut self, prefix: Prefix, list: List) {
+        match prefix.address {
+            IpAddress::Ipv4(address) => self.ipv4.insert(address.as_bytes(), prefix.len, list),
+            IpAddress::Ipv6(address) => self.ipv6.insert(address.as_bytes(), prefix.len, list),
+        }
+    }
+}
+
+/// Decides on connection attempts with allow and deny lists of address prefixes
+///
+/// The longest prefix matching the peer's address decides which list the attempt belongs to, so
+/// a host can be allowed from within a denied network and the other way around. If the same
+/// prefix is in both lists, the deny list wins. IPv4-mapped IPv6 addresses are matched against
+/// the IPv4 prefixes.
+///
+/// The policy can be combined with other limiters with [`Limiter::and_then`]. Note that the most
+/// restrictive outcome of the combined limiters is used, so allowed prefixes can still be limited
+/// by the other limiters.
+#[derive(Clone, Debug)]
+pub struct PrefixPolicy {
+    ipv4: Trie,
+    ipv6: Trie,
+    allow_outcome: Outcome,
+    deny_outcome: Outcome,
+    default_outcome: Outcome,
+}
+
+impl PrefixPolicy {
+    /// Returns a builder for the policy, which allows every attempt until prefixes are added
+    #[inline]
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    #[inline]
+    fn list(&self, address: &SocketAddress) -> Option<List> {
+        match address.unmap() {
+            SocketAddress::IpV4 { ip, .. } => self.ipv4.longest_match(&ip[..]),
+            SocketAddress::IpV6 { ip, .. } => self.ipv6.longest_match(&ip[..]),
+        }
+    }
+}
+
+impl Limiter for PrefixPolicy {
+    #[inline]
+    fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+        match self.list(&info.remote_address) {
+            Some(List::Allow) => self.allow_outcome.clone(),
+            Some(List::Deny) => self.deny_outcome.clone(),
+            None => self.default_outcome.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inet::{IpV4Address, IpV6Address, SocketAddressV4, SocketAddressV6};
+
+    fn v4(address: [u8; 4], len: u8) -> Prefix {
+        Prefix::new(IpV4Address::from(address), len).unwrap()
+    }
+
+    fn v6(address: [u16; 8], len: u8) -> Prefix {
+        Prefix::new(IpV6Address::from(address), len).unwrap()
+    }
+
+    fn outcome_v4(policy: &mut PrefixPolicy, address: [u8; 4]) -> Outcome {
+        let attempt =
+            ConnectionAttempt::builder().with_remote_address(SocketAddressV4::new(address, 443));
+        policy.on_connection_attempt(&attempt.build())
+    }
+
+    fn outcome_v6(policy: &mut PrefixPolicy, address: [u16; 8]) -> Outcome {
+        let attempt = ConnectionAttempt::builder()
+            .with_remote_address(SocketAddressV6::new(IpV6Address::from(address), 443));
+        policy.on_connection_attempt(&attempt.build())
+    }
+
+    #[test]
+    fn prefix_len_test() {
+        assert!(Prefix::new(IpV4Address::from([10, 0, 0, 0]), 32).is_ok());
+        assert!(Prefix::new(IpV4Address::from([10, 0, 0, 0]), 33).is_err());
+        assert!(Prefix::new(IpV6Address::from([0u16; 8]), 128).is_ok());
+        assert!(Prefix::new(IpV6Address::from([0u16; 8]), 129).is_err());
+
+        // IPv4-mapped prefixes are converted to IPv4
+        let mapped = v6([0, 0, 0, 0, 0, 0xffff, 0x0a00, 0], 104);
+        assert_eq!(mapped, v4([10, 0, 0, 0], 8));
+        // shorter prefixes cover more than the mapped range so stay IPv6
+        assert_eq!(v6([0, 0, 0, 0, 0, 0xffff, 0, 0], 80).len(), 80);
+
+        // networks are converted the same way
+        let net: IpNet = "::ffff:10.0.0.0/104".parse().unwrap();
+        assert_eq!(Prefix::from(net), v4([10, 0, 0, 0], 8));
+        let net: IpNet = "2001:db8::/32".parse().unwrap();
+        assert_eq!(Prefix::from(net), v6([0x2001, 0xdb8, 0, 0, 0, 0, 0, 0], 32));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_test() {
+        let prefix: Prefix = serde_json::from_str("\"::ffff:10.0.0.0/104\"").unwrap();
+        assert_eq!(prefix, v4([10, 0, 0, 0], 8));
+        assert_eq!(serde_json::to_string(&prefix).unwrap(), "\"10.0.0.0/8\"");
+        assert!(serde_json::from_str::<Prefix>("\"10.0.0.0/33\"").is_err());
+    }
+
+    #[test]
+    fn default_outcome_test() {
+        let mut policy = PrefixPolicy::builder().build();
+        assert_eq!(outcome_v4(&mut policy, [10, 0, 0, 1]), Outcome::allow());
+
+        let mut policy = PrefixPolicy::builder()
+            .with_allow(v4([10, 0, 0, 0], 8))
+            .with_default_outcome(Outcome::drop())
+            .build();
+        assert_eq!(outcome_v4(&mut policy, [10, 1, 2, 3]), Outcome::allow());
+        assert_eq!(outcome_v4(&mut policy, [11, 1, 2, 3]), Outcome::drop());
+        assert_eq!(
+            outcome_v6(&mut policy, [0x2001, 0xdb8, 0, 0, 0, 0, 0, 1]),
+            Outcome::drop()
+        );
+    }
+
+    #[test]
+    fn deny_outcome_test() {
+        let mut policy = PrefixPolicy::builder()
+            .with_deny(v4([192, 0, 2, 0], 24))
+            .build();
+        assert_eq!(outcome_v4(&mut policy, [192, 0, 2, 1]), Outcome::drop());
+
+        let close = Outcome::close().with_reason("denied");
+        let mut policy = PrefixPolicy::builder()
+            .with_deny(v4([192, 0, 2, 0], 24))
+            .with_deny_outcome(close.clone())
+            .with_allow(v4([198, 51, 100, 0], 24))
+            .with_allow_outcome(Outcome::retry())
+            .build();
+        assert_eq!(outcome_v4(&mut policy, [192, 0, 2, 1]), close);
+        assert_eq!(outcome_v4(&mut policy, [198, 51, 100, 1]), Outcome::retry());
+        assert_eq!(outcome_v4(&mut policy, [203, 0, 113, 1]), Outcome::allow());
+    }
+
+    #[test]
+    fn longest_prefix_test() {
+        let mut policy = PrefixPolicy::builder()
+            .with_deny(v4([10, 0, 0, 0], 8))
+            .with_allow(v4([10, 1, 0, 0], 16))
+            .with_deny(v4([10, 1, 1, 0], 24))
+            .with_allow(v4([10, 1, 1, 1], 32))
+            .with_deny(v6([0x2001, 0xdb8, 0, 0, 0, 0, 0, 0], 32))
+            .with_allow(v6([0x2001, 0xdb8, 0x1, 0, 0, 0, 0, 0], 48))
+            .build();
+
+        assert!(outcome_v4(&mut policy, [10, 2, 0, 1]).is_drop());
+        assert!(outcome_v4(&mut policy, [10, 1, 2, 1]).is_allow());
+        assert!(outcome_v4(&mut policy, [10, 1, 1, 2]).is_drop());
+        assert!(outcome_v4(&mut policy, [10, 1, 1, 1]).is_allow());
+        assert!(outcome_v4(&mut policy, [11, 0, 0, 1]).is_allow());
+
+        assert!(outcome_v6(&mut policy, [0x2001, 0xdb8, 0x2, 0, 0, 0, 0, 1]).is_drop());
+        assert!(outcome_v6(&mut policy, [0x2001, 0xdb8, 0x1, 0, 0, 0, 0, 1]).is_allow());
+
+        // the order prefixes are added in doesn't matter
+        let mut policy = PrefixPolicy::builder()
+            .with_allow(v4([10, 1, 0, 0], 16))
+            .with_deny(v4([10, 0, 0, 0], 8))
+            .build();
+        assert!(outcome_v4(&mut policy, [10, 1, 2, 1]).is_allow());
+        assert!(outcome_v4(&mut policy, [10, 2, 2, 1]).is_drop());
+    }
+
+    #[test]
+    fn same_prefix_test() {
+        for deny_first in [false, true] {
+            let mut builder = PrefixPolicy::builder();
+            if deny_first {
+                builder = builder.with_deny(v4([10, 0, 0, 0], 8));
+            }
+            builder = builder.with_allow(v4([10, 0, 0, 0], 8));
+            if !deny_first {
+                builder = builder.with_deny(v4([10, 0, 0, 0], 8));
+            }
+
+            let mut policy = builder.build();
+            assert!(outcome_v4(&mut policy, [10, 0, 0, 1]).is_drop());
+        }
+    }
+
+    #[test]
+    fn zero_len_prefix_test() {
+        let mut policy = PrefixPolicy::builder()
+            .with_deny(v4([0, 0, 0, 0], 0))
+            .with_allow(v4([10, 0, 0, 0], 8))
+            .build();
+
+        assert!(outcome_v4(&mut policy, [10, 0, 0, 1]).is_allow());
+        assert!(outcome_v4(&mut policy, [11, 0, 0, 1]).is_drop());
+        // the IPv4 default route doesn't cover IPv6 addresses
+        assert!(outcome_v6(&mut policy, [0x2001, 0xdb8, 0, 0, 0, 0, 0, 1]).is_allow());
+    }
+
+    #[test]
+    fn ipv4_mapped_test() {
+        let mut policy = PrefixPolicy::builder()
+            .with_deny(v4([192, 0, 2, 0], 24))
+            .build();
+
+        // ::ffff:192.0.2.1
+        let mapped = [0, 0, 0, 0, 0, 0xffff, 0xc000, 0x0201];
+        assert!(outcome_v6(&mut policy, mapped).is_drop());
+        // ::ffff:198.51.100.1
+        let mapped = [0, 0, 0, 0, 0, 0xffff, 0xc633, 0x6401];
+        assert!(outcome_v6(&mut policy, mapped).is_allow());
+
+        // mapped prefixes match IPv4 addresses as well
+        let mut policy = PrefixPolicy::builder()
+            .with_deny(v6([0, 0, 0, 0, 0, 0xffff, 0xc000, 0x0200], 120))
+            .build();
+        assert!(outcome_v4(&mut policy, [192, 0, 2, 1]).is_drop());
+    }
+
+    #[test]
+    fn and_then_test() {
+        let policy = PrefixPolicy::builder()
+            .with_deny(v4([192, 0, 2, 0], 24))
+            .build();
+        let mut limiter =
+            policy.and_then(|_: &ConnectionAttempt| Outcome::close().with_reason("busy"));
+
+        assert!(outcome_v4(&mut limiter.0, [192, 0, 2, 1]).is_drop());
+
+        let attempt = ConnectionAttempt::builder()
+            .with_remote_address(SocketAddressV4::new([192, 0, 2, 1], 443));
+        assert!(limiter.on_connection_attempt(&attempt.build()).is_drop());
+
+        let attempt = ConnectionAttempt::builder()
+            .with_remote_address(SocketAddressV4::new([198, 51, 100, 1], 443));
+        assert!(limiter.on_connection_attempt(&attempt.build()).is_close());
+    }
+}
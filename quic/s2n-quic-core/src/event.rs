@@ -119,6 +119,15 @@ impl Timestamp {
     pub fn saturating_duration_since(self, earlier: Self) -> Duration {
         self.0.saturating_duration_since(earlier.0)
     }
+
+    /// Converts the timestamp to a [`SystemTime`](std::time::SystemTime) relative to the anchor
+    ///
+    /// See [`crate::time::Anchor`] for more details.
+    #[cfg(any(test, feature = "std"))]
+    #[inline]
+    pub fn as_system_time(&self, anchor: &crate::time::Anchor) -> Option<std::time::SystemTime> {
+        self.0.as_system_time(anchor)
+    }
 }
 
 impl IntoEvent<Timestamp> for crate::time::Timestamp {
@@ -151,6 +160,25 @@ impl<'a> TlsSession<'a> {
     pub fn cipher_suite(&self) -> crate::event::api::CipherSuite {
         self.session.cipher_suite().into_event()
     }
+
+    pub fn key_exchange_group(&self) -> Option<crate::crypto::tls::KeyExchangeGroup> {
+        self.session.key_exchange_group()
+    }
+
+    pub fn handshake_type(&self) -> Option<crate::crypto::tls::handshake::HandshakeType> {
+        self.session.handshake_type()
+    }
+
+    pub fn handshake_stats(&self) -> Option<crate::crypto::tls::HandshakeStats> {
+        self.session.handshake_stats()
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn peer_cert_chain_der(
+        &self,
+    ) -> Result<alloc::vec::Vec<alloc::vec::Vec<u8>>, crate::crypto::tls::ChainError> {
+        self.session.peer_cert_chain_der()
+    }
 }
 
 impl<'a> crate::event::IntoEvent<TlsSession<'a>> for TlsSession<'a> {
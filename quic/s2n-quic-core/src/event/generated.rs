@@ -202,6 +202,15 @@ pub mod api {
         HandshakeDone {},
         #[non_exhaustive]
         Datagram { len: u16 },
+        #[non_exhaustive]
+        AckFrequency {
+            sequence_number: u64,
+            ack_eliciting_threshold: u64,
+            request_max_ack_delay: Duration,
+            reordering_threshold: u64,
+        },
+        #[non_exhaustive]
+        ImmediateAck {},
     }
     #[derive(Clone, Debug)]
     #[non_exhaustive]
@@ -307,7 +316,10 @@ pub mod api {
         UnknownDestinationConnectionId {},
         #[non_exhaustive]
         #[doc = " The connection attempt was rejected."]
-        RejectedConnectionAttempt {},
+        RejectedConnectionAttempt {
+            #[doc = " The reason provided by the endpoint limits, if any"]
+            reason: Option<&'static str>,
+        },
         #[non_exhaustive]
         #[doc = " A datagram was received from an unknown server address."]
         UnknownServerAddress {},
@@ -326,6 +338,23 @@ pub mod api {
     }
     #[derive(Clone, Debug)]
     #[non_exhaustive]
+    #[doc = " The outcome of a connection attempt, as decided by the endpoint limits"]
+    pub enum ConnectionAttemptOutcome {
+        #[non_exhaustive]
+        #[doc = " The attempt was allowed to continue"]
+        Allow {},
+        #[non_exhaustive]
+        #[doc = " The client was asked to validate its address with a Retry packet"]
+        Retry {},
+        #[non_exhaustive]
+        #[doc = " The attempt was silently dropped"]
+        Drop {},
+        #[non_exhaustive]
+        #[doc = " The attempt was refused with a CONNECTION_CLOSE frame"]
+        Close {},
+    }
+    #[derive(Clone, Debug)]
+    #[non_exhaustive]
     pub enum KeySpace {
         #[non_exhaustive]
         Initial {},
@@ -454,6 +483,8 @@ pub mod api {
         IpScopeChange {},
         #[non_exhaustive]
         ConnectionMigrationDisabled {},
+        #[non_exhaustive]
+        Limited {},
     }
     #[derive(Clone, Debug)]
     #[non_exhaustive]
@@ -524,8 +555,13 @@ pub mod api {
     #[non_exhaustive]
     pub enum PathChallengeStatus {
         #[non_exhaustive]
+        #[doc = " A PATH_CHALLENGE was issued and the path is awaiting a PATH_RESPONSE"]
+        Pending {},
+        #[non_exhaustive]
+        #[doc = " The path was validated by a PATH_RESPONSE from the peer"]
         Validated {},
         #[non_exhaustive]
+        #[doc = " The path validation failed or was abandoned before a PATH_RESPONSE was received"]
         Abandoned {},
     }
     #[derive(Clone, Debug)]
@@ -1095,6 +1131,28 @@ pub mod api {
     }
     #[derive(Clone, Debug)]
     #[non_exhaustive]
+    #[doc = " The endpoint limits decided on the outcome of a connection attempt"]
+    pub struct EndpointConnectionAttemptDecided {
+        pub outcome: ConnectionAttemptOutcome,
+        #[doc = " The number of handshakes in progress when the attempt arrived"]
+        pub inflight_handshakes: usize,
+    }
+    impl Event for EndpointConnectionAttemptDecided {
+        const NAME: &'static str = "transport:connection_attempt_decided";
+    }
+    #[derive(Clone, Debug)]
+    #[non_exhaustive]
+    #[doc = " A Retry or CONNECTION_CLOSE packet for a connection attempt was dropped because too many"]
+    #[doc = " responses were already queued"]
+    pub struct EndpointResponseDropped {
+        #[doc = " The outcome the dropped packet was responding with"]
+        pub outcome: ConnectionAttemptOutcome,
+    }
+    impl Event for EndpointResponseDropped {
+        const NAME: &'static str = "transport:response_dropped";
+    }
+    #[derive(Clone, Debug)]
+    #[non_exhaustive]
     #[doc = " Emitted when the platform sends at least one packet"]
     pub struct PlatformTx {
         #[doc = " The number of packets sent"]
@@ -1295,6 +1353,29 @@ pub mod api {
                 Self::IpV6 { port, .. } => *port,
             }
         }
+        #[doc = " Converts the address into IPv4 if it is mapped, otherwise the address is unchanged"]
+        #[doc = ""]
+        #[doc = " Limiters should key their state by the unmapped address, since dual-stack sockets report"]
+        #[doc = " IPv4 peers with IPv4-mapped addresses."]
+        #[inline]
+        pub fn unmap(&self) -> Self {
+            match self {
+                Self::IpV4 { ip, port } => Self::IpV4 {
+                    ip: *ip,
+                    port: *port,
+                },
+                Self::IpV6 { ip, port } => match crate::inet::IpV6Address::from(**ip).unmap() {
+                    crate::inet::IpAddress::Ipv4(_) => Self::IpV4 {
+                        ip: ip[12..].try_into().expect("slice is 4 bytes"),
+                        port: *port,
+                    },
+                    crate::inet::IpAddress::Ipv6(_) => Self::IpV6 {
+                        ip: *ip,
+                        port: *port,
+                    },
+                },
+            }
+        }
     }
     impl<'a> IntoEvent<api::SocketAddress<'a>> for &'a crate::inet::SocketAddress {
         #[inline]
@@ -1554,6 +1635,23 @@ pub mod api {
             }
         }
     }
+    impl IntoEvent<builder::Frame> for &crate::frame::AckFrequency {
+        #[inline]
+        fn into_event(self) -> builder::Frame {
+            builder::Frame::AckFrequency {
+                sequence_number: self.sequence_number.as_u64(),
+                ack_eliciting_threshold: self.ack_eliciting_threshold.as_u64(),
+                request_max_ack_delay: self.request_max_ack_delay(),
+                reordering_threshold: self.reordering_threshold.as_u64(),
+            }
+        }
+    }
+    impl IntoEvent<builder::Frame> for &crate::frame::ImmediateAck {
+        #[inline]
+        fn into_event(self) -> builder::Frame {
+            builder::Frame::ImmediateAck {}
+        }
+    }
     impl IntoEvent<builder::StreamType> for &crate::stream::StreamType {
         #[inline]
         fn into_event(self) -> builder::StreamType {
@@ -2309,6 +2407,35 @@ pub mod tracing {
             tracing :: event ! (target : "endpoint_connection_attempt_failed" , parent : parent , tracing :: Level :: DEBUG , error = tracing :: field :: debug (error));
         }
         #[inline]
+        fn on_endpoint_connection_attempt_decided(
+            &mut self,
+            meta: &api::EndpointMeta,
+            event: &api::EndpointConnectionAttemptDecided,
+        ) {
+            let parent = match meta.endpoint_type {
+                api::EndpointType::Client {} => self.client.id(),
+                api::EndpointType::Server {} => self.server.id(),
+            };
+            let api::EndpointConnectionAttemptDecided {
+                outcome,
+                inflight_handshakes,
+            } = event;
+            tracing :: event ! (target : "endpoint_connection_attempt_decided" , parent : parent , tracing :: Level :: DEBUG , outcome = tracing :: field :: debug (outcome) , inflight_handshakes = tracing :: field :: debug (inflight_handshakes));
+        }
+        #[inline]
+        fn on_endpoint_response_dropped(
+            &mut self,
+            meta: &api::EndpointMeta,
+            event: &api::EndpointResponseDropped,
+        ) {
+            let parent = match meta.endpoint_type {
+                api::EndpointType::Client {} => self.client.id(),
+                api::EndpointType::Server {} => self.server.id(),
+            };
+            let api::EndpointResponseDropped { outcome } = event;
+            tracing :: event ! (target : "endpoint_response_dropped" , parent : parent , tracing :: Level :: DEBUG , outcome = tracing :: field :: debug (outcome));
+        }
+        #[inline]
         fn on_platform_tx(&mut self, meta: &api::EndpointMeta, event: &api::PlatformTx) {
             let parent = match meta.endpoint_type {
                 api::EndpointType::Client {} => self.client.id(),
@@ -2761,6 +2888,13 @@ pub mod builder {
         Datagram {
             len: u16,
         },
+        AckFrequency {
+            sequence_number: u64,
+            ack_eliciting_threshold: u64,
+            request_max_ack_delay: Duration,
+            reordering_threshold: u64,
+        },
+        ImmediateAck,
     }
     impl IntoEvent<api::Frame> for Frame {
         #[inline]
@@ -2855,6 +2989,18 @@ pub mod builder {
                 Self::Datagram { len } => Datagram {
                     len: len.into_event(),
                 },
+                Self::AckFrequency {
+                    sequence_number,
+                    ack_eliciting_threshold,
+                    request_max_ack_delay,
+                    reordering_threshold,
+                } => AckFrequency {
+                    sequence_number: sequence_number.into_event(),
+                    ack_eliciting_threshold: ack_eliciting_threshold.into_event(),
+                    request_max_ack_delay: request_max_ack_delay.into_event(),
+                    reordering_threshold: reordering_threshold.into_event(),
+                },
+                Self::ImmediateAck => ImmediateAck {},
             }
         }
     }
@@ -3016,7 +3162,10 @@ pub mod builder {
         #[doc = " datagram can not be processed and is dropped."]
         UnknownDestinationConnectionId,
         #[doc = " The connection attempt was rejected."]
-        RejectedConnectionAttempt,
+        RejectedConnectionAttempt {
+            #[doc = " The reason provided by the endpoint limits, if any"]
+            reason: Option<&'static str>,
+        },
         #[doc = " A datagram was received from an unknown server address."]
         UnknownServerAddress,
         #[doc = " The peer initiated a connection migration before the handshake was confirmed."]
@@ -3039,7 +3188,9 @@ pub mod builder {
                 Self::InvalidDestinationConnectionId => InvalidDestinationConnectionId {},
                 Self::InvalidSourceConnectionId => InvalidSourceConnectionId {},
                 Self::UnknownDestinationConnectionId => UnknownDestinationConnectionId {},
-                Self::RejectedConnectionAttempt => RejectedConnectionAttempt {},
+                Self::RejectedConnectionAttempt { reason } => RejectedConnectionAttempt {
+                    reason: reason.into_event(),
+                },
                 Self::UnknownServerAddress => UnknownServerAddress {},
                 Self::ConnectionMigrationDuringHandshake => ConnectionMigrationDuringHandshake {},
                 Self::RejectedConnectionMigration => RejectedConnectionMigration {},
@@ -3049,6 +3200,30 @@ pub mod builder {
         }
     }
     #[derive(Clone, Debug)]
+    #[doc = " The outcome of a connection attempt, as decided by the endpoint limits"]
+    pub enum ConnectionAttemptOutcome {
+        #[doc = " The attempt was allowed to continue"]
+        Allow,
+        #[doc = " The client was asked to validate its address with a Retry packet"]
+        Retry,
+        #[doc = " The attempt was silently dropped"]
+        Drop,
+        #[doc = " The attempt was refused with a CONNECTION_CLOSE frame"]
+        Close,
+    }
+    impl IntoEvent<api::ConnectionAttemptOutcome> for ConnectionAttemptOutcome {
+        #[inline]
+        fn into_event(self) -> api::ConnectionAttemptOutcome {
+            use api::ConnectionAttemptOutcome::*;
+            match self {
+                Self::Allow => Allow {},
+                Self::Retry => Retry {},
+                Self::Drop => Drop {},
+                Self::Close => Close {},
+            }
+        }
+    }
+    #[derive(Clone, Debug)]
     pub enum KeySpace {
         Initial,
         Handshake,
@@ -3251,6 +3426,7 @@ pub mod builder {
         PortScopeChanged,
         IpScopeChange,
         ConnectionMigrationDisabled,
+        Limited,
     }
     impl IntoEvent<api::MigrationDenyReason> for MigrationDenyReason {
         #[inline]
@@ -3261,6 +3437,7 @@ pub mod builder {
                 Self::PortScopeChanged => PortScopeChanged {},
                 Self::IpScopeChange => IpScopeChange {},
                 Self::ConnectionMigrationDisabled => ConnectionMigrationDisabled {},
+                Self::Limited => Limited {},
             }
         }
     }
@@ -3359,7 +3536,11 @@ pub mod builder {
     }
     #[derive(Clone, Debug)]
     pub enum PathChallengeStatus {
+        #[doc = " A PATH_CHALLENGE was issued and the path is awaiting a PATH_RESPONSE"]
+        Pending,
+        #[doc = " The path was validated by a PATH_RESPONSE from the peer"]
         Validated,
+        #[doc = " The path validation failed or was abandoned before a PATH_RESPONSE was received"]
         Abandoned,
     }
     impl IntoEvent<api::PathChallengeStatus> for PathChallengeStatus {
@@ -3367,6 +3548,7 @@ pub mod builder {
         fn into_event(self) -> api::PathChallengeStatus {
             use api::PathChallengeStatus::*;
             match self {
+                Self::Pending => Pending {},
                 Self::Validated => Validated {},
                 Self::Abandoned => Abandoned {},
             }
@@ -4340,6 +4522,42 @@ pub mod builder {
         }
     }
     #[derive(Clone, Debug)]
+    #[doc = " The endpoint limits decided on the outcome of a connection attempt"]
+    pub struct EndpointConnectionAttemptDecided {
+        pub outcome: ConnectionAttemptOutcome,
+        #[doc = " The number of handshakes in progress when the attempt arrived"]
+        pub inflight_handshakes: usize,
+    }
+    impl IntoEvent<api::EndpointConnectionAttemptDecided> for EndpointConnectionAttemptDecided {
+        #[inline]
+        fn into_event(self) -> api::EndpointConnectionAttemptDecided {
+            let EndpointConnectionAttemptDecided {
+                outcome,
+                inflight_handshakes,
+            } = self;
+            api::EndpointConnectionAttemptDecided {
+                outcome: outcome.into_event(),
+                inflight_handshakes: inflight_handshakes.into_event(),
+            }
+        }
+    }
+    #[derive(Clone, Debug)]
+    #[doc = " A Retry or CONNECTION_CLOSE packet for a connection attempt was dropped because too many"]
+    #[doc = " responses were already queued"]
+    pub struct EndpointResponseDropped {
+        #[doc = " The outcome the dropped packet was responding with"]
+        pub outcome: ConnectionAttemptOutcome,
+    }
+    impl IntoEvent<api::EndpointResponseDropped> for EndpointResponseDropped {
+        #[inline]
+        fn into_event(self) -> api::EndpointResponseDropped {
+            let EndpointResponseDropped { outcome } = self;
+            api::EndpointResponseDropped {
+                outcome: outcome.into_event(),
+            }
+        }
+    }
+    #[derive(Clone, Debug)]
     #[doc = " Emitted when the platform sends at least one packet"]
     pub struct PlatformTx {
         #[doc = " The number of packets sent"]
@@ -5234,6 +5452,26 @@ mod traits {
             let _ = meta;
             let _ = event;
         }
+        #[doc = "Called when the `EndpointConnectionAttemptDecided` event is triggered"]
+        #[inline]
+        fn on_endpoint_connection_attempt_decided(
+            &mut self,
+            meta: &EndpointMeta,
+            event: &EndpointConnectionAttemptDecided,
+        ) {
+            let _ = meta;
+            let _ = event;
+        }
+        #[doc = "Called when the `EndpointResponseDropped` event is triggered"]
+        #[inline]
+        fn on_endpoint_response_dropped(
+            &mut self,
+            meta: &EndpointMeta,
+            event: &EndpointResponseDropped,
+        ) {
+            let _ = meta;
+            let _ = event;
+        }
         #[doc = "Called when the `PlatformTx` event is triggered"]
         #[inline]
         fn on_platform_tx(&mut self, meta: &EndpointMeta, event: &PlatformTx) {
@@ -5859,6 +6097,24 @@ mod traits {
             (self.1).on_endpoint_connection_attempt_failed(meta, event);
         }
         #[inline]
+        fn on_endpoint_connection_attempt_decided(
+            &mut self,
+            meta: &EndpointMeta,
+            event: &EndpointConnectionAttemptDecided,
+        ) {
+            (self.0).on_endpoint_connection_attempt_decided(meta, event);
+            (self.1).on_endpoint_connection_attempt_decided(meta, event);
+        }
+        #[inline]
+        fn on_endpoint_response_dropped(
+            &mut self,
+            meta: &EndpointMeta,
+            event: &EndpointResponseDropped,
+        ) {
+            (self.0).on_endpoint_response_dropped(meta, event);
+            (self.1).on_endpoint_response_dropped(meta, event);
+        }
+        #[inline]
         fn on_platform_tx(&mut self, meta: &EndpointMeta, event: &PlatformTx) {
             (self.0).on_platform_tx(meta, event);
             (self.1).on_platform_tx(meta, event);
@@ -5959,6 +6215,13 @@ mod traits {
             &mut self,
             event: builder::EndpointConnectionAttemptFailed,
         );
+        #[doc = "Publishes a `EndpointConnectionAttemptDecided` event to the publisher's subscriber"]
+        fn on_endpoint_connection_attempt_decided(
+            &mut self,
+            event: builder::EndpointConnectionAttemptDecided,
+        );
+        #[doc = "Publishes a `EndpointResponseDropped` event to the publisher's subscriber"]
+        fn on_endpoint_response_dropped(&mut self, event: builder::EndpointResponseDropped);
         #[doc = "Publishes a `PlatformTx` event to the publisher's subscriber"]
         fn on_platform_tx(&mut self, event: builder::PlatformTx);
         #[doc = "Publishes a `PlatformTxError` event to the publisher's subscriber"]
@@ -6055,6 +6318,23 @@ mod traits {
             self.subscriber.on_event(&self.meta, &event);
         }
         #[inline]
+        fn on_endpoint_connection_attempt_decided(
+            &mut self,
+            event: builder::EndpointConnectionAttemptDecided,
+        ) {
+            let event = event.into_event();
+            self.subscriber
+                .on_endpoint_connection_attempt_decided(&self.meta, &event);
+            self.subscriber.on_event(&self.meta, &event);
+        }
+        #[inline]
+        fn on_endpoint_response_dropped(&mut self, event: builder::EndpointResponseDropped) {
+            let event = event.into_event();
+            self.subscriber
+                .on_endpoint_response_dropped(&self.meta, &event);
+            self.subscriber.on_event(&self.meta, &event);
+        }
+        #[inline]
         fn on_platform_tx(&mut self, event: builder::PlatformTx) {
             let event = event.into_event();
             self.subscriber.on_platform_tx(&self.meta, &event);
@@ -6679,6 +6959,8 @@ pub mod testing {
         pub endpoint_datagram_received: u32,
         pub endpoint_datagram_dropped: u32,
         pub endpoint_connection_attempt_failed: u32,
+        pub endpoint_connection_attempt_decided: u32,
+        pub endpoint_response_dropped: u32,
         pub platform_tx: u32,
         pub platform_tx_error: u32,
         pub platform_rx: u32,
@@ -6759,6 +7041,8 @@ pub mod testing {
                 endpoint_datagram_received: 0,
                 endpoint_datagram_dropped: 0,
                 endpoint_connection_attempt_failed: 0,
+                endpoint_connection_attempt_decided: 0,
+                endpoint_response_dropped: 0,
                 platform_tx: 0,
                 platform_tx_error: 0,
                 platform_rx: 0,
@@ -7296,6 +7580,22 @@ pub mod testing {
             self.endpoint_connection_attempt_failed += 1;
             self.output.push(format!("{meta:?} {event:?}"));
         }
+        fn on_endpoint_connection_attempt_decided(
+            &mut self,
+            meta: &api::EndpointMeta,
+            event: &api::EndpointConnectionAttemptDecided,
+        ) {
+            self.endpoint_connection_attempt_decided += 1;
+            self.output.push(format!("{meta:?} {event:?}"));
+        }
+        fn on_endpoint_response_dropped(
+            &mut self,
+            meta: &api::EndpointMeta,
+            event: &api::EndpointResponseDropped,
+        ) {
+            self.endpoint_response_dropped += 1;
+            self.output.push(format!("{meta:?} {event:?}"));
+        }
         fn on_platform_tx(&mut self, meta: &api::EndpointMeta, event: &api::PlatformTx) {
             self.platform_tx += 1;
             self.output.push(format!("{meta:?} {event:?}"));
@@ -7390,6 +7690,8 @@ pub mod testing {
         pub endpoint_datagram_received: u32,
         pub endpoint_datagram_dropped: u32,
         pub endpoint_connection_attempt_failed: u32,
+        pub endpoint_connection_attempt_decided: u32,
+        pub endpoint_response_dropped: u32,
         pub platform_tx: u32,
         pub platform_tx_error: u32,
         pub platform_rx: u32,
@@ -7460,6 +7762,8 @@ pub mod testing {
                 endpoint_datagram_received: 0,
                 endpoint_datagram_dropped: 0,
                 endpoint_connection_attempt_failed: 0,
+                endpoint_connection_attempt_decided: 0,
+                endpoint_response_dropped: 0,
                 platform_tx: 0,
                 platform_tx_error: 0,
                 platform_rx: 0,
@@ -7509,6 +7813,19 @@ pub mod testing {
             let event = event.into_event();
             self.output.push(format!("{event:?}"));
         }
+        fn on_endpoint_connection_attempt_decided(
+            &mut self,
+            event: builder::EndpointConnectionAttemptDecided,
+        ) {
+            self.endpoint_connection_attempt_decided += 1;
+            let event = event.into_event();
+            self.output.push(format!("{event:?}"));
+        }
+        fn on_endpoint_response_dropped(&mut self, event: builder::EndpointResponseDropped) {
+            self.endpoint_response_dropped += 1;
+            let event = event.into_event();
+            self.output.push(format!("{event:?}"));
+        }
         fn on_platform_tx(&mut self, event: builder::PlatformTx) {
             self.platform_tx += 1;
             let event = event.into_event();
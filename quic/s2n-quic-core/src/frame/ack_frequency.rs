@@ -0,0 +1,96 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{frame::Tag, varint::VarInt};
+use s2n_codec::{decoder_invariant, decoder_parameterized_value, Encoder, EncoderValue};
+
+// The ACK_FREQUENCY frame is defined by the ACK frequency extension
+// (https://datatracker.ietf.org/doc/html/draft-ietf-quic-ack-frequency#section-4)
+//
+// An endpoint sends an ACK_FREQUENCY frame (type=0xaf) to request that its peer
+// adjust the frequency at which it sends acknowledgements.
+
+// The frame type doesn't fit in a single byte variable-length integer, so the
+// first byte of its two byte encoding is matched instead
+macro_rules! ack_frequency_tag {
+    () => {
+        0x40u8
+    };
+}
+
+const FRAME_TYPE: VarInt = VarInt::from_u8(0xaf);
+
+// ACK_FREQUENCY Frame {
+//   Type (i) = 0xaf,
+//   Sequence Number (i),
+//   Ack-Eliciting Threshold (i),
+//   Request Max Ack Delay (i),
+//   Reordering Threshold (i),
+// }
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AckFrequency {
+    /// A sequence number assigned to the frame by the sender, allowing
+    /// receivers to ignore obsolete frames
+    pub sequence_number: VarInt,
+
+    /// The maximum number of ack-eliciting packets the recipient of this
+    /// frame receives before sending an acknowledgement
+    pub ack_eliciting_threshold: VarInt,
+
+    /// The value, in microseconds, to which the recipient of this frame
+    /// should update its max_ack_delay
+    pub request_max_ack_delay: VarInt,
+
+    /// The maximum packet reordering before eliciting an immediate ACK.
+    /// A value of 0 indicates no immediate ACK is sent due to reordering.
+    pub reordering_threshold: VarInt,
+}
+
+impl AckFrequency {
+    pub const fn tag(self) -> u8 {
+        FRAME_TYPE.as_u64() as u8
+    }
+
+    /// Returns the requested max_ack_delay as a `core::time::Duration`
+    pub const fn request_max_ack_delay(&self) -> core::time::Duration {
+        core::time::Duration::from_micros(self.request_max_ack_delay.as_u64())
+    }
+}
+
+decoder_parameterized_value!(
+    impl<'a> AckFrequency {
+        fn decode(_tag: Tag, buffer: Buffer) -> Result<Self> {
+            // the first byte of the frame type was already consumed
+            let (frame_type, buffer) = buffer.decode::<u8>()?;
+            decoder_invariant!(
+                frame_type as u64 == FRAME_TYPE.as_u64(),
+                "invalid frame"
+            );
+
+            let (sequence_number, buffer) = buffer.decode()?;
+            let (ack_eliciting_threshold, buffer) = buffer.decode()?;
+            let (request_max_ack_delay, buffer) = buffer.decode()?;
+            let (reordering_threshold, buffer) = buffer.decode()?;
+
+            let frame = AckFrequency {
+                sequence_number,
+                ack_eliciting_threshold,
+                request_max_ack_delay,
+                reordering_threshold,
+            };
+
+            Ok((frame, buffer))
+        }
+    }
+);
+
+impl EncoderValue for AckFrequency {
+    fn encode<E: Encoder>(&self, buffer: &mut E) {
+        buffer.encode(&FRAME_TYPE);
+        buffer.encode(&self.sequence_number);
+        buffer.encode(&self.ack_eliciting_threshold);
+        buffer.encode(&self.request_max_ack_delay);
+        buffer.encode(&self.reordering_threshold);
+    }
+}
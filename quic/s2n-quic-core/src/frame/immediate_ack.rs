@@ -0,0 +1,25 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+// The IMMEDIATE_ACK frame is defined by the ACK frequency extension
+// (https://datatracker.ietf.org/doc/html/draft-ietf-quic-ack-frequency#section-5)
+//
+// An endpoint can send an IMMEDIATE_ACK frame (type=0x1f) to request that its
+// peer send a packet containing an ACK frame without delay.
+
+macro_rules! immediate_ack_tag {
+    () => {
+        0x1fu8
+    };
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ImmediateAck;
+
+impl ImmediateAck {
+    pub const fn tag(self) -> u8 {
+        immediate_ack_tag!()
+    }
+}
+
+simple_frame_codec!(ImmediateAck {}, immediate_ack_tag!());
@@ -164,9 +164,6 @@ macro_rules! frames {
             ) -> DecoderBufferMutResult<'a, Self::Output> {
                 let tag = buffer.peek_byte(0)?;
                 match tag {
-                    // Make sure the single byte frame tags fit into a small variable-integer
-                    // otherwise fallback to extension selection
-                    0b0100_0000..=0xff => self.handle_extension_frame(buffer),
                     $(
                         $tag_macro!() => {
                             let buffer = buffer.skip(core::mem::size_of::<Tag>())?;
@@ -175,6 +172,8 @@ macro_rules! frames {
                             Ok((output, buffer))
                         },
                     )*
+                    // Any frame types which aren't known, including those that don't fit into
+                    // a small variable-integer, fallback to extension selection
                     _ => self.handle_extension_frame(buffer),
                 }
             }
@@ -199,6 +198,45 @@ macro_rules! frames {
     };
 }
 
+/// An iterator over the frames in a decrypted packet payload
+///
+/// Each frame is returned along with its byte offset in the payload. Iteration stops after
+/// the first frame which fails to decode, so untrusted payloads can be inspected without
+/// any additional validation.
+#[derive(Debug)]
+pub struct Iter<'a> {
+    buffer: Option<DecoderBufferMut<'a>>,
+    len: usize,
+}
+
+impl<'a> Iter<'a> {
+    #[inline]
+    pub fn new(payload: DecoderBufferMut<'a>) -> Self {
+        Self {
+            len: payload.len(),
+            buffer: Some(payload),
+        }
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = Result<(usize, FrameMut<'a>), DecoderError>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let buffer = self.buffer.take().filter(|buffer| !buffer.is_empty())?;
+        let offset = self.len - buffer.len();
+
+        match buffer.decode() {
+            Ok((frame, remaining)) => {
+                self.buffer = Some(remaining);
+                Some(Ok((offset, frame)))
+            }
+            Err(error) => Some(Err(error)),
+        }
+    }
+}
+
 // This implements a codec for a frame that contains simple
 // values that don't vary based on the tag
 macro_rules! simple_frame_codec {
@@ -256,6 +294,8 @@ frames! {
     connection_close_tag => connection_close, handle_connection_close_frame, ConnectionClose['a];
     handshake_done_tag => handshake_done, handle_handshake_done_frame, HandshakeDone;
     datagram_tag => datagram, handle_datagram_frame, Datagram[Data];
+    immediate_ack_tag => immediate_ack, handle_immediate_ack_frame, ImmediateAck;
+    ack_frequency_tag => ack_frequency, handle_ack_frequency_frame, AckFrequency;
 }
 
 #[derive(Clone, Copy, Debug, Default)]
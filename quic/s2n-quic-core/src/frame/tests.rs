@@ -1,9 +1,15 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::frame::FrameRef;
+use crate::{
+    frame::{self, FrameRef},
+    varint::VarInt,
+};
 use bolero::check;
-use s2n_codec::{assert_codec_round_trip_bytes_mut, Encoder, EncoderLenEstimator, EncoderValue};
+use s2n_codec::{
+    assert_codec_round_trip_bytes_mut, DecoderBuffer, DecoderBufferMut, Encoder, EncoderBuffer,
+    EncoderLenEstimator, EncoderValue,
+};
 
 #[test]
 fn round_trip() {
@@ -20,3 +26,74 @@ fn round_trip() {
         }
     });
 }
+
+#[test]
+fn iter_test() {
+    check!().for_each(|input| {
+        let mut input = input.to_vec();
+        let len = input.len();
+        let mut previous = None;
+
+        for result in frame::Iter::new(DecoderBufferMut::new(&mut input)) {
+            let offset = if let Ok((offset, _frame)) = result {
+                offset
+            } else {
+                break;
+            };
+
+            // frames are returned in order and always consume at least the tag
+            assert!(offset < len);
+            assert!(previous.map_or(true, |previous| previous < offset));
+            previous = Some(offset);
+        }
+    });
+}
+
+#[test]
+fn iter_round_trip_test() {
+    let mut buffer = [0u8; 128];
+    let mut encoder = EncoderBuffer::new(&mut buffer);
+
+    let frames: [FrameRef; 5] = [
+        frame::Ping.into(),
+        frame::MaxData {
+            maximum_data: VarInt::from_u32(1 << 20),
+        }
+        .into(),
+        frame::Crypto {
+            offset: VarInt::from_u8(3),
+            data: DecoderBuffer::new(b"hello"),
+        }
+        .into(),
+        frame::Padding { length: 4 }.into(),
+        frame::Stream {
+            stream_id: VarInt::from_u8(4),
+            offset: VarInt::from_u16(1000),
+            is_last_frame: true,
+            is_fin: true,
+            data: DecoderBuffer::new(b"world"),
+        }
+        .into(),
+    ];
+
+    let mut expected = vec![];
+    for frame in &frames {
+        expected.push((encoder.len(), frame.tag(), frame.encoding_size()));
+        encoder.encode(frame);
+    }
+    let len = encoder.len();
+    let mut payload = buffer[..len].to_vec();
+    let original = payload.clone();
+
+    let actual: Vec<_> = frame::Iter::new(DecoderBufferMut::new(&mut payload))
+        .map(|result| {
+            let (offset, frame) = result.unwrap();
+            let mut encoded = vec![0; frame.encoding_size()];
+            EncoderBuffer::new(&mut encoded).encode(&frame);
+            assert_eq!(encoded, original[offset..offset + encoded.len()]);
+            (offset, frame.tag(), encoded.len())
+        })
+        .collect();
+
+    assert_eq!(actual, expected);
+}
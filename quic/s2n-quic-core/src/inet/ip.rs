@@ -17,6 +17,11 @@ use bolero_generator::*;
 /// is geared towards `no_std` environments and zerocopy decoding.
 ///
 /// The size is also consistent across target operating systems.
+///
+/// Comparisons and hashing are representation-sensitive: an IPv4 address isn't equal to its
+/// IPv4-mapped IPv6 form, even though both identify the same host. Addresses should be
+/// [unmapped](Self::unmap) before they are used as keys, or compared with
+/// [`eq_unmapped`](Self::eq_unmapped).
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(any(test, feature = "generator"), derive(TypeGenerator))]
 pub enum IpAddress {
@@ -35,6 +40,65 @@ impl IpAddress {
         }
     }
 
+    /// Returns `true` if the addresses are equal once they are both unmapped
+    #[inline]
+    pub fn eq_unmapped(&self, other: &Self) -> bool {
+        self.unmap() == other.unmap()
+    }
+
+    /// Returns `true` if this is the unspecified address for its family
+    #[inline]
+    pub const fn is_unspecified(&self) -> bool {
+        match self {
+            Self::Ipv4(addr) => addr.is_unspecified(),
+            Self::Ipv6(addr) => addr.is_unspecified(),
+        }
+    }
+
+    /// Returns `true` if this is a loopback address
+    ///
+    /// IPv4-mapped addresses are classified by the IPv4 address they map.
+    #[inline]
+    pub const fn is_loopback(&self) -> bool {
+        match self {
+            Self::Ipv4(addr) => addr.is_loopback(),
+            Self::Ipv6(addr) => addr.is_loopback(),
+        }
+    }
+
+    /// Returns `true` if this is an RFC 1918 private IPv4 address or a unique local IPv6 address
+    ///
+    /// IPv4-mapped addresses are classified by the IPv4 address they map.
+    #[inline]
+    pub const fn is_private(&self) -> bool {
+        match self {
+            Self::Ipv4(addr) => addr.is_private(),
+            Self::Ipv6(addr) => addr.is_private(),
+        }
+    }
+
+    /// Returns `true` if this is a link-local address
+    ///
+    /// IPv4-mapped addresses are classified by the IPv4 address they map.
+    #[inline]
+    pub const fn is_link_local(&self) -> bool {
+        match self {
+            Self::Ipv4(addr) => addr.is_link_local(),
+            Self::Ipv6(addr) => addr.is_link_local(),
+        }
+    }
+
+    /// Returns `true` if this is a multicast address
+    ///
+    /// IPv4-mapped addresses are classified by the IPv4 address they map.
+    #[inline]
+    pub const fn is_multicast(&self) -> bool {
+        match self {
+            Self::Ipv4(addr) => addr.is_multicast(),
+            Self::Ipv6(addr) => addr.is_multicast(),
+        }
+    }
+
     /// Converts the IP address into IPv6 if it is IPv4, otherwise the address is unchanged
     #[inline]
     #[must_use]
@@ -55,6 +119,15 @@ impl IpAddress {
     }
 }
 
+impl fmt::Display for IpAddress {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Ipv4(addr) => addr.fmt(fmt),
+            Self::Ipv6(addr) => addr.fmt(fmt),
+        }
+    }
+}
+
 impl From<IpV4Address> for IpAddress {
     fn from(ip: IpV4Address) -> Self {
         Self::Ipv4(ip)
@@ -109,6 +182,11 @@ impl<'a> From<&'a IpV6Address> for IpAddressRef<'a> {
 /// is geared towards `no_std` environments and zerocopy decoding.
 ///
 /// The size is also consistent across target operating systems.
+///
+/// Comparisons and hashing are representation-sensitive: dual-stack sockets report IPv4 peers
+/// with IPv4-mapped IPv6 addresses, so `1.2.3.4:443` and `[::ffff:1.2.3.4]:443` aren't equal and
+/// hash differently. Addresses should be [unmapped](Self::unmap) before they are used as keys,
+/// or compared with [`eq_unmapped`](Self::eq_unmapped).
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(any(test, feature = "generator"), derive(TypeGenerator))]
 #[cfg_attr(kani, derive(kani::Arbitrary))]
@@ -167,6 +245,48 @@ impl SocketAddress {
             Self::IpV6(addr) => addr.unmap(),
         }
     }
+
+    /// Returns `true` if the addresses are equal once they are both unmapped
+    #[inline]
+    pub fn eq_unmapped(&self, other: &Self) -> bool {
+        self.unmap() == other.unmap()
+    }
+
+    /// Returns `true` if the IP address is a loopback address
+    #[inline]
+    pub const fn is_loopback(&self) -> bool {
+        match self {
+            Self::IpV4(addr) => addr.ip().is_loopback(),
+            Self::IpV6(addr) => addr.ip().is_loopback(),
+        }
+    }
+
+    /// Returns `true` if the IP address is a private or unique local address
+    #[inline]
+    pub const fn is_private(&self) -> bool {
+        match self {
+            Self::IpV4(addr) => addr.ip().is_private(),
+            Self::IpV6(addr) => addr.ip().is_private(),
+        }
+    }
+
+    /// Returns `true` if the IP address is a link-local address
+    #[inline]
+    pub const fn is_link_local(&self) -> bool {
+        match self {
+            Self::IpV4(addr) => addr.ip().is_link_local(),
+            Self::IpV6(addr) => addr.ip().is_link_local(),
+        }
+    }
+
+    /// Returns `true` if the IP address is a multicast address
+    #[inline]
+    pub const fn is_multicast(&self) -> bool {
+        match self {
+            Self::IpV4(addr) => addr.ip().is_multicast(),
+            Self::IpV6(addr) => addr.ip().is_multicast(),
+        }
+    }
 }
 
 impl Default for SocketAddress {
@@ -412,6 +532,97 @@ mod tests {
         }
     }
 
+    #[test]
+    fn eq_unmapped_test() {
+        let ipv4: SocketAddress = SocketAddressV4::new([1, 2, 3, 4], 443).into();
+
+        let mapped: SocketAddress = "[::ffff:1.2.3.4]:443".parse().unwrap();
+        assert_ne!(ipv4, mapped);
+        assert!(ipv4.eq_unmapped(&mapped));
+        assert!(mapped.eq_unmapped(&ipv4));
+        assert_eq!(mapped.unmap(), ipv4);
+        assert!(ipv4.ip().eq_unmapped(&mapped.ip()));
+
+        let compatible: SocketAddress = "[::1.2.3.4]:443".parse().unwrap();
+        assert_ne!(ipv4, compatible);
+        assert!(ipv4.eq_unmapped(&compatible));
+        assert!(mapped.eq_unmapped(&compatible));
+        assert_eq!(compatible.unmap(), ipv4);
+
+        // the port is still compared
+        assert!(!ipv4.eq_unmapped(&"[::ffff:1.2.3.4]:80".parse().unwrap()));
+
+        // plain IPv6 addresses are unchanged
+        for plain in ["[2001:db8::1.2.3.4]:443", "[::1]:443", "[::]:443"] {
+            let plain: SocketAddress = plain.parse().unwrap();
+            assert_eq!(plain.unmap(), plain);
+            assert!(plain.eq_unmapped(&plain));
+            assert!(!plain.eq_unmapped(&ipv4));
+        }
+    }
+
+    #[test]
+    fn classification_test() {
+        let loopback: SocketAddress = "127.0.0.1:443".parse().unwrap();
+        assert!(loopback.is_loopback());
+        assert!(loopback.ip().is_loopback());
+        let mapped: SocketAddress = "[::ffff:127.0.0.1]:443".parse().unwrap();
+        assert!(mapped.is_loopback());
+        assert!("[::1]:443".parse::<SocketAddress>().unwrap().is_loopback());
+
+        let private: SocketAddress = "[::ffff:192.168.1.1]:443".parse().unwrap();
+        assert!(private.is_private());
+        assert!("[fd00::1]:443"
+            .parse::<SocketAddress>()
+            .unwrap()
+            .is_private());
+        assert!(!"8.8.8.8:443".parse::<SocketAddress>().unwrap().is_private());
+
+        assert!("[fe80::1]:443"
+            .parse::<SocketAddress>()
+            .unwrap()
+            .is_link_local());
+        assert!("169.254.0.1:443"
+            .parse::<SocketAddress>()
+            .unwrap()
+            .is_link_local());
+        assert!("[ff02::1]:443"
+            .parse::<SocketAddress>()
+            .unwrap()
+            .is_multicast());
+        assert!("239.0.0.1:443"
+            .parse::<SocketAddress>()
+            .unwrap()
+            .is_multicast());
+
+        assert!(IpAddress::from(IpV6Address::UNSPECIFIED).is_unspecified());
+        assert!(!IpAddress::from(IpV4Address::LOCALHOST).is_unspecified());
+    }
+
+    #[test]
+    fn event_unmap_test() {
+        use crate::event::{api, IntoEvent};
+
+        let tests: &[(&str, &str)] = &[
+            ("1.2.3.4:443", "1.2.3.4:443"),
+            ("[::ffff:1.2.3.4]:443", "1.2.3.4:443"),
+            ("[::1.2.3.4]:443", "1.2.3.4:443"),
+            ("[2001:db8::1]:443", "[2001:db8::1]:443"),
+            ("[::1]:443", "[::1]:443"),
+        ];
+
+        for (address, expected) in tests {
+            let address: SocketAddress = address.parse().unwrap();
+            let expected: SocketAddress = expected.parse().unwrap();
+            let event: api::SocketAddress = (&address).into_event();
+            let expected: api::SocketAddress = (&expected).into_event();
+            let unmapped = event.unmap();
+            // the address family is implied by the length of the address
+            assert_eq!(unmapped.ip(), expected.ip());
+            assert_eq!(unmapped.port(), expected.port());
+        }
+    }
+
     #[test]
     fn display_test() {
         for test in TESTS.iter() {
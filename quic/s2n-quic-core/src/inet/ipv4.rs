@@ -26,6 +26,47 @@ impl IpV4Address {
         octets: [0; IPV4_LEN],
     };
 
+    /// The IPv4 loopback address, `127.0.0.1`
+    pub const LOCALHOST: Self = Self {
+        octets: [127, 0, 0, 1],
+    };
+
+    /// Returns `true` if this is the unspecified address, `0.0.0.0`
+    #[inline]
+    pub const fn is_unspecified(&self) -> bool {
+        matches!(self.octets, [0, 0, 0, 0])
+    }
+
+    /// Returns `true` if this is a loopback address, in `127.0.0.0/8`
+    #[inline]
+    pub const fn is_loopback(&self) -> bool {
+        matches!(self.octets, [127, _, _, _])
+    }
+
+    /// Returns `true` if this is a private address, in `10.0.0.0/8`, `172.16.0.0/12` or
+    /// `192.168.0.0/16`
+    ///
+    /// Unlike [`Self::unicast_scope`], the shared address space `100.64.0.0/10` isn't included.
+    #[inline]
+    pub const fn is_private(&self) -> bool {
+        matches!(
+            self.octets,
+            [10, _, _, _] | [172, 16..=31, _, _] | [192, 168, _, _]
+        )
+    }
+
+    /// Returns `true` if this is a link-local address, in `169.254.0.0/16`
+    #[inline]
+    pub const fn is_link_local(&self) -> bool {
+        matches!(self.octets, [169, 254, _, _])
+    }
+
+    /// Returns `true` if this is a multicast address, in `224.0.0.0/4`
+    #[inline]
+    pub const fn is_multicast(&self) -> bool {
+        matches!(self.octets, [224..=239, _, _, _])
+    }
+
     /// Returns the [`ip::UnicastScope`] for the given address
     ///
     /// See the [IANA Registry](https://www.iana.org/assignments/iana-ipv4-special-registry/iana-ipv4-special-registry.xhtml)
@@ -753,6 +794,73 @@ mod tests {
     use bolero::{check, generator::*};
     use s2n_codec::{DecoderBuffer, DecoderBufferMut};
 
+    #[test]
+    fn classification_test() {
+        // (address, unspecified, loopback, private, link-local, multicast)
+        let tests = [
+            ([0, 0, 0, 0], true, false, false, false, false),
+            ([0, 0, 0, 1], false, false, false, false, false),
+            ([126, 255, 255, 255], false, false, false, false, false),
+            ([127, 0, 0, 0], false, true, false, false, false),
+            ([127, 0, 0, 1], false, true, false, false, false),
+            ([127, 255, 255, 255], false, true, false, false, false),
+            ([128, 0, 0, 0], false, false, false, false, false),
+            ([9, 255, 255, 255], false, false, false, false, false),
+            ([10, 0, 0, 0], false, false, true, false, false),
+            ([10, 255, 255, 255], false, false, true, false, false),
+            ([11, 0, 0, 0], false, false, false, false, false),
+            ([100, 64, 0, 0], false, false, false, false, false),
+            ([169, 253, 255, 255], false, false, false, false, false),
+            ([169, 254, 0, 0], false, false, false, true, false),
+            ([169, 254, 255, 255], false, false, false, true, false),
+            ([169, 255, 0, 0], false, false, false, false, false),
+            ([172, 15, 255, 255], false, false, false, false, false),
+            ([172, 16, 0, 0], false, false, true, false, false),
+            ([172, 31, 255, 255], false, false, true, false, false),
+            ([172, 32, 0, 0], false, false, false, false, false),
+            ([192, 167, 255, 255], false, false, false, false, false),
+            ([192, 168, 0, 0], false, false, true, false, false),
+            ([192, 168, 255, 255], false, false, true, false, false),
+            ([192, 169, 0, 0], false, false, false, false, false),
+            ([223, 255, 255, 255], false, false, false, false, false),
+            ([224, 0, 0, 0], false, false, false, false, true),
+            ([239, 255, 255, 255], false, false, false, false, true),
+            ([240, 0, 0, 0], false, false, false, false, false),
+            ([255, 255, 255, 255], false, false, false, false, false),
+        ];
+
+        for (octets, unspecified, loopback, private, link_local, multicast) in tests {
+            let ip = IpV4Address::from(octets);
+            assert_eq!(ip.is_unspecified(), unspecified, "{ip}");
+            assert_eq!(ip.is_loopback(), loopback, "{ip}");
+            assert_eq!(ip.is_private(), private, "{ip}");
+            assert_eq!(ip.is_link_local(), link_local, "{ip}");
+            assert_eq!(ip.is_multicast(), multicast, "{ip}");
+        }
+
+        assert!(IpV4Address::LOCALHOST.is_loopback());
+        assert!(IpV4Address::UNSPECIFIED.is_unspecified());
+
+        // the predicates can be used in const contexts
+        const IS_LOOPBACK: bool = IpV4Address::LOCALHOST.is_loopback();
+        assert!(IS_LOOPBACK);
+    }
+
+    /// Asserts the predicates match the standard library
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn classification_std_test() {
+        check!().with_type::<[u8; 4]>().cloned().for_each(|octets| {
+            let subject = IpV4Address::from(octets);
+            let expected = std::net::Ipv4Addr::from(octets);
+            assert_eq!(subject.is_unspecified(), expected.is_unspecified());
+            assert_eq!(subject.is_loopback(), expected.is_loopback());
+            assert_eq!(subject.is_private(), expected.is_private());
+            assert_eq!(subject.is_link_local(), expected.is_link_local());
+            assert_eq!(subject.is_multicast(), expected.is_multicast());
+        });
+    }
+
     /// Asserts the Scope returned matches a known implementation
     #[test]
     #[cfg_attr(kani, kani::proof, kani::unwind(5), kani::solver(kissat))]
@@ -24,6 +24,73 @@ impl IpV6Address {
         octets: [0; IPV6_LEN],
     };
 
+    /// The IPv6 loopback address, `::1`
+    pub const LOCALHOST: Self = Self {
+        octets: [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+    };
+
+    /// Returns the IPv4 address if this is an IPv4-mapped address, in `::ffff:0:0/96`
+    ///
+    /// Unlike [`Self::unmap`], IPv4-compatible and NAT64 addresses aren't converted.
+    #[inline]
+    pub const fn to_ipv4_mapped(&self) -> Option<IpV4Address> {
+        match self.octets {
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, a, b, c, d] => Some(IpV4Address {
+                octets: [a, b, c, d],
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if this is the unspecified address, `::`
+    #[inline]
+    pub const fn is_unspecified(&self) -> bool {
+        matches!(self.segments(), [0, 0, 0, 0, 0, 0, 0, 0])
+    }
+
+    /// Returns `true` if this is the loopback address, `::1`, or an IPv4-mapped loopback address
+    #[inline]
+    pub const fn is_loopback(&self) -> bool {
+        if let Some(ip) = self.to_ipv4_mapped() {
+            return ip.is_loopback();
+        }
+
+        matches!(self.segments(), [0, 0, 0, 0, 0, 0, 0, 1])
+    }
+
+    /// Returns `true` if this is a unique local address, in `fc00::/7`, or an IPv4-mapped private
+    /// address
+    #[inline]
+    pub const fn is_private(&self) -> bool {
+        if let Some(ip) = self.to_ipv4_mapped() {
+            return ip.is_private();
+        }
+
+        matches!(self.segments(), [0xfc00..=0xfdff, ..])
+    }
+
+    /// Returns `true` if this is a link-local unicast address, in `fe80::/10`, or an IPv4-mapped
+    /// link-local address
+    #[inline]
+    pub const fn is_link_local(&self) -> bool {
+        if let Some(ip) = self.to_ipv4_mapped() {
+            return ip.is_link_local();
+        }
+
+        matches!(self.segments(), [0xfe80..=0xfebf, ..])
+    }
+
+    /// Returns `true` if this is a multicast address, in `ff00::/8`, or an IPv4-mapped multicast
+    /// address
+    #[inline]
+    pub const fn is_multicast(&self) -> bool {
+        if let Some(ip) = self.to_ipv4_mapped() {
+            return ip.is_multicast();
+        }
+
+        matches!(self.segments(), [0xff00..=0xffff, ..])
+    }
+
     #[inline]
     pub const fn segments(&self) -> [u16; 8] {
         let octets = &self.octets;
@@ -237,34 +304,52 @@ impl fmt::Debug for IpV6Address {
 }
 
 impl fmt::Display for IpV6Address {
-    #[allow(clippy::many_single_char_names)]
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        match self.segments() {
-            [0, 0, 0, 0, 0, 0, 0, 0] => write!(fmt, "::"),
-            [0, 0, 0, 0, 0, 0, 0, 1] => write!(fmt, "::1"),
-            // Ipv4 Compatible address
-            [0, 0, 0, 0, 0, 0, g, h] => write!(
-                fmt,
-                "::{}.{}.{}.{}",
-                (g >> 8) as u8,
-                g as u8,
-                (h >> 8) as u8,
-                h as u8
-            ),
-            // Ipv4-Mapped address
-            [0, 0, 0, 0, 0, 0xffff, g, h] => write!(
-                fmt,
-                "::ffff:{}.{}.{}.{}",
-                (g >> 8) as u8,
-                g as u8,
-                (h >> 8) as u8,
-                h as u8
-            ),
-            // TODO better formatting
-            [a, b, c, d, e, f, g, h] => {
-                write!(fmt, "{a:x}:{b:x}:{c:x}:{d:x}:{e:x}:{f:x}:{g:x}:{h:x}")
+        let segments = self.segments();
+
+        // Ipv4-Mapped address
+        if let [0, 0, 0, 0, 0, 0xffff, g, h] = segments {
+            let [a, b] = g.to_be_bytes();
+            let [c, d] = h.to_be_bytes();
+            return write!(fmt, "::ffff:{a}.{b}.{c}.{d}");
+        }
+
+        // Shorten the longest run of zero groups, preferring the first run if several are equally
+        // long, as recommended by RFC 5952
+        let mut longest = 0..0;
+        let mut current = 0..0;
+        for (index, segment) in segments.iter().enumerate() {
+            if *segment == 0 {
+                if current.is_empty() {
+                    current = index..index;
+                }
+                current.end = index + 1;
+                if current.len() > longest.len() {
+                    longest = current.clone();
+                }
+            } else {
+                current = 0..0;
+            }
+        }
+
+        let write_groups = |fmt: &mut fmt::Formatter, groups: &[u16]| -> fmt::Result {
+            for (index, group) in groups.iter().enumerate() {
+                if index > 0 {
+                    fmt.write_str(":")?;
+                }
+                write!(fmt, "{group:x}")?;
             }
+            Ok(())
+        };
+
+        // a single zero group isn't shortened
+        if longest.len() < 2 {
+            return write_groups(fmt, &segments);
         }
+
+        write_groups(fmt, &segments[..longest.start])?;
+        fmt.write_str("::")?;
+        write_groups(fmt, &segments[longest.end..])
     }
 }
 
@@ -665,6 +750,125 @@ mod tests {
     use bolero::{check, generator::*};
     use s2n_codec::{DecoderBuffer, DecoderBufferMut};
 
+    #[test]
+    fn classification_test() {
+        // (address, unspecified, loopback, private, link-local, multicast)
+        let tests: &[(&str, bool, bool, bool, bool, bool)] = &[
+            ("::", true, false, false, false, false),
+            ("::1", false, true, false, false, false),
+            ("::2", false, false, false, false, false),
+            (
+                "fbff:ffff:ffff:ffff:ffff:ffff:ffff:ffff",
+                false,
+                false,
+                false,
+                false,
+                false,
+            ),
+            ("fc00::", false, false, true, false, false),
+            (
+                "fdff:ffff:ffff:ffff:ffff:ffff:ffff:ffff",
+                false,
+                false,
+                true,
+                false,
+                false,
+            ),
+            ("fe00::", false, false, false, false, false),
+            (
+                "fe7f:ffff:ffff:ffff:ffff:ffff:ffff:ffff",
+                false,
+                false,
+                false,
+                false,
+                false,
+            ),
+            ("fe80::", false, false, false, true, false),
+            (
+                "febf:ffff:ffff:ffff:ffff:ffff:ffff:ffff",
+                false,
+                false,
+                false,
+                true,
+                false,
+            ),
+            ("fec0::", false, false, false, false, false),
+            (
+                "feff:ffff:ffff:ffff:ffff:ffff:ffff:ffff",
+                false,
+                false,
+                false,
+                false,
+                false,
+            ),
+            ("ff00::", false, false, false, false, true),
+            ("ff02::1", false, false, false, false, true),
+            (
+                "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff",
+                false,
+                false,
+                false,
+                false,
+                true,
+            ),
+            ("2001:db8::1", false, false, false, false, false),
+            // IPv4-mapped addresses use the IPv4 classification
+            ("::ffff:0.0.0.0", false, false, false, false, false),
+            ("::ffff:127.0.0.1", false, true, false, false, false),
+            ("::ffff:10.0.0.1", false, false, true, false, false),
+            ("::ffff:172.31.255.255", false, false, true, false, false),
+            ("::ffff:169.254.0.1", false, false, false, true, false),
+            ("::ffff:224.0.0.1", false, false, false, false, true),
+            ("::ffff:8.8.8.8", false, false, false, false, false),
+            // IPv4-compatible and NAT64 addresses aren't mapped
+            ("::127.0.0.1", false, false, false, false, false),
+            ("64:ff9b::10.0.0.1", false, false, false, false, false),
+        ];
+
+        for (address, unspecified, loopback, private, link_local, multicast) in tests {
+            let ip: IpV6Address = address.parse().unwrap();
+            assert_eq!(ip.is_unspecified(), *unspecified, "{ip}");
+            assert_eq!(ip.is_loopback(), *loopback, "{ip}");
+            assert_eq!(ip.is_private(), *private, "{ip}");
+            assert_eq!(ip.is_link_local(), *link_local, "{ip}");
+            assert_eq!(ip.is_multicast(), *multicast, "{ip}");
+        }
+
+        assert!(IpV6Address::LOCALHOST.is_loopback());
+        assert_eq!(IpV6Address::LOCALHOST.to_string(), "::1");
+        assert!(IpV6Address::UNSPECIFIED.is_unspecified());
+
+        // the predicates can be used in const contexts
+        const IS_LOOPBACK: bool = IpV6Address::LOCALHOST.is_loopback();
+        assert!(IS_LOOPBACK);
+    }
+
+    /// Asserts the predicates match the standard library, where it has stable equivalents
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn classification_std_test() {
+        check!()
+            .with_type::<[u8; 16]>()
+            .cloned()
+            .for_each(|octets| {
+                let subject = IpV6Address::from(octets);
+                let expected = std::net::Ipv6Addr::from(octets);
+                assert_eq!(
+                    subject.to_ipv4_mapped().map(std::net::Ipv4Addr::from),
+                    expected.to_ipv4_mapped()
+                );
+
+                if let Some(ip) = expected.to_ipv4_mapped() {
+                    assert_eq!(subject.is_loopback(), ip.is_loopback());
+                    assert_eq!(subject.is_multicast(), ip.is_multicast());
+                } else {
+                    assert_eq!(subject.is_unspecified(), expected.is_unspecified());
+                    assert_eq!(subject.is_loopback(), expected.is_loopback());
+                    assert_eq!(subject.is_multicast(), expected.is_multicast());
+                }
+            });
+    }
+
     /// Asserts the UnicastScope returned matches a known implementation
     #[test]
     #[cfg_attr(kani, kani::proof, kani::unwind(17), kani::solver(kissat))]
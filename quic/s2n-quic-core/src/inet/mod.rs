@@ -11,6 +11,10 @@ pub mod ethernet;
 pub mod ip;
 pub mod ipv4;
 pub mod ipv6;
+pub mod net;
+mod parse;
+#[cfg(feature = "serde")]
+mod serde;
 pub mod udp;
 pub mod unspecified;
 
@@ -19,4 +23,6 @@ pub use ecn::*;
 pub use ip::*;
 pub use ipv4::{IpV4Address, SocketAddressV4};
 pub use ipv6::{IpV6Address, SocketAddressV6};
+pub use net::{IpNet, IpV4Net, IpV6Net, PrefixLenError};
+pub use parse::AddressParseError;
 pub use unspecified::*;
@@ -0,0 +1,488 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! IP address prefixes in CIDR notation, such as `10.0.0.0/8` or `2001:db8::/32`
+//!
+//! ```rust
+//! use s2n_quic_core::inet::{IpAddress, IpNet};
+//!
+//! let net: IpNet = "192.0.2.0/24".parse().unwrap();
+//! assert!(net.contains(&"192.0.2.1".parse::<IpAddress>().unwrap()));
+//! assert!(!net.contains(&"198.51.100.1".parse::<IpAddress>().unwrap()));
+//! assert_eq!(net.to_string(), "192.0.2.0/24");
+//! ```
+
+use crate::inet::{AddressParseError, IpAddress, IpV4Address, IpV6Address};
+use core::{fmt, str::FromStr};
+
+/// The error returned when a prefix length is longer than its address
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PrefixLenError(&'static str);
+
+impl fmt::Display for PrefixLenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PrefixLenError {}
+
+/// Clears the bits of `octets` after the first `len` bits
+#[inline]
+fn mask<const N: usize>(mut octets: [u8; N], len: u8) -> [u8; N] {
+    let len = len as usize;
+    for (index, octet) in octets.iter_mut().enumerate() {
+        let start = index * 8;
+        if len <= start {
+            *octet = 0;
+        } else if len < start + 8 {
+            *octet &= !(u8::MAX >> (len - start));
+        }
+    }
+    octets
+}
+
+/// Masks an address to its first `len` bits, such as for grouping addresses into buckets
+///
+/// Lengths longer than the address keep the entire address. Like the other address types, the
+/// address is masked in the representation it's given, so IPv4-mapped IPv6 addresses should be
+/// [unmapped](IpAddress::unmap) first if they should be grouped with IPv4 addresses.
+///
+/// ```rust
+/// use s2n_quic_core::inet::{net::truncate, IpAddress};
+///
+/// let address: IpAddress = "192.0.2.123".parse().unwrap();
+/// assert_eq!(truncate(address, 24), "192.0.2.0".parse::<IpAddress>().unwrap());
+/// ```
+#[inline]
+#[must_use]
+pub fn truncate<A: Into<IpAddress>>(address: A, len: u8) -> IpAddress {
+    match address.into() {
+        IpAddress::Ipv4(address) => {
+            let octets: [u8; 4] = address.into();
+            IpV4Address::from(mask(octets, len)).into()
+        }
+        IpAddress::Ipv6(address) => {
+            let octets: [u8; 16] = address.into();
+            IpV6Address::from(mask(octets, len)).into()
+        }
+    }
+}
+
+macro_rules! impl_net {
+    ($name:ident, $address:ident, $max_len:expr, $error:literal) => {
+        impl $name {
+            /// The length of a prefix which matches a single address
+            pub const MAX_LEN: u8 = $max_len;
+
+            /// Creates a prefix matching the first `len` bits of `address`
+            ///
+            /// The bits of the address after the prefix are cleared. Returns an error if `len`
+            /// is longer than the address.
+            #[inline]
+            pub fn new<A: Into<$address>>(address: A, len: u8) -> Result<Self, PrefixLenError> {
+                if len > Self::MAX_LEN {
+                    return Err(PrefixLenError($error));
+                }
+
+                let address: $address = address.into();
+                let octets: [u8; $max_len / 8] = address.into();
+                let address = $address::from(mask(octets, len));
+                Ok(Self { address, len })
+            }
+
+            /// Returns the first address of the prefix
+            #[inline]
+            pub fn address(&self) -> $address {
+                self.address
+            }
+
+            /// Returns the number of leading bits matched by the prefix
+            #[inline]
+            #[allow(clippy::len_without_is_empty)]
+            pub fn len(&self) -> u8 {
+                self.len
+            }
+
+            /// Returns `true` if the first `len` bits of `address` match the prefix
+            #[inline]
+            pub fn contains(&self, address: &$address) -> bool {
+                let octets: [u8; $max_len / 8] = (*address).into();
+                $address::from(mask(octets, self.len)) == self.address
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}/{}", self.address, self.len)
+            }
+        }
+    };
+}
+
+/// An IPv4 address prefix, such as `10.0.0.0/8`
+///
+/// The address of the prefix is always masked to its length, so prefixes which match the same
+/// addresses are equal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IpV4Net {
+    address: IpV4Address,
+    len: u8,
+}
+
+impl_net!(
+    IpV4Net,
+    IpV4Address,
+    32,
+    "IPv4 prefixes can't be longer than 32 bits"
+);
+
+/// An IPv6 address prefix, such as `2001:db8::/32`
+///
+/// The address of the prefix is always masked to its length, so prefixes which match the same
+/// addresses are equal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IpV6Net {
+    address: IpV6Address,
+    len: u8,
+}
+
+impl_net!(
+    IpV6Net,
+    IpV6Address,
+    128,
+    "IPv6 prefixes can't be longer than 128 bits"
+);
+
+/// An address prefix, either IPv4 or IPv6
+///
+/// Like [`IpAddress`], comparisons are representation-sensitive: an IPv4 prefix never contains
+/// an IPv6 address, including IPv4-mapped addresses. Addresses should be
+/// [unmapped](IpAddress::unmap) before they are checked against IPv4 prefixes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IpNet {
+    Ipv4(IpV4Net),
+    Ipv6(IpV6Net),
+}
+
+impl IpNet {
+    /// Creates a prefix matching the first `len` bits of `address`
+    ///
+    /// The bits of the address after the prefix are cleared. Returns an error if `len` is longer
+    /// than the address.
+    #[inline]
+    pub fn new<A: Into<IpAddress>>(address: A, len: u8) -> Result<Self, PrefixLenError> {
+        match address.into() {
+            IpAddress::Ipv4(address) => IpV4Net::new(address, len).map(Self::Ipv4),
+            IpAddress::Ipv6(address) => IpV6Net::new(address, len).map(Self::Ipv6),
+        }
+    }
+
+    /// Returns the first address of the prefix
+    #[inline]
+    pub fn address(&self) -> IpAddress {
+        match self {
+            Self::Ipv4(net) => net.address().into(),
+            Self::Ipv6(net) => net.address().into(),
+        }
+    }
+
+    /// Returns the number of leading bits matched by the prefix
+    #[inline]
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u8 {
+        match self {
+            Self::Ipv4(net) => net.len(),
+            Self::Ipv6(net) => net.len(),
+        }
+    }
+
+    /// Returns `true` if the address is in the same family and its first `len` bits match the
+    /// prefix
+    #[inline]
+    pub fn contains(&self, address: &IpAddress) -> bool {
+        match (self, address) {
+            (Self::Ipv4(net), IpAddress::Ipv4(address)) => net.contains(address),
+            (Self::Ipv6(net), IpAddress::Ipv6(address)) => net.contains(address),
+            _ => false,
+        }
+    }
+}
+
+impl From<IpV4Net> for IpNet {
+    #[inline]
+    fn from(net: IpV4Net) -> Self {
+        Self::Ipv4(net)
+    }
+}
+
+impl From<IpV6Net> for IpNet {
+    #[inline]
+    fn from(net: IpV6Net) -> Self {
+        Self::Ipv6(net)
+    }
+}
+
+impl fmt::Display for IpNet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Ipv4(net) => net.fmt(f),
+            Self::Ipv6(net) => net.fmt(f),
+        }
+    }
+}
+
+/// Splits a prefix into its address and decimal length
+///
+/// The length must be written without leading zeros, like the octets of IPv4 addresses.
+fn split(s: &str) -> Result<(&str, u8), AddressParseError> {
+    let (address, len) = s.split_once('/').ok_or(AddressParseError::InvalidIpNet)?;
+
+    let is_valid = match len.as_bytes() {
+        [] => false,
+        [_] => true,
+        [b'0', ..] => false,
+        _ => len.len() <= 3,
+    };
+    if !is_valid || !len.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(AddressParseError::InvalidPrefixLen);
+    }
+    let len = len
+        .parse()
+        .map_err(|_| AddressParseError::InvalidPrefixLen)?;
+
+    Ok((address, len))
+}
+
+/// Maps address errors to prefix errors, keeping the errors which explain themselves
+fn address_error(error: AddressParseError) -> AddressParseError {
+    match error {
+        AddressParseError::UnsupportedZoneId => error,
+        _ => AddressParseError::InvalidIpNet,
+    }
+}
+
+impl FromStr for IpV4Net {
+    type Err = AddressParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, len) = split(s)?;
+        let address = address.parse::<IpV4Address>().map_err(address_error)?;
+        Self::new(address, len).map_err(|_| AddressParseError::InvalidPrefixLen)
+    }
+}
+
+impl FromStr for IpV6Net {
+    type Err = AddressParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, len) = split(s)?;
+        let address = address.parse::<IpV6Address>().map_err(address_error)?;
+        Self::new(address, len).map_err(|_| AddressParseError::InvalidPrefixLen)
+    }
+}
+
+impl FromStr for IpNet {
+    type Err = AddressParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, len) = split(s)?;
+        let address = address.parse::<IpAddress>().map_err(address_error)?;
+        Self::new(address, len).map_err(|_| AddressParseError::InvalidPrefixLen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bolero::check;
+    use std::collections::BTreeSet;
+
+    fn net(s: &str) -> IpNet {
+        s.parse().unwrap()
+    }
+
+    fn ip(s: &str) -> IpAddress {
+        s.parse().unwrap()
+    }
+
+    /// A reference implementation of `contains` using integer shifts
+    fn reference_contains(net: &IpNet, address: &IpAddress) -> bool {
+        match (net.address(), address) {
+            (IpAddress::Ipv4(prefix), IpAddress::Ipv4(address)) => {
+                let prefix = u32::from_be_bytes(prefix.into());
+                let address = u32::from_be_bytes((*address).into());
+                net.len() == 0 || (prefix ^ address) >> (32 - net.len() as u32) == 0
+            }
+            (IpAddress::Ipv6(prefix), IpAddress::Ipv6(address)) => {
+                let prefix = u128::from_be_bytes(prefix.into());
+                let address = u128::from_be_bytes((*address).into());
+                net.len() == 0 || (prefix ^ address) >> (128 - net.len() as u32) == 0
+            }
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn parse_test() {
+        let tests = [
+            ("10.0.0.0/8", "10.0.0.0/8"),
+            ("192.0.2.1/32", "192.0.2.1/32"),
+            ("0.0.0.0/0", "0.0.0.0/0"),
+            // host bits are cleared
+            ("192.0.2.123/24", "192.0.2.0/24"),
+            ("2001:db8::/32", "2001:db8::/32"),
+            ("2001:db8:1:2::1/48", "2001:db8:1::/48"),
+            ("::/0", "::/0"),
+            ("::ffff:192.0.2.1/128", "::ffff:192.0.2.1/128"),
+        ];
+
+        for (input, expected) in tests {
+            assert_eq!(net(input).to_string(), expected, "{input}");
+            assert_eq!(net(expected).to_string(), expected);
+        }
+
+        for input in [
+            "10.0.0.0",
+            "10.0.0.0/",
+            "10.0.0.0/33",
+            "10.0.0.0/08",
+            "10.0.0.0/+8",
+            "10.0.0.0/8/8",
+            "2001:db8::/129",
+            "2001:db8::/1000",
+            "10.0.0/8",
+            "/8",
+        ] {
+            assert!(input.parse::<IpNet>().is_err(), "{input}");
+        }
+
+        assert_eq!(
+            "10.0.0.0/33".parse::<IpNet>(),
+            Err(AddressParseError::InvalidPrefixLen)
+        );
+        assert_eq!(
+            "10.0.0/8".parse::<IpNet>(),
+            Err(AddressParseError::InvalidIpNet)
+        );
+        assert_eq!(
+            "fe80::%1/64".parse::<IpNet>(),
+            Err(AddressParseError::UnsupportedZoneId)
+        );
+
+        // the family specific types only parse their own family
+        assert!("10.0.0.0/8".parse::<IpV4Net>().is_ok());
+        assert!("10.0.0.0/8".parse::<IpV6Net>().is_err());
+        assert!("2001:db8::/32".parse::<IpV6Net>().is_ok());
+        assert!("2001:db8::/32".parse::<IpV4Net>().is_err());
+    }
+
+    #[test]
+    fn new_test() {
+        assert!(IpV4Net::new([10, 0, 0, 0], 32).is_ok());
+        assert!(IpV4Net::new([10, 0, 0, 0], 33).is_err());
+        assert!(IpV6Net::new([0u8; 16], 128).is_ok());
+        assert!(IpV6Net::new([0u8; 16], 129).is_err());
+        assert!(IpNet::new(ip("10.0.0.0"), 33).is_err());
+
+        let net = IpNet::new(ip("10.1.2.3"), 8).unwrap();
+        assert_eq!(net.address(), ip("10.0.0.0"));
+        assert_eq!(net.len(), 8);
+        assert_eq!(net, self::net("10.0.0.0/8"));
+    }
+
+    #[test]
+    fn contains_test() {
+        let v4 = net("192.0.2.0/24");
+        assert!(v4.contains(&ip("192.0.2.0")));
+        assert!(v4.contains(&ip("192.0.2.255")));
+        assert!(!v4.contains(&ip("192.0.3.0")));
+        // prefixes are representation-sensitive
+        assert!(!v4.contains(&ip("::ffff:192.0.2.1")));
+        assert!(v4.contains(&ip("::ffff:192.0.2.1").unmap()));
+
+        let v6 = net("2001:db8::/32");
+        assert!(v6.contains(&ip("2001:db8:ffff::1")));
+        assert!(!v6.contains(&ip("2001:db9::1")));
+        assert!(!v6.contains(&ip("32.1.13.184")));
+
+        assert!(net("0.0.0.0/0").contains(&ip("255.255.255.255")));
+        assert!(net("::/0").contains(&ip("ffff::")));
+        assert!(!net("::/0").contains(&ip("0.0.0.0")));
+    }
+
+    #[test]
+    fn truncate_test() {
+        assert_eq!(truncate(ip("192.0.2.123"), 24), ip("192.0.2.0"));
+        assert_eq!(truncate(ip("192.0.2.123"), 20), ip("192.0.0.0"));
+        assert_eq!(truncate(ip("192.0.2.123"), 0), ip("0.0.0.0"));
+        // longer lengths keep the entire address
+        assert_eq!(truncate(ip("192.0.2.123"), 64), ip("192.0.2.123"));
+        assert_eq!(truncate(ip("2001:db8:1:2::1"), 56), ip("2001:db8:1::"));
+        assert_eq!(truncate(ip("2001:db8:1:2::1"), 255), ip("2001:db8:1:2::1"));
+    }
+
+    #[test]
+    fn ord_test() {
+        let nets: BTreeSet<IpNet> = [
+            "2001:db8::/32",
+            "10.0.0.0/16",
+            "10.0.0.0/8",
+            "192.0.2.0/24",
+            "10.0.0.0/8",
+        ]
+        .into_iter()
+        .map(net)
+        .collect();
+
+        let nets: Vec<_> = nets.iter().map(|net| net.to_string()).collect();
+        assert_eq!(
+            nets,
+            ["10.0.0.0/8", "10.0.0.0/16", "192.0.2.0/24", "2001:db8::/32"]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn ipv4_contains_test() {
+        check!()
+            .with_type::<([u8; 4], [u8; 4], u8)>()
+            .cloned()
+            .for_each(|(prefix, address, len)| {
+                let len = len % (IpV4Net::MAX_LEN + 1);
+                let net = IpNet::new(IpV4Address::from(prefix), len).unwrap();
+                let address = IpV4Address::from(address).into();
+                assert_eq!(net.contains(&address), reference_contains(&net, &address));
+                assert_eq!(
+                    net.contains(&address),
+                    truncate(address, len) == net.address()
+                );
+                assert!(net.contains(&net.address()));
+                assert_eq!(net.to_string().parse::<IpNet>(), Ok(net));
+            });
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn ipv6_contains_test() {
+        check!()
+            .with_type::<([u8; 16], [u8; 16], u8)>()
+            .cloned()
+            .for_each(|(prefix, mut address, len)| {
+                let len = len % (IpV6Net::MAX_LEN + 1);
+                // share the leading bytes of the prefix so matches are also covered
+                let shared = (len as usize / 8).min(address.len());
+                address[..shared].copy_from_slice(&prefix[..shared]);
+
+                let net = IpNet::new(IpV6Address::from(prefix), len).unwrap();
+                let address = IpV6Address::from(address).into();
+                assert_eq!(net.contains(&address), reference_contains(&net, &address));
+                assert_eq!(
+                    net.contains(&address),
+                    truncate(address, len) == net.address()
+                );
+                assert_eq!(net.to_string().parse::<IpNet>(), Ok(net));
+            });
+    }
+}
@@ -0,0 +1,447 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parses addresses from their standard textual forms without depending on `std::net`
+
+use crate::inet::{
+    IpAddress, IpV4Address, IpV6Address, SocketAddress, SocketAddressV4, SocketAddressV6,
+};
+use core::{fmt, str::FromStr};
+
+/// The error returned when an address can't be parsed from a string
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AddressParseError {
+    /// The string isn't an IPv4 address in dotted decimal notation
+    InvalidIpV4Address,
+    /// The string isn't an IPv6 address
+    InvalidIpV6Address,
+    /// The string is neither an IPv4 nor an IPv6 address
+    InvalidIpAddress,
+    /// The string isn't an IPv4 address followed by a port
+    InvalidSocketAddressV4,
+    /// The string isn't a bracketed IPv6 address followed by a port
+    InvalidSocketAddressV6,
+    /// The string is neither an IPv4 nor an IPv6 socket address
+    InvalidSocketAddress,
+    /// The port isn't a decimal number between 0 and 65535
+    InvalidPort,
+    /// The IPv6 address has a zone ID
+    ///
+    /// Zone IDs can't be represented by the address types, so they are refused rather than
+    /// silently discarded.
+    UnsupportedZoneId,
+    /// The string isn't an address followed by a `/` and a prefix length
+    InvalidIpNet,
+    /// The prefix length isn't a decimal number at most as long as the address
+    InvalidPrefixLen,
+}
+
+impl fmt::Display for AddressParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match self {
+            Self::InvalidIpV4Address => "invalid IPv4 address syntax",
+            Self::InvalidIpV6Address => "invalid IPv6 address syntax",
+            Self::InvalidIpAddress => "invalid IP address syntax",
+            Self::InvalidSocketAddressV4 => "invalid IPv4 socket address syntax",
+            Self::InvalidSocketAddressV6 => "invalid IPv6 socket address syntax",
+            Self::InvalidSocketAddress => "invalid socket address syntax",
+            Self::InvalidPort => "invalid port",
+            Self::UnsupportedZoneId => "IPv6 zone IDs are not supported",
+            Self::InvalidIpNet => "invalid IP prefix syntax",
+            Self::InvalidPrefixLen => "invalid prefix length",
+        };
+        f.write_str(description)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AddressParseError {}
+
+/// Parses an IPv4 address in dotted decimal notation
+///
+/// Each octet must be written without leading zeros, which some parsers interpret as octal.
+fn ipv4(s: &str) -> Option<[u8; 4]> {
+    let mut octets = [0; 4];
+    let mut parts = s.split('.');
+
+    for octet in octets.iter_mut() {
+        let part = parts.next()?.as_bytes();
+
+        let is_valid = match part {
+            [] => false,
+            [_] => true,
+            [b'0', ..] => false,
+            _ => part.len() <= 3,
+        };
+        if !is_valid || !part.iter().all(u8::is_ascii_digit) {
+            return None;
+        }
+
+        let value = part
+            .iter()
+            .fold(0u16, |value, digit| value * 10 + (digit - b'0') as u16);
+        *octet = value.try_into().ok()?;
+    }
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some(octets)
+}
+
+/// Parses a run of colon separated groups into `groups`, returning the number of groups
+///
+/// If `allow_ipv4` is set, the last group may be an IPv4 address, which fills two groups.
+fn ipv6_groups(s: &str, groups: &mut [u16], allow_ipv4: bool) -> Option<usize> {
+    if s.is_empty() {
+        return Some(0);
+    }
+
+    let mut len = 0;
+    let mut parts = s.split(':').peekable();
+
+    while let Some(part) = parts.next() {
+        if allow_ipv4 && parts.peek().is_none() && part.contains('.') {
+            let [a, b, c, d] = ipv4(part)?;
+            let slots = groups.get_mut(len..len + 2)?;
+            slots[0] = u16::from_be_bytes([a, b]);
+            slots[1] = u16::from_be_bytes([c, d]);
+            len += 2;
+            continue;
+        }
+
+        if part.is_empty() || part.len() > 4 {
+            return None;
+        }
+
+        *groups.get_mut(len)? = u16::from_str_radix(part, 16).ok()?;
+        len += 1;
+    }
+
+    Some(len)
+}
+
+/// Parses an IPv6 address, including the compressed and mixed IPv4 forms
+fn ipv6(s: &str) -> Result<[u16; 8], AddressParseError> {
+    if s.contains('%') {
+        return Err(AddressParseError::UnsupportedZoneId);
+    }
+
+    let invalid = AddressParseError::InvalidIpV6Address;
+    // reject signs, which `from_str_radix` would otherwise accept
+    if !s
+        .bytes()
+        .all(|byte| byte.is_ascii_hexdigit() || byte == b':' || byte == b'.')
+    {
+        return Err(invalid);
+    }
+
+    let mut segments = [0; 8];
+
+    match s.find("::") {
+        Some(index) => {
+            let (head, tail) = (&s[..index], &s[index + 2..]);
+            // only a single run of groups may be elided
+            if tail.contains("::") {
+                return Err(invalid);
+            }
+
+            let mut tail_groups = [0; 8];
+            let head_len = ipv6_groups(head, &mut segments, false).ok_or(invalid)?;
+            let tail_len = ipv6_groups(tail, &mut tail_groups, true).ok_or(invalid)?;
+
+            // the elided run must contain at least one group
+            if head_len + tail_len > 7 {
+                return Err(invalid);
+            }
+
+            segments[8 - tail_len..].copy_from_slice(&tail_groups[..tail_len]);
+        }
+        None => {
+            if ipv6_groups(s, &mut segments, true) != Some(8) {
+                return Err(invalid);
+            }
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Parses a decimal port number
+fn port(s: &str) -> Result<u16, AddressParseError> {
+    let invalid = AddressParseError::InvalidPort;
+
+    if s.is_empty() || s.len() > 5 || !s.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(invalid);
+    }
+
+    s.parse().map_err(|_| invalid)
+}
+
+impl FromStr for IpV4Address {
+    type Err = AddressParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ipv4(s)
+            .map(Self::from)
+            .ok_or(AddressParseError::InvalidIpV4Address)
+    }
+}
+
+impl FromStr for IpV6Address {
+    type Err = AddressParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ipv6(s).map(Self::from)
+    }
+}
+
+impl FromStr for IpAddress {
+    type Err = AddressParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(ip) = s.parse::<IpV4Address>() {
+            return Ok(ip.into());
+        }
+
+        match s.parse::<IpV6Address>() {
+            Ok(ip) => Ok(ip.into()),
+            Err(AddressParseError::UnsupportedZoneId) => Err(AddressParseError::UnsupportedZoneId),
+            Err(_) => Err(AddressParseError::InvalidIpAddress),
+        }
+    }
+}
+
+impl FromStr for SocketAddressV4 {
+    type Err = AddressParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (ip, port_str) = s
+            .rsplit_once(':')
+            .ok_or(AddressParseError::InvalidSocketAddressV4)?;
+        let ip = ip
+            .parse::<IpV4Address>()
+            .map_err(|_| AddressParseError::InvalidSocketAddressV4)?;
+
+        Ok(Self::new(ip, port(port_str)?))
+    }
+}
+
+impl FromStr for SocketAddressV6 {
+    type Err = AddressParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = AddressParseError::InvalidSocketAddressV6;
+
+        let s = s.strip_prefix('[').ok_or(invalid)?;
+        let (ip, port_str) = s.rsplit_once("]:").ok_or(invalid)?;
+        let ip = match ip.parse::<IpV6Address>() {
+            Ok(ip) => ip,
+            Err(AddressParseError::UnsupportedZoneId) => {
+                return Err(AddressParseError::UnsupportedZoneId)
+            }
+            Err(_) => return Err(invalid),
+        };
+
+        Ok(Self::new(ip, port(port_str)?))
+    }
+}
+
+impl FromStr for SocketAddress {
+    type Err = AddressParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // IPv6 socket addresses are always bracketed
+        let address = if s.starts_with('[') {
+            s.parse::<SocketAddressV6>().map(Self::from)
+        } else {
+            s.parse::<SocketAddressV4>().map(Self::from)
+        };
+
+        address.map_err(|error| match error {
+            AddressParseError::InvalidPort | AddressParseError::UnsupportedZoneId => error,
+            _ => AddressParseError::InvalidSocketAddress,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bolero::check;
+    use std::net;
+
+    #[test]
+    fn ipv4_test() {
+        for (input, expected) in [
+            ("0.0.0.0", Some([0, 0, 0, 0])),
+            ("127.0.0.1", Some([127, 0, 0, 1])),
+            ("255.255.255.255", Some([255, 255, 255, 255])),
+            ("256.0.0.1", None),
+            ("1.2.3", None),
+            ("1.2.3.4.5", None),
+            ("01.2.3.4", None),
+            ("1..3.4", None),
+            ("+1.2.3.4", None),
+            ("1.2.3.4 ", None),
+            ("", None),
+        ] {
+            let actual = input.parse::<IpV4Address>().ok().map(<[u8; 4]>::from);
+            assert_eq!(actual, expected, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn ipv6_test() {
+        for (input, expected) in [
+            ("::", Some([0, 0, 0, 0, 0, 0, 0, 0])),
+            ("::1", Some([0, 0, 0, 0, 0, 0, 0, 1])),
+            ("1::", Some([1, 0, 0, 0, 0, 0, 0, 0])),
+            ("1:2:3:4:5:6:7:8", Some([1, 2, 3, 4, 5, 6, 7, 8])),
+            ("1:2:3:4:5:6::8", Some([1, 2, 3, 4, 5, 6, 0, 8])),
+            ("2001:DB8::1", Some([0x2001, 0xdb8, 0, 0, 0, 0, 0, 1])),
+            (
+                "::ffff:192.0.2.1",
+                Some([0, 0, 0, 0, 0, 0xffff, 0xc000, 0x201]),
+            ),
+            (
+                "1:2:3:4:5:6:1.2.3.4",
+                Some([1, 2, 3, 4, 5, 6, 0x102, 0x304]),
+            ),
+            ("1:2:3:4:5:6:7:8:9", None),
+            ("1:2:3:4:5:6:7::8", None),
+            ("1::2::3", None),
+            (":::", None),
+            (":1::", None),
+            ("1:", None),
+            ("12345::", None),
+            ("::1.2.3.4:5", None),
+            ("1.2.3.4::", None),
+            ("::+1", None),
+            ("", None),
+        ] {
+            let actual = input.parse::<IpV6Address>().ok().map(|ip| ip.segments());
+            assert_eq!(actual, expected, "{input:?}");
+        }
+
+        assert_eq!(
+            "fe80::1%eth0".parse::<IpV6Address>(),
+            Err(AddressParseError::UnsupportedZoneId)
+        );
+    }
+
+    #[test]
+    fn socket_address_test() {
+        for input in [
+            "127.0.0.1:80",
+            "0.0.0.0:0",
+            "255.255.255.255:65535",
+            "[::]:443",
+            "[::1]:123",
+            "[::ffff:192.0.2.1]:4433",
+            "[2001:db8:85a3::8a2e:370:7334]:9000",
+        ] {
+            let address: SocketAddress = input.parse().unwrap();
+            assert_eq!(address.to_string(), input);
+            let expected: net::SocketAddr = input.parse().unwrap();
+            assert_eq!(net::SocketAddr::from(address), expected);
+        }
+
+        for (input, expected) in [
+            ("127.0.0.1", AddressParseError::InvalidSocketAddress),
+            ("127.0.0.1:", AddressParseError::InvalidPort),
+            ("127.0.0.1:65536", AddressParseError::InvalidPort),
+            ("127.0.0.1:+80", AddressParseError::InvalidPort),
+            ("::1:80", AddressParseError::InvalidSocketAddress),
+            ("[::1]", AddressParseError::InvalidSocketAddress),
+            ("[::1]80", AddressParseError::InvalidSocketAddress),
+            ("[1.2.3.4]:80", AddressParseError::InvalidSocketAddress),
+            ("[fe80::1%2]:80", AddressParseError::UnsupportedZoneId),
+        ] {
+            assert_eq!(input.parse::<SocketAddress>(), Err(expected), "{input:?}");
+        }
+
+        assert!("[::1]:80".parse::<SocketAddressV4>().is_err());
+        assert!("127.0.0.1:80".parse::<SocketAddressV6>().is_err());
+    }
+
+    #[test]
+    fn ip_address_test() {
+        assert_eq!(
+            "10.0.0.1".parse::<IpAddress>(),
+            Ok(IpV4Address::from([10, 0, 0, 1]).into())
+        );
+        assert_eq!(
+            "::ffff:10.0.0.1".parse::<IpAddress>(),
+            Ok(IpV4Address::from([10, 0, 0, 1]).to_ipv6_mapped().into())
+        );
+        assert_eq!(
+            "10.0.0".parse::<IpAddress>(),
+            Err(AddressParseError::InvalidIpAddress)
+        );
+    }
+
+    /// Asserts addresses are displayed the same way as the standard library and parse back to
+    /// the same value
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn ipv6_round_trip_test() {
+        check!()
+            .with_type::<([u16; 8], u8)>()
+            .cloned()
+            .for_each(|(mut segments, zeros)| {
+                // zero out some of the segments so the compressed forms are covered
+                for (index, segment) in segments.iter_mut().enumerate() {
+                    if zeros & (1 << index) != 0 {
+                        *segment = 0;
+                    }
+                }
+
+                let ip = IpV6Address::from(segments);
+                let display = ip.to_string();
+                assert_eq!(display, net::Ipv6Addr::from(segments).to_string());
+                assert_eq!(display.parse::<IpV6Address>(), Ok(ip));
+
+                // the standard library's forms are also accepted
+                let expanded = format!(
+                    "{:x}:{:x}:{:x}:{:x}:{:x}:{:x}:{:x}:{:x}",
+                    segments[0],
+                    segments[1],
+                    segments[2],
+                    segments[3],
+                    segments[4],
+                    segments[5],
+                    segments[6],
+                    segments[7]
+                );
+                assert_eq!(expanded.parse::<IpV6Address>(), Ok(ip));
+
+                let address = SocketAddressV6::new(ip, 443);
+                assert_eq!(address.to_string().parse::<SocketAddressV6>(), Ok(address));
+            });
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn ipv4_round_trip_test() {
+        check!()
+            .with_type::<([u8; 4], u16)>()
+            .cloned()
+            .for_each(|(octets, port)| {
+                let ip = IpV4Address::from(octets);
+                let display = ip.to_string();
+                assert_eq!(display, net::Ipv4Addr::from(octets).to_string());
+                assert_eq!(display.parse::<IpV4Address>(), Ok(ip));
+
+                let address = SocketAddressV4::new(ip, port);
+                assert_eq!(address.to_string().parse::<SocketAddressV4>(), Ok(address));
+
+                // mapped addresses keep their IPv4 form
+                let mapped = ip.to_ipv6_mapped();
+                assert_eq!(mapped.to_string(), format!("::ffff:{display}"));
+                assert_eq!(mapped.to_string().parse::<IpV6Address>(), Ok(mapped));
+            });
+    }
+}
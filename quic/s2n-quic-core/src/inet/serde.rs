@@ -0,0 +1,103 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Serializes addresses and prefixes in their standard textual forms
+
+use crate::inet::{
+    IpAddress, IpNet, IpV4Address, IpV4Net, IpV6Address, IpV6Net, SocketAddress, SocketAddressV4,
+    SocketAddressV6,
+};
+use core::{fmt, marker::PhantomData, str::FromStr};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Deserializes a value from a string with its `FromStr` implementation
+struct Visitor<T>(&'static str, PhantomData<T>);
+
+impl<'de, T> de::Visitor<'de> for Visitor<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.0)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        value.parse().map_err(E::custom)
+    }
+}
+
+macro_rules! impl_serde {
+    ($ty:ty, $expecting:literal) => {
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer.deserialize_str(Visitor($expecting, PhantomData))
+            }
+        }
+    };
+}
+
+impl_serde!(IpV4Address, "an IPv4 address");
+impl_serde!(IpV6Address, "an IPv6 address");
+impl_serde!(IpAddress, "an IP address");
+impl_serde!(SocketAddressV4, "an IPv4 socket address");
+impl_serde!(SocketAddressV6, "an IPv6 socket address");
+impl_serde!(SocketAddress, "a socket address");
+impl_serde!(IpV4Net, "an IPv4 prefix in CIDR notation");
+impl_serde!(IpV6Net, "an IPv6 prefix in CIDR notation");
+impl_serde!(IpNet, "an IP prefix in CIDR notation");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{from_str, json, to_value};
+
+    fn round_trip<T>(value: T, expected: &str)
+    where
+        T: Serialize + for<'de> Deserialize<'de> + PartialEq + fmt::Debug,
+    {
+        assert_eq!(to_value(&value).unwrap(), json!(expected));
+        assert_eq!(from_str::<T>(&format!("{expected:?}")).unwrap(), value);
+    }
+
+    #[test]
+    fn round_trip_test() {
+        round_trip(IpV4Address::from([192, 0, 2, 1]), "192.0.2.1");
+        round_trip("2001:db8::1".parse::<IpV6Address>().unwrap(), "2001:db8::1");
+        round_trip(
+            IpAddress::from(IpV4Address::from([10, 0, 0, 1])),
+            "10.0.0.1",
+        );
+        round_trip(SocketAddressV4::new([127, 0, 0, 1], 443), "127.0.0.1:443");
+        round_trip(
+            "[::ffff:192.0.2.1]:4433"
+                .parse::<SocketAddressV6>()
+                .unwrap(),
+            "[::ffff:192.0.2.1]:4433",
+        );
+        round_trip(
+            "[2001:db8::1]:443".parse::<SocketAddress>().unwrap(),
+            "[2001:db8::1]:443",
+        );
+        round_trip("10.0.0.0/8".parse::<IpV4Net>().unwrap(), "10.0.0.0/8");
+        round_trip("2001:db8::/32".parse::<IpV6Net>().unwrap(), "2001:db8::/32");
+        round_trip("192.0.2.0/24".parse::<IpNet>().unwrap(), "192.0.2.0/24");
+    }
+
+    #[test]
+    fn invalid_test() {
+        assert!(from_str::<IpAddress>("\"192.0.2\"").is_err());
+        assert!(from_str::<SocketAddress>("\"192.0.2.1\"").is_err());
+        assert!(from_str::<IpNet>("\"192.0.2.0/33\"").is_err());
+        // addresses are only accepted in their textual forms
+        assert!(from_str::<IpV4Address>("[192, 0, 2, 1]").is_err());
+    }
+}
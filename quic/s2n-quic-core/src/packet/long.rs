@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    packet::{encoding::PacketPayloadLenCursor, number::TruncatedPacketNumber},
+    packet::{
+        encoding::PacketPayloadLenCursor, number::TruncatedPacketNumber, version::QUIC_VERSION_2,
+    },
     varint::VarInt,
 };
 use core::convert::TryFrom;
@@ -170,6 +172,38 @@ impl From<PacketType> for u8 {
     }
 }
 
+// https://www.rfc-editor.org/rfc/rfc9369#section-3.2
+// All version 2 long header packet types are different. The Type field values are:
+//
+// * Initial: 0b01
+// * 0-RTT: 0b10
+// * Handshake: 0b11
+// * Retry: 0b00
+
+/// Maps the packet type in the first byte of a long header from its version 1 value to the
+/// value used by the given version
+#[inline]
+pub(crate) fn encode_packet_type(version: Version, tag: u8) -> u8 {
+    if version == QUIC_VERSION_2 {
+        let packet_type = tag.wrapping_add(1 << PACKET_TYPE_OFFSET) & PACKET_TYPE_MASK;
+        (tag & !PACKET_TYPE_MASK) | packet_type
+    } else {
+        tag
+    }
+}
+
+/// Maps the packet type in the first byte of a long header from the value used by the given
+/// version to its version 1 value
+#[inline]
+pub(crate) fn decode_packet_type(version: Version, tag: u8) -> u8 {
+    if version == QUIC_VERSION_2 {
+        let packet_type = tag.wrapping_sub(1 << PACKET_TYPE_OFFSET) & PACKET_TYPE_MASK;
+        (tag & !PACKET_TYPE_MASK) | packet_type
+    } else {
+        tag
+    }
+}
+
 //= https://www.rfc-editor.org/rfc/rfc9000#section-17.2
 //# Reserved Bits:  Two bits (those with a mask of 0x0c) of byte 0 are
 //#    reserved across multiple packet types.  These bits are protected
@@ -6,6 +6,11 @@ use s2n_codec::{decoder_invariant, DecoderBufferMut, DecoderBufferMutResult, Dec
 
 pub(crate) type Tag = u8;
 
+//= https://www.rfc-editor.org/rfc/rfc9000#section-17.2
+//# Header Form:  The most significant bit (0x80) of byte 0 (the first
+//#   byte) is set to 1 for long headers.
+const LONG_HEADER_FORM_BIT: u8 = 0x80;
+
 #[macro_use]
 pub mod short;
 #[macro_use]
@@ -27,6 +32,7 @@ pub mod long;
 
 pub mod number;
 pub mod stateless_reset;
+pub mod version;
 
 #[cfg(test)]
 mod tests;
@@ -56,6 +62,11 @@ pub enum ProtectedPacket<'a> {
 }
 
 impl<'a> ProtectedPacket<'a> {
+    /// Decodes the first packet in the buffer, returning it along with any coalesced packets
+    /// which follow it
+    ///
+    /// Only the unprotected parts of the packet are decoded. The returned packet can then be
+    /// unprotected and decrypted with the keys for its packet number space.
     pub fn decode<Validator: connection::id::Validator>(
         buffer: DecoderBufferMut<'a>,
         connection_info: &ConnectionInfo,
@@ -64,6 +75,24 @@ impl<'a> ProtectedPacket<'a> {
         BasicPacketDecoder.decode_packet(buffer, connection_info, connection_id_validator)
     }
 
+    /// Returns an iterator over each of the packets coalesced into a datagram
+    ///
+    /// The length of short header destination connection IDs isn't encoded on the wire, so
+    /// it is determined by the `connection_id_validator`.
+    #[inline]
+    pub fn decode_datagram<'v, Validator: connection::id::Validator>(
+        buffer: DecoderBufferMut<'a>,
+        connection_info: &'v ConnectionInfo<'v>,
+        connection_id_validator: &'v Validator,
+    ) -> Packets<'a, 'v, Validator> {
+        Packets {
+            len: buffer.len(),
+            buffer: Some(buffer),
+            connection_info,
+            connection_id_validator,
+        }
+    }
+
     /// Returns the packet's destination connection ID
     pub fn destination_connection_id(&self) -> &[u8] {
         match self {
@@ -100,6 +129,35 @@ impl<'a> ProtectedPacket<'a> {
     }
 }
 
+/// An iterator over the packets in a datagram, returned by [`ProtectedPacket::decode_datagram`]
+///
+/// Each packet is returned along with its byte offset in the datagram. Iteration stops after
+/// the first packet which fails to decode.
+pub struct Packets<'a, 'v, Validator> {
+    buffer: Option<DecoderBufferMut<'a>>,
+    len: usize,
+    connection_info: &'v ConnectionInfo<'v>,
+    connection_id_validator: &'v Validator,
+}
+
+impl<'a, 'v, Validator: connection::id::Validator> Iterator for Packets<'a, 'v, Validator> {
+    type Item = Result<(usize, ProtectedPacket<'a>), DecoderError>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let buffer = self.buffer.take().filter(|buffer| !buffer.is_empty())?;
+        let offset = self.len - buffer.len();
+
+        match ProtectedPacket::decode(buffer, self.connection_info, self.connection_id_validator) {
+            Ok((packet, remaining)) => {
+                self.buffer = Some(remaining);
+                Some(Ok((offset, packet)))
+            }
+            Err(error) => Some(Err(error)),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum CleartextPacket<'a> {
     Short(short::CleartextShort<'a>),
@@ -224,7 +282,17 @@ pub trait PacketDecoder<'a> {
             }};
         }
 
-        match tag >> 4 {
+        // Long header packet types depend on the version so map them to the version 1 values
+        // before dispatching. The original tag is still passed to the packet decoders, since it
+        // is protected as part of the header.
+        let packet_type = match peek.decode::<long::Version>() {
+            Ok((version, _)) if tag & LONG_HEADER_FORM_BIT != 0 => {
+                long::decode_packet_type(version, tag)
+            }
+            _ => tag,
+        };
+
+        match packet_type >> 4 {
             short_tag!() => {
                 let (packet, buffer) = short::ProtectedShort::decode(
                     tag,
@@ -12,7 +12,7 @@ use crate::{
     packet::{
         decoding::HeaderDecoder,
         initial::ProtectedInitial,
-        long::{DestinationConnectionIdLen, SourceConnectionIdLen, Version},
+        long::{encode_packet_type, DestinationConnectionIdLen, SourceConnectionIdLen, Version},
         Tag,
     },
     random, token,
@@ -157,7 +157,7 @@ impl<'a> Retry<'a> {
 
         outcome?;
 
-        let tag = C::generate_tag(buffer.as_mut_slice());
+        let tag = C::generate_tag_for_version(packet.version, buffer.as_mut_slice())?;
         buffer.write_slice(&tag);
         let end = buffer.len();
         let start =
@@ -190,7 +190,7 @@ impl<'a> Retry<'a> {
         //# of packets that have accidentally been corrupted by the network, and
         //# only an entity that observes an Initial packet can send a valid Retry
         //# packet.
-        Crypto::validate(buf, *self.retry_integrity_tag)?;
+        Crypto::validate_for_version(self.version, buf, *self.retry_integrity_tag)?;
 
         Ok(())
     }
@@ -209,7 +209,7 @@ impl<'a> Retry<'a> {
             // The last 4 bits are unused. They are set to 0x0f here to allow easy testing with
             // example packets provided in the RFC.
             // https://www.rfc-editor.org/rfc/rfc9001#section-A.2
-            tag: (retry_tag!() << 4) | 0x0f,
+            tag: encode_packet_type(initial_packet.version, (retry_tag!() << 4) | 0x0f),
             version: initial_packet.version,
             destination_connection_id: initial_packet.source_connection_id(),
             source_connection_id: local_connection_id,
@@ -424,4 +424,28 @@ mod tests {
 
         assert_eq!(pseudo_scratch, retry::example::PSEUDO_PACKET);
     }
+
+    #[test]
+    fn test_v2_decode() {
+        let mut buf = retry::example_v2::PACKET;
+        let decoder = DecoderBufferMut::new(&mut buf);
+        let remote_address = inet::ip::SocketAddress::default();
+        let connection_info = connection::id::ConnectionInfo::new(&remote_address);
+        let (packet, _) = packet::ProtectedPacket::decode(decoder, &connection_info, &20).unwrap();
+        let packet = match packet {
+            packet::ProtectedPacket::Retry(retry) => retry,
+            _ => panic!("expected retry packet type"),
+        };
+
+        assert_eq!(packet.version, retry::example_v2::VERSION);
+        assert_eq!(packet.retry_integrity_tag, &retry::example_v2::EXPECTED_TAG);
+        assert_eq!(packet.retry_token, retry::example::TOKEN);
+
+        let pseudo_packet = packet.pseudo_packet(&retry::example::ODCID);
+        let mut pseudo_scratch: Vec<u8> = vec![0; pseudo_packet.encoding_size()];
+        let mut encoder = EncoderBuffer::new(&mut pseudo_scratch);
+        pseudo_packet.encode(&mut encoder);
+
+        assert_eq!(pseudo_scratch, retry::example_v2::PSEUDO_PACKET);
+    }
 }
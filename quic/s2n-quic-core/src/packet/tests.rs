@@ -4,14 +4,17 @@
 use crate::{
     connection::id::ConnectionInfo,
     crypto::key::testing,
+    frame,
     inet::SocketAddress,
     packet::{
-        encoding::PacketEncoder, number::PacketNumberSpace, CleartextPacket, ProtectedPacket,
+        encoding::PacketEncoder, handshake::Handshake, number::PacketNumberSpace, short::Short,
+        CleartextPacket, KeyPhase, ProtectedPacket,
     },
     transport,
+    varint::VarInt,
 };
 use bolero::check;
-use s2n_codec::{DecoderBufferMut, Encoder, EncoderBuffer};
+use s2n_codec::{DecoderBuffer, DecoderBufferMut, Encoder, EncoderBuffer};
 
 #[test]
 fn round_trip() {
@@ -168,3 +171,242 @@ fn encode_packet<'a>(packet: CleartextPacket, mut encoder: EncoderBuffer<'a>) ->
         Err(err) => err.take_buffer(),
     }
 }
+
+#[test]
+fn decode_datagram_test() {
+    check!().for_each(|data| {
+        let mut data = data.to_vec();
+        let len = data.len();
+        let remote_address = SocketAddress::default();
+        let connection_info = ConnectionInfo::new(&remote_address);
+        let mut previous = None;
+
+        for result in
+            ProtectedPacket::decode_datagram(DecoderBufferMut::new(&mut data), &connection_info, &8)
+        {
+            let (offset, packet) = if let Ok(packet) = result {
+                packet
+            } else {
+                break;
+            };
+
+            assert!(offset < len);
+            assert!(previous.map_or(true, |previous| previous < offset));
+            previous = Some(offset);
+
+            // decrypting arbitrary packets must not panic
+            if let Ok(CleartextPacket::Short(packet)) = decrypt_packet(packet) {
+                let _ = frame::Iter::new(packet.payload).count();
+            }
+        }
+    });
+}
+
+/// Encodes coalesced packets with the packet encoders and decodes them with the public
+/// decoding API
+#[test]
+fn decode_datagram_round_trip_test() {
+    let dcid = [1u8; 8];
+    let scid = [2u8; 4];
+
+    let mut handshake_payload = vec![0; 64];
+    let mut encoder = EncoderBuffer::new(&mut handshake_payload);
+    encoder.encode(&frame::Crypto {
+        offset: VarInt::ZERO,
+        data: DecoderBuffer::new(b"finished"),
+    });
+    encoder.encode(&frame::Padding { length: 16 });
+    let len = encoder.len();
+    handshake_payload.truncate(len);
+
+    let mut short_payload = vec![0; 64];
+    let mut encoder = EncoderBuffer::new(&mut short_payload);
+    encoder.encode(&frame::Ping);
+    encoder.encode(&frame::Stream {
+        stream_id: VarInt::from_u8(0),
+        offset: VarInt::ZERO,
+        is_last_frame: true,
+        is_fin: false,
+        data: DecoderBuffer::new(b"hello world"),
+    });
+    let len = encoder.len();
+    short_payload.truncate(len);
+
+    let mut datagram = vec![0; 256];
+    let encoder = EncoderBuffer::new(&mut datagram);
+    let (_, encoder) = Handshake {
+        version: 1,
+        destination_connection_id: &dcid[..],
+        source_connection_id: &scid[..],
+        packet_number: PacketNumberSpace::Handshake.new_packet_number(VarInt::from_u8(1)),
+        payload: DecoderBuffer::new(&handshake_payload),
+    }
+    .encode_packet(
+        &testing::Key::new(),
+        &testing::HeaderKey::new(),
+        PacketNumberSpace::Handshake.new_packet_number(VarInt::ZERO),
+        None,
+        encoder,
+    )
+    .unwrap();
+    let short_offset = encoder.len();
+    let (_, encoder) = Short {
+        spin_bit: Default::default(),
+        key_phase: KeyPhase::Zero,
+        destination_connection_id: &dcid[..],
+        packet_number: PacketNumberSpace::ApplicationData.new_packet_number(VarInt::from_u8(7)),
+        payload: DecoderBuffer::new(&short_payload),
+    }
+    .encode_packet(
+        &testing::Key::new(),
+        &testing::HeaderKey::new(),
+        PacketNumberSpace::ApplicationData.new_packet_number(VarInt::ZERO),
+        None,
+        encoder,
+    )
+    .unwrap();
+    let len = encoder.len();
+    datagram.truncate(len);
+
+    let remote_address = SocketAddress::default();
+    let connection_info = ConnectionInfo::new(&remote_address);
+    let mut packets = ProtectedPacket::decode_datagram(
+        DecoderBufferMut::new(&mut datagram),
+        &connection_info,
+        &dcid.len(),
+    );
+
+    let (offset, packet) = packets.next().unwrap().unwrap();
+    assert_eq!(offset, 0);
+    assert_eq!(packet.version(), Some(1));
+    assert_eq!(packet.destination_connection_id(), dcid);
+    assert_eq!(packet.source_connection_id(), Some(&scid[..]));
+    let packet = if let CleartextPacket::Handshake(packet) = decrypt_packet(packet).unwrap() {
+        packet
+    } else {
+        panic!("expected a handshake packet");
+    };
+    assert_eq!(packet.packet_number.as_u64(), 1);
+    let frames: Vec<_> = frame::Iter::new(packet.payload)
+        .map(|result| {
+            let (offset, frame) = result.unwrap();
+            (offset, frame.tag())
+        })
+        .collect();
+    // a CRYPTO frame (tag, offset, length and 8 bytes of data) followed by PADDING
+    assert_eq!(frames, [(0, 0x06), (11, 0x00)]);
+
+    let (offset, packet) = packets.next().unwrap().unwrap();
+    assert_eq!(offset, short_offset);
+    assert_eq!(packet.destination_connection_id(), dcid);
+    let packet = if let CleartextPacket::Short(packet) = decrypt_packet(packet).unwrap() {
+        packet
+    } else {
+        panic!("expected a short packet");
+    };
+    assert_eq!(packet.packet_number.as_u64(), 7);
+    let mut frames = frame::Iter::new(packet.payload);
+    assert!(matches!(
+        frames.next(),
+        Some(Ok((0, frame::Frame::Ping(_))))
+    ));
+    match frames.next() {
+        Some(Ok((1, frame::Frame::Stream(stream)))) => {
+            assert_eq!(stream.data.as_less_safe_slice(), b"hello world");
+        }
+        other => panic!("unexpected frame {other:?}"),
+    }
+    assert!(frames.next().is_none());
+
+    assert!(packets.next().is_none());
+}
+
+#[test]
+fn is_zero_rtt_test() {
+    use crate::packet::zero_rtt::is_zero_rtt;
+
+    // the packet number length and reserved bits don't affect the type
+    for byte in 0xd0..=0xdf {
+        assert!(is_zero_rtt(byte));
+    }
+
+    // Initial, Handshake, Retry and short header packets
+    for byte in [0xc0, 0xe0, 0xf0, 0x40, 0x50] {
+        assert!(!is_zero_rtt(byte));
+    }
+}
+
+#[test]
+fn packet_type_mapping_test() {
+    use crate::packet::{
+        long::{decode_packet_type, encode_packet_type, PacketType},
+        version::{QUIC_VERSION_1, QUIC_VERSION_2},
+    };
+
+    for (packet_type, v2_bits) in [
+        (PacketType::Initial, 0b01),
+        (PacketType::ZeroRtt, 0b10),
+        (PacketType::Handshake, 0b11),
+        (PacketType::Retry, 0b00),
+    ] {
+        // the other bits in the first byte are preserved
+        for low_bits in [0x00, 0x03, 0x0f] {
+            let tag = 0xc0 | packet_type.into_bits() | low_bits;
+            let v2_tag = 0xc0 | (v2_bits << 4) | low_bits;
+
+            assert_eq!(encode_packet_type(QUIC_VERSION_1, tag), tag);
+            assert_eq!(decode_packet_type(QUIC_VERSION_1, tag), tag);
+            assert_eq!(encode_packet_type(QUIC_VERSION_2, tag), v2_tag);
+            assert_eq!(decode_packet_type(QUIC_VERSION_2, v2_tag), tag);
+        }
+    }
+}
+
+/// Encodes a version 2 Handshake packet and ensures it's decoded as a Handshake packet
+#[test]
+fn version_2_handshake_test() {
+    use crate::packet::version::QUIC_VERSION_2;
+
+    let dcid = [1u8; 8];
+    let scid = [2u8; 4];
+    let payload = [0u8; 32];
+
+    let mut datagram = vec![0; 128];
+    let encoder = EncoderBuffer::new(&mut datagram);
+    let (_, encoder) = Handshake {
+        version: QUIC_VERSION_2,
+        destination_connection_id: &dcid[..],
+        source_connection_id: &scid[..],
+        packet_number: PacketNumberSpace::Handshake.new_packet_number(VarInt::from_u8(1)),
+        payload: DecoderBuffer::new(&payload),
+    }
+    .encode_packet(
+        &testing::Key::new(),
+        &testing::HeaderKey::new(),
+        PacketNumberSpace::Handshake.new_packet_number(VarInt::ZERO),
+        None,
+        encoder,
+    )
+    .unwrap();
+    let len = encoder.len();
+    datagram.truncate(len);
+
+    // version 2 Handshake packets use the version 1 Retry type bits
+    assert_eq!(datagram[0] & 0xf0, 0xf0);
+
+    let remote_address = SocketAddress::default();
+    let connection_info = ConnectionInfo::new(&remote_address);
+    let (packet, _) =
+        ProtectedPacket::decode(DecoderBufferMut::new(&mut datagram), &connection_info, &8)
+            .unwrap();
+    assert_eq!(packet.version(), Some(QUIC_VERSION_2));
+
+    let packet = if let CleartextPacket::Handshake(packet) = decrypt_packet(packet).unwrap() {
+        packet
+    } else {
+        panic!("expected a handshake packet");
+    };
+    assert_eq!(packet.packet_number.as_u64(), 1);
+    assert_eq!(packet.destination_connection_id(), dcid);
+    assert_eq!(packet.source_connection_id(), scid);
+}
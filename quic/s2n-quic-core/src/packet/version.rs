@@ -0,0 +1,57 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! QUIC version numbers
+
+//= https://www.rfc-editor.org/rfc/rfc9000#section-15
+//# This version of the specification is identified by the number
+//# 0x00000001.
+pub const QUIC_VERSION_1: u32 = 0x0000_0001;
+
+// https://www.rfc-editor.org/rfc/rfc9369#section-3.1
+// The Version field of long headers is 0x6b3343cf.
+pub const QUIC_VERSION_2: u32 = 0x6b33_43cf;
+
+/// Returns `true` if the version is reserved for exercising version negotiation
+///
+/// Versions following the `0x?a?a?a?a` pattern are never used by an actual protocol.
+/// See <https://www.rfc-editor.org/rfc/rfc9000#section-15>.
+#[inline]
+pub const fn is_reserved(version: u32) -> bool {
+    version & 0x0f0f_0f0f == 0x0a0a_0a0a
+}
+
+/// Generates a random reserved version for greasing
+///
+/// Endpoints can advertise reserved versions to make sure peers ignore versions they
+/// don't support.
+#[inline]
+pub fn reserved<R: crate::random::Generator + ?Sized>(random_generator: &mut R) -> u32 {
+    let mut bytes = [0; 4];
+    random_generator.public_random_fill(&mut bytes);
+    (u32::from_be_bytes(bytes) & 0xf0f0_f0f0) | 0x0a0a_0a0a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_reserved_test() {
+        for version in [0x0a0a_0a0a, 0x1a2a_3a4a, 0xdada_dada, 0xfafa_fafa] {
+            assert!(is_reserved(version), "{version:#x}");
+        }
+
+        for version in [0, QUIC_VERSION_1, QUIC_VERSION_2, 0xff00_001d, 0x0a0a_0a0b] {
+            assert!(!is_reserved(version), "{version:#x}");
+        }
+    }
+
+    #[test]
+    fn reserved_test() {
+        let mut generator = crate::random::testing::Generator::default();
+        for _ in 0..10 {
+            assert!(is_reserved(reserved(&mut generator)));
+        }
+    }
+}
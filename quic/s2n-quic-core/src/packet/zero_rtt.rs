@@ -7,8 +7,8 @@ use crate::{
         decoding::HeaderDecoder,
         encoding::{PacketEncoder, PacketPayloadEncoder},
         long::{
-            DestinationConnectionIdLen, LongPayloadEncoder, LongPayloadLenCursor,
-            SourceConnectionIdLen, Version,
+            encode_packet_type, DestinationConnectionIdLen, LongPayloadEncoder,
+            LongPayloadLenCursor, SourceConnectionIdLen, Version,
         },
         number::{
             PacketNumber, PacketNumberLen, PacketNumberSpace, ProtectedPacketNumber,
@@ -54,6 +54,12 @@ pub struct ZeroRtt<DCID, SCID, PacketNumber, Payload> {
     pub payload: Payload,
 }
 
+/// Returns `true` if the packet starting with the given byte is a 0-RTT packet
+#[inline]
+pub fn is_zero_rtt(first_byte: u8) -> bool {
+    first_byte >> 4 == zero_rtt_tag!()
+}
+
 pub type ProtectedZeroRtt<'a> =
     ZeroRtt<CheckedRange, CheckedRange, ProtectedPacketNumber, ProtectedPayload<'a>>;
 pub type EncryptedZeroRtt<'a> =
@@ -201,7 +207,7 @@ impl<DCID: EncoderValue, SCID: EncoderValue, PacketNumber, Payload>
     ZeroRtt<DCID, SCID, PacketNumber, Payload>
 {
     fn encode_header<E: Encoder>(&self, packet_number_len: PacketNumberLen, encoder: &mut E) {
-        let mut tag: u8 = zero_rtt_tag!() << 4;
+        let mut tag: u8 = encode_packet_type(self.version, zero_rtt_tag!() << 4);
         tag |= packet_number_len.into_packet_tag_mask();
         tag.encode(encoder);
 
@@ -2,13 +2,14 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    event,
+    connection, event,
     event::{
         api::{Path, SocketAddress},
         IntoEvent,
     },
-    inet,
+    inet, transport,
 };
+use core::fmt;
 
 #[derive(Debug)]
 #[non_exhaustive]
@@ -17,6 +18,8 @@ pub struct Attempt<'a> {
     pub active_path: Path<'a>,
     /// Information about the packet triggering the migration attempt
     pub packet: PacketInfo<'a>,
+    /// The number of the connection's other paths which are currently being validated
+    pub inflight_path_validations: usize,
 }
 
 #[derive(Debug)]
@@ -25,6 +28,8 @@ pub struct AttemptBuilder<'a> {
     pub active_path: Path<'a>,
     /// Information about the packet triggering the migration attempt
     pub packet: PacketInfo<'a>,
+    /// The number of the connection's other paths which are currently being validated
+    pub inflight_path_validations: usize,
 }
 
 impl<'a> From<AttemptBuilder<'a>> for Attempt<'a> {
@@ -33,6 +38,7 @@ impl<'a> From<AttemptBuilder<'a>> for Attempt<'a> {
         Self {
             active_path: builder.active_path,
             packet: builder.packet,
+            inflight_path_validations: builder.inflight_path_validations,
         }
     }
 }
@@ -60,8 +66,6 @@ impl<'a> From<PacketInfoBuilder<'a>> for PacketInfo<'a> {
     }
 }
 
-// TODO: Add an outcome that allows the connection to be closed/stateless reset https://github.com/aws/s2n-quic/issues/317
-
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum Outcome {
@@ -76,6 +80,13 @@ pub enum Outcome {
     /// The connection will drop the packet that attempted to migrate and not reserve any state
     /// for the new path.
     Deny(DenyReason),
+
+    /// Closes the connection with the provided error
+    ///
+    /// Since the packet that attempted to migrate may have been spoofed by an off-path attacker,
+    /// the connection is only closed once the packet has been authenticated. Packets that fail
+    /// authentication are dropped and the connection remains open.
+    Close(transport::Error),
     // Additional outcomes must be handled in the path::Manager
 }
 
@@ -89,6 +100,8 @@ pub enum DenyReason {
     IpScopeChanged,
     // All connection migrations are disabled
     ConnectionMigrationDisabled,
+    // Too many paths are already being validated
+    Limited,
 }
 
 impl IntoEvent<event::builder::ConnectionMigrationDenied> for DenyReason {
@@ -101,11 +114,64 @@ impl IntoEvent<event::builder::ConnectionMigrationDenied> for DenyReason {
             DenyReason::ConnectionMigrationDisabled => {
                 event::builder::MigrationDenyReason::ConnectionMigrationDisabled
             }
+            DenyReason::Limited => event::builder::MigrationDenyReason::Limited,
         };
         event::builder::ConnectionMigrationDenied { reason }
     }
 }
 
+/// The reason a connection migration initiated by the local endpoint failed
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Only clients are allowed to initiate connection migrations
+    InvalidEndpointType,
+    /// The handshake has not been confirmed yet
+    HandshakeNotConfirmed,
+    /// The peer sent the `disable_active_migration` transport parameter
+    DisabledByPeer,
+    /// Another connection migration is currently in progress
+    InProgress,
+    /// The peer has not provided an unused connection ID for the new path
+    InsufficientConnectionIds,
+    /// The connection has reached the maximum number of paths
+    PathLimitExceeded,
+    /// The peer did not respond to the PATH_CHALLENGE on the new path before the
+    /// validation timer expired
+    ///
+    /// The connection continues to use the original path.
+    ValidationTimeout,
+    /// The connection was closed
+    ConnectionError { error: connection::Error },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidEndpointType => write!(f, "Only clients can initiate a migration."),
+            Self::HandshakeNotConfirmed => write!(f, "The handshake has not been confirmed."),
+            Self::DisabledByPeer => write!(f, "The peer has disabled active migration."),
+            Self::InProgress => write!(f, "A migration is already in progress."),
+            Self::InsufficientConnectionIds => {
+                write!(f, "No unused connection IDs are available for a new path.")
+            }
+            Self::PathLimitExceeded => write!(f, "The maximum number of paths has been reached."),
+            Self::ValidationTimeout => write!(f, "The new path could not be validated."),
+            Self::ConnectionError { .. } => write!(f, "Connection-level error occurred."),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+impl From<connection::Error> for Error {
+    #[inline]
+    fn from(error: connection::Error) -> Self {
+        Self::ConnectionError { error }
+    }
+}
+
 /// Validates a path migration attempt from an active path to another
 pub trait Validator: 'static + Send {
     /// Called on each connection migration attempt for a connection
@@ -100,6 +100,11 @@ pub trait Handle: 'static + Copy + Send + fmt::Debug {
     /// Returns the local address for the given handle
     fn local_address(&self) -> LocalAddress;
 
+    /// Updates the local address to the given value
+    ///
+    /// This is used by clients initiating a connection migration to a new local address.
+    fn set_local_address(&mut self, addr: LocalAddress);
+
     /// Returns `true` if the two handles are equal from a network perspective
     ///
     /// This function is used to determine if a connection has migrated to another
@@ -206,6 +211,11 @@ impl Handle for RemoteAddress {
         SocketAddressV4::UNSPECIFIED.into()
     }
 
+    #[inline]
+    fn set_local_address(&mut self, _addr: LocalAddress) {
+        // the local address is not tracked
+    }
+
     #[inline]
     fn eq(&self, other: &Self) -> bool {
         PartialEq::eq(&self.unmap(), &other.unmap())
@@ -262,6 +272,11 @@ impl Handle for Tuple {
         self.local_address
     }
 
+    #[inline]
+    fn set_local_address(&mut self, addr: LocalAddress) {
+        self.local_address = addr;
+    }
+
     #[inline]
     fn eq(&self, other: &Self) -> bool {
         PartialEq::eq(&self.local_address.unmap(), &other.local_address.unmap())
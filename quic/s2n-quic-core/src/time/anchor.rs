@@ -0,0 +1,164 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Converts monotonic [`Timestamp`]s to wall clock times
+
+use crate::time::{Duration, Timestamp};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A [`Timestamp`] paired with the [`SystemTime`] observed at the same instant
+///
+/// Anchors are usually created with [`Clock::anchor`](crate::time::Clock::anchor). Timestamps
+/// from the same clock can then be converted to wall clock times relative to the anchor. The
+/// anchor's `SystemTime` is used as a fixed reference, so conversions are unaffected by later
+/// adjustments to the system clock.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Anchor {
+    timestamp: Timestamp,
+    system_time: SystemTime,
+}
+
+impl Anchor {
+    /// Creates an anchor from a timestamp and the system time at which it was taken
+    #[inline]
+    pub const fn new(timestamp: Timestamp, system_time: SystemTime) -> Self {
+        Self {
+            timestamp,
+            system_time,
+        }
+    }
+
+    /// Returns the timestamp of the anchor
+    #[inline]
+    pub const fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// Returns the system time of the anchor
+    #[inline]
+    pub const fn system_time(&self) -> SystemTime {
+        self.system_time
+    }
+}
+
+impl From<Anchor> for (Timestamp, SystemTime) {
+    #[inline]
+    fn from(anchor: Anchor) -> Self {
+        (anchor.timestamp, anchor.system_time)
+    }
+}
+
+impl Timestamp {
+    /// Converts the timestamp to a [`SystemTime`] relative to the anchor
+    ///
+    /// Timestamps before the anchor are supported. Returns `None` if the result isn't
+    /// representable as a `SystemTime`.
+    #[inline]
+    pub fn as_system_time(self, anchor: &Anchor) -> Option<SystemTime> {
+        if let Some(delta) = self.checked_duration_since(anchor.timestamp) {
+            anchor.system_time.checked_add(delta)
+        } else {
+            let delta = anchor.timestamp.saturating_duration_since(self);
+            anchor.system_time.checked_sub(delta)
+        }
+    }
+
+    /// Returns the time since the UNIX epoch, relative to the anchor
+    ///
+    /// Returns `None` if the timestamp converts to a time before the UNIX epoch.
+    #[inline]
+    pub fn since_unix_epoch(self, anchor: &Anchor) -> Option<Duration> {
+        self.as_system_time(anchor)?.duration_since(UNIX_EPOCH).ok()
+    }
+
+    /// Returns the number of milliseconds since the UNIX epoch, relative to the anchor
+    #[inline]
+    pub fn unix_millis(self, anchor: &Anchor) -> Option<u64> {
+        self.since_unix_epoch(anchor)?.as_millis().try_into().ok()
+    }
+
+    /// Returns the number of microseconds since the UNIX epoch, relative to the anchor
+    #[inline]
+    pub fn unix_micros(self, anchor: &Anchor) -> Option<u64> {
+        self.since_unix_epoch(anchor)?.as_micros().try_into().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{Clock, NoopClock};
+
+    fn at(micros: u64) -> Timestamp {
+        unsafe { Timestamp::from_duration(Duration::from_micros(micros)) }
+    }
+
+    fn anchor() -> Anchor {
+        // 2023-11-14T22:13:20Z
+        Anchor::new(
+            at(1_000_000),
+            UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+        )
+    }
+
+    #[test]
+    fn conversion_test() {
+        let anchor = anchor();
+
+        assert_eq!(
+            at(1_000_000).as_system_time(&anchor),
+            Some(anchor.system_time())
+        );
+        assert_eq!(
+            at(1_250_000).as_system_time(&anchor),
+            Some(anchor.system_time() + Duration::from_millis(250))
+        );
+        assert_eq!(at(1_000_000).unix_millis(&anchor), Some(1_700_000_000_000));
+        assert_eq!(
+            at(1_000_001).unix_micros(&anchor),
+            Some(1_700_000_000_000_001)
+        );
+
+        let (timestamp, system_time) = anchor.into();
+        assert_eq!(timestamp, anchor.timestamp());
+        assert_eq!(system_time, anchor.system_time());
+    }
+
+    #[test]
+    fn before_anchor_test() {
+        let anchor = anchor();
+
+        assert_eq!(
+            at(1).as_system_time(&anchor),
+            Some(anchor.system_time() - Duration::from_micros(999_999))
+        );
+        assert_eq!(at(500_000).unix_millis(&anchor), Some(1_699_999_999_500));
+
+        // timestamps which convert to a time before the UNIX epoch don't have a UNIX time
+        let anchor = Anchor::new(at(u64::MAX), UNIX_EPOCH + Duration::from_secs(1));
+        assert!(at(1).as_system_time(&anchor).is_some());
+        assert_eq!(at(1).unix_millis(&anchor), None);
+        assert_eq!(at(u64::MAX).unix_millis(&anchor), Some(1000));
+    }
+
+    #[test]
+    fn large_delta_test() {
+        let anchor = Anchor::new(at(1), UNIX_EPOCH);
+        let max = at(u64::MAX);
+
+        let expected = Duration::from_micros(u64::MAX - 1);
+        assert_eq!(max.since_unix_epoch(&anchor), Some(expected));
+        assert_eq!(max.unix_micros(&anchor), Some(u64::MAX - 1));
+        assert_eq!(max.unix_millis(&anchor), Some((u64::MAX - 1) / 1000));
+    }
+
+    #[test]
+    fn clock_anchor_test() {
+        let anchor = NoopClock.anchor();
+        assert_eq!(anchor.timestamp(), NoopClock.get_time());
+        assert_eq!(
+            anchor.timestamp().as_system_time(&anchor),
+            Some(anchor.system_time())
+        );
+    }
+}
@@ -19,6 +19,14 @@ pub use self::std::*;
 pub trait Clock {
     /// Returns the current [`Timestamp`]
     fn get_time(&self) -> Timestamp;
+
+    /// Returns an [`Anchor`](crate::time::Anchor) pairing the current [`Timestamp`] with the
+    /// current system time
+    #[cfg(any(test, feature = "std"))]
+    #[inline]
+    fn anchor(&self) -> crate::time::Anchor {
+        crate::time::Anchor::new(self.get_time(), ::std::time::SystemTime::now())
+    }
 }
 
 pub trait ClockWithTimer: Clock {
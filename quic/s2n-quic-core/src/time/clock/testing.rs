@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::time::{Duration, Timestamp};
-use core::cell::Cell;
+use core::{
+    cell::Cell,
+    task::{Context, Poll, Waker},
+};
 
 fn initial() -> Timestamp {
     unsafe {
@@ -44,3 +47,162 @@ impl Clock {
         advance(duration);
     }
 }
+
+/// A [`Clock`](super::Clock) whose time is only changed by explicit calls to
+/// [`MockClock::set`] and [`MockClock::advance`]
+///
+/// Clones of the clock share the same time, so a clone can be handed to the component
+/// under test while the test harness keeps another to drive time forward. Timers created
+/// from the clock are woken as soon as the clock passes their expiration, which allows
+/// time-driven behavior to be tested without any real sleeping.
+#[derive(Clone, Debug, Default)]
+pub struct MockClock {
+    state: ::std::sync::Arc<::std::sync::Mutex<MockState>>,
+}
+
+#[derive(Debug)]
+struct MockState {
+    now: Timestamp,
+    wakers: ::std::vec::Vec<Waker>,
+}
+
+impl Default for MockState {
+    fn default() -> Self {
+        Self {
+            now: initial(),
+            wakers: ::std::vec::Vec::new(),
+        }
+    }
+}
+
+impl MockClock {
+    /// Creates a new clock starting at the lowest possible [`Timestamp`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the current time to the given [`Timestamp`]
+    ///
+    /// Any pending timers are woken to re-check their expiration.
+    pub fn set(&self, timestamp: Timestamp) {
+        let wakers = {
+            let mut state = self.state.lock().unwrap();
+            state.now = timestamp;
+            core::mem::take(&mut state.wakers)
+        };
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Advances the current time by the given [`Duration`]
+    pub fn advance(&self, duration: Duration) {
+        let now = self.state.lock().unwrap().now;
+        self.set(now + duration);
+    }
+}
+
+impl super::Clock for MockClock {
+    fn get_time(&self) -> Timestamp {
+        self.state.lock().unwrap().now
+    }
+}
+
+impl super::ClockWithTimer for MockClock {
+    type Timer = MockTimer;
+
+    fn timer(&self) -> Self::Timer {
+        MockTimer {
+            clock: self.clone(),
+            expiration: None,
+        }
+    }
+}
+
+/// A [`Timer`](super::Timer) which expires once its [`MockClock`] is advanced past the target
+#[derive(Debug)]
+pub struct MockTimer {
+    clock: MockClock,
+    expiration: Option<Timestamp>,
+}
+
+impl super::Timer for MockTimer {
+    fn poll_ready(&mut self, cx: &mut Context) -> Poll<()> {
+        let expiration = if let Some(expiration) = self.expiration {
+            expiration
+        } else {
+            return Poll::Pending;
+        };
+
+        let mut state = self.clock.state.lock().unwrap();
+
+        if expiration.has_elapsed(state.now) {
+            self.expiration = None;
+            return Poll::Ready(());
+        }
+
+        if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+
+        Poll::Pending
+    }
+
+    fn update(&mut self, timestamp: Timestamp) {
+        self.expiration = Some(timestamp);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::{clock::Timer as _, timer, Clock as _, ClockWithTimer as _};
+    use futures_test::task::new_count_waker;
+
+    #[test]
+    fn mock_clock_test() {
+        let clock = MockClock::new();
+        let harness = clock.clone();
+        let start = clock.get_time();
+
+        harness.advance(Duration::from_millis(250));
+        assert_eq!(clock.get_time() - start, Duration::from_millis(250));
+
+        harness.set(start);
+        assert_eq!(clock.get_time(), start);
+    }
+
+    #[test]
+    fn idle_timer_test() {
+        let idle_timeout = Duration::from_secs(30);
+        let clock = MockClock::new();
+        let harness = clock.clone();
+
+        let mut idle_timer = timer::Timer::default();
+        idle_timer.set(clock.get_time() + idle_timeout);
+
+        let mut wakeup = clock.timer();
+        wakeup.update(clock.get_time() + idle_timeout);
+
+        let (waker, wake_count) = new_count_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        assert!(wakeup.poll_ready(&mut cx).is_pending());
+        assert!(idle_timer.poll_expiration(clock.get_time()).is_pending());
+
+        harness.advance(idle_timeout - Duration::from_millis(1));
+        assert_eq!(wake_count.get(), 1);
+        assert!(wakeup.poll_ready(&mut cx).is_pending());
+        assert!(idle_timer.poll_expiration(clock.get_time()).is_pending());
+
+        harness.advance(Duration::from_millis(1));
+        assert_eq!(wake_count.get(), 2);
+        assert!(wakeup.poll_ready(&mut cx).is_ready());
+        assert!(idle_timer.poll_expiration(clock.get_time()).is_ready());
+        assert!(!idle_timer.is_armed());
+
+        // the timer isn't rearmed until it's updated again
+        assert!(wakeup.poll_ready(&mut cx).is_pending());
+    }
+}
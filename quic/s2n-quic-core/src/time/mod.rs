@@ -1,11 +1,19 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(any(test, feature = "std"))]
+mod anchor;
 pub mod clock;
+#[cfg(feature = "serde")]
+pub mod serde;
 pub mod timer;
 mod timestamp;
 pub mod token_bucket;
+#[cfg(feature = "alloc")]
+pub mod wheel;
 
+#[cfg(any(test, feature = "std"))]
+pub use anchor::Anchor;
 pub use clock::*;
 pub use core::time::Duration;
 pub use timer::Timer;
@@ -0,0 +1,226 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Serializes [`Duration`]s as integer milliseconds for use with `#[serde(with = "...")]`
+//!
+//! Durations are deserialized from either an integer number of milliseconds, or a string of
+//! numbers with units such as `"250ms"`, `"1m 30s"` or `"2h"`. The supported units are `ns`,
+//! `us`, `ms`, `s`, `m` and `h`. Durations which aren't a whole number of milliseconds are
+//! serialized as a string of nanoseconds, such as `"1500us"` is serialized as `"1500000ns"`, so
+//! they round trip without losing precision.
+//!
+//! ```rust
+//! # use core::time::Duration;
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Config {
+//!     #[serde(with = "s2n_quic_core::time::serde")]
+//!     timeout: Duration,
+//!     #[serde(default, with = "s2n_quic_core::time::serde::option")]
+//!     idle_timeout: Option<Duration>,
+//! }
+//! ```
+
+use core::{fmt, time::Duration};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Serializes the duration as integer milliseconds
+pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    if duration.subsec_nanos() % 1_000_000 == 0 {
+        if let Ok(millis) = u64::try_from(duration.as_millis()) {
+            return serializer.serialize_u64(millis);
+        }
+    }
+
+    serializer.collect_str(&format_args!("{}ns", duration.as_nanos()))
+}
+
+/// Deserializes the duration from integer milliseconds or a string with units
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    deserializer.deserialize_any(Visitor)
+}
+
+pub mod option {
+    //! Serializes optional [`Duration`]s, with `None` serialized as a missing value
+
+    use super::*;
+
+    /// Serializes the duration as integer milliseconds, if it is set
+    pub fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => serializer.serialize_some(&Wrapper(*duration)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    /// Deserializes the duration from integer milliseconds or a string with units, if it is set
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        let duration = Option::<Wrapper>::deserialize(deserializer)?;
+        Ok(duration.map(|Wrapper(duration)| duration))
+    }
+}
+
+/// Allows the serialization functions to be used for nested values
+struct Wrapper(Duration);
+
+impl Serialize for Wrapper {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Wrapper {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize(deserializer).map(Self)
+    }
+}
+
+struct Visitor;
+
+impl<'de> de::Visitor<'de> for Visitor {
+    type Value = Duration;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a number of milliseconds or a duration such as \"1s\" or \"250ms\"")
+    }
+
+    fn visit_u64<E: de::Error>(self, millis: u64) -> Result<Self::Value, E> {
+        Ok(Duration::from_millis(millis))
+    }
+
+    fn visit_i64<E: de::Error>(self, millis: i64) -> Result<Self::Value, E> {
+        let millis = u64::try_from(millis)
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(millis), &self))?;
+        self.visit_u64(millis)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        parse(value).ok_or_else(|| E::invalid_value(de::Unexpected::Str(value), &self))
+    }
+}
+
+/// Parses a duration from a sequence of numbers with units, such as `"1m 30s"`
+///
+/// Returns `None` if any number is missing its unit or the duration overflows.
+fn parse(value: &str) -> Option<Duration> {
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return None;
+    }
+
+    let mut total = Duration::ZERO;
+
+    while !rest.is_empty() {
+        let digits = rest.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return None;
+        }
+        let amount: u64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+
+        let unit_len = rest.bytes().take_while(u8::is_ascii_alphabetic).count();
+        let unit = &rest[..unit_len];
+        rest = rest[unit_len..].trim_start();
+
+        let duration = match unit {
+            "ns" => Duration::from_nanos(amount),
+            "us" => Duration::from_micros(amount),
+            "ms" => Duration::from_millis(amount),
+            "s" => Duration::from_secs(amount),
+            "m" => Duration::from_secs(amount.checked_mul(60)?),
+            "h" => Duration::from_secs(amount.checked_mul(60 * 60)?),
+            _ => return None,
+        };
+
+        total = total.checked_add(duration)?;
+    }
+
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{from_value, json, to_value};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Config {
+        #[serde(with = "super")]
+        timeout: Duration,
+        #[serde(default, with = "super::option")]
+        idle_timeout: Option<Duration>,
+    }
+
+    #[test]
+    fn parse_test() {
+        let tests = [
+            ("0ms", Some(Duration::ZERO)),
+            ("250ms", Some(Duration::from_millis(250))),
+            ("1s", Some(Duration::from_secs(1))),
+            ("1m 30s", Some(Duration::from_secs(90))),
+            ("1m30s", Some(Duration::from_secs(90))),
+            ("2h", Some(Duration::from_secs(2 * 60 * 60))),
+            ("1500us", Some(Duration::from_micros(1500))),
+            ("42ns", Some(Duration::from_nanos(42))),
+            (" 1s ", Some(Duration::from_secs(1))),
+            ("", None),
+            ("100", None),
+            ("1 s", None),
+            ("1d", None),
+            ("-1s", None),
+            ("1.5s", None),
+            ("18446744073709551615h", None),
+        ];
+
+        for (value, expected) in tests {
+            assert_eq!(parse(value), expected, "{value:?}");
+        }
+    }
+
+    #[test]
+    fn round_trip_test() {
+        for (config, expected) in [
+            (
+                Config {
+                    timeout: Duration::from_secs(3),
+                    idle_timeout: None,
+                },
+                json!({ "timeout": 3000, "idle_timeout": null }),
+            ),
+            (
+                Config {
+                    timeout: Duration::from_micros(1500),
+                    idle_timeout: Some(Duration::from_millis(250)),
+                },
+                json!({ "timeout": "1500000ns", "idle_timeout": 250 }),
+            ),
+        ] {
+            let value = to_value(&config).unwrap();
+            assert_eq!(value, expected);
+            assert_eq!(from_value::<Config>(value).unwrap(), config);
+        }
+    }
+
+    #[test]
+    fn deserialize_test() {
+        let config: Config = from_value(json!({ "timeout": "1m 30s" })).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                timeout: Duration::from_secs(90),
+                idle_timeout: None,
+            }
+        );
+
+        let config: Config = from_value(json!({ "timeout": 10, "idle_timeout": "5s" })).unwrap();
+        assert_eq!(config.idle_timeout, Some(Duration::from_secs(5)));
+
+        assert!(from_value::<Config>(json!({ "timeout": -1 })).is_err());
+        assert!(from_value::<Config>(json!({ "timeout": "soon" })).is_err());
+        assert!(from_value::<Config>(json!({ "timeout": 1.5 })).is_err());
+    }
+}
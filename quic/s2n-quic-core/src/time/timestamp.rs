@@ -89,9 +89,23 @@ impl Timestamp {
     /// If `earlier` is more recent, the method returns a `Duration` of 0.
     #[inline]
     pub fn saturating_duration_since(self, earlier: Self) -> Duration {
-        self.checked_sub(earlier.as_duration_impl())
-            .map(Self::as_duration_impl)
-            .unwrap_or_default()
+        self.checked_duration_since(earlier).unwrap_or_default()
+    }
+
+    /// Returns the `Duration` which elapsed since an earlier `Timestamp`.
+    /// If `earlier` is more recent, the method returns `None`.
+    #[inline]
+    pub fn checked_duration_since(self, earlier: Self) -> Option<Duration> {
+        self.0
+            .get()
+            .checked_sub(earlier.0.get())
+            .map(Duration::from_micros)
+    }
+
+    /// Returns the absolute `Duration` between two `Timestamp`s, regardless of their order
+    #[inline]
+    pub fn abs_diff(self, other: Self) -> Duration {
+        Duration::from_micros(self.0.get().abs_diff(other.0.get()))
     }
 
     /// Creates a `Timestamp` from a `Duration` since the time source's epoch.
@@ -245,6 +259,21 @@ mod tests {
             Duration::from_millis(0),
             initial.saturating_duration_since(higher)
         );
+
+        // Checked Timestamp sub
+        assert_eq!(
+            Some(Duration::from_millis(200)),
+            higher.checked_duration_since(initial)
+        );
+        assert_eq!(
+            Some(Duration::ZERO),
+            initial.checked_duration_since(initial)
+        );
+        assert_eq!(None, initial.checked_duration_since(higher));
+
+        // Absolute difference
+        assert_eq!(Duration::from_millis(200), higher.abs_diff(initial));
+        assert_eq!(Duration::from_millis(200), initial.abs_diff(higher));
     }
 
     #[test]
@@ -257,4 +286,17 @@ mod tests {
         let initial = Timestamp::from_duration_impl(Duration::from_micros(1u64 << 63));
         timestamp_math(initial);
     }
+
+    #[test]
+    fn large_delta_test() {
+        let min = Timestamp::from_duration_impl(Duration::from_micros(1));
+        let max = Timestamp::from_duration_impl(Duration::from_micros(u64::MAX));
+        let delta = Duration::from_micros(u64::MAX - 1);
+
+        assert_eq!(Some(delta), max.checked_duration_since(min));
+        assert_eq!(None, min.checked_duration_since(max));
+        assert_eq!(delta, max.saturating_duration_since(min));
+        assert_eq!(Duration::ZERO, min.saturating_duration_since(max));
+        assert_eq!(delta, min.abs_diff(max));
+    }
 }
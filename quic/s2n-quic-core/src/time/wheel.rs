@@ -0,0 +1,639 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A hierarchical timing wheel for tracking large numbers of timers
+//!
+//! Time is divided into ticks of a fixed granularity. The wheel has [`LEVELS`] levels of
+//! [`SLOTS`] slots each, where a slot at level `n` spans `SLOTS^n` ticks. Timers are placed in
+//! the lowest level whose slot doesn't contain the current tick, and are moved to lower levels
+//! ("cascaded") once the wheel reaches the start of their slot. Timers beyond the range of the
+//! wheel are kept in an overflow list which is re-examined each time the wheel wraps.
+//!
+//! Timers are stored in a slab and linked into slots through indices, so arming, re-arming and
+//! cancelling a timer is O(1) and never allocates.
+
+use crate::time::{Duration, Timestamp};
+use alloc::vec::Vec;
+
+/// The number of bits used to index a slot in a level
+const SLOT_BITS: u32 = 6;
+
+/// The number of slots in each level of the wheel
+pub const SLOTS: usize = 1 << SLOT_BITS;
+
+/// The number of levels in the wheel
+pub const LEVELS: usize = 6;
+
+const SLOT_MASK: u64 = SLOTS as u64 - 1;
+
+/// The number of bits of ticks covered by the wheel before timers go into the overflow list
+const WHEEL_BITS: u32 = SLOT_BITS * LEVELS as u32;
+
+const LISTS: usize = SLOTS * LEVELS;
+const PENDING: u16 = LISTS as u16;
+const OVERFLOW: u16 = LISTS as u16 + 1;
+const UNLINKED: u16 = u16::MAX;
+
+const NIL: u32 = u32::MAX;
+
+/// Identifies a timer in a [`Wheel`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Key(u32);
+
+#[derive(Debug)]
+struct Entry<T> {
+    value: Option<T>,
+    tick: u64,
+    list: u16,
+    prev: u32,
+    /// The next entry in the list, or the next free entry if the entry is vacant
+    next: u32,
+}
+
+/// A hierarchical timing wheel
+///
+/// Values are inserted into the wheel with [`Wheel::insert`], which returns a [`Key`] that
+/// can then be armed any number of times with [`Wheel::arm`]. Expired timers are returned
+/// by [`Wheel::poll_expired`] in order of their tick.
+///
+/// Timers never expire before their deadline, but may expire up to one tick after it.
+#[derive(Debug)]
+pub struct Wheel<T> {
+    entries: Vec<Entry<T>>,
+    free: u32,
+    len: usize,
+    heads: [u32; LISTS + 2],
+    pending_tail: u32,
+    occupied: [u64; LEVELS],
+    epoch: Timestamp,
+    granularity: u128,
+    elapsed: u64,
+}
+
+impl<T> Wheel<T> {
+    /// Creates a wheel starting at `epoch`, which expires timers with the given `granularity`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `granularity` is zero
+    pub fn new(epoch: Timestamp, granularity: Duration) -> Self {
+        assert!(!granularity.is_zero(), "granularity must be non-zero");
+
+        Self {
+            entries: Vec::new(),
+            free: NIL,
+            len: 0,
+            heads: [NIL; LISTS + 2],
+            pending_tail: NIL,
+            occupied: [0; LEVELS],
+            epoch,
+            granularity: granularity.as_nanos(),
+            elapsed: 0,
+        }
+    }
+
+    /// Returns the number of values in the wheel
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the wheel doesn't contain any values
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Inserts an unarmed timer into the wheel
+    pub fn insert(&mut self, value: T) -> Key {
+        self.len += 1;
+
+        let entry = Entry {
+            value: Some(value),
+            tick: 0,
+            list: UNLINKED,
+            prev: NIL,
+            next: NIL,
+        };
+
+        if self.free != NIL {
+            let index = self.free;
+            self.free = self.entries[index as usize].next;
+            self.entries[index as usize] = entry;
+            return Key(index);
+        }
+
+        let index = self.entries.len() as u32;
+        assert_ne!(index, NIL, "wheel capacity exceeded");
+        self.entries.push(entry);
+        Key(index)
+    }
+
+    /// Removes a timer from the wheel, returning its value
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key has already been removed
+    pub fn remove(&mut self, key: Key) -> T {
+        self.cancel(key);
+
+        let entry = &mut self.entries[key.0 as usize];
+        let value = entry.value.take().expect("invalid timer key");
+        entry.next = self.free;
+        self.free = key.0;
+        self.len -= 1;
+
+        value
+    }
+
+    /// Returns a reference to the value of the timer
+    #[inline]
+    pub fn get(&self, key: Key) -> Option<&T> {
+        self.entries.get(key.0 as usize)?.value.as_ref()
+    }
+
+    /// Returns a mutable reference to the value of the timer
+    #[inline]
+    pub fn get_mut(&mut self, key: Key) -> Option<&mut T> {
+        self.entries.get_mut(key.0 as usize)?.value.as_mut()
+    }
+
+    /// Arms the timer to expire at the given deadline, replacing any previous deadline
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key has been removed
+    pub fn arm(&mut self, key: Key, deadline: Timestamp) {
+        self.cancel(key);
+        let tick = self.deadline_tick(deadline);
+        self.entries[key.0 as usize].tick = tick;
+        self.link(key.0);
+    }
+
+    /// Cancels the timer, if it is armed
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key has been removed
+    pub fn cancel(&mut self, key: Key) {
+        let entry = &self.entries[key.0 as usize];
+        assert!(entry.value.is_some(), "invalid timer key");
+
+        if entry.list != UNLINKED {
+            self.unlink(key.0);
+        }
+    }
+
+    /// Returns `true` if the timer is armed and hasn't been returned by [`Self::poll_expired`]
+    #[inline]
+    pub fn is_armed(&self, key: Key) -> bool {
+        self.entries.get(key.0 as usize).map_or(false, |entry| {
+            entry.value.is_some() && entry.list != UNLINKED
+        })
+    }
+
+    /// Returns the next time the wheel needs to be polled
+    ///
+    /// This is either the earliest tick of a level 0 timer or the start of the slot of a
+    /// higher level which needs to be cascaded, so it never comes after the next deadline.
+    /// The returned value is in the past if expired timers haven't been polled yet.
+    pub fn next_expiration(&self) -> Option<Timestamp> {
+        if self.heads[PENDING as usize] != NIL {
+            return Some(self.tick_timestamp(self.elapsed));
+        }
+
+        let (tick, _list) = self.next_list()?;
+        Some(self.tick_timestamp(tick))
+    }
+
+    /// Returns the next timer which has expired at `now`
+    ///
+    /// Empty slots are skipped using a per-level occupancy bitmap, so the work performed is
+    /// proportional to the number of timers moved rather than the number of ticks elapsed.
+    /// Returned timers are unarmed and may be re-armed immediately.
+    pub fn poll_expired(&mut self, now: Timestamp) -> Option<Key> {
+        let now_tick = self.now_tick(now);
+
+        loop {
+            let index = self.heads[PENDING as usize];
+            if index != NIL {
+                self.unlink(index);
+                return Some(Key(index));
+            }
+
+            match self.next_list() {
+                Some((tick, list)) if tick <= now_tick => self.cascade(list, tick),
+                _ => {
+                    self.elapsed = self.elapsed.max(now_tick);
+                    return None;
+                }
+            }
+        }
+    }
+
+    /// Converts a deadline into a tick, rounding up so timers never expire early
+    fn deadline_tick(&self, deadline: Timestamp) -> u64 {
+        let nanos = deadline.saturating_duration_since(self.epoch).as_nanos();
+        let ticks = (nanos + self.granularity - 1) / self.granularity;
+        ticks.try_into().unwrap_or(u64::MAX)
+    }
+
+    /// Converts the current time into a tick, rounding down
+    fn now_tick(&self, now: Timestamp) -> u64 {
+        let nanos = now.saturating_duration_since(self.epoch).as_nanos();
+        (nanos / self.granularity).try_into().unwrap_or(u64::MAX)
+    }
+
+    fn tick_timestamp(&self, tick: u64) -> Timestamp {
+        let nanos = self.granularity.saturating_mul(tick as u128);
+        self.epoch + Duration::from_nanos(nanos.try_into().unwrap_or(u64::MAX))
+    }
+
+    /// Returns the list a timer at `tick` belongs in, given the current tick
+    fn list_for(&self, tick: u64) -> u16 {
+        if tick <= self.elapsed {
+            return PENDING;
+        }
+
+        let diff = self.elapsed ^ tick;
+
+        // timers outside of the current rotation of the wheel wait for it to wrap
+        if diff >> WHEEL_BITS != 0 {
+            return OVERFLOW;
+        }
+
+        let significant = 63 - (diff | SLOT_MASK).leading_zeros();
+        let level = significant / SLOT_BITS;
+        let slot = (tick >> (level * SLOT_BITS)) & SLOT_MASK;
+
+        (level as usize * SLOTS + slot as usize) as u16
+    }
+
+    /// Returns the earliest occupied list along with the tick it needs to be cascaded at
+    fn next_list(&self) -> Option<(u64, u16)> {
+        // timers in a level are always earlier than timers in any of the levels above it
+        for (level, occupied) in self.occupied.iter().enumerate() {
+            if *occupied == 0 {
+                continue;
+            }
+
+            let shift = level as u32 * SLOT_BITS;
+            let current_slot = (self.elapsed >> shift) & SLOT_MASK;
+            let occupied = occupied & (u64::MAX << current_slot);
+            debug_assert_ne!(occupied, 0, "slots behind the wheel should be empty");
+
+            let slot = occupied.trailing_zeros() as u64;
+            let level_start = self.elapsed & !((1u64 << (shift + SLOT_BITS)) - 1);
+            let tick = level_start + (slot << shift);
+
+            return Some((tick, (level * SLOTS) as u16 + slot as u16));
+        }
+
+        if self.heads[OVERFLOW as usize] != NIL {
+            let tick = ((self.elapsed >> WHEEL_BITS) + 1)
+                .checked_shl(WHEEL_BITS)
+                .unwrap_or(u64::MAX);
+            return Some((tick, OVERFLOW));
+        }
+
+        None
+    }
+
+    /// Advances the wheel to `tick` and redistributes the timers in `list`
+    fn cascade(&mut self, list: u16, tick: u64) {
+        self.elapsed = self.elapsed.max(tick);
+
+        let mut index = self.heads[list as usize];
+        self.heads[list as usize] = NIL;
+        if (list as usize) < LISTS {
+            self.occupied[list as usize / SLOTS] &= !(1 << (list as usize % SLOTS));
+        }
+
+        while index != NIL {
+            let entry = &mut self.entries[index as usize];
+            let next = entry.next;
+            entry.list = UNLINKED;
+            self.link(index);
+            index = next;
+        }
+    }
+
+    fn link(&mut self, index: u32) {
+        let list = self.list_for(self.entries[index as usize].tick);
+
+        if list == PENDING {
+            // the pending list is appended to so timers are returned in order
+            let tail = self.pending_tail;
+            let entry = &mut self.entries[index as usize];
+            entry.list = list;
+            entry.prev = tail;
+            entry.next = NIL;
+
+            if tail == NIL {
+                self.heads[PENDING as usize] = index;
+            } else {
+                self.entries[tail as usize].next = index;
+            }
+            self.pending_tail = index;
+            return;
+        }
+
+        let head = self.heads[list as usize];
+        let entry = &mut self.entries[index as usize];
+        entry.list = list;
+        entry.prev = NIL;
+        entry.next = head;
+
+        if head != NIL {
+            self.entries[head as usize].prev = index;
+        }
+        self.heads[list as usize] = index;
+
+        if (list as usize) < LISTS {
+            self.occupied[list as usize / SLOTS] |= 1 << (list as usize % SLOTS);
+        }
+    }
+
+    fn unlink(&mut self, index: u32) {
+        let entry = &mut self.entries[index as usize];
+        let list = entry.list;
+        let prev = entry.prev;
+        let next = entry.next;
+        entry.list = UNLINKED;
+        entry.prev = NIL;
+        entry.next = NIL;
+
+        debug_assert_ne!(list, UNLINKED);
+
+        if prev == NIL {
+            self.heads[list as usize] = next;
+        } else {
+            self.entries[prev as usize].next = next;
+        }
+
+        if next != NIL {
+            self.entries[next as usize].prev = prev;
+        } else if list == PENDING {
+            self.pending_tail = prev;
+        }
+
+        if (list as usize) < LISTS && self.heads[list as usize] == NIL {
+            self.occupied[list as usize / SLOTS] &= !(1 << (list as usize % SLOTS));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::clock::testing;
+    use bolero::{check, generator::*};
+    use std::collections::BTreeSet;
+
+    const GRANULARITY: Duration = Duration::from_millis(1);
+
+    fn wheel() -> (Wheel<u64>, Timestamp) {
+        let epoch = testing::now();
+        (Wheel::new(epoch, GRANULARITY), epoch)
+    }
+
+    fn at(epoch: Timestamp, tick: u64) -> Timestamp {
+        epoch + Duration::from_millis(tick)
+    }
+
+    fn expired(wheel: &mut Wheel<u64>, now: Timestamp) -> Vec<u64> {
+        let mut expired = vec![];
+        while let Some(key) = wheel.poll_expired(now) {
+            expired.push(*wheel.get(key).unwrap());
+        }
+        expired
+    }
+
+    #[test]
+    fn expiration_test() {
+        let (mut wheel, epoch) = wheel();
+        let key = wheel.insert(10);
+        assert!(!wheel.is_armed(key));
+        assert_eq!(wheel.next_expiration(), None);
+
+        wheel.arm(key, at(epoch, 10));
+        assert!(wheel.is_armed(key));
+        assert_eq!(wheel.next_expiration(), Some(at(epoch, 10)));
+
+        assert!(expired(&mut wheel, at(epoch, 9)).is_empty());
+        assert_eq!(expired(&mut wheel, at(epoch, 10)), [10]);
+        assert!(!wheel.is_armed(key));
+        assert_eq!(wheel.next_expiration(), None);
+
+        // deadlines between ticks are rounded up
+        wheel.arm(key, at(epoch, 20) + Duration::from_micros(1));
+        assert!(expired(&mut wheel, at(epoch, 21) - Duration::from_micros(1)).is_empty());
+        assert_eq!(expired(&mut wheel, at(epoch, 21)), [10]);
+
+        // deadlines in the past expire on the next poll
+        wheel.arm(key, epoch);
+        assert_eq!(wheel.next_expiration(), Some(at(epoch, 21)));
+        assert_eq!(expired(&mut wheel, at(epoch, 21)), [10]);
+
+        assert_eq!(wheel.remove(key), 10);
+        assert!(wheel.is_empty());
+        assert_eq!(wheel.get(key), None);
+    }
+
+    #[test]
+    fn cancel_test() {
+        let (mut wheel, epoch) = wheel();
+        let a = wheel.insert(1);
+        let b = wheel.insert(2);
+
+        wheel.arm(a, at(epoch, 5));
+        wheel.arm(b, at(epoch, 5000));
+        wheel.cancel(a);
+        assert!(!wheel.is_armed(a));
+        assert!(wheel.next_expiration().unwrap() > at(epoch, 5));
+
+        wheel.cancel(b);
+        assert_eq!(wheel.next_expiration(), None);
+        assert!(expired(&mut wheel, at(epoch, 10_000)).is_empty());
+
+        // cancelling an unarmed timer is a no-op
+        wheel.cancel(a);
+    }
+
+    #[test]
+    fn order_test() {
+        let (mut wheel, epoch) = wheel();
+        for tick in [300u64, 7, 64, 4096, 63, 65, 1] {
+            let key = wheel.insert(tick);
+            wheel.arm(key, at(epoch, tick));
+        }
+
+        assert_eq!(
+            expired(&mut wheel, at(epoch, 10_000)),
+            [1, 7, 63, 64, 65, 300, 4096]
+        );
+    }
+
+    /// Checks that timers around the boundaries of each level expire at exactly their tick
+    #[test]
+    fn cascade_boundary_test() {
+        let mut ticks = vec![];
+        for level in 1..=LEVELS as u32 {
+            let boundary = 1u64 << (level * SLOT_BITS);
+            ticks.extend([boundary - 1, boundary, boundary + 1]);
+        }
+
+        for start in [0, 1, 62, 63, 64, 4095] {
+            for &offset in &ticks {
+                let (mut wheel, epoch) = wheel();
+
+                // advance the wheel so the timers are armed relative to a non-zero tick
+                assert!(expired(&mut wheel, at(epoch, start)).is_empty());
+
+                let tick = start + offset;
+                let key = wheel.insert(tick);
+                wheel.arm(key, at(epoch, tick));
+
+                let next = wheel.next_expiration().unwrap();
+                assert!(next <= at(epoch, tick), "start={start} offset={offset}");
+                assert!(expired(&mut wheel, at(epoch, tick - 1)).is_empty());
+                assert_eq!(
+                    expired(&mut wheel, at(epoch, tick)),
+                    [tick],
+                    "start={start} offset={offset}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn overflow_test() {
+        let (mut wheel, epoch) = wheel();
+        let tick = (1u64 << WHEEL_BITS) * 3 + 5;
+        let key = wheel.insert(tick);
+        wheel.arm(key, at(epoch, tick));
+
+        assert_eq!(wheel.next_expiration(), Some(at(epoch, 1u64 << WHEEL_BITS)));
+        assert!(expired(&mut wheel, at(epoch, tick - 1)).is_empty());
+        assert_eq!(expired(&mut wheel, at(epoch, tick)), [tick]);
+    }
+
+    #[test]
+    fn rearm_test() {
+        let (mut wheel, epoch) = wheel();
+        let keys: Vec<_> = (0..100).map(|value| wheel.insert(value)).collect();
+        let capacity = wheel.entries.capacity();
+
+        for round in 0..1000u64 {
+            for key in &keys {
+                wheel.arm(*key, at(epoch, round * 7 % 5000 + 1));
+            }
+        }
+
+        // re-arming moves existing entries rather than allocating new ones
+        assert_eq!(wheel.entries.capacity(), capacity);
+        assert_eq!(wheel.len(), 100);
+
+        // removed slots are reused
+        wheel.remove(keys[3]);
+        let key = wheel.insert(1000);
+        assert_eq!(key, keys[3]);
+        assert_eq!(wheel.entries.len(), 100);
+    }
+
+    #[derive(Clone, Copy, Debug, TypeGenerator)]
+    enum Operation {
+        Arm { timer: u8, delay: u32 },
+        Cancel { timer: u8 },
+        Advance { delay: u32 },
+    }
+
+    /// Compares the wheel against a simple set of deadlines
+    #[test]
+    fn differential_test() {
+        check!()
+            .with_type::<Vec<Operation>>()
+            .for_each(|operations| {
+                const TIMERS: usize = 16;
+
+                let (mut wheel, epoch) = wheel();
+                let keys: Vec<_> = (0..TIMERS as u64)
+                    .map(|value| wheel.insert(value))
+                    .collect();
+
+                let mut now = 0u64;
+                let mut deadlines = [None; TIMERS];
+                let mut oracle = BTreeSet::new();
+
+                for operation in operations.iter().copied() {
+                    match operation {
+                        Operation::Arm { timer, delay } => {
+                            let timer = timer as usize % TIMERS;
+                            // exercise all of the levels, along with the overflow list
+                            let delay = (delay as u64 % 1024) << (delay % 7 * 5);
+                            let tick = now + delay;
+
+                            if let Some(tick) = deadlines[timer].take() {
+                                oracle.remove(&(tick, timer));
+                            }
+                            deadlines[timer] = Some(tick);
+                            oracle.insert((tick, timer));
+                            wheel.arm(keys[timer], at(epoch, tick));
+                        }
+                        Operation::Cancel { timer } => {
+                            let timer = timer as usize % TIMERS;
+                            if let Some(tick) = deadlines[timer].take() {
+                                oracle.remove(&(tick, timer));
+                            }
+                            wheel.cancel(keys[timer]);
+                        }
+                        Operation::Advance { delay } => {
+                            now += (delay as u64 % 1024) << (delay % 7 * 5);
+
+                            let mut actual: Vec<_> = expired(&mut wheel, at(epoch, now))
+                                .into_iter()
+                                .map(|timer| timer as usize)
+                                .collect();
+
+                            let mut expected = vec![];
+                            while let Some(&(tick, timer)) = oracle.iter().next() {
+                                if tick > now {
+                                    break;
+                                }
+                                oracle.remove(&(tick, timer));
+                                deadlines[timer] = None;
+                                expected.push((tick, timer));
+                            }
+
+                            // timers are returned in tick order, while timers in the same tick
+                            // are unordered
+                            let ticks: Vec<_> = actual
+                                .iter()
+                                .map(|actual| {
+                                    expected
+                                        .iter()
+                                        .find(|(_, timer)| timer == actual)
+                                        .expect("unexpected expiration")
+                                        .0
+                                })
+                                .collect();
+                            assert!(ticks.windows(2).all(|w| w[0] <= w[1]));
+
+                            actual.sort_unstable();
+                            let mut expected: Vec<_> =
+                                expected.into_iter().map(|(_, timer)| timer).collect();
+                            expected.sort_unstable();
+                            assert_eq!(actual, expected);
+                        }
+                    }
+
+                    if let Some(&(tick, _)) = oracle.iter().next() {
+                        let next = wheel.next_expiration().unwrap();
+                        assert!(next <= at(epoch, tick));
+                    } else {
+                        assert_eq!(wheel.next_expiration(), None);
+                    }
+                }
+            });
+    }
+}
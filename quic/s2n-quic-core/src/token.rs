@@ -7,6 +7,12 @@ use crate::{
     inet, random,
 };
 
+#[cfg(feature = "alloc")]
+pub mod store;
+
+#[cfg(feature = "alloc")]
+pub use store::Store;
+
 #[non_exhaustive]
 pub struct Context<'a> {
     pub remote_address: SocketAddress<'a>,
@@ -58,6 +64,27 @@ pub trait Format: 'static + Send {
         context: &mut Context<'_>,
         token: &[u8],
     ) -> Option<connection::InitialId>;
+
+    /// Returns `true` if a token delivered in a NEW_TOKEN frame is valid.
+    ///
+    /// The endpoint calls this instead of [`Format::validate_token`] for tokens that
+    /// [`Format::token_source`] identifies as [`Source::NewTokenFrame`]. Callers should detect
+    /// duplicate tokens and treat them as invalid. The default implementation doesn't accept
+    /// NEW_TOKEN frame tokens.
+    fn validate_new_token(&mut self, context: &mut Context<'_>, token: &[u8]) -> bool {
+        let _ = (context, token);
+        false
+    }
+
+    /// Returns how the token was provided to the client
+    ///
+    /// This is used to report the token to the endpoint limits. Tokens that can't be
+    /// identified are reported as Retry tokens, which is what the default implementation does
+    /// for all tokens.
+    fn token_source(&self, token: &[u8]) -> Source {
+        let _ = token;
+        Source::RetryPacket
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
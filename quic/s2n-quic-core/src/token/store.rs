@@ -0,0 +1,190 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Client-side storage for address validation tokens
+//!
+//! Servers can issue tokens in NEW_TOKEN frames which the client presents in the Initial
+//! packet of a future connection to the same server. This proves ownership of the client
+//! address and allows the server to skip the Retry round trip.
+
+use crate::application::ServerName;
+use alloc::{collections::VecDeque, vec::Vec};
+
+/// Stores address validation tokens received from servers
+///
+/// Implementations can persist the tokens to allow them to be used across endpoint restarts.
+pub trait Store: 'static + Send {
+    /// Called when the server identified by `server_name` issued a token in a NEW_TOKEN frame
+    fn on_new_token(&mut self, server_name: &ServerName, token: &[u8]);
+
+    /// Removes and returns a token to include in the next Initial packet sent to `server_name`
+    fn take_token(&mut self, server_name: &ServerName) -> Option<Vec<u8>>;
+}
+
+//= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
+//# Clients that want to break continuity of identity with a server can
+//# discard tokens provided using the NEW_TOKEN frame.
+/// A token store which discards all of the tokens issued by servers
+#[derive(Debug, Default)]
+pub struct Disabled(());
+
+impl Store for Disabled {
+    #[inline]
+    fn on_new_token(&mut self, _server_name: &ServerName, _token: &[u8]) {}
+
+    #[inline]
+    fn take_token(&mut self, _server_name: &ServerName) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+/// The default number of servers for which a token is retained
+pub const DEFAULT_CAPACITY: usize = 256;
+
+/// An in-memory store which retains the most recent token for a bounded number of servers
+///
+/// Once the capacity is reached, the server which issued its token the longest time ago is
+/// evicted.
+#[derive(Debug)]
+pub struct Bounded {
+    capacity: usize,
+    /// Tokens ordered from the least to the most recently issued
+    entries: VecDeque<(ServerName, Vec<u8>)>,
+}
+
+impl Default for Bounded {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl Bounded {
+    /// Creates a store which retains tokens for up to `capacity` servers
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// Returns the number of servers with a stored token
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no tokens are stored
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[inline]
+    fn position(&self, server_name: &ServerName) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|(name, _)| name == server_name)
+    }
+}
+
+impl Store for Bounded {
+    fn on_new_token(&mut self, server_name: &ServerName, token: &[u8]) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
+        //# For a client, this ambiguity means that
+        //# sending the most recent unused token is most likely to be effective.
+        if let Some(index) = self.position(server_name) {
+            self.entries.remove(index);
+        }
+
+        while self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+
+        self.entries
+            .push_back((server_name.clone(), token.to_vec()));
+    }
+
+    fn take_token(&mut self, server_name: &ServerName) -> Option<Vec<u8>> {
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
+        //# A client SHOULD NOT reuse a token from a NEW_TOKEN frame for
+        //# different connection attempts.
+        let index = self.position(server_name)?;
+        self.entries.remove(index).map(|(_, token)| token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_once_test() {
+        let mut store = Bounded::default();
+        let server: ServerName = "example.com".into();
+
+        assert_eq!(store.take_token(&server), None);
+
+        store.on_new_token(&server, &[1, 2, 3]);
+        assert_eq!(store.take_token(&server), Some(vec![1, 2, 3]));
+        assert_eq!(store.take_token(&server), None);
+    }
+
+    #[test]
+    fn latest_token_test() {
+        let mut store = Bounded::default();
+        let server: ServerName = "example.com".into();
+
+        store.on_new_token(&server, &[1]);
+        store.on_new_token(&server, &[2]);
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.take_token(&server), Some(vec![2]));
+    }
+
+    #[test]
+    fn per_server_test() {
+        let mut store = Bounded::default();
+        let a: ServerName = "a.example.com".into();
+        let b: ServerName = "b.example.com".into();
+
+        store.on_new_token(&a, &[1]);
+        assert_eq!(store.take_token(&b), None);
+        assert_eq!(store.take_token(&a), Some(vec![1]));
+    }
+
+    #[test]
+    fn capacity_test() {
+        let mut store = Bounded::new(2);
+        let a: ServerName = "a.example.com".into();
+        let b: ServerName = "b.example.com".into();
+        let c: ServerName = "c.example.com".into();
+
+        store.on_new_token(&a, &[1]);
+        store.on_new_token(&b, &[2]);
+        // refreshing a token moves the server to the back of the eviction order
+        store.on_new_token(&a, &[3]);
+        store.on_new_token(&c, &[4]);
+
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.take_token(&b), None);
+        assert_eq!(store.take_token(&a), Some(vec![3]));
+        assert_eq!(store.take_token(&c), Some(vec![4]));
+        assert!(store.is_empty());
+
+        let mut store = Bounded::new(0);
+        store.on_new_token(&a, &[1]);
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn disabled_test() {
+        let mut store = Disabled::default();
+        let server: ServerName = "example.com".into();
+
+        store.on_new_token(&server, &[1]);
+        assert_eq!(store.take_token(&server), None);
+    }
+}
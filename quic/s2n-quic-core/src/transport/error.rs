@@ -317,6 +317,13 @@ impl_errors! {
     /// confidentiality or integrity limit for the AEAD algorithm used by
     /// the given connection.
     AEAD_LIMIT_REACHED = 0xf.with_frame_type(UNKNOWN_FRAME_TYPE),
+
+    // https://www.rfc-editor.org/rfc/rfc9368#section-10.2
+    // VERSION_NEGOTIATION_ERROR (0x11): Error signaling version downgrade
+    // or other version negotiation failures.
+    /// An endpoint detected a version downgrade or
+    /// another version negotiation failure.
+    VERSION_NEGOTIATION_ERROR = 0x11.with_frame_type(UNKNOWN_FRAME_TYPE),
 }
 
 //= https://www.rfc-editor.org/rfc/rfc9000#section-20.1
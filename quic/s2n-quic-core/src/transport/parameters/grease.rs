@@ -0,0 +1,86 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{TransportParameterId, TransportParameterLength};
+use crate::{random, varint::VarInt};
+use s2n_codec::{Encoder, EncoderValue};
+
+//= https://www.rfc-editor.org/rfc/rfc9000#section-18.1
+//# Transport parameters with an identifier of the form "31 * N + 27" for
+//# integer values of N are reserved to exercise the requirement that
+//# unknown transport parameters be ignored.  These transport parameters
+//# have no semantics and can carry arbitrary values.
+
+/// The maximum length of the value sent with a reserved transport parameter
+const MAX_VALUE_LEN: usize = 16;
+
+/// A reserved transport parameter with an arbitrary value, which peers are required to ignore
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Grease {
+    id: TransportParameterId,
+    value: [u8; MAX_VALUE_LEN],
+    len: u8,
+}
+
+impl Grease {
+    /// Generates a reserved transport parameter with a random identifier and value
+    pub fn new<R: random::Generator + ?Sized>(random_generator: &mut R) -> Self {
+        let mut n = [0; 4];
+        random_generator.public_random_fill(&mut n);
+        let n = u32::from_le_bytes(n) as u64;
+        let id = VarInt::new(31 * n + 27).expect("reserved ids are always less than 2^62");
+
+        let len = random::gen_range_biased(random_generator, 0..=MAX_VALUE_LEN);
+        let mut value = [0; MAX_VALUE_LEN];
+        random_generator.public_random_fill(&mut value[..len]);
+
+        Self {
+            id,
+            value,
+            len: len as u8,
+        }
+    }
+
+    /// Returns `true` if the transport parameter identifier is reserved for greasing
+    #[inline]
+    pub fn is_reserved(id: TransportParameterId) -> bool {
+        id.as_u64()
+            .checked_sub(27)
+            .map_or(false, |value| value % 31 == 0)
+    }
+
+    /// The identifier of the transport parameter
+    #[inline]
+    pub fn id(&self) -> TransportParameterId {
+        self.id
+    }
+
+    /// The arbitrary value of the transport parameter
+    #[inline]
+    pub fn value(&self) -> &[u8] {
+        &self.value[..self.len as usize]
+    }
+}
+
+impl EncoderValue for Grease {
+    fn encode<E: Encoder>(&self, buffer: &mut E) {
+        buffer.encode(&self.id);
+        buffer.encode_with_len_prefix::<TransportParameterLength, _>(&self.value());
+    }
+}
+
+/// Encodes transport parameters followed by an optional reserved transport parameter
+#[derive(Clone, Copy, Debug)]
+pub struct WithGrease<'a, T> {
+    pub parameters: &'a T,
+    pub grease: Option<Grease>,
+}
+
+impl<'a, T: EncoderValue> EncoderValue for WithGrease<'a, T> {
+    fn encode<E: Encoder>(&self, buffer: &mut E) {
+        buffer.encode(self.parameters);
+        if let Some(grease) = self.grease.as_ref() {
+            buffer.encode(grease);
+        }
+    }
+}
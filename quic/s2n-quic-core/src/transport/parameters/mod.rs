@@ -295,7 +295,7 @@ macro_rules! transport_parameter {
         impl TransportParameter for $name {
             type CodecValue = $encodable_type;
 
-            const ID: TransportParameterId = TransportParameterId::from_u8($tag);
+            const ID: TransportParameterId = TransportParameterId::from_u32($tag);
 
             fn from_codec_value(value: Self::CodecValue) -> Self {
                 Self(value)
@@ -851,6 +851,255 @@ impl TransportParameterValidator for MaxAckDelay {
     }
 }
 
+// The min_ack_delay transport parameter is defined by the ACK frequency extension
+// (https://datatracker.ietf.org/doc/html/draft-ietf-quic-ack-frequency#section-3)
+//
+// min_ack_delay (0xff04de1b): A variable-length integer representing the minimum
+//    amount of time, in microseconds, that the endpoint sending this value is
+//    willing to delay an acknowledgment. Values of 2^24 or greater are invalid.
+//    Advertising this transport parameter indicates support for receiving
+//    ACK_FREQUENCY and IMMEDIATE_ACK frames.
+
+transport_parameter!(MinAckDelay(VarInt), 0xff04de1b);
+
+impl MinAckDelay {
+    /// Converts the min_ack_delay into a `core::time::Duration`
+    pub const fn as_duration(self) -> Duration {
+        Duration::from_micros(self.0.as_u64())
+    }
+}
+
+impl TryFrom<Duration> for MinAckDelay {
+    type Error = ValidationError;
+
+    fn try_from(value: Duration) -> Result<Self, Self::Error> {
+        let value: VarInt = value.as_micros().try_into()?;
+        value.try_into()
+    }
+}
+
+impl From<MinAckDelay> for Duration {
+    fn from(value: MinAckDelay) -> Self {
+        value.as_duration()
+    }
+}
+
+impl TransportParameterValidator for MinAckDelay {
+    fn validate(self) -> Result<Self, DecoderError> {
+        decoder_invariant!(
+            *self.0 < 2u64.pow(24),
+            "min_ack_delay must be less than 2^24"
+        );
+        Ok(self)
+    }
+}
+
+optional_transport_parameter!(MinAckDelay);
+
+// The version_information transport parameter is defined by compatible version negotiation
+// (https://www.rfc-editor.org/rfc/rfc9368#section-3)
+//
+// Version Information {
+//   Chosen Version (32),
+//   Available Versions (32) ...,
+// }
+
+/// The maximum number of available versions that are stored from a peer's version_information
+const MAX_AVAILABLE_VERSIONS: usize = 8;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct VersionInformation {
+    chosen_version: u32,
+    available_versions: [u32; MAX_AVAILABLE_VERSIONS],
+    available_versions_len: u8,
+}
+
+impl VersionInformation {
+    /// Creates version information with the version in use and the versions the
+    /// endpoint supports, in order of preference
+    pub fn new(chosen_version: u32, available_versions: &[u32]) -> Result<Self, ValidationError> {
+        if available_versions.len() > MAX_AVAILABLE_VERSIONS {
+            return Err(ValidationError("too many available versions"));
+        }
+
+        let mut value = Self {
+            chosen_version,
+            available_versions: [0; MAX_AVAILABLE_VERSIONS],
+            available_versions_len: 0,
+        };
+
+        for version in available_versions.iter().copied() {
+            value.push(version);
+        }
+
+        value.validate()?;
+
+        Ok(value)
+    }
+
+    /// The version of the packets in use by the endpoint
+    #[inline]
+    pub fn chosen_version(&self) -> u32 {
+        self.chosen_version
+    }
+
+    /// The versions supported by the endpoint
+    ///
+    /// Reserved versions sent by a peer are omitted, as well as any versions beyond the first 8.
+    #[inline]
+    pub fn available_versions(&self) -> &[u32] {
+        &self.available_versions[..self.available_versions_len as usize]
+    }
+
+    /// Validates the version information a client received from the server
+    ///
+    /// `version` is the version of the connection's long header packets and `supported_versions`
+    /// are the versions the client supports, in order of preference. If the client received a
+    /// Version Negotiation packet before using `version`, `after_version_negotiation` is set
+    /// and the server's available versions are checked for a downgrade.
+    pub fn validate_server(
+        &self,
+        version: u32,
+        supported_versions: &[u32],
+        after_version_negotiation: bool,
+    ) -> Result<(), crate::transport::Error> {
+        // https://www.rfc-editor.org/rfc/rfc9368#section-4
+        // Clients MUST validate that the server's Chosen Version is equal to the
+        // negotiated version; if they do not match, the client MUST close the
+        // connection with a version negotiation error.
+        if self.chosen_version != version {
+            return Err(crate::transport::Error::VERSION_NEGOTIATION_ERROR
+                .with_reason("chosen version does not match the negotiated version"));
+        }
+
+        // https://www.rfc-editor.org/rfc/rfc9368#section-4
+        // If the client has reacted to a Version Negotiation packet, the client MUST
+        // validate that it would have selected the same version based on the server's
+        // Available Versions; if not, the client MUST close the connection with a
+        // version negotiation error.
+        if after_version_negotiation {
+            let expected = supported_versions
+                .iter()
+                .find(|supported| self.available_versions().contains(supported));
+
+            if expected != Some(&version) {
+                return Err(crate::transport::Error::VERSION_NEGOTIATION_ERROR
+                    .with_reason("version downgrade detected"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates the version information a server received from the client
+    ///
+    /// `version` is the version of the long header packets the client sent. Since the server
+    /// doesn't switch versions, the client's Chosen Version must match it.
+    pub fn validate_client(&self, version: u32) -> Result<(), crate::transport::Error> {
+        // The client sets its Chosen Version to the version of its first Initial packet, so a
+        // mismatch means the packet or the parameter was tampered with
+        // (https://www.rfc-editor.org/rfc/rfc9368#section-4)
+        if self.chosen_version != version {
+            return Err(crate::transport::Error::VERSION_NEGOTIATION_ERROR
+                .with_reason("chosen version does not match the original version"));
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    fn push(&mut self, version: u32) {
+        if let Some(slot) = self
+            .available_versions
+            .get_mut(self.available_versions_len as usize)
+        {
+            *slot = version;
+            self.available_versions_len += 1;
+        }
+    }
+}
+
+impl core::fmt::Debug for VersionInformation {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("VersionInformation")
+            .field("chosen_version", &self.chosen_version)
+            .field("available_versions", &self.available_versions())
+            .finish()
+    }
+}
+
+impl TransportParameter for VersionInformation {
+    type CodecValue = Self;
+
+    const ID: TransportParameterId = TransportParameterId::from_u8(0x11);
+
+    fn from_codec_value(value: Self) -> Self {
+        value
+    }
+
+    fn try_into_codec_value(&self) -> Option<&Self> {
+        Some(self)
+    }
+
+    fn default_value() -> Self {
+        unimplemented!(
+            "VersionInformation is an optional transport parameter, so the default is None"
+        )
+    }
+}
+
+impl TransportParameterValidator for VersionInformation {
+    fn validate(self) -> Result<Self, DecoderError> {
+        // https://www.rfc-editor.org/rfc/rfc9368#section-3
+        // If an endpoint receives a Chosen Version equal to zero, or any Available
+        // Version equal to zero, it MUST treat it as a parsing failure.
+        decoder_invariant!(self.chosen_version != 0, "chosen version cannot be zero");
+        decoder_invariant!(
+            !self.available_versions().contains(&0),
+            "available versions cannot be zero"
+        );
+        Ok(self)
+    }
+}
+
+decoder_value!(
+    impl<'a> VersionInformation {
+        fn decode(buffer: Buffer) -> Result<Self> {
+            let (chosen_version, mut buffer) = buffer.decode::<u32>()?;
+
+            let mut value = Self {
+                chosen_version,
+                available_versions: [0; MAX_AVAILABLE_VERSIONS],
+                available_versions_len: 0,
+            };
+
+            while !buffer.is_empty() {
+                let (version, remaining) = buffer.decode::<u32>()?;
+                // zero is checked here since it could be past the stored versions
+                decoder_invariant!(version != 0, "available versions cannot be zero");
+                // reserved versions are only used for greasing so don't take up space
+                if !crate::packet::version::is_reserved(version) {
+                    value.push(version);
+                }
+                buffer = remaining;
+            }
+
+            Ok((value, buffer))
+        }
+    }
+);
+
+impl EncoderValue for VersionInformation {
+    fn encode<E: Encoder>(&self, buffer: &mut E) {
+        buffer.encode(&self.chosen_version);
+        for version in self.available_versions() {
+            buffer.encode(version);
+        }
+    }
+}
+
+optional_transport_parameter!(VersionInformation);
+
 //= https://www.rfc-editor.org/rfc/rfc9000#section-18.2
 //# disable_active_migration (0x0c): The disable active migration
 //#    transport parameter is included if the endpoint does not support
@@ -1189,6 +1438,9 @@ impl<
 mod disabled_parameter;
 pub use disabled_parameter::DisabledParameter;
 
+mod grease;
+pub use grease::{Grease, WithGrease};
+
 /// Specific TransportParameters sent by the client endpoint
 pub type ClientTransportParameters = TransportParameters<
     DisabledParameter<OriginalDestinationConnectionId>,
@@ -1388,6 +1640,16 @@ macro_rules! impl_transport_parameters {
                     }
                 }
 
+                // https://datatracker.ietf.org/doc/html/draft-ietf-quic-ack-frequency#section-3
+                // Receiving a min_ack_delay that is greater than the max_ack_delay MUST be
+                // treated as a connection error of type TRANSPORT_PARAMETER_ERROR.
+                if let Some(min_ack_delay) = parameters.min_ack_delay {
+                    s2n_codec::decoder_invariant!(
+                        min_ack_delay.as_duration() <= parameters.max_ack_delay.as_duration(),
+                        "min_ack_delay cannot be greater than max_ack_delay"
+                    );
+                }
+
                 Ok(parameters)
             }
         }
@@ -1412,6 +1674,8 @@ impl_transport_parameters!(
         max_datagram_frame_size: MaxDatagramFrameSize,
         ack_delay_exponent: AckDelayExponent,
         max_ack_delay: MaxAckDelay,
+        min_ack_delay: Option<MinAckDelay>,
+        version_information: Option<VersionInformation>,
         migration_support: MigrationSupport,
         active_connection_id_limit: ActiveConnectionIdLimit,
         original_destination_connection_id: OriginalDestinationConnectionId,
@@ -1466,5 +1730,8 @@ impl<
         load!(ack_delay_exponent, ack_delay_exponent);
         load!(max_active_connection_ids, active_connection_id_limit);
         load!(max_datagram_frame_size, max_datagram_frame_size);
+
+        // the min_ack_delay is capped at the max_ack_delay
+        self.min_ack_delay = limits.min_ack_delay();
     }
 }
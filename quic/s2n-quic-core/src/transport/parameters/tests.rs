@@ -68,6 +68,8 @@ fn server_transport_parameters() -> ServerTransportParameters {
         max_datagram_frame_size: MaxDatagramFrameSize::new(0u16).unwrap(),
         ack_delay_exponent: 2u8.try_into().unwrap(),
         max_ack_delay: integer_value.try_into().unwrap(),
+        min_ack_delay: None,
+        version_information: None,
         migration_support: MigrationSupport::Disabled,
         active_connection_id_limit: integer_value.try_into().unwrap(),
         original_destination_connection_id: Some([1, 2, 3, 4, 5, 6, 7, 8][..].try_into().unwrap()),
@@ -110,6 +112,8 @@ fn client_transport_parameters() -> ClientTransportParameters {
         max_datagram_frame_size: MaxDatagramFrameSize::new(0u16).unwrap(),
         ack_delay_exponent: 2u8.try_into().unwrap(),
         max_ack_delay: integer_value.try_into().unwrap(),
+        min_ack_delay: None,
+        version_information: None,
         migration_support: MigrationSupport::Disabled,
         active_connection_id_limit: integer_value.try_into().unwrap(),
         original_destination_connection_id: Default::default(),
@@ -199,3 +203,189 @@ fn compute_data_window_test() {
         37_500_000
     );
 }
+
+#[test]
+fn min_ack_delay_test() {
+    let mut value = client_transport_parameters();
+    value.min_ack_delay = Some(MinAckDelay::try_from(Duration::from_millis(1)).unwrap());
+    assert_codec_round_trip_value!(ClientTransportParameters, value);
+
+    // values of 2^24 or greater are invalid
+    assert!(MinAckDelay::new(2u64.pow(24) - 1).is_some());
+    assert!(MinAckDelay::new(2u64.pow(24)).is_none());
+
+    // the min_ack_delay can't exceed the max_ack_delay
+    value.max_ack_delay = MaxAckDelay::try_from(Duration::from_millis(1)).unwrap();
+    value.min_ack_delay = Some(MinAckDelay::try_from(Duration::from_micros(1001)).unwrap());
+
+    let mut buffer = vec![0; 32 * 1024];
+    let mut encoder = s2n_codec::EncoderBuffer::new(&mut buffer);
+    encoder.encode(&value);
+    let (encoded, _) = encoder.split_off();
+    assert!(ClientTransportParameters::decode(DecoderBuffer::new(encoded)).is_err());
+}
+
+#[test]
+fn version_information_test() {
+    use crate::packet::version::{QUIC_VERSION_1, QUIC_VERSION_2};
+
+    let mut value = client_transport_parameters();
+    value.version_information =
+        Some(VersionInformation::new(QUIC_VERSION_1, &[QUIC_VERSION_2, QUIC_VERSION_1]).unwrap());
+    assert_codec_round_trip_value!(ClientTransportParameters, value);
+
+    let mut value = server_transport_parameters();
+    value.version_information =
+        Some(VersionInformation::new(QUIC_VERSION_2, &[QUIC_VERSION_1, QUIC_VERSION_2]).unwrap());
+    assert_codec_round_trip_value!(ServerTransportParameters, value);
+
+    // reserved versions are used for greasing and aren't stored when received
+    let info = VersionInformation::new(QUIC_VERSION_1, &[0x1a2a_3a4a, QUIC_VERSION_1]).unwrap();
+    assert_eq!(info.available_versions(), &[0x1a2a_3a4a, QUIC_VERSION_1]);
+    let mut buffer = vec![0x11, 12];
+    buffer.extend_from_slice(&info.encode_to_vec());
+    let (value, _) = ClientTransportParameters::decode(DecoderBuffer::new(&buffer)).unwrap();
+    assert_eq!(
+        value.version_information.unwrap().available_versions(),
+        &[QUIC_VERSION_1]
+    );
+
+    // zero versions are invalid
+    assert!(VersionInformation::new(0, &[QUIC_VERSION_1]).is_err());
+    assert!(VersionInformation::new(QUIC_VERSION_1, &[0]).is_err());
+    assert!(VersionInformation::new(QUIC_VERSION_1, &[QUIC_VERSION_1; 9]).is_err());
+
+    // the value length must be a multiple of 4 and the chosen version is required
+    for value in [&[][..], &[0, 0, 0, 1, 0, 0][..], &[0, 0, 0, 0][..]] {
+        let mut buffer = vec![0x11, value.len() as u8];
+        buffer.extend_from_slice(value);
+        assert!(ClientTransportParameters::decode(DecoderBuffer::new(&buffer)).is_err());
+    }
+
+    // versions past the stored limit are ignored
+    let mut buffer = vec![0x11, 4 * 11];
+    for _ in 0..11 {
+        buffer.extend_from_slice(&QUIC_VERSION_1.to_be_bytes());
+    }
+    let (value, _) = ClientTransportParameters::decode(DecoderBuffer::new(&buffer)).unwrap();
+    let info = value.version_information.unwrap();
+    assert_eq!(info.chosen_version(), QUIC_VERSION_1);
+    assert_eq!(info.available_versions(), &[QUIC_VERSION_1; 8]);
+}
+
+#[test]
+fn version_downgrade_test() {
+    use crate::packet::version::{QUIC_VERSION_1, QUIC_VERSION_2};
+
+    let client_versions = [QUIC_VERSION_2, QUIC_VERSION_1];
+
+    // a v1-only server
+    let server = VersionInformation::new(QUIC_VERSION_1, &[QUIC_VERSION_1]).unwrap();
+    assert!(server
+        .validate_server(QUIC_VERSION_1, &client_versions, false)
+        .is_ok());
+    assert!(server
+        .validate_server(QUIC_VERSION_1, &client_versions, true)
+        .is_ok());
+
+    // a v2-preferring server
+    let server =
+        VersionInformation::new(QUIC_VERSION_2, &[QUIC_VERSION_2, QUIC_VERSION_1]).unwrap();
+    assert!(server
+        .validate_server(QUIC_VERSION_2, &client_versions, false)
+        .is_ok());
+    assert!(server
+        .validate_server(QUIC_VERSION_2, &client_versions, true)
+        .is_ok());
+
+    // the chosen version must match the version in use
+    let err = server
+        .validate_server(QUIC_VERSION_1, &client_versions, false)
+        .unwrap_err();
+    assert_eq!(
+        err.code,
+        crate::transport::Error::VERSION_NEGOTIATION_ERROR.code
+    );
+
+    // an attacker forged a Version Negotiation packet to force v1 when both endpoints
+    // support v2
+    let server =
+        VersionInformation::new(QUIC_VERSION_1, &[QUIC_VERSION_2, QUIC_VERSION_1]).unwrap();
+    assert!(server
+        .validate_server(QUIC_VERSION_1, &client_versions, false)
+        .is_ok());
+    let err = server
+        .validate_server(QUIC_VERSION_1, &client_versions, true)
+        .unwrap_err();
+    assert_eq!(
+        err.code,
+        crate::transport::Error::VERSION_NEGOTIATION_ERROR.code
+    );
+}
+
+#[test]
+fn client_chosen_version_test() {
+    use crate::packet::version::{QUIC_VERSION_1, QUIC_VERSION_2};
+
+    let client =
+        VersionInformation::new(QUIC_VERSION_1, &[QUIC_VERSION_2, QUIC_VERSION_1]).unwrap();
+    assert!(client.validate_client(QUIC_VERSION_1).is_ok());
+
+    // the client's Initial packets used a different version than it claims to have chosen
+    let err = client.validate_client(QUIC_VERSION_2).unwrap_err();
+    assert_eq!(
+        err.code,
+        crate::transport::Error::VERSION_NEGOTIATION_ERROR.code
+    );
+}
+
+#[test]
+fn grease_test() {
+    let mut generator = crate::random::testing::Generator::default();
+
+    for _ in 0..100 {
+        let grease = Grease::new(&mut generator);
+        assert!(Grease::is_reserved(grease.id()));
+        assert!(grease.value().len() <= 16);
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-7.4.2
+        //= type=test
+        //# An endpoint MUST ignore transport parameters that it does
+        //# not support.
+        let value = client_transport_parameters();
+        let encoded = WithGrease {
+            parameters: &value,
+            grease: Some(grease),
+        }
+        .encode_to_vec();
+        assert_ne!(encoded, value.encode_to_vec());
+        let (decoded, _) = ClientTransportParameters::decode(DecoderBuffer::new(&encoded)).unwrap();
+        assert_eq!(decoded, value);
+
+        let value = server_transport_parameters();
+        let encoded = WithGrease {
+            parameters: &value,
+            grease: Some(grease),
+        }
+        .encode_to_vec();
+        let (decoded, _) = ServerTransportParameters::decode(DecoderBuffer::new(&encoded)).unwrap();
+        assert_eq!(decoded, value);
+    }
+
+    for id in [27u8, 58, 89] {
+        assert!(Grease::is_reserved(VarInt::from_u8(id)));
+    }
+    for id in [0u8, 26, 28, 0x11] {
+        assert!(!Grease::is_reserved(VarInt::from_u8(id)));
+    }
+
+    // multiple reserved parameters with arbitrary values are ignored
+    let mut buffer = vec![];
+    for id in [27u8, 58] {
+        buffer.extend_from_slice(&[id, 3, 0xff, 0xff, 0xff]);
+    }
+    // 31 * 1000 + 27 encoded as a 4-byte varint
+    buffer.extend_from_slice(&[0x80, 0x00, 0x79, 0x33, 0]);
+    let (decoded, _) = ClientTransportParameters::decode(DecoderBuffer::new(&buffer)).unwrap();
+    assert_eq!(decoded, ClientTransportParameters::default());
+}
@@ -5,6 +5,7 @@ use core::{
     convert::{TryFrom, TryInto},
     fmt,
     ops::Deref,
+    str::FromStr,
 };
 use s2n_codec::{decoder_value, Encoder, EncoderValue};
 
@@ -41,6 +42,27 @@ impl fmt::Display for VarIntError {
 #[cfg(feature = "std")]
 impl std::error::Error for VarIntError {}
 
+/// Returned when a string can't be parsed as a [`VarInt`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseVarIntError {
+    /// The string isn't a valid decimal integer
+    Invalid(core::num::ParseIntError),
+    /// The integer is greater than [`VarInt::MAX`]
+    RangeExceeded,
+}
+
+impl fmt::Display for ParseVarIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Invalid(error) => write!(f, "invalid varint: {error}"),
+            Self::RangeExceeded => VarIntError.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseVarIntError {}
+
 // === API ===
 
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, PartialOrd, Ord)]
@@ -76,6 +98,7 @@ impl VarInt {
     /// Callers need to ensure the value is less than or equal to VarInt::MAX
     #[inline(always)]
     pub const unsafe fn new_unchecked(value: u64) -> Self {
+        debug_assert!(value <= MAX_VARINT_VALUE, "varint range exceeded");
         Self(value)
     }
 
@@ -298,6 +321,13 @@ impl TryFrom<u64> for VarInt {
     }
 }
 
+impl From<VarInt> for u128 {
+    #[inline]
+    fn from(v: VarInt) -> u128 {
+        v.0 as u128
+    }
+}
+
 impl TryFrom<u128> for VarInt {
     type Error = VarIntError;
 
@@ -311,6 +341,16 @@ impl TryFrom<u128> for VarInt {
     }
 }
 
+impl FromStr for VarInt {
+    type Err = ParseVarIntError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value: u64 = s.parse().map_err(ParseVarIntError::Invalid)?;
+        Self::new(value).map_err(|_| ParseVarIntError::RangeExceeded)
+    }
+}
+
 impl core::ops::Add for VarInt {
     type Output = Self;
 
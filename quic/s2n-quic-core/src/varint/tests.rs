@@ -306,3 +306,70 @@ fn checked_ops_test() {
         }
     })
 }
+
+#[test]
+fn from_str_test() {
+    assert_eq!("0".parse(), Ok(VarInt::ZERO));
+    assert_eq!("1234".parse(), Ok(VarInt::from_u16(1234)));
+    assert_eq!(MAX_VARINT_VALUE.to_string().parse(), Ok(VarInt::MAX));
+    assert_eq!(
+        (MAX_VARINT_VALUE + 1).to_string().parse::<VarInt>(),
+        Err(ParseVarIntError::RangeExceeded)
+    );
+    assert!(matches!(
+        "-1".parse::<VarInt>(),
+        Err(ParseVarIntError::Invalid(_))
+    ));
+    assert!(matches!(
+        "".parse::<VarInt>(),
+        Err(ParseVarIntError::Invalid(_))
+    ));
+
+    check!().with_type().cloned().for_each(|v: VarInt| {
+        assert_eq!(v.to_string().parse(), Ok(v));
+    });
+}
+
+#[test]
+fn conversion_test() {
+    assert_eq!(VarInt::try_from(MAX_VARINT_VALUE).ok(), Some(VarInt::MAX));
+    assert!(VarInt::try_from(MAX_VARINT_VALUE + 1).is_err());
+    assert!(VarInt::try_from(u64::MAX).is_err());
+
+    assert_eq!(
+        VarInt::try_from(MAX_VARINT_VALUE as u128).ok(),
+        Some(VarInt::MAX)
+    );
+    assert!(VarInt::try_from(MAX_VARINT_VALUE as u128 + 1).is_err());
+    assert!(VarInt::try_from(u128::MAX).is_err());
+    assert_eq!(u128::from(VarInt::MAX), MAX_VARINT_VALUE as u128);
+
+    assert_eq!(
+        VarInt::try_from(1234usize).ok(),
+        Some(VarInt::from_u16(1234))
+    );
+    assert_eq!(VarInt::from(u32::MAX), VarInt::from_u32(u32::MAX));
+    let value: Result<u32, _> = VarInt::from_u32(u32::MAX).try_into();
+    assert_eq!(value, Ok(u32::MAX));
+    let value: Result<u32, _> = (VarInt::from_u32(u32::MAX) + VarInt::from_u8(1)).try_into();
+    assert!(value.is_err());
+}
+
+/// Checks that values round trip at the boundaries of each encoding length
+#[test]
+fn encoding_boundary_test() {
+    for (value, len) in [
+        (0, 1),
+        ((1 << 6) - 1, 1),
+        (1 << 6, 2),
+        ((1 << 14) - 1, 2),
+        (1 << 14, 4),
+        ((1 << 30) - 1, 4),
+        (1 << 30, 8),
+        (MAX_VARINT_VALUE, 8),
+    ] {
+        let value = VarInt::new(value).unwrap();
+        assert_eq!(value.encoding_size(), len, "{value}");
+        assert_codec_round_trip_value!(VarInt, value);
+    }
+}
@@ -106,6 +106,13 @@ impl Handle for Tuple {
         self.local_address.into()
     }
 
+    #[inline]
+    fn set_local_address(&mut self, addr: path::LocalAddress) {
+        // keep the MAC address since it isn't known by the caller
+        self.local_address.ip = addr.ip();
+        self.local_address.port = addr.port();
+    }
+
     #[inline]
     fn eq(&self, other: &Self) -> bool {
         // TODO only compare everything if the other is all filled out
@@ -20,6 +20,14 @@ use self::ring as platform;
 
 pub use negotiated::NegotiatedCipherSuite;
 
+/// The labels used to derive the packet protection keys from a secret
+struct Labels {
+    key: &'static [u8],
+    iv: &'static [u8],
+    hp: &'static [u8],
+    key_update: &'static [u8],
+}
+
 macro_rules! impl_cipher_suite {
     (
         $name:ident,
@@ -32,6 +40,10 @@ macro_rules! impl_cipher_suite {
         $iv_label:expr,
         $hp_label:expr,
         $key_update_label:expr,
+        $v2_key_label:expr,
+        $v2_iv_label:expr,
+        $v2_hp_label:expr,
+        $v2_key_update_label:expr,
         $confidentiality_limit:expr,
         $integrity_limit:expr,
         $test_name:ident
@@ -45,24 +57,57 @@ macro_rules! impl_cipher_suite {
 
             type Key = platform::$lower::Key;
 
+            const V1_LABELS: Labels = Labels {
+                key: &$key_label,
+                iv: &$iv_label,
+                hp: &$hp_label,
+                key_update: &$key_update_label,
+            };
+
+            // https://www.rfc-editor.org/rfc/rfc9369#section-3.3.2
+            const V2_LABELS: Labels = Labels {
+                key: &$v2_key_label,
+                iv: &$v2_iv_label,
+                hp: &$v2_hp_label,
+                key_update: &$v2_key_update_label,
+            };
+
             // ignore casing warnings in order to preserve the IANA name
             #[allow(non_camel_case_types, clippy::all)]
             pub struct $name {
                 secret: hkdf::Prk,
                 iv: iv::Iv,
                 key: Key,
+                labels: &'static Labels,
             }
 
             impl $name {
                 pub fn new(secret: hkdf::Prk) -> (Self, HeaderKey) {
-                    let iv = Self::new_iv(&secret);
+                    Self::new_with_labels(secret, &V1_LABELS)
+                }
+
+                /// Creates a cipher_suite with the labels defined for QUIC version 2
+                pub fn new_v2(secret: hkdf::Prk) -> (Self, HeaderKey) {
+                    Self::new_with_labels(secret, &V2_LABELS)
+                }
+
+                fn new_with_labels(
+                    secret: hkdf::Prk,
+                    labels: &'static Labels,
+                ) -> (Self, HeaderKey) {
+                    let iv = Self::new_iv(&secret, labels);
                     let key = {
-                        let secret = Self::new_key_secret(&secret);
+                        let secret = Self::new_key_secret(&secret, labels);
                         Key::new(&*secret)
                     };
-                    let header_key = Self::new_header_key(&secret);
+                    let header_key = Self::new_header_key(&secret, labels);
 
-                    let key = Self { secret, iv, key };
+                    let key = Self {
+                        secret,
+                        iv,
+                        key,
+                        labels,
+                    };
 
                     (key, header_key)
                 }
@@ -71,35 +116,41 @@ macro_rules! impl_cipher_suite {
                 /// https://www.rfc-editor.org/rfc/rfc9001#section-6
                 #[inline]
                 pub fn update(&self) -> Self {
+                    let labels = self.labels;
                     let secret: hkdf::Prk = self
                         .secret
-                        .expand(&[&$key_update_label], $digest)
+                        .expand(&[labels.key_update], $digest)
                         .expect("label size verified")
                         .into();
 
-                    let iv = Self::new_iv(&secret);
+                    let iv = Self::new_iv(&secret, labels);
                     let key = {
-                        let key = Self::new_key_secret(&secret);
+                        let key = Self::new_key_secret(&secret, labels);
                         // ask the existing key to derive the next one so it can persist any
                         // configuration
                         self.key.update(&*key)
                     };
-                    Self { secret, iv, key }
+                    Self {
+                        secret,
+                        iv,
+                        key,
+                        labels,
+                    }
                 }
 
                 #[inline]
                 pub fn update_pmtu(&mut self, mtu: u16) {
                     if self.key.should_update_pmtu(mtu) {
-                        let secret = Self::new_key_secret(&self.secret);
+                        let secret = Self::new_key_secret(&self.secret, self.labels);
                         self.key.update_pmtu(&*secret, mtu);
                     }
                 }
 
-                fn new_key_secret(secret: &hkdf::Prk) -> Zeroizing<[u8; KEY_LEN]> {
+                fn new_key_secret(secret: &hkdf::Prk, labels: &Labels) -> Zeroizing<[u8; KEY_LEN]> {
                     let mut key = Zeroizing::new([0u8; KEY_LEN]);
 
                     secret
-                        .expand(&[&$key_label], &$cipher)
+                        .expand(&[labels.key], &$cipher)
                         .expect("label size verified")
                         .fill(&mut key.as_mut())
                         .expect("fill size verified");
@@ -107,12 +158,12 @@ macro_rules! impl_cipher_suite {
                     key
                 }
 
-                fn new_iv(secret: &hkdf::Prk) -> iv::Iv {
-                    iv::Iv::new(secret, &$iv_label)
+                fn new_iv(secret: &hkdf::Prk, labels: &Labels) -> iv::Iv {
+                    iv::Iv::new(secret, labels.iv)
                 }
 
-                fn new_header_key(secret: &hkdf::Prk) -> HeaderKey {
-                    HeaderKey::new::<{ KEY_LEN }>(secret, &$hp_label, &$header_protection)
+                fn new_header_key(secret: &hkdf::Prk, labels: &Labels) -> HeaderKey {
+                    HeaderKey::new::<{ KEY_LEN }>(secret, labels.hp, &$header_protection)
                 }
             }
 
@@ -247,6 +298,33 @@ macro_rules! impl_cipher_suite {
                     $key_update_label,
                     "key update label mismatch"
                 );
+
+                assert_eq!(
+                    compute_vec_label($cipher.key_len(), b"quicv2 key"),
+                    $v2_key_label,
+                    "v2 key label mismatch"
+                );
+
+                assert_eq!(
+                    compute_vec_label(iv::NONCE_LEN, b"quicv2 iv"),
+                    $v2_iv_label,
+                    "v2 iv label mismatch"
+                );
+
+                assert_eq!(
+                    compute_vec_label($header_protection.key_len(), b"quicv2 hp"),
+                    $v2_hp_label,
+                    "v2 hp label mismatch"
+                );
+
+                assert_eq!(
+                    compute_vec_label(
+                        $digest.hmac_algorithm().digest_algorithm().output_len,
+                        b"quicv2 ku"
+                    ),
+                    $v2_key_update_label,
+                    "v2 key update label mismatch"
+                );
             }
         }
 
@@ -268,6 +346,10 @@ impl_cipher_suite!(
     label::QUIC_IV_12,
     label::QUIC_HP_32,
     label::QUIC_KU_48,
+    label::QUICV2_KEY_32,
+    label::QUICV2_IV_12,
+    label::QUICV2_HP_32,
+    label::QUICV2_KU_48,
     u64::pow(2, 23), // Confidentiality limit
     u64::pow(2, 52), // Integrity limit
     tls_aes_256_gcm_sha384_test
@@ -288,6 +370,10 @@ impl_cipher_suite!(
     label::QUIC_IV_12,
     label::QUIC_HP_32,
     label::QUIC_KU_32,
+    label::QUICV2_KEY_32,
+    label::QUICV2_IV_12,
+    label::QUICV2_HP_32,
+    label::QUICV2_KU_32,
     u64::pow(2, 62), // Confidentiality limit even though specification notes it can be disregarded
     u64::pow(2, 36), // Integrity limit
     tls_chacha20_poly1305_sha256_test
@@ -305,6 +391,10 @@ impl_cipher_suite!(
     label::QUIC_IV_12,
     label::QUIC_HP_16,
     label::QUIC_KU_32,
+    label::QUICV2_KEY_16,
+    label::QUICV2_IV_12,
+    label::QUICV2_HP_16,
+    label::QUICV2_KU_32,
     u64::pow(2, 23), // Confidentiality limit
     u64::pow(2, 52), // Integrity limit
     tls_aes_128_gcm_sha256_test
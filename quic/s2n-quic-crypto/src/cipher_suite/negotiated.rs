@@ -66,6 +66,26 @@ impl NegotiatedCipherSuite {
         })
     }
 
+    /// Create a cipher_suite with a given negotiated algorithm and secret, using the
+    /// QUIC version 2 labels
+    pub fn new_v2(algorithm: &aead::Algorithm, secret: hkdf::Prk) -> Option<(Self, HeaderKey)> {
+        Some(match algorithm {
+            _ if algorithm == &aead::AES_256_GCM => {
+                let (cipher_suite, header_key) = TLS_AES_256_GCM_SHA384::new_v2(secret);
+                (cipher_suite.into(), header_key)
+            }
+            _ if algorithm == &aead::CHACHA20_POLY1305 => {
+                let (cipher_suite, header_key) = TLS_CHACHA20_POLY1305_SHA256::new_v2(secret);
+                (cipher_suite.into(), header_key)
+            }
+            _ if algorithm == &aead::AES_128_GCM => {
+                let (cipher_suite, header_key) = TLS_AES_128_GCM_SHA256::new_v2(secret);
+                (cipher_suite.into(), header_key)
+            }
+            _ => return None,
+        })
+    }
+
     /// Update the cipher_suite as defined in
     /// https://www.rfc-editor.org/rfc/rfc9001#section-6
     pub fn update(&self) -> Self {
@@ -6,9 +6,10 @@ use s2n_quic_core::{
     crypto::{
         self,
         label::{CLIENT_IN, SERVER_IN},
-        scatter, CryptoError, Key, INITIAL_SALT,
+        scatter, CryptoError, Key, INITIAL_SALT, INITIAL_SALT_V2,
     },
     endpoint,
+    packet::version::{QUIC_VERSION_1, QUIC_VERSION_2},
 };
 
 header_key!(InitialHeaderKey);
@@ -24,12 +25,25 @@ pub struct InitialKey {
 lazy_static::lazy_static! {
     /// Compute the Initial salt once, as the seed is constant
     static ref INITIAL_SIGNING_KEY: hkdf::Salt = hkdf::Salt::new(hkdf::HKDF_SHA256, &INITIAL_SALT);
+
+    /// Compute the QUIC version 2 Initial salt once, as the seed is constant
+    static ref INITIAL_SIGNING_KEY_V2: hkdf::Salt = hkdf::Salt::new(hkdf::HKDF_SHA256, &INITIAL_SALT_V2);
 }
 
 impl InitialKey {
-    fn new(endpoint: endpoint::Type, connection_id: &[u8]) -> (Self, InitialHeaderKey) {
-        let initial_secret = INITIAL_SIGNING_KEY.extract(connection_id);
-        let digest = INITIAL_SIGNING_KEY.algorithm();
+    fn new(
+        version: u32,
+        endpoint: endpoint::Type,
+        connection_id: &[u8],
+    ) -> Option<(Self, InitialHeaderKey)> {
+        let (signing_key, new_cipher_suite): (_, fn(_) -> _) = match version {
+            QUIC_VERSION_1 => (&*INITIAL_SIGNING_KEY, CipherSuite::new),
+            QUIC_VERSION_2 => (&*INITIAL_SIGNING_KEY_V2, CipherSuite::new_v2),
+            _ => return None,
+        };
+
+        let initial_secret = signing_key.extract(connection_id);
+        let digest = signing_key.algorithm();
 
         let client_secret = initial_secret
             .expand(&[&CLIENT_IN], digest)
@@ -43,12 +57,12 @@ impl InitialKey {
 
         let (sealer, opener) = match endpoint {
             endpoint::Type::Client => (
-                CipherSuite::new(client_secret),
-                CipherSuite::new(server_secret),
+                new_cipher_suite(client_secret),
+                new_cipher_suite(server_secret),
             ),
             endpoint::Type::Server => (
-                CipherSuite::new(server_secret),
-                CipherSuite::new(client_secret),
+                new_cipher_suite(server_secret),
+                new_cipher_suite(client_secret),
             ),
         };
 
@@ -63,7 +77,7 @@ impl InitialKey {
             opener: header_opener,
         });
 
-        (key, header_key)
+        Some((key, header_key))
     }
 }
 
@@ -71,11 +85,27 @@ impl crypto::InitialKey for InitialKey {
     type HeaderKey = InitialHeaderKey;
 
     fn new_server(connection_id: &[u8]) -> (Self, Self::HeaderKey) {
-        Self::new(endpoint::Type::Server, connection_id)
+        Self::new(QUIC_VERSION_1, endpoint::Type::Server, connection_id)
+            .expect("version 1 is always supported")
     }
 
     fn new_client(connection_id: &[u8]) -> (Self, Self::HeaderKey) {
-        Self::new(endpoint::Type::Client, connection_id)
+        Self::new(QUIC_VERSION_1, endpoint::Type::Client, connection_id)
+            .expect("version 1 is always supported")
+    }
+
+    fn new_server_for_version(
+        version: u32,
+        connection_id: &[u8],
+    ) -> Option<(Self, Self::HeaderKey)> {
+        Self::new(version, endpoint::Type::Server, connection_id)
+    }
+
+    fn new_client_for_version(
+        version: u32,
+        connection_id: &[u8],
+    ) -> Option<(Self, Self::HeaderKey)> {
+        Self::new(version, endpoint::Type::Client, connection_id)
     }
 }
 
@@ -132,6 +162,8 @@ mod tests {
                 EXAMPLE_CLIENT_INITIAL_PAYLOAD, EXAMPLE_CLIENT_INITIAL_PROTECTED_PACKET,
                 EXAMPLE_DCID, EXAMPLE_SERVER_INITIAL_PAYLOAD,
                 EXAMPLE_SERVER_INITIAL_PROTECTED_PACKET,
+                EXAMPLE_V2_CLIENT_INITIAL_PROTECTED_PACKET,
+                EXAMPLE_V2_SERVER_INITIAL_PROTECTED_PACKET,
             },
             InitialKey as _,
         },
@@ -159,6 +191,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rfc_example_v2_server_test() {
+        test_round_trip(
+            &InitialKey::new_client_for_version(QUIC_VERSION_2, &EXAMPLE_DCID).unwrap(),
+            &InitialKey::new_server_for_version(QUIC_VERSION_2, &EXAMPLE_DCID).unwrap(),
+            &EXAMPLE_V2_CLIENT_INITIAL_PROTECTED_PACKET,
+            &EXAMPLE_CLIENT_INITIAL_PAYLOAD,
+        );
+    }
+
+    #[test]
+    fn rfc_example_v2_client_test() {
+        test_round_trip(
+            &InitialKey::new_server_for_version(QUIC_VERSION_2, &EXAMPLE_DCID).unwrap(),
+            &InitialKey::new_client_for_version(QUIC_VERSION_2, &EXAMPLE_DCID).unwrap(),
+            &EXAMPLE_V2_SERVER_INITIAL_PROTECTED_PACKET,
+            &EXAMPLE_SERVER_INITIAL_PAYLOAD,
+        );
+    }
+
+    #[test]
+    fn unsupported_version_test() {
+        assert!(InitialKey::new_server_for_version(0xff00_0020, &EXAMPLE_DCID).is_none());
+        assert!(InitialKey::new_client_for_version(0xff00_0020, &EXAMPLE_DCID).is_none());
+        // version 2 keys can't open version 1 packets
+        let (key, header_key) =
+            InitialKey::new_server_for_version(QUIC_VERSION_2, &EXAMPLE_DCID).unwrap();
+        let mut packet = EXAMPLE_CLIENT_INITIAL_PROTECTED_PACKET.to_vec();
+        let decoder = DecoderBufferMut::new(&mut packet);
+        let remote_address = SocketAddress::default();
+        let connection_info = ConnectionInfo::new(&remote_address);
+        let (packet, _) = ProtectedPacket::decode(decoder, &connection_info, &20).unwrap();
+        let packet = match packet {
+            ProtectedPacket::Initial(initial) => initial,
+            _ => panic!("expected initial packet type"),
+        };
+        let result = packet
+            .unprotect(&header_key, Default::default())
+            .and_then(|packet| packet.decrypt(&key));
+        assert!(result.is_err());
+    }
+
     fn test_round_trip(
         sealer: &(InitialKey, InitialHeaderKey),
         opener: &(InitialKey, InitialHeaderKey),
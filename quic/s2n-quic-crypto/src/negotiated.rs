@@ -2,8 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    cipher_suite::NegotiatedCipherSuite as CipherSuite, header_key::HeaderKeyPair, Algorithm,
-    SecretPair,
+    cipher_suite::NegotiatedCipherSuite as CipherSuite,
+    header_key::{HeaderKey, HeaderKeyPair},
+    Algorithm, Prk, SecretPair,
 };
 use s2n_quic_core::{
     crypto::{scatter, CryptoError, Key},
@@ -21,14 +22,32 @@ impl KeyPair {
         endpoint: endpoint::Type,
         algorithm: &Algorithm,
         secrets: SecretPair,
+    ) -> Option<(Self, HeaderKeyPair)> {
+        Self::new_with(endpoint, algorithm, secrets, CipherSuite::new)
+    }
+
+    /// Creates a key pair using the QUIC version 2 labels
+    pub fn new_v2(
+        endpoint: endpoint::Type,
+        algorithm: &Algorithm,
+        secrets: SecretPair,
+    ) -> Option<(Self, HeaderKeyPair)> {
+        Self::new_with(endpoint, algorithm, secrets, CipherSuite::new_v2)
+    }
+
+    fn new_with(
+        endpoint: endpoint::Type,
+        algorithm: &Algorithm,
+        secrets: SecretPair,
+        new_cipher_suite: fn(&Algorithm, Prk) -> Option<(CipherSuite, HeaderKey)>,
     ) -> Option<(Self, HeaderKeyPair)> {
         let (sealer_secret, opener_secret) = match endpoint {
             endpoint::Type::Client => (secrets.client, secrets.server),
             endpoint::Type::Server => (secrets.server, secrets.client),
         };
 
-        let (sealer, header_sealer) = CipherSuite::new(algorithm, sealer_secret)?;
-        let (opener, header_opener) = CipherSuite::new(algorithm, opener_secret)?;
+        let (sealer, header_sealer) = new_cipher_suite(algorithm, sealer_secret)?;
+        let (opener, header_opener) = new_cipher_suite(algorithm, opener_secret)?;
 
         let key = Self { sealer, opener };
         let header_key = HeaderKeyPair {
@@ -128,6 +147,21 @@ macro_rules! negotiated_crypto {
                 Some((key, header_key))
             }
 
+            /// Create a cipher_suite for an endpoint type with a given negotiated algorithm and
+            /// secret, using the QUIC version 2 labels
+            pub fn new_v2(
+                endpoint: s2n_quic_core::endpoint::Type,
+                algorithm: &$crate::Algorithm,
+                secrets: $crate::SecretPair,
+            ) -> Option<(Self, $header_key)> {
+                let (key, header_key) =
+                    crate::negotiated::KeyPair::new_v2(endpoint, algorithm, secrets)?;
+
+                let key = Self(key);
+                let header_key = $header_key::from(header_key);
+                Some((key, header_key))
+            }
+
             /// Update the cipher suite as defined in
             /// <https://www.rfc-editor.org/rfc/rfc9001#section-6>
             #[inline]
@@ -3,10 +3,13 @@
 
 use crate::{constant_time, ring_aead as aead};
 use core::convert::TryInto;
-use s2n_quic_core::crypto::{
-    self,
-    retry::{IntegrityTag, NONCE_BYTES, SECRET_KEY_BYTES},
-    CryptoError,
+use s2n_quic_core::{
+    crypto::{
+        self,
+        retry::{IntegrityTag, NONCE_BYTES, NONCE_BYTES_V2, SECRET_KEY_BYTES, SECRET_KEY_BYTES_V2},
+        CryptoError,
+    },
+    packet::version::{QUIC_VERSION_1, QUIC_VERSION_2},
 };
 
 lazy_static::lazy_static! {
@@ -14,15 +17,20 @@ lazy_static::lazy_static! {
     static ref SECRET_KEY: aead::LessSafeKey = aead::LessSafeKey::new(
         aead::UnboundKey::new(&aead::AES_128_GCM, &SECRET_KEY_BYTES).unwrap(),
     );
+
+    /// Compute the QUIC version 2 key once, as the seed is constant
+    static ref SECRET_KEY_V2: aead::LessSafeKey = aead::LessSafeKey::new(
+        aead::UnboundKey::new(&aead::AES_128_GCM, &SECRET_KEY_BYTES_V2).unwrap(),
+    );
 }
 
 #[derive(Debug)]
 pub struct RetryKey;
 
-impl crypto::RetryKey for RetryKey {
-    fn generate_tag(pseudo_packet: &[u8]) -> IntegrityTag {
-        let nonce = aead::Nonce::assume_unique_for_key(NONCE_BYTES);
-        let tag = SECRET_KEY
+impl RetryKey {
+    fn seal(key: &aead::LessSafeKey, nonce: [u8; 12], pseudo_packet: &[u8]) -> IntegrityTag {
+        let nonce = aead::Nonce::assume_unique_for_key(nonce);
+        let tag = key
             .seal_in_place_separate_tag(nonce, aead::Aad::from(pseudo_packet), &mut [])
             .expect("in_out len is 0 and should always be less than the nonce max bytes");
 
@@ -30,9 +38,32 @@ impl crypto::RetryKey for RetryKey {
             .try_into()
             .expect("AES_128_GCM tag len should always be 128 bits")
     }
+}
+
+impl crypto::RetryKey for RetryKey {
+    fn generate_tag(pseudo_packet: &[u8]) -> IntegrityTag {
+        Self::seal(&SECRET_KEY, NONCE_BYTES, pseudo_packet)
+    }
 
     fn validate(pseudo_packet: &[u8], tag: IntegrityTag) -> Result<(), CryptoError> {
-        let expected = Self::generate_tag(pseudo_packet);
+        Self::validate_for_version(QUIC_VERSION_1, pseudo_packet, tag)
+    }
+
+    fn generate_tag_for_version(version: u32, pseudo_packet: &[u8]) -> Option<IntegrityTag> {
+        match version {
+            QUIC_VERSION_1 => Some(Self::seal(&SECRET_KEY, NONCE_BYTES, pseudo_packet)),
+            QUIC_VERSION_2 => Some(Self::seal(&SECRET_KEY_V2, NONCE_BYTES_V2, pseudo_packet)),
+            _ => None,
+        }
+    }
+
+    fn validate_for_version(
+        version: u32,
+        pseudo_packet: &[u8],
+        tag: IntegrityTag,
+    ) -> Result<(), CryptoError> {
+        let expected = Self::generate_tag_for_version(version, pseudo_packet)
+            .ok_or(CryptoError::DECRYPT_ERROR)?;
 
         constant_time::verify_slices_are_equal(&expected, &tag)
             .map_err(|_| CryptoError::DECRYPT_ERROR)
@@ -65,6 +96,37 @@ mod tests {
         assert!(RetryKey::validate(&retry::example::PSEUDO_PACKET, invalid_tag).is_err());
     }
 
+    #[test]
+    fn test_v2_tag_validation() {
+        let version = retry::example_v2::VERSION;
+
+        assert_eq!(
+            RetryKey::generate_tag_for_version(version, &retry::example_v2::PSEUDO_PACKET),
+            Some(retry::example_v2::EXPECTED_TAG)
+        );
+        assert!(RetryKey::validate_for_version(
+            version,
+            &retry::example_v2::PSEUDO_PACKET,
+            retry::example_v2::EXPECTED_TAG
+        )
+        .is_ok());
+
+        // the version 1 tag doesn't validate with the version 2 key and vice versa
+        assert!(RetryKey::validate_for_version(
+            version,
+            &retry::example::PSEUDO_PACKET,
+            retry::example::EXPECTED_TAG
+        )
+        .is_err());
+        assert!(RetryKey::validate(
+            &retry::example_v2::PSEUDO_PACKET,
+            retry::example_v2::EXPECTED_TAG
+        )
+        .is_err());
+
+        assert!(RetryKey::generate_tag_for_version(0xff00_0020, &[]).is_none());
+    }
+
     fn pn(space: PacketNumberSpace) -> TruncatedPacketNumber {
         let pn = space.new_packet_number(VarInt::new(0x1).unwrap());
         pn.truncate(pn).unwrap()
@@ -192,6 +192,31 @@ impl<'a> SocketAddress<'a> {
             Self::IpV6 { port, .. } => *port,
         }
     }
+
+    /// Converts the address into IPv4 if it is mapped, otherwise the address is unchanged
+    ///
+    /// Limiters should key their state by the unmapped address, since dual-stack sockets report
+    /// IPv4 peers with IPv4-mapped addresses.
+    #[inline]
+    pub fn unmap(&self) -> Self {
+        match self {
+            Self::IpV4 { ip, port } => Self::IpV4 {
+                ip: *ip,
+                port: *port,
+            },
+            Self::IpV6 { ip, port } => match crate::inet::IpV6Address::from(**ip).unmap() {
+                crate::inet::IpAddress::Ipv4(_) => Self::IpV4 {
+                    // every unmapped form carries the IPv4 address in the last 4 bytes
+                    ip: ip[12..].try_into().expect("slice is 4 bytes"),
+                    port: *port,
+                },
+                crate::inet::IpAddress::Ipv6(_) => Self::IpV6 {
+                    ip: *ip,
+                    port: *port,
+                },
+            },
+        }
+    }
 }
 
 impl<'a> IntoEvent<api::SocketAddress<'a>> for &'a crate::inet::SocketAddress {
@@ -369,6 +394,13 @@ enum Frame {
     Datagram {
         len: u16,
     },
+    AckFrequency {
+        sequence_number: u64,
+        ack_eliciting_threshold: u64,
+        request_max_ack_delay: Duration,
+        reordering_threshold: u64,
+    },
+    ImmediateAck,
 }
 
 impl IntoEvent<builder::Frame> for &crate::frame::Padding {
@@ -572,6 +604,25 @@ where
     }
 }
 
+impl IntoEvent<builder::Frame> for &crate::frame::AckFrequency {
+    #[inline]
+    fn into_event(self) -> builder::Frame {
+        builder::Frame::AckFrequency {
+            sequence_number: self.sequence_number.as_u64(),
+            ack_eliciting_threshold: self.ack_eliciting_threshold.as_u64(),
+            request_max_ack_delay: self.request_max_ack_delay(),
+            reordering_threshold: self.reordering_threshold.as_u64(),
+        }
+    }
+}
+
+impl IntoEvent<builder::Frame> for &crate::frame::ImmediateAck {
+    #[inline]
+    fn into_event(self) -> builder::Frame {
+        builder::Frame::ImmediateAck {}
+    }
+}
+
 enum StreamType {
     Bidirectional,
     Unidirectional,
@@ -714,7 +765,10 @@ enum DatagramDropReason {
     /// datagram can not be processed and is dropped.
     UnknownDestinationConnectionId,
     /// The connection attempt was rejected.
-    RejectedConnectionAttempt,
+    RejectedConnectionAttempt {
+        /// The reason provided by the endpoint limits, if any
+        reason: Option<&'static str>,
+    },
     /// A datagram was received from an unknown server address.
     UnknownServerAddress,
     /// The peer initiated a connection migration before the handshake was confirmed.
@@ -727,6 +781,18 @@ enum DatagramDropReason {
     InsufficientConnectionIds,
 }
 
+/// The outcome of a connection attempt, as decided by the endpoint limits
+enum ConnectionAttemptOutcome {
+    /// The attempt was allowed to continue
+    Allow,
+    /// The client was asked to validate its address with a Retry packet
+    Retry,
+    /// The attempt was silently dropped
+    Drop,
+    /// The attempt was refused with a CONNECTION_CLOSE frame
+    Close,
+}
+
 enum KeySpace {
     Initial {},
     Handshake {},
@@ -833,6 +899,7 @@ enum MigrationDenyReason {
     PortScopeChanged,
     IpScopeChange,
     ConnectionMigrationDisabled,
+    Limited,
 }
 
 /// The current state of the ECN controller for the path
@@ -897,7 +964,11 @@ impl CipherSuite {
 }
 
 enum PathChallengeStatus {
+    /// A PATH_CHALLENGE was issued and the path is awaiting a PATH_RESPONSE
+    Pending,
+    /// The path was validated by a PATH_RESPONSE from the peer
     Validated,
+    /// The path validation failed or was abandoned before a PATH_RESPONSE was received
     Abandoned,
 }
 
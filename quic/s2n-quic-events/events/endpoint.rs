@@ -66,3 +66,21 @@ struct EndpointDatagramDropped {
 struct EndpointConnectionAttemptFailed {
     error: crate::connection::Error,
 }
+
+#[event("transport:connection_attempt_decided")]
+#[subject(endpoint)]
+/// The endpoint limits decided on the outcome of a connection attempt
+struct EndpointConnectionAttemptDecided {
+    outcome: ConnectionAttemptOutcome,
+    /// The number of handshakes in progress when the attempt arrived
+    inflight_handshakes: usize,
+}
+
+#[event("transport:response_dropped")]
+#[subject(endpoint)]
+/// A Retry or CONNECTION_CLOSE packet for a connection attempt was dropped because too many
+/// responses were already queued
+struct EndpointResponseDropped {
+    /// The outcome the dropped packet was responding with
+    outcome: ConnectionAttemptOutcome,
+}
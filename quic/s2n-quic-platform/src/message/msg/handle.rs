@@ -35,8 +35,6 @@ impl Handle {
         #[cfg(s2n_quic_platform_pktinfo)]
         match self.local_address.0 {
             SocketAddress::IpV4(addr) => {
-                use s2n_quic_core::inet::Unspecified;
-
                 let ip = addr.ip();
 
                 if ip.is_unspecified() {
@@ -51,8 +49,6 @@ impl Handle {
                     .unwrap();
             }
             SocketAddress::IpV6(addr) => {
-                use s2n_quic_core::inet::Unspecified;
-
                 let ip = addr.ip();
 
                 if ip.is_unspecified() {
@@ -95,6 +91,11 @@ impl path::Handle for Handle {
         self.local_address
     }
 
+    #[inline]
+    fn set_local_address(&mut self, addr: LocalAddress) {
+        self.local_address = addr;
+    }
+
     #[inline]
     fn eq(&self, other: &Self) -> bool {
         let mut eq = true;
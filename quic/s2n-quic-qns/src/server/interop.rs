@@ -87,14 +87,13 @@ impl Interop {
     }
 
     fn server(&self) -> Result<Server> {
-        let mut max_handshakes = 100;
+        let mut endpoint_limits =
+            endpoint_limits::Default::builder().with_inflight_handshake_limit(100)?;
         if let Some(Testcase::Retry) = self.testcase {
-            max_handshakes = 0;
+            // every attempt counts towards the rate, so each one without a Retry token is retried
+            endpoint_limits = endpoint_limits.with_retry_above_attempt_rate(0.0)?;
         }
-
-        let endpoint_limits = endpoint_limits::Default::builder()
-            .with_inflight_handshake_limit(max_handshakes)?
-            .build()?;
+        let endpoint_limits = endpoint_limits.build()?;
 
         let limits = self.limits.limits();
 
@@ -28,7 +28,7 @@ impl Server {
     pub fn build_s2n_tls(&self, alpns: &[String]) -> Result<s2n_tls::Server<s2n_tls::Server>> {
         // The server builder defaults to a chain because this allows certs to just work, whether
         // the PEM contains a single cert or a chain
-        let mut tls = s2n_tls::Server::builder()
+        let tls = s2n_tls::Server::builder()
             .with_certificate(
                 s2n_tls::ca(self.certificate.as_ref())?,
                 s2n_tls::private_key(self.private_key.as_ref())?,
@@ -46,15 +46,15 @@ impl Server {
             }
         }
 
-        if let Some(ticket_key) = &self.ticket_key {
-            let config = tls.config_mut();
-            config.enable_session_tickets(true)?;
-            config.add_session_ticket_key(
+        let tls = if let Some(ticket_key) = &self.ticket_key {
+            tls.with_session_ticket_key(
                 "keyname".as_bytes(),
                 ticket_key.as_bytes(),
                 std::time::SystemTime::now(),
-            )?;
-        }
+            )?
+        } else {
+            tls
+        };
 
         let server = s2n_tls::Server::from_loader(tls.build()?);
         Ok(server)
@@ -65,6 +65,8 @@ impl tls::Endpoint for Client {
         //# Endpoints MUST send the quic_transport_parameters extension;
         let transport_parameters = transport_parameters.encode_to_vec();
 
+        // rustls doesn't send IP literals in the `server_name` extension, but still verifies the
+        // server's certificate against them
         let rustls_server_name =
             rustls::ServerName::try_from(server_name.as_ref()).expect("invalid server name");
 
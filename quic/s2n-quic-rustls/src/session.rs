@@ -60,6 +60,15 @@ impl tls::TlsSession for Session {
             CipherSuite::Unknown
         }
     }
+
+    fn peer_cert_chain_der(&self) -> Result<Vec<Vec<u8>>, tls::ChainError> {
+        let chain = self
+            .connection
+            .peer_certificates()
+            .map(|chain| chain.iter().map(|cert| cert.0.clone()).collect())
+            .unwrap_or_default();
+        Ok(chain)
+    }
 }
 
 impl fmt::Debug for Session {
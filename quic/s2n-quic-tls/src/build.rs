@@ -0,0 +1,122 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Validation of builder settings which would otherwise only fail during a handshake
+
+use core::fmt;
+
+/// An inconsistent configuration detected when building a client or server
+///
+/// These are returned by `validate` on the builders, and inside an
+/// [`InvalidInput`](std::io::ErrorKind::InvalidInput) error from `build`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BuildError {
+    /// Client authentication was enabled but the trust store was cleared without adding any
+    /// certificates, so no client can be authenticated
+    ClientAuthWithoutTrustAnchors,
+    /// The trust store was cleared without adding any certificates, so no server can be
+    /// verified
+    EmptyTrustStore,
+    /// Session ticket keys were added but session tickets were disabled afterwards
+    TicketKeysWithoutTickets,
+    /// An OCSP response was provided but no certificate chain was configured to staple it to
+    OcspResponseWithoutCertificate,
+    /// The server has no certificate chain and no ClientHello handler to select a config with
+    /// one, so every handshake would fail
+    MissingCertificate,
+}
+
+impl std::error::Error for BuildError {}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ClientAuthWithoutTrustAnchors => write!(
+                f,
+                "client authentication requires at least one trusted certificate"
+            ),
+            Self::EmptyTrustStore => write!(
+                f,
+                "the trust store is empty; at least one trusted certificate is required"
+            ),
+            Self::TicketKeysWithoutTickets => write!(
+                f,
+                "session ticket keys were added but session tickets are disabled"
+            ),
+            Self::OcspResponseWithoutCertificate => write!(
+                f,
+                "an OCSP response requires a certificate chain to staple it to"
+            ),
+            Self::MissingCertificate => write!(
+                f,
+                "the server requires a certificate chain or a ClientHello handler which selects one"
+            ),
+        }
+    }
+}
+
+/// Settings recorded by the builders that s2n-tls doesn't expose from its config
+#[derive(Debug, Default)]
+pub(crate) struct Settings {
+    /// Set once `config_mut` is called, since the config may then change without being recorded
+    pub untracked: bool,
+    /// The trust store was wiped and nothing has been trusted since
+    pub empty_trust_store: bool,
+    pub client_auth: bool,
+    pub x509_verification_disabled: bool,
+    pub ticket_keys: bool,
+    pub session_tickets: bool,
+    /// A certificate chain was configured with `with_certificate`
+    pub certificate: bool,
+    /// An OCSP response was provided with `with_ocsp_response`
+    pub ocsp_response: bool,
+    /// A ClientHello handler was set, which may select a config with a certificate chain
+    pub client_hello_handler: bool,
+}
+
+impl Settings {
+    pub fn on_wipe_trust_store(&mut self) {
+        self.empty_trust_store = true;
+    }
+
+    pub fn on_trust(&mut self) {
+        self.empty_trust_store = false;
+    }
+
+    pub fn validate_server(&self) -> Result<(), BuildError> {
+        if self.untracked {
+            return Ok(());
+        }
+
+        if self.client_auth && self.empty_trust_store {
+            return Err(BuildError::ClientAuthWithoutTrustAnchors);
+        }
+
+        if self.ticket_keys && !self.session_tickets {
+            return Err(BuildError::TicketKeysWithoutTickets);
+        }
+
+        if self.ocsp_response && !self.certificate {
+            return Err(BuildError::OcspResponseWithoutCertificate);
+        }
+
+        if !self.certificate && !self.client_hello_handler {
+            return Err(BuildError::MissingCertificate);
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_client(&self) -> Result<(), BuildError> {
+        if self.untracked {
+            return Ok(());
+        }
+
+        if self.empty_trust_store && !self.x509_verification_disabled {
+            return Err(BuildError::EmptyTrustStore);
+        }
+
+        Ok(())
+    }
+}
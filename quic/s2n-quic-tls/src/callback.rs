@@ -1,6 +1,7 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::message::Messages;
 use bytes::{Bytes, BytesMut};
 use core::{ffi::c_void, marker::PhantomData};
 use s2n_quic_core::{
@@ -171,6 +172,10 @@ where
                 let secret = Prk::new_less_safe(prk_algo, secret);
                 self.state.secrets = Secrets::Half { secret, id };
                 self.state.cipher_suite = cipher_suite;
+                if self.state.key_exchange_group.is_none() {
+                    // the key exchange has completed by the time the first secret is derived
+                    self.state.key_exchange_group = unsafe { get_key_exchange_group(conn) };
+                }
 
                 Ok(())
             }
@@ -244,6 +249,12 @@ where
                             get_application_params(conn)?
                         };
 
+                        if self.endpoint.is_client() {
+                            // the server's certificate, along with any stapled OCSP response, has
+                            // been received by the time the application secrets are derived
+                            self.state.ocsp_response = unsafe { get_ocsp_response(conn) };
+                        }
+
                         self.context.on_one_rtt_keys(key, header_key, params)?;
                     }
                 }
@@ -289,6 +300,10 @@ where
         // NOTE: we don't immediately flush to the packet space since s2n-tls may do
         //       several small writes in a row.
         self.send_buffer.extend_from_slice(data);
+        self.state.bytes_sent += data.len() as u64;
+        if let Some(messages) = self.state.messages.as_mut() {
+            messages.on_sent(data);
+        }
 
         data.len()
     }
@@ -338,6 +353,10 @@ where
         if let Some(chunk) = chunk {
             let len = chunk.len();
             data[..len].copy_from_slice(&chunk);
+            self.state.bytes_received += len as u64;
+            if let Some(messages) = self.state.messages.as_mut() {
+                messages.on_received(&data[..len]);
+            }
             len
         } else {
             0
@@ -351,6 +370,12 @@ pub struct State {
     tx_phase: HandshakePhase,
     secrets: Secrets,
     cipher_suite: CipherSuite,
+    key_exchange_group: Option<tls::KeyExchangeGroup<'static>>,
+    /// The OCSP response stapled to the server's certificate, only recorded by clients
+    ocsp_response: Option<Vec<u8>>,
+    bytes_sent: u64,
+    bytes_received: u64,
+    pub(crate) messages: Option<Messages>,
 }
 
 impl State {
@@ -365,6 +390,18 @@ impl State {
     pub fn cipher_suite(&self) -> CipherSuite {
         self.cipher_suite
     }
+
+    pub fn key_exchange_group(&self) -> Option<tls::KeyExchangeGroup<'static>> {
+        self.key_exchange_group
+    }
+
+    pub fn ocsp_response(&self) -> Option<&[u8]> {
+        self.ocsp_response.as_deref()
+    }
+
+    pub fn handshake_stats(&self) -> tls::HandshakeStats {
+        tls::HandshakeStats::new(self.bytes_sent, self.bytes_received)
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
@@ -503,8 +540,49 @@ unsafe fn get_application_protocol<'a>(
     connection: *mut s2n_connection,
 ) -> Result<&'a [u8], CryptoError> {
     let ptr = s2n_get_application_protocol(connection).into_result().ok();
+    //= https://www.rfc-editor.org/rfc/rfc9001#section-8.1
+    //# When using ALPN, endpoints MUST immediately close a connection (see
+    //# Section 10.2 of [QUIC-TRANSPORT]) with a no_application_protocol TLS
+    //# alert (QUIC error code 0x178; see Section 4.8) if an application
+    //# protocol is not negotiated.
     ptr.and_then(|ptr| get_cstr_slice(ptr))
-        .ok_or(CryptoError::MISSING_EXTENSION)
+        .filter(|protocol| !protocol.is_empty())
+        .ok_or(CryptoError::NO_APPLICATION_PROTOCOL)
+}
+
+/// Returns the name of the negotiated KEM group or, if a hybrid group wasn't negotiated,
+/// the negotiated curve
+///
+/// s2n-tls returns pointers to static strings for these names, so they aren't tied to the
+/// lifetime of the connection.
+unsafe fn get_key_exchange_group(
+    connection: *mut s2n_connection,
+) -> Option<tls::KeyExchangeGroup<'static>> {
+    let name = |ptr: *const libc::c_char| {
+        if ptr.is_null() {
+            return None;
+        }
+        let name = core::str::from_utf8(get_cstr_slice(ptr)?).ok()?;
+        // s2n-tls uses "NONE" if nothing was negotiated
+        if name == "NONE" {
+            return None;
+        }
+        Some(name)
+    };
+
+    if let Some(kem_group) = name(s2n_connection_get_kem_group_name(connection)) {
+        return Some(tls::KeyExchangeGroup::new(kem_group, true));
+    }
+
+    let curve = name(s2n_connection_get_curve(connection))?;
+    Some(tls::KeyExchangeGroup::new(curve, false))
+}
+
+/// Returns a copy of the OCSP response stapled to the peer's certificate, if any
+unsafe fn get_ocsp_response(connection: *mut s2n_connection) -> Option<Vec<u8>> {
+    let mut len = 0u32;
+    let ptr = s2n_connection_get_ocsp_response(connection, &mut len);
+    get_slice(ptr, len as _).map(<[u8]>::to_vec)
 }
 
 unsafe fn get_transport_parameters<'a>(connection: *mut s2n_connection) -> Option<&'a [u8]> {
@@ -1,28 +1,12 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::invalid_input;
 use bytes::Bytes;
 use s2n_tls::error::Error;
+use std::borrow::Cow;
 
-impl Format {
-    pub fn as_pem(&self) -> Option<&[u8]> {
-        if let Format::Pem(bytes) = &self {
-            Some(bytes.as_ref())
-        } else {
-            None
-        }
-    }
-
-    #[allow(dead_code)] // remove if s2n-tls ever starts supporting DER certs
-    pub fn as_der(&self) -> Option<&[u8]> {
-        if let Format::Der(bytes) = &self {
-            Some(bytes.as_ref())
-        } else {
-            None
-        }
-    }
-}
-
+#[derive(Clone)]
 pub(crate) enum Format {
     Pem(Bytes),
     Der(Bytes),
@@ -30,8 +14,25 @@ pub(crate) enum Format {
     None,
 }
 
+impl Format {
+    /// Returns the PEM encoding of the value, converting from DER if needed
+    ///
+    /// DER values may contain several concatenated elements, e.g. a certificate chain. Each
+    /// element is encoded as a separate PEM block with the provided `label`.
+    fn to_pem(&self, label: &str) -> Result<Option<Cow<[u8]>>, Error> {
+        match self {
+            Format::Pem(bytes) => Ok(Some(Cow::Borrowed(bytes.as_ref()))),
+            Format::Der(bytes) => der_to_pem(label, bytes).map(|pem| Some(Cow::Owned(pem))),
+            Format::None => Ok(None),
+        }
+    }
+}
+
 macro_rules! cert_type {
     ($name:ident, $trait:ident, $method:ident) => {
+        /// Cloning is cheap since the encoded bytes are reference counted, which allows
+        /// loading the value once and attaching it to several builders
+        #[derive(Clone)]
         pub struct $name(pub(crate) Format);
 
         pub trait $trait {
@@ -109,5 +110,133 @@ macro_rules! cert_type {
 cert_type!(PrivateKey, IntoPrivateKey, into_private_key);
 cert_type!(Certificate, IntoCertificate, into_certificate);
 
+impl PrivateKey {
+    /// Returns the PEM encoding of the key, or `None` if the key operations are offloaded
+    ///
+    /// DER keys may be encoded as PKCS#8, SEC1 (EC) or PKCS#1 (RSA), which is detected from the
+    /// structure of the key to select the matching PEM label.
+    pub(crate) fn to_pem(&self) -> Result<Option<Cow<[u8]>>, Error> {
+        let label = match &self.0 {
+            Format::Der(bytes) => {
+                let mut elements = der_elements(bytes)?;
+                let key = match (elements.next(), elements.next()) {
+                    (Some(key), None) => key,
+                    _ => return Err(invalid_input("DER private keys must contain a single key")),
+                };
+
+                private_key_label(key).ok_or_else(|| {
+                    invalid_input("DER private keys must be encoded as PKCS#8, SEC1 or PKCS#1")
+                })?
+            }
+            _ => "PRIVATE KEY",
+        };
+
+        self.0.to_pem(label)
+    }
+}
+
+impl Certificate {
+    /// Returns the PEM encoding of the certificate chain
+    pub(crate) fn to_pem(&self) -> Result<Cow<[u8]>, Error> {
+        self.0
+            .to_pem("CERTIFICATE")?
+            .ok_or_else(|| invalid_input("missing certificate"))
+    }
+}
+
+/// Splits concatenated DER-encoded values into their top-level elements
+fn der_elements(mut der: &[u8]) -> Result<impl Iterator<Item = &[u8]>, Error> {
+    let mut elements = vec![];
+
+    if der.is_empty() {
+        return Err(invalid_input("DER values must not be empty"));
+    }
+
+    while !der.is_empty() {
+        let len = der_element_len(der).ok_or_else(|| invalid_input("malformed DER value"))?;
+        let (element, remaining) = der.split_at(len);
+        elements.push(element);
+        der = remaining;
+    }
+
+    Ok(elements.into_iter())
+}
+
+const INTEGER: u8 = 0x02;
+const OCTET_STRING: u8 = 0x04;
+const SEQUENCE: u8 = 0x30;
+
+/// Returns the total length of the first DER element, including the tag and length octets
+fn der_element_len(der: &[u8]) -> Option<usize> {
+    let (tag, header_len, content_len) = der_header(der)?;
+
+    // certificates and private keys are always encoded as a SEQUENCE
+    if tag != SEQUENCE {
+        return None;
+    }
+
+    let len = header_len.checked_add(content_len)?;
+    if len > der.len() {
+        return None;
+    }
+
+    Some(len)
+}
+
+/// Returns the tag, the length of the tag and length octets, and the content length of the
+/// first DER element
+fn der_header(der: &[u8]) -> Option<(u8, usize, usize)> {
+    let tag = *der.first()?;
+    let first = *der.get(1)? as usize;
+    let (header_len, content_len) = if first < 0x80 {
+        (2, first)
+    } else {
+        let octets = first & 0x7f;
+        // indefinite lengths aren't allowed in DER and lengths beyond 4 octets are unreasonable
+        if octets == 0 || octets > 4 {
+            return None;
+        }
+        let len = der
+            .get(2..2 + octets)?
+            .iter()
+            .fold(0usize, |len, octet| (len << 8) | *octet as usize);
+        (2 + octets, len)
+    };
+
+    Some((tag, header_len, content_len))
+}
+
+/// Returns the PEM label of a DER-encoded private key
+///
+/// Each format starts with a version INTEGER, and is identified by the element which follows.
+fn private_key_label(key: &[u8]) -> Option<&'static str> {
+    let (_, header_len, _) = der_header(key)?;
+    let contents = key.get(header_len..)?;
+
+    let (tag, header_len, content_len) = der_header(contents)?;
+    if tag != INTEGER {
+        return None;
+    }
+    let remaining = contents.get(header_len.checked_add(content_len)?..)?;
+
+    match *remaining.first()? {
+        // PrivateKeyInfo, followed by the algorithm identifier
+        SEQUENCE => Some("PRIVATE KEY"),
+        // ECPrivateKey, followed by the private key
+        OCTET_STRING => Some("EC PRIVATE KEY"),
+        // RSAPrivateKey, followed by the modulus
+        INTEGER => Some("RSA PRIVATE KEY"),
+        _ => None,
+    }
+}
+
+fn der_to_pem(label: &str, der: &[u8]) -> Result<Vec<u8>, Error> {
+    let blocks: Vec<_> = der_elements(der)?
+        .map(|element| pem::Pem::new(label, element))
+        .collect();
+    let config = pem::EncodeConfig::new().set_line_ending(pem::LineEnding::LF);
+    Ok(pem::encode_many_config(&blocks, config).into_bytes())
+}
+
 #[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_private_key")))]
 pub const OFFLOAD_PRIVATE_KEY: PrivateKey = PrivateKey(Format::None);
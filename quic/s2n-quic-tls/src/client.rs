@@ -1,27 +1,89 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_message_handler")))]
+use crate::message::MessageHandler;
 use crate::{
+    build::{BuildError, Settings},
     certificate::{IntoCertificate, IntoPrivateKey},
-    keylog::KeyLogHandle,
+    clock::{MonotonicClock, WallClock},
+    invalid_input,
+    keylog::{KeyLog, KeyLogFile, KeyLogHandle, KeyLogHandler},
+    message::MessageHandle,
     params::Params,
+    pin::PublicKeyPins,
+    policy::{self, SecurityPolicy},
     session::Session,
+    verify::VerifyHostName,
     ConfigLoader,
 };
 use s2n_codec::EncoderValue;
 use s2n_quic_core::{application::ServerName, crypto::tls, endpoint};
 use s2n_tls::{
-    callbacks::VerifyHostNameCallback,
+    callbacks::{SessionTicket, SessionTicketCallback, VerifyHostNameCallback},
     config::{self, Config},
+    connection::Connection,
     enums::ClientAuthType,
     error::Error,
 };
-use std::sync::Arc;
+use std::{path::Path, sync::Arc, time::Duration};
+
+/// Stores session tickets issued by servers so they can be used to resume later sessions
+///
+/// Tickets are opaque, serialized byte strings which can be persisted in any external storage.
+/// Servers may issue multiple tickets on a single connection and each one of them is passed to
+/// [`SessionTicketHandler::on_session_ticket`].
+pub trait SessionTicketHandler: 'static + Send + Sync {
+    /// Called when a session ticket is received from `server_name`
+    ///
+    /// The ticket should not be used after `lifetime` has elapsed.
+    fn on_session_ticket(&self, server_name: &ServerName, ticket: &[u8], lifetime: Duration);
+
+    /// Returns a previously stored ticket which will be used to resume a session with `server_name`
+    ///
+    /// If the ticket is rejected by the server, a full handshake is performed instead.
+    fn load_session_ticket(&self, server_name: &ServerName) -> Option<Vec<u8>>;
+}
+
+type SessionTicketHandle = Arc<dyn SessionTicketHandler>;
+
+/// Forwards tickets from s2n-tls to the application's [`SessionTicketHandler`]
+struct SessionTicketAdapter(SessionTicketHandle);
+
+impl SessionTicketCallback for SessionTicketAdapter {
+    fn on_session_ticket(&self, connection: &mut Connection, session_ticket: &SessionTicket) {
+        let server_name = if let Some(server_name) = connection.server_name() {
+            ServerName::from(server_name)
+        } else {
+            // tickets without a server name can't be associated with a future connection
+            return;
+        };
+
+        let lifetime = if let Ok(lifetime) = session_ticket.lifetime() {
+            lifetime
+        } else {
+            return;
+        };
+
+        let mut ticket = if let Ok(len) = session_ticket.len() {
+            vec![0; len]
+        } else {
+            return;
+        };
+
+        if session_ticket.data(&mut ticket).is_ok() {
+            self.0.on_session_ticket(&server_name, &ticket, lifetime);
+        }
+    }
+}
 
 pub struct Client<L: ConfigLoader = Config> {
     loader: L,
     #[allow(dead_code)] // we need to hold on to the handle to ensure it is cleaned up correctly
     keylog: Option<KeyLogHandle>,
+    session_tickets: Option<SessionTicketHandle>,
+    pins: Option<Arc<PublicKeyPins>>,
+    messages: Option<MessageHandle>,
     params: Params,
 }
 
@@ -43,6 +105,9 @@ impl<L: ConfigLoader> Client<L> {
         Self {
             loader,
             keylog: None,
+            session_tickets: None,
+            pins: None,
+            messages: None,
             params: Default::default(),
         }
     }
@@ -63,9 +128,17 @@ impl<L: ConfigLoader> ConfigLoader for Client<L> {
     }
 }
 
+/// Builds a [`Client`] with defaults suited to QUIC
+///
+/// QUIC support is enabled, a security policy supporting TLS 1.3 is selected and the `h3`
+/// application protocol is offered. Use [`Builder::config_mut`] to configure s2n-tls directly.
 pub struct Builder {
     config: config::Builder,
     keylog: Option<KeyLogHandle>,
+    session_tickets: Option<SessionTicketHandle>,
+    pins: Option<Arc<PublicKeyPins>>,
+    messages: Option<MessageHandle>,
+    settings: Settings,
 }
 
 impl Default for Builder {
@@ -79,30 +152,69 @@ impl Default for Builder {
         Self {
             config,
             keylog: None,
+            session_tickets: None,
+            pins: None,
+            messages: None,
+            settings: Settings::default(),
         }
     }
 }
 
 impl Builder {
+    /// Returns the underlying s2n-tls config builder
+    ///
+    /// Changes made through the config aren't visible to [`Self::validate`], so calling this
+    /// opts out of validation: `validate` always succeeds and [`Self::build`] no longer rejects
+    /// inconsistent settings. The caller is responsible for ensuring the config can complete a
+    /// handshake, and that any security policy set on it offers TLS 1.3.
     pub fn config_mut(&mut self) -> &mut s2n_tls::config::Builder {
+        self.settings.untracked = true;
         &mut self.config
     }
 
+    /// Sets the security policy used to negotiate the TLS handshake
+    pub fn with_security_policy(mut self, policy: SecurityPolicy) -> Result<Self, Error> {
+        self.config.set_security_policy(&policy.to_policy()?)?;
+        Ok(self)
+    }
+
+    /// Sets the security policy used to negotiate the TLS handshake by its s2n-tls name
+    ///
+    /// This can be used for policies without a [`SecurityPolicy`] constant. Policies which don't
+    /// offer TLS 1.3 are rejected, since TLS 1.3 is required by QUIC.
+    pub fn with_security_policy_name(mut self, name: &str) -> Result<Self, Error> {
+        self.config.set_security_policy(&policy::from_name(name)?)?;
+        Ok(self)
+    }
+
+    /// Sets the application protocols offered to servers, in order of preference
+    ///
+    /// QUIC requires an application protocol to be negotiated, so at least one must be provided.
+    /// Each protocol must be between 1 and 255 bytes.
     pub fn with_application_protocols<P: IntoIterator<Item = I>, I: AsRef<[u8]>>(
         mut self,
         protocols: P,
     ) -> Result<Self, Error> {
+        let protocols: Vec<I> = protocols.into_iter().collect();
+        crate::validate_application_protocols(&protocols)?;
         self.config.set_application_protocol_preference(protocols)?;
         Ok(self)
     }
 
+    /// Adds a trusted certificate in either PEM or DER format
     pub fn with_certificate<C: IntoCertificate>(mut self, certificate: C) -> Result<Self, Error> {
         let certificate = certificate.into_certificate()?;
-        let certificate = certificate
-            .0
-            .as_pem()
-            .expect("pem is currently the only certificate format supported");
-        self.config.trust_pem(certificate)?;
+        self.config.trust_pem(&certificate.to_pem()?)?;
+        self.settings.on_trust();
+        Ok(self)
+    }
+
+    /// Requests that servers staple an OCSP response to their certificate chain
+    ///
+    /// Stapled responses are validated by s2n-tls against the trust store of this client, and
+    /// are rejected once expired according to the clock set with [`Self::with_wall_clock`].
+    pub fn with_ocsp_stapling(mut self) -> Result<Self, Error> {
+        self.config.enable_ocsp()?;
         Ok(self)
     }
 
@@ -117,6 +229,40 @@ impl Builder {
     /// Calling this method after a method that modifies the trust store will clear it.
     pub fn with_empty_trust_store(mut self) -> Result<Self, Error> {
         self.config.wipe_trust_store()?;
+        self.settings.on_wipe_trust_store();
+        Ok(self)
+    }
+
+    /// Disables all verification of the certificates presented by servers
+    ///
+    /// **This is insecure and should only be used for testing.** Any server will be accepted,
+    /// regardless of its certificate or server name.
+    #[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_dangerous_configs")))]
+    pub fn with_x509_verification_disabled(mut self) -> Result<Self, Error> {
+        // Safety: the caller is explicitly opting into accepting unverified peers
+        unsafe {
+            self.config.disable_x509_verification()?;
+        }
+        self.settings.x509_verification_disabled = true;
+        Ok(self)
+    }
+
+    /// Adds the certificates in a CA file and/or directory to the trust store
+    ///
+    /// These are used to verify the certificates presented by servers. The directory must be
+    /// in the hashed format produced by OpenSSL's `c_rehash`. At least one of `file` or `dir`
+    /// must be provided.
+    pub fn with_trust_location(
+        mut self,
+        file: Option<&Path>,
+        dir: Option<&Path>,
+    ) -> Result<Self, Error> {
+        if file.is_none() && dir.is_none() {
+            return Err(invalid_input("a trust file or directory must be provided"));
+        }
+
+        self.config.trust_location(file, dir)?;
+        self.settings.on_trust();
         Ok(self)
     }
 
@@ -132,16 +278,10 @@ impl Builder {
     ) -> Result<Self, Error> {
         let certificate = certificate.into_certificate()?;
         let private_key = private_key.into_private_key()?;
-        self.config.load_pem(
-            certificate
-                .0
-                .as_pem()
-                .expect("pem is currently the only certificate format supported"),
-            private_key
-                .0
-                .as_pem()
-                .expect("pem is currently the only certificate format supported"),
-        )?;
+        let private_key = private_key
+            .to_pem()?
+            .ok_or_else(|| invalid_input("client identities require a private key"))?;
+        self.config.load_pem(&certificate.to_pem()?, &private_key)?;
         self.config.set_client_auth_type(ClientAuthType::Required)?;
         Ok(self)
     }
@@ -152,43 +292,136 @@ impl Builder {
     /// handshake. If this function is invoked, the default server name validation
     /// logic is disabled; this should only be used in very specific cases where normal
     /// TLS hostname validation is not appropriate.
+    ///
+    /// Host names which are not valid UTF-8 are rejected without invoking the handler. If the
+    /// handler panics, the certificate is rejected.
     pub fn with_verify_host_name_callback<T: 'static + VerifyHostNameCallback>(
         mut self,
         handler: T,
     ) -> Result<Self, Error> {
-        self.config.set_verify_host_callback(handler)?;
+        self.config
+            .set_verify_host_callback(VerifyHostName(handler))?;
+        Ok(self)
+    }
+
+    /// Only accepts servers whose leaf certificate public key matches one of the `pins`
+    ///
+    /// Each pin is the SHA-256 digest of a DER-encoded SubjectPublicKeyInfo. The certificate
+    /// chain is still validated against the trust store, unless validation is disabled with
+    /// `with_x509_verification_disabled`, in which case only the pins are checked. Servers
+    /// which don't match are rejected with a `bad_certificate` CRYPTO_ERROR.
+    ///
+    /// The pins are checked once s2n-tls reports that the handshake is complete. By then the
+    /// 1-RTT keys have already been installed, but the connection is closed before the handshake
+    /// is reported to the application, so no application data is exchanged with a server which
+    /// doesn't match.
+    pub fn with_pinned_public_keys(mut self, pins: Vec<[u8; 32]>) -> Result<Self, Error> {
+        self.pins = Some(Arc::new(PublicKeyPins::new(pins)?));
         Ok(self)
     }
 
+    /// Sets the maximum number of certificates accepted in a server's certificate chain
+    ///
+    /// Chains exceeding this length are rejected during the handshake. The length must be
+    /// at least 1.
     pub fn with_max_cert_chain_depth(mut self, len: u16) -> Result<Self, Error> {
+        if len == 0 {
+            return Err(invalid_input("the max cert chain depth must be at least 1"));
+        }
+
         self.config.set_max_cert_chain_depth(len)?;
         Ok(self)
     }
 
-    pub fn with_key_logging(mut self) -> Result<Self, Error> {
-        use crate::keylog::KeyLog;
+    /// Passes the raw bytes of every handshake message to the provided handler
+    ///
+    /// This is intended for diagnosing interoperability issues and conformance testing. See
+    /// [`crate::message::Transcript`] for a handler which records the messages into memory.
+    #[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_message_handler")))]
+    pub fn with_message_handler<T: MessageHandler>(mut self, handler: T) -> Result<Self, Error> {
+        self.messages = Some(Arc::new(handler));
+        Ok(self)
+    }
+
+    /// Enables session resumption with tickets stored by the provided handler
+    ///
+    /// Every ticket issued by a server is passed to the handler, which is later queried for a
+    /// ticket when connecting to the same server name.
+    pub fn with_session_ticket_handler<T: SessionTicketHandler>(
+        mut self,
+        handler: T,
+    ) -> Result<Self, Error> {
+        let handler: SessionTicketHandle = Arc::new(handler);
+        self.config.enable_session_tickets(true)?;
+        self.config
+            .set_session_ticket_callback(SessionTicketAdapter(handler.clone()))?;
+        self.session_tickets = Some(handler);
+        Ok(self)
+    }
 
-        self.keylog = KeyLog::try_open();
+    /// Replaces the system clock used to check certificate, OCSP response and session ticket
+    /// validity
+    ///
+    /// See [`crate::clock::TestClock`] for a clock which can be advanced from tests.
+    pub fn with_wall_clock<T: WallClock>(mut self, clock: T) -> Result<Self, Error> {
+        self.config.set_wall_clock(clock)?;
+        Ok(self)
+    }
 
+    /// Replaces the monotonic clock used by s2n-tls to measure elapsed time
+    pub fn with_monotonic_clock<T: MonotonicClock>(mut self, clock: T) -> Result<Self, Error> {
+        self.config.set_monotonic_clock(clock)?;
+        Ok(self)
+    }
+
+    /// Logs TLS secrets to the file specified by the `SSLKEYLOGFILE` environment variable
+    ///
+    /// Key logging is disabled if the variable isn't set or the file can't be opened.
+    pub fn with_key_logging(self) -> Result<Self, Error> {
+        if let Some(file) = KeyLogFile::from_env() {
+            self.with_key_log_handler(file)
+        } else {
+            self.with_key_log(None)
+        }
+    }
+
+    /// Passes TLS secrets negotiated by every connection to the provided handler
+    ///
+    /// This should only be used for debugging, since the secrets can decrypt all traffic.
+    pub fn with_key_log_handler<T: KeyLogHandler>(self, handler: T) -> Result<Self, Error> {
+        self.with_key_log(Some(KeyLog::new(handler)))
+    }
+
+    fn with_key_log(mut self, keylog: Option<KeyLogHandle>) -> Result<Self, Error> {
         unsafe {
             // Safety: the KeyLog is stored on `self` to ensure it outlives `config`
-            if let Some(keylog) = self.keylog.as_ref() {
-                self.config
-                    .set_key_log_callback(Some(KeyLog::callback), Arc::as_ptr(keylog) as *mut _)?;
-            } else {
-                // disable key logging if it failed to create a file
-                self.config
-                    .set_key_log_callback(None, core::ptr::null_mut())?;
-            }
+            KeyLog::set(&mut self.config, keylog.as_ref())?;
         }
-
+        self.keylog = keylog;
         Ok(self)
     }
 
+    /// Checks the settings for inconsistencies which would cause every handshake to fail
+    ///
+    /// This performs the same checks as [`Self::build`], returning the specific
+    /// [`BuildError`]. No checks are performed once [`Self::config_mut`] has been called.
+    pub fn validate(&self) -> Result<(), BuildError> {
+        self.settings.validate_client()
+    }
+
+    /// Builds the [`Client`]
+    ///
+    /// Inconsistent settings are rejected with the [`BuildError`] returned by
+    /// [`Self::validate`], inside an [`InvalidInput`](std::io::ErrorKind::InvalidInput) error.
     pub fn build(self) -> Result<Client, Error> {
+        self.validate().map_err(invalid_input)?;
+
         Ok(Client {
             loader: self.config.build()?,
             keylog: self.keylog,
+            session_tickets: self.session_tickets,
+            pins: self.pins,
+            messages: self.messages,
             params: Default::default(),
         })
     }
@@ -209,9 +442,26 @@ impl<L: ConfigLoader> tls::Endpoint for Client<L> {
         let config = self.loader.load(crate::ConnectionContext {
             server_name: Some(&server_name),
         });
-        self.params.with(params, |params| {
+        let ticket = self
+            .session_tickets
+            .as_ref()
+            .and_then(|handler| handler.load_session_ticket(&server_name));
+        let mut session = self.params.with(params, |params| {
             Session::new(endpoint::Type::Client, config, params, Some(server_name)).unwrap()
-        })
+        });
+
+        if let Some(ticket) = ticket {
+            // an invalid ticket only means the session can't be resumed so a full handshake is
+            // performed instead
+            let _ = session.connection.set_session_ticket(&ticket);
+        }
+
+        session.pins = self.pins.clone();
+        if let Some(handler) = self.messages.as_ref() {
+            session.set_message_handler(handler.clone());
+        }
+
+        session
     }
 
     fn max_tag_length(&self) -> usize {
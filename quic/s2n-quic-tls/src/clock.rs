@@ -0,0 +1,63 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Clocks used by s2n-tls to check session ticket lifetimes and OCSP response validity
+//!
+//! By default the system clocks are used. Overriding them makes it possible to simulate
+//! expiry deterministically in tests.
+
+pub use s2n_tls::callbacks::{MonotonicClock, WallClock};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// A clock which only moves when it is advanced
+///
+/// Clones share the same time, so a clone can be passed to a builder while the original is
+/// advanced from the test. It can be used as both the wall clock and the monotonic clock.
+#[derive(Clone, Debug)]
+pub struct TestClock(Arc<Mutex<Duration>>);
+
+impl Default for TestClock {
+    /// Creates a clock starting at the current system time
+    fn default() -> Self {
+        Self::new(SystemTime::now())
+    }
+}
+
+impl TestClock {
+    /// Creates a clock starting at `start`
+    ///
+    /// Times before the unix epoch are clamped to the epoch.
+    pub fn new(start: SystemTime) -> Self {
+        let since_epoch = start.duration_since(UNIX_EPOCH).unwrap_or_default();
+        Self(Arc::new(Mutex::new(since_epoch)))
+    }
+
+    /// Returns the current time of the clock
+    pub fn now(&self) -> SystemTime {
+        UNIX_EPOCH + self.since_epoch()
+    }
+
+    /// Moves the clock forward by `duration`
+    pub fn advance(&self, duration: Duration) {
+        *self.0.lock().unwrap() += duration;
+    }
+
+    fn since_epoch(&self) -> Duration {
+        *self.0.lock().unwrap()
+    }
+}
+
+impl WallClock for TestClock {
+    fn get_time_since_epoch(&self) -> Duration {
+        self.since_epoch()
+    }
+}
+
+impl MonotonicClock for TestClock {
+    fn get_time(&self) -> Duration {
+        self.since_epoch()
+    }
+}
@@ -1,51 +1,85 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
+//! Logging of TLS secrets in the [NSS key log format](https://firefox-source-docs.mozilla.org/security/nss/legacy/key_log_format/index.html)
+//!
+//! This can be used by tools like Wireshark to decrypt captured traffic, and should never be
+//! enabled in production.
+
 use libc::{c_int, c_void};
-use s2n_tls::ffi::*;
+use s2n_tls::{config, error::Error, ffi::*};
 use std::{
     fs::{File, OpenOptions},
-    io::{BufWriter, Write},
+    io::{self, BufWriter, Write},
+    panic::{catch_unwind, AssertUnwindSafe},
+    path::Path,
     sync::{Arc, Mutex},
 };
 
-pub type KeyLogHandle = Arc<KeyLog>;
+/// Receives secrets negotiated during TLS handshakes
+///
+/// A single handler is shared by all of the connections created by an endpoint, which may
+/// run on multiple threads, so it must be both `Send` and `Sync`.
+pub trait KeyLogHandler: 'static + Send + Sync {
+    /// Called for each secret negotiated by a connection
+    fn on_key_log(&self, line: &KeyLogLine);
+}
 
-pub struct KeyLog(Mutex<BufWriter<File>>);
+/// A single line in the NSS key log format
+///
+/// Lines consist of a label, the hex-encoded client random, and the hex-encoded secret,
+/// separated by spaces.
+#[derive(Clone, Copy, Debug)]
+pub struct KeyLogLine<'a>(pub(crate) &'a [u8]);
 
-impl KeyLog {
-    pub fn try_open() -> Option<KeyLogHandle> {
-        let path = std::env::var("SSLKEYLOGFILE").ok()?;
-        let file = OpenOptions::new()
-            .append(true)
-            .create(true)
-            .open(path)
-            .ok()?;
-        let file = BufWriter::new(file);
-        let file = Mutex::new(file);
-        let keylog = Self(file);
-        let keylog = Arc::new(keylog);
-        Some(keylog)
+impl<'a> KeyLogLine<'a> {
+    /// Returns the entire line, without a trailing newline
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
     }
 
-    pub unsafe extern "C" fn callback(
-        ctx: *mut c_void,
-        _conn: *mut s2n_connection,
-        logline: *mut u8,
-        len: usize,
-    ) -> c_int {
-        let handle = &mut *(ctx as *mut Self);
-        let logline = core::slice::from_raw_parts(logline, len);
+    /// Returns the label of the secret, e.g. `CLIENT_TRAFFIC_SECRET_0`
+    pub fn label(&self) -> Option<&'a [u8]> {
+        self.field(0)
+    }
 
-        // ignore any errors
-        let _ = handle.on_logline(logline);
+    /// Returns the hex-encoded client random of the connection
+    pub fn client_random(&self) -> Option<&'a [u8]> {
+        self.field(1)
+    }
 
-        0
+    /// Returns the hex-encoded secret
+    pub fn secret(&self) -> Option<&'a [u8]> {
+        self.field(2)
+    }
+
+    fn field(&self, index: usize) -> Option<&'a [u8]> {
+        self.0.split(|b| *b == b' ').nth(index)
+    }
+}
+
+/// Appends key log lines to a file
+pub struct KeyLogFile(Mutex<BufWriter<File>>);
+
+impl KeyLogFile {
+    /// Opens the file at `path`, appending to it if it already exists
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().append(true).create(true).open(path)?;
+        let file = BufWriter::new(file);
+        Ok(Self(Mutex::new(file)))
+    }
+
+    /// Opens the file specified by the `SSLKEYLOGFILE` environment variable
+    ///
+    /// Returns `None` if the variable isn't set or the file can't be opened.
+    pub fn from_env() -> Option<Self> {
+        let path = std::env::var_os("SSLKEYLOGFILE")?;
+        Self::open(path).ok()
     }
 
-    fn on_logline(&mut self, logline: &[u8]) -> Option<()> {
+    fn write(&self, line: &[u8]) -> Option<()> {
         let mut file = self.0.lock().ok()?;
-        file.write_all(logline).ok()?;
+        file.write_all(line).ok()?;
         file.write_all(b"\n").ok()?;
 
         // ensure keys are immediately written so tools can use them
@@ -54,3 +88,56 @@ impl KeyLog {
         Some(())
     }
 }
+
+impl KeyLogHandler for KeyLogFile {
+    fn on_key_log(&self, line: &KeyLogLine) {
+        // ignore any errors
+        let _ = self.write(line.as_bytes());
+    }
+}
+
+pub(crate) type KeyLogHandle = Arc<KeyLog>;
+
+pub(crate) struct KeyLog(Box<dyn KeyLogHandler>);
+
+impl KeyLog {
+    pub fn new<T: KeyLogHandler>(handler: T) -> KeyLogHandle {
+        Arc::new(Self(Box::new(handler)))
+    }
+
+    /// Configures the key log callback on the config
+    ///
+    /// Passing `None` will disable key logging.
+    ///
+    /// # Safety
+    ///
+    /// The handle must outlive the config and any connections created from it
+    pub unsafe fn set(
+        config: &mut config::Builder,
+        handle: Option<&KeyLogHandle>,
+    ) -> Result<(), Error> {
+        if let Some(handle) = handle {
+            config.set_key_log_callback(Some(Self::callback), Arc::as_ptr(handle) as *mut _)?;
+        } else {
+            config.set_key_log_callback(None, core::ptr::null_mut())?;
+        }
+        Ok(())
+    }
+
+    unsafe extern "C" fn callback(
+        ctx: *mut c_void,
+        _conn: *mut s2n_connection,
+        logline: *mut u8,
+        len: usize,
+    ) -> c_int {
+        let handle = &*(ctx as *const Self);
+        let logline = core::slice::from_raw_parts(logline, len);
+
+        // don't allow panics to unwind into s2n-tls
+        let _ = catch_unwind(AssertUnwindSafe(|| {
+            handle.0.on_key_log(&KeyLogLine(logline));
+        }));
+
+        0
+    }
+}
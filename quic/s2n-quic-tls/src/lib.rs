@@ -46,16 +46,63 @@ impl ConfigLoader for Box<dyn ConfigLoader> {
     }
 }
 
+/// Creates an error for invalid arguments, which are rejected before being passed to s2n-tls
+pub(crate) fn invalid_input<M>(msg: M) -> error::Error
+where
+    M: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    error::Error::io_error(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg))
+}
+
+/// Ensures the application protocols can be encoded in the ALPN extension
+pub(crate) fn validate_application_protocols<I: AsRef<[u8]>>(
+    protocols: &[I],
+) -> Result<(), error::Error> {
+    if protocols.is_empty() {
+        return Err(invalid_input(
+            "at least one application protocol must be provided",
+        ));
+    }
+
+    //= https://www.rfc-editor.org/rfc/rfc7301#section-3.1
+    //# Empty strings
+    //# MUST NOT be included and byte strings MUST NOT be truncated.
+    for protocol in protocols {
+        let len = protocol.as_ref().len();
+        if len == 0 || len > u8::MAX as usize {
+            return Err(invalid_input(
+                "application protocols must be between 1 and 255 bytes",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+mod build;
 mod callback;
-mod keylog;
+#[cfg(not(any(test, all(s2n_quic_unstable, feature = "unstable_message_handler"))))]
+#[allow(dead_code)] // handlers can only be set with the unstable feature
+mod message;
 mod params;
+mod pin;
 mod session;
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_client_hello")))]
+mod sni;
+mod verify;
 
 pub mod certificate;
 pub mod client;
+pub mod clock;
+pub mod keylog;
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_message_handler")))]
+pub mod message;
+pub mod policy;
 pub mod server;
 
+pub use build::BuildError;
 pub use client::Client;
+pub use policy::SecurityPolicy;
 pub use s2n_tls::*;
 pub use server::Server;
 
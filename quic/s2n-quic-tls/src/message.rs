@@ -0,0 +1,244 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Inspection of the raw TLS handshake messages exchanged by each connection
+//!
+//! This is intended for debugging and conformance testing. The messages contain everything
+//! sent during the handshake, so handlers should avoid logging them in production.
+
+use core::fmt;
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_message_handler")))]
+use std::sync::Mutex;
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::Arc,
+};
+
+/// The length of the `msg_type` and `length` fields preceding each handshake message
+const HEADER_LEN: usize = 4;
+
+/// The direction a handshake message was sent in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+/// A contiguous piece of a single handshake message
+///
+/// Messages may be split across several fragments, in which case the fragments are passed to
+/// the handler in order. The first fragment of each message only contains its 4-byte header.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct Fragment<'a> {
+    pub direction: Direction,
+    /// The `HandshakeType` of the message, e.g. `1` for a ClientHello
+    pub msg_type: u8,
+    /// The raw bytes of the fragment
+    pub bytes: &'a [u8],
+    /// Whether this is the first fragment of the message, containing its header
+    pub is_first: bool,
+    /// Whether this is the final fragment of the message
+    pub is_last: bool,
+}
+
+/// Receives the handshake messages exchanged by each connection
+///
+/// A single handler is shared by all of the connections created by an endpoint, which may
+/// run on multiple threads, so it must be both `Send` and `Sync`. If the handler panics, the
+/// fragment is dropped.
+pub trait MessageHandler: 'static + Send + Sync {
+    /// Called for each fragment of a handshake message
+    fn on_fragment(&self, fragment: &Fragment);
+}
+
+pub(crate) type MessageHandle = Arc<dyn MessageHandler>;
+
+/// Splits the handshake data exchanged by a connection into message fragments
+pub(crate) struct Messages {
+    handler: MessageHandle,
+    sent: Reader,
+    received: Reader,
+}
+
+impl fmt::Debug for Messages {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Messages")
+            .field("sent", &self.sent)
+            .field("received", &self.received)
+            .finish()
+    }
+}
+
+impl Messages {
+    pub fn new(handler: MessageHandle) -> Self {
+        Self {
+            handler,
+            sent: Reader::default(),
+            received: Reader::default(),
+        }
+    }
+
+    pub fn on_sent(&mut self, data: &[u8]) {
+        let handler = &*self.handler;
+        self.sent.read(data, |msg_type, bytes, is_first, is_last| {
+            emit(handler, Direction::Sent, msg_type, bytes, is_first, is_last)
+        });
+    }
+
+    pub fn on_received(&mut self, data: &[u8]) {
+        let handler = &*self.handler;
+        self.received
+            .read(data, |msg_type, bytes, is_first, is_last| {
+                emit(
+                    handler,
+                    Direction::Received,
+                    msg_type,
+                    bytes,
+                    is_first,
+                    is_last,
+                )
+            });
+    }
+}
+
+fn emit(
+    handler: &dyn MessageHandler,
+    direction: Direction,
+    msg_type: u8,
+    bytes: &[u8],
+    is_first: bool,
+    is_last: bool,
+) {
+    let fragment = Fragment {
+        direction,
+        msg_type,
+        bytes,
+        is_first,
+        is_last,
+    };
+
+    // don't allow panics to unwind into s2n-tls
+    let _ = catch_unwind(AssertUnwindSafe(|| handler.on_fragment(&fragment)));
+}
+
+/// Tracks the message boundaries in a single direction
+#[derive(Debug, Default)]
+struct Reader {
+    header: [u8; HEADER_LEN],
+    header_len: usize,
+    msg_type: u8,
+    /// The number of bytes remaining in the current message body
+    remaining: usize,
+}
+
+impl Reader {
+    fn read<F: FnMut(u8, &[u8], bool, bool)>(&mut self, mut data: &[u8], mut on_fragment: F) {
+        while !data.is_empty() {
+            if self.remaining > 0 {
+                let len = self.remaining.min(data.len());
+                let (body, rest) = data.split_at(len);
+                self.remaining -= len;
+                on_fragment(self.msg_type, body, false, self.remaining == 0);
+                data = rest;
+                continue;
+            }
+
+            // headers are buffered in case they are split across writes
+            let len = (HEADER_LEN - self.header_len).min(data.len());
+            let (header, rest) = data.split_at(len);
+            self.header[self.header_len..][..len].copy_from_slice(header);
+            self.header_len += len;
+            data = rest;
+
+            if self.header_len < HEADER_LEN {
+                return;
+            }
+
+            self.header_len = 0;
+            self.msg_type = self.header[0];
+            self.remaining =
+                u32::from_be_bytes([0, self.header[1], self.header[2], self.header[3]]) as usize;
+            on_fragment(self.msg_type, &self.header, true, self.remaining == 0);
+        }
+    }
+}
+
+/// A complete handshake message recorded by a [`Transcript`]
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_message_handler")))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Message {
+    pub direction: Direction,
+    pub msg_type: u8,
+    /// The entire message, including its header
+    pub bytes: Vec<u8>,
+}
+
+/// Records every handshake message into memory
+///
+/// Clones share the same transcript, so a clone can be passed to the builder while the
+/// original is used to inspect the recorded messages. Messages are recorded in the order they
+/// were exchanged, so the transcript should only be used with a single connection at a time.
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_message_handler")))]
+#[derive(Clone, Debug, Default)]
+pub struct Transcript(Arc<Mutex<Vec<Message>>>);
+
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_message_handler")))]
+impl Transcript {
+    /// Returns all of the complete messages recorded so far
+    pub fn messages(&self) -> Vec<Message> {
+        let messages = self.0.lock().unwrap();
+        messages
+            .iter()
+            .filter(|message| message.is_complete())
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the direction and type of each complete message recorded so far
+    pub fn msg_types(&self) -> Vec<(Direction, u8)> {
+        self.messages()
+            .iter()
+            .map(|message| (message.direction, message.msg_type))
+            .collect()
+    }
+
+    /// Removes all of the recorded messages
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear();
+    }
+}
+
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_message_handler")))]
+impl Message {
+    fn is_complete(&self) -> bool {
+        self.bytes.len() >= HEADER_LEN
+            && self.bytes.len() - HEADER_LEN
+                == u32::from_be_bytes([0, self.bytes[1], self.bytes[2], self.bytes[3]]) as usize
+    }
+}
+
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_message_handler")))]
+impl MessageHandler for Transcript {
+    fn on_fragment(&self, fragment: &Fragment) {
+        let mut messages = self.0.lock().unwrap();
+
+        if fragment.is_first {
+            messages.push(Message {
+                direction: fragment.direction,
+                msg_type: fragment.msg_type,
+                bytes: fragment.bytes.to_vec(),
+            });
+            return;
+        }
+
+        if let Some(message) = messages
+            .iter_mut()
+            .rev()
+            .find(|message| message.direction == fragment.direction && !message.is_complete())
+        {
+            message.bytes.extend_from_slice(fragment.bytes);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Pinning of peer public keys by the SHA-256 digest of their SubjectPublicKeyInfo
+
+use crate::invalid_input;
+use s2n_quic_crypto::{constant_time, digest};
+use s2n_tls::error::Error;
+
+const SEQUENCE: u8 = 0x30;
+/// The explicit tag of the optional version field in a TBSCertificate
+const VERSION: u8 = 0xa0;
+
+#[derive(Debug)]
+pub(crate) struct PublicKeyPins(Vec<[u8; 32]>);
+
+impl PublicKeyPins {
+    pub(crate) fn new(pins: Vec<[u8; 32]>) -> Result<Self, Error> {
+        if pins.is_empty() {
+            return Err(invalid_input(
+                "at least one public key pin must be provided",
+            ));
+        }
+
+        Ok(Self(pins))
+    }
+
+    /// Returns `true` if the public key of the DER-encoded certificate matches a pin
+    pub(crate) fn verify(&self, certificate: &[u8]) -> bool {
+        let spki = if let Some(spki) = subject_public_key_info(certificate) {
+            spki
+        } else {
+            return false;
+        };
+
+        let digest = digest::digest(&digest::SHA256, spki);
+        let digest = digest.as_ref();
+
+        // compare against every pin in constant time so the matching pin isn't revealed
+        self.0.iter().fold(false, |matched, pin| {
+            matched | constant_time::verify_slices_are_equal(pin, digest).is_ok()
+        })
+    }
+}
+
+/// Returns the encoded SubjectPublicKeyInfo of a DER-encoded X.509 certificate
+///
+/// ```text
+/// Certificate ::= SEQUENCE {
+///     tbsCertificate       TBSCertificate,
+///     ...
+/// }
+///
+/// TBSCertificate ::= SEQUENCE {
+///     version         [0]  EXPLICIT Version DEFAULT v1,
+///     serialNumber         CertificateSerialNumber,
+///     signature            AlgorithmIdentifier,
+///     issuer               Name,
+///     validity             Validity,
+///     subject              Name,
+///     subjectPublicKeyInfo SubjectPublicKeyInfo,
+///     ...
+/// }
+/// ```
+fn subject_public_key_info(certificate: &[u8]) -> Option<&[u8]> {
+    let (tag, certificate, _) = read_element(certificate)?;
+    if tag != SEQUENCE {
+        return None;
+    }
+
+    let (tag, tbs, _) = read_element(certificate)?;
+    if tag != SEQUENCE {
+        return None;
+    }
+
+    let mut remaining = tbs;
+    if remaining.first() == Some(&VERSION) {
+        remaining = read_element(remaining)?.2;
+    }
+
+    // skip the serial number, signature, issuer, validity and subject
+    for _ in 0..5 {
+        remaining = read_element(remaining)?.2;
+    }
+
+    let spki_len = remaining.len() - read_element(remaining)?.2.len();
+    let spki = &remaining[..spki_len];
+    if spki.first() != Some(&SEQUENCE) {
+        return None;
+    }
+
+    Some(spki)
+}
+
+/// Reads a single DER element, returning the tag, the contents and the remaining input
+fn read_element(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let tag = *input.first()?;
+    let first = *input.get(1)? as usize;
+
+    let (header_len, len) = if first < 0x80 {
+        (2, first)
+    } else {
+        let octets = first & 0x7f;
+        if octets == 0 || octets > 4 {
+            return None;
+        }
+        let len = input
+            .get(2..2 + octets)?
+            .iter()
+            .fold(0usize, |len, octet| (len << 8) | *octet as usize);
+        (2 + octets, len)
+    };
+
+    let end = header_len.checked_add(len)?;
+    let contents = input.get(header_len..end)?;
+    Some((tag, contents, &input[end..]))
+}
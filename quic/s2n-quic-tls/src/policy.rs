@@ -0,0 +1,229 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Security policies which are compatible with QUIC
+
+use crate::invalid_input;
+use core::{ffi::c_void, task::Poll};
+use s2n_codec::{DecoderBuffer, DecoderError};
+use s2n_tls::{
+    config,
+    connection::Connection,
+    enums::{Blinding, Mode, Version},
+    error::Error,
+    ffi::s2n_status_code,
+    security::Policy,
+};
+
+/// A security policy which supports TLS 1.3, as required by QUIC
+///
+/// See the [s2n-tls usage guide](https://github.com/aws/s2n-tls/blob/main/docs/USAGE-GUIDE.md#security-policies)
+/// for the contents of each policy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SecurityPolicy(&'static str);
+
+impl SecurityPolicy {
+    /// Only negotiates TLS 1.3 with the default s2n-tls preferences
+    pub const DEFAULT_TLS13: Self = Self("default_tls13");
+
+    /// Negotiates TLS 1.0 through TLS 1.3 with the `20190801` preferences
+    pub const V20190801: Self = Self("20190801");
+
+    /// Negotiates TLS 1.0 through TLS 1.3 with the `20190802` preferences
+    pub const V20190802: Self = Self("20190802");
+
+    /// Negotiates TLS 1.3 and prefers hybrid post-quantum key exchange groups
+    ///
+    /// Classical groups are negotiated with peers that don't support post-quantum key exchange.
+    /// Hybrid groups are only offered if s2n-tls is built with a libcrypto that supports them,
+    /// e.g. by enabling the `pq` feature of s2n-tls.
+    pub const PQ_TLS13_20230601: Self = Self("PQ-TLS-1-3-2023-06-01");
+
+    /// Returns all of the policies with a [`SecurityPolicy`] constant
+    ///
+    /// This is a curated subset of the policies s2n-tls knows about, which are known to be
+    /// compatible with QUIC. s2n-tls doesn't expose a list of its policies, most of which don't
+    /// support TLS 1.3. Other policies can still be selected by name with
+    /// `with_security_policy_name`, which rejects any that don't offer TLS 1.3.
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::DEFAULT_TLS13,
+            Self::V20190801,
+            Self::V20190802,
+            Self::PQ_TLS13_20230601,
+        ]
+    }
+
+    /// Returns the s2n-tls name of the policy
+    pub fn name(&self) -> &'static str {
+        self.0
+    }
+
+    /// Returns the IANA values of the cipher suites enabled by the policy, in order of preference
+    ///
+    /// The suites are read from a ClientHello written by s2n-tls with the policy, so suites
+    /// which aren't supported by the libcrypto s2n-tls was built with aren't included. Policies
+    /// which also negotiate earlier versions of TLS include their TLS 1.2 suites, even though
+    /// QUIC only uses the TLS 1.3 suites.
+    pub fn ciphers(&self) -> Result<Vec<u16>, Error> {
+        Ok(Offer::new(&self.to_policy()?)?.ciphers)
+    }
+
+    /// Returns the IANA values of the signature schemes enabled by the policy, in order of
+    /// preference
+    ///
+    /// Like [`Self::ciphers`], these are read from a ClientHello written by s2n-tls with the
+    /// policy.
+    pub fn signature_algorithms(&self) -> Result<Vec<u16>, Error> {
+        Ok(Offer::new(&self.to_policy()?)?.signature_algorithms)
+    }
+
+    pub(crate) fn to_policy(self) -> Result<Policy, Error> {
+        Policy::from_version(self.0)
+    }
+}
+
+/// Looks up a policy by its s2n-tls name
+///
+/// Policies which don't support TLS 1.3 are rejected, since they can't be used with QUIC.
+pub(crate) fn from_name(name: &str) -> Result<Policy, Error> {
+    let policy = Policy::from_version(name)?;
+
+    if Offer::new(&policy)?.version != Version::TLS13 {
+        return Err(invalid_input(format!(
+            "security policy {name:?} does not support TLS 1.3, which is required by QUIC"
+        )));
+    }
+
+    Ok(policy)
+}
+
+/// The ClientHello extension listing the signature schemes offered by the client, defined in
+/// RFC 8446 section 4.2
+const SIGNATURE_ALGORITHMS_EXTENSION: u16 = 13;
+
+/// Cipher suite values which only signal client capabilities
+const SIGNALING_CIPHER_SUITES: &[u16] = &[
+    // TLS_EMPTY_RENEGOTIATION_INFO_SCSV
+    0x00ff, // TLS_FALLBACK_SCSV
+    0x5600,
+];
+
+/// What s2n-tls offers in a ClientHello written with a policy
+///
+/// s2n-tls doesn't expose the contents of a policy, so a client connection using the policy
+/// writes its ClientHello into a buffer and the offer is read back from it.
+struct Offer {
+    /// The highest protocol version offered
+    version: Version,
+    ciphers: Vec<u16>,
+    signature_algorithms: Vec<u16>,
+}
+
+impl Offer {
+    fn new(policy: &Policy) -> Result<Self, Error> {
+        unsafe extern "C" fn send_cb(
+            context: *mut c_void,
+            data: *const u8,
+            len: u32,
+        ) -> s2n_status_code::Type {
+            let records = &mut *(context as *mut Vec<u8>);
+            records.extend_from_slice(core::slice::from_raw_parts(data, len as _));
+            len as _
+        }
+
+        unsafe extern "C" fn recv_cb(
+            _context: *mut c_void,
+            _data: *mut u8,
+            _len: u32,
+        ) -> s2n_status_code::Type {
+            // nothing is ever received, so the connection blocks after sending the ClientHello
+            errno::set_errno(errno::Errno(libc::EWOULDBLOCK));
+            -1
+        }
+
+        let mut config = config::Builder::default();
+        config.set_security_policy(policy)?;
+        let config = config.build()?;
+
+        // declared before the connection so it's dropped after it
+        let mut records = Vec::<u8>::new();
+
+        let mut connection = Connection::new(Mode::Client);
+        connection.set_blinding(Blinding::SelfService)?;
+        connection.set_config(config)?;
+        connection.set_send_callback(Some(send_cb))?;
+        connection.set_receive_callback(Some(recv_cb))?;
+        unsafe {
+            // Safety: `records` outlives the connection
+            connection.set_send_context(&mut records as *mut Vec<u8> as *mut c_void)?;
+        }
+
+        if let Poll::Ready(Err(error)) = connection.poll_negotiate() {
+            return Err(error);
+        }
+
+        let version = connection.client_protocol_version()?;
+        let (ciphers, signature_algorithms) = parse_client_hello(&records).map_err(|_| {
+            Error::io_error(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "s2n-tls wrote an invalid ClientHello",
+            ))
+        })?;
+
+        Ok(Self {
+            version,
+            ciphers,
+            signature_algorithms,
+        })
+    }
+}
+
+/// Reads the cipher suites and signature schemes from the records carrying a ClientHello
+fn parse_client_hello(records: &[u8]) -> Result<(Vec<u16>, Vec<u16>), DecoderError> {
+    // a ClientHello with many key shares may be split over several records
+    let mut message = Vec::new();
+    let mut buffer = DecoderBuffer::new(records);
+    while !buffer.is_empty() {
+        // the content type and legacy record version precede the fragment
+        let (fragment, remaining) = buffer.skip(3)?.decode_slice_with_len_prefix::<u16>()?;
+        message.extend_from_slice(fragment.into_less_safe_slice());
+        buffer = remaining;
+    }
+
+    // the message type and length precede the ClientHello, which is made up of the legacy
+    // version, random, legacy session id, cipher suites, legacy compression methods and
+    // extensions, see RFC 8446 section 4.1.2
+    let buffer = DecoderBuffer::new(&message).skip(4)?;
+    let buffer = buffer.skip(2 + 32)?.skip_with_len_prefix::<u8>()?;
+    let (cipher_suites, buffer) = buffer.decode_slice_with_len_prefix::<u16>()?;
+    let (mut extensions, _) = buffer
+        .skip_with_len_prefix::<u8>()?
+        .decode_slice_with_len_prefix::<u16>()?;
+
+    let mut ciphers = parse_list(cipher_suites)?;
+    ciphers.retain(|cipher| !SIGNALING_CIPHER_SUITES.contains(cipher));
+
+    let mut signature_algorithms = Vec::new();
+    while !extensions.is_empty() {
+        let (extension_type, remaining) = extensions.decode::<u16>()?;
+        let (data, remaining) = remaining.decode_slice_with_len_prefix::<u16>()?;
+        if extension_type == SIGNATURE_ALGORITHMS_EXTENSION {
+            let (schemes, _) = data.decode_slice_with_len_prefix::<u16>()?;
+            signature_algorithms = parse_list(schemes)?;
+        }
+        extensions = remaining;
+    }
+
+    Ok((ciphers, signature_algorithms))
+}
+
+fn parse_list(mut buffer: DecoderBuffer) -> Result<Vec<u16>, DecoderError> {
+    let mut values = Vec::with_capacity(buffer.len() / 2);
+    while !buffer.is_empty() {
+        let (value, remaining) = buffer.decode::<u16>()?;
+        values.push(value);
+        buffer = remaining;
+    }
+    Ok(values)
+}
@@ -1,11 +1,21 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_message_handler")))]
+use crate::message::MessageHandler;
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_client_hello")))]
+use crate::sni::SniResolver;
 use crate::{
-    certificate::{Format, IntoCertificate, IntoPrivateKey},
-    keylog::KeyLogHandle,
+    build::{BuildError, Settings},
+    certificate::{IntoCertificate, IntoPrivateKey},
+    clock::{MonotonicClock, WallClock},
+    invalid_input,
+    keylog::{KeyLog, KeyLogFile, KeyLogHandle, KeyLogHandler},
+    message::MessageHandle,
     params::Params,
+    policy::{self, SecurityPolicy},
     session::Session,
+    verify::VerifyHostName,
     ConfigLoader,
 };
 use s2n_codec::EncoderValue;
@@ -20,12 +30,18 @@ use s2n_tls::{
     enums::ClientAuthType,
     error::Error,
 };
-use std::sync::Arc;
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_client_hello")))]
+use std::collections::HashMap;
+use std::{path::Path, time::SystemTime};
+
+/// The maximum length of a session ticket key name accepted by s2n-tls
+const MAX_TICKET_KEY_NAME_LEN: usize = 16;
 
 pub struct Server<L: ConfigLoader = Config> {
     loader: L,
     #[allow(dead_code)] // we need to hold on to the handle to ensure it is cleaned up correctly
     keylog: Option<KeyLogHandle>,
+    messages: Option<MessageHandle>,
     params: Params,
 }
 
@@ -47,6 +63,7 @@ impl<L: ConfigLoader> Server<L> {
         Self {
             loader,
             keylog: None,
+            messages: None,
             params: Default::default(),
         }
     }
@@ -54,8 +71,9 @@ impl<L: ConfigLoader> Server<L> {
 
 impl Default for Server {
     fn default() -> Self {
+        // the default server has no certificate, so it is built without validation
         Self::builder()
-            .build()
+            .build_unvalidated()
             .expect("could not create a default server")
     }
 }
@@ -73,9 +91,17 @@ impl<L: ConfigLoader> From<Server<L>> for Config {
     }
 }
 
+/// Builds a [`Server`] with defaults suited to QUIC
+///
+/// QUIC support is enabled, a security policy supporting TLS 1.3 is selected and the `h3`
+/// application protocol is accepted. A certificate should be configured with
+/// [`Builder::with_certificate`] unless the config is swapped in a ClientHello handler.
 pub struct Builder {
     config: config::Builder,
     keylog: Option<KeyLogHandle>,
+    messages: Option<MessageHandle>,
+    ocsp_response: Option<Vec<u8>>,
+    settings: Settings,
 }
 
 impl Default for Builder {
@@ -89,24 +115,74 @@ impl Default for Builder {
         Self {
             config,
             keylog: None,
+            messages: None,
+            ocsp_response: None,
+            settings: Settings::default(),
         }
     }
 }
 
 impl Builder {
+    /// Returns the underlying s2n-tls config builder
+    ///
+    /// Changes made through the config aren't visible to [`Self::validate`], so calling this
+    /// opts out of validation: `validate` always succeeds and [`Self::build`] no longer rejects
+    /// inconsistent settings, including a missing certificate. The caller is responsible for
+    /// ensuring the config can complete a handshake, and that any security policy set on it
+    /// offers TLS 1.3.
     pub fn config_mut(&mut self) -> &mut s2n_tls::config::Builder {
+        self.settings.untracked = true;
         &mut self.config
     }
 
+    /// Sets a handler which is called after the ClientHello is received
+    ///
+    /// The handler can inspect the ClientHello through the connection (e.g. the requested
+    /// server name) and select a different [`Config`] to continue the handshake with by
+    /// calling `Connection::set_config`. The connection holds a reference to the replacement
+    /// config for the remainder of its lifetime. The replacement config should be built from
+    /// another [`Builder`] to ensure it is configured correctly for QUIC.
+    ///
+    /// The handler may also return a pending future to resolve the config asynchronously,
+    /// in which case the handshake is resumed once the future completes.
     #[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_client_hello")))]
     pub fn with_client_hello_handler<T: 'static + ClientHelloCallback>(
         mut self,
         handler: T,
     ) -> Result<Self, Error> {
         self.config.set_client_hello_callback(handler)?;
+        self.settings.client_hello_handler = true;
+        Ok(self)
+    }
+
+    /// Selects a config for each connection based on the server name requested by the client
+    ///
+    /// Server names are matched case-insensitively, either exactly or against wildcard names
+    /// such as `*.example.com`, which match a single label. If no name matches, the `default`
+    /// config is used or, if none is provided, the handshake is rejected. Each config should
+    /// be built from another [`Builder`] to ensure it is configured correctly for QUIC.
+    /// Names which only differ by case are rejected, since they would match the same clients.
+    ///
+    /// This installs a ClientHello handler, replacing any set with
+    /// [`Self::with_client_hello_handler`].
+    #[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_client_hello")))]
+    pub fn with_sni_configs(
+        mut self,
+        configs: HashMap<String, Config>,
+        default: Option<Config>,
+    ) -> Result<Self, Error> {
+        let resolver = SniResolver::new(configs, default)?;
+        self.config.set_client_hello_callback(resolver)?;
+        self.settings.client_hello_handler = true;
         Ok(self)
     }
 
+    /// Offloads private key operations to the provided handler
+    ///
+    /// This allows private keys to be stored in an HSM or KMS. The handler can return a
+    /// pending future, in which case the handshake is blocked until the future completes,
+    /// without blocking the endpoint. The certificate should be configured with
+    /// [`crate::certificate::OFFLOAD_PRIVATE_KEY`] in place of the private key.
     #[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_private_key")))]
     pub fn with_private_key_handler<T: 'static + PrivateKeyCallback>(
         mut self,
@@ -116,29 +192,68 @@ impl Builder {
         Ok(self)
     }
 
+    /// Sets the security policy used to negotiate the TLS handshake
+    pub fn with_security_policy(mut self, policy: SecurityPolicy) -> Result<Self, Error> {
+        self.config.set_security_policy(&policy.to_policy()?)?;
+        Ok(self)
+    }
+
+    /// Sets the security policy used to negotiate the TLS handshake by its s2n-tls name
+    ///
+    /// This can be used for policies without a [`SecurityPolicy`] constant. Policies which don't
+    /// offer TLS 1.3 are rejected, since TLS 1.3 is required by QUIC.
+    pub fn with_security_policy_name(mut self, name: &str) -> Result<Self, Error> {
+        self.config.set_security_policy(&policy::from_name(name)?)?;
+        Ok(self)
+    }
+
+    /// Sets the application protocols offered to clients, in order of preference
+    ///
+    /// QUIC requires an application protocol to be negotiated, so at least one must be provided.
+    /// Each protocol must be between 1 and 255 bytes.
     pub fn with_application_protocols<P: IntoIterator<Item = I>, I: AsRef<[u8]>>(
         mut self,
         protocols: P,
     ) -> Result<Self, Error> {
+        let protocols: Vec<I> = protocols.into_iter().collect();
+        crate::validate_application_protocols(&protocols)?;
         self.config.set_application_protocol_preference(protocols)?;
         Ok(self)
     }
 
+    /// Sets the certificate chain and private key presented to peers
+    ///
+    /// Values can be provided in either PEM or DER format. DER-encoded certificate chains are
+    /// the concatenation of each certificate, starting with the leaf. DER-encoded private keys
+    /// can be in PKCS#8, SEC1 or PKCS#1 format.
     pub fn with_certificate<C: IntoCertificate, PK: IntoPrivateKey>(
         mut self,
         certificate: C,
         private_key: PK,
     ) -> Result<Self, Error> {
-        let private_key = private_key.into_private_key()?.0;
-        let certificate = certificate.into_certificate()?.0;
-        let certificate = certificate
-            .as_pem()
-            .expect("pem is currently the only certificate format supported");
-        match private_key {
-            Format::Pem(bytes) => self.config.load_pem(certificate, bytes.as_ref())?,
-            Format::None => self.config.load_public_pem(certificate)?,
-            Format::Der(_) => panic!("der private keys not supported"),
+        let private_key = private_key.into_private_key()?;
+        let certificate = certificate.into_certificate()?;
+        let certificate = certificate.to_pem()?;
+        match private_key.to_pem()? {
+            Some(private_key) => self.config.load_pem(&certificate, &private_key)?,
+            None => self.config.load_public_pem(&certificate)?,
         };
+        self.settings.certificate = true;
+        Ok(self)
+    }
+
+    /// Sets the DER-encoded OCSP response which is stapled to the certificate chain
+    ///
+    /// The response is sent to clients that request OCSP stapling. It is applied when the
+    /// server is built, which fails with [`BuildError::OcspResponseWithoutCertificate`] if no
+    /// certificate chain was configured with [`Self::with_certificate`].
+    pub fn with_ocsp_response(mut self, ocsp_response: &[u8]) -> Result<Self, Error> {
+        if ocsp_response.is_empty() {
+            return Err(invalid_input("OCSP responses must not be empty"));
+        }
+
+        self.ocsp_response = Some(ocsp_response.to_vec());
+        self.settings.ocsp_response = true;
         Ok(self)
     }
 
@@ -147,11 +262,8 @@ impl Builder {
         certificate: C,
     ) -> Result<Self, Error> {
         let certificate = certificate.into_certificate()?;
-        let certificate = certificate
-            .0
-            .as_pem()
-            .expect("pem is currently the only certificate format supported");
-        self.config.trust_pem(certificate)?;
+        self.config.trust_pem(&certificate.to_pem()?)?;
+        self.settings.on_trust();
         Ok(self)
     }
 
@@ -166,12 +278,48 @@ impl Builder {
     /// Calling this method after a method that modifies the trust store will clear it.
     pub fn with_empty_trust_store(mut self) -> Result<Self, Error> {
         self.config.wipe_trust_store()?;
+        self.settings.on_wipe_trust_store();
+        Ok(self)
+    }
+
+    /// Adds the certificates in a CA file and/or directory to the trust store
+    ///
+    /// These are used to verify the certificates presented by clients. The directory must be
+    /// in the hashed format produced by OpenSSL's `c_rehash`. At least one of `file` or `dir`
+    /// must be provided.
+    pub fn with_trust_location(
+        mut self,
+        file: Option<&Path>,
+        dir: Option<&Path>,
+    ) -> Result<Self, Error> {
+        if file.is_none() && dir.is_none() {
+            return Err(invalid_input("a trust file or directory must be provided"));
+        }
+
+        self.config.trust_location(file, dir)?;
+        self.settings.on_trust();
         Ok(self)
     }
 
     /// Configures this server instance to require client authentication (mutual TLS).
-    pub fn with_client_authentication(mut self) -> Result<Self, Error> {
-        self.config.set_client_auth_type(ClientAuthType::Required)?;
+    pub fn with_client_authentication(self) -> Result<Self, Error> {
+        self.with_client_auth_type(ClientAuthType::Required)
+    }
+
+    /// Configures whether this server instance requests a certificate from clients.
+    ///
+    /// * [`ClientAuthType::None`] doesn't request a certificate, which is the default.
+    /// * [`ClientAuthType::Optional`] requests a certificate but still accepts clients that
+    ///   don't present one.
+    /// * [`ClientAuthType::Required`] fails the handshake if the client doesn't present a
+    ///   valid certificate.
+    ///
+    /// The certificate chain presented by the client can be read with `peer_cert_chain_der` on
+    /// the `TlsSession` provided in the `on_tls_exporter_ready` event, which is emitted once the
+    /// handshake completes.
+    pub fn with_client_auth_type(mut self, auth_type: ClientAuthType) -> Result<Self, Error> {
+        self.config.set_client_auth_type(auth_type)?;
+        self.settings.client_auth = !matches!(auth_type, ClientAuthType::None);
         Ok(self)
     }
 
@@ -179,49 +327,158 @@ impl Builder {
     /// server instance when a client certificate is presented during the mutual TLS handshake.
     #[deprecated(note = "use `with_verify_host_name_callback` instead")]
     pub fn with_verify_client_certificate_handler<T: 'static + VerifyHostNameCallback>(
-        mut self,
+        self,
         handler: T,
     ) -> Result<Self, Error> {
-        self.config.set_verify_host_callback(handler)?;
-        Ok(self)
+        self.with_verify_host_name_callback(handler)
     }
 
     /// Set the host name verification callback.
     ///
     /// This will be invoked when a client certificate is presented during a mutual TLS
-    /// handshake.
+    /// handshake. Host names which are not valid UTF-8 are rejected without invoking the
+    /// handler. If the handler panics, the certificate is rejected.
     pub fn with_verify_host_name_callback<T: 'static + VerifyHostNameCallback>(
         mut self,
         handler: T,
     ) -> Result<Self, Error> {
-        self.config.set_verify_host_callback(handler)?;
+        self.config
+            .set_verify_host_callback(VerifyHostName(handler))?;
         Ok(self)
     }
 
-    pub fn with_key_logging(mut self) -> Result<Self, Error> {
-        use crate::keylog::KeyLog;
+    /// Sets the maximum number of certificates accepted in a client's certificate chain
+    ///
+    /// Chains exceeding this length are rejected during the handshake. The length must be
+    /// at least 1. This only applies when client authentication is enabled.
+    pub fn with_max_cert_chain_depth(mut self, len: u16) -> Result<Self, Error> {
+        if len == 0 {
+            return Err(invalid_input("the max cert chain depth must be at least 1"));
+        }
 
-        self.keylog = KeyLog::try_open();
+        self.config.set_max_cert_chain_depth(len)?;
+        Ok(self)
+    }
+
+    /// Enables or disables TLS 1.3 session tickets for this server.
+    ///
+    /// Tickets are only issued once at least one key has been added with
+    /// [`Self::with_session_ticket_key`].
+    pub fn with_session_tickets(mut self, enabled: bool) -> Result<Self, Error> {
+        self.config.enable_session_tickets(enabled)?;
+        self.settings.session_tickets = enabled;
+        Ok(self)
+    }
+
+    /// Adds a key used to encrypt and decrypt session tickets and enables session tickets.
+    ///
+    /// Servers configured with the same key name and secret are able to resume sessions
+    /// established with each other. The key becomes active for encryption at `intro_time`.
+    ///
+    /// The `name` must be between 1 and 16 bytes and the `key` must not be empty.
+    pub fn with_session_ticket_key(
+        mut self,
+        name: &[u8],
+        key: &[u8],
+        intro_time: SystemTime,
+    ) -> Result<Self, Error> {
+        if name.is_empty() || name.len() > MAX_TICKET_KEY_NAME_LEN {
+            return Err(invalid_input(format!(
+                "session ticket key names must be between 1 and {MAX_TICKET_KEY_NAME_LEN} bytes"
+            )));
+        }
+
+        if key.is_empty() {
+            return Err(invalid_input("session ticket keys must not be empty"));
+        }
+
+        self.config.enable_session_tickets(true)?;
+        self.config.add_session_ticket_key(name, key, intro_time)?;
+        self.settings.session_tickets = true;
+        self.settings.ticket_keys = true;
+        Ok(self)
+    }
+
+    /// Replaces the system clock used to check session ticket key lifetimes and the validity
+    /// of client certificates
+    ///
+    /// This should be set before adding session ticket keys, since their intro times are
+    /// checked against it. See [`crate::clock::TestClock`] for a clock which can be advanced
+    /// from tests.
+    pub fn with_wall_clock<T: WallClock>(mut self, clock: T) -> Result<Self, Error> {
+        self.config.set_wall_clock(clock)?;
+        Ok(self)
+    }
+
+    /// Replaces the monotonic clock used by s2n-tls to measure elapsed time
+    pub fn with_monotonic_clock<T: MonotonicClock>(mut self, clock: T) -> Result<Self, Error> {
+        self.config.set_monotonic_clock(clock)?;
+        Ok(self)
+    }
 
+    /// Passes the raw bytes of every handshake message to the provided handler
+    ///
+    /// This is intended for diagnosing interoperability issues and conformance testing. See
+    /// [`crate::message::Transcript`] for a handler which records the messages into memory.
+    #[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_message_handler")))]
+    pub fn with_message_handler<T: MessageHandler>(mut self, handler: T) -> Result<Self, Error> {
+        self.messages = Some(std::sync::Arc::new(handler));
+        Ok(self)
+    }
+
+    /// Logs TLS secrets to the file specified by the `SSLKEYLOGFILE` environment variable
+    ///
+    /// Key logging is disabled if the variable isn't set or the file can't be opened.
+    pub fn with_key_logging(self) -> Result<Self, Error> {
+        if let Some(file) = KeyLogFile::from_env() {
+            self.with_key_log_handler(file)
+        } else {
+            self.with_key_log(None)
+        }
+    }
+
+    /// Passes TLS secrets negotiated by every connection to the provided handler
+    ///
+    /// This should only be used for debugging, since the secrets can decrypt all traffic.
+    pub fn with_key_log_handler<T: KeyLogHandler>(self, handler: T) -> Result<Self, Error> {
+        self.with_key_log(Some(KeyLog::new(handler)))
+    }
+
+    fn with_key_log(mut self, keylog: Option<KeyLogHandle>) -> Result<Self, Error> {
         unsafe {
             // Safety: the KeyLog is stored on `self` to ensure it outlives `config`
-            if let Some(keylog) = self.keylog.as_ref() {
-                self.config
-                    .set_key_log_callback(Some(KeyLog::callback), Arc::as_ptr(keylog) as *mut _)?;
-            } else {
-                // disable key logging if it failed to create a file
-                self.config
-                    .set_key_log_callback(None, core::ptr::null_mut())?;
-            }
+            KeyLog::set(&mut self.config, keylog.as_ref())?;
         }
-
+        self.keylog = keylog;
         Ok(self)
     }
 
+    /// Checks the settings for inconsistencies which would cause every handshake to fail
+    ///
+    /// This performs the same checks as [`Self::build`], returning the specific
+    /// [`BuildError`]. No checks are performed once [`Self::config_mut`] has been called.
+    pub fn validate(&self) -> Result<(), BuildError> {
+        self.settings.validate_server()
+    }
+
+    /// Builds the [`Server`]
+    ///
+    /// Inconsistent settings are rejected with the [`BuildError`] returned by
+    /// [`Self::validate`], inside an [`InvalidInput`](std::io::ErrorKind::InvalidInput) error.
     pub fn build(self) -> Result<Server, Error> {
+        self.validate().map_err(invalid_input)?;
+        self.build_unvalidated()
+    }
+
+    fn build_unvalidated(mut self) -> Result<Server, Error> {
+        if let Some(ocsp_response) = self.ocsp_response.as_deref() {
+            self.config.set_ocsp_data(ocsp_response)?;
+        }
+
         Ok(Server {
             loader: self.config.build()?,
             keylog: self.keylog,
+            messages: self.messages,
             params: Default::default(),
         })
     }
@@ -234,9 +491,15 @@ impl<L: ConfigLoader> tls::Endpoint for Server<L> {
         let config = self
             .loader
             .load(crate::ConnectionContext { server_name: None });
-        self.params.with(params, |params| {
+        let mut session = self.params.with(params, |params| {
             Session::new(endpoint::Type::Server, config, params, None).unwrap()
-        })
+        });
+
+        if let Some(handler) = self.messages.as_ref() {
+            session.set_message_handler(handler.clone());
+        }
+
+        session
     }
 
     fn new_client_session<Params: EncoderValue>(
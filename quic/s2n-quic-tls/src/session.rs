@@ -1,7 +1,11 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::callback::{self, Callback};
+use crate::{
+    callback::{self, Callback},
+    message::{MessageHandle, Messages},
+    pin::PublicKeyPins,
+};
 use bytes::BytesMut;
 use core::{marker::PhantomData, task::Poll};
 use s2n_quic_core::{
@@ -16,6 +20,7 @@ use s2n_tls::{
     enums::{Blinding, Mode},
     error::{Error, ErrorType},
 };
+use std::sync::Arc;
 
 #[derive(Debug)]
 pub struct Session {
@@ -28,6 +33,10 @@ pub struct Session {
     // This is only set for the client to avoid an extra allocation
     server_name: Option<ServerName>,
     received_ticket: bool,
+    /// Public keys the peer's leaf certificate must match, if any
+    pub(crate) pins: Option<Arc<PublicKeyPins>>,
+    handshake_type: Option<tls::handshake::HandshakeType>,
+    handshake_stats: Option<tls::HandshakeStats>,
 }
 
 impl Session {
@@ -49,6 +58,8 @@ impl Session {
         connection.set_blinding(Blinding::SelfService)?;
 
         if let Some(server_name) = server_name.as_ref() {
+            // s2n-tls verifies the server's certificate against this name, so IP literals are also
+            // set even though RFC 6066 doesn't allow them in the `server_name` extension
             connection
                 .set_server_name(server_name)
                 .expect("invalid server name value");
@@ -63,8 +74,102 @@ impl Session {
             emitted_server_name: false,
             server_name,
             received_ticket: false,
+            pins: None,
+            handshake_type: None,
+            handshake_stats: None,
         })
     }
+
+    /// Returns the OCSP response the server stapled to its certificate
+    ///
+    /// This is only available to clients which requested stapling with
+    /// [`client::Builder::with_ocsp_stapling`](crate::client::Builder::with_ocsp_stapling), once
+    /// the server's certificate has been received, and is `None` if the server didn't staple a
+    /// response. s2n-tls validates the response against the client's trust store before the
+    /// handshake completes.
+    pub fn ocsp_response(&self) -> Option<&[u8]> {
+        self.state.ocsp_response()
+    }
+
+    /// Passes every handshake message exchanged by the session to the handler
+    pub(crate) fn set_message_handler(&mut self, handler: MessageHandle) {
+        self.state.messages = Some(Messages::new(handler));
+    }
+
+    fn load_handshake_type(&self) -> Option<tls::handshake::HandshakeType> {
+        let name = self.connection.handshake_type().ok()?;
+
+        let mut handshake_type = tls::handshake::HandshakeType::default();
+        handshake_type.resumed = self.connection.resumed();
+
+        let mut no_client_cert = false;
+        for flag in name.split('|') {
+            match flag {
+                "HELLO_RETRY_REQUEST" => handshake_type.hello_retry_request = true,
+                "CLIENT_AUTH" => handshake_type.client_auth = true,
+                // the server requested a certificate but the client didn't provide one
+                "NO_CLIENT_CERT" => no_client_cert = true,
+                _ => {}
+            }
+        }
+        handshake_type.client_auth &= !no_client_cert;
+
+        Some(handshake_type)
+    }
+
+    /// Checks the peer's leaf certificate against the configured public key pins
+    ///
+    /// This is called once `poll_negotiate` completes, after the 1-RTT keys are installed but
+    /// before the handshake is reported as complete.
+    fn verify_public_key_pins(&self) -> Result<(), transport::Error> {
+        let pins = if let Some(pins) = self.pins.as_ref() {
+            pins
+        } else {
+            return Ok(());
+        };
+
+        let verified = self
+            .connection
+            .peer_cert_chain()
+            .ok()
+            .and_then(|chain| {
+                let leaf = chain.iter().next()?.ok()?;
+                Some(pins.verify(leaf.der().ok()?))
+            })
+            .unwrap_or(false);
+
+        ensure!(
+            verified,
+            Err(CryptoError::BAD_CERTIFICATE
+                .with_reason("the peer's public key does not match a pin")
+                .into())
+        );
+
+        Ok(())
+    }
+}
+
+/// Maps an s2n-tls error to the CRYPTO_ERROR sent to the peer
+///
+/// Errors with a TLS alert carry it in the error code. Failures on the local side, such as an
+/// invalid configuration or an error in an application callback, are reported as
+/// `internal_error` since they aren't the peer's fault. Anything else is a `handshake_failure`.
+///
+/// The s2n-tls error name, such as `S2N_ERR_CERT_UNTRUSTED`, is kept as the reason so it can be
+/// read from the connection error.
+fn crypto_error(error: &Error) -> CryptoError {
+    let crypto_error = if let Some(alert) = error.alert() {
+        CryptoError::new(alert)
+    } else {
+        match error.kind() {
+            ErrorType::UsageError | ErrorType::InternalError | ErrorType::IOError => {
+                CryptoError::INTERNAL_ERROR
+            }
+            _ => CryptoError::HANDSHAKE_FAILURE,
+        }
+    };
+
+    crypto_error.with_reason(error.name())
 }
 
 impl CryptoSuite for Session {
@@ -94,6 +199,35 @@ impl tls::TlsSession for Session {
     fn cipher_suite(&self) -> CipherSuite {
         self.state.cipher_suite()
     }
+
+    fn key_exchange_group(&self) -> Option<tls::KeyExchangeGroup> {
+        self.state.key_exchange_group()
+    }
+
+    fn handshake_type(&self) -> Option<tls::handshake::HandshakeType> {
+        self.handshake_type
+    }
+
+    fn handshake_stats(&self) -> Option<tls::HandshakeStats> {
+        self.handshake_stats
+    }
+
+    fn peer_cert_chain_der(&self) -> Result<Vec<Vec<u8>>, tls::ChainError> {
+        // the chain is only available once the peer's certificate has been verified
+        ensure!(self.handshake_complete, Err(tls::ChainError::failure()));
+
+        let chain = match self.connection.peer_cert_chain() {
+            Ok(chain) => chain,
+            // the peer didn't present a certificate
+            Err(_) => return Ok(Vec::new()),
+        };
+
+        chain
+            .iter()
+            .map(|cert| Ok(cert?.der()?.to_vec()))
+            .collect::<Result<Vec<Vec<u8>>, Error>>()
+            .map_err(|_| tls::ChainError::failure())
+    }
 }
 
 impl tls::Session for Session {
@@ -126,18 +260,20 @@ impl tls::Session for Session {
             Poll::Ready(Ok(())) => {
                 // s2n-tls has indicated that the handshake is complete
                 if !self.handshake_complete {
+                    self.verify_public_key_pins()?;
+                    self.handshake_type = self.load_handshake_type();
+                    // post-handshake messages, such as session tickets, aren't included
+                    self.handshake_stats = Some(self.state.handshake_stats());
                     self.state.on_handshake_complete();
                     context.on_handshake_complete()?;
-                    context.on_tls_exporter_ready(self)?;
+                    // the peer's certificate chain is read from the exporter-ready event, so the
+                    // handshake is marked complete before it's emitted
                     self.handshake_complete = true;
+                    context.on_tls_exporter_ready(self)?;
                 }
                 Poll::Ready(Ok(()))
             }
-            Poll::Ready(Err(e)) => Poll::Ready(Err(e
-                .alert()
-                .map(CryptoError::new)
-                .unwrap_or(CryptoError::HANDSHAKE_FAILURE)
-                .into())),
+            Poll::Ready(Err(e)) => Poll::Ready(Err(crypto_error(&e).into())),
             Poll::Pending => Poll::Pending,
         }
     }
@@ -181,11 +317,7 @@ impl tls::Session for Session {
                 if matches!(e.kind(), ErrorType::Blocked) {
                     Ok(())
                 } else {
-                    Err(e
-                        .alert()
-                        .map(CryptoError::new)
-                        .unwrap_or(CryptoError::HANDSHAKE_FAILURE)
-                        .into())
+                    Err(crypto_error(&e).into())
                 }
             }
         }
@@ -0,0 +1,93 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Selects a config based on the server name requested in the ClientHello
+
+use crate::invalid_input;
+use s2n_tls::{
+    callbacks::{ClientHelloCallback, ConnectionFuture},
+    config::Config,
+    connection::Connection,
+    error::Error,
+};
+use std::{collections::HashMap, pin::Pin};
+
+pub(crate) struct SniResolver {
+    exact: HashMap<String, Config>,
+    /// Wildcard configs, keyed by the name without the leading `*.`
+    wildcard: HashMap<String, Config>,
+    default: Option<Config>,
+}
+
+impl SniResolver {
+    pub(crate) fn new(
+        configs: HashMap<String, Config>,
+        default: Option<Config>,
+    ) -> Result<Self, Error> {
+        let mut exact = HashMap::new();
+        let mut wildcard = HashMap::new();
+
+        for (name, config) in configs {
+            let name = name.to_ascii_lowercase();
+
+            let previous = if let Some(suffix) = name.strip_prefix("*.") {
+                if suffix.is_empty() || suffix.contains('*') {
+                    return Err(invalid_input(format!(
+                        "invalid wildcard server name {name:?}"
+                    )));
+                }
+                wildcard.insert(suffix.to_string(), config)
+            } else {
+                if name.is_empty() || name.contains('*') {
+                    return Err(invalid_input(format!("invalid server name {name:?}")));
+                }
+                exact.insert(name.clone(), config)
+            };
+
+            // names are matched case-insensitively, so names only differing by case would
+            // otherwise silently replace each other
+            if previous.is_some() {
+                return Err(invalid_input(format!(
+                    "server name {name:?} is configured more than once"
+                )));
+            }
+        }
+
+        Ok(Self {
+            exact,
+            wildcard,
+            default,
+        })
+    }
+
+    fn resolve(&self, server_name: Option<&str>) -> Option<&Config> {
+        let config = server_name.and_then(|server_name| {
+            let server_name = server_name.to_ascii_lowercase();
+
+            if let Some(config) = self.exact.get(&server_name) {
+                return Some(config);
+            }
+
+            // wildcards only match a single label
+            let (_label, suffix) = server_name.split_once('.')?;
+            self.wildcard.get(suffix)
+        });
+
+        config.or(self.default.as_ref())
+    }
+}
+
+impl ClientHelloCallback for SniResolver {
+    fn on_client_hello(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<Option<Pin<Box<dyn ConnectionFuture>>>, Error> {
+        let config = self
+            .resolve(connection.server_name())
+            .cloned()
+            .ok_or_else(|| invalid_input("no config matches the requested server name"))?;
+
+        connection.set_config(config)?;
+        Ok(None)
+    }
+}
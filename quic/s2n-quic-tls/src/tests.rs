@@ -1,7 +1,12 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{certificate, client, server};
+use crate::{
+    certificate, client,
+    clock::TestClock,
+    keylog::{KeyLogHandler, KeyLogLine},
+    server, BuildError,
+};
 use core::{
     sync::atomic::{AtomicBool, AtomicU8, Ordering},
     task::Poll,
@@ -9,10 +14,14 @@ use core::{
 use openssl::{ec::EcKey, ecdsa::EcdsaSig};
 use pin_project::pin_project;
 use s2n_quic_core::{
+    application::ServerName,
     crypto::tls::{
         self,
-        testing::certificates::{CERT_PEM, KEY_PEM, UNTRUSTED_CERT_PEM, UNTRUSTED_KEY_PEM},
-        Endpoint,
+        testing::certificates::{
+            CERT_DER, CERT_PEM, KEY_DER, KEY_PEM, MTLS_CA_CERT, MTLS_SERVER_CERT, MTLS_SERVER_KEY,
+            OCSP_RESPONSE_DER, UNTRUSTED_CERT_PEM, UNTRUSTED_KEY_PEM,
+        },
+        Endpoint, Session as _,
     },
     transport,
 };
@@ -21,11 +30,17 @@ use s2n_tls::callbacks::ClientHelloCallback;
 #[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_client_hello")))]
 use s2n_tls::callbacks::{PrivateKeyCallback, PrivateKeyOperation};
 use s2n_tls::{
-    callbacks::{ConnectionFuture, VerifyHostNameCallback},
+    callbacks::{ConnectionFuture, SessionTicket, SessionTicketCallback, VerifyHostNameCallback},
+    config::ConnectionInitializer,
     connection::Connection,
+    enums::ClientAuthType,
     error::Error,
 };
-use std::{sync::Arc, time::SystemTime};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
 
 pub struct MyCallbackHandler {
     done: Arc<AtomicBool>,
@@ -100,6 +115,66 @@ impl crate::ConfigLoader for ResumptionConfig {
     }
 }
 
+/// Stores session tickets received by a client and applies them to new connections
+#[derive(Clone, Default)]
+struct TicketStore(Arc<Mutex<VecDeque<Vec<u8>>>>);
+
+impl TicketStore {
+    fn len(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+}
+
+impl SessionTicketCallback for TicketStore {
+    fn on_session_ticket(&self, _connection: &mut Connection, session_ticket: &SessionTicket) {
+        let mut ticket = vec![0; session_ticket.len().unwrap()];
+        session_ticket.data(&mut ticket).unwrap();
+        self.0.lock().unwrap().push_back(ticket);
+    }
+}
+
+impl ConnectionInitializer for TicketStore {
+    fn initialize_connection(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<Option<std::pin::Pin<Box<dyn ConnectionFuture>>>, Error> {
+        if let Some(ticket) = self.0.lock().unwrap().pop_front() {
+            connection.set_session_ticket(&ticket)?;
+        }
+        Ok(None)
+    }
+}
+
+/// Stores session tickets by server name through the safe client API
+#[derive(Clone, Default)]
+struct TicketCache(Arc<Mutex<HashMap<String, Vec<Vec<u8>>>>>);
+
+impl TicketCache {
+    fn len(&self, server_name: &str) -> usize {
+        self.0
+            .lock()
+            .unwrap()
+            .get(server_name)
+            .map_or(0, |tickets| tickets.len())
+    }
+}
+
+impl client::SessionTicketHandler for TicketCache {
+    fn on_session_ticket(&self, server_name: &ServerName, ticket: &[u8], lifetime: Duration) {
+        assert!(lifetime > Duration::ZERO);
+        self.0
+            .lock()
+            .unwrap()
+            .entry(server_name.to_string())
+            .or_default()
+            .push(ticket.to_vec());
+    }
+
+    fn load_session_ticket(&self, server_name: &ServerName) -> Option<Vec<u8>> {
+        self.0.lock().unwrap().get_mut(&**server_name)?.pop()
+    }
+}
+
 #[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_private_key")))]
 impl PrivateKeyCallback for MyCallbackHandler {
     fn handle_operation(
@@ -151,6 +226,172 @@ impl ConnectionFuture for MyPrivateKeyFuture {
     }
 }
 
+/// Replaces the connection config with a tenant config based on the offered server name
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_client_hello")))]
+struct ConfigSwapHandler {
+    tenants: HashMap<String, s2n_tls::config::Config>,
+    done: Arc<AtomicBool>,
+    wait_counter: Option<Arc<AtomicU8>>,
+}
+
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_client_hello")))]
+impl ConfigSwapHandler {
+    fn new(server_name: &str, tenant: server::Server, wait_counter: Option<u8>) -> Self {
+        let mut tenants = HashMap::new();
+        tenants.insert(server_name.to_string(), tenant.into());
+        Self {
+            tenants,
+            done: Arc::new(AtomicBool::new(false)),
+            wait_counter: wait_counter.map(|count| Arc::new(AtomicU8::new(count))),
+        }
+    }
+}
+
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_client_hello")))]
+impl ClientHelloCallback for ConfigSwapHandler {
+    fn on_client_hello(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<Option<std::pin::Pin<Box<dyn s2n_tls::callbacks::ConnectionFuture>>>, Error> {
+        let config = connection
+            .server_name()
+            .and_then(|server_name| self.tenants.get(server_name))
+            .cloned();
+
+        if let Some(wait_counter) = self.wait_counter.as_ref() {
+            // simulate fetching the config from a remote store
+            return Ok(Some(Box::pin(ConfigSwapFuture {
+                config,
+                done: self.done.clone(),
+                wait_counter: wait_counter.clone(),
+            })));
+        }
+
+        if let Some(config) = config {
+            connection.set_config(config)?;
+        }
+        self.done.store(true, Ordering::SeqCst);
+        Ok(None)
+    }
+}
+
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_client_hello")))]
+struct ConfigSwapFuture {
+    config: Option<s2n_tls::config::Config>,
+    done: Arc<AtomicBool>,
+    wait_counter: Arc<AtomicU8>,
+}
+
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_client_hello")))]
+impl ConnectionFuture for ConfigSwapFuture {
+    fn poll(
+        mut self: std::pin::Pin<&mut Self>,
+        connection: &mut Connection,
+        _ctx: &mut core::task::Context,
+    ) -> Poll<Result<(), Error>> {
+        if self.wait_counter.fetch_sub(1, Ordering::SeqCst) != 0 {
+            return Poll::Pending;
+        }
+
+        if let Some(config) = self.config.take() {
+            connection.set_config(config)?;
+        }
+        self.done.store(true, Ordering::SeqCst);
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_client_hello")))]
+fn s2n_server_with_config_swap(
+    server_name: &str,
+    wait_counter: Option<u8>,
+) -> (server::Server, Arc<AtomicBool>) {
+    let tenant = server::Builder::default()
+        .with_certificate(CERT_PEM, KEY_PEM)
+        .unwrap()
+        .build()
+        .unwrap();
+    let handler = ConfigSwapHandler::new(server_name, tenant, wait_counter);
+    let done = handler.done.clone();
+    // the initial config presents a certificate the client doesn't trust, so the handshake
+    // only succeeds if the tenant config is swapped in
+    let tls = server::Builder::default()
+        .with_certificate(UNTRUSTED_CERT_PEM, UNTRUSTED_KEY_PEM)
+        .unwrap()
+        .with_client_hello_handler(handler)
+        .unwrap()
+        .build()
+        .unwrap();
+    (tls, done)
+}
+
+/// Signs private key operations on a separate thread to simulate an HSM or KMS
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_private_key")))]
+struct ThreadedSigner {
+    done: Arc<AtomicBool>,
+    delay: Duration,
+}
+
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_private_key")))]
+impl PrivateKeyCallback for ThreadedSigner {
+    fn handle_operation(
+        &self,
+        _connection: &mut Connection,
+        op: PrivateKeyOperation,
+    ) -> Result<Option<std::pin::Pin<Box<dyn s2n_tls::callbacks::ConnectionFuture>>>, Error> {
+        let mut input = vec![0; op.input_size()?];
+        op.input(&mut input)?;
+
+        let signature = Arc::new(Mutex::new(None));
+        let output = signature.clone();
+        let delay = self.delay;
+        std::thread::spawn(move || {
+            std::thread::sleep(delay);
+            let key = EcKey::private_key_from_pem(KEY_PEM.as_bytes())
+                .expect("Failed to create EcKey from pem");
+            let sig = EcdsaSig::sign(&input, &key).expect("Failed to sign input");
+            let sig = sig.to_der().expect("Failed to convert signature to der");
+            *output.lock().unwrap() = Some(sig);
+        });
+
+        Ok(Some(Box::pin(ThreadedSignature {
+            done: self.done.clone(),
+            signature,
+            op: Some(op),
+        })))
+    }
+}
+
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_private_key")))]
+#[pin_project]
+struct ThreadedSignature {
+    done: Arc<AtomicBool>,
+    signature: Arc<Mutex<Option<Vec<u8>>>>,
+    op: Option<PrivateKeyOperation>,
+}
+
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_private_key")))]
+impl ConnectionFuture for ThreadedSignature {
+    fn poll(
+        self: std::pin::Pin<&mut Self>,
+        conn: &mut Connection,
+        _ctx: &mut core::task::Context,
+    ) -> Poll<Result<(), Error>> {
+        let this = self.project();
+
+        let signature = if let Some(signature) = this.signature.lock().unwrap().take() {
+            signature
+        } else {
+            return Poll::Pending;
+        };
+
+        let op = this.op.take().expect("Missing pkey operation");
+        op.set_output(conn, &signature)?;
+        this.done.store(true, Ordering::SeqCst);
+        Poll::Ready(Ok(()))
+    }
+}
+
 pub struct VerifyHostNameClientCertVerifier {
     host_name: String,
 }
@@ -177,6 +418,23 @@ impl VerifyHostNameCallback for RejectAllClientCertificatesHandler {
     }
 }
 
+pub struct PanickingHostNameVerifier;
+
+impl VerifyHostNameCallback for PanickingHostNameVerifier {
+    fn verify_host_name(&self, _host_name: &str) -> bool {
+        panic!("verify host name handler panicked");
+    }
+}
+
+#[derive(Clone, Default)]
+struct KeyLogRecorder(Arc<Mutex<Vec<Vec<u8>>>>);
+
+impl KeyLogHandler for KeyLogRecorder {
+    fn on_key_log(&self, line: &KeyLogLine) {
+        self.0.lock().unwrap().push(line.as_bytes().to_vec());
+    }
+}
+
 fn s2n_client() -> client::Client {
     client::Builder::default()
         .with_certificate(CERT_PEM)
@@ -231,6 +489,25 @@ fn s2n_client_with_resumption() -> Result<client::Client, Error> {
     builder.build()
 }
 
+fn s2n_client_with_ticket_store(tickets: &TicketStore) -> Result<client::Client, Error> {
+    let mut builder = client::Builder::default().with_certificate(CERT_PEM)?;
+
+    builder
+        .config_mut()
+        .enable_session_tickets(true)?
+        .set_session_ticket_callback(tickets.clone())?
+        .set_connection_initializer(tickets.clone())?;
+
+    builder.build()
+}
+
+fn s2n_client_with_ticket_cache(tickets: &TicketCache) -> Result<client::Client, Error> {
+    client::Builder::default()
+        .with_certificate(CERT_PEM)?
+        .with_session_ticket_handler(tickets.clone())?
+        .build()
+}
+
 fn s2n_server() -> server::Server {
     server::Builder::default()
         .with_certificate(CERT_PEM, KEY_PEM)
@@ -249,6 +526,23 @@ fn s2n_server_with_client_auth() -> Result<server::Server, Error> {
         .build()
 }
 
+fn s2n_server_with_session_ticket_key() -> Result<server::Server, Error> {
+    server::Builder::default()
+        .with_certificate(CERT_PEM, KEY_PEM)?
+        .with_session_ticket_key(TICKET_KEY_NAME, &TICKET_KEY, SystemTime::now())?
+        .build()
+}
+
+fn s2n_server_with_optional_client_auth() -> Result<server::Server, Error> {
+    server::Builder::default()
+        .with_empty_trust_store()?
+        .with_client_auth_type(ClientAuthType::Optional)?
+        .with_verify_host_name_callback(VerifyHostNameClientCertVerifier::new("qlaws.qlaws"))?
+        .with_certificate(CERT_PEM, KEY_PEM)?
+        .with_trusted_certificate(CERT_PEM)?
+        .build()
+}
+
 fn s2n_server_with_resumption() -> server::Server<ResumptionConfig> {
     server::Server::from_loader(ResumptionConfig)
 }
@@ -331,103 +625,1047 @@ fn s2n_client_s2n_server_pkey_callback_test() {
 
 #[test]
 #[cfg_attr(miri, ignore)]
-fn s2n_client_s2n_server_test() {
-    let mut client_endpoint = s2n_client();
-    let mut server_endpoint = s2n_server();
+fn s2n_client_s2n_server_client_hello_config_swap_test() {
+    for wait_counter in [None, Some(0), Some(3)] {
+        let (mut server_endpoint, done) = s2n_server_with_config_swap("localhost", wait_counter);
+        let mut client_endpoint = s2n_client();
 
-    run(&mut server_endpoint, &mut client_endpoint, None);
+        run(&mut server_endpoint, &mut client_endpoint, Some(done));
+    }
+}
+
+/// Records the server name offered in each ClientHello
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_client_hello")))]
+#[derive(Clone, Default)]
+struct ServerNameRecorder(Arc<Mutex<Vec<Option<String>>>>);
+
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_client_hello")))]
+impl ServerNameRecorder {
+    fn server(&self) -> server::Server {
+        server::Builder::default()
+            .with_certificate(CERT_PEM, KEY_PEM)
+            .unwrap()
+            .with_client_hello_handler(self.clone())
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    fn take(&self) -> Vec<Option<String>> {
+        core::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_client_hello")))]
+impl ClientHelloCallback for ServerNameRecorder {
+    fn on_client_hello(
+        &self,
+        connection: &mut Connection,
+    ) -> Result<Option<std::pin::Pin<Box<dyn s2n_tls::callbacks::ConnectionFuture>>>, Error> {
+        let server_name = connection.server_name().map(String::from);
+        self.0.lock().unwrap().push(server_name);
+        Ok(None)
+    }
 }
 
 #[test]
 #[cfg_attr(miri, ignore)]
-fn s2n_client_s2n_server_resumption_test() {
-    let mut client_endpoint = s2n_client_with_resumption().unwrap();
-    let mut server_endpoint = s2n_server_with_resumption();
+fn s2n_client_s2n_server_client_hello_server_name_test() {
+    let recorder = ServerNameRecorder::default();
+    let mut server_endpoint = recorder.server();
+    let mut client_endpoint = s2n_client();
 
-    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
-    assert!(
-        !pair.client.context.application.rx.is_empty(),
-        "expected session ticket message in RX"
-    );
+    run_result_with_server_name(
+        &mut server_endpoint,
+        &mut client_endpoint,
+        None,
+        "localhost",
+    )
+    .unwrap();
+    assert_eq!(recorder.take(), [Some("localhost".to_string())]);
 }
 
 #[test]
 #[cfg_attr(miri, ignore)]
-fn rustls_client_s2n_server_resumption_test() {
+fn rustls_client_s2n_server_client_hello_ip_server_name_test() {
+    let recorder = ServerNameRecorder::default();
+    let mut server_endpoint = recorder.server();
     let mut client_endpoint = rustls_client();
-    let mut server_endpoint = s2n_server_with_resumption();
 
-    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
-    assert!(
-        !pair.client.context.application.rx.is_empty(),
-        "expected session ticket message in RX"
+    // IP literals aren't sent in the `server_name` extension. The test certificate isn't issued
+    // for the address so the client fails to verify it after the ClientHello is received.
+    let result = run_result_with_server_name(
+        &mut server_endpoint,
+        &mut client_endpoint,
+        None,
+        "127.0.0.1",
     );
+    assert!(result.is_err());
+    assert_eq!(recorder.take(), [None]);
+}
+
+#[cfg(any(test, all(s2n_quic_unstable, feature = "unstable_client_hello")))]
+fn s2n_server_with_sni_configs(names: &[&str], with_default: bool) -> server::Server {
+    let tenant = || -> s2n_tls::config::Config {
+        server::Builder::default()
+            .with_certificate(CERT_PEM, KEY_PEM)
+            .unwrap()
+            .build()
+            .unwrap()
+            .into()
+    };
+
+    let configs = names
+        .iter()
+        .map(|name| (name.to_string(), tenant()))
+        .collect();
+    let default = if with_default { Some(tenant()) } else { None };
+
+    // the initial config presents a certificate the client doesn't trust, so the handshake
+    // only succeeds if one of the tenant configs is selected
+    server::Builder::default()
+        .with_certificate(UNTRUSTED_CERT_PEM, UNTRUSTED_KEY_PEM)
+        .unwrap()
+        .with_sni_configs(configs, default)
+        .unwrap()
+        .build()
+        .unwrap()
 }
 
 #[test]
 #[cfg_attr(miri, ignore)]
-fn rustls_client_s2n_server_test() {
-    let mut client_endpoint = rustls_client();
-    let mut server_endpoint = s2n_server();
+fn s2n_client_s2n_server_sni_configs_test() {
+    // (configured names, has default, requested name, expected to succeed)
+    let cases = [
+        (&["LocalHost"][..], false, "localhost", true),
+        (&["*.example.com"][..], false, "API.example.com", true),
+        (&["*.example.com"][..], false, "a.b.example.com", false),
+        (&["*.example.com"][..], false, "example.com", false),
+        (&["example.com"][..], true, "other.com", true),
+        (&["example.com"][..], false, "other.com", false),
+    ];
+
+    for (names, with_default, server_name, success) in cases {
+        let mut server_endpoint = s2n_server_with_sni_configs(names, with_default);
+        // the test certificate is only issued for localhost
+        let mut client_endpoint = client::Builder::default()
+            .with_certificate(CERT_PEM)
+            .unwrap()
+            .with_verify_host_name_callback(VerifyHostNameClientCertVerifier::new("localhost"))
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let result = run_result_with_server_name(
+            &mut server_endpoint,
+            &mut client_endpoint,
+            None,
+            server_name,
+        );
+        assert_eq!(result.is_ok(), success, "{server_name} with {names:?}");
+    }
+}
 
-    run(&mut server_endpoint, &mut client_endpoint, None);
+#[test]
+fn sni_configs_validation_test() {
+    for name in ["*", "*.", "a.*.com", ""] {
+        let mut configs = HashMap::new();
+        configs.insert(
+            name.to_string(),
+            s2n_tls::config::Config::from(s2n_server()),
+        );
+        assert!(server::Builder::default()
+            .with_sni_configs(configs, None)
+            .is_err());
+    }
+
+    // names are case-insensitive, so these would otherwise replace each other
+    for names in [
+        ["example.com", "EXAMPLE.com"],
+        ["*.example.com", "*.Example.COM"],
+    ] {
+        let configs = names
+            .iter()
+            .map(|name| {
+                let config = s2n_tls::config::Config::from(s2n_server());
+                (name.to_string(), config)
+            })
+            .collect();
+        assert!(server::Builder::default()
+            .with_sni_configs(configs, None)
+            .is_err());
+    }
 }
 
 #[test]
 #[cfg_attr(miri, ignore)]
-fn s2n_client_rustls_server_test() {
+fn s2n_client_s2n_server_client_hello_unknown_tenant_test() {
+    let (mut server_endpoint, done) = s2n_server_with_config_swap("example.com", None);
     let mut client_endpoint = s2n_client();
-    let mut server_endpoint = rustls_server();
 
-    run(&mut server_endpoint, &mut client_endpoint, None);
+    // the original config is kept, which the client doesn't trust
+    run_result(&mut server_endpoint, &mut client_endpoint, Some(done)).unwrap_err();
 }
 
 #[test]
 #[cfg_attr(miri, ignore)]
-fn s2n_client_s2n_server_client_auth_test() {
-    let mut client_endpoint = s2n_client_with_client_auth().unwrap();
-    let mut server_endpoint = s2n_server_with_client_auth().unwrap();
+fn s2n_client_s2n_server_per_connection_config_test() {
+    let tenant = server::Builder::default()
+        .with_certificate(CERT_PEM, KEY_PEM)
+        .unwrap()
+        .with_application_protocols(["hq-interop"])
+        .unwrap()
+        .build()
+        .unwrap();
+    let handler = ConfigSwapHandler::new("example.com", tenant, None);
+    let done = handler.done.clone();
+    let mut server_endpoint = server::Builder::default()
+        .with_certificate(CERT_PEM, KEY_PEM)
+        .unwrap()
+        .with_application_protocols(["h3"])
+        .unwrap()
+        .with_client_hello_handler(handler)
+        .unwrap()
+        .build()
+        .unwrap();
+    // the test certificate is only issued for localhost
+    let mut client_endpoint = client::Builder::default()
+        .with_certificate(CERT_PEM)
+        .unwrap()
+        .with_verify_host_name_callback(VerifyHostNameClientCertVerifier::new("localhost"))
+        .unwrap()
+        .with_application_protocols(["h3", "hq-interop"])
+        .unwrap()
+        .build()
+        .unwrap();
 
-    run(&mut server_endpoint, &mut client_endpoint, None);
+    // connections on the same endpoint negotiate with the config selected for them
+    for (server_name, expected) in [
+        ("example.com", "hq-interop"),
+        ("localhost", "h3"),
+        ("example.com", "hq-interop"),
+    ] {
+        let pair = run_result_with_server_name(
+            &mut server_endpoint,
+            &mut client_endpoint,
+            Some(done.clone()),
+            server_name,
+        )
+        .unwrap();
+
+        assert_eq!(
+            pair.server.context.application_protocol.as_deref(),
+            Some(expected.as_bytes())
+        );
+    }
 }
 
 #[test]
 #[cfg_attr(miri, ignore)]
-fn s2n_client_no_client_auth_s2n_server_requires_client_auth_test() {
+fn s2n_client_s2n_server_threaded_pkey_callback_test() {
+    let done = Arc::new(AtomicBool::new(false));
+    let handler = ThreadedSigner {
+        done: done.clone(),
+        delay: Duration::from_millis(50),
+    };
+    let mut server_endpoint = server::Builder::default()
+        .with_certificate(CERT_PEM, certificate::OFFLOAD_PRIVATE_KEY)
+        .unwrap()
+        .with_private_key_handler(handler)
+        .unwrap()
+        .build()
+        .unwrap();
     let mut client_endpoint = s2n_client();
-    let mut server_endpoint = s2n_server_with_client_auth().unwrap();
-
-    let test_result = run_result(&mut server_endpoint, &mut client_endpoint, None);
 
-    // The handshake should fail because the server requires client auth,
-    // but the client does not support it.
-    assert!(test_result.is_err());
-    let e = test_result.unwrap_err();
-    assert_eq!(e.description().unwrap(), "UNEXPECTED_MESSAGE");
+    // the endpoint keeps polling the handshake while the signature is computed on another thread
+    run(&mut server_endpoint, &mut client_endpoint, Some(done));
 }
 
 #[test]
 #[cfg_attr(miri, ignore)]
-fn s2n_client_with_client_auth_s2n_server_does_not_require_client_auth_test() {
-    let mut client_endpoint = s2n_client_with_client_auth().unwrap();
+fn s2n_client_s2n_server_test() {
+    let mut client_endpoint = s2n_client();
     let mut server_endpoint = s2n_server();
 
-    let test_result = run_result(&mut server_endpoint, &mut client_endpoint, None);
-
-    // The handshake should fail because the client requires client auth,
-    // but the server does not support it.
-    assert!(test_result.is_err());
-    let e = test_result.unwrap_err();
-    assert_eq!(e.description().unwrap(), "UNEXPECTED_MESSAGE");
+    run(&mut server_endpoint, &mut client_endpoint, None);
 }
 
 #[test]
 #[cfg_attr(miri, ignore)]
-fn s2n_client_with_client_auth_s2n_server_does_not_trust_client_certificate() {
-    let mut client_endpoint = s2n_client_with_client_auth().unwrap();
-    let mut server_endpoint = s2n_server_with_client_auth_verifier_rejects_client_certs().unwrap();
-
-    let test_result = run_result(&mut server_endpoint, &mut client_endpoint, None);
+fn s2n_client_s2n_server_resumption_test() {
+    let mut client_endpoint = s2n_client_with_resumption().unwrap();
+    let mut server_endpoint = s2n_server_with_resumption();
+
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert!(
+        !pair.client.context.application.rx.is_empty(),
+        "expected session ticket message in RX"
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_s2n_server_shared_ticket_key_test() {
+    let tickets = TicketStore::default();
+    let mut client_endpoint = s2n_client_with_ticket_store(&tickets).unwrap();
+
+    let mut server_endpoint = s2n_server_with_session_ticket_key().unwrap();
+    let mut pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert!(!pair.server.session.connection.resumed());
+
+    pair.client
+        .session
+        .process_post_handshake_message(&mut pair.client.context)
+        .unwrap();
+    assert_eq!(tickets.len(), 1, "client should receive a session ticket");
+
+    // a separate server configured with the same ticket key should resume the session
+    let mut server_endpoint = s2n_server_with_session_ticket_key().unwrap();
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert!(pair.server.session.connection.resumed());
+    assert!(pair.client.session.connection.resumed());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_server_session_ticket_expiry_test() {
+    let clock = TestClock::default();
+    let mut server_endpoint = server::Builder::default()
+        .with_certificate(CERT_PEM, KEY_PEM)
+        .unwrap()
+        .with_wall_clock(clock.clone())
+        .unwrap()
+        .with_session_ticket_key(TICKET_KEY_NAME, &TICKET_KEY, clock.now())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let tickets = TicketCache::default();
+    let mut client_endpoint = s2n_client_with_ticket_cache(&tickets).unwrap();
+
+    let mut pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert!(!pair.server.session.connection.resumed());
+    pair.client
+        .session
+        .process_post_handshake_message(&mut pair.client.context)
+        .unwrap();
+    assert_eq!(tickets.len("localhost"), 1);
+
+    let mut pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert!(pair.server.session.connection.resumed());
+    pair.client
+        .session
+        .process_post_handshake_message(&mut pair.client.context)
+        .unwrap();
+    assert_eq!(tickets.len("localhost"), 1);
+
+    // the ticket key and session lifetimes both default to less than a day, so once the
+    // server's clock moves past them the ticket is refused and a full handshake is performed
+    clock.advance(Duration::from_secs(24 * 60 * 60));
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert!(!pair.server.session.connection.resumed());
+    assert!(!pair.client.session.connection.resumed());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_s2n_server_fragmented_ticket_test() {
+    let tickets = TicketStore::default();
+    let mut client_endpoint = s2n_client_with_ticket_store(&tickets).unwrap();
+    let mut server_endpoint = s2n_server_with_session_ticket_key().unwrap();
+
+    let mut pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+
+    // deliver the NewSessionTicket message across several CRYPTO frames
+    let message: Vec<u8> = pair
+        .client
+        .context
+        .application
+        .rx
+        .drain(..)
+        .flat_map(|chunk| chunk.to_vec())
+        .collect();
+    assert!(!message.is_empty(), "expected session ticket message in RX");
+
+    for chunk in message.chunks(16) {
+        assert_eq!(tickets.len(), 0, "ticket should wait for the full message");
+        pair.client
+            .context
+            .application
+            .rx
+            .push_back(bytes::Bytes::copy_from_slice(chunk));
+        pair.client
+            .session
+            .process_post_handshake_message(&mut pair.client.context)
+            .unwrap();
+    }
+
+    assert_eq!(tickets.len(), 1, "client should receive a session ticket");
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_s2n_server_handshake_type_test() {
+    use tls::TlsSession as _;
+
+    let tickets = TicketStore::default();
+    let mut client_endpoint = s2n_client_with_ticket_store(&tickets).unwrap();
+    let mut server_endpoint = s2n_server_with_session_ticket_key().unwrap();
+
+    let mut pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    let handshake_type = pair.server.session.handshake_type().unwrap();
+    assert!(!handshake_type.resumed);
+    assert!(!handshake_type.client_auth);
+    assert_eq!(pair.client.session.handshake_type(), Some(handshake_type));
+
+    pair.client
+        .session
+        .process_post_handshake_message(&mut pair.client.context)
+        .unwrap();
+
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert!(pair.server.session.handshake_type().unwrap().resumed);
+    assert!(pair.client.session.handshake_type().unwrap().resumed);
+
+    let mut client_endpoint = s2n_client_with_client_auth().unwrap();
+    let mut server_endpoint = s2n_server_with_client_auth().unwrap();
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert!(pair.server.session.handshake_type().unwrap().client_auth);
+    assert!(pair.client.session.handshake_type().unwrap().client_auth);
+
+    // the server requests a certificate, but the client doesn't have one
+    let mut client_endpoint = s2n_client();
+    let mut server_endpoint = s2n_server_with_optional_client_auth().unwrap();
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert!(!pair.server.session.handshake_type().unwrap().client_auth);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_s2n_server_hello_retry_request_test() {
+    use tls::TlsSession as _;
+
+    // the client only sends a key share for its preferred group, x25519, which the server's
+    // policy doesn't support, so the server asks for a share for another group
+    let mut client_endpoint = s2n_client();
+    let mut server_endpoint = server::Builder::default()
+        .with_certificate(CERT_PEM, KEY_PEM)
+        .unwrap()
+        .with_security_policy_name("20230317")
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    let handshake_type = pair.server.session.handshake_type().unwrap();
+    assert!(handshake_type.hello_retry_request);
+    assert!(!handshake_type.resumed);
+    assert_eq!(pair.client.session.handshake_type(), Some(handshake_type));
+
+    // without a key share mismatch, no HelloRetryRequest is sent
+    let mut server_endpoint = s2n_server();
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert!(
+        !pair
+            .server
+            .session
+            .handshake_type()
+            .unwrap()
+            .hello_retry_request
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_s2n_server_message_handler_test() {
+    use crate::message::{Direction::*, Transcript};
+
+    const CLIENT_HELLO: u8 = 1;
+    const SERVER_HELLO: u8 = 2;
+    const NEW_SESSION_TICKET: u8 = 4;
+    const ENCRYPTED_EXTENSIONS: u8 = 8;
+    const CERTIFICATE: u8 = 11;
+    const CERTIFICATE_VERIFY: u8 = 15;
+    const FINISHED: u8 = 20;
+
+    let transcript = Transcript::default();
+    let tickets = TicketStore::default();
+    let mut client_endpoint = s2n_client_with_ticket_store(&tickets).unwrap();
+    let mut server_endpoint = server::Builder::default()
+        .with_certificate(CERT_PEM, KEY_PEM)
+        .unwrap()
+        .with_session_ticket_key(TICKET_KEY_NAME, &TICKET_KEY, SystemTime::now())
+        .unwrap()
+        .with_message_handler(transcript.clone())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let mut pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    pair.client
+        .session
+        .process_post_handshake_message(&mut pair.client.context)
+        .unwrap();
+
+    let messages = transcript.msg_types();
+    assert_eq!(
+        messages[..7],
+        [
+            (Received, CLIENT_HELLO),
+            (Sent, SERVER_HELLO),
+            (Sent, ENCRYPTED_EXTENSIONS),
+            (Sent, CERTIFICATE),
+            (Sent, CERTIFICATE_VERIFY),
+            (Sent, FINISHED),
+            (Received, FINISHED),
+        ]
+    );
+    assert!(messages[7..]
+        .iter()
+        .all(|message| *message == (Sent, NEW_SESSION_TICKET)));
+
+    // each message is recorded with its header
+    for message in transcript.messages() {
+        assert_eq!(message.bytes[0], message.msg_type);
+    }
+
+    // resumed handshakes don't authenticate with a certificate
+    transcript.clear();
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert!(pair.server.session.handshake_type().unwrap().resumed);
+    assert_eq!(
+        transcript.msg_types()[..5],
+        [
+            (Received, CLIENT_HELLO),
+            (Sent, SERVER_HELLO),
+            (Sent, ENCRYPTED_EXTENSIONS),
+            (Sent, FINISHED),
+            (Received, FINISHED),
+        ]
+    );
+}
+
+/// Returns the extensions in a ClientHello message, keyed by their type
+fn client_hello_extensions(message: &[u8]) -> HashMap<u16, &[u8]> {
+    fn split(input: &[u8], len: usize) -> (&[u8], &[u8]) {
+        input.split_at(len)
+    }
+
+    fn vector(input: &[u8], len_bytes: usize) -> (&[u8], &[u8]) {
+        let (len, input) = split(input, len_bytes);
+        let len = len.iter().fold(0, |len, b| (len << 8) | *b as usize);
+        split(input, len)
+    }
+
+    let (_header, body) = split(message, 4);
+    // legacy_version and random
+    let (_, body) = split(body, 2 + 32);
+    let (_session_id, body) = vector(body, 1);
+    let (_cipher_suites, body) = vector(body, 2);
+    let (_compression_methods, body) = vector(body, 1);
+    let (mut extensions, remaining) = vector(body, 2);
+    assert!(remaining.is_empty());
+
+    let mut parsed = HashMap::new();
+    while !extensions.is_empty() {
+        let (extension_type, rest) = split(extensions, 2);
+        let (data, rest) = vector(rest, 2);
+        parsed.insert(
+            u16::from_be_bytes([extension_type[0], extension_type[1]]),
+            data,
+        );
+        extensions = rest;
+    }
+    parsed
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_s2n_server_raw_client_hello_test() {
+    use crate::message::{Direction, Transcript};
+
+    const CLIENT_HELLO: u8 = 1;
+    const SERVER_NAME: u16 = 0;
+
+    let transcript = Transcript::default();
+    let mut server_endpoint = server::Builder::default()
+        .with_certificate(CERT_PEM, KEY_PEM)
+        .unwrap()
+        .with_message_handler(transcript.clone())
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut client_endpoint = s2n_client();
+
+    run(&mut server_endpoint, &mut client_endpoint, None);
+
+    let client_hello = transcript
+        .messages()
+        .into_iter()
+        .find(|message| message.direction == Direction::Received)
+        .unwrap();
+    assert_eq!(client_hello.msg_type, CLIENT_HELLO);
+
+    // the captured message can be re-parsed and includes the requested server name
+    let extensions = client_hello_extensions(&client_hello.bytes);
+    let server_name = extensions[&SERVER_NAME];
+    assert!(server_name.ends_with(b"localhost"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn message_handler_panic_test() {
+    use crate::message::{Fragment, MessageHandler};
+
+    struct PanickingHandler;
+
+    impl MessageHandler for PanickingHandler {
+        fn on_fragment(&self, _fragment: &Fragment) {
+            panic!("the handshake should continue");
+        }
+    }
+
+    let mut server_endpoint = server::Builder::default()
+        .with_certificate(CERT_PEM, KEY_PEM)
+        .unwrap()
+        .with_message_handler(PanickingHandler)
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut client_endpoint = s2n_client();
+
+    run(&mut server_endpoint, &mut client_endpoint, None);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_s2n_server_handshake_stats_test() {
+    use tls::TlsSession as _;
+
+    let tickets = TicketStore::default();
+    let mut client_endpoint = s2n_client_with_ticket_store(&tickets).unwrap();
+    let mut server_endpoint = s2n_server_with_session_ticket_key().unwrap();
+
+    let mut pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    let server = pair.server.session.handshake_stats().unwrap();
+    let client = pair.client.session.handshake_stats().unwrap();
+
+    // the server flight includes the certificate chain
+    assert!(server.bytes_sent > CERT_DER.len() as u64);
+    assert!(client.bytes_received > CERT_DER.len() as u64);
+    // the client doesn't send anything after its Finished message
+    assert_eq!(client.bytes_sent, server.bytes_received);
+
+    pair.client
+        .session
+        .process_post_handshake_message(&mut pair.client.context)
+        .unwrap();
+
+    // the session ticket isn't counted as part of the handshake
+    assert_eq!(pair.client.session.handshake_stats(), Some(client));
+
+    // resumed handshakes don't send a certificate
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert!(pair.server.session.handshake_type().unwrap().resumed);
+    let resumed = pair.server.session.handshake_stats().unwrap();
+    assert!(resumed.bytes_sent > 0);
+    assert!(resumed.bytes_sent < server.bytes_sent);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_session_ticket_handler_test() {
+    let tickets = TicketCache::default();
+    let mut client_endpoint = s2n_client_with_ticket_cache(&tickets).unwrap();
+    let mut server_endpoint = s2n_server_with_session_ticket_key().unwrap();
+
+    let mut pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert!(!pair.client.session.connection.resumed());
+    assert_eq!(tickets.len("localhost"), 0);
+
+    pair.client
+        .session
+        .process_post_handshake_message(&mut pair.client.context)
+        .unwrap();
+    assert_eq!(tickets.len("localhost"), 1, "ticket should be stored");
+
+    // the stored ticket should be loaded for the next connection to the same server
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert!(pair.client.session.connection.resumed());
+    assert_eq!(tickets.len("localhost"), 0, "ticket should be consumed");
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_session_ticket_persistence_test() {
+    let mut server_endpoint = s2n_server_with_session_ticket_key().unwrap();
+
+    // tickets are serialized by one client instance
+    let tickets = TicketCache::default();
+    let mut client_endpoint = s2n_client_with_ticket_cache(&tickets).unwrap();
+    let mut pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    pair.client
+        .session
+        .process_post_handshake_message(&mut pair.client.context)
+        .unwrap();
+    drop(pair);
+    drop(client_endpoint);
+    let ticket = tickets.0.lock().unwrap()["localhost"].clone();
+
+    // and restored by a new one, with a separate config
+    let restored = TicketCache::default();
+    restored
+        .0
+        .lock()
+        .unwrap()
+        .insert("localhost".to_string(), ticket);
+    let mut client_endpoint = s2n_client_with_ticket_cache(&restored).unwrap();
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert!(pair.client.session.connection.resumed());
+
+    // corrupt tickets fall back to a full handshake
+    let corrupt = TicketCache::default();
+    corrupt
+        .0
+        .lock()
+        .unwrap()
+        .insert("localhost".to_string(), vec![vec![42; 64]]);
+    let mut client_endpoint = s2n_client_with_ticket_cache(&corrupt).unwrap();
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert!(!pair.client.session.connection.resumed());
+}
+
+#[test]
+fn session_ticket_key_validation_test() {
+    let now = SystemTime::now();
+
+    for name in [&b""[..], &[1u8; 17][..]] {
+        assert!(server::Builder::default()
+            .with_session_ticket_key(name, &TICKET_KEY, now)
+            .is_err());
+    }
+
+    assert!(server::Builder::default()
+        .with_session_ticket_key(TICKET_KEY_NAME, &[], now)
+        .is_err());
+
+    assert!(server::Builder::default()
+        .with_session_ticket_key(&[1u8; 16], &TICKET_KEY, now)
+        .is_ok());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn rustls_client_s2n_server_resumption_test() {
+    let mut client_endpoint = rustls_client();
+    let mut server_endpoint = s2n_server_with_resumption();
+
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert!(
+        !pair.client.context.application.rx.is_empty(),
+        "expected session ticket message in RX"
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_ocsp_stapling_s2n_server_test() {
+    let mut client_endpoint = client::Builder::default()
+        .with_certificate(CERT_PEM)
+        .unwrap()
+        .with_ocsp_stapling()
+        .unwrap()
+        .build()
+        .unwrap();
+    // servers without a stapled response should still be able to complete the handshake
+    let mut server_endpoint = s2n_server();
+
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert_eq!(pair.client.session.ocsp_response(), None);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_ocsp_stapling_s2n_server_ocsp_response_test() {
+    let mut client_endpoint = client::Builder::default()
+        .with_certificate(CERT_PEM)
+        .unwrap()
+        .with_ocsp_stapling()
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut server_endpoint = server::Builder::default()
+        .with_certificate(CERT_PEM, KEY_PEM)
+        .unwrap()
+        .with_ocsp_response(OCSP_RESPONSE_DER)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert_eq!(pair.client.session.ocsp_response(), Some(OCSP_RESPONSE_DER));
+    // servers don't record the response they stapled
+    assert_eq!(pair.server.session.ocsp_response(), None);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_ocsp_stapling_expired_response_test() {
+    // the test response is valid until 2500, while the certificate is valid until 4096
+    let clock = TestClock::default();
+    clock.advance(Duration::from_secs(500 * 366 * 24 * 60 * 60));
+
+    let mut client_endpoint = client::Builder::default()
+        .with_certificate(CERT_PEM)
+        .unwrap()
+        .with_wall_clock(clock)
+        .unwrap()
+        .with_ocsp_stapling()
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut server_endpoint = server::Builder::default()
+        .with_certificate(CERT_PEM, KEY_PEM)
+        .unwrap()
+        .with_ocsp_response(OCSP_RESPONSE_DER)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert!(run_result(&mut server_endpoint, &mut client_endpoint, None).is_err());
+}
+
+#[test]
+fn s2n_server_ocsp_response_test() {
+    assert!(server::Builder::default()
+        .with_certificate(CERT_PEM, KEY_PEM)
+        .unwrap()
+        .with_ocsp_response(&[])
+        .is_err());
+
+    // the response is stapled to the certificate chain, so one must be configured
+    let server = server::Builder::default()
+        .with_ocsp_response(OCSP_RESPONSE_DER)
+        .unwrap();
+    assert_eq!(
+        server.validate(),
+        Err(BuildError::OcspResponseWithoutCertificate)
+    );
+    assert!(server.build().is_err());
+
+    // the response is applied at build time, so it can be provided before the certificate
+    assert!(server::Builder::default()
+        .with_ocsp_response(OCSP_RESPONSE_DER)
+        .unwrap()
+        .with_certificate(CERT_PEM, KEY_PEM)
+        .unwrap()
+        .build()
+        .is_ok());
+
+    let mut server_endpoint = server::Builder::default()
+        .with_certificate(CERT_PEM, KEY_PEM)
+        .unwrap()
+        .with_ocsp_response(OCSP_RESPONSE_DER)
+        .unwrap()
+        .build()
+        .unwrap();
+    // clients that don't request stapling never see the response
+    let mut client_endpoint = s2n_client();
+
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+    assert_eq!(pair.client.session.ocsp_response(), None);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_s2n_server_security_policy_test() {
+    use crate::SecurityPolicy;
+
+    for policy in SecurityPolicy::all().iter().copied() {
+        let mut client_endpoint = client::Builder::default()
+            .with_certificate(CERT_PEM)
+            .unwrap()
+            .with_security_policy(policy)
+            .unwrap()
+            .build()
+            .unwrap();
+        let mut server_endpoint = server::Builder::default()
+            .with_certificate(CERT_PEM, KEY_PEM)
+            .unwrap()
+            .with_security_policy_name(policy.name())
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+
+        // each policy should negotiate one of the TLS 1.3 suites required by QUIC
+        assert_ne!(
+            tls::TlsSession::cipher_suite(&pair.server.session),
+            tls::CipherSuite::Unknown,
+            "{policy:?}"
+        );
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn security_policy_offer_test() {
+    use crate::SecurityPolicy;
+
+    // TLS_AES_128_GCM_SHA256, TLS_AES_256_GCM_SHA384 and TLS_CHACHA20_POLY1305_SHA256
+    let ciphers = SecurityPolicy::DEFAULT_TLS13.ciphers().unwrap();
+    for cipher in [0x1301, 0x1302, 0x1303] {
+        assert!(ciphers.contains(&cipher), "{cipher:#06x}: {ciphers:x?}");
+    }
+
+    for policy in SecurityPolicy::all() {
+        let ciphers = policy.ciphers().unwrap();
+        assert!(ciphers.contains(&0x1301), "{policy:?}: {ciphers:x?}");
+        // signaling values aren't cipher suites
+        assert!(!ciphers.contains(&0x00ff), "{policy:?}: {ciphers:x?}");
+
+        // ecdsa_secp256r1_sha256 is used by the test certificate
+        let schemes = policy.signature_algorithms().unwrap();
+        assert!(schemes.contains(&0x0403), "{policy:?}: {schemes:x?}");
+    }
+}
+
+#[test]
+fn tls12_security_policy_test() {
+    for name in ["20170210", "20190214", "test_all_tls12", "20140601"] {
+        assert!(server::Builder::default()
+            .with_security_policy_name(name)
+            .is_err());
+        assert!(client::Builder::default()
+            .with_security_policy_name(name)
+            .is_err());
+    }
+
+    assert!(client::Builder::default()
+        .with_security_policy_name("not a policy")
+        .is_err());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn rustls_client_s2n_server_test() {
+    let mut client_endpoint = rustls_client();
+    let mut server_endpoint = s2n_server();
+
+    run(&mut server_endpoint, &mut client_endpoint, None);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_rustls_server_test() {
+    let mut client_endpoint = s2n_client();
+    let mut server_endpoint = rustls_server();
+
+    run(&mut server_endpoint, &mut client_endpoint, None);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_s2n_server_client_auth_test() {
+    let mut client_endpoint = s2n_client_with_client_auth().unwrap();
+    let mut server_endpoint = s2n_server_with_client_auth().unwrap();
+
+    run(&mut server_endpoint, &mut client_endpoint, None);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_with_client_auth_s2n_server_optional_client_auth_test() {
+    let mut client_endpoint = s2n_client_with_client_auth().unwrap();
+    let mut server_endpoint = s2n_server_with_optional_client_auth().unwrap();
+
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+
+    let chain = tls::TlsSession::peer_cert_chain_der(&pair.server.session).unwrap();
+    assert_eq!(
+        chain,
+        vec![CERT_DER.to_vec()],
+        "server should receive the client certificate"
+    );
+
+    let chain = tls::TlsSession::peer_cert_chain_der(&pair.client.session).unwrap();
+    assert_eq!(chain, vec![CERT_DER.to_vec()]);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_no_client_auth_s2n_server_optional_client_auth_test() {
+    let mut client_endpoint = s2n_client();
+    let mut server_endpoint = s2n_server_with_optional_client_auth().unwrap();
+
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+
+    let chain = tls::TlsSession::peer_cert_chain_der(&pair.server.session).unwrap();
+    assert!(chain.is_empty(), "client should not present a certificate");
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_no_client_auth_s2n_server_requires_client_auth_test() {
+    let mut client_endpoint = s2n_client();
+    let mut server_endpoint = s2n_server_with_client_auth().unwrap();
+
+    let test_result = run_result(&mut server_endpoint, &mut client_endpoint, None);
+
+    // The handshake should fail because the server requires client auth,
+    // but the client does not support it.
+    assert!(test_result.is_err());
+    let e = test_result.unwrap_err();
+    assert_eq!(e.description().unwrap(), "UNEXPECTED_MESSAGE");
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_untrusted_server_alert_test() {
+    let mut server_endpoint = server::Builder::default()
+        .with_certificate(UNTRUSTED_CERT_PEM, UNTRUSTED_KEY_PEM)
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut client_endpoint = s2n_client();
+
+    let e = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap_err();
+
+    assert!(e.description().is_some());
+
+    // the TLS alert should be carried in the CRYPTO_ERROR code, rather than a generic failure
+    let crypto_error = e.try_into_crypto_error().expect("expected a CRYPTO_ERROR");
+    assert_ne!(
+        crypto_error.code,
+        s2n_quic_core::crypto::CryptoError::HANDSHAKE_FAILURE.code
+    );
+    // the s2n-tls error name is kept for debugging
+    assert!(e.reason.starts_with("S2N_ERR_"), "{}", e.reason);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_with_client_auth_s2n_server_does_not_require_client_auth_test() {
+    let mut client_endpoint = s2n_client_with_client_auth().unwrap();
+    let mut server_endpoint = s2n_server();
+
+    let test_result = run_result(&mut server_endpoint, &mut client_endpoint, None);
+
+    // The handshake should fail because the client requires client auth,
+    // but the server does not support it.
+    assert!(test_result.is_err());
+    let e = test_result.unwrap_err();
+    assert_eq!(e.description().unwrap(), "UNEXPECTED_MESSAGE");
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_with_client_auth_s2n_server_does_not_trust_client_certificate() {
+    let mut client_endpoint = s2n_client_with_client_auth().unwrap();
+    let mut server_endpoint = s2n_server_with_client_auth_verifier_rejects_client_certs().unwrap();
+
+    let test_result = run_result(&mut server_endpoint, &mut client_endpoint, None);
 
     // The handshake should fail because the certificate presented by the client is rejected by the
     // application level host verification check on the cert.
@@ -474,13 +1712,79 @@ fn s2n_client_with_custom_hostname_auth_accepts_server_name() {
     run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
 }
 
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_with_panicking_hostname_auth_rejects_server_name() {
+    let mut client_endpoint = client::Builder::default()
+        .with_certificate(CERT_PEM)
+        .unwrap()
+        .with_verify_host_name_callback(PanickingHostNameVerifier)
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut server_endpoint = s2n_server();
+
+    let test_result = run_result(&mut server_endpoint, &mut client_endpoint, None);
+
+    // The handshake should fail because the panic is treated as a rejection
+    assert!(test_result.is_err());
+    let e = test_result.unwrap_err();
+    assert_eq!(e.description().unwrap(), "HANDSHAKE_FAILURE");
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_s2n_server_key_log_test() {
+    let client_lines = KeyLogRecorder::default();
+    let mut client_endpoint = client::Builder::default()
+        .with_certificate(CERT_PEM)
+        .unwrap()
+        .with_key_log_handler(client_lines.clone())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let server_lines = KeyLogRecorder::default();
+    let mut server_endpoint = server::Builder::default()
+        .with_certificate(CERT_PEM, KEY_PEM)
+        .unwrap()
+        .with_key_log_handler(server_lines.clone())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    run(&mut server_endpoint, &mut client_endpoint, None);
+
+    for lines in [client_lines, server_lines] {
+        let lines = lines.0.lock().unwrap();
+        let line = lines
+            .iter()
+            .map(|line| KeyLogLine(&line[..]))
+            .find(|line| line.label() == Some(b"CLIENT_TRAFFIC_SECRET_0"))
+            .expect("missing client traffic secret");
+
+        // the client random is 32 bytes, hex encoded
+        assert_eq!(line.client_random().unwrap().len(), 64);
+        assert!(!line.secret().unwrap().is_empty());
+    }
+}
+
 /// Executes the handshake to completion
 fn run_result<S: Endpoint, C: Endpoint>(
     server: &mut S,
     client: &mut C,
     client_hello_cb_done: Option<Arc<AtomicBool>>,
 ) -> Result<tls::testing::Pair<S::Session, C::Session>, transport::Error> {
-    let mut pair = tls::testing::Pair::new(server, client, "localhost".into());
+    run_result_with_server_name(server, client, client_hello_cb_done, "localhost")
+}
+
+fn run_result_with_server_name<S: Endpoint, C: Endpoint>(
+    server: &mut S,
+    client: &mut C,
+    client_hello_cb_done: Option<Arc<AtomicBool>>,
+    server_name: &str,
+) -> Result<tls::testing::Pair<S::Session, C::Session>, transport::Error> {
+    let mut pair = tls::testing::Pair::new(server, client, server_name.into());
 
     while pair.is_handshaking() {
         pair.poll(client_hello_cb_done.as_ref())?;
@@ -499,6 +1803,681 @@ fn run<S: Endpoint, C: Endpoint>(
     run_result(server, client, client_hello_cb_done).unwrap();
 }
 
+fn pem_to_der(pem: &str) -> Vec<u8> {
+    openssl::x509::X509::from_pem(pem.as_bytes())
+        .unwrap()
+        .to_der()
+        .unwrap()
+}
+
+fn key_pem_to_der(pem: &str) -> Vec<u8> {
+    openssl::pkey::PKey::private_key_from_pem(pem.as_bytes())
+        .unwrap()
+        .private_key_to_pkcs8()
+        .unwrap()
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_s2n_server_der_test() {
+    let mut server_endpoint = server::Builder::default()
+        .with_certificate(CERT_DER, KEY_DER)
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut client_endpoint = client::Builder::default()
+        .with_certificate(CERT_DER)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    run(&mut server_endpoint, &mut client_endpoint, None);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_s2n_server_der_key_format_test() {
+    // SEC1 encoding of the EC test key
+    let sec1 = openssl::pkey::PKey::private_key_from_pem(KEY_PEM.as_bytes())
+        .unwrap()
+        .ec_key()
+        .unwrap()
+        .private_key_to_der()
+        .unwrap();
+    // PKCS#1 encoding of the RSA untrusted key
+    let pkcs1 = openssl::pkey::PKey::private_key_from_pem(UNTRUSTED_KEY_PEM.as_bytes())
+        .unwrap()
+        .rsa()
+        .unwrap()
+        .private_key_to_der()
+        .unwrap();
+
+    for (certificate, private_key) in [(CERT_PEM, sec1), (UNTRUSTED_CERT_PEM, pkcs1)] {
+        let mut server_endpoint = server::Builder::default()
+            .with_certificate(certificate, private_key)
+            .unwrap()
+            .build()
+            .unwrap();
+        let mut client_endpoint = client::Builder::default()
+            .with_certificate(certificate)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        run(&mut server_endpoint, &mut client_endpoint, None);
+    }
+
+    // a SEQUENCE without a version isn't a supported key format
+    assert!(server::Builder::default()
+        .with_certificate(CERT_DER, &[0x30, 0x02, 0x04, 0x00][..])
+        .is_err());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_s2n_server_der_chain_test() {
+    let mut chain = pem_to_der(MTLS_SERVER_CERT);
+    chain.extend_from_slice(&pem_to_der(MTLS_CA_CERT));
+
+    let mut server_endpoint = server::Builder::default()
+        .with_certificate(chain, key_pem_to_der(MTLS_SERVER_KEY))
+        .unwrap()
+        .build()
+        .unwrap();
+    let mut client_endpoint = client::Builder::default()
+        .with_certificate(pem_to_der(MTLS_CA_CERT))
+        .unwrap()
+        .build()
+        .unwrap();
+
+    run(&mut server_endpoint, &mut client_endpoint, None);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_s2n_server_tls_exporter_test() {
+    use tls::TlsSession as _;
+
+    let mut server_endpoint = s2n_server();
+    let mut client_endpoint = s2n_client();
+
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+
+    let export = |session: &crate::session::Session, context: &[u8]| {
+        let mut output = [0; 32];
+        session
+            .tls_exporter(b"EXPERIMENTAL EXPORTER s2n-quic", context, &mut output)
+            .unwrap();
+        output
+    };
+
+    let server_output = export(&pair.server.session, b"context");
+    let client_output = export(&pair.client.session, b"context");
+    assert_eq!(server_output, client_output);
+
+    // a different context derives a different output
+    assert_ne!(
+        server_output,
+        export(&pair.server.session, b"other context")
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_s2n_server_key_exchange_group_test() {
+    use tls::TlsSession as _;
+
+    let mut server_endpoint = s2n_server();
+    let mut client_endpoint = s2n_client();
+
+    let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+
+    let server_group = pair.server.session.key_exchange_group();
+    let client_group = pair.client.session.key_exchange_group();
+    assert!(server_group.is_some());
+    assert_eq!(server_group, client_group);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_s2n_server_pq_fallback_test() {
+    use crate::SecurityPolicy;
+    use tls::TlsSession as _;
+
+    let policies = [
+        (
+            SecurityPolicy::PQ_TLS13_20230601,
+            SecurityPolicy::DEFAULT_TLS13,
+        ),
+        (
+            SecurityPolicy::DEFAULT_TLS13,
+            SecurityPolicy::PQ_TLS13_20230601,
+        ),
+        (
+            SecurityPolicy::PQ_TLS13_20230601,
+            SecurityPolicy::PQ_TLS13_20230601,
+        ),
+    ];
+
+    for (client_policy, server_policy) in policies {
+        let mut client_endpoint = client::Builder::default()
+            .with_certificate(CERT_PEM)
+            .unwrap()
+            .with_security_policy(client_policy)
+            .unwrap()
+            .build()
+            .unwrap();
+        let mut server_endpoint = server::Builder::default()
+            .with_certificate(CERT_PEM, KEY_PEM)
+            .unwrap()
+            .with_security_policy(server_policy)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let pair = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap();
+
+        let server_group = pair.server.session.key_exchange_group().unwrap();
+        let client_group = pair.client.session.key_exchange_group().unwrap();
+        assert_eq!(server_group, client_group);
+
+        // a classical group is negotiated if either peer doesn't support post-quantum groups
+        if client_policy != server_policy {
+            assert!(!server_group.is_post_quantum, "{server_group:?}");
+        } else if cfg!(all(s2n_quic_unstable, s2n_quic_enable_pq_tls)) {
+            // builds with post-quantum support negotiate one of the policy's hybrid Kyber groups
+            assert!(server_group.is_post_quantum, "{server_group:?}");
+            assert!(server_group.name.contains("_kyber-"), "{server_group:?}");
+        }
+    }
+}
+
+fn public_key_pin(pem: &str) -> [u8; 32] {
+    let certificate = openssl::x509::X509::from_pem(pem.as_bytes()).unwrap();
+    let spki = certificate
+        .public_key()
+        .unwrap()
+        .public_key_to_der()
+        .unwrap();
+    openssl::sha::sha256(&spki)
+}
+
+fn s2n_client_with_pins(pins: Vec<[u8; 32]>) -> client::Client {
+    client::Builder::default()
+        .with_x509_verification_disabled()
+        .unwrap()
+        .with_pinned_public_keys(pins)
+        .unwrap()
+        .build()
+        .unwrap()
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_public_key_pin_test() {
+    let mut server_endpoint = server::Builder::default()
+        .with_certificate(UNTRUSTED_CERT_PEM, UNTRUSTED_KEY_PEM)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    // a matching pin is accepted, even though the certificate isn't trusted
+    let mut client_endpoint = s2n_client_with_pins(vec![
+        public_key_pin(CERT_PEM),
+        public_key_pin(UNTRUSTED_CERT_PEM),
+    ]);
+    run(&mut server_endpoint, &mut client_endpoint, None);
+
+    let mut client_endpoint = s2n_client_with_pins(vec![public_key_pin(CERT_PEM)]);
+    let e = run_result(&mut server_endpoint, &mut client_endpoint, None).unwrap_err();
+    assert_eq!(
+        e.try_into_crypto_error().unwrap().code,
+        s2n_quic_core::crypto::CryptoError::BAD_CERTIFICATE.code
+    );
+
+    // pins are checked in addition to the trust store by default
+    let mut client_endpoint = client::Builder::default()
+        .with_certificate(CERT_PEM)
+        .unwrap()
+        .with_pinned_public_keys(vec![public_key_pin(UNTRUSTED_CERT_PEM)])
+        .unwrap()
+        .build()
+        .unwrap();
+    assert!(run_result(&mut server_endpoint, &mut client_endpoint, None).is_err());
+
+    assert!(client::Builder::default()
+        .with_pinned_public_keys(vec![])
+        .is_err());
+}
+
+/// Encodes a DER element with the given tag and contents
+fn der_element(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let mut element = vec![tag];
+    let len = contents.len();
+    if len < 0x80 {
+        element.push(len as u8);
+    } else {
+        let octets: Vec<u8> = len
+            .to_be_bytes()
+            .iter()
+            .copied()
+            .skip_while(|octet| *octet == 0)
+            .collect();
+        element.push(0x80 | octets.len() as u8);
+        element.extend_from_slice(&octets);
+    }
+    element.extend_from_slice(contents);
+    element
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn public_key_pin_verify_test() {
+    use crate::pin::PublicKeyPins;
+    use bolero::check;
+
+    check!()
+        .with_type::<(bool, Vec<u8>, Vec<u8>, Vec<u8>)>()
+        .cloned()
+        .for_each(|(versioned, key, other_key, fields)| {
+            // the certificate fields other than the public key aren't interpreted, so they're
+            // filled with the same generated contents
+            let field = der_element(0x30, &fields);
+            let spki = der_element(0x30, &key);
+
+            let mut tbs = vec![];
+            if versioned {
+                tbs.extend(der_element(0xa0, &der_element(0x02, &[2])));
+            }
+            for _ in 0..5 {
+                tbs.extend_from_slice(&field);
+            }
+            tbs.extend_from_slice(&spki);
+            let certificate = der_element(0x30, &der_element(0x30, &tbs));
+
+            let pin = openssl::sha::sha256(&spki);
+            let other_pin = openssl::sha::sha256(&der_element(0x30, &other_key));
+
+            let pins = PublicKeyPins::new(vec![other_pin, pin]).unwrap();
+            assert!(pins.verify(&certificate));
+
+            let pins = PublicKeyPins::new(vec![other_pin]).unwrap();
+            assert_eq!(pins.verify(&certificate), key == other_key);
+
+            // truncated certificates are never accepted, and no input causes a panic
+            let pins = PublicKeyPins::new(vec![pin]).unwrap();
+            for len in 0..certificate.len() {
+                assert!(!pins.verify(&certificate[..len]));
+            }
+            pins.verify(&fields);
+        });
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_x509_verification_disabled_test() {
+    let mut server_endpoint = server::Builder::default()
+        .with_certificate(UNTRUSTED_CERT_PEM, UNTRUSTED_KEY_PEM)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    // the server's certificate isn't trusted by default
+    let mut client_endpoint = s2n_client();
+    assert!(run_result(&mut server_endpoint, &mut client_endpoint, None).is_err());
+
+    let mut client_endpoint = client::Builder::default()
+        .with_x509_verification_disabled()
+        .unwrap()
+        .build()
+        .unwrap();
+    run(&mut server_endpoint, &mut client_endpoint, None);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_trust_location_test() {
+    let ca_file = std::path::Path::new(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../s2n-quic-core/certs/cert.pem"
+    ));
+
+    let mut server_endpoint = s2n_server();
+    let mut client_endpoint = client::Builder::default()
+        .with_empty_trust_store()
+        .unwrap()
+        .with_trust_location(Some(ca_file), None)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    run(&mut server_endpoint, &mut client_endpoint, None);
+
+    assert!(client::Builder::default()
+        .with_trust_location(None, None)
+        .is_err());
+}
+
+fn s2n_server_with_mtls_chain() -> server::Server {
+    let mut chain = MTLS_SERVER_CERT.to_string();
+    chain.push_str(MTLS_CA_CERT);
+
+    server::Builder::default()
+        .with_certificate(chain, MTLS_SERVER_KEY)
+        .unwrap()
+        .build()
+        .unwrap()
+}
+
+fn s2n_client_with_max_cert_chain_depth(len: u16) -> client::Client {
+    client::Builder::default()
+        .with_certificate(MTLS_CA_CERT)
+        .unwrap()
+        .with_max_cert_chain_depth(len)
+        .unwrap()
+        .build()
+        .unwrap()
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_max_cert_chain_depth_test() {
+    // the server presents a chain of 2 certificates
+    let mut server_endpoint = s2n_server_with_mtls_chain();
+
+    let mut client_endpoint = s2n_client_with_max_cert_chain_depth(2);
+    run(&mut server_endpoint, &mut client_endpoint, None);
+
+    let mut client_endpoint = s2n_client_with_max_cert_chain_depth(1);
+    assert!(run_result(&mut server_endpoint, &mut client_endpoint, None).is_err());
+
+    assert!(client::Builder::default()
+        .with_max_cert_chain_depth(0)
+        .is_err());
+    assert!(server::Builder::default()
+        .with_max_cert_chain_depth(0)
+        .is_err());
+}
+
+#[test]
+fn application_protocols_validation_test() {
+    let empty: [&[u8]; 0] = [];
+    assert!(server::Builder::default()
+        .with_application_protocols(empty)
+        .is_err());
+    assert!(client::Builder::default()
+        .with_application_protocols(empty)
+        .is_err());
+
+    let too_long = [0u8; 256];
+    for protocols in [&[&b""[..]][..], &[&b"h3"[..], &too_long[..]][..]] {
+        assert!(server::Builder::default()
+            .with_application_protocols(protocols)
+            .is_err());
+        assert!(client::Builder::default()
+            .with_application_protocols(protocols)
+            .is_err());
+    }
+
+    let longest = [b'a'; 255];
+    assert!(server::Builder::default()
+        .with_application_protocols([&longest[..]])
+        .is_ok());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn s2n_client_s2n_server_application_protocol_test() {
+    let new_server = || {
+        server::Builder::default()
+            .with_certificate(CERT_PEM, KEY_PEM)
+            .unwrap()
+            .with_application_protocols(["hq-interop", "h3"])
+            .unwrap()
+            .build()
+            .unwrap()
+    };
+    let new_client = |protocols: &[&str]| {
+        client::Builder::default()
+            .with_certificate(CERT_PEM)
+            .unwrap()
+            .with_application_protocols(protocols)
+            .unwrap()
+            .build()
+            .unwrap()
+    };
+
+    // the most preferred protocol of the server is selected
+    let pair = run_result(
+        &mut new_server(),
+        &mut new_client(&["h3", "hq-interop"]),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        pair.server.context.application_protocol.as_deref(),
+        Some(&b"hq-interop"[..])
+    );
+
+    // handshakes without a mutual protocol are rejected
+    let error = run_result(&mut new_server(), &mut new_client(&["h2"]), None).unwrap_err();
+    let error = error
+        .try_into_crypto_error()
+        .expect("expected a CRYPTO_ERROR");
+    assert_eq!(
+        error.code,
+        s2n_quic_core::crypto::CryptoError::NO_APPLICATION_PROTOCOL.code
+    );
+}
+
+#[test]
+fn server_build_validation_test() {
+    let builder = || {
+        server::Builder::default()
+            .with_certificate(CERT_PEM, KEY_PEM)
+            .unwrap()
+    };
+
+    // a server without a certificate can't complete any handshake
+    let server = server::Builder::default();
+    assert_eq!(server.validate(), Err(BuildError::MissingCertificate));
+    assert!(server.build().is_err());
+    assert!(builder().build().is_ok());
+
+    // client authentication without any trust anchors
+    assert_eq!(
+        builder()
+            .with_empty_trust_store()
+            .unwrap()
+            .with_client_authentication()
+            .unwrap()
+            .validate(),
+        Err(BuildError::ClientAuthWithoutTrustAnchors)
+    );
+    assert_eq!(
+        builder()
+            .with_empty_trust_store()
+            .unwrap()
+            .with_client_authentication()
+            .unwrap()
+            .with_trusted_certificate(CERT_PEM)
+            .unwrap()
+            .validate(),
+        Ok(())
+    );
+    assert_eq!(
+        builder().with_empty_trust_store().unwrap().validate(),
+        Ok(())
+    );
+
+    // ticket keys with tickets disabled
+    let key = [0; 16];
+    assert_eq!(
+        builder()
+            .with_session_ticket_key(b"key", &key, SystemTime::now())
+            .unwrap()
+            .with_session_tickets(false)
+            .unwrap()
+            .validate(),
+        Err(BuildError::TicketKeysWithoutTickets)
+    );
+    assert_eq!(
+        builder()
+            .with_session_tickets(false)
+            .unwrap()
+            .with_session_ticket_key(b"key", &key, SystemTime::now())
+            .unwrap()
+            .validate(),
+        Ok(())
+    );
+
+    // changes made through the config opt out of validation
+    let mut server = builder()
+        .with_empty_trust_store()
+        .unwrap()
+        .with_client_authentication()
+        .unwrap();
+    server.config_mut().trust_pem(CERT_PEM.as_bytes()).unwrap();
+    assert_eq!(server.validate(), Ok(()));
+    assert!(server.build().is_ok());
+
+    let mut server = server::Builder::default();
+    server
+        .config_mut()
+        .load_pem(CERT_PEM.as_bytes(), KEY_PEM.as_bytes())
+        .unwrap();
+    assert_eq!(server.validate(), Ok(()));
+    assert!(server.build().is_ok());
+}
+
+#[test]
+fn client_build_validation_test() {
+    // an empty trust store can't verify any server
+    let client = client::Builder::default().with_empty_trust_store().unwrap();
+    assert_eq!(client.validate(), Err(BuildError::EmptyTrustStore));
+    assert!(client.build().is_err());
+    assert_eq!(
+        client::Builder::default()
+            .with_empty_trust_store()
+            .unwrap()
+            .with_certificate(CERT_PEM)
+            .unwrap()
+            .validate(),
+        Ok(())
+    );
+    assert_eq!(
+        client::Builder::default()
+            .with_empty_trust_store()
+            .unwrap()
+            .with_x509_verification_disabled()
+            .unwrap()
+            .validate(),
+        Ok(())
+    );
+
+    // clearing the trust store discards previously trusted certificates
+    assert_eq!(
+        client::Builder::default()
+            .with_certificate(CERT_PEM)
+            .unwrap()
+            .with_empty_trust_store()
+            .unwrap()
+            .validate(),
+        Err(BuildError::EmptyTrustStore)
+    );
+}
+
+#[test]
+fn der_validation_test() {
+    // mismatched certificate and key
+    assert!(server::Builder::default()
+        .with_certificate(CERT_DER, key_pem_to_der(UNTRUSTED_KEY_PEM))
+        .is_err());
+
+    // truncated certificate
+    assert!(server::Builder::default()
+        .with_certificate(&CERT_DER[..CERT_DER.len() - 1], KEY_DER)
+        .is_err());
+
+    // multiple private keys
+    let mut keys = KEY_DER.to_vec();
+    keys.extend_from_slice(KEY_DER);
+    assert!(server::Builder::default()
+        .with_certificate(CERT_DER, keys)
+        .is_err());
+
+    // empty values
+    assert!(client::Builder::default()
+        .with_certificate(&[0u8; 0][..])
+        .is_err());
+    assert!(client::Builder::default()
+        .with_certificate(&[0x04, 0x00][..])
+        .is_err());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn shared_certificate_test() {
+    use crate::certificate::{IntoCertificate, IntoPrivateKey};
+
+    let certificate = CERT_PEM.into_certificate().unwrap();
+    let private_key = KEY_PEM.into_private_key().unwrap();
+
+    let build = || {
+        server::Builder::default()
+            .with_certificate(certificate.clone(), private_key.clone())
+            .unwrap()
+            .build()
+            .unwrap()
+    };
+
+    let mut first = build();
+    let mut second = build();
+    let mut client_endpoint = s2n_client();
+
+    // drop the original values before the servers are used
+    drop(certificate);
+    drop(private_key);
+
+    run(&mut first, &mut client_endpoint, None);
+    drop(first);
+    run(&mut second, &mut client_endpoint, None);
+}
+
+#[test]
+fn config_send_sync() {
+    use crate::Server;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+    fn assert_send<T: Send>() {}
+
+    assert_send_sync::<s2n_tls::config::Config>();
+    assert_send::<server::Server>();
+    assert_send::<client::Client>();
+
+    let config: s2n_tls::config::Config = server::Builder::default()
+        .with_certificate(CERT_PEM, KEY_PEM)
+        .unwrap()
+        .build()
+        .unwrap()
+        .into();
+
+    // make sure a single config can be shared across several threads
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let config = config.clone();
+            std::thread::spawn(move || {
+                let mut server = Server::from_loader(config);
+                let _ = server.new_server_session(&1);
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
 #[test]
 fn config_loader() {
     use crate::{ConfigLoader, Server};
@@ -0,0 +1,16 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use s2n_tls::callbacks::VerifyHostNameCallback;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Wraps an application's [`VerifyHostNameCallback`] so panics don't unwind into s2n-tls
+pub(crate) struct VerifyHostName<T>(pub T);
+
+impl<T: VerifyHostNameCallback> VerifyHostNameCallback for VerifyHostName<T> {
+    fn verify_host_name(&self, host_name: &str) -> bool {
+        // a panicking handler rejects the certificate rather than unwinding across the
+        // FFI boundary
+        catch_unwind(AssertUnwindSafe(|| self.0.verify_host_name(host_name))).unwrap_or(false)
+    }
+}
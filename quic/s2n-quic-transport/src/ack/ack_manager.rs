@@ -10,6 +10,7 @@ use crate::{
     processed_packet::ProcessedPacket,
     transmission,
 };
+use core::time::Duration;
 use s2n_quic_core::{
     ack,
     counter::{Counter, Saturating},
@@ -18,12 +19,28 @@ use s2n_quic_core::{
         builder::{AckAction, AckProcessed},
         IntoEvent as _,
     },
-    frame::{ack::EcnCounts, Ack, Ping},
+    frame::{ack::EcnCounts, Ack, AckFrequency, ImmediateAck, Ping},
     packet::number::{PacketNumber, PacketNumberSpace},
     time::{timer, Timer, Timestamp},
+    transport,
     varint::VarInt,
 };
 
+// TODO update to draft link after published
+// https://github.com/quicwg/base-drafts/pull/3623
+// An ACK frame SHOULD be generated for at least every 10th ack-eliciting packet
+const DEFAULT_PACKET_TOLERANCE: u8 = 10;
+
+// https://datatracker.ietf.org/doc/html/draft-ietf-quic-ack-frequency#section-6.2
+// Without an ACK_FREQUENCY frame, an ACK is sent immediately upon any reordering,
+// as described in RFC 9000.
+const DEFAULT_REORDERING_THRESHOLD: VarInt = VarInt::from_u8(1);
+
+// The largest max_ack_delay a peer can request with an ACK_FREQUENCY frame. Larger requests are
+// clamped to the largest value the max_ack_delay transport parameter can take, so the peer can't
+// stretch the PTO and idle timers beyond what the transport parameter would allow.
+const MAX_REQUESTED_ACK_DELAY: Duration = Duration::from_millis(1 << 14);
+
 //= https://www.rfc-editor.org/rfc/rfc9000#section-13.2
 //# Endpoints acknowledge all packets they receive and process.  However,
 //# only ack-eliciting packets cause an ACK frame to be sent within the
@@ -52,8 +69,27 @@ pub struct AckManager {
     pub(super) ack_ranges: ack::Ranges,
 
     /// Locally configured AckSettings
+    ///
+    /// The `max_ack_delay` may be updated by the peer with an ACK_FREQUENCY frame
     pub ack_settings: ack::Settings,
 
+    /// The min_ack_delay advertised to the peer, if the ACK frequency extension is enabled
+    min_ack_delay: Option<Duration>,
+
+    /// The number of processed packets after which an ACK is sent without delay
+    packet_tolerance: u8,
+
+    /// The amount of reordering after which an ACK is sent without delay
+    ///
+    /// A value of 0 indicates an ACK is never sent immediately due to reordering.
+    reordering_threshold: VarInt,
+
+    /// The largest sequence number of the processed ACK_FREQUENCY frames
+    ack_frequency_sequence_number: Option<VarInt>,
+
+    /// The smallest missing packet number which hasn't been reported in an ACK frame
+    missing_packet_number: Option<PacketNumber>,
+
     /// The largest packet number that we've acked - used for pn decoding
     largest_received_packet_number_acked: PacketNumber,
 
@@ -79,6 +115,11 @@ impl AckManager {
             ack_delay_timer: Timer::default(),
             ack_eliciting_transmissions: AckElicitingTransmissionSet::default(),
             ack_settings,
+            min_ack_delay: None,
+            packet_tolerance: DEFAULT_PACKET_TOLERANCE,
+            reordering_threshold: DEFAULT_REORDERING_THRESHOLD,
+            ack_frequency_sequence_number: None,
+            missing_packet_number: None,
             ack_ranges: ack::Ranges::new(ack_settings.ack_ranges_limit as usize),
             largest_received_packet_number_acked: packet_space
                 .new_packet_number(VarInt::from_u8(0)),
@@ -90,6 +131,12 @@ impl AckManager {
         }
     }
 
+    /// Enables processing of ACK_FREQUENCY frames, after advertising the given min_ack_delay
+    pub fn with_min_ack_delay(mut self, min_ack_delay: Option<Duration>) -> Self {
+        self.min_ack_delay = min_ack_delay;
+        self
+    }
+
     /// Called when an outgoing packet is being assembled
     pub fn on_transmit<W: WriteContext>(&mut self, context: &mut W) -> bool {
         let constraint = context.transmission_constraint();
@@ -175,6 +222,9 @@ impl AckManager {
 
         // reset the number of packets since transmission
         self.processed_packets_since_transmission = Counter::new(0);
+
+        // any missing packets have now been reported to the peer
+        self.missing_packet_number = None;
     }
 
     /// Called when a set of packets was acknowledged
@@ -213,9 +263,8 @@ impl AckManager {
         let now = processed_packet.datagram.timestamp;
 
         // perform some checks before inserting into the ack_ranges
-        let (is_ordered, is_largest) = self
-            .ack_ranges
-            .max_value()
+        let largest_received = self.ack_ranges.max_value();
+        let (is_ordered, is_largest) = largest_received
             .and_then(|max_value| {
                 // check to see if the packet number is the next one in the sequence
                 let is_ordered = packet_number == max_value.next()?;
@@ -292,22 +341,26 @@ impl AckManager {
         if processed_packet.is_ack_eliciting() {
             let mut should_activate = false;
 
-            //= https://www.rfc-editor.org/rfc/rfc9000#section-13.2.1
-            //# In order to assist loss detection at the sender, an endpoint SHOULD
-            //# generate and send an ACK frame without delay when it receives an ack-
-            //# eliciting packet either:
-            //#
-            //# *  when the received packet has a packet number less than another
-            //#    ack-eliciting packet that has been received, or
-
-            should_activate |= !is_largest;
-
-            //= https://www.rfc-editor.org/rfc/rfc9000#section-13.2.1
-            //# *  when the packet has a packet number larger than the highest-
-            //#    numbered ack-eliciting packet that has been received and there are
-            //#    missing packets between that packet and this packet.
-
-            should_activate |= !is_ordered;
+            if self.reordering_threshold == DEFAULT_REORDERING_THRESHOLD {
+                //= https://www.rfc-editor.org/rfc/rfc9000#section-13.2.1
+                //# In order to assist loss detection at the sender, an endpoint SHOULD
+                //# generate and send an ACK frame without delay when it receives an ack-
+                //# eliciting packet either:
+                //#
+                //# *  when the received packet has a packet number less than another
+                //#    ack-eliciting packet that has been received, or
+
+                should_activate |= !is_largest;
+
+                //= https://www.rfc-editor.org/rfc/rfc9000#section-13.2.1
+                //# *  when the packet has a packet number larger than the highest-
+                //#    numbered ack-eliciting packet that has been received and there are
+                //#    missing packets between that packet and this packet.
+
+                should_activate |= !is_ordered;
+            } else if self.reordering_threshold > VarInt::from_u8(0) {
+                should_activate |= self.on_reordering(packet_number, largest_received);
+            }
 
             //= https://www.rfc-editor.org/rfc/rfc9000#section-13.2.1
             //# Similarly, packets marked with the ECN Congestion Experienced (CE)
@@ -315,15 +368,14 @@ impl AckManager {
             //# reduce the peer's response time to congestion events.
             should_activate |= processed_packet.datagram.ecn.congestion_experienced();
 
-            // TODO update to draft link after published
-            // https://github.com/quicwg/base-drafts/pull/3623
-            // An ACK frame SHOULD be generated for at least every 10th ack-eliciting packet
-
-            // TODO support delayed ack proposal
-            // https://tools.ietf.org/html/draft-iyengar-quic-delayed-ack-00
-            let packet_tolerance = 10;
+            // The packet tolerance defaults to every 10th packet, unless the peer
+            // requested a different threshold with an ACK_FREQUENCY frame
+            should_activate |= self.processed_packets_since_transmission >= self.packet_tolerance;
 
-            should_activate |= self.processed_packets_since_transmission >= packet_tolerance;
+            // https://datatracker.ietf.org/doc/html/draft-ietf-quic-ack-frequency#section-5
+            // On receiving an IMMEDIATE_ACK frame, an endpoint SHOULD send an ACK frame
+            // without delay.
+            should_activate |= processed_packet.immediate_ack;
 
             //= https://www.rfc-editor.org/rfc/rfc9000#section-9.3.3
             //# An endpoint that receives a PATH_CHALLENGE on an active path SHOULD
@@ -350,6 +402,110 @@ impl AckManager {
         }
     }
 
+    /// Called when an ACK_FREQUENCY frame is received from the peer
+    pub fn on_ack_frequency_frame(&mut self, frame: &AckFrequency) -> Result<(), transport::Error> {
+        let min_ack_delay = self.ensure_ack_frequency_enabled()?;
+
+        // https://datatracker.ietf.org/doc/html/draft-ietf-quic-ack-frequency#section-4
+        // Receipt of an invalid Request Max Ack Delay value MUST be treated as a
+        // connection error of type PROTOCOL_VIOLATION. A value is invalid if it is
+        // smaller than the receiver's advertised min_ack_delay.
+        if frame.request_max_ack_delay() < min_ack_delay {
+            return Err(transport::Error::PROTOCOL_VIOLATION
+                .with_reason("request_max_ack_delay is less than min_ack_delay"));
+        }
+
+        // https://datatracker.ietf.org/doc/html/draft-ietf-quic-ack-frequency#section-4
+        // On the first received ACK_FREQUENCY frame in a connection, an endpoint MUST
+        // immediately record all values from the frame. On a subsequent ACK_FREQUENCY
+        // frame, the endpoint MUST ignore the frame if its sequence number is not
+        // greater than the largest one already received.
+        if self
+            .ack_frequency_sequence_number
+            .map_or(false, |largest| frame.sequence_number <= largest)
+        {
+            return Ok(());
+        }
+        self.ack_frequency_sequence_number = Some(frame.sequence_number);
+
+        // An ACK is sent after receiving more packets than the threshold. Thresholds which don't
+        // fit in the tolerance are clamped, which only results in ACKs being sent more often than
+        // the peer requested.
+        self.packet_tolerance = frame
+            .ack_eliciting_threshold
+            .as_u64()
+            .saturating_add(1)
+            .try_into()
+            .unwrap_or(u8::MAX);
+        self.ack_settings.max_ack_delay =
+            frame.request_max_ack_delay().min(MAX_REQUESTED_ACK_DELAY);
+        self.reordering_threshold = frame.reordering_threshold;
+        self.missing_packet_number = None;
+
+        Ok(())
+    }
+
+    /// Called when an IMMEDIATE_ACK frame is received from the peer
+    ///
+    /// The ACK is sent once the packet containing the frame is processed.
+    pub fn on_immediate_ack_frame(&self, _frame: &ImmediateAck) -> Result<(), transport::Error> {
+        self.ensure_ack_frequency_enabled()?;
+        Ok(())
+    }
+
+    /// Returns the advertised min_ack_delay if the ACK frequency extension is enabled
+    fn ensure_ack_frequency_enabled(&self) -> Result<Duration, transport::Error> {
+        // https://datatracker.ietf.org/doc/html/draft-ietf-quic-ack-frequency#section-3
+        // Receiving an ACK_FREQUENCY or IMMEDIATE_ACK frame without having sent the
+        // min_ack_delay transport parameter MUST be treated as a connection error of
+        // type PROTOCOL_VIOLATION.
+        self.min_ack_delay.ok_or_else(|| {
+            transport::Error::PROTOCOL_VIOLATION
+                .with_reason("the ACK frequency extension was not negotiated")
+        })
+    }
+
+    /// Returns `true` if the received packet exceeds the negotiated reordering threshold
+    ///
+    /// https://datatracker.ietf.org/doc/html/draft-ietf-quic-ack-frequency#section-6.2
+    /// An ACK is sent immediately when the distance between the largest received packet
+    /// number and the smallest unreported missing packet number is at least the
+    /// reordering threshold.
+    fn on_reordering(
+        &mut self,
+        packet_number: PacketNumber,
+        largest_received: Option<PacketNumber>,
+    ) -> bool {
+        // record the start of a new gap if nothing else is missing
+        if self.missing_packet_number.is_none() {
+            if let Some(largest_received) = largest_received.filter(|pn| packet_number > *pn) {
+                self.missing_packet_number = largest_received
+                    .next()
+                    .filter(|missing| *missing != packet_number);
+            }
+        }
+
+        let largest_received = if let Some(largest) = self.ack_ranges.max_value() {
+            largest
+        } else {
+            return false;
+        };
+
+        // skip over any missing packets which have since arrived
+        while let Some(missing) = self.missing_packet_number {
+            if !self.ack_ranges.contains(&missing) {
+                break;
+            }
+            self.missing_packet_number = missing.next().filter(|next| *next < largest_received);
+        }
+
+        self.missing_packet_number
+            .and_then(|missing| largest_received.checked_distance(missing))
+            .map_or(false, |distance| {
+                distance >= self.reordering_threshold.as_u64()
+            })
+    }
+
     /// Called when the connection timer expired
     pub fn on_timeout(&mut self, timestamp: Timestamp) {
         // NOTE: ack_elicitation_timer is not actively polled
@@ -634,6 +790,180 @@ mod tests {
         );
     }
 
+    /// Helper function to process an ack-eliciting packet with the given packet number
+    fn helper_process_packet(manager: &mut AckManager, packet_number: u8, immediate_ack: bool) {
+        let pn =
+            PacketNumberSpace::ApplicationData.new_packet_number(VarInt::from_u8(packet_number));
+        let datagram = helper_datagram_info(ExplicitCongestionNotification::default());
+        let mut processed_packet = ProcessedPacket::new(pn, &datagram);
+        processed_packet.ack_elicitation = AckElicitation::Eliciting;
+        processed_packet.immediate_ack = immediate_ack;
+
+        let path = helper_path_server();
+        let path_id = path::Id::test_id();
+        manager.on_processed_packet(
+            &processed_packet,
+            path_event!(path, path_id),
+            &mut Publisher::no_snapshot(),
+        );
+    }
+
+    fn helper_ack_frequency(
+        sequence_number: u8,
+        ack_eliciting_threshold: u8,
+        request_max_ack_delay: Duration,
+        reordering_threshold: u8,
+    ) -> AckFrequency {
+        AckFrequency {
+            sequence_number: VarInt::from_u8(sequence_number),
+            ack_eliciting_threshold: VarInt::from_u8(ack_eliciting_threshold),
+            request_max_ack_delay: VarInt::try_from(request_max_ack_delay.as_micros()).unwrap(),
+            reordering_threshold: VarInt::from_u8(reordering_threshold),
+        }
+    }
+
+    #[test]
+    fn ack_frequency_not_negotiated() {
+        let mut manager =
+            AckManager::new(PacketNumberSpace::ApplicationData, ack::Settings::default());
+
+        let frame = helper_ack_frequency(0, 1, Duration::from_millis(25), 1);
+        assert_eq!(
+            manager.on_ack_frequency_frame(&frame).unwrap_err().code,
+            transport::Error::PROTOCOL_VIOLATION.code
+        );
+        assert_eq!(
+            manager
+                .on_immediate_ack_frame(&ImmediateAck)
+                .unwrap_err()
+                .code,
+            transport::Error::PROTOCOL_VIOLATION.code
+        );
+    }
+
+    #[test]
+    fn ack_frequency_frame() {
+        let mut manager =
+            AckManager::new(PacketNumberSpace::ApplicationData, ack::Settings::default())
+                .with_min_ack_delay(Some(Duration::from_millis(1)));
+        assert!(manager.on_immediate_ack_frame(&ImmediateAck).is_ok());
+
+        // the requested max_ack_delay can't be less than the min_ack_delay
+        let frame = helper_ack_frequency(0, 1, Duration::from_micros(999), 1);
+        assert_eq!(
+            manager.on_ack_frequency_frame(&frame).unwrap_err().code,
+            transport::Error::PROTOCOL_VIOLATION.code
+        );
+
+        let frame = helper_ack_frequency(1, 20, Duration::from_millis(50), 0);
+        manager.on_ack_frequency_frame(&frame).unwrap();
+        assert_eq!(manager.packet_tolerance, 21);
+        assert_eq!(
+            manager.ack_settings.max_ack_delay,
+            Duration::from_millis(50)
+        );
+        assert_eq!(manager.reordering_threshold, VarInt::from_u8(0));
+
+        // frames with older sequence numbers are ignored
+        for sequence_number in [0, 1] {
+            let frame = helper_ack_frequency(sequence_number, 2, Duration::from_millis(5), 1);
+            manager.on_ack_frequency_frame(&frame).unwrap();
+            assert_eq!(manager.packet_tolerance, 21);
+        }
+
+        let frame = helper_ack_frequency(2, 2, Duration::from_millis(5), 1);
+        manager.on_ack_frequency_frame(&frame).unwrap();
+        assert_eq!(manager.packet_tolerance, 3);
+        assert_eq!(manager.ack_settings.max_ack_delay, Duration::from_millis(5));
+    }
+
+    #[test]
+    fn ack_frequency_frame_clamping() {
+        let mut manager =
+            AckManager::new(PacketNumberSpace::ApplicationData, ack::Settings::default())
+                .with_min_ack_delay(Some(Duration::from_millis(1)));
+
+        let frame = AckFrequency {
+            sequence_number: VarInt::from_u8(0),
+            ack_eliciting_threshold: VarInt::MAX,
+            request_max_ack_delay: VarInt::MAX,
+            reordering_threshold: VarInt::from_u8(1),
+        };
+        manager.on_ack_frequency_frame(&frame).unwrap();
+
+        assert_eq!(manager.packet_tolerance, u8::MAX);
+        assert_eq!(manager.ack_settings.max_ack_delay, MAX_REQUESTED_ACK_DELAY);
+    }
+
+    #[test]
+    fn ack_eliciting_threshold() {
+        let mut manager =
+            AckManager::new(PacketNumberSpace::ApplicationData, ack::Settings::default())
+                .with_min_ack_delay(Some(Duration::from_millis(1)));
+        let frame = helper_ack_frequency(0, 20, Duration::from_millis(25), 0);
+        manager.on_ack_frequency_frame(&frame).unwrap();
+
+        // the default tolerance of 10 packets is raised by the peer
+        for packet_number in 1..=20 {
+            helper_process_packet(&mut manager, packet_number, false);
+            assert!(!manager.transmission_state.is_active());
+        }
+
+        helper_process_packet(&mut manager, 21, false);
+        assert!(manager.transmission_state.is_active());
+    }
+
+    #[test]
+    fn reordering_threshold() {
+        // By default, any gap in the packet numbers causes an immediate ACK
+        let mut manager =
+            AckManager::new(PacketNumberSpace::ApplicationData, ack::Settings::default());
+        helper_process_packet(&mut manager, 1, false);
+        assert!(!manager.transmission_state.is_active());
+        helper_process_packet(&mut manager, 3, false);
+        assert!(manager.transmission_state.is_active());
+
+        let mut manager =
+            AckManager::new(PacketNumberSpace::ApplicationData, ack::Settings::default())
+                .with_min_ack_delay(Some(Duration::from_millis(1)));
+        let frame = helper_ack_frequency(0, 100, Duration::from_millis(25), 3);
+        manager.on_ack_frequency_frame(&frame).unwrap();
+
+        helper_process_packet(&mut manager, 1, false);
+        // packet 2 is missing
+        helper_process_packet(&mut manager, 3, false);
+        helper_process_packet(&mut manager, 4, false);
+        assert!(!manager.transmission_state.is_active());
+
+        // the gap exceeds the threshold once packet 5 arrives
+        helper_process_packet(&mut manager, 5, false);
+        assert!(manager.transmission_state.is_active());
+
+        // a late packet which fills the gap doesn't require an immediate ACK
+        let mut manager =
+            AckManager::new(PacketNumberSpace::ApplicationData, ack::Settings::default())
+                .with_min_ack_delay(Some(Duration::from_millis(1)));
+        manager.on_ack_frequency_frame(&frame).unwrap();
+
+        for packet_number in [1, 3, 2, 4, 5] {
+            helper_process_packet(&mut manager, packet_number, false);
+            assert!(!manager.transmission_state.is_active());
+        }
+    }
+
+    #[test]
+    fn immediate_ack() {
+        let mut manager =
+            AckManager::new(PacketNumberSpace::ApplicationData, ack::Settings::default())
+                .with_min_ack_delay(Some(Duration::from_millis(1)));
+
+        helper_process_packet(&mut manager, 1, false);
+        assert!(!manager.transmission_state.is_active());
+
+        helper_process_packet(&mut manager, 2, true);
+        assert!(manager.transmission_state.is_active());
+    }
+
     #[test]
     #[cfg(target_pointer_width = "64")]
     fn size_of_snapshots() {
@@ -17,6 +17,7 @@ use s2n_quic_core::{
     application,
     application::ServerName,
     inet::SocketAddress,
+    path::migration,
     query::{Query, QueryMut},
     stream::StreamType,
 };
@@ -189,6 +190,26 @@ impl Connection {
         self.api.keep_alive(enabled)
     }
 
+    /// Starts migrating the connection to a new local address
+    ///
+    /// The outcome of the migration can be polled with [`Self::poll_migrate`].
+    #[inline]
+    pub fn migrate(&self, local_address: SocketAddress) -> Result<(), migration::Error> {
+        self.api.migrate(local_address)
+    }
+
+    /// Polls for the outcome of a migration started with [`Self::migrate`]
+    ///
+    /// The method will return
+    /// - `Poll::Ready(Ok(()))` if the connection switched to the new local address or
+    ///   no migration is in progress
+    /// - `Poll::Ready(Err(migration_error))` if the migration failed
+    /// - `Poll::Pending` if the new path is still being validated
+    #[inline]
+    pub fn poll_migrate(&self, context: &Context) -> Poll<Result<(), migration::Error>> {
+        self.api.poll_migrate(context)
+    }
+
     #[inline]
     pub fn local_address(&self) -> Result<SocketAddress, connection::Error> {
         self.api.local_address()
@@ -18,6 +18,7 @@ use s2n_quic_core::{
     application,
     application::ServerName,
     inet::SocketAddress,
+    path::migration,
     query::{Query, QueryMut},
     stream::{ops, StreamId, StreamType},
 };
@@ -64,6 +65,10 @@ pub(crate) trait ConnectionApiProvider: Sync + Send {
 
     fn keep_alive(&self, enabled: bool) -> Result<(), connection::Error>;
 
+    fn migrate(&self, local_address: SocketAddress) -> Result<(), migration::Error>;
+
+    fn poll_migrate(&self, context: &Context) -> Poll<Result<(), migration::Error>>;
+
     fn local_address(&self) -> Result<SocketAddress, connection::Error>;
 
     fn remote_address(&self) -> Result<SocketAddress, connection::Error>;
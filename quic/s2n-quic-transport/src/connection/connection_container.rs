@@ -17,7 +17,10 @@ use crate::{
     },
     stream,
 };
-use alloc::{collections::BTreeMap, sync::Arc};
+use alloc::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+};
 use bytes::Bytes;
 use core::{
     cell::Cell,
@@ -35,6 +38,7 @@ use s2n_quic_core::{
     application::ServerName,
     event::supervisor,
     inet::SocketAddress,
+    path::migration,
     query::{Query, QueryMut},
     recovery::K_GRANULARITY,
     time::Timestamp,
@@ -321,6 +325,14 @@ impl<C: connection::Trait, L: connection::Lock<C>> ConnectionApiProvider for Con
         self.api_write_call(|conn| conn.keep_alive(enabled))
     }
 
+    fn migrate(&self, local_address: SocketAddress) -> Result<(), migration::Error> {
+        self.api_write_call(|conn| conn.migrate(local_address))
+    }
+
+    fn poll_migrate(&self, context: &Context) -> Poll<Result<(), migration::Error>> {
+        self.api_poll_call(|conn| conn.poll_migrate(context))
+    }
+
     fn local_address(&self) -> Result<SocketAddress, connection::Error> {
         self.api_read_call(|conn| conn.local_address())
     }
@@ -359,6 +371,8 @@ impl<C: connection::Trait, L: connection::Lock<C>> ConnectionApiProvider for Con
 /// A Connection can be a member in any of those, in addition to being a member of
 /// `ConnectionContainer::connection_map`.
 struct InterestLists<C: connection::Trait, L: connection::Lock<C>> {
+    /// Lifecycle changes which haven't been reported to the endpoint limits yet
+    lifecycle_events: VecDeque<LifecycleEvent>,
     /// Connections which have been finalized
     done_connections: LinkedList<DoneConnectionsAdapter<C, L>>,
     /// Connections which need to transmit data
@@ -377,6 +391,7 @@ struct InterestLists<C: connection::Trait, L: connection::Lock<C>> {
 impl<C: connection::Trait, L: connection::Lock<C>> InterestLists<C, L> {
     fn new() -> Self {
         Self {
+            lifecycle_events: VecDeque::new(),
             done_connections: LinkedList::new(DoneConnectionsAdapter::new()),
             waiting_for_transmission: LinkedList::new(WaitingForTransmissionAdapter::new()),
             waiting_for_connection_id: LinkedList::new(WaitingForConnectionIdAdapter::new()),
@@ -483,11 +498,17 @@ impl<C: connection::Trait, L: connection::Lock<C>> InterestLists<C, L> {
 
         // Accepted connections are only automatically pushed into the accepted connections queue.
         if interests.accept {
-            node.inner.write(|conn| {
+            let remote_address = node.inner.write(|conn| {
                 debug_assert!(!conn.is_handshaking());
                 conn.mark_as_accepted();
+                conn.remote_address()
             })?;
 
+            if let Ok(remote_address) = remote_address {
+                self.lifecycle_events
+                    .push_back(LifecycleEvent::HandshakeCompleted { remote_address });
+            }
+
             // Decrement the inflight handshakes because this connection completed the
             // handshake and is being passed to the application to be accepted.
             self.handshake_connections -= 1;
@@ -521,6 +542,35 @@ impl<C: connection::Trait, L: connection::Lock<C>> InterestLists<C, L> {
             }
         }
 
+        if interests.server_name {
+            let server_name = node.inner.write(|conn| {
+                conn.mark_server_name_reported();
+                (
+                    conn.server_name(),
+                    conn.remote_address(),
+                    conn.local_address(),
+                )
+            })?;
+
+            if let (Some(server_name), Ok(remote_address), Ok(local_address)) = server_name {
+                self.lifecycle_events.push_back(LifecycleEvent::ServerName {
+                    internal_connection_id: id,
+                    server_name,
+                    remote_address,
+                    local_address,
+                });
+            }
+        }
+
+        if interests.new_token {
+            let token = node.inner.write(|conn| conn.take_pending_token())?;
+
+            if let Some((server_name, token)) = token {
+                self.lifecycle_events
+                    .push_back(LifecycleEvent::NewToken { server_name, token });
+            }
+        }
+
         if interests.finalization != node.done_connections_link.is_linked() {
             if interests.finalization {
                 if <C::Config as endpoint::Config>::ENDPOINT_TYPE.is_client() {
@@ -546,13 +596,25 @@ impl<C: connection::Trait, L: connection::Lock<C>> InterestLists<C, L> {
                     }
                 }
 
-                if node.inner.read(|conn| !conn.is_accepted())? {
+                let (remote_address, is_accepted, error) = node
+                    .inner
+                    .read(|conn| (conn.remote_address(), conn.is_accepted(), conn.error()))?;
+
+                if !is_accepted {
                     // Decrement the inflight handshakes because the connection has
                     // been finalized before it was handed back to the application
                     // and thus this count was not decremented previously
                     self.handshake_connections -= 1;
                 }
 
+                if let Ok(remote_address) = remote_address {
+                    self.lifecycle_events.push_back(LifecycleEvent::Closed {
+                        remote_address,
+                        handshake_completed: is_accepted,
+                        error,
+                    });
+                }
+
                 insert_interest!(done_connections, push_back);
             } else {
                 unreachable!("Done connections should never report not done later");
@@ -822,6 +884,19 @@ impl<C: connection::Trait, L: connection::Lock<C>> ConnectionContainer<C, L> {
         self.interest_lists.connection_count
     }
 
+    /// Returns `true` if there are lifecycle changes which haven't been drained yet
+    pub fn has_lifecycle_events(&self) -> bool {
+        !self.interest_lists.lifecycle_events.is_empty()
+    }
+
+    /// Removes the oldest lifecycle change which hasn't been reported yet
+    ///
+    /// Reporting a change can modify connections and produce further changes, so they're
+    /// removed one at a time rather than drained.
+    pub fn pop_lifecycle_event(&mut self) -> Option<LifecycleEvent> {
+        self.interest_lists.lifecycle_events.pop_front()
+    }
+
     /// Looks up the `Connection` with the given ID and executes the provided function
     /// on it.
     ///
@@ -1077,6 +1152,32 @@ impl<C: connection::Trait, L: connection::Lock<C>> ConnectionContainer<C, L> {
     }
 }
 
+/// A change in the lifecycle of a connection which is reported to the endpoint limits or the
+/// token store
+#[derive(Debug)]
+pub enum LifecycleEvent {
+    /// The server received the server name requested by the client
+    ServerName {
+        internal_connection_id: InternalConnectionId,
+        server_name: ServerName,
+        remote_address: SocketAddress,
+        local_address: SocketAddress,
+    },
+    /// The client received an address validation token from the server
+    NewToken {
+        server_name: ServerName,
+        token: Vec<u8>,
+    },
+    /// The connection completed its handshake and was handed to the application
+    HandshakeCompleted { remote_address: SocketAddress },
+    /// The connection was finalized
+    Closed {
+        remote_address: SocketAddress,
+        handshake_completed: bool,
+        error: Option<connection::Error>,
+    },
+}
+
 /// Return values for iterations over a `Connection` list.
 /// The value instructs the iterator whether iteration will be continued.
 #[derive(Clone, Copy, Debug)]
@@ -29,7 +29,7 @@ use s2n_quic_core::{
         version_negotiation::ProtectedVersionNegotiation,
         zero_rtt::ProtectedZeroRtt,
     },
-    path::MaxMtu,
+    path::{migration, MaxMtu},
     query,
     time::{Timer, Timestamp},
 };
@@ -95,6 +95,15 @@ impl connection::Trait for TestConnection {
         self.interests.accept = false;
     }
 
+    fn mark_server_name_reported(&mut self) {
+        self.interests.server_name = false;
+    }
+
+    fn take_pending_token(&mut self) -> Option<(ServerName, Vec<u8>)> {
+        self.interests.new_token = false;
+        None
+    }
+
     fn on_new_connection_id(
         &mut self,
         _connection_id_format: &mut <Self::Config as endpoint::Config>::ConnectionIdFormat,
@@ -131,6 +140,8 @@ impl connection::Trait for TestConnection {
     fn on_wakeup(
         &mut self,
         _timestamp: Timestamp,
+        _congestion_controller_endpoint: &mut <Self::Config as endpoint::Config>::CongestionControllerEndpoint,
+        _random_generator: &mut <Self::Config as endpoint::Config>::RandomGenerator,
         _subscriber: &mut <Self::Config as endpoint::Config>::EventSubscriber,
         _datagram: &mut <Self::Config as endpoint::Config>::DatagramEndpoint,
     ) -> Result<(), connection::Error> {
@@ -297,6 +308,14 @@ impl connection::Trait for TestConnection {
         todo!()
     }
 
+    fn migrate(&mut self, _local_address: SocketAddress) -> Result<(), migration::Error> {
+        todo!()
+    }
+
+    fn poll_migrate(&mut self, _context: &Context) -> Poll<Result<(), migration::Error>> {
+        todo!()
+    }
+
     fn local_address(&self) -> Result<SocketAddress, connection::Error> {
         todo!()
     }
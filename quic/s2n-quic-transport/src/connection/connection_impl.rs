@@ -54,7 +54,7 @@ use s2n_quic_core::{
         version_negotiation::ProtectedVersionNegotiation,
         zero_rtt::ProtectedZeroRtt,
     },
-    path::{Handle as _, MaxMtu},
+    path::{migration, Handle as _, MaxMtu},
     query,
     recovery::CongestionController,
     stateless_reset::token::Generator as _,
@@ -159,6 +159,8 @@ pub struct ConnectionImpl<Config: endpoint::Config> {
     timers: ConnectionTimers,
     /// Describes whether the connection is known to be accepted by the application
     accept_state: AcceptState,
+    /// Whether the server name requested by the client was reported to the endpoint limits
+    server_name_reported: bool,
     /// The current state of the connection
     state: ConnectionState,
     /// Manage the paths that the connection could use
@@ -625,6 +627,7 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
             local_id_registry: parameters.local_id_registry,
             timers: Default::default(),
             accept_state: AcceptState::Handshaking,
+            server_name_reported: false,
             state: ConnectionState::Handshaking,
             path_manager,
             limits: parameters.limits,
@@ -729,9 +732,13 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
         let close_context = s2n_quic_core::connection::close::Context::new(&remote_address);
         let active_path_id = self.path_manager.active_path_id();
 
-        if let Some((early_connection_close, connection_close)) =
-            s2n_quic_core::connection::error::as_frame(error, close_formatter, &close_context)
-        {
+        let frames = match error {
+            // only servers refuse handshakes which took too long, so clients close silently
+            Error::MaxHandshakeDurationExceeded { .. } if Config::ENDPOINT_TYPE.is_client() => None,
+            _ => s2n_quic_core::connection::error::as_frame(error, close_formatter, &close_context),
+        };
+
+        if let Some((early_connection_close, connection_close)) = frames {
             let mut outcome = transmission::Outcome::default();
             let mut context = transmission_context!(
                 self,
@@ -787,6 +794,9 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
             space.datagram_manager.receiver.on_connection_error(error);
         }
 
+        // Notify the application of any migration that is still in progress
+        self.path_manager.on_connection_error(error);
+
         //= https://www.rfc-editor.org/rfc/rfc9000#section-10.2.1
         //# In the closing state, an endpoint retains only enough information to
         //# generate a packet containing a CONNECTION_CLOSE frame and to identify
@@ -1104,6 +1114,8 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
     fn on_wakeup(
         &mut self,
         timestamp: Timestamp,
+        congestion_controller_endpoint: &mut Config::CongestionControllerEndpoint,
+        random_generator: &mut Config::RandomGenerator,
         subscriber: &mut Config::EventSubscriber,
         datagram: &mut Config::DatagramEndpoint,
     ) -> Result<(), connection::Error> {
@@ -1113,6 +1125,14 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
         // check if crypto progress can be made
         self.update_crypto_state(timestamp, subscriber, datagram)?;
 
+        // create the path for a migration the application requested
+        self.path_manager.on_local_migration_requested(
+            congestion_controller_endpoint,
+            self.limits.initial_round_trip_time(),
+            random_generator,
+            &mut self.event_context.publisher(timestamp, subscriber),
+        );
+
         // return an error if the application set one
         self.error?;
 
@@ -1774,6 +1794,18 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
         self.accept_state = AcceptState::Active;
     }
 
+    fn mark_server_name_reported(&mut self) {
+        debug_assert!(
+            self.space_manager.server_name.is_some(),
+            "mark_server_name_reported() should only be called once the server name is known"
+        );
+        self.server_name_reported = true;
+    }
+
+    fn take_pending_token(&mut self) -> Option<(ServerName, Vec<u8>)> {
+        self.space_manager.take_pending_token()
+    }
+
     fn interests(&self) -> ConnectionInterests {
         use crate::connection::finalization::Provider as _;
         use timer::Provider as _;
@@ -1791,6 +1823,16 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
 
                 interests.transmission = self.can_transmit(constraint);
 
+                // Servers report the server name so the endpoint limits can decide whether the
+                // connection continues
+                interests.server_name = Config::ENDPOINT_TYPE.is_server()
+                    && !self.server_name_reported
+                    && self.space_manager.server_name.is_some();
+
+                // Clients move tokens issued by the server into the endpoint's token store
+                interests.new_token =
+                    Config::ENDPOINT_TYPE.is_client() && self.space_manager.has_pending_token();
+
                 interests.new_connection_id =
                     // Only issue new Connection Ids to the peer when we know they won't be used
                     // for Initial or Handshake packets.
@@ -1959,6 +2001,24 @@ impl<Config: endpoint::Config> connection::Trait for ConnectionImpl<Config> {
         Ok(())
     }
 
+    fn migrate(&mut self, local_address: SocketAddress) -> Result<(), migration::Error> {
+        self.error?;
+
+        let handshake_confirmed = self.space_manager.is_handshake_confirmed();
+        self.path_manager
+            .request_local_migration(local_address.into(), handshake_confirmed)?;
+
+        self.wakeup_handle.wakeup();
+
+        Ok(())
+    }
+
+    fn poll_migrate(&mut self, context: &Context) -> Poll<Result<(), migration::Error>> {
+        self.error?;
+
+        self.path_manager.poll_local_migration(context)
+    }
+
     fn local_address(&self) -> Result<SocketAddress, connection::Error> {
         Ok(*self.path_manager.active_path().handle.local_address())
     }
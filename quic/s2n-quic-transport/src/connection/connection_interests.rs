@@ -17,6 +17,12 @@ pub struct ConnectionInterests {
     /// Is `true` if a `Connection` completed the handshake and should be transferred
     /// to the application via an accept call.
     pub accept: bool,
+    /// Is `true` if a `Connection` received the server name requested by the client and
+    /// should report it to the endpoint limits.
+    pub server_name: bool,
+    /// Is `true` if a `Connection` received an address validation token from the server which
+    /// should be moved into the endpoint's token store.
+    pub new_token: bool,
     /// Is `true` if a `Connection` wants to send data
     pub transmission: bool,
     /// Is `true` if a `Connection` needs a new connection id
@@ -44,6 +50,8 @@ impl ConnectionInterests {
             finalization: self.finalization && other.finalization,
             closing: self.closing && other.closing,
             accept: self.accept || other.accept,
+            server_name: self.server_name || other.server_name,
+            new_token: self.new_token || other.new_token,
             transmission: self.transmission || other.transmission,
             new_connection_id: self.new_connection_id || other.new_connection_id,
             ack: self.ack || other.ack,
@@ -84,6 +92,8 @@ mod tests {
         let a = ConnectionInterests {
             transmission: false,
             accept: true,
+            server_name: true,
+            new_token: true,
             finalization: true,
             closing: true,
             new_connection_id: false,
@@ -95,6 +105,8 @@ mod tests {
         let b = ConnectionInterests {
             transmission: true,
             accept: false,
+            server_name: false,
+            new_token: false,
             finalization: false,
             closing: false,
             new_connection_id: true,
@@ -106,6 +118,8 @@ mod tests {
         let c = ConnectionInterests {
             transmission: false,
             accept: false,
+            server_name: false,
+            new_token: false,
             finalization: true,
             closing: true,
             new_connection_id: false,
@@ -117,6 +131,8 @@ mod tests {
             ConnectionInterests {
                 transmission: true,
                 accept: true,
+                server_name: true,
+                new_token: true,
                 finalization: false,
                 closing: false,
                 new_connection_id: true,
@@ -130,6 +146,8 @@ mod tests {
             ConnectionInterests {
                 transmission: false,
                 accept: true,
+                server_name: true,
+                new_token: true,
                 finalization: true,
                 closing: true,
                 new_connection_id: false,
@@ -143,6 +161,8 @@ mod tests {
             ConnectionInterests {
                 transmission: true,
                 accept: false,
+                server_name: false,
+                new_token: false,
                 finalization: false,
                 closing: false,
                 new_connection_id: true,
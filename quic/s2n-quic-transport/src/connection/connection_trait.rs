@@ -32,7 +32,7 @@ use s2n_quic_core::{
         zero_rtt::ProtectedZeroRtt,
         ProtectedPacket,
     },
-    path::{Handle as _, MaxMtu},
+    path::{migration, Handle as _, MaxMtu},
     query,
     time::Timestamp,
 };
@@ -72,6 +72,16 @@ pub trait ConnectionTrait: 'static + Send + Sized {
     /// no longer be signalled.
     fn mark_as_accepted(&mut self);
 
+    /// Marks a connection which advertised it received the server name requested by the client
+    /// (via [`ConnectionInterests`]) as having reported it to the endpoint limits. After this
+    /// call the `server_name` interest should no longer be signalled.
+    fn mark_server_name_reported(&mut self);
+
+    /// Takes the address validation token which the client received from the server
+    /// (signalled via the `new_token` interest in [`ConnectionInterests`]) along with the name of
+    /// the server, so it can be moved into the endpoint's token store.
+    fn take_pending_token(&mut self) -> Option<(ServerName, Vec<u8>)>;
+
     /// Generates and registers new connection IDs using the given `ConnectionIdFormat` and
     /// `StatelessResetTokenGenerator`
     fn on_new_connection_id(
@@ -108,6 +118,8 @@ pub trait ConnectionTrait: 'static + Send + Sized {
     fn on_wakeup(
         &mut self,
         timestamp: Timestamp,
+        congestion_controller_endpoint: &mut <Self::Config as endpoint::Config>::CongestionControllerEndpoint,
+        random_generator: &mut <Self::Config as endpoint::Config>::RandomGenerator,
         subscriber: &mut <Self::Config as endpoint::Config>::EventSubscriber,
         datagram: &mut <Self::Config as endpoint::Config>::DatagramEndpoint,
     ) -> Result<(), connection::Error>;
@@ -476,6 +488,10 @@ pub trait ConnectionTrait: 'static + Send + Sized {
 
     fn keep_alive(&mut self, enabled: bool) -> Result<(), connection::Error>;
 
+    fn migrate(&mut self, local_address: SocketAddress) -> Result<(), migration::Error>;
+
+    fn poll_migrate(&mut self, context: &Context) -> Poll<Result<(), migration::Error>>;
+
     fn local_address(&self) -> Result<SocketAddress, connection::Error>;
 
     fn remote_address(&self) -> Result<SocketAddress, connection::Error>;
@@ -27,7 +27,9 @@ pub(crate) mod peer_id_registry;
 pub(crate) mod transmission;
 
 pub(crate) use api_provider::{ConnectionApi, ConnectionApiProvider};
-pub(crate) use connection_container::{ConnectionContainer, ConnectionContainerIterationResult};
+pub(crate) use connection_container::{
+    ConnectionContainer, ConnectionContainerIterationResult, LifecycleEvent,
+};
 pub(crate) use connection_id_mapper::ConnectionIdMapper;
 pub(crate) use connection_interests::ConnectionInterests;
 pub(crate) use connection_timers::ConnectionTimers;
@@ -591,6 +591,18 @@ impl PeerIdRegistry {
         self.consume_new_id_inner()
     }
 
+    /// Retires a peer_id that is no longer used by any path
+    pub fn retire_id(&mut self, peer_id: &connection::PeerId) {
+        for id_info in self.registered_ids.iter_mut() {
+            if id_info.id == *peer_id && matches!(id_info.status, InUse) {
+                id_info.status = PendingRetirement;
+                self.transmission_interest.clear();
+            }
+        }
+
+        self.check_consistency();
+    }
+
     // Validate that the ACTIVE_CONNECTION_ID_LIMIT has not been exceeded
     fn check_active_connection_id_limit(
         &self,
@@ -476,6 +476,39 @@ pub fn consume_new_id_should_error_if_no_ids_are_available() {
     assert_eq!(None, reg.consume_new_id_inner());
 }
 
+//= https://www.rfc-editor.org/rfc/rfc9000#section-5.1.2
+//= type=test
+//# Endpoints SHOULD retire connection IDs when
+//# they are no longer actively using either the local or destination
+//# address for which the connection ID was used.
+#[test]
+pub fn retire_id_should_only_retire_in_use_ids() {
+    let id_1 = id(b"id01");
+    let mut random_generator = random::testing::Generator(123);
+    let mut mapper = ConnectionIdMapper::new(&mut random_generator, endpoint::Type::Server);
+    let mut reg = mapper.create_server_peer_id_registry(
+        InternalConnectionIdGenerator::new().generate_id(),
+        id_1,
+        false,
+    );
+
+    let id_2 = id(b"id02");
+    assert!(reg.on_new_connection_id(&id_2, 1, 0, &TEST_TOKEN_2).is_ok());
+
+    // id_2 has not been consumed yet
+    reg.retire_id(&id_2);
+    assert_eq!(New, reg.registered_ids[1].status);
+
+    assert_eq!(InUse, reg.registered_ids[0].status);
+    reg.retire_id(&id_1);
+    assert_eq!(PendingRetirement, reg.registered_ids[0].status);
+    assert!(!reg.is_active(&id_1));
+    assert_eq!(
+        transmission::Interest::NewData,
+        reg.get_transmission_interest()
+    );
+}
+
 #[test]
 fn error_conversion() {
     //= https://www.rfc-editor.org/rfc/rfc9000#section-19.15
@@ -0,0 +1,122 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Measures the rate of connection attempts received by the endpoint over a sliding window
+
+use core::time::Duration;
+use s2n_quic_core::time::Timestamp;
+
+/// The number of buckets the window is split into
+///
+/// Attempts expire from the window one bucket at a time, and the current bucket is still being
+/// filled, so the rate is measured over the most recent 7/8ths to all of the window.
+const BUCKETS: usize = 8;
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Bucket {
+    /// The index of the bucket-length interval since `start` the count is for
+    epoch: u64,
+    count: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct AttemptRate {
+    buckets: [Bucket; BUCKETS],
+    /// The time of the first attempt, which the bucket epochs are relative to
+    start: Option<Timestamp>,
+    window: Duration,
+}
+
+impl AttemptRate {
+    /// Records a connection attempt, returning the number of attempts per second over the window,
+    /// including this one
+    pub fn on_attempt(&mut self, now: Timestamp, window: Duration) -> f32 {
+        // the limiter chooses the window so start over if it changes
+        if self.window != window || self.start.is_none() {
+            *self = Self {
+                start: Some(now),
+                window,
+                ..Default::default()
+            };
+        }
+
+        let bucket_len = (window.as_nanos() / BUCKETS as u128).max(1);
+        let start = self.start.expect("start is set above");
+        let epoch = (now.saturating_duration_since(start).as_nanos() / bucket_len) as u64;
+
+        let bucket = &mut self.buckets[epoch as usize % BUCKETS];
+        if bucket.epoch != epoch {
+            *bucket = Bucket { epoch, count: 0 };
+        }
+        bucket.count = bucket.count.saturating_add(1);
+
+        let count: u32 = self
+            .buckets
+            .iter()
+            .filter(|bucket| bucket.epoch + BUCKETS as u64 > epoch)
+            .map(|bucket| bucket.count)
+            .sum();
+
+        let window = window.as_secs_f32();
+        if window > 0.0 {
+            count as f32 / window
+        } else {
+            count as f32
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s2n_quic_core::time::{testing::Clock, Clock as _};
+
+    const WINDOW: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn steady_rate_test() {
+        let mut clock = Clock::default();
+        let mut rate = AttemptRate::default();
+
+        // 100 attempts per second
+        let mut last = 0.0;
+        for _ in 0..500 {
+            clock.inc_by(Duration::from_millis(10));
+            last = rate.on_attempt(clock.get_time(), WINDOW);
+        }
+
+        // the current bucket is only partially filled
+        assert!((87.0..=101.0).contains(&last), "{last}");
+    }
+
+    #[test]
+    fn burst_expires_test() {
+        let mut clock = Clock::default();
+        let mut rate = AttemptRate::default();
+
+        for count in 1..=50 {
+            assert_eq!(rate.on_attempt(clock.get_time(), WINDOW), count as f32);
+        }
+
+        // the burst ages out of the window
+        clock.inc_by(WINDOW * 2);
+        assert_eq!(rate.on_attempt(clock.get_time(), WINDOW), 1.0);
+    }
+
+    #[test]
+    fn window_test() {
+        let mut clock = Clock::default();
+        let mut rate = AttemptRate::default();
+
+        // the rate is scaled to attempts per second
+        let window = Duration::from_millis(100);
+        for _ in 0..10 {
+            rate.on_attempt(clock.get_time(), window);
+        }
+        assert_eq!(rate.on_attempt(clock.get_time(), window), 110.0);
+
+        // changing the window starts the measurement over
+        clock.inc_by(Duration::from_millis(1));
+        assert_eq!(rate.on_attempt(clock.get_time(), WINDOW), 1.0);
+    }
+}
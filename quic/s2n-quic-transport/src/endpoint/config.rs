@@ -26,6 +26,8 @@ pub trait Config: 'static + Send + Sized + core::fmt::Debug {
     type RandomGenerator: random::Generator;
     /// The validation token format
     type TokenFormat: s2n_quic_core::token::Format;
+    /// The store for address validation tokens issued by servers
+    type TokenStore: s2n_quic_core::token::Store;
     /// The endpoint limits
     type EndpointLimits: endpoint::Limiter;
     /// The connection limits
@@ -75,6 +77,9 @@ pub struct Context<'a, Cfg: Config> {
     /// Token generator / validator
     pub token: &'a mut Cfg::TokenFormat,
 
+    /// Stores the tokens issued by servers for future connections
+    pub token_store: &'a mut Cfg::TokenStore,
+
     /// The connection limits
     pub connection_limits: &'a mut Cfg::ConnectionLimits,
 
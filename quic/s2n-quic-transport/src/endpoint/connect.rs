@@ -53,6 +53,12 @@ impl Connect {
     }
 
     /// Specifies the server name to use for the connection
+    ///
+    /// The name is used to verify the server's certificate, and takes precedence over the remote
+    /// address, which is used otherwise. DNS host names are also sent in the TLS `server_name`
+    /// extension. IP literals, such as `"127.0.0.1"`, are only used for verification since
+    /// RFC 6066 doesn't allow them in the extension. Attempts with any other name, such as one
+    /// with a trailing dot, fail without contacting the server.
     #[must_use]
     pub fn with_server_name<Name: Into<ServerName>>(self, server_name: Name) -> Self {
         Self {
@@ -60,6 +66,21 @@ impl Connect {
             ..self
         }
     }
+
+    /// Returns the server name used for the connection, if one was specified
+    ///
+    /// ```rust
+    /// # use s2n_quic_core::inet::SocketAddressV4;
+    /// # use s2n_quic_transport::endpoint::connect::Connect;
+    /// let connect = Connect::new(SocketAddressV4::new([192, 0, 2, 1], 443));
+    /// assert!(connect.server_name().is_none());
+    ///
+    /// let connect = connect.with_server_name("example.com");
+    /// assert_eq!(connect.server_name().map(|name| &**name), Some("example.com"));
+    /// ```
+    pub fn server_name(&self) -> Option<&ServerName> {
+        self.server_name.as_ref()
+    }
 }
 
 /// Make it easy for applications to create a connection attempt without importing the `Connect` struct
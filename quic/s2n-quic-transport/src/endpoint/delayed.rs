@@ -0,0 +1,234 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Holds responses to connection attempts until the delay requested by the endpoint limits has
+//! elapsed
+
+use alloc::collections::VecDeque;
+use hashbrown::{hash_map::Entry as MapEntry, HashMap};
+use s2n_quic_core::{connection::PeerId, inet::SocketAddress, path, time::Timestamp};
+
+/// When to send a delayed response, and how many responses can be held at once
+#[derive(Clone, Copy, Debug)]
+pub struct Schedule {
+    pub transmit_at: Timestamp,
+    /// The source connection ID of the attempt, used to detect retransmitted Initial packets
+    pub source_connection_id: PeerId,
+    pub max_len: usize,
+}
+
+#[derive(Debug)]
+struct Entry<Path: path::Handle, T> {
+    transmit_at: Timestamp,
+    path: Path,
+    source_connection_id: PeerId,
+    value: T,
+}
+
+/// Identifies the connection attempt a response is held for
+///
+/// Only the remote address is included, so a retransmitted Initial packet is detected even if it
+/// arrives on another local address.
+type Key = (SocketAddress, PeerId);
+
+#[inline]
+fn key<Path: path::Handle>(path: &Path, source_connection_id: &PeerId) -> Key {
+    (path.remote_address().0.unmap(), *source_connection_id)
+}
+
+/// A queue of delayed transmissions, ordered by the time they should be sent
+#[derive(Debug)]
+pub struct Queue<Path: path::Handle, T> {
+    entries: VecDeque<Entry<Path, T>>,
+    /// The number of held responses for each connection attempt, so lookups don't need to scan
+    /// the queue
+    index: HashMap<Key, usize>,
+}
+
+impl<Path: path::Handle, T> Default for Queue<Path, T> {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::new(),
+            index: HashMap::new(),
+        }
+    }
+}
+
+impl<Path: path::Handle, T> Queue<Path, T> {
+    /// Returns `true` if a response to the given connection attempt is being held
+    #[inline]
+    pub fn contains(&self, path: &Path, source_connection_id: &PeerId) -> bool {
+        self.index.contains_key(&key(path, source_connection_id))
+    }
+
+    /// Holds the value until the scheduled time
+    ///
+    /// Returns `false` without queueing the value if `max_len` values are already being held.
+    pub fn push(&mut self, schedule: Schedule, path: Path, value: T) -> bool {
+        let Schedule {
+            transmit_at,
+            source_connection_id,
+            max_len,
+        } = schedule;
+
+        if self.entries.len() >= max_len {
+            return false;
+        }
+
+        // delays are usually the same for every attempt so search from the back
+        let index = self
+            .entries
+            .iter()
+            .rposition(|entry| entry.transmit_at <= transmit_at)
+            .map_or(0, |index| index + 1);
+
+        *self
+            .index
+            .entry(key(&path, &source_connection_id))
+            .or_default() += 1;
+
+        self.entries.insert(
+            index,
+            Entry {
+                transmit_at,
+                path,
+                source_connection_id,
+                value,
+            },
+        );
+
+        true
+    }
+
+    /// Returns the time the next value should be sent
+    #[inline]
+    pub fn next_expiration(&self) -> Option<Timestamp> {
+        self.entries.front().map(|entry| entry.transmit_at)
+    }
+
+    /// Removes the next value if its delay has elapsed
+    #[inline]
+    pub fn pop_expired(&mut self, now: Timestamp) -> Option<T> {
+        if self.next_expiration()? > now {
+            return None;
+        }
+
+        let entry = self.entries.pop_front()?;
+
+        if let MapEntry::Occupied(mut count) = self
+            .index
+            .entry(key(&entry.path, &entry.source_connection_id))
+        {
+            *count.get_mut() -= 1;
+            if *count.get() == 0 {
+                count.remove();
+            }
+        }
+
+        Some(entry.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::time::Duration;
+    use s2n_quic_core::{
+        inet::SocketAddressV4,
+        path::RemoteAddress,
+        time::{testing::Clock, Clock as _},
+    };
+
+    fn schedule(transmit_at: Timestamp, id: u8, max_len: usize) -> Schedule {
+        Schedule {
+            transmit_at,
+            source_connection_id: peer_id(id),
+            max_len,
+        }
+    }
+
+    fn peer_id(id: u8) -> PeerId {
+        PeerId::try_from_bytes(&[id; 8]).unwrap()
+    }
+
+    #[test]
+    fn ordering_test() {
+        let clock = Clock::default();
+        let now = clock.get_time();
+        let path: RemoteAddress = SocketAddressV4::new([127, 0, 0, 1], 443).into();
+        let mut queue = Queue::default();
+
+        assert!(queue.push(schedule(now + Duration::from_secs(2), 1, 10), path, 1));
+        assert!(queue.push(schedule(now + Duration::from_secs(1), 2, 10), path, 2));
+        assert!(queue.push(schedule(now + Duration::from_secs(2), 3, 10), path, 3));
+
+        assert_eq!(queue.next_expiration(), Some(now + Duration::from_secs(1)));
+        assert_eq!(queue.pop_expired(now), None);
+        assert_eq!(queue.pop_expired(now + Duration::from_secs(1)), Some(2));
+        assert_eq!(queue.pop_expired(now + Duration::from_secs(1)), None);
+
+        // values with the same delay are sent in the order they were queued
+        let later = now + Duration::from_secs(2);
+        assert_eq!(queue.pop_expired(later), Some(1));
+        assert_eq!(queue.pop_expired(later), Some(3));
+        assert_eq!(queue.pop_expired(later), None);
+        assert_eq!(queue.next_expiration(), None);
+    }
+
+    #[test]
+    fn contains_test() {
+        let clock = Clock::default();
+        let now = clock.get_time();
+        let path: RemoteAddress = SocketAddressV4::new([127, 0, 0, 1], 443).into();
+        let other_path: RemoteAddress = SocketAddressV4::new([127, 0, 0, 1], 444).into();
+        let mut queue = Queue::default();
+
+        assert!(queue.push(schedule(now, 1, 10), path, ()));
+        assert!(queue.contains(&path, &peer_id(1)));
+        assert!(!queue.contains(&path, &peer_id(2)));
+        assert!(!queue.contains(&other_path, &peer_id(1)));
+
+        assert_eq!(queue.pop_expired(now), Some(()));
+        assert!(!queue.contains(&path, &peer_id(1)));
+    }
+
+    #[test]
+    fn max_len_test() {
+        let clock = Clock::default();
+        let now = clock.get_time();
+        let path: RemoteAddress = SocketAddressV4::new([127, 0, 0, 1], 443).into();
+        let mut queue = Queue::default();
+
+        for id in 0..3 {
+            assert!(queue.push(schedule(now, id, 3), path, id));
+        }
+        assert!(!queue.push(schedule(now, 3, 3), path, 3));
+        assert_eq!(queue.entries.len(), 3);
+        assert!(!queue.contains(&path, &peer_id(3)));
+    }
+
+    #[test]
+    fn index_test() {
+        let clock = Clock::default();
+        let now = clock.get_time();
+        let path: RemoteAddress = SocketAddressV4::new([127, 0, 0, 1], 443).into();
+        let mapped_path: RemoteAddress = SocketAddressV4::new([127, 0, 0, 1], 443)
+            .to_ipv6_mapped()
+            .into();
+        let mut queue = Queue::default();
+
+        // the same attempt can be held more than once
+        assert!(queue.push(schedule(now, 1, 10), path, 1));
+        assert!(queue.push(schedule(now + Duration::from_secs(1), 1, 10), path, 2));
+
+        // IPv4-mapped addresses refer to the same peer
+        assert!(queue.contains(&mapped_path, &peer_id(1)));
+
+        assert_eq!(queue.pop_expired(now), Some(1));
+        assert!(queue.contains(&path, &peer_id(1)));
+
+        assert_eq!(queue.pop_expired(now + Duration::from_secs(1)), Some(2));
+        assert!(!queue.contains(&path, &peer_id(1)));
+        assert!(queue.index.is_empty());
+    }
+}
@@ -8,21 +8,26 @@ use crate::{
         limits::{ConnectionInfo as LimitsInfo, Limiter as _},
         Trait as _,
     },
-    endpoint,
+    endpoint::{self, version},
     recovery::congestion_controller::{self, Endpoint as _},
-    space::PacketSpaceManager,
+    space::{new_token, PacketSpaceManager},
 };
 use core::convert::TryInto;
 use s2n_codec::DecoderBufferMut;
 use s2n_quic_core::{
     crypto::{tls, tls::Endpoint as TLSEndpoint, CryptoSuite, InitialKey},
     datagram::{Endpoint, PreConnectionInfo},
+    endpoint::{limits::ConnectionOpenInfo, Limiter as _},
     event::{self, supervisor, ConnectionPublisher, IntoEvent, Subscriber as _},
     inet::{datagram, DatagramInfo},
     packet::initial::ProtectedInitial,
     path::Handle as _,
     stateless_reset::token::Generator as _,
-    transport::{self, parameters::ServerTransportParameters},
+    token::{self, Format as _},
+    transport::{
+        self,
+        parameters::{Grease, ServerTransportParameters, WithGrease},
+    },
 };
 
 impl<Config: endpoint::Config> endpoint::Endpoint<Config> {
@@ -156,12 +161,26 @@ impl<Config: endpoint::Config> endpoint::Endpoint<Config> {
 
         let mut transport_parameters = ServerTransportParameters::default();
 
-        let limits = self
+        let mut limits = self
             .config
             .context()
             .connection_limits
             .on_connection(&LimitsInfo::new(&remote_address));
 
+        // the endpoint limits can shorten the handshake of every connection
+        if let Some(max_handshake_duration) = self
+            .config
+            .context()
+            .endpoint_limits
+            .max_handshake_duration()
+        {
+            if max_handshake_duration < limits.max_handshake_duration() {
+                limits = limits
+                    .with_max_handshake_duration(max_handshake_duration)
+                    .expect("durations are always valid");
+            }
+        }
+
         transport_parameters.load_limits(&limits);
 
         //= https://www.rfc-editor.org/rfc/rfc9000#section-7.3
@@ -218,9 +237,36 @@ impl<Config: endpoint::Config> endpoint::Endpoint<Config> {
             .try_into()
             .expect("Failed to convert max_datagram_frame_size");
 
-        let tls_session = endpoint_context
-            .tls
-            .new_server_session(&transport_parameters);
+        transport_parameters.version_information = Some(version::version_information(
+            packet.version,
+            limits.grease(),
+            endpoint_context.random_generator,
+        ));
+
+        let grease = limits
+            .grease()
+            .then(|| Grease::new(endpoint_context.random_generator));
+
+        let tls_session = endpoint_context.tls.new_server_session(&WithGrease {
+            parameters: &transport_parameters,
+            grease,
+        });
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
+        //# A server MAY provide clients with an address validation token during
+        //# one connection that can be used on a subsequent connection.
+        let new_token = {
+            let mut context = token::Context::new(
+                &remote_address,
+                &source_connection_id,
+                endpoint_context.random_generator,
+            );
+            let mut token = vec![0; <Config::TokenFormat as token::Format>::TOKEN_LEN];
+            endpoint_context
+                .token
+                .generate_new_token(&mut context, &initial_connection_id, &mut token)
+                .map(|_| token)
+        };
 
         let path_info = congestion_controller::PathInfo::new(&remote_address);
         let congestion_controller = endpoint_context
@@ -260,6 +306,7 @@ impl<Config: endpoint::Config> endpoint::Endpoint<Config> {
             initial_key,
             initial_header_key,
             datagram.timestamp,
+            new_token::Manager::server(new_token),
             &mut publisher,
         );
 
@@ -401,6 +448,14 @@ impl<Config: endpoint::Config> endpoint::Endpoint<Config> {
         self.connections
             .insert_server_connection(connection, internal_connection_id);
 
+        // The connection is reported right away, rather than with the other lifecycle events, so
+        // the limiter counts it before the next attempt in the batch
+        let info = ConnectionOpenInfo::new(&remote_address, datagram.timestamp.into_event());
+        self.config
+            .context()
+            .endpoint_limits
+            .on_connection_opened(&info);
+
         Ok(())
     }
 }
@@ -8,28 +8,32 @@ use crate::{
         self,
         limits::{ConnectionInfo as LimitsInfo, Limiter as _},
         ConnectionContainer, ConnectionContainerIterationResult, ConnectionIdMapper,
-        InternalConnectionId, InternalConnectionIdGenerator, Trait as _,
+        InternalConnectionId, InternalConnectionIdGenerator, LifecycleEvent, Trait as _,
     },
     endpoint,
     endpoint::close::CloseHandle,
     recovery::congestion_controller::{self, Endpoint as _},
-    space::PacketSpaceManager,
+    space::{new_token, PacketSpaceManager},
     wakeup_queue::WakeupQueue,
 };
-use alloc::collections::VecDeque;
+use alloc::{collections::VecDeque, string::ToString, vec::Vec};
 use core::{
     convert::TryInto,
     task::{self, Poll},
 };
 use s2n_codec::{DecoderBuffer, DecoderBufferMut};
 use s2n_quic_core::{
+    application::ServerName,
     connection::{
         id::{ConnectionInfo, Generator},
         InitialId, LocalId, PeerId,
     },
     crypto::{tls, tls::Endpoint as _, CryptoSuite, InitialKey},
     datagram::{Endpoint as DatagramEndpoint, PreConnectionInfo},
-    endpoint::{limits::Outcome, Limiter as _},
+    endpoint::{
+        limits::{AttemptId, Outcome, TokenStatus},
+        Limiter as _,
+    },
     event::{
         self, supervisor, ConnectionPublisher, EndpointPublisher as _, IntoEvent, Subscriber as _,
     },
@@ -42,18 +46,22 @@ use s2n_quic_core::{
     stateless_reset::token::{Generator as _, LEN as StatelessResetTokenLen},
     time::{Clock, Timestamp},
     token::{self, Format},
-    transport::parameters::ClientTransportParameters,
+    transport::parameters::{ClientTransportParameters, Grease, WithGrease},
 };
 
+mod attempt_rate;
 pub mod close;
 mod config;
 pub mod connect;
+mod delayed;
 pub mod handle;
 mod initial;
 mod packet_buffer;
+mod pending;
+mod refuse;
 mod retry;
 mod stateless_reset;
-mod version;
+pub(crate) mod version;
 
 // exports
 pub use config::{Config, Context};
@@ -83,6 +91,13 @@ pub struct Endpoint<Cfg: Config> {
     dequeued_wakeups: VecDeque<InternalConnectionId>,
     version_negotiator: version::Negotiator<Cfg>,
     retry_dispatch: retry::Dispatch<Cfg::PathHandle>,
+    refuse_dispatch: refuse::Dispatch<Cfg::PathHandle>,
+    /// Connection attempts waiting for the endpoint limits to decide on their outcome
+    pending_attempts: pending::Queue<Cfg::PathHandle>,
+    /// The rate of connection attempts reported to the endpoint limits
+    attempt_rate: attempt_rate::AttemptRate,
+    /// The id assigned to the next connection attempt passed to the endpoint limits
+    next_attempt_id: AttemptId,
     stateless_reset_dispatch: stateless_reset::Dispatch<Cfg::PathHandle>,
     close_packet_buffer: packet_buffer::Buffer,
     /// The largest maximum transmission unit (MTU) that can be sent on a path
@@ -113,6 +128,10 @@ impl<Cfg: Config> s2n_quic_core::endpoint::Endpoint for Endpoint<Cfg> {
 
             self.receive_datagram(&mut header, payload, timestamp)
         });
+
+        if let Some(timestamp) = now {
+            self.on_lifecycle_events(timestamp);
+        }
     }
 
     fn transmit<Tx, C>(&mut self, queue: &mut Tx, clock: &C)
@@ -163,10 +182,15 @@ impl<Cfg: Config> s2n_quic_core::endpoint::Endpoint for Endpoint<Cfg> {
                 endpoint_context.event_subscriber,
             );
             self.version_negotiator.on_transmit(queue, &mut publisher);
-            self.retry_dispatch.on_transmit(queue, &mut publisher);
+            self.retry_dispatch
+                .on_transmit(queue, &mut publisher, timestamp);
+            self.refuse_dispatch
+                .on_transmit(queue, &mut publisher, timestamp);
             self.stateless_reset_dispatch
                 .on_transmit(queue, &mut publisher);
         }
+
+        self.on_lifecycle_events(timestamp);
     }
 
     fn poll_wakeups<C: Clock>(
@@ -211,6 +235,8 @@ impl<Cfg: Config> s2n_quic_core::endpoint::Endpoint for Endpoint<Cfg> {
 
                 if let Err(error) = conn.on_wakeup(
                     timestamp,
+                    endpoint_context.congestion_controller,
+                    endpoint_context.random_generator,
                     endpoint_context.event_subscriber,
                     endpoint_context.datagram,
                 ) {
@@ -235,11 +261,9 @@ impl<Cfg: Config> s2n_quic_core::endpoint::Endpoint for Endpoint<Cfg> {
                         wakeup_count += 1;
 
                         let time = clock.get_time();
-                        if let Err(err) = self.create_client_connection(request, time) {
-                            // TODO report that the connection was not successfully created
-                            // TODO emit event
-                            dbg!(err);
-                        }
+                        // any error is returned to the application through the request, so
+                        // there's nothing left to do with it here
+                        let _ = self.create_client_connection(request, time);
                     }
                     Poll::Ready(None) => {
                         // the client handle has been dropped so break from loop
@@ -249,6 +273,16 @@ impl<Cfg: Config> s2n_quic_core::endpoint::Endpoint for Endpoint<Cfg> {
             }
         }
 
+        if !self.pending_attempts.is_empty() {
+            let timestamp = *now.get_or_insert_with(|| clock.get_time());
+            wakeup_count += self.poll_pending_attempts(cx, timestamp);
+        }
+
+        if self.connections.has_lifecycle_events() {
+            let timestamp = now.unwrap_or_else(|| clock.get_time());
+            self.on_lifecycle_events(timestamp);
+        }
+
         if wakeup_count > 0 {
             Poll::Ready(Ok(wakeup_count))
         } else {
@@ -258,7 +292,15 @@ impl<Cfg: Config> s2n_quic_core::endpoint::Endpoint for Endpoint<Cfg> {
 
     #[inline]
     fn timeout(&self) -> Option<Timestamp> {
-        self.connections.next_expiration()
+        [
+            self.connections.next_expiration(),
+            self.retry_dispatch.next_expiration(),
+            self.refuse_dispatch.next_expiration(),
+            self.pending_attempts.next_expiration(),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
     }
 
     #[inline]
@@ -312,6 +354,10 @@ impl<Cfg: Config> Endpoint<Cfg> {
             dequeued_wakeups: VecDeque::new(),
             version_negotiator: version::Negotiator::default(),
             retry_dispatch: retry::Dispatch::default(),
+            refuse_dispatch: refuse::Dispatch::default(),
+            pending_attempts: pending::Queue::default(),
+            attempt_rate: Default::default(),
+            next_attempt_id: AttemptId::default(),
             stateless_reset_dispatch: stateless_reset::Dispatch::default(),
             close_packet_buffer: Default::default(),
             max_mtu: Default::default(),
@@ -325,24 +371,168 @@ impl<Cfg: Config> Endpoint<Cfg> {
         &mut self,
         header: &datagram::Header<Cfg::PathHandle>,
         packet: &ProtectedInitial,
+        coalesced: &[u8],
         payload_len: usize,
+        token_status: TokenStatus,
+        retry_token_dcid: Option<connection::InitialId>,
+        early_data_offered: bool,
         timestamp: Timestamp,
     ) -> Option<()> {
         if !self.connections.can_accept() {
             return None;
         }
 
+        // make sure the limiter knows about any connections that closed before this attempt
+        self.on_lifecycle_events(timestamp);
+
         let remote_address = header.path.remote_address();
+        let local_address = header.path.local_address();
+        let source_connection_id = PeerId::try_from_bytes(packet.source_connection_id())?;
+        let original_destination_connection_id =
+            connection::UnboundedId::try_from_bytes(packet.destination_connection_id())?;
 
+        // Retransmitted Initial packets are dropped while the response to the attempt is delayed
+        // or the endpoint limits are still deciding on its outcome
+        if self
+            .retry_dispatch
+            .is_delayed(&header.path, &source_connection_id)
+            || self
+                .refuse_dispatch
+                .is_delayed(&header.path, &source_connection_id)
+            || self
+                .pending_attempts
+                .contains(&header.path, &source_connection_id)
+        {
+            let context = self.config.context();
+            let mut publisher = event::EndpointPublisherSubscriber::new(
+                event::builder::EndpointMeta {
+                    endpoint_type: Cfg::ENDPOINT_TYPE,
+                    timestamp,
+                },
+                None,
+                context.event_subscriber,
+            );
+            publisher.on_endpoint_datagram_dropped(event::builder::EndpointDatagramDropped {
+                len: payload_len as u16,
+                reason: event::builder::DatagramDropReason::RejectedConnectionAttempt {
+                    reason: None,
+                },
+            });
+            return None;
+        }
+
+        let inflight_handshakes = self.connections.handshake_connections();
+        let connection_count = self.connections.len();
+        let attempt_rate_window = self.config.context().endpoint_limits.attempt_rate_window();
+        let recent_attempt_rate = self.attempt_rate.on_attempt(timestamp, attempt_rate_window);
+        let attempt_id = self.next_attempt_id.next();
         let attempt = s2n_quic_core::endpoint::limits::ConnectionAttempt::new(
-            self.connections.handshake_connections(),
-            self.connections.len(),
+            inflight_handshakes,
+            connection_count,
             &remote_address,
+            &local_address,
+            original_destination_connection_id.as_bytes(),
             timestamp.into_event(),
-        );
+        )
+        .with_attempt_id(attempt_id)
+        .with_token_status(token_status)
+        .with_datagram_len(payload_len)
+        .with_quic_version(packet.version)
+        .with_recent_attempt_rate(recent_attempt_rate)
+        .with_early_data_offered(early_data_offered);
+
+        // the endpoint task's waker isn't available here so pending attempts are polled again in
+        // `poll_wakeups`
+        let waker = pending::noop_waker();
+        let mut cx = task::Context::from_waker(&waker);
+        let context = self.config.context();
+        let outcome = match context
+            .endpoint_limits
+            .poll_on_connection_attempt(&mut cx, &attempt)
+        {
+            Poll::Ready(outcome) => outcome,
+            Poll::Pending => {
+                let max_pending_attempts = context.endpoint_limits.max_pending_attempts();
+
+                // packets coalesced with the Initial packet are kept so they can be processed once
+                // the attempt is allowed
+                let mut bytes = Vec::with_capacity(packet.payload.len() + coalesced.len());
+                bytes.extend_from_slice(packet.payload.as_bytes());
+                bytes.extend_from_slice(coalesced);
+
+                let attempt = pending::Attempt {
+                    attempt_id,
+                    header: *header,
+                    source_connection_id,
+                    original_destination_connection_id,
+                    token_status,
+                    retry_token_dcid,
+                    packet: bytes,
+                    payload_len,
+                    quic_version: packet.version,
+                    recent_attempt_rate,
+                    early_data_offered,
+                    inflight_handshakes,
+                    connection_count,
+                    arrived_at: timestamp,
+                    expires_at: timestamp + context.endpoint_limits.pending_attempt_timeout(),
+                };
+
+                if !self.pending_attempts.push(attempt, max_pending_attempts) {
+                    context
+                        .endpoint_limits
+                        .on_connection_attempt_cancelled(attempt_id);
+
+                    let mut publisher = event::EndpointPublisherSubscriber::new(
+                        event::builder::EndpointMeta {
+                            endpoint_type: Cfg::ENDPOINT_TYPE,
+                            timestamp,
+                        },
+                        None,
+                        context.event_subscriber,
+                    );
+                    publisher.on_endpoint_datagram_dropped(
+                        event::builder::EndpointDatagramDropped {
+                            len: payload_len as u16,
+                            reason: event::builder::DatagramDropReason::RejectedConnectionAttempt {
+                                reason: None,
+                            },
+                        },
+                    );
+                }
+
+                return None;
+            }
+        };
+
+        self.on_connection_attempt_outcome(
+            header,
+            packet,
+            payload_len,
+            source_connection_id,
+            token_status,
+            inflight_handshakes,
+            outcome,
+            timestamp,
+        )
+    }
 
+    /// Applies the outcome the endpoint limits decided on for a connection attempt
+    #[allow(clippy::too_many_arguments)]
+    fn on_connection_attempt_outcome(
+        &mut self,
+        header: &datagram::Header<Cfg::PathHandle>,
+        packet: &ProtectedInitial,
+        payload_len: usize,
+        source_connection_id: PeerId,
+        token_status: TokenStatus,
+        inflight_handshakes: usize,
+        outcome: Outcome,
+        timestamp: Timestamp,
+    ) -> Option<()> {
+        let remote_address = header.path.remote_address();
         let context = self.config.context();
-        let outcome = context.endpoint_limits.on_connection_attempt(&attempt);
+        let max_delayed_responses = context.endpoint_limits.max_delayed_responses();
         let mut publisher = event::EndpointPublisherSubscriber::new(
             event::builder::EndpointMeta {
                 endpoint_type: Cfg::ENDPOINT_TYPE,
@@ -352,9 +542,56 @@ impl<Cfg: Config> Endpoint<Cfg> {
             context.event_subscriber,
         );
 
+        // report the decision as the limiter made it, before the endpoint overrides it below
+        publisher.on_endpoint_connection_attempt_decided(
+            event::builder::EndpointConnectionAttemptDecided {
+                outcome: attempt_outcome_event(&outcome),
+                inflight_handshakes,
+            },
+        );
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.2
+        //# In response to processing an Initial packet containing a token that
+        //# was provided in a Retry packet, a server cannot send another Retry
+        //# packet; it can only refuse the connection or permit it to proceed.
+        let outcome = if outcome.is_retry() && token_status.is_valid() {
+            Outcome::allow()
+        } else {
+            outcome
+        };
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
+        //# If the token is invalid, then the
+        //# server SHOULD proceed as if the client did not have a validated
+        //# address, including potentially sending a Retry packet.
+        //
+        // Tokens from NEW_TOKEN frames can expire or be presented from another address, so
+        // those attempts are handled like attempts without a token.
+        let invalid_retry_token = matches!(token_status, TokenStatus::RetryToken { valid: false });
+
+        // Attempts with invalid Retry tokens are discarded unless the limiter chose to refuse them
+        if invalid_retry_token && (outcome.is_allow() || outcome.is_retry()) {
+            //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.2
+            //= type=TODO
+            //= tracking-issue=344
+            //# Instead, the
+            //# server SHOULD immediately close (Section 10.2) the connection with an
+            //# INVALID_TOKEN error.
+            publisher.on_endpoint_datagram_dropped(event::builder::EndpointDatagramDropped {
+                len: payload_len as u16,
+                reason: event::builder::DatagramDropReason::InvalidRetryToken,
+            });
+
+            //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
+            //# Servers MAY
+            //# discard any Initial packet that does not carry the expected token.
+            return None;
+        }
+
         match outcome {
+            // 0-RTT isn't supported yet, so any early data offered with the attempt is rejected
             Outcome::Allow { .. } => Some(()),
-            Outcome::Retry { .. } => {
+            Outcome::Retry { delay, .. } => {
                 //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.2
                 //# A server can also use a Retry packet to defer the state and
                 //# processing costs of connection establishment.  Requiring the server
@@ -368,45 +605,120 @@ impl<Cfg: Config> Endpoint<Cfg> {
 
                 let local_connection_id = context.connection_id_format.generate(&connection_info);
 
-                self.retry_dispatch.queue::<
-                    _,
-                    <<<Cfg as Config>::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::RetryKey,
-                >(
-                    header.path,
-                    packet,
-                    local_connection_id,
-                    context.random_generator,
-                    context.token
-                );
+                if delay.is_zero() {
+                    self.retry_dispatch.queue::<
+                        _,
+                        <<<Cfg as Config>::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::RetryKey,
+                    >(
+                        header.path,
+                        packet,
+                        local_connection_id,
+                        context.random_generator,
+                        context.token
+                    );
+                } else {
+                    let queued = self.retry_dispatch.queue_delayed::<
+                        _,
+                        <<<Cfg as Config>::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::RetryKey,
+                    >(
+                        header.path,
+                        packet,
+                        local_connection_id,
+                        context.random_generator,
+                        context.token,
+                        delayed::Schedule {
+                            transmit_at: timestamp + delay,
+                            source_connection_id,
+                            max_len: max_delayed_responses,
+                        },
+                    );
+
+                    if !queued {
+                        publisher.on_endpoint_response_dropped(
+                            event::builder::EndpointResponseDropped {
+                                outcome: event::builder::ConnectionAttemptOutcome::Retry,
+                            },
+                        );
+                    }
+                }
 
                 None
             }
-            Outcome::Close { .. } => {
+            Outcome::Close { error, delay, .. } => {
                 //= https://www.rfc-editor.org/rfc/rfc9000#section-5.2.2
-                //= type=TODO
-                //= tracking-issue=270
                 //# If a server refuses to accept a new connection, it SHOULD send an
                 //# Initial packet containing a CONNECTION_CLOSE frame with error code
                 //# CONNECTION_REFUSED.
+                // The error code defaults to CONNECTION_REFUSED but can be changed by the limiter
 
                 publisher.on_endpoint_datagram_dropped(event::builder::EndpointDatagramDropped {
                     len: payload_len as u16,
-                    reason: event::builder::DatagramDropReason::RejectedConnectionAttempt,
+                    reason: event::builder::DatagramDropReason::RejectedConnectionAttempt {
+                        reason: Some(error.reason).filter(|reason| !reason.is_empty()),
+                    },
                 });
 
+                //= https://www.rfc-editor.org/rfc/rfc9000#section-14.1
+                //# A server MUST discard an Initial packet that is carried in a UDP
+                //# datagram with a payload that is smaller than the smallest allowed
+                //# maximum datagram size of 1200 bytes.
+                if payload_len < 1200 {
+                    return None;
+                }
+
+                let connection_info = ConnectionInfo::new(&remote_address);
+                let local_connection_id = context.connection_id_format.generate(&connection_info);
+
+                let queued = if delay.is_zero() {
+                    self.refuse_dispatch.queue::<
+                        <<<Cfg as Config>::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::InitialKey,
+                    >(
+                        header.path,
+                        packet,
+                        local_connection_id,
+                        error,
+                    )
+                } else {
+                    self.refuse_dispatch.queue_delayed::<
+                        <<<Cfg as Config>::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::InitialKey,
+                    >(
+                        header.path,
+                        packet,
+                        local_connection_id,
+                        error,
+                        delayed::Schedule {
+                            transmit_at: timestamp + delay,
+                            source_connection_id,
+                            max_len: max_delayed_responses,
+                        },
+                    )
+                };
+
+                if !queued {
+                    publisher.on_endpoint_response_dropped(
+                        event::builder::EndpointResponseDropped {
+                            outcome: event::builder::ConnectionAttemptOutcome::Close,
+                        },
+                    );
+                }
+
                 None
             }
-            Outcome::Drop { .. } => {
+            Outcome::Drop { reason, .. } => {
                 publisher.on_endpoint_datagram_dropped(event::builder::EndpointDatagramDropped {
                     len: payload_len as u16,
-                    reason: event::builder::DatagramDropReason::RejectedConnectionAttempt,
+                    reason: event::builder::DatagramDropReason::RejectedConnectionAttempt {
+                        reason,
+                    },
                 });
                 None
             }
             _ => {
                 publisher.on_endpoint_datagram_dropped(event::builder::EndpointDatagramDropped {
                     len: payload_len as u16,
-                    reason: event::builder::DatagramDropReason::RejectedConnectionAttempt,
+                    reason: event::builder::DatagramDropReason::RejectedConnectionAttempt {
+                        reason: None,
+                    },
                 });
                 // Outcome is non_exhaustive so drop on things we don't understand
                 None
@@ -414,6 +726,199 @@ impl<Cfg: Config> Endpoint<Cfg> {
         }
     }
 
+    /// Polls the connection attempts waiting for the endpoint limits, returning the number of
+    /// attempts which completed
+    fn poll_pending_attempts(&mut self, cx: &mut task::Context<'_>, timestamp: Timestamp) -> usize {
+        let endpoint_limits = self.config.context().endpoint_limits;
+
+        self.pending_attempts.poll(|attempt| {
+            let remote_address = attempt.header.path.remote_address();
+            let local_address = attempt.header.path.local_address();
+            let info = s2n_quic_core::endpoint::limits::ConnectionAttempt::new(
+                attempt.inflight_handshakes,
+                attempt.connection_count,
+                &remote_address,
+                &local_address,
+                attempt.original_destination_connection_id.as_bytes(),
+                attempt.arrived_at.into_event(),
+            )
+            .with_attempt_id(attempt.attempt_id)
+            .with_token_status(attempt.token_status)
+            .with_datagram_len(attempt.payload_len)
+            .with_quic_version(attempt.quic_version)
+            .with_recent_attempt_rate(attempt.recent_attempt_rate)
+            .with_early_data_offered(attempt.early_data_offered);
+            endpoint_limits.poll_on_connection_attempt(cx, &info)
+        });
+
+        let mut count = 0;
+        while let Some((attempt, outcome)) = self.pending_attempts.pop_ready() {
+            count += 1;
+            self.on_pending_attempt_ready(attempt, outcome, timestamp);
+        }
+
+        count
+    }
+
+    /// Resumes processing a connection attempt once the endpoint limits decided on its outcome
+    fn on_pending_attempt_ready(
+        &mut self,
+        attempt: pending::Attempt<Cfg::PathHandle>,
+        outcome: Outcome,
+        timestamp: Timestamp,
+    ) {
+        if !self.connections.can_accept() {
+            return;
+        }
+
+        let pending::Attempt {
+            header,
+            source_connection_id,
+            token_status,
+            retry_token_dcid,
+            packet: mut bytes,
+            payload_len,
+            inflight_handshakes,
+            ..
+        } = attempt;
+
+        let endpoint_context = self.config.context();
+        let remote_address = header.path.remote_address();
+        let connection_info = ConnectionInfo::new(&remote_address);
+        let (packet, remaining) = match ProtectedPacket::decode(
+            DecoderBufferMut::new(&mut bytes),
+            &connection_info,
+            endpoint_context.connection_id_format,
+        ) {
+            Ok((ProtectedPacket::Initial(packet), remaining)) => (packet, remaining),
+            // the packet was already decoded as an Initial packet when the attempt arrived
+            _ => return,
+        };
+
+        if self
+            .on_connection_attempt_outcome(
+                &header,
+                &packet,
+                payload_len,
+                source_connection_id,
+                token_status,
+                inflight_handshakes,
+                outcome,
+                timestamp,
+            )
+            .is_none()
+        {
+            return;
+        }
+
+        let destination_connection_id =
+            match connection::LocalId::try_from_bytes(packet.destination_connection_id()) {
+                Some(connection_id) => connection_id,
+                None => return,
+            };
+
+        let datagram = DatagramInfo {
+            timestamp,
+            payload_len,
+            ecn: header.ecn,
+            destination_connection_id,
+            destination_connection_id_classification: connection::id::Classification::Initial,
+            source_connection_id: Some(source_connection_id),
+        };
+
+        if let Err(err) =
+            self.handle_initial_packet(&header, &datagram, packet, remaining, retry_token_dcid)
+        {
+            let mut publisher = event::EndpointPublisherSubscriber::new(
+                event::builder::EndpointMeta {
+                    endpoint_type: Cfg::ENDPOINT_TYPE,
+                    timestamp,
+                },
+                None,
+                self.config.context().event_subscriber,
+            );
+            publisher.on_endpoint_connection_attempt_failed(
+                event::builder::EndpointConnectionAttemptFailed { error: err },
+            );
+        }
+    }
+
+    /// Notifies the endpoint limits of connections that received a server name, completed their
+    /// handshake or closed and moves tokens issued by servers into the token store
+    fn on_lifecycle_events(&mut self, timestamp: Timestamp) {
+        use s2n_quic_core::endpoint::limits::{
+            ConnectionCloseInfo, HandshakeInfo, ServerNameAttempt,
+        };
+
+        let event_timestamp = timestamp.into_event();
+
+        while let Some(event) = self.connections.pop_lifecycle_event() {
+            let endpoint_context = self.config.context();
+            let endpoint_limits = endpoint_context.endpoint_limits;
+
+            match event {
+                LifecycleEvent::ServerName {
+                    internal_connection_id,
+                    server_name,
+                    remote_address,
+                    local_address,
+                } => {
+                    let info = ServerNameAttempt::new(
+                        &server_name,
+                        &remote_address,
+                        &local_address,
+                        self.connections.handshake_connections(),
+                        self.connections.len(),
+                        event_timestamp,
+                    );
+                    let outcome = endpoint_limits.on_server_name(&info);
+
+                    debug_assert!(
+                        outcome.is_allow() || outcome.is_close(),
+                        "limiters can only allow or close connections once the server name is known"
+                    );
+
+                    if let Outcome::Close { error, .. } = outcome {
+                        let close_packet_buffer = &mut self.close_packet_buffer;
+                        self.connections
+                            .with_connection(internal_connection_id, |conn| {
+                                conn.close(
+                                    error.into(),
+                                    endpoint_context.connection_close_formatter,
+                                    close_packet_buffer,
+                                    timestamp,
+                                    endpoint_context.event_subscriber,
+                                    endpoint_context.packet_interceptor,
+                                );
+                            });
+                    }
+                }
+                LifecycleEvent::NewToken { server_name, token } => {
+                    endpoint_context
+                        .token_store
+                        .on_new_token(&server_name, &token);
+                }
+                LifecycleEvent::HandshakeCompleted { remote_address } => {
+                    let info = HandshakeInfo::new(&remote_address, event_timestamp);
+                    endpoint_limits.on_handshake_completed(&info);
+                }
+                LifecycleEvent::Closed {
+                    remote_address,
+                    handshake_completed,
+                    error,
+                } => {
+                    let info = ConnectionCloseInfo::new(
+                        &remote_address,
+                        handshake_completed,
+                        error,
+                        event_timestamp,
+                    );
+                    endpoint_limits.on_connection_closed(&info);
+                }
+            }
+        }
+    }
+
     /// Ingests a single datagram
     fn receive_datagram(
         &mut self,
@@ -498,6 +1003,53 @@ impl<Cfg: Config> Endpoint<Cfg> {
             endpoint_context.event_subscriber,
         );
 
+        // Give the endpoint limits a chance to drop attempts with unsupported versions before
+        // a Version Negotiation packet is sent in response
+        if let ProtectedPacket::Initial(initial) = &packet {
+            if Cfg::ENDPOINT_TYPE.is_server() && !version::is_supported(initial.version) {
+                let local_address = header.path.local_address();
+                let inflight_handshakes = self.connections.handshake_connections();
+                let recent_attempt_rate = self.attempt_rate.on_attempt(
+                    timestamp,
+                    endpoint_context.endpoint_limits.attempt_rate_window(),
+                );
+                let attempt = s2n_quic_core::endpoint::limits::ConnectionAttempt::new(
+                    inflight_handshakes,
+                    self.connections.len(),
+                    &remote_address,
+                    &local_address,
+                    initial.destination_connection_id(),
+                    timestamp.into_event(),
+                )
+                .with_attempt_id(self.next_attempt_id.next())
+                .with_datagram_len(payload_len)
+                .with_quic_version(initial.version)
+                .with_recent_attempt_rate(recent_attempt_rate);
+
+                let outcome = endpoint_context
+                    .endpoint_limits
+                    .on_connection_attempt(&attempt);
+                publisher.on_endpoint_connection_attempt_decided(
+                    event::builder::EndpointConnectionAttemptDecided {
+                        outcome: attempt_outcome_event(&outcome),
+                        inflight_handshakes,
+                    },
+                );
+
+                if let Outcome::Drop { reason, .. } = outcome {
+                    publisher.on_endpoint_datagram_dropped(
+                        event::builder::EndpointDatagramDropped {
+                            len: payload_len as u16,
+                            reason: event::builder::DatagramDropReason::RejectedConnectionAttempt {
+                                reason,
+                            },
+                        },
+                    );
+                    return;
+                }
+            }
+        }
+
         // Ensure the version is supported. This check occurs before the destination
         // connection ID is parsed since future versions of QUIC could have different
         // length requirements for connection IDs.
@@ -667,70 +1219,66 @@ impl<Cfg: Config> Endpoint<Cfg> {
                 //# the peer uses a connection ID chosen by the endpoint and the
                 //# connection ID contains at least 64 bits of entropy
 
-                //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.2
-                //# In response to processing an Initial packet containing a token that
-                //# was provided in a Retry packet, a server cannot send another Retry
-                //# packet; it can only refuse the connection or permit it to proceed.
-                let retry_token_dcid = if !packet.token().is_empty() {
+                let (token_status, retry_token_dcid) = if !packet.token().is_empty() {
                     let mut context = token::Context::new(
                         &remote_address,
                         &source_connection_id,
                         endpoint_context.random_generator,
                     );
 
-                    let outcome = endpoint_context
-                        .token
-                        .validate_token(&mut context, packet.token());
-
-                    if outcome.is_none() {
-                        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
-                        //= type=TODO
-                        //= tracking-issue=344
-                        //# If the token is invalid, then the
-                        //# server SHOULD proceed as if the client did not have a validated
-                        //# address, including potentially sending a Retry packet.
-
-                        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.2
-                        //= type=TODO
-                        //= tracking-issue=344
-                        //# Instead, the
-                        //# server SHOULD immediately close (Section 10.2) the connection with an
-                        //# INVALID_TOKEN error.
-                        publisher.on_endpoint_datagram_dropped(
-                            event::builder::EndpointDatagramDropped {
-                                len: payload_len as u16,
-                                reason: event::builder::DatagramDropReason::InvalidRetryToken,
-                            },
-                        );
-
-                        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
-                        //# Servers MAY
-                        //# discard any Initial packet that does not carry the expected token.
-                        return;
-                    }
-
                     //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
                     //# If the validation succeeds, the server SHOULD then allow
                     //# the handshake to proceed.
-                    outcome
-                } else {
-                    //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.2
-                    //# Upon receiving the client's Initial packet, the server can request
-                    //# address validation by sending a Retry packet (Section 17.2.5)
-                    //# containing a token.
-                    if self
-                        .connection_allowed(header, &packet, payload_len, timestamp)
-                        .is_none()
-                    {
-                        //= https://www.rfc-editor.org/rfc/rfc9000#section-17.2.5.1
-                        //# A server MUST NOT send more than one Retry
-                        //# packet in response to a single UDP datagram.
-                        return;
+                    match endpoint_context.token.token_source(packet.token()) {
+                        token::Source::RetryPacket => {
+                            let retry_token_dcid = endpoint_context
+                                .token
+                                .validate_token(&mut context, packet.token());
+                            let valid = retry_token_dcid.is_some();
+                            (TokenStatus::RetryToken { valid }, retry_token_dcid)
+                        }
+                        // Tokens from NEW_TOKEN frames prove the address but aren't bound to the
+                        // connection IDs of the current attempt
+                        token::Source::NewTokenFrame => {
+                            let valid = endpoint_context
+                                .token
+                                .validate_new_token(&mut context, packet.token());
+                            (TokenStatus::NewToken { valid }, None)
+                        }
                     }
-
-                    None
+                } else {
+                    (TokenStatus::None, None)
                 };
 
+                // 0-RTT packets coalesced with the Initial packet indicate the client is
+                // offering early data
+                let early_data_offered = remaining
+                    .peek_byte(0)
+                    .map_or(false, s2n_quic_core::packet::zero_rtt::is_zero_rtt);
+
+                //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.2
+                //# Upon receiving the client's Initial packet, the server can request
+                //# address validation by sending a Retry packet (Section 17.2.5)
+                //# containing a token.
+                if self
+                    .connection_allowed(
+                        header,
+                        &packet,
+                        remaining.peek().into_less_safe_slice(),
+                        payload_len,
+                        token_status,
+                        retry_token_dcid,
+                        early_data_offered,
+                        timestamp,
+                    )
+                    .is_none()
+                {
+                    //= https://www.rfc-editor.org/rfc/rfc9000#section-17.2.5.1
+                    //# A server MUST NOT send more than one Retry
+                    //# packet in response to a single UDP datagram.
+                    return;
+                }
+
                 if let Err(err) = self.handle_initial_packet(
                     header,
                     &datagram,
@@ -923,6 +1471,28 @@ impl<Cfg: Config> Endpoint<Cfg> {
                     panic!("Generated connection ID was already in use");
                 }
             });
+
+        // drop any connection attempts the endpoint limits took too long to decide on
+        while let Some(attempt) = self.pending_attempts.pop_expired(timestamp) {
+            endpoint_context
+                .endpoint_limits
+                .on_connection_attempt_cancelled(attempt.attempt_id);
+
+            let mut publisher = event::EndpointPublisherSubscriber::new(
+                event::builder::EndpointMeta {
+                    endpoint_type: Cfg::ENDPOINT_TYPE,
+                    timestamp,
+                },
+                None,
+                endpoint_context.event_subscriber,
+            );
+            publisher.on_endpoint_datagram_dropped(event::builder::EndpointDatagramDropped {
+                len: attempt.payload_len as u16,
+                reason: event::builder::DatagramDropReason::RejectedConnectionAttempt {
+                    reason: None,
+                },
+            });
+        }
     }
 
     fn create_client_connection(
@@ -939,6 +1509,22 @@ impl<Cfg: Config> Endpoint<Cfg> {
             sender,
         } = request;
 
+        // without a server name, the server's certificate is verified against the address being
+        // connected to
+        let hostname =
+            hostname.unwrap_or_else(|| ServerName::from(remote_address.ip().unmap().to_string()));
+
+        // IP literals aren't sent in the `server_name` extension but are still used to verify the
+        // server's certificate
+        if !hostname.is_valid_host_name() && !hostname.is_ip_address() {
+            let error = connection::Error::immediate_close(
+                "the server name must be a valid DNS host name or an IP address",
+            );
+            // the application may have stopped waiting for the connection so ignore the result
+            let _ = sender.send(Err(error));
+            return Err(error);
+        }
+
         let internal_connection_id = self.connection_id_generator.generate_id();
         let local_connection_id = self
             .config
@@ -1047,6 +1633,12 @@ impl<Cfg: Config> Endpoint<Cfg> {
             .on_connection(&LimitsInfo::new(&remote_address));
         transport_parameters.load_limits(&limits);
 
+        transport_parameters.version_information = Some(version::version_information(
+            quic_version,
+            limits.grease(),
+            endpoint_context.random_generator,
+        ));
+
         transport_parameters.max_datagram_frame_size = endpoint_context
             .datagram
             .max_datagram_frame_size(&PreConnectionInfo::new())
@@ -1059,6 +1651,10 @@ impl<Cfg: Config> Endpoint<Cfg> {
         .try_into()
         .unwrap();
 
+        let grease = limits
+            .grease()
+            .then(|| Grease::new(endpoint_context.random_generator));
+
         //= https://www.rfc-editor.org/rfc/rfc9000#section-7.2
         //# The Destination Connection ID field from the first Initial packet
         //# sent by a client is used to determine packet protection keys for
@@ -1070,23 +1666,39 @@ impl<Cfg: Config> Endpoint<Cfg> {
             <<Cfg::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::InitialKey::new_client(
                 original_destination_connection_id.as_bytes(),
             );
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
+        //# In a future connection, the client includes this
+        //# token in Initial packets to provide address validation.
+        let token = endpoint_context.token_store.take_token(&hostname);
+        let new_token = new_token::Manager::client(hostname.clone());
+
         let tls_session = endpoint_context
             .tls
-            // TODO should SNI be optional? rustls expects a SNI but other tls providers dont seem
-            // to require this value.
+            // the providers decide whether the name is sent in the `server_name` extension, which
+            // excludes IP literals
             .new_client_session(
-                &transport_parameters,
-                hostname.expect("application should provide a valid server name"),
+                &WithGrease {
+                    parameters: &transport_parameters,
+                    grease,
+                },
+                hostname,
             );
-        let space_manager = PacketSpaceManager::new(
+        let mut space_manager = PacketSpaceManager::new(
             original_destination_connection_id,
             tls_session,
             initial_key,
             initial_header_key,
             timestamp,
+            new_token,
             &mut publisher,
         );
 
+        if let Some(token) = token {
+            if let Some((initial, _)) = space_manager.initial_mut() {
+                initial.set_new_token(token);
+            }
+        }
+
         let wakeup_handle = self
             .wakeup_queue
             .create_wakeup_handle(internal_connection_id);
@@ -1122,6 +1734,17 @@ impl<Cfg: Config> Endpoint<Cfg> {
     }
 }
 
+/// Maps the outcome chosen by the endpoint limits to its event representation
+fn attempt_outcome_event(outcome: &Outcome) -> event::builder::ConnectionAttemptOutcome {
+    match outcome {
+        Outcome::Allow { .. } => event::builder::ConnectionAttemptOutcome::Allow,
+        Outcome::Retry { .. } => event::builder::ConnectionAttemptOutcome::Retry,
+        Outcome::Close { .. } => event::builder::ConnectionAttemptOutcome::Close,
+        // Outcome is non_exhaustive and the endpoint drops anything it doesn't understand
+        _ => event::builder::ConnectionAttemptOutcome::Drop,
+    }
+}
+
 #[cfg(any(test, feature = "testing"))]
 pub mod testing {
     use super::*;
@@ -1142,6 +1765,7 @@ pub mod testing {
         type StatelessResetTokenGenerator = stateless_reset::token::testing::Generator;
         type RandomGenerator = random::testing::Generator;
         type TokenFormat = s2n_quic_core::token::testing::Format;
+        type TokenStore = s2n_quic_core::token::store::Disabled;
         type ConnectionLimits = s2n_quic_core::connection::limits::Limits;
         type StreamManager = crate::stream::DefaultStreamManager;
         type ConnectionCloseFormatter = s2n_quic_core::connection::close::Development;
@@ -1172,6 +1796,7 @@ pub mod testing {
         type StatelessResetTokenGenerator = stateless_reset::token::testing::Generator;
         type RandomGenerator = random::testing::Generator;
         type TokenFormat = s2n_quic_core::token::testing::Format;
+        type TokenStore = s2n_quic_core::token::store::Disabled;
         type ConnectionLimits = s2n_quic_core::connection::limits::Limits;
         type StreamManager = crate::stream::DefaultStreamManager;
         type ConnectionCloseFormatter = s2n_quic_core::connection::close::Development;
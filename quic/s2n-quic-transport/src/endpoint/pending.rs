@@ -0,0 +1,329 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Holds connection attempts while the endpoint limits decide on their outcome
+
+use alloc::{collections::VecDeque, vec::Vec};
+use core::task::{Poll, RawWaker, RawWakerVTable, Waker};
+use hashbrown::{hash_map::Entry, HashMap};
+use s2n_quic_core::{
+    connection::{InitialId, PeerId, UnboundedId},
+    endpoint::limits::{AttemptId, Outcome, TokenStatus},
+    inet::{datagram, SocketAddress},
+    path,
+    time::Timestamp,
+};
+
+/// A connection attempt which is waiting for the endpoint limits to decide on its outcome
+#[derive(Debug)]
+pub struct Attempt<Path: path::Handle> {
+    /// The id the endpoint limits are polled with
+    pub attempt_id: AttemptId,
+    pub header: datagram::Header<Path>,
+    /// The source connection ID of the attempt, used to detect retransmitted Initial packets
+    pub source_connection_id: PeerId,
+    pub original_destination_connection_id: UnboundedId,
+    pub token_status: TokenStatus,
+    /// The original destination connection ID from a valid Retry token
+    pub retry_token_dcid: Option<InitialId>,
+    /// A copy of the protected Initial packet which started the attempt, followed by any packets
+    /// coalesced with it in the same datagram
+    pub packet: Vec<u8>,
+    /// The length of the datagram which carried the packet
+    pub payload_len: usize,
+    pub quic_version: u32,
+    /// The recent connection attempt rate when the attempt arrived
+    pub recent_attempt_rate: f32,
+    /// Whether a 0-RTT packet was coalesced with the Initial packet
+    pub early_data_offered: bool,
+    /// The number of handshakes in progress when the attempt arrived
+    pub inflight_handshakes: usize,
+    /// The number of connections open when the attempt arrived
+    pub connection_count: usize,
+    pub arrived_at: Timestamp,
+    pub expires_at: Timestamp,
+}
+
+/// Identifies a connection attempt
+///
+/// Only the remote address is included, so a retransmitted Initial packet is detected even if it
+/// arrives on another local address.
+type Key = (SocketAddress, PeerId);
+
+#[inline]
+fn key<Path: path::Handle>(path: &Path, source_connection_id: &PeerId) -> Key {
+    (path.remote_address().0.unmap(), *source_connection_id)
+}
+
+/// A queue of connection attempts, ordered by the time they expire
+#[derive(Debug)]
+pub struct Queue<Path: path::Handle> {
+    attempts: VecDeque<Attempt<Path>>,
+    ready: VecDeque<(Attempt<Path>, Outcome)>,
+    /// The number of held attempts for each key, so lookups don't need to scan the queues
+    index: HashMap<Key, usize>,
+}
+
+impl<Path: path::Handle> Default for Queue<Path> {
+    fn default() -> Self {
+        Self {
+            attempts: VecDeque::new(),
+            ready: VecDeque::new(),
+            index: HashMap::new(),
+        }
+    }
+}
+
+impl<Path: path::Handle> Queue<Path> {
+    /// Returns `true` if the given connection attempt is waiting for its outcome
+    #[inline]
+    pub fn contains(&self, path: &Path, source_connection_id: &PeerId) -> bool {
+        self.index.contains_key(&key(path, source_connection_id))
+    }
+
+    /// Returns `true` if there are no attempts waiting for their outcome
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.attempts.is_empty() && self.ready.is_empty()
+    }
+
+    /// Holds the attempt until its outcome is ready
+    ///
+    /// Returns `false` without queueing the attempt if `max_len` attempts are already held.
+    pub fn push(&mut self, attempt: Attempt<Path>, max_len: usize) -> bool {
+        if self.attempts.len() >= max_len {
+            return false;
+        }
+
+        *self
+            .index
+            .entry(key(&attempt.header.path, &attempt.source_connection_id))
+            .or_default() += 1;
+
+        // the timeout is usually the same for every attempt so search from the back
+        let index = self
+            .attempts
+            .iter()
+            .rposition(|held| held.expires_at <= attempt.expires_at)
+            .map_or(0, |index| index + 1);
+        self.attempts.insert(index, attempt);
+
+        true
+    }
+
+    /// Polls each attempt for its outcome
+    ///
+    /// Attempts with an outcome are moved to the ready queue, in the order they expire.
+    pub fn poll<F: FnMut(&Attempt<Path>) -> Poll<Outcome>>(&mut self, mut poll_attempt: F) {
+        // rebuild the queue in a single pass rather than removing ready attempts from the middle
+        for _ in 0..self.attempts.len() {
+            let attempt = self
+                .attempts
+                .pop_front()
+                .expect("the queue holds an attempt for each iteration");
+
+            if let Poll::Ready(outcome) = poll_attempt(&attempt) {
+                self.ready.push_back((attempt, outcome));
+            } else {
+                self.attempts.push_back(attempt);
+            }
+        }
+    }
+
+    /// Removes the next attempt with an outcome
+    #[inline]
+    pub fn pop_ready(&mut self) -> Option<(Attempt<Path>, Outcome)> {
+        let (attempt, outcome) = self.ready.pop_front()?;
+        self.remove_key(&attempt);
+        Some((attempt, outcome))
+    }
+
+    /// Returns the time the next attempt times out
+    #[inline]
+    pub fn next_expiration(&self) -> Option<Timestamp> {
+        self.attempts.front().map(|attempt| attempt.expires_at)
+    }
+
+    /// Removes the next attempt which has waited longer than its timeout
+    #[inline]
+    pub fn pop_expired(&mut self, now: Timestamp) -> Option<Attempt<Path>> {
+        if self.next_expiration()? > now {
+            return None;
+        }
+
+        let attempt = self.attempts.pop_front()?;
+        self.remove_key(&attempt);
+        Some(attempt)
+    }
+
+    #[inline]
+    fn remove_key(&mut self, attempt: &Attempt<Path>) {
+        if let Entry::Occupied(mut count) = self
+            .index
+            .entry(key(&attempt.header.path, &attempt.source_connection_id))
+        {
+            *count.get_mut() -= 1;
+            if *count.get() == 0 {
+                count.remove();
+            }
+        }
+    }
+}
+
+/// Returns a waker which does nothing when woken
+///
+/// Attempts are first polled while receiving datagrams, where the endpoint task's waker isn't
+/// available. Pending attempts are polled again with the real waker the next time the endpoint
+/// polls for wakeups.
+pub fn noop_waker() -> Waker {
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(core::ptr::null(), &VTABLE)
+    }
+
+    fn noop(_: *const ()) {}
+
+    // Safety: the vtable functions don't access the data pointer
+    unsafe { Waker::from_raw(RawWaker::new(core::ptr::null(), &VTABLE)) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::time::Duration;
+    use s2n_quic_core::{
+        inet::{ExplicitCongestionNotification, SocketAddressV4},
+        path::RemoteAddress,
+        time::{testing::Clock, Clock as _},
+    };
+
+    fn path() -> RemoteAddress {
+        SocketAddressV4::new([127, 0, 0, 1], 443).into()
+    }
+
+    fn attempt(id: u8, arrived_at: Timestamp) -> Attempt<RemoteAddress> {
+        Attempt {
+            attempt_id: AttemptId::new(id as u64),
+            header: datagram::Header {
+                path: path(),
+                ecn: ExplicitCongestionNotification::default(),
+            },
+            source_connection_id: peer_id(id),
+            original_destination_connection_id: UnboundedId::try_from_bytes(&[id; 8]).unwrap(),
+            token_status: TokenStatus::None,
+            retry_token_dcid: None,
+            packet: Vec::new(),
+            payload_len: 1200,
+            quic_version: 1,
+            recent_attempt_rate: 0.0,
+            early_data_offered: false,
+            inflight_handshakes: 0,
+            connection_count: 0,
+            arrived_at,
+            expires_at: arrived_at + Duration::from_secs(1),
+        }
+    }
+
+    fn peer_id(id: u8) -> PeerId {
+        PeerId::try_from_bytes(&[id; 8]).unwrap()
+    }
+
+    #[test]
+    fn poll_test() {
+        let clock = Clock::default();
+        let now = clock.get_time();
+        let path: RemoteAddress = SocketAddressV4::new([127, 0, 0, 1], 443).into();
+        let mut queue = Queue::default();
+
+        for id in 0..3 {
+            assert!(queue.push(attempt(id, now), 10));
+        }
+
+        // only the middle attempt is ready
+        queue.poll(|attempt| {
+            if attempt.source_connection_id == peer_id(1) {
+                Poll::Ready(Outcome::allow())
+            } else {
+                Poll::Pending
+            }
+        });
+
+        // the attempt is still held until the endpoint takes the outcome
+        assert!(queue.contains(&path, &peer_id(1)));
+        let (ready, outcome) = queue.pop_ready().unwrap();
+        assert_eq!(ready.source_connection_id, peer_id(1));
+        assert!(outcome.is_allow());
+        assert!(queue.pop_ready().is_none());
+        assert!(!queue.contains(&path, &peer_id(1)));
+
+        queue.poll(|_| Poll::Ready(Outcome::drop()));
+        assert_eq!(
+            queue.pop_ready().unwrap().0.source_connection_id,
+            peer_id(0)
+        );
+        assert_eq!(
+            queue.pop_ready().unwrap().0.source_connection_id,
+            peer_id(2)
+        );
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn expiration_test() {
+        let clock = Clock::default();
+        let now = clock.get_time();
+        let mut queue = Queue::default();
+
+        assert!(queue.push(attempt(1, now + Duration::from_secs(1)), 10));
+        assert!(queue.push(attempt(2, now), 10));
+
+        assert_eq!(queue.next_expiration(), Some(now + Duration::from_secs(1)));
+        assert!(queue.pop_expired(now).is_none());
+
+        let expired = queue.pop_expired(now + Duration::from_secs(1)).unwrap();
+        assert_eq!(expired.source_connection_id, peer_id(2));
+        assert!(queue.pop_expired(now + Duration::from_secs(1)).is_none());
+        assert_eq!(queue.next_expiration(), Some(now + Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn max_len_test() {
+        let clock = Clock::default();
+        let now = clock.get_time();
+        let mut queue = Queue::default();
+
+        for id in 0..3 {
+            assert!(queue.push(attempt(id, now), 3));
+        }
+        assert!(!queue.push(attempt(3, now), 3));
+        assert_eq!(queue.attempts.len(), 3);
+        assert!(!queue.contains(&path(), &peer_id(3)));
+    }
+
+    #[test]
+    fn index_test() {
+        let clock = Clock::default();
+        let now = clock.get_time();
+        let mapped_path: RemoteAddress = SocketAddressV4::new([127, 0, 0, 1], 443)
+            .to_ipv6_mapped()
+            .into();
+        let mut queue = Queue::default();
+
+        // the same attempt can be held more than once
+        assert!(queue.push(attempt(1, now), 10));
+        assert!(queue.push(attempt(1, now + Duration::from_secs(1)), 10));
+
+        // IPv4-mapped addresses refer to the same peer
+        assert!(queue.contains(&mapped_path, &peer_id(1)));
+
+        assert!(queue.pop_expired(now + Duration::from_secs(1)).is_some());
+        assert!(queue.contains(&path(), &peer_id(1)));
+
+        queue.poll(|_| Poll::Ready(Outcome::allow()));
+        assert!(queue.contains(&path(), &peer_id(1)));
+        assert!(queue.pop_ready().is_some());
+        assert!(!queue.contains(&path(), &peer_id(1)));
+        assert!(queue.index.is_empty());
+    }
+}
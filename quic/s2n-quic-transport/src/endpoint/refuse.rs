@@ -0,0 +1,375 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Sends CONNECTION_CLOSE frames for connection attempts that are refused by the endpoint limits
+
+use crate::endpoint::{self, delayed};
+use alloc::collections::VecDeque;
+use core::ops::Range;
+use s2n_codec::{encoder::scatter, Encoder, EncoderBuffer, EncoderValue};
+use s2n_quic_core::{
+    connection,
+    crypto::{InitialHeaderKey, InitialKey},
+    event,
+    frame::{ConnectionClose, Padding},
+    inet::ExplicitCongestionNotification,
+    io::tx,
+    packet::{
+        encoding::{PacketEncoder, PacketPayloadEncoder},
+        initial::{Initial, ProtectedInitial},
+        number::PacketNumberSpace,
+    },
+    path::{self, MINIMUM_MTU},
+    time, transport,
+    varint::VarInt,
+};
+
+/// The maximum length of the reason phrase included in the CONNECTION_CLOSE frame
+///
+/// This leaves room for the long header with maximum-length connection IDs, the rest of the
+/// frame and the AEAD tag, so the packet always fits in the minimum MTU.
+const MAX_REASON_LEN: usize = MINIMUM_MTU as usize - 128;
+
+#[derive(Debug)]
+pub struct Dispatch<Path: path::Handle> {
+    transmissions: VecDeque<Transmission<Path>>,
+    delayed: delayed::Queue<Path, Transmission<Path>>,
+    max_peers: usize,
+}
+
+impl<Path: path::Handle> Default for Dispatch<Path> {
+    fn default() -> Self {
+        Self::new(endpoint::DEFAULT_MAX_PEERS)
+    }
+}
+
+impl<Path: path::Handle> Dispatch<Path> {
+    pub fn new(max_peers: usize) -> Self {
+        Self {
+            transmissions: VecDeque::with_capacity(max_peers),
+            delayed: Default::default(),
+            max_peers,
+        }
+    }
+
+    /// Queues a CONNECTION_CLOSE packet to be sent on the next transmission
+    ///
+    /// Returns `false` if the packet was dropped because packets for `max_peers` peers are
+    /// already waiting to be sent.
+    pub fn queue<K: InitialKey>(
+        &mut self,
+        path_handle: Path,
+        packet: &ProtectedInitial,
+        local_connection_id: connection::LocalId,
+        error: transport::Error,
+    ) -> bool
+    where
+        K::HeaderKey: InitialHeaderKey,
+    {
+        if self.transmissions.len() >= self.max_peers {
+            return false;
+        }
+
+        if let Some(transmission) =
+            Transmission::new::<K>(path_handle, packet, local_connection_id, error)
+        {
+            self.transmissions.push_back(transmission);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Queues a CONNECTION_CLOSE packet to be sent at the scheduled time
+    ///
+    /// Returns `false` if the packet was dropped because too many packets are already delayed.
+    pub fn queue_delayed<K: InitialKey>(
+        &mut self,
+        path_handle: Path,
+        packet: &ProtectedInitial,
+        local_connection_id: connection::LocalId,
+        error: transport::Error,
+        schedule: delayed::Schedule,
+    ) -> bool
+    where
+        K::HeaderKey: InitialHeaderKey,
+    {
+        if let Some(transmission) =
+            Transmission::new::<K>(path_handle, packet, local_connection_id, error)
+        {
+            self.delayed.push(schedule, path_handle, transmission)
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` if a CONNECTION_CLOSE packet is being delayed for the connection attempt
+    pub fn is_delayed(
+        &self,
+        path_handle: &Path,
+        source_connection_id: &connection::PeerId,
+    ) -> bool {
+        self.delayed.contains(path_handle, source_connection_id)
+    }
+
+    /// Returns the time the next delayed CONNECTION_CLOSE packet should be sent
+    pub fn next_expiration(&self) -> Option<time::Timestamp> {
+        self.delayed.next_expiration()
+    }
+
+    pub fn on_transmit<Tx: tx::Queue<Handle = Path>, Pub: event::EndpointPublisher>(
+        &mut self,
+        queue: &mut Tx,
+        publisher: &mut Pub,
+        timestamp: time::Timestamp,
+    ) {
+        while let Some(transmission) = self.delayed.pop_expired(timestamp) {
+            self.transmissions.push_back(transmission);
+        }
+
+        while let Some(transmission) = self.transmissions.pop_front() {
+            match queue.push(&transmission) {
+                Ok(tx::Outcome { len, .. }) => {
+                    publisher.on_endpoint_packet_sent(event::builder::EndpointPacketSent {
+                        packet_header: event::builder::PacketHeader::Initial {
+                            number: 0,
+                            version: transmission.version,
+                        },
+                    });
+
+                    publisher.on_endpoint_datagram_sent(event::builder::EndpointDatagramSent {
+                        len: len as u16,
+                        gso_offset: 0,
+                    });
+                }
+                Err(_) => {
+                    self.transmissions.push_front(transmission);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+pub struct Transmission<Path: path::Handle> {
+    path: Path,
+    packet: [u8; MINIMUM_MTU as usize],
+    packet_range: Range<usize>,
+    version: u32,
+}
+
+impl<Path: path::Handle> core::fmt::Debug for Transmission<Path> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Transmission")
+            .field("remote_address", &self.path.remote_address())
+            .field("local_address", &self.path.local_address())
+            .field("packet", &&self.packet[self.packet_range.clone()])
+            .finish()
+    }
+}
+
+impl<Path: path::Handle> Transmission<Path> {
+    pub fn new<K: InitialKey>(
+        path: Path,
+        packet: &ProtectedInitial,
+        local_connection_id: connection::LocalId,
+        error: transport::Error,
+    ) -> Option<Self>
+    where
+        K::HeaderKey: InitialHeaderKey,
+    {
+        //= https://www.rfc-editor.org/rfc/rfc9001#section-5.2
+        //# Initial packets apply the packet protection process, but use a secret
+        //# derived from the Destination Connection ID field from the client's
+        //# first Initial packet.
+        let (key, header_key) = K::new_server(packet.destination_connection_id());
+
+        let mut frame: ConnectionClose = error.into();
+        frame.reason = frame.reason.map(truncate_reason);
+
+        let packet_number = PacketNumberSpace::Initial.new_packet_number(VarInt::from_u8(0));
+
+        let initial = Initial {
+            version: packet.version,
+            destination_connection_id: packet.source_connection_id(),
+            source_connection_id: local_connection_id.as_ref(),
+            token: &[0u8; 0][..],
+            packet_number,
+            payload: Payload { frame },
+        };
+
+        let mut packet_buf = [0u8; MINIMUM_MTU as usize];
+        let (_protected_packet, buffer) = initial
+            .encode_packet(
+                &key,
+                &header_key,
+                packet_number,
+                None,
+                EncoderBuffer::new(&mut packet_buf),
+            )
+            .ok()?;
+        let packet_range = 0..buffer.len();
+
+        Some(Self {
+            path,
+            packet: packet_buf,
+            packet_range,
+            version: packet.version,
+        })
+    }
+}
+
+/// Truncates the reason phrase so the packet fits in the minimum MTU, without splitting any
+/// UTF-8 characters
+fn truncate_reason(reason: &[u8]) -> &[u8] {
+    if reason.len() <= MAX_REASON_LEN {
+        return reason;
+    }
+
+    let mut len = MAX_REASON_LEN;
+    // UTF-8 continuation bytes are of the form 0b10xxxxxx
+    while len > 0 && reason[len] & 0b1100_0000 == 0b1000_0000 {
+        len -= 1;
+    }
+
+    &reason[..len]
+}
+
+/// A payload containing a single CONNECTION_CLOSE frame, padded to the minimum length
+struct Payload<'a> {
+    frame: ConnectionClose<'a>,
+}
+
+impl<'a> PacketPayloadEncoder for Payload<'a> {
+    fn encoding_size_hint<E: Encoder>(&mut self, _encoder: &E, minimum_len: usize) -> usize {
+        self.frame.encoding_size().max(minimum_len)
+    }
+
+    fn encode(
+        &mut self,
+        buffer: &mut scatter::Buffer,
+        minimum_len: usize,
+        _header_len: usize,
+        _tag_len: usize,
+    ) {
+        buffer.encode(&self.frame);
+
+        if let Some(length) = minimum_len.checked_sub(buffer.len()).filter(|len| *len > 0) {
+            buffer.encode(&Padding { length });
+        }
+    }
+}
+
+impl<Path: path::Handle> AsRef<[u8]> for Transmission<Path> {
+    fn as_ref(&self) -> &[u8] {
+        &self.packet[self.packet_range.clone()]
+    }
+}
+
+impl<Path: path::Handle> tx::Message for &Transmission<Path> {
+    type Handle = Path;
+
+    #[inline]
+    fn path_handle(&self) -> &Self::Handle {
+        &self.path
+    }
+
+    #[inline]
+    fn ecn(&mut self) -> ExplicitCongestionNotification {
+        Default::default()
+    }
+
+    #[inline]
+    fn delay(&mut self) -> time::Duration {
+        Default::default()
+    }
+
+    #[inline]
+    fn ipv6_flow_label(&mut self) -> u32 {
+        0
+    }
+
+    #[inline]
+    fn can_gso(&self, segment_len: usize, _segment_count: usize) -> bool {
+        segment_len >= self.as_ref().len()
+    }
+
+    #[inline]
+    fn write_payload(
+        &mut self,
+        mut buffer: tx::PayloadBuffer,
+        _gso_offset: usize,
+    ) -> Result<usize, tx::Error> {
+        buffer.write(self.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s2n_codec::DecoderBufferMut;
+    use s2n_quic_core::{
+        connection::id::ConnectionInfo, crypto::key::testing::Key, inet::SocketAddress,
+        packet::ProtectedPacket, path::RemoteAddress,
+    };
+
+    #[test]
+    fn max_peers_test() {
+        let mut dispatch = Dispatch::<RemoteAddress>::new(2);
+
+        let packet_number = PacketNumberSpace::Initial.new_packet_number(VarInt::default());
+        let mut buffer = vec![0u8; 1200];
+        let mut encoder = EncoderBuffer::new(&mut buffer);
+        encoder.encode(&Initial {
+            version: 1,
+            destination_connection_id: &[1u8, 2, 3][..],
+            source_connection_id: &[4u8, 5, 6, 7][..],
+            token: &[0u8; 0][..],
+            packet_number: packet_number.truncate(packet_number).unwrap(),
+            payload: &[1u8, 2, 3, 4, 5][..],
+        });
+        let len = encoder.len();
+
+        let remote_address = SocketAddress::default();
+        let connection_info = ConnectionInfo::new(&remote_address);
+        let decoder = DecoderBufferMut::new(&mut buffer[..len]);
+        let packet = match ProtectedPacket::decode(decoder, &connection_info, &3)
+            .unwrap()
+            .0
+        {
+            ProtectedPacket::Initial(packet) => packet,
+            _ => panic!("expected an Initial packet"),
+        };
+
+        let path = RemoteAddress::from(remote_address);
+        for expected in [true, true, false, false] {
+            assert_eq!(
+                dispatch.queue::<Key>(
+                    path,
+                    &packet,
+                    connection::LocalId::TEST_ID,
+                    transport::Error::CONNECTION_REFUSED,
+                ),
+                expected
+            );
+        }
+
+        // CONNECTION_CLOSE packets aren't queued for more than max_peers peers
+        assert_eq!(dispatch.transmissions.len(), 2);
+    }
+
+    #[test]
+    fn truncate_reason_test() {
+        let short = b"server busy";
+        assert_eq!(truncate_reason(short), short);
+
+        let long = "a".repeat(MAX_REASON_LEN * 2);
+        assert_eq!(truncate_reason(long.as_bytes()).len(), MAX_REASON_LEN);
+
+        // multi-byte characters aren't split
+        let long = "é".repeat(MAX_REASON_LEN);
+        let truncated = truncate_reason(long.as_bytes());
+        assert!(truncated.len() <= MAX_REASON_LEN);
+        assert!(core::str::from_utf8(truncated).is_ok());
+    }
+}
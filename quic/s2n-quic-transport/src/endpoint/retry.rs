@@ -1,7 +1,7 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::endpoint;
+use crate::endpoint::{self, delayed};
 use alloc::collections::VecDeque;
 use core::ops::Range;
 use s2n_quic_core::{
@@ -20,6 +20,7 @@ pub struct Dispatch<Path: path::Handle> {
     // TODO: Find a better datastructure capable of handling delays in transmission
     // https://github.com/aws/s2n-quic/issues/280
     transmissions: VecDeque<Transmission<Path>>,
+    delayed: delayed::Queue<Path, Transmission<Path>>,
 }
 
 impl<Path: path::Handle> Default for Dispatch<Path> {
@@ -32,6 +33,7 @@ impl<Path: path::Handle> Dispatch<Path> {
     pub fn new(max_peers: usize) -> Self {
         Self {
             transmissions: VecDeque::with_capacity(max_peers),
+            delayed: Default::default(),
         }
     }
 
@@ -54,11 +56,55 @@ impl<Path: path::Handle> Dispatch<Path> {
         }
     }
 
+    /// Queues a Retry packet to be sent at the scheduled time
+    ///
+    /// Returns `false` if the packet was dropped because too many packets are already delayed.
+    pub fn queue_delayed<T: token::Format, C: RetryKey>(
+        &mut self,
+        path_handle: Path,
+        packet: &packet::initial::ProtectedInitial,
+        local_connection_id: connection::LocalId,
+        random: &mut dyn random::Generator,
+        token_format: &mut T,
+        schedule: delayed::Schedule,
+    ) -> bool {
+        if let Some(transmission) = Transmission::new::<_, C>(
+            path_handle,
+            packet,
+            local_connection_id,
+            random,
+            token_format,
+        ) {
+            self.delayed.push(schedule, path_handle, transmission)
+        } else {
+            false
+        }
+    }
+
+    /// Returns `true` if a Retry packet is being delayed for the connection attempt
+    pub fn is_delayed(
+        &self,
+        path_handle: &Path,
+        source_connection_id: &connection::PeerId,
+    ) -> bool {
+        self.delayed.contains(path_handle, source_connection_id)
+    }
+
+    /// Returns the time the next delayed Retry packet should be sent
+    pub fn next_expiration(&self) -> Option<time::Timestamp> {
+        self.delayed.next_expiration()
+    }
+
     pub fn on_transmit<Tx: tx::Queue<Handle = Path>, Pub: event::EndpointPublisher>(
         &mut self,
         queue: &mut Tx,
         publisher: &mut Pub,
+        timestamp: time::Timestamp,
     ) {
+        while let Some(transmission) = self.delayed.pop_expired(timestamp) {
+            self.transmissions.push_back(transmission);
+        }
+
         while let Some(transmission) = self.transmissions.pop_front() {
             match queue.push(&transmission) {
                 Ok(tx::Outcome { len, .. }) => {
@@ -12,6 +12,8 @@ use s2n_quic_core::{
     packet,
     packet::ProtectedPacket,
     path::{self, MINIMUM_MTU},
+    random,
+    transport::parameters::VersionInformation,
 };
 
 #[derive(Debug)]
@@ -23,16 +25,42 @@ pub struct Negotiator<Config: endpoint::Config> {
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct Error;
 
-const SUPPORTED_VERSIONS: &[u32] = &[
+pub(crate) const SUPPORTED_VERSIONS: &[u32] = &[
     0x1, // Draft 34 / Version 1 (https://github.com/quicwg/base-drafts/wiki/21st-Implementation-Draft)
 ];
 
+/// Returns `true` if the endpoint supports the given QUIC version
+#[inline]
+pub fn is_supported(version: u32) -> bool {
+    SUPPORTED_VERSIONS.iter().any(|&v| v == version)
+}
+
+/// Returns the version_information transport parameter for a connection using `chosen_version`
+///
+/// If `grease` is set, a random reserved version is advertised along with the supported
+/// versions.
+pub fn version_information<R: random::Generator + ?Sized>(
+    chosen_version: u32,
+    grease: bool,
+    random_generator: &mut R,
+) -> VersionInformation {
+    let mut available_versions = [0; SUPPORTED_VERSIONS.len() + 1];
+    available_versions[..SUPPORTED_VERSIONS.len()].copy_from_slice(SUPPORTED_VERSIONS);
+
+    let len = if grease {
+        available_versions[SUPPORTED_VERSIONS.len()] = packet::version::reserved(random_generator);
+        available_versions.len()
+    } else {
+        SUPPORTED_VERSIONS.len()
+    };
+
+    VersionInformation::new(chosen_version, &available_versions[..len])
+        .expect("supported versions are valid")
+}
+
 macro_rules! is_supported {
     ($packet:ident, $publisher:ident) => {{
-        let supported = SUPPORTED_VERSIONS
-            .iter()
-            .cloned()
-            .any(|v| v == $packet.version);
+        let supported = is_supported($packet.version);
 
         if supported {
             //= https://tools.ietf.org/id/draft-marx-qlog-event-definitions-quic-h3-02#5.3.1
@@ -691,4 +719,19 @@ mod tests {
             "servers should not negotiate with version negotiation packets"
         );
     }
+
+    #[test]
+    fn version_information_test() {
+        let mut generator = s2n_quic_core::random::testing::Generator::default();
+
+        let info = version_information(0x1, false, &mut generator);
+        assert_eq!(info.chosen_version(), 0x1);
+        assert_eq!(info.available_versions(), SUPPORTED_VERSIONS);
+
+        let info = version_information(0x1, true, &mut generator);
+        assert_eq!(info.chosen_version(), 0x1);
+        let (reserved, supported) = info.available_versions().split_last().unwrap();
+        assert_eq!(supported, SUPPORTED_VERSIONS);
+        assert!(packet::version::is_reserved(*reserved));
+    }
 }
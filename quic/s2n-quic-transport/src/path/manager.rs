@@ -9,7 +9,10 @@ use crate::{
     path::{challenge, Path},
     transmission,
 };
-use core::time::Duration;
+use core::{
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
 use s2n_quic_core::{
     ack,
     connection::{self, PeerId},
@@ -20,7 +23,7 @@ use s2n_quic_core::{
     packet::number::PacketNumberSpace,
     path::{
         migration::{self, Validator as _},
-        Handle as _, Id, MaxMtu,
+        Handle as _, Id, LocalAddress, MaxMtu,
     },
     random,
     recovery::congestion_controller::{self, Endpoint as _},
@@ -66,6 +69,39 @@ pub struct Manager<Config: endpoint::Config> {
     /// The `paths` data structure will need to be enhanced to include garbage collection
     /// of old paths to overcome this limitation.
     pending_packet_authentication: Option<u8>,
+
+    /// The error to close the connection with once the path pending packet authentication
+    /// receives an authenticated packet
+    ///
+    /// This is set when the migration validator chooses to close the connection. The close is
+    /// deferred until authentication so an off-path attacker can't spoof packets to close the
+    /// connection.
+    pending_migration_close: Option<transport::Error>,
+
+    /// The state of a connection migration initiated by the application
+    local_migration: LocalMigration,
+
+    /// The waker to notify once the local migration completes
+    local_migration_waker: Option<Waker>,
+
+    /// Set if the peer sent the `disable_active_migration` transport parameter
+    active_migration_disabled: bool,
+}
+
+/// The state of a connection migration initiated by the application
+#[derive(Debug)]
+enum LocalMigration {
+    /// No migration is in progress
+    Idle,
+    /// The application requested migrating to a new local address
+    ///
+    /// The path is created on the next connection wakeup, since it requires access to
+    /// endpoint-level providers.
+    Requested(LocalAddress),
+    /// The new path is being validated
+    Validating(u8),
+    /// The migration finished and the result has not been returned to the application yet
+    Complete(Result<(), migration::Error>),
 }
 
 impl<Config: endpoint::Config> Manager<Config> {
@@ -76,6 +112,10 @@ impl<Config: endpoint::Config> Manager<Config> {
             active: 0,
             last_known_active_validated_path: None,
             pending_packet_authentication: None,
+            pending_migration_close: None,
+            local_migration: LocalMigration::Idle,
+            local_migration_waker: None,
+            active_migration_disabled: false,
         };
         manager.paths[0].activated = true;
         manager.paths[0].is_active = true;
@@ -125,7 +165,7 @@ impl<Config: endpoint::Config> Manager<Config> {
         // The usage of 'apparent' is vague and its not clear if the previous path should
         // always be validated or only if the new active path is not validated.
         if !self.active_path().is_challenge_pending() {
-            self.set_challenge(self.active_path_id(), random_generator);
+            self.set_challenge(self.active_path_id(), random_generator, publisher);
         }
 
         let amplification_outcome = self.activate_path(publisher, prev_path_id, new_path_id);
@@ -201,21 +241,38 @@ impl<Config: endpoint::Config> Manager<Config> {
     /// Returns the Path for the provided address if the PathManager knows about it
     #[inline]
     pub fn path(&self, handle: &Config::PathHandle) -> Option<(Id, &Path<Config>)> {
-        self.paths
-            .iter()
-            .enumerate()
-            .find(|(_id, path)| Path::eq_by_handle(path, handle))
-            .map(|(id, path)| (path_id(id as u8), path))
+        let idx = self.path_index(handle)?;
+        Some((path_id(idx as u8), &self.paths[idx]))
     }
 
     /// Returns the Path for the provided address if the PathManager knows about it
     #[inline]
     pub fn path_mut(&mut self, handle: &Config::PathHandle) -> Option<(Id, &mut Path<Config>)> {
+        let idx = self.path_index(handle)?;
+        Some((path_id(idx as u8), &mut self.paths[idx]))
+    }
+
+    #[inline]
+    fn path_index(&self, handle: &Config::PathHandle) -> Option<usize> {
+        // A client that migrated to a new local address has multiple paths to the same server
+        // address, so prefer the path that matches the local address as well.
+        if Config::ENDPOINT_TYPE.is_client() && self.paths.len() > 1 {
+            if let Some(idx) = self
+                .paths
+                .iter()
+                .position(|path| s2n_quic_core::path::Handle::eq(&path.handle, handle))
+            {
+                return Some(idx);
+            }
+
+            if Path::eq_by_handle(self.active_path(), handle) {
+                return Some(self.active as usize);
+            }
+        }
+
         self.paths
-            .iter_mut()
-            .enumerate()
-            .find(|(_id, path)| Path::eq_by_handle(path, handle))
-            .map(|(id, path)| (path_id(id as u8), path))
+            .iter()
+            .position(|path| Path::eq_by_handle(path, handle))
     }
 
     /// Returns an iterator over all paths pending path_challenge or path_response
@@ -345,21 +402,31 @@ impl<Config: endpoint::Config> Manager<Config> {
                 local_address: &local_address,
             }
             .into(),
+            inflight_path_validations: self
+                .paths
+                .iter()
+                .filter(|path| path.is_challenge_pending())
+                .count(),
         }
         .into();
 
-        match migration_validator.on_migration_attempt(&attempt) {
+        let close_error = match migration_validator.on_migration_attempt(&attempt) {
             migration::Outcome::Allow => {
                 // no-op: allow the migration to continue
+                None
             }
             migration::Outcome::Deny(reason) => {
                 publisher.on_connection_migration_denied(reason.into_event());
                 return Err(DatagramDropReason::RejectedConnectionMigration);
             }
+            migration::Outcome::Close(error) => {
+                // create the path so the packet can be authenticated before closing
+                Some(error)
+            }
             _ => {
                 unimplemented!("unimplemented migration outcome");
             }
-        }
+        };
 
         // Determine which index will be used for the newly created path
         //
@@ -372,6 +439,7 @@ impl<Config: endpoint::Config> Manager<Config> {
             self.pending_packet_authentication = Some(idx as _);
             idx
         };
+        self.pending_migration_close = close_error;
 
         // TODO: Support deletion of old paths: https://github.com/aws/s2n-quic/issues/741
         // The current path manager implementation does not delete or reuse indices
@@ -471,7 +539,243 @@ impl<Config: endpoint::Config> Manager<Config> {
         Ok((new_path_id, amplification_outcome))
     }
 
-    fn set_challenge(&mut self, path_id: Id, random_generator: &mut dyn random::Generator) {
+    /// Called when the peer sends the `disable_active_migration` transport parameter
+    #[inline]
+    pub fn on_active_migration_disabled(&mut self) {
+        self.active_migration_disabled = true;
+    }
+
+    /// Requests migrating the connection to a new local address
+    ///
+    /// The path for the new address is created in [`Self::on_local_migration_requested`] and the
+    /// outcome of the migration can be polled with [`Self::poll_local_migration`].
+    pub fn request_local_migration(
+        &mut self,
+        local_address: LocalAddress,
+        handshake_confirmed: bool,
+    ) -> Result<(), migration::Error> {
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9
+        //# Clients are responsible for initiating all migrations.
+        if Config::ENDPOINT_TYPE.is_server() {
+            return Err(migration::Error::InvalidEndpointType);
+        }
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9
+        //# An endpoint MUST NOT initiate
+        //# connection migration before the handshake is confirmed, as defined in
+        //# Section 4.1.2 of [QUIC-TLS].
+        if !handshake_confirmed {
+            return Err(migration::Error::HandshakeNotConfirmed);
+        }
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9
+        //# If the peer sent the disable_active_migration transport parameter, an
+        //# endpoint also MUST NOT send packets (including probing packets; see
+        //# Section 9.1) from a different local address to the address the peer
+        //# used during the handshake, unless the endpoint has acted on a
+        //# preferred_address transport parameter from the peer.
+        if self.active_migration_disabled {
+            return Err(migration::Error::DisabledByPeer);
+        }
+
+        if matches!(
+            self.local_migration,
+            LocalMigration::Requested(_) | LocalMigration::Validating(_)
+        ) {
+            return Err(migration::Error::InProgress);
+        }
+
+        self.local_migration =
+            if self.active_path().local_address().unmap() == local_address.unmap() {
+                // the connection is already using the requested address
+                LocalMigration::Complete(Ok(()))
+            } else {
+                LocalMigration::Requested(local_address)
+            };
+
+        Ok(())
+    }
+
+    /// Creates and starts validating the path for a requested local migration
+    pub fn on_local_migration_requested<Pub: event::ConnectionPublisher>(
+        &mut self,
+        congestion_controller_endpoint: &mut Config::CongestionControllerEndpoint,
+        initial_rtt: Duration,
+        random_generator: &mut dyn random::Generator,
+        publisher: &mut Pub,
+    ) {
+        let local_address = if let LocalMigration::Requested(local_address) = self.local_migration {
+            local_address
+        } else {
+            return;
+        };
+
+        match self.create_local_migration_path(
+            local_address,
+            congestion_controller_endpoint,
+            initial_rtt,
+            random_generator,
+            publisher,
+        ) {
+            Ok(new_path_id) => {
+                self.local_migration = LocalMigration::Validating(new_path_id.as_u8());
+            }
+            Err(error) => self.complete_local_migration(Err(error)),
+        }
+    }
+
+    fn create_local_migration_path<Pub: event::ConnectionPublisher>(
+        &mut self,
+        local_address: LocalAddress,
+        congestion_controller_endpoint: &mut Config::CongestionControllerEndpoint,
+        initial_rtt: Duration,
+        random_generator: &mut dyn random::Generator,
+        publisher: &mut Pub,
+    ) -> Result<Id, migration::Error> {
+        debug_assert!(Config::ENDPOINT_TYPE.is_client());
+
+        // TODO: Support deletion of old paths: https://github.com/aws/s2n-quic/issues/741
+        if self.paths.len() >= MAX_ALLOWED_PATHS {
+            return Err(migration::Error::PathLimitExceeded);
+        }
+        let new_path_id = path_id(self.paths.len() as u8);
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9.5
+        //# An endpoint MUST NOT reuse a connection ID when sending from more
+        //# than one local address -- for example, when initiating connection
+        //# migration as described in Section 9.2 or when probing a new network
+        //# path as described in Section 9.1.
+        let peer_connection_id = self
+            .peer_id_registry
+            .consume_new_id_for_new_path()
+            .ok_or(migration::Error::InsufficientConnectionIds)?;
+
+        let mut handle = self.active_path().handle;
+        handle.set_local_address(local_address);
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9.2
+        //# When migrating, the new path might not support the endpoint's current
+        //# sending rate.  Therefore, the endpoint resets its congestion
+        //# controller and RTT estimate, as described in Section 9.4.
+        let rtt = self.active_path().rtt_estimator.for_new_path(initial_rtt);
+        let path_info = congestion_controller::PathInfo::new(&handle.remote_address());
+        let cc = congestion_controller_endpoint.new_congestion_controller(path_info);
+
+        let path = Path::new(
+            handle,
+            peer_connection_id,
+            self.active_path().local_connection_id,
+            rtt,
+            cc,
+            false,
+            self.max_mtu(),
+        );
+
+        let active_path = self.active_path();
+        let active_path_id = self.active_path_id();
+        publisher.on_path_created(event::builder::PathCreated {
+            active: path_event!(active_path, active_path_id),
+            new: path_event!(path, new_path_id),
+        });
+
+        publisher.on_mtu_updated(event::builder::MtuUpdated {
+            path_id: new_path_id.into_event(),
+            mtu: path.mtu_controller.mtu() as u16,
+            cause: MtuUpdatedCause::NewPath,
+        });
+
+        self.paths.push(path);
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-9.2
+        //# To establish reachability on the new path, an endpoint initiates path
+        //# validation (Section 8.2) on the new path.
+        self.set_challenge(new_path_id, random_generator, publisher);
+
+        Ok(new_path_id)
+    }
+
+    /// Switches to the path of a local migration once it has been validated
+    fn on_local_migration_validated<Pub: event::ConnectionPublisher>(
+        &mut self,
+        new_path_id: Id,
+        publisher: &mut Pub,
+    ) {
+        let prev_path_id = self.active_path_id();
+
+        if prev_path_id != new_path_id {
+            self.activate_path(publisher, prev_path_id, new_path_id);
+
+            // Restart ECN validation to check that the new path supports ECN
+            let path = self.active_path_mut();
+            path.ecn_controller
+                .restart(path_event!(path, new_path_id), publisher);
+
+            //= https://www.rfc-editor.org/rfc/rfc9000#section-5.1.2
+            //# Endpoints SHOULD retire connection IDs when
+            //# they are no longer actively using either the local or destination
+            //# address for which the connection ID was used.
+            let prev_path = &mut self[prev_path_id];
+            let prev_peer_connection_id = prev_path.peer_connection_id;
+            prev_path.abandon_challenge(publisher, prev_path_id.as_u8() as u64);
+            self.peer_id_registry.retire_id(&prev_peer_connection_id);
+        }
+
+        self.last_known_active_validated_path = Some(new_path_id.as_u8());
+        self.complete_local_migration(Ok(()));
+    }
+
+    /// Polls for the outcome of a migration requested with [`Self::request_local_migration`]
+    ///
+    /// Returns `Poll::Ready(Ok(()))` if no migration is in progress.
+    pub fn poll_local_migration(
+        &mut self,
+        context: &Context,
+    ) -> Poll<Result<(), migration::Error>> {
+        match core::mem::replace(&mut self.local_migration, LocalMigration::Idle) {
+            LocalMigration::Idle => Poll::Ready(Ok(())),
+            LocalMigration::Complete(result) => Poll::Ready(result),
+            state => {
+                self.local_migration = state;
+
+                if !self
+                    .local_migration_waker
+                    .as_ref()
+                    .map_or(false, |waker| waker.will_wake(context.waker()))
+                {
+                    self.local_migration_waker = Some(context.waker().clone());
+                }
+
+                Poll::Pending
+            }
+        }
+    }
+
+    /// Called when the connection is closed
+    #[inline]
+    pub fn on_connection_error(&mut self, error: connection::Error) {
+        if matches!(
+            self.local_migration,
+            LocalMigration::Requested(_) | LocalMigration::Validating(_)
+        ) {
+            self.complete_local_migration(Err(error.into()));
+        }
+    }
+
+    #[inline]
+    fn complete_local_migration(&mut self, result: Result<(), migration::Error>) {
+        self.local_migration = LocalMigration::Complete(result);
+
+        if let Some(waker) = self.local_migration_waker.take() {
+            waker.wake();
+        }
+    }
+
+    fn set_challenge<Pub: event::ConnectionPublisher>(
+        &mut self,
+        path_id: Id,
+        random_generator: &mut dyn random::Generator,
+        publisher: &mut Pub,
+    ) {
         //= https://www.rfc-editor.org/rfc/rfc9000#section-8.2.1
         //# The endpoint MUST use unpredictable data in every PATH_CHALLENGE
         //# frame so that it can associate the peer's response with the
@@ -504,6 +808,13 @@ impl<Config: endpoint::Config> Manager<Config> {
         //# upon receiving a probe packet from a different address.
         let challenge = challenge::Challenge::new(abandon_duration, data);
         self[path_id].set_challenge(challenge);
+
+        let path = &self[path_id];
+        publisher.on_path_challenge_updated(event::builder::PathChallengeUpdated {
+            path_challenge_status: event::builder::PathChallengeStatus::Pending,
+            path: path_event!(path, path_id),
+            challenge_data: path.challenge.challenge_data(),
+        });
     }
 
     /// Returns true if a valid initial packet has been received
@@ -597,11 +908,19 @@ impl<Config: endpoint::Config> Manager<Config> {
                 // The path is now validated, so it is unblocked if it was
                 // previously amplification limited
                 debug_assert!(!path.at_amplification_limit());
-                return match (was_amplification_limited, path.is_active()) {
+                let amplification_outcome = match (was_amplification_limited, path.is_active()) {
                     (true, true) => AmplificationOutcome::ActivePathUnblocked,
                     (true, false) => AmplificationOutcome::InactivePathUnblocked,
                     _ => AmplificationOutcome::Unchanged,
                 };
+
+                // Switch to the new path if it was created for a local migration
+                if matches!(self.local_migration, LocalMigration::Validating(idx) if idx as u64 == id)
+                {
+                    self.on_local_migration_validated(path_id(id as u8), publisher);
+                }
+
+                return amplification_outcome;
             }
         }
         AmplificationOutcome::Unchanged
@@ -646,8 +965,14 @@ impl<Config: endpoint::Config> Manager<Config> {
         if self.pending_packet_authentication == Some(path_id.as_u8()) {
             self.pending_packet_authentication = None;
 
+            // The migration validator chose to close the connection now that the packet is
+            // known to be from the peer
+            if let Some(error) = self.pending_migration_close.take() {
+                return Err(error);
+            }
+
             // We can finally arm the challenge after authenticating the packet
-            self.set_challenge(path_id, random_generator);
+            self.set_challenge(path_id, random_generator, publisher);
         }
 
         let mut amplification_outcome = AmplificationOutcome::Unchanged;
@@ -655,7 +980,13 @@ impl<Config: endpoint::Config> Manager<Config> {
         //= https://www.rfc-editor.org/rfc/rfc9000#section-9.2
         //# An endpoint can migrate a connection to a new local address by
         //# sending packets containing non-probing frames from that address.
-        if !path_validation_probing.is_probing() && self.active_path_id() != path_id {
+        //
+        // Clients only change the active path when initiating a local migration, since servers
+        // do not migrate.
+        if Config::ENDPOINT_TYPE.is_server()
+            && !path_validation_probing.is_probing()
+            && self.active_path_id() != path_id
+        {
             amplification_outcome =
                 self.update_active_path(path_id, random_generator, publisher)?;
             //= https://www.rfc-editor.org/rfc/rfc9000#section-9.3
@@ -672,7 +1003,7 @@ impl<Config: endpoint::Config> Manager<Config> {
                 //# packets to the new peer address and MUST initiate path validation
                 //# (Section 8.2) to verify the peer's ownership of the address if
                 //# validation is not already underway.
-                self.set_challenge(self.active_path_id(), random_generator);
+                self.set_challenge(self.active_path_id(), random_generator, publisher);
             }
         }
         Ok(amplification_outcome)
@@ -753,6 +1084,14 @@ impl<Config: endpoint::Config> Manager<Config> {
             path.on_timeout(timestamp, path_id(id as u8), random_generator, publisher);
         }
 
+        if let LocalMigration::Validating(idx) = self.local_migration {
+            // The challenge is no longer pending before the path was validated, so the
+            // migration failed and the connection continues using the original path
+            if !self[path_id(idx)].is_challenge_pending() {
+                self.complete_local_migration(Err(migration::Error::ValidationTimeout));
+            }
+        }
+
         let mut amplification_outcome = AmplificationOutcome::Unchanged;
 
         if self.active_path().failed_validation() {
@@ -1533,6 +1533,149 @@ fn temporary_until_authenticated() {
     );
 }
 
+/// Records each migration attempt and responds with the configured outcome
+struct MigrationRecorder {
+    outcome: migration::Outcome,
+    inflight_path_validations: Vec<usize>,
+}
+
+impl migration::Validator for MigrationRecorder {
+    fn on_migration_attempt(&mut self, attempt: &migration::Attempt) -> migration::Outcome {
+        self.inflight_path_validations
+            .push(attempt.inflight_path_validations);
+        self.outcome
+    }
+}
+
+#[test]
+// Ensure the migration validator can close the connection, but only after a packet
+// on the new path is authenticated
+fn migration_close_after_authentication() {
+    let mut publisher = Publisher::snapshot();
+    let now = NoopClock {}.get_time();
+    let datagram = DatagramInfo {
+        timestamp: now,
+        payload_len: 0,
+        ecn: ExplicitCongestionNotification::default(),
+        destination_connection_id: connection::LocalId::TEST_ID,
+        destination_connection_id_classification: connection::id::Classification::Local,
+        source_connection_id: None,
+    };
+    let error = transport::Error::CONNECTION_REFUSED;
+    let mut validator = MigrationRecorder {
+        outcome: migration::Outcome::Allow,
+        inflight_path_validations: vec![],
+    };
+
+    let addr = |addr: &str| -> RemoteAddress {
+        let addr: SocketAddr = addr.parse().unwrap();
+        SocketAddress::from(addr).into()
+    };
+
+    let first_path = ServerPath::new(
+        addr("127.0.0.1:8001"),
+        connection::PeerId::try_from_bytes(&[1]).unwrap(),
+        connection::LocalId::TEST_ID,
+        RttEstimator::default(),
+        Default::default(),
+        false,
+        DEFAULT_MAX_MTU,
+    );
+    let mut manager = manager_server(first_path);
+
+    // probe a second path so it starts validating
+    let (second_path_id, _amplification_outcome) = manager
+        .on_datagram_received(
+            &addr("127.0.0.2:8001"),
+            &datagram,
+            true,
+            &mut Default::default(),
+            &mut validator,
+            DEFAULT_MAX_MTU,
+            DEFAULT_INITIAL_RTT,
+            &mut publisher,
+        )
+        .unwrap();
+    manager
+        .on_processed_packet(
+            second_path_id,
+            None,
+            path_validation::Probe::Probing,
+            &mut random::testing::Generator(123),
+            &mut publisher,
+        )
+        .unwrap();
+    assert!(manager[second_path_id].is_challenge_pending());
+
+    // the validator closes on the next attempt, which is replaced before it's authenticated
+    validator.outcome = migration::Outcome::Close(error);
+    let (third_path_id, _amplification_outcome) = manager
+        .on_datagram_received(
+            &addr("127.0.0.3:8001"),
+            &datagram,
+            true,
+            &mut Default::default(),
+            &mut validator,
+            DEFAULT_MAX_MTU,
+            DEFAULT_INITIAL_RTT,
+            &mut publisher,
+        )
+        .unwrap();
+
+    validator.outcome = migration::Outcome::Allow;
+    let (fourth_path_id, _amplification_outcome) = manager
+        .on_datagram_received(
+            &addr("127.0.0.4:8001"),
+            &datagram,
+            true,
+            &mut Default::default(),
+            &mut validator,
+            DEFAULT_MAX_MTU,
+            DEFAULT_INITIAL_RTT,
+            &mut publisher,
+        )
+        .unwrap();
+    assert_eq!(third_path_id, fourth_path_id);
+    manager
+        .on_processed_packet(
+            fourth_path_id,
+            None,
+            path_validation::Probe::Probing,
+            &mut random::testing::Generator(123),
+            &mut publisher,
+        )
+        .unwrap();
+
+    // the connection is closed once a packet on the new path is authenticated
+    validator.outcome = migration::Outcome::Close(error);
+    let (fifth_path_id, _amplification_outcome) = manager
+        .on_datagram_received(
+            &addr("127.0.0.5:8001"),
+            &datagram,
+            true,
+            &mut Default::default(),
+            &mut validator,
+            DEFAULT_MAX_MTU,
+            DEFAULT_INITIAL_RTT,
+            &mut publisher,
+        )
+        .unwrap();
+    assert_eq!(
+        manager.on_processed_packet(
+            fifth_path_id,
+            None,
+            path_validation::Probe::Probing,
+            &mut random::testing::Generator(123),
+            &mut publisher,
+        ),
+        Err(error)
+    );
+    assert!(!manager[fifth_path_id].is_challenge_pending());
+
+    // only paths with a challenge pending count as inflight validations
+    assert_eq!(validator.inflight_path_validations, [0, 1, 1, 2]);
+}
+
 // The last_known_active_validated_path needs to be both validated and also
 // activated (the active path at some point in the connection).
 //
@@ -0,0 +1,249 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Requests the peer to adjust how frequently it acknowledges packets
+//!
+//! The ACK frequency extension is defined in
+//! https://datatracker.ietf.org/doc/html/draft-ietf-quic-ack-frequency
+
+use crate::{
+    contexts::WriteContext,
+    sync::{flag, flag::Flag},
+    transmission,
+};
+use core::time::Duration;
+use s2n_quic_core::{
+    ack,
+    connection::limits::Limits,
+    frame::{AckFrequency, ImmediateAck},
+    packet::number::PacketNumber,
+    recovery::loss::K_PACKET_THRESHOLD,
+    transport::parameters::{MaxAckDelay, MinAckDelay},
+    varint::VarInt,
+};
+
+// https://datatracker.ietf.org/doc/html/draft-ietf-quic-ack-frequency#section-6.1
+// Without an ACK_FREQUENCY frame, an endpoint acknowledges at least every other
+// ack-eliciting packet, as described in RFC 9000.
+const DEFAULT_ACK_ELICITING_THRESHOLD: VarInt = VarInt::from_u8(1);
+
+#[derive(Debug, Default)]
+pub struct Manager {
+    /// Set if the peer advertised support for the extension with the min_ack_delay
+    /// transport parameter
+    peer_min_ack_delay: Option<Duration>,
+
+    /// The max_ack_delay requested from the peer with the ACK_FREQUENCY frame
+    requested_max_ack_delay: Option<Duration>,
+
+    /// Delivers the ACK_FREQUENCY frame to the peer
+    request: Flag<AckFrequencyWriter>,
+}
+
+impl Manager {
+    pub fn new(
+        limits: &Limits,
+        peer_min_ack_delay: Option<MinAckDelay>,
+        peer_max_ack_delay: MaxAckDelay,
+    ) -> Self {
+        let peer_min_ack_delay = if let Some(min_ack_delay) = peer_min_ack_delay {
+            min_ack_delay.as_duration()
+        } else {
+            // the peer doesn't support the extension
+            return Self::default();
+        };
+
+        let ack_eliciting_threshold = limits.requested_ack_eliciting_threshold();
+        let requested_max_ack_delay = limits.requested_max_ack_delay();
+
+        if ack_eliciting_threshold.is_none() && requested_max_ack_delay.is_none() {
+            // the peer's default acknowledgement behavior is used
+            return Self {
+                peer_min_ack_delay: Some(peer_min_ack_delay),
+                ..Default::default()
+            };
+        }
+
+        // https://datatracker.ietf.org/doc/html/draft-ietf-quic-ack-frequency#section-4
+        // A Request Max Ack Delay value smaller than the peer's min_ack_delay is invalid.
+        let requested_max_ack_delay = requested_max_ack_delay
+            .unwrap_or_else(|| peer_max_ack_delay.as_duration())
+            .max(peer_min_ack_delay);
+
+        let frame = AckFrequency {
+            sequence_number: VarInt::from_u8(0),
+            ack_eliciting_threshold: ack_eliciting_threshold
+                .unwrap_or(DEFAULT_ACK_ELICITING_THRESHOLD),
+            request_max_ack_delay: requested_max_ack_delay
+                .as_micros()
+                .try_into()
+                .unwrap_or(VarInt::MAX),
+            // Request an immediate ACK once enough packets are reordered for loss
+            // detection to declare the missing packets lost
+            reordering_threshold: VarInt::from_u32(K_PACKET_THRESHOLD as u32),
+        };
+
+        let mut request = Flag::new(AckFrequencyWriter { frame: Some(frame) });
+        request.send();
+
+        Self {
+            peer_min_ack_delay: Some(peer_min_ack_delay),
+            requested_max_ack_delay: Some(requested_max_ack_delay),
+            request,
+        }
+    }
+
+    /// Returns `true` if the peer supports the ACK frequency extension
+    #[inline]
+    pub fn is_enabled(&self) -> bool {
+        self.peer_min_ack_delay.is_some()
+    }
+
+    /// Returns the max_ack_delay requested from the peer, if any
+    ///
+    /// The peer may delay acknowledgements by this amount once the request is received,
+    /// so it should be accounted for when computing the probe timeout.
+    #[inline]
+    pub fn requested_max_ack_delay(&self) -> Option<Duration> {
+        self.requested_max_ack_delay
+    }
+
+    /// Called when an outgoing packet is being assembled
+    pub fn on_transmit<W: WriteContext>(&mut self, context: &mut W) {
+        // https://datatracker.ietf.org/doc/html/draft-ietf-quic-ack-frequency#section-7
+        // Sending an IMMEDIATE_ACK frame in a probe elicits an acknowledgement without
+        // delay, which speeds up loss recovery when the peer is delaying ACKs.
+        if self.is_enabled()
+            && context.transmission_mode() == transmission::Mode::LossRecoveryProbing
+        {
+            let _ = context.write_frame(&ImmediateAck);
+        }
+
+        let _ = self.request.on_transmit(context);
+    }
+
+    /// This method gets called when a packet delivery got acknowledged
+    #[inline]
+    pub fn on_packet_ack<A: ack::Set>(&mut self, ack_set: &A) {
+        self.request.on_packet_ack(ack_set);
+    }
+
+    /// This method gets called when a packet loss is reported
+    #[inline]
+    pub fn on_packet_loss<A: ack::Set>(&mut self, ack_set: &A) {
+        self.request.on_packet_loss(ack_set);
+    }
+}
+
+impl transmission::interest::Provider for Manager {
+    #[inline]
+    fn transmission_interest<Q: transmission::interest::Query>(
+        &self,
+        query: &mut Q,
+    ) -> transmission::interest::Result {
+        self.request.transmission_interest(query)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AckFrequencyWriter {
+    frame: Option<AckFrequency>,
+}
+
+impl flag::Writer for AckFrequencyWriter {
+    fn write_frame<W: WriteContext>(&mut self, context: &mut W) -> Option<PacketNumber> {
+        context.write_frame(self.frame.as_ref()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        contexts::testing::{MockWriteContext, OutgoingFrameBuffer},
+        transmission::interest::Provider,
+    };
+    use s2n_quic_core::{endpoint, frame::Frame, time::clock::testing as time};
+
+    fn write_frames(manager: &mut Manager, mode: transmission::Mode) -> OutgoingFrameBuffer {
+        let mut frame_buffer = OutgoingFrameBuffer::new();
+        let mut context = MockWriteContext::new(
+            time::now(),
+            &mut frame_buffer,
+            transmission::Constraint::None,
+            mode,
+            endpoint::Type::Server,
+        );
+        manager.on_transmit(&mut context);
+        frame_buffer
+    }
+
+    #[test]
+    fn disabled_test() {
+        let limits = Limits::default()
+            .with_requested_ack_eliciting_threshold(10)
+            .unwrap();
+        let mut manager = Manager::new(&limits, None, MaxAckDelay::default());
+
+        assert!(!manager.is_enabled());
+        assert!(!manager.has_transmission_interest());
+        assert_eq!(manager.requested_max_ack_delay(), None);
+
+        let frame_buffer = write_frames(&mut manager, transmission::Mode::LossRecoveryProbing);
+        assert!(frame_buffer.is_empty());
+    }
+
+    #[test]
+    fn request_test() {
+        let limits = Limits::default()
+            .with_requested_ack_eliciting_threshold(10)
+            .unwrap()
+            .with_requested_max_ack_delay(Duration::from_micros(500))
+            .unwrap();
+        let peer_min_ack_delay = MinAckDelay::try_from(Duration::from_millis(1)).unwrap();
+        let mut manager = Manager::new(&limits, Some(peer_min_ack_delay), MaxAckDelay::default());
+
+        assert!(manager.is_enabled());
+        assert!(manager.has_transmission_interest());
+        // the requested delay is raised to the peer's min_ack_delay
+        assert_eq!(
+            manager.requested_max_ack_delay(),
+            Some(Duration::from_millis(1))
+        );
+
+        let mut frame_buffer = write_frames(&mut manager, transmission::Mode::Normal);
+        let mut frame = frame_buffer.pop_front().unwrap();
+        assert_eq!(
+            frame.as_frame(),
+            Frame::AckFrequency(AckFrequency {
+                sequence_number: VarInt::from_u8(0),
+                ack_eliciting_threshold: VarInt::from_u8(10),
+                request_max_ack_delay: VarInt::from_u16(1000),
+                reordering_threshold: VarInt::from_u8(3),
+            })
+        );
+        assert!(frame_buffer.is_empty());
+        assert!(!manager.has_transmission_interest());
+    }
+
+    #[test]
+    fn immediate_ack_test() {
+        let peer_min_ack_delay = MinAckDelay::try_from(Duration::from_millis(1)).unwrap();
+        let mut manager = Manager::new(
+            &Limits::default(),
+            Some(peer_min_ack_delay),
+            MaxAckDelay::default(),
+        );
+
+        // nothing is requested without any configured values
+        assert!(manager.is_enabled());
+        assert!(!manager.has_transmission_interest());
+        assert!(write_frames(&mut manager, transmission::Mode::Normal).is_empty());
+
+        // probes include an IMMEDIATE_ACK frame
+        let mut frame_buffer = write_frames(&mut manager, transmission::Mode::LossRecoveryProbing);
+        let mut frame = frame_buffer.pop_front().unwrap();
+        assert_eq!(frame.as_frame(), Frame::ImmediateAck(ImmediateAck));
+        assert!(frame_buffer.is_empty());
+    }
+}
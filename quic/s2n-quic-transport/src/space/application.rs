@@ -10,8 +10,8 @@ use crate::{
     recovery,
     recovery::CongestionController,
     space::{
-        datagram, keep_alive::KeepAlive, CryptoStream, HandshakeStatus, PacketSpace,
-        TxPacketNumbers,
+        ack_frequency, datagram, keep_alive::KeepAlive, new_token, CryptoStream, HandshakeStatus,
+        PacketSpace, TxPacketNumbers,
     },
     stream::Manager as _,
     sync::flag,
@@ -27,9 +27,9 @@ use s2n_quic_core::{
     event::{self, ConnectionPublisher as _, IntoEvent},
     frame::{
         ack::AckRanges, crypto::CryptoRef, datagram::DatagramRef, stream::StreamRef, Ack,
-        ConnectionClose, DataBlocked, HandshakeDone, MaxData, MaxStreamData, MaxStreams,
-        NewConnectionId, NewToken, PathChallenge, PathResponse, ResetStream, RetireConnectionId,
-        StopSending, StreamDataBlocked, StreamsBlocked,
+        AckFrequency, ConnectionClose, DataBlocked, HandshakeDone, ImmediateAck, MaxData,
+        MaxStreamData, MaxStreams, NewConnectionId, NewToken, PathChallenge, PathResponse,
+        ResetStream, RetireConnectionId, StopSending, StreamDataBlocked, StreamsBlocked,
     },
     inet::DatagramInfo,
     packet::{
@@ -75,6 +75,10 @@ pub struct ApplicationSpace<Config: endpoint::Config> {
     processed_packet_numbers: SlidingWindow,
     recovery_manager: recovery::Manager<Config>,
     pub datagram_manager: datagram::Manager<Config>,
+    /// Requests the peer to adjust its acknowledgement frequency
+    ack_frequency: ack_frequency::Manager,
+    /// Issues and receives address validation tokens
+    pub new_token: new_token::Manager,
     /// Counter used for detecting an Optimistic Ack attack
     skip_counter: Option<Counter<u32, Saturating>>,
     /// Keeps track of if the TLS session still exists. If it does, we buffer
@@ -106,6 +110,8 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
         keep_alive: KeepAlive,
         max_mtu: MaxMtu,
         datagram_manager: datagram::Manager<Config>,
+        ack_frequency: ack_frequency::Manager,
+        new_token: new_token::Manager,
     ) -> Self {
         let key_set = KeySet::new(key, Self::key_limits(max_mtu));
 
@@ -122,6 +128,8 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
             processed_packet_numbers: SlidingWindow::default(),
             recovery_manager: recovery::Manager::new(PacketNumberSpace::ApplicationData),
             datagram_manager,
+            ack_frequency,
+            new_token,
             skip_counter: None,
             buffer_crypto_frames: Config::ENDPOINT_TYPE.is_client(),
         }
@@ -223,6 +231,8 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
                 &mut self.recovery_manager,
                 &mut self.crypto_stream,
                 &mut self.datagram_manager,
+                &mut self.ack_frequency,
+                &mut self.new_token,
             ),
             timestamp: context.timestamp,
             transmission_constraint,
@@ -436,6 +446,9 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
         // Retire the local connection ID used during the handshake to reduce linkability (if enabled)
         local_id_registry.on_handshake_confirmed();
 
+        // Servers issue an address validation token for future connections
+        self.new_token.on_handshake_confirmed();
+
         //= https://www.rfc-editor.org/rfc/rfc9002#section-6.2.1
         //# A sender SHOULD restart its PTO timer every time an ack-eliciting
         //# packet is sent or acknowledged, or when Initial or Handshake keys are
@@ -570,6 +583,8 @@ impl<Config: endpoint::Config> ApplicationSpace<Config> {
                 handshake_status,
                 ping: &mut self.ping,
                 stream_manager: &mut self.stream_manager,
+                ack_frequency: &mut self.ack_frequency,
+                new_token: &mut self.new_token,
                 local_id_registry,
                 path_id,
                 path_manager,
@@ -724,6 +739,8 @@ impl<Config: endpoint::Config> transmission::interest::Provider for ApplicationS
         self.recovery_manager.transmission_interest(query)?;
         self.stream_manager.transmission_interest(query)?;
         self.datagram_manager.transmission_interest(query)?;
+        self.ack_frequency.transmission_interest(query)?;
+        self.new_token.transmission_interest(query)?;
         Ok(())
     }
 }
@@ -740,6 +757,8 @@ struct RecoveryContext<'a, Config: endpoint::Config> {
     crypto_stream: &'a mut CryptoStream,
     ping: &'a mut flag::Ping,
     stream_manager: &'a mut Config::StreamManager,
+    ack_frequency: &'a mut ack_frequency::Manager,
+    new_token: &'a mut new_token::Manager,
     local_id_registry: &'a mut connection::LocalIdRegistry,
     path_id: path::Id,
     path_manager: &'a mut path::Manager<Config>,
@@ -803,6 +822,8 @@ impl<'a, Config: endpoint::Config> recovery::Context<Config> for RecoveryContext
             .on_packet_ack(packet_number_range, publisher);
         self.crypto_stream.on_packet_ack(packet_number_range);
         self.ping.on_packet_ack(packet_number_range);
+        self.ack_frequency.on_packet_ack(packet_number_range);
+        self.new_token.on_packet_ack(packet_number_range);
         self.stream_manager.on_packet_ack(packet_number_range);
         self.local_id_registry.on_packet_ack(packet_number_range);
         self.path_manager.on_packet_ack(packet_number_range);
@@ -823,6 +844,8 @@ impl<'a, Config: endpoint::Config> recovery::Context<Config> for RecoveryContext
         self.handshake_status
             .on_packet_loss(packet_number_range, publisher);
         self.ping.on_packet_loss(packet_number_range);
+        self.ack_frequency.on_packet_loss(packet_number_range);
+        self.new_token.on_packet_loss(packet_number_range);
         self.stream_manager.on_packet_loss(packet_number_range);
         self.local_id_registry.on_packet_loss(packet_number_range);
         self.path_manager.on_packet_loss(packet_number_range);
@@ -990,7 +1013,9 @@ impl<Config: endpoint::Config> PacketSpace<Config> for ApplicationSpace<Config>
                 .with_reason(Self::INVALID_FRAME_ERROR)
                 .with_frame_type(frame.tag().into()));
         }
-        // TODO add support for NEW_TOKEN_FRAMEs on the client
+
+        // the token is moved into the endpoint's token store for future connections
+        self.new_token.on_new_token_frame(&frame);
         Ok(())
     }
 
@@ -1128,6 +1153,14 @@ impl<Config: endpoint::Config> PacketSpace<Config> for ApplicationSpace<Config>
         Ok(())
     }
 
+    fn handle_ack_frequency_frame(&mut self, frame: AckFrequency) -> Result<(), transport::Error> {
+        self.ack_manager.on_ack_frequency_frame(&frame)
+    }
+
+    fn handle_immediate_ack_frame(&mut self, frame: ImmediateAck) -> Result<(), transport::Error> {
+        self.ack_manager.on_immediate_ack_frame(&frame)
+    }
+
     fn on_processed_packet<Pub: event::ConnectionPublisher>(
         &mut self,
         processed_packet: ProcessedPacket,
@@ -82,8 +82,15 @@ impl CryptoStream {
         //# violations of this requirement as a connection error of type
         //# PROTOCOL_VIOLATION.
 
-        if self.is_finished && frame.offset + frame.data.len() > self.rx.total_received_len() {
-            return Err(transport::Error::PROTOCOL_VIOLATION);
+        if self.is_finished {
+            let extends_past_end = frame
+                .offset
+                .checked_add_usize(frame.data.len())
+                .map_or(true, |end| end > self.rx.total_received_len());
+
+            if extends_past_end {
+                return Err(transport::Error::PROTOCOL_VIOLATION);
+            }
         }
 
         //= https://www.rfc-editor.org/rfc/rfc9000#section-7.5
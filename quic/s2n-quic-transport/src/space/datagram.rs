@@ -47,12 +47,14 @@ impl<Config: endpoint::Config> Manager<Config> {
         context: &mut W,
         stream_manager: &mut Config::StreamManager,
         datagrams_prioritized: bool,
+        packet_capacity: usize,
     ) {
         let mut packet = Packet {
             context,
             has_pending_streams: stream_manager.has_pending_streams(),
             datagrams_prioritized,
             max_datagram_payload: self.max_datagram_payload,
+            packet_capacity,
         };
         self.sender.on_transmit(&mut packet);
     }
@@ -100,6 +102,7 @@ struct Packet<'a, C: WriteContext> {
     has_pending_streams: bool,
     datagrams_prioritized: bool,
     max_datagram_payload: u64,
+    packet_capacity: usize,
 }
 
 impl<'a, C: WriteContext> s2n_quic_core::datagram::Packet for Packet<'a, C> {
@@ -116,6 +119,16 @@ impl<'a, C: WriteContext> s2n_quic_core::datagram::Packet for Packet<'a, C> {
             )
     }
 
+    /// Returns the largest datagram that fits in a packet on the current path
+    fn max_datagram_size(&self) -> usize {
+        // A datagram filling the packet is written as the last frame and omits the length
+        let size = self
+            .packet_capacity
+            .saturating_sub(frame::datagram::DATAGRAM_TAG.encoding_size());
+        let max_datagram_payload = self.max_datagram_payload.try_into().unwrap_or(usize::MAX);
+        size.min(max_datagram_payload)
+    }
+
     /// Writes a single datagram to a packet
     fn write_datagram(&mut self, data: &[u8]) -> Result<(), WriteError> {
         self.write_datagram_vectored(&[data])
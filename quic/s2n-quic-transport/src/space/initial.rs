@@ -47,7 +47,7 @@ pub struct InitialSpace<Config: endpoint::Config> {
     //= https://www.rfc-editor.org/rfc/rfc9000#section-17.2.5.3
     //# Subsequent Initial packets from the client include the connection ID
     //# and token values from the Retry packet.
-    retry_token: Vec<u8>,
+    token: Vec<u8>,
     processed_packet_numbers: SlidingWindow,
     recovery_manager: recovery::Manager<Config>,
 }
@@ -77,12 +77,22 @@ impl<Config: endpoint::Config> InitialSpace<Config> {
             crypto_stream: CryptoStream::new(),
             tx_packet_numbers: TxPacketNumbers::new(PacketNumberSpace::Initial, now),
             received_hello_message: false,
-            retry_token: Vec::new(),
+            token: Vec::new(),
             processed_packet_numbers: SlidingWindow::default(),
             recovery_manager: recovery::Manager::new(PacketNumberSpace::Initial),
         }
     }
 
+    /// Sets the token from a NEW_TOKEN frame of a previous connection to the server
+    pub fn set_new_token(&mut self, token: Vec<u8>) {
+        debug_assert!(Config::ENDPOINT_TYPE.is_client());
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
+        //# The client MUST include the token in all Initial packets it sends,
+        //# unless a Retry replaces the token with a newer one.
+        self.token = token;
+    }
+
     /// This method gets called when a Retry packet is processed.
     ///
     /// Reset the TLS stack and recover state when the first Retry packet is processed.
@@ -96,7 +106,7 @@ impl<Config: endpoint::Config> InitialSpace<Config> {
         publisher: &mut Pub,
     ) {
         debug_assert!(Config::ENDPOINT_TYPE.is_client());
-        self.retry_token = retry_token.to_vec();
+        self.token = retry_token.to_vec();
 
         //= https://www.rfc-editor.org/rfc/rfc9000#section-17.2.5.2
         //# Changing the Destination Connection ID field also results in
@@ -200,7 +210,7 @@ impl<Config: endpoint::Config> InitialSpace<Config> {
             version: context.quic_version,
             destination_connection_id,
             source_connection_id: context.path_manager[context.path_id].local_connection_id,
-            token: self.retry_token.as_slice(),
+            token: self.token.as_slice(),
             packet_number,
             payload,
         };
@@ -24,9 +24,9 @@ use s2n_quic_core::{
     event::{self, IntoEvent},
     frame::{
         ack::AckRanges, crypto::CryptoRef, datagram::DatagramRef, stream::StreamRef, Ack,
-        ConnectionClose, DataBlocked, HandshakeDone, MaxData, MaxStreamData, MaxStreams,
-        NewConnectionId, NewToken, PathChallenge, PathResponse, ResetStream, RetireConnectionId,
-        StopSending, StreamDataBlocked, StreamsBlocked,
+        AckFrequency, ConnectionClose, DataBlocked, HandshakeDone, ImmediateAck, MaxData,
+        MaxStreamData, MaxStreams, NewConnectionId, NewToken, PathChallenge, PathResponse,
+        ResetStream, RetireConnectionId, StopSending, StreamDataBlocked, StreamsBlocked,
     },
     inet::DatagramInfo,
     packet::number::{PacketNumber, PacketNumberSpace},
@@ -35,6 +35,7 @@ use s2n_quic_core::{
     varint::VarInt,
 };
 
+pub(crate) mod ack_frequency;
 mod application;
 mod crypto_stream;
 pub(crate) mod datagram;
@@ -42,6 +43,7 @@ mod handshake;
 mod handshake_status;
 mod initial;
 mod keep_alive;
+pub(crate) mod new_token;
 mod session_context;
 mod tx_packet_numbers;
 
@@ -77,6 +79,9 @@ pub struct PacketSpaceManager<Config: endpoint::Config> {
     //# another mechanism is used for agreeing on an application protocol,
     //# endpoints MUST use ALPN for this purpose.
     pub application_protocol: Bytes,
+    /// Address validation token state, which is moved into the application space once it is
+    /// created
+    new_token: new_token::Manager,
 }
 
 impl<Config: endpoint::Config> fmt::Debug for PacketSpaceManager<Config> {
@@ -111,6 +116,7 @@ impl<Config: endpoint::Config> PacketSpaceManager<Config> {
         initial_key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::InitialKey,
         header_key: <<Config::TLSEndpoint as tls::Endpoint>::Session as CryptoSuite>::InitialHeaderKey,
         now: Timestamp,
+        new_token: new_token::Manager,
         publisher: &mut Pub,
     ) -> Self {
         let ack_manager = AckManager::new(PacketNumberSpace::Initial, ack::Settings::EARLY);
@@ -137,6 +143,7 @@ impl<Config: endpoint::Config> PacketSpaceManager<Config> {
             handshake_status: HandshakeStatus::default(),
             server_name: None,
             application_protocol: Bytes::new(),
+            new_token,
         }
     }
 
@@ -146,6 +153,19 @@ impl<Config: endpoint::Config> PacketSpaceManager<Config> {
 
     packet_space_api!(ApplicationSpace<Config>, application, application_mut);
 
+    /// Returns `true` if the client received an address validation token which hasn't been moved
+    /// into the token store yet
+    pub fn has_pending_token(&self) -> bool {
+        self.application()
+            .map_or(false, |space| space.new_token.has_pending_token())
+    }
+
+    /// Returns the most recent address validation token received from the server along with the
+    /// name of the server
+    pub fn take_pending_token(&mut self) -> Option<(ServerName, Vec<u8>)> {
+        self.application.as_mut()?.new_token.take_pending_token()
+    }
+
     #[allow(dead_code)] // 0RTT hasn't been started yet
     pub fn zero_rtt_crypto(
         &self,
@@ -264,6 +284,7 @@ impl<Config: endpoint::Config> PacketSpaceManager<Config> {
                 limits,
                 server_name: &mut self.server_name,
                 application_protocol: &mut self.application_protocol,
+                new_token: &mut self.new_token,
                 waker,
                 publisher,
                 datagram,
@@ -310,6 +331,7 @@ impl<Config: endpoint::Config> PacketSpaceManager<Config> {
                 limits,
                 server_name: &mut self.server_name,
                 application_protocol: &mut self.application_protocol,
+                new_token: &mut self.new_token,
                 waker,
                 publisher,
                 datagram,
@@ -794,6 +816,8 @@ pub trait PacketSpace<Config: endpoint::Config>: Sized {
     default_frame_handler!(handle_stream_data_blocked_frame, StreamDataBlocked);
     default_frame_handler!(handle_streams_blocked_frame, StreamsBlocked);
     default_frame_handler!(handle_new_token_frame, NewToken);
+    default_frame_handler!(handle_ack_frequency_frame, AckFrequency);
+    default_frame_handler!(handle_immediate_ack_frame, ImmediateAck);
 
     fn on_processed_packet<Pub: event::ConnectionPublisher>(
         &mut self,
@@ -1042,6 +1066,15 @@ pub trait PacketSpace<Config: endpoint::Config>: Sized {
                     )
                     .map_err(on_error)?;
                 }
+                Frame::AckFrequency(frame) => {
+                    let on_error = on_frame_processed!(frame);
+                    self.handle_ack_frequency_frame(frame).map_err(on_error)?;
+                }
+                Frame::ImmediateAck(frame) => {
+                    let on_error = on_frame_processed!(frame);
+                    self.handle_immediate_ack_frame(frame).map_err(on_error)?;
+                    processed_packet.immediate_ack = true;
+                }
             }
 
             payload = remaining;
@@ -0,0 +1,217 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Issues and receives address validation tokens in NEW_TOKEN frames
+//!
+//! Servers deliver a single token once the handshake is confirmed. Clients hold on to the most
+//! recent token until the endpoint moves it into the token store.
+
+use crate::{
+    contexts::WriteContext,
+    sync::{flag, flag::Flag},
+    transmission,
+};
+use s2n_quic_core::{ack, application::ServerName, frame::NewToken, packet::number::PacketNumber};
+
+#[derive(Debug, Default)]
+pub struct Manager {
+    /// The token the server issues once the handshake is confirmed
+    token_to_issue: Option<Vec<u8>>,
+
+    /// Delivers the NEW_TOKEN frame to the client
+    issue: Flag<NewTokenWriter>,
+
+    /// The name of the server the client is connected to
+    server_name: Option<ServerName>,
+
+    /// The most recent token received from the server
+    received: Vec<u8>,
+
+    /// Set if the received token hasn't been moved into the token store yet
+    has_pending_token: bool,
+}
+
+impl Manager {
+    /// Creates a manager for a server which issues `token` once the handshake is confirmed
+    pub fn server(token: Option<Vec<u8>>) -> Self {
+        Self {
+            token_to_issue: token,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a manager for a client connected to `server_name`
+    pub fn client(server_name: ServerName) -> Self {
+        Self {
+            server_name: Some(server_name),
+            ..Default::default()
+        }
+    }
+
+    /// Called when the handshake is confirmed
+    pub fn on_handshake_confirmed(&mut self) {
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
+        //# The server uses the NEW_TOKEN frame (Section 19.7) to provide the
+        //# client with an address validation token that can be used to validate
+        //# future connections.
+        if let Some(token) = self.token_to_issue.take() {
+            self.issue = Flag::new(NewTokenWriter { token: Some(token) });
+            self.issue.send();
+        }
+    }
+
+    /// Called when the client receives a NEW_TOKEN frame
+    pub fn on_new_token_frame(&mut self, frame: &NewToken) {
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-19.7
+        //# Clients are responsible for discarding
+        //# duplicate values, which might be used to link connection attempts;
+        //# see Section 8.1.3.
+        if self.received == frame.token {
+            return;
+        }
+
+        self.received.clear();
+        self.received.extend_from_slice(frame.token);
+        self.has_pending_token = true;
+    }
+
+    /// Returns `true` if a received token needs to be moved into the token store
+    #[inline]
+    pub fn has_pending_token(&self) -> bool {
+        self.has_pending_token && self.server_name.is_some()
+    }
+
+    /// Returns the most recently received token along with the name of the server that issued it
+    pub fn take_pending_token(&mut self) -> Option<(ServerName, Vec<u8>)> {
+        if !self.has_pending_token() {
+            return None;
+        }
+
+        self.has_pending_token = false;
+        let server_name = self.server_name.clone()?;
+        Some((server_name, self.received.clone()))
+    }
+
+    /// Called when an outgoing packet is being assembled
+    #[inline]
+    pub fn on_transmit<W: WriteContext>(&mut self, context: &mut W) {
+        let _ = self.issue.on_transmit(context);
+    }
+
+    /// This method gets called when a packet delivery got acknowledged
+    #[inline]
+    pub fn on_packet_ack<A: ack::Set>(&mut self, ack_set: &A) {
+        self.issue.on_packet_ack(ack_set);
+    }
+
+    /// This method gets called when a packet loss is reported
+    #[inline]
+    pub fn on_packet_loss<A: ack::Set>(&mut self, ack_set: &A) {
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-13.3
+        //# NEW_TOKEN frames are retransmitted if the packet containing them
+        //# is lost.
+        self.issue.on_packet_loss(ack_set);
+    }
+}
+
+impl transmission::interest::Provider for Manager {
+    #[inline]
+    fn transmission_interest<Q: transmission::interest::Query>(
+        &self,
+        query: &mut Q,
+    ) -> transmission::interest::Result {
+        self.issue.transmission_interest(query)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct NewTokenWriter {
+    token: Option<Vec<u8>>,
+}
+
+impl flag::Writer for NewTokenWriter {
+    fn write_frame<W: WriteContext>(&mut self, context: &mut W) -> Option<PacketNumber> {
+        let token = self.token.as_deref()?;
+        context.write_frame(&NewToken { token })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        contexts::testing::{MockWriteContext, OutgoingFrameBuffer},
+        transmission::interest::Provider,
+    };
+    use s2n_quic_core::{endpoint, frame::Frame, time::clock::testing as time};
+
+    fn write_frames(manager: &mut Manager) -> OutgoingFrameBuffer {
+        let mut frame_buffer = OutgoingFrameBuffer::new();
+        let mut context = MockWriteContext::new(
+            time::now(),
+            &mut frame_buffer,
+            transmission::Constraint::None,
+            transmission::Mode::Normal,
+            endpoint::Type::Server,
+        );
+        manager.on_transmit(&mut context);
+        frame_buffer
+    }
+
+    #[test]
+    fn issue_test() {
+        let mut manager = Manager::server(Some(vec![1, 2, 3]));
+
+        // the token is only sent once the handshake is confirmed
+        assert!(!manager.has_transmission_interest());
+        assert!(write_frames(&mut manager).is_empty());
+
+        manager.on_handshake_confirmed();
+        assert!(manager.has_transmission_interest());
+
+        let mut frame_buffer = write_frames(&mut manager);
+        let mut frame = frame_buffer.pop_front().unwrap();
+        assert_eq!(
+            frame.as_frame(),
+            Frame::NewToken(NewToken { token: &[1, 2, 3] })
+        );
+        assert!(frame_buffer.is_empty());
+        assert!(!manager.has_transmission_interest());
+    }
+
+    #[test]
+    fn no_token_test() {
+        let mut manager = Manager::server(None);
+
+        manager.on_handshake_confirmed();
+        assert!(!manager.has_transmission_interest());
+        assert!(write_frames(&mut manager).is_empty());
+    }
+
+    #[test]
+    fn receive_test() {
+        let server_name: ServerName = "example.com".into();
+        let mut manager = Manager::client(server_name.clone());
+
+        assert!(!manager.has_pending_token());
+        assert_eq!(manager.take_pending_token(), None);
+
+        manager.on_new_token_frame(&NewToken { token: &[1, 2, 3] });
+        assert!(manager.has_pending_token());
+        assert_eq!(
+            manager.take_pending_token(),
+            Some((server_name.clone(), vec![1, 2, 3]))
+        );
+        assert!(!manager.has_pending_token());
+
+        // duplicate tokens are discarded
+        manager.on_new_token_frame(&NewToken { token: &[1, 2, 3] });
+        assert!(!manager.has_pending_token());
+
+        manager.on_new_token_frame(&NewToken { token: &[4, 5, 6] });
+        assert_eq!(
+            manager.take_pending_token(),
+            Some((server_name, vec![4, 5, 6]))
+        );
+    }
+}
@@ -6,8 +6,8 @@ use crate::{
     connection::{self, limits::Limits},
     endpoint, path,
     space::{
-        datagram, keep_alive::KeepAlive, ApplicationSpace, HandshakeSpace, HandshakeStatus,
-        InitialSpace,
+        ack_frequency, datagram, keep_alive::KeepAlive, new_token, ApplicationSpace,
+        HandshakeSpace, HandshakeStatus, InitialSpace,
     },
     stream,
 };
@@ -30,8 +30,8 @@ use s2n_quic_core::{
         self,
         parameters::{
             ActiveConnectionIdLimit, ClientTransportParameters, DatagramLimits,
-            InitialFlowControlLimits, InitialSourceConnectionId, MaxAckDelay,
-            ServerTransportParameters,
+            InitialFlowControlLimits, InitialSourceConnectionId, MaxAckDelay, MigrationSupport,
+            MinAckDelay, ServerTransportParameters,
         },
     },
 };
@@ -52,6 +52,7 @@ pub struct SessionContext<'a, Config: endpoint::Config, Pub: event::ConnectionPu
     pub limits: &'a mut Limits,
     pub server_name: &'a mut Option<ServerName>,
     pub application_protocol: &'a mut Bytes,
+    pub new_token: &'a mut new_token::Manager,
     pub waker: &'a Waker,
     pub publisher: &'a mut Pub,
     pub datagram: &'a mut Config::DatagramEndpoint,
@@ -70,6 +71,7 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
             ActiveConnectionIdLimit,
             DatagramLimits,
             MaxAckDelay,
+            Option<MinAckDelay>,
         ),
         transport::Error,
     > {
@@ -163,6 +165,20 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
                 .with_reason("missing original_destination_connection_id"));
         }
 
+        // https://www.rfc-editor.org/rfc/rfc9368#section-4
+        // Clients MUST validate that the server's Chosen Version is equal to the
+        // negotiated version.
+        //
+        // The client never reacts to Version Negotiation packets, so there's no
+        // incompatible negotiation to check for a downgrade.
+        if let Some(version_information) = peer_parameters.version_information {
+            version_information.validate_server(
+                self.publisher.quic_version(),
+                endpoint::version::SUPPORTED_VERSIONS,
+                false,
+            )?;
+        }
+
         //= https://www.rfc-editor.org/rfc/rfc9000#section-10.3
         //# Servers can also issue a stateless_reset_token transport parameter during the
         //# handshake that applies to the connection ID that it selected during
@@ -176,6 +192,13 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
                 .register_initial_stateless_reset_token(stateless_reset_token);
         }
 
+        if matches!(
+            peer_parameters.migration_support,
+            MigrationSupport::Disabled
+        ) {
+            self.path_manager.on_active_migration_disabled();
+        }
+
         // Load the peer's transport parameters into the connection's limits
         self.limits.load_peer(&peer_parameters);
 
@@ -188,6 +211,7 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
             active_connection_id_limit,
             datagram_limits,
             peer_parameters.max_ack_delay,
+            peer_parameters.min_ack_delay,
         ))
     }
 
@@ -201,6 +225,7 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
             ActiveConnectionIdLimit,
             DatagramLimits,
             MaxAckDelay,
+            Option<MinAckDelay>,
         ),
         transport::Error,
     > {
@@ -234,6 +259,12 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
                 .as_bytes(),
         )?;
 
+        // the client's Chosen Version must be the version of the packets it sent, since the
+        // server never switches to a compatible version
+        if let Some(version_information) = peer_parameters.version_information {
+            version_information.validate_client(self.publisher.quic_version())?;
+        }
+
         // Load the peer's transport parameters into the connection's limits
         self.limits.load_peer(&peer_parameters);
 
@@ -246,6 +277,7 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
             active_connection_id_limit,
             datagram_limits,
             peer_parameters.max_ack_delay,
+            peer_parameters.min_ack_delay,
         ))
     }
 
@@ -373,11 +405,16 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
 
         // Parse transport parameters
         let param_decoder = DecoderBuffer::new(application_parameters.transport_parameters);
-        let (peer_flow_control_limits, active_connection_id_limit, datagram_limits, max_ack_delay) =
-            match Config::ENDPOINT_TYPE {
-                endpoint::Type::Client => self.on_server_params(param_decoder)?,
-                endpoint::Type::Server => self.on_client_params(param_decoder)?,
-            };
+        let (
+            peer_flow_control_limits,
+            active_connection_id_limit,
+            datagram_limits,
+            max_ack_delay,
+            min_ack_delay,
+        ) = match Config::ENDPOINT_TYPE {
+            endpoint::Type::Client => self.on_server_params(param_decoder)?,
+            endpoint::Type::Server => self.on_client_params(param_decoder)?,
+        };
 
         self.local_id_registry
             .set_active_connection_id_limit(active_connection_id_limit.as_u64());
@@ -393,8 +430,15 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
         let ack_manager = AckManager::new(
             PacketNumberSpace::ApplicationData,
             self.limits.ack_settings(),
+        )
+        .with_min_ack_delay(
+            self.limits
+                .min_ack_delay()
+                .map(|min_ack_delay| min_ack_delay.as_duration()),
         );
 
+        let ack_frequency = ack_frequency::Manager::new(self.limits, min_ack_delay, max_ack_delay);
+
         let keep_alive = KeepAlive::new(
             self.limits.max_idle_timeout(),
             self.limits.max_keep_alive_period(),
@@ -409,6 +453,13 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
             datagram_limits.max_datagram_payload,
         );
 
+        // The peer may delay acknowledgements for longer than its max_ack_delay after
+        // receiving the ACK_FREQUENCY frame, so the larger value is used for the PTO
+        let max_ack_delay = ack_frequency
+            .requested_max_ack_delay()
+            .and_then(|requested| MaxAckDelay::try_from(requested).ok())
+            .map_or(max_ack_delay, |requested| requested.max(max_ack_delay));
+
         self.path_manager
             .active_path_mut()
             .rtt_estimator
@@ -425,6 +476,8 @@ impl<'a, Config: endpoint::Config, Pub: event::ConnectionPublisher>
             keep_alive,
             max_mtu,
             datagram_manager,
+            ack_frequency,
+            core::mem::take(self.new_token),
         )));
         self.publisher.on_key_update(event::builder::KeyUpdate {
             key_type: event::builder::KeyType::OneRtt { generation: 0 },
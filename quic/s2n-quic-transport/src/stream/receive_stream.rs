@@ -109,7 +109,8 @@ impl MissingData {
         // Instead, we just use a simple range
 
         let frame_start = *frame.offset;
-        let frame_end = *(frame.offset + frame.data.len());
+        // the frame is provided by the peer so make sure we don't overflow
+        let frame_end = frame_start.saturating_add(frame.data.len() as u64);
         let frame_range = frame_start..frame_end;
 
         // update the start if it overlaps the offset of the frame
@@ -65,6 +65,14 @@ impl Default for DeliveryState {
 }
 
 impl<W: Writer> Flag<W> {
+    /// Creates a flag which transmits frames with the given writer
+    pub fn new(writer: W) -> Self {
+        Self {
+            delivery: DeliveryState::default(),
+            writer,
+        }
+    }
+
     /// Returns `true` if the flag hasn't been sent
     pub fn is_idle(&self) -> bool {
         matches!(self.delivery, DeliveryState::Idle)
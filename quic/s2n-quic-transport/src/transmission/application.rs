@@ -8,7 +8,7 @@ use crate::{
     endpoint, path,
     path::mtu,
     recovery,
-    space::{datagram, CryptoStream, HandshakeStatus},
+    space::{ack_frequency, datagram, new_token, CryptoStream, HandshakeStatus},
     stream::Manager as _,
     sync::{flag, flag::Ping},
     transmission::{self, Mode, Provider as _},
@@ -39,6 +39,8 @@ impl<'a, Config: endpoint::Config> Payload<'a, Config> {
         recovery_manager: &'a mut recovery::Manager<Config>,
         crypto_stream: &'a mut CryptoStream,
         datagram_manager: &'a mut datagram::Manager<Config>,
+        ack_frequency: &'a mut ack_frequency::Manager,
+        new_token: &'a mut new_token::Manager,
     ) -> Self {
         if transmission_mode != Mode::PathValidationOnly {
             debug_assert_eq!(path_id, path_manager.active_path_id());
@@ -56,6 +58,8 @@ impl<'a, Config: endpoint::Config> Payload<'a, Config> {
                     recovery_manager,
                     crypto_stream,
                     datagram_manager,
+                    ack_frequency,
+                    new_token,
                     prioritize_datagrams: false,
                 })
             }
@@ -113,6 +117,8 @@ pub struct Normal<'a, Config: endpoint::Config> {
     recovery_manager: &'a mut recovery::Manager<Config>,
     crypto_stream: &'a mut CryptoStream,
     datagram_manager: &'a mut datagram::Manager<Config>,
+    ack_frequency: &'a mut ack_frequency::Manager,
+    new_token: &'a mut new_token::Manager,
     prioritize_datagrams: bool,
 }
 
@@ -121,6 +127,10 @@ impl<'a, Config: endpoint::Config> Normal<'a, Config> {
         let can_transmit = context.transmission_constraint().can_transmit()
             || context.transmission_constraint().can_retransmit();
 
+        // Record the space in the empty packet so the datagram sender can size
+        // datagrams to the current path MTU
+        let packet_capacity = context.remaining_capacity();
+
         //= https://www.rfc-editor.org/rfc/rfc9221#section-5
         //# DATAGRAM frames cannot be fragmented;
         //
@@ -133,6 +143,7 @@ impl<'a, Config: endpoint::Config> Normal<'a, Config> {
                 context,
                 self.stream_manager,
                 self.prioritize_datagrams,
+                packet_capacity,
             );
         }
         let did_send_ack = self.ack_manager.on_transmit(context);
@@ -148,6 +159,7 @@ impl<'a, Config: endpoint::Config> Normal<'a, Config> {
                     context,
                     self.stream_manager,
                     self.prioritize_datagrams,
+                    packet_capacity,
                 );
             }
 
@@ -188,6 +200,10 @@ impl<'a, Config: endpoint::Config> Normal<'a, Config> {
         self.local_id_registry.on_transmit(context);
 
         self.path_manager.on_transmit(context);
+
+        self.ack_frequency.on_transmit(context);
+
+        self.new_token.on_transmit(context);
     }
 }
 
@@ -200,6 +216,8 @@ impl<'a, Config: endpoint::Config> transmission::interest::Provider for Normal<'
         self.handshake_status.transmission_interest(query)?;
         self.stream_manager.transmission_interest(query)?;
         self.datagram_manager.transmission_interest(query)?;
+        self.ack_frequency.transmission_interest(query)?;
+        self.new_token.transmission_interest(query)?;
         self.local_id_registry.transmission_interest(query)?;
         self.path_manager.transmission_interest(query)?;
         self.crypto_stream.transmission_interest(query)?;
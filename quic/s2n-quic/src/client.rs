@@ -86,11 +86,14 @@ impl Client {
 
     /// Establishes a connection to the specified endpoint
     ///
+    /// The peer's certificate is verified against the server name of the attempt, or against the
+    /// remote address if no server name was specified. See [`Connect::with_server_name`].
+    ///
     /// # Examples
     ///
     /// ```rust,no_run
     /// # use std::error::Error;
-    /// use s2n_quic::Client;
+    /// use s2n_quic::{client::Connect, Client};
     /// use std::{net::SocketAddr, path::Path};
     ///
     /// # async fn connect() -> Result<(), Box<dyn Error>> {
@@ -100,7 +103,8 @@ impl Client {
     ///     .start()?;
     ///
     /// let addr: SocketAddr = "127.0.0.1:443".parse()?;
-    /// let connection = client.connect(addr.into()).await?;
+    /// let connect = Connect::new(addr).with_server_name("localhost");
+    /// let connection = client.connect(connect).await?;
     /// #
     /// #    Ok(())
     /// # }
@@ -123,7 +127,7 @@ impl Client {
     ///
     /// ```rust,no_run
     /// # use std::error::Error;
-    /// use s2n_quic::Client;
+    /// use s2n_quic::{client::Connect, Client};
     /// use std::{net::SocketAddr, path::Path};
     ///
     /// # async fn connect() -> Result<(), Box<dyn Error>> {
@@ -133,7 +137,8 @@ impl Client {
     ///     .start()?;
     ///
     /// let addr: SocketAddr = "127.0.0.1:443".parse()?;
-    /// let connection = client.connect(addr.into()).await?;
+    /// let connect = Connect::new(addr).with_server_name("localhost");
+    /// let connection = client.connect(connect).await?;
     ///
     /// client.wait_idle().await?;
     /// #
@@ -276,6 +276,31 @@ impl<Providers: ClientProviders> Builder<Providers> {
         ClientProviders
     );
 
+    impl_provider_method!(
+        /// Sets the address token store provider for the [`Client`]
+        ///
+        /// # Examples
+        ///
+        /// Retains tokens issued by up to 16 servers
+        ///
+        /// ```rust,no_run
+        /// # use std::error::Error;
+        /// use s2n_quic::{Client, provider::address_token_store};
+        /// #
+        /// # #[tokio::main]
+        /// # async fn main() -> Result<(), Box<dyn Error>> {
+        /// let client = Client::builder()
+        ///     .with_address_token_store(address_token_store::Bounded::new(16))?
+        ///     .start()?;
+        /// #
+        /// #    Ok(())
+        /// # }
+        /// ```
+        with_address_token_store,
+        address_token_store,
+        ClientProviders
+    );
+
     /// Starts the [`Client`] with the configured providers
     ///
     /// # Examples
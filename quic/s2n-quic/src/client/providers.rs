@@ -21,6 +21,7 @@ impl_providers_state! {
         sync: Sync,
         tls: Tls,
         datagram: Datagram,
+        address_token_store: AddressTokenStore,
     }
 
     /// Opaque trait containing all of the configured providers
@@ -40,6 +41,7 @@ impl<
         Sync: sync::Provider,
         Tls: tls::Provider,
         Datagram: datagram::Provider,
+        AddressTokenStore: address_token_store::Provider,
     >
     Providers<
         CongestionController,
@@ -54,6 +56,7 @@ impl<
         Sync,
         Tls,
         Datagram,
+        AddressTokenStore,
     >
 {
     pub fn start(self) -> Result<Client, StartError> {
@@ -70,6 +73,7 @@ impl<
             sync,
             tls,
             datagram,
+            address_token_store,
         } = self;
 
         let congestion_controller = congestion_controller.start().map_err(StartError::new)?;
@@ -88,6 +92,7 @@ impl<
         let path_migration = PathMigration;
         let tls = tls.start_client().map_err(StartError::new)?;
         let datagram = datagram.start().map_err(StartError::new)?;
+        let token_store = address_token_store.start().map_err(StartError::new)?;
 
         // Validate providers
         // TODO: Add more validation https://github.com/aws/s2n-quic/issues/285
@@ -114,6 +119,7 @@ impl<
             sync,
             tls,
             token,
+            token_store,
             path_handle: PhantomData,
             path_migration,
             datagram,
@@ -202,6 +208,7 @@ struct EndpointConfig<
     Sync,
     Tls,
     Datagram,
+    TokenStore,
 > {
     congestion_controller: CongestionController,
     connection_close_formatter: ConnectionCloseFormatter,
@@ -215,6 +222,7 @@ struct EndpointConfig<
     sync: Sync,
     tls: Tls,
     token: Token,
+    token_store: TokenStore,
     path_handle: PhantomData<PathHandle>,
     path_migration: PathMigration,
     datagram: Datagram,
@@ -233,6 +241,7 @@ impl<
         Sync,
         Tls: crypto::tls::Endpoint,
         Datagram: s2n_quic_core::datagram::Endpoint,
+        TokenStore: address_token_store::Store,
     > core::fmt::Debug
     for EndpointConfig<
         CongestionController,
@@ -247,6 +256,7 @@ impl<
         Sync,
         Tls,
         Datagram,
+        TokenStore,
     >
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -267,6 +277,7 @@ impl<
         Sync: 'static + Send,
         Tls: crypto::tls::Endpoint,
         Datagram: s2n_quic_core::datagram::Endpoint,
+        TokenStore: address_token_store::Store,
     > endpoint::Config
     for EndpointConfig<
         CongestionController,
@@ -281,6 +292,7 @@ impl<
         Sync,
         Tls,
         Datagram,
+        TokenStore,
     >
 {
     type ConnectionIdFormat = ConnectionID;
@@ -296,6 +308,7 @@ impl<
     type EventSubscriber = Event;
     type TLSEndpoint = Tls;
     type TokenFormat = Token;
+    type TokenStore = TokenStore;
     type ConnectionLimits = Limits;
     type StreamManager = stream::DefaultStreamManager;
     type PathMigrationValidator = PathMigration;
@@ -315,6 +328,7 @@ impl<
             tls: &mut self.tls,
             endpoint_limits: &mut self.endpoint_limits,
             token: &mut self.token,
+            token_store: &mut self.token_store,
             connection_limits: &mut self.limits,
             event_subscriber: &mut self.event,
             path_migration: &mut self.path_migration,
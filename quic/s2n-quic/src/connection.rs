@@ -11,7 +11,7 @@ mod handle;
 
 pub use acceptor::*;
 pub use handle::*;
-pub use s2n_quic_core::connection::Error;
+pub use s2n_quic_core::{connection::Error, path::migration::Error as MigrationError};
 
 pub mod error {
     pub use s2n_quic_core::transport::error::Code;
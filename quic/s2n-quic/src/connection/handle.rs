@@ -186,6 +186,52 @@ macro_rules! impl_handle_api {
             self.0.keep_alive(enabled)
         }
 
+        /// Migrates the connection to a new local address
+        ///
+        /// The connection validates the path from the new address with a PATH_CHALLENGE and
+        /// only switches to it once the server responds. If the validation fails, the connection
+        /// continues using the original path and
+        /// [`MigrationError::ValidationTimeout`](`crate::connection::MigrationError::ValidationTimeout`)
+        /// is returned.
+        ///
+        /// Note that the IO provider must be able to send and receive packets on the new local
+        /// address. Only clients can initiate a migration.
+        ///
+        /// # Examples
+        ///
+        /// ```rust,no_run
+        /// # async fn test() -> Result<(), s2n_quic::connection::MigrationError> {
+        /// #   let mut connection: s2n_quic::connection::Handle = todo!();
+        /// #
+        /// connection.migrate("192.168.1.2:4433".parse().unwrap()).await?;
+        /// #
+        /// #   Ok(())
+        /// # }
+        /// ```
+        #[inline]
+        pub async fn migrate(
+            &mut self,
+            local_addr: std::net::SocketAddr,
+        ) -> core::result::Result<(), $crate::connection::MigrationError> {
+            self.0.migrate(local_addr.into())?;
+            futures::future::poll_fn(|cx| self.poll_migrate(cx)).await
+        }
+
+        /// Polls for the outcome of a migration started with [`Self::migrate`]
+        ///
+        /// The method will return
+        /// - `Poll::Ready(Ok(()))` if the connection switched to the new local address or no
+        ///   migration is in progress
+        /// - `Poll::Ready(Err(migration_error))` if the migration failed
+        /// - `Poll::Pending` if the new path is still being validated
+        #[inline]
+        pub fn poll_migrate(
+            &mut self,
+            cx: &mut core::task::Context,
+        ) -> core::task::Poll<core::result::Result<(), $crate::connection::MigrationError>> {
+            self.0.poll_migrate(cx)
+        }
+
         /// Closes the Connection with the provided error code
         ///
         /// This will immediately terminate all outstanding streams.
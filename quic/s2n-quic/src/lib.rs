@@ -78,11 +78,14 @@ mod tests;
         // add new unstable features to this list
         any(
             feature = "unstable_client_hello",
+            feature = "unstable_dangerous_configs",
+            feature = "unstable_message_handler",
             feature = "unstable-provider-datagram",
             feature = "unstable-provider-io-testing",
             feature = "unstable-provider-io-turmoil",
             feature = "unstable-provider-io-xdp",
             feature = "unstable-provider-packet-interceptor",
+            feature = "unstable-provider-path-migration",
             feature = "unstable-provider-random",
             feature = "unstable-congestion-controller",
             feature = "unstable_resumption",
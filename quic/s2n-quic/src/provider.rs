@@ -8,6 +8,7 @@ use core::fmt;
 mod macros;
 
 pub mod address_token;
+pub mod address_token_store;
 pub mod congestion_controller;
 pub mod connection_id;
 pub mod endpoint_limits;
@@ -19,7 +20,6 @@ pub mod tls;
 
 // These providers are not currently exposed to applications
 pub(crate) mod connection_close_formatter;
-pub(crate) mod path_migration;
 pub(crate) mod sync;
 
 cfg_if!(
@@ -30,6 +30,14 @@ cfg_if!(
     }
 );
 
+cfg_if!(
+    if #[cfg(any(test, feature = "unstable-provider-path-migration"))] {
+        pub mod path_migration;
+    } else {
+        pub(crate) mod path_migration;
+    }
+);
+
 cfg_if!(
     if #[cfg(any(test, feature = "unstable-provider-random"))] {
         pub mod random;
@@ -6,7 +6,9 @@
 //! The default provider will randomly generate a 256 bit key. This key will be used to sign and
 //! verify tokens. The key can be rotated at a duration set by the user.
 //!
-//! The default provider does not support tokens delivered in a NEW_TOKEN frame.
+//! Tokens delivered in a NEW_TOKEN frame are signed with a separate set of keys which rotate less
+//! often. They are only bound to the client IP address, since the client port and connection IDs
+//! change between connections.
 
 use core::{mem::size_of, time::Duration};
 use hash_hasher::HashHasher;
@@ -29,16 +31,27 @@ struct BaseKey {
     //# To protect against such attacks, servers MUST ensure that
     //# replay of tokens is prevented or limited.
     duplicate_filter: cuckoofilter::CuckooFilter<HashHasher>,
+
+    // Number of tokens the duplicate filter is sized for
+    filter_capacity: usize,
+
+    // Set once the duplicate filter failed to record a token. A failed insertion evicts an
+    // earlier entry, so replays can no longer be detected until the key is regenerated.
+    filter_saturated: bool,
 }
 
 impl BaseKey {
     pub fn new(active_duration: Duration) -> Self {
+        Self::with_filter_capacity(active_duration, cuckoofilter::DEFAULT_CAPACITY)
+    }
+
+    pub fn with_filter_capacity(active_duration: Duration, filter_capacity: usize) -> Self {
         Self {
             active_duration,
             key: None,
-            duplicate_filter: cuckoofilter::CuckooFilter::with_capacity(
-                cuckoofilter::DEFAULT_CAPACITY,
-            ),
+            duplicate_filter: cuckoofilter::CuckooFilter::with_capacity(filter_capacity),
+            filter_capacity,
+            filter_saturated: false,
         }
     }
 
@@ -70,8 +83,8 @@ impl BaseKey {
 
         // TODO clear the filter instead of recreating. This is pending a merge to crates.io
         // (https://github.com/axiomhq/rust-cuckoofilter/pull/52)
-        self.duplicate_filter =
-            cuckoofilter::CuckooFilter::with_capacity(cuckoofilter::DEFAULT_CAPACITY);
+        self.duplicate_filter = cuckoofilter::CuckooFilter::with_capacity(self.filter_capacity);
+        self.filter_saturated = false;
 
         self.key = Some((expires_at, key));
 
@@ -80,6 +93,7 @@ impl BaseKey {
 }
 
 const DEFAULT_KEY_ROTATION_PERIOD: Duration = Duration::from_millis(1000);
+const DEFAULT_NEW_TOKEN_KEY_ROTATION_PERIOD: Duration = Duration::from_secs(60 * 60);
 
 #[derive(Debug)]
 pub struct Provider {
@@ -91,12 +105,22 @@ pub struct Provider {
     /// To fulfill this SHOULD, we rotate the key periodically. This allows
     /// customers to control the token lifetime without adding bytes to the token itself.
     key_rotation_period: Duration,
+
+    //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
+    //# Unlike the token that is created for a Retry packet, which is used
+    //# immediately, the token sent in the NEW_TOKEN frame can be used after
+    //# some period of time has passed.
+    /// Tokens from NEW_TOKEN frames expire after at most two of these periods. If more tokens are
+    /// redeemed within that time than the duplicate filter can record, the remaining tokens signed
+    /// with the same key are rejected and the clients fall back to a Retry.
+    new_token_key_rotation_period: Duration,
 }
 
 impl Default for Provider {
     fn default() -> Self {
         Self {
             key_rotation_period: DEFAULT_KEY_ROTATION_PERIOD,
+            new_token_key_rotation_period: DEFAULT_NEW_TOKEN_KEY_ROTATION_PERIOD,
         }
     }
 }
@@ -116,6 +140,13 @@ impl super::Provider for Provider {
                 BaseKey::new(self.key_rotation_period * 2),
                 BaseKey::new(self.key_rotation_period * 2),
             ],
+            new_token_key_rotation_period: self.new_token_key_rotation_period,
+            current_new_token_key_rotates_at: s2n_quic_platform::time::now(),
+            current_new_token_key: 0,
+            new_token_keys: [
+                BaseKey::new(self.new_token_key_rotation_period * 2),
+                BaseKey::new(self.new_token_key_rotation_period * 2),
+            ],
         };
 
         Ok(format)
@@ -143,6 +174,18 @@ pub struct Format {
 
     /// Key used to sign keys
     keys: [BaseKey; 2],
+
+    /// Key validity period for tokens sent in NEW_TOKEN frames
+    new_token_key_rotation_period: Duration,
+
+    /// Timestamp to rotate the current NEW_TOKEN key
+    current_new_token_key_rotates_at: s2n_quic_core::time::Timestamp,
+
+    /// Which key is used to sign NEW_TOKEN tokens
+    current_new_token_key: u8,
+
+    /// Keys used to sign NEW_TOKEN tokens
+    new_token_keys: [BaseKey; 2],
 }
 
 impl Format {
@@ -158,6 +201,15 @@ impl Format {
         self.current_key
     }
 
+    fn current_new_token_key(&mut self) -> u8 {
+        let now = s2n_quic_platform::time::now();
+        if now > self.current_new_token_key_rotates_at {
+            self.current_new_token_key ^= 1;
+            self.current_new_token_key_rotates_at = now + self.new_token_key_rotation_period;
+        }
+        self.current_new_token_key
+    }
+
     // Retry Tokens need to include the original destination connection id from the transport
     // parameters. This OCID is included in the tag.
     fn tag_retry_token(
@@ -223,52 +275,107 @@ impl Format {
 
         None
     }
+
+    // NEW_TOKEN tokens are used on a later connection, which has different connection IDs and
+    // possibly a different port, so only the client IP address is included in the tag.
+    fn tag_new_token(
+        &mut self,
+        token: &Token,
+        context: &mut super::Context<'_>,
+    ) -> Option<hmac::Tag> {
+        let mut ctx = self.new_token_keys[token.header.key_id() as usize].hasher(context.random)?;
+
+        ctx.update(token.header.as_bytes());
+        ctx.update(&token.nonce);
+
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.4
+        //# Tokens sent in NEW_TOKEN frames MUST include information that allows
+        //# the server to verify that the client IP address has not changed from
+        //# when the token was issued.
+        match context.remote_address {
+            SocketAddress::IpV4 { ip, .. } => ctx.update(ip),
+            SocketAddress::IpV6 { ip, .. } => ctx.update(ip),
+            _ => {
+                // we are unable to hash the address so bail
+                return None;
+            }
+        };
+
+        Some(ctx.sign())
+    }
+
+    // Using the key id in the token, verify the token
+    fn verify_new_token(&mut self, context: &mut super::Context<'_>, token: &Token) -> bool {
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.4
+        //# Tokens that are provided
+        //# in NEW_TOKEN frames (Section 19.7) need to be valid for longer but
+        //# SHOULD NOT be accepted multiple times.
+        if self.new_token_keys[token.header.key_id() as usize]
+            .duplicate_filter
+            .contains(token)
+        {
+            return false;
+        }
+
+        let tag = match self.tag_new_token(token, context) {
+            Some(tag) => tag,
+            None => return false,
+        };
+
+        if constant_time::verify_slices_are_equal(&token.hmac, tag.as_ref()).is_err() {
+            return false;
+        }
+
+        // Only add the token once it has been validated.
+        //
+        // Unlike Retry tokens, NEW_TOKEN tokens stay valid for up to two rotation periods, which
+        // is long enough for the duplicate filter to fill up. Rather than accepting tokens which
+        // can no longer be checked for replays, every NEW_TOKEN token signed with this key is
+        // rejected once the filter fails to record one. Clients then fall back to the Retry
+        // round trip until the key is regenerated.
+        let key = &mut self.new_token_keys[token.header.key_id() as usize];
+        if key.filter_saturated || key.duplicate_filter.add(token).is_err() {
+            key.filter_saturated = true;
+            return false;
+        }
+
+        true
+    }
 }
 
 impl super::Format for Format {
     const TOKEN_LEN: usize = size_of::<Token>();
 
-    /// The default provider does not support NEW_TOKEN frame tokens
     fn generate_new_token(
         &mut self,
-        _context: &mut super::Context<'_>,
+        context: &mut super::Context<'_>,
         _source_connection_id: &connection::LocalId,
-        _output_buffer: &mut [u8],
+        output_buffer: &mut [u8],
     ) -> Option<()> {
-        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
-        //= type=TODO
-        //= tracking-issue=418
-        //# A server MAY provide clients with an address validation token during
-        //# one connection that can be used on a subsequent connection.
-
-        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.4
-        //= type=TODO
-        //= tracking-issue=346
-        //# Tokens sent in NEW_TOKEN frames MUST include information that allows
-        //# the server to verify that the client IP address has not changed from
-        //# when the token was issued.
+        let buffer = DecoderBufferMut::new(output_buffer);
+        let (token, _) = buffer
+            .decode::<&mut Token>()
+            .expect("Provided output buffer did not match TOKEN_LEN");
 
         //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
-        //= type=TODO
-        //= tracking-issue=345
         //# A token issued with NEW_TOKEN MUST NOT include information that would
         //# allow values to be linked by an observer to the connection on which
         //# it was issued.
+        token.header = Header::new(Source::NewTokenFrame, self.current_new_token_key());
+        token.odcid_len = 0;
+        token.original_destination_connection_id = [0; 20];
 
         //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
-        //= type=TODO
-        //= tracking-issue=387
         //# A server MUST ensure that every NEW_TOKEN frame it sends
         //# is unique across all clients, with the exception of those sent to
         //# repair losses of previously sent NEW_TOKEN frames.
+        context.random.public_random_fill(&mut token.nonce[..]);
 
-        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
-        //= type=TODO
-        //= tracking-issue=394
-        //# A server MAY provide clients with an address validation token during
-        //# one connection that can be used on a subsequent connection.
+        let tag = self.tag_new_token(token, context)?;
 
-        None
+        token.hmac.copy_from_slice(tag.as_ref());
+
+        Some(())
     }
 
     //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.2
@@ -338,20 +445,32 @@ impl super::Format for Format {
 
         match source {
             Source::RetryPacket => self.validate_retry_token(context, token),
-            Source::NewTokenFrame => None, // Not supported in the default provider
+            // NEW_TOKEN tokens are validated with `validate_new_token`
+            Source::NewTokenFrame => None,
         }
-        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.4
-        //= type=TODO
-        //= tracking-issue=347
-        //# Tokens that are provided
-        //# in NEW_TOKEN frames (Section 19.7) need to be valid for longer but
-        //# SHOULD NOT be accepted multiple times.
+    }
 
-        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.3
-        //= type=TODO
-        //= tracking-issue=388
-        //# Clients that want to break continuity of identity with a server can
-        //# discard tokens provided using the NEW_TOKEN frame.
+    fn validate_new_token(&mut self, context: &mut super::Context<'_>, token: &[u8]) -> bool {
+        let buffer = DecoderBuffer::new(token);
+        let token = match buffer.decode::<&Token>() {
+            Ok((token, remaining)) if remaining.is_empty() => token,
+            _ => return false,
+        };
+
+        if token.header.version() != TOKEN_VERSION {
+            return false;
+        }
+
+        match token.header.token_source() {
+            Source::NewTokenFrame => self.verify_new_token(context, token),
+            Source::RetryPacket => false,
+        }
+    }
+
+    fn token_source(&self, token: &[u8]) -> Source {
+        token
+            .first()
+            .map_or(Source::RetryPacket, |header| Header(*header).token_source())
     }
 }
 
@@ -471,6 +590,7 @@ mod tests {
     use std::{net::SocketAddr, sync::Arc};
 
     const TEST_KEY_ROTATION_PERIOD: Duration = Duration::from_millis(1000);
+    const TEST_NEW_TOKEN_KEY_ROTATION_PERIOD: Duration = Duration::from_secs(10);
 
     fn get_test_format() -> Format {
         Format {
@@ -481,6 +601,13 @@ mod tests {
             ],
             current_key_rotates_at: time::now(),
             current_key: 0,
+            new_token_key_rotation_period: TEST_NEW_TOKEN_KEY_ROTATION_PERIOD,
+            new_token_keys: [
+                BaseKey::new(TEST_NEW_TOKEN_KEY_ROTATION_PERIOD * 2),
+                BaseKey::new(TEST_NEW_TOKEN_KEY_ROTATION_PERIOD * 2),
+            ],
+            current_new_token_key_rotates_at: time::now(),
+            current_new_token_key: 0,
         }
     }
 
@@ -505,6 +632,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_token_source() {
+        let clock = Arc::new(time::testing::MockClock::new());
+        time::testing::set_local_clock(clock);
+
+        let mut format = get_test_format();
+        let conn_id = connection::PeerId::try_from_bytes(&[2, 4, 6, 8, 10]).unwrap();
+        let orig_conn_id =
+            connection::InitialId::try_from_bytes(&[0, 1, 2, 3, 4, 5, 6, 7]).unwrap();
+        let addr = SocketAddress::default();
+        let mut token = [0; Format::TOKEN_LEN];
+        let mut random = random::testing::Generator(5);
+        let mut context = Context::new(&addr, &conn_id, &mut random);
+
+        format
+            .generate_retry_token(&mut context, &orig_conn_id, &mut token)
+            .unwrap();
+        assert_eq!(format.token_source(&token), Source::RetryPacket);
+
+        let header = Header::new(Source::NewTokenFrame, 0);
+        assert_eq!(format.token_source(&[header.0]), Source::NewTokenFrame);
+        assert_eq!(format.token_source(&[]), Source::RetryPacket);
+    }
+
     #[test]
     fn test_valid_retry_tokens() {
         let clock = Arc::new(time::testing::MockClock::new());
@@ -758,4 +909,167 @@ mod tests {
                 assert!(format.validate_token(&mut context, token).is_none())
             });
     }
+
+    fn generate_new_token(format: &mut Format, addr: &SocketAddress) -> [u8; Format::TOKEN_LEN] {
+        let conn_id = connection::PeerId::try_from_bytes(&[2, 4, 6, 8, 10]).unwrap();
+        let local_id = connection::LocalId::TEST_ID;
+        let mut token = [0; Format::TOKEN_LEN];
+        let mut random = random::testing::Generator(5);
+        let mut context = Context::new(addr, &conn_id, &mut random);
+        format
+            .generate_new_token(&mut context, &local_id, &mut token)
+            .unwrap();
+        token
+    }
+
+    fn validate_new_token(format: &mut Format, addr: &SocketAddress, token: &[u8]) -> bool {
+        // the token is used on a new connection with different connection IDs
+        let conn_id = connection::PeerId::try_from_bytes(&[1, 3, 5, 7, 9]).unwrap();
+        let mut random = random::testing::Generator(5);
+        let mut context = Context::new(addr, &conn_id, &mut random);
+        format.validate_new_token(&mut context, token)
+    }
+
+    #[test]
+    fn test_valid_new_tokens() {
+        let clock = Arc::new(time::testing::MockClock::new());
+        time::testing::set_local_clock(clock);
+
+        let mut format = get_test_format();
+        let addr: SocketAddress = "127.0.0.1:443".parse::<SocketAddr>().unwrap().into();
+        let token = generate_new_token(&mut format, &addr);
+
+        assert_eq!(format.token_source(&token), Source::NewTokenFrame);
+
+        // NEW_TOKEN tokens are not accepted in place of Retry tokens
+        let conn_id = connection::PeerId::try_from_bytes(&[2, 4, 6, 8, 10]).unwrap();
+        let mut random = random::testing::Generator(5);
+        let mut context = Context::new(&addr, &conn_id, &mut random);
+        assert_eq!(format.validate_token(&mut context, &token), None);
+
+        assert!(validate_new_token(&mut format, &addr, &token));
+    }
+
+    #[test]
+    fn test_new_token_ip_validation() {
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.4
+        //= type=test
+        //# Tokens sent in NEW_TOKEN frames MUST include information that allows
+        //# the server to verify that the client IP address has not changed from
+        //# when the token was issued.
+        let clock = Arc::new(time::testing::MockClock::new());
+        time::testing::set_local_clock(clock);
+
+        let mut format = get_test_format();
+        let addr: SocketAddress = "127.0.0.1:443".parse::<SocketAddr>().unwrap().into();
+        let token = generate_new_token(&mut format, &addr);
+
+        let incorrect_address: SocketAddress =
+            "127.0.0.2:443".parse::<SocketAddr>().unwrap().into();
+        assert!(!validate_new_token(&mut format, &incorrect_address, &token));
+
+        // The port is expected to change between connections
+        let new_port: SocketAddress = "127.0.0.1:444".parse::<SocketAddr>().unwrap().into();
+        assert!(validate_new_token(&mut format, &new_port, &token));
+    }
+
+    #[test]
+    fn test_expired_new_token() {
+        let clock = Arc::new(time::testing::MockClock::new());
+        time::testing::set_local_clock(clock.clone());
+
+        let mut format = get_test_format();
+        let addr = SocketAddress::default();
+        let first_token = generate_new_token(&mut format, &addr);
+        let second_token = generate_new_token(&mut format, &addr);
+
+        // NEW_TOKEN tokens outlive Retry tokens
+        clock.adjust_by(TEST_KEY_ROTATION_PERIOD * 2);
+        assert!(validate_new_token(&mut format, &addr, &first_token));
+
+        clock.adjust_by(TEST_NEW_TOKEN_KEY_ROTATION_PERIOD * 2);
+        assert!(!validate_new_token(&mut format, &addr, &second_token));
+    }
+
+    #[test]
+    fn test_duplicate_new_token_detection() {
+        //= https://www.rfc-editor.org/rfc/rfc9000#section-8.1.4
+        //= type=test
+        //# Tokens that are provided
+        //# in NEW_TOKEN frames (Section 19.7) need to be valid for longer but
+        //# SHOULD NOT be accepted multiple times.
+        let clock = Arc::new(time::testing::MockClock::new());
+        time::testing::set_local_clock(clock);
+
+        let mut format = get_test_format();
+        let addr = SocketAddress::default();
+        let token = generate_new_token(&mut format, &addr);
+
+        assert!(validate_new_token(&mut format, &addr, &token));
+        assert!(!validate_new_token(&mut format, &addr, &token));
+    }
+
+    #[test]
+    fn test_saturated_new_token_filter() {
+        let clock = Arc::new(time::testing::MockClock::new());
+        time::testing::set_local_clock(clock.clone());
+
+        let mut format = get_test_format();
+        format.new_token_keys = [
+            BaseKey::with_filter_capacity(TEST_NEW_TOKEN_KEY_ROTATION_PERIOD * 2, 8),
+            BaseKey::with_filter_capacity(TEST_NEW_TOKEN_KEY_ROTATION_PERIOD * 2, 8),
+        ];
+        let addr = SocketAddress::default();
+        let conn_id = connection::PeerId::TEST_ID;
+        let local_id = connection::LocalId::TEST_ID;
+
+        let mut tokens = vec![];
+        for seed in 0..=u8::MAX {
+            let mut token = [0; Format::TOKEN_LEN];
+            let mut random = random::testing::Generator(seed);
+            let mut context = Context::new(&addr, &conn_id, &mut random);
+            format
+                .generate_new_token(&mut context, &local_id, &mut token)
+                .unwrap();
+            tokens.push(token);
+        }
+
+        let results: Vec<bool> = tokens
+            .iter()
+            .map(|token| validate_new_token(&mut format, &addr, token))
+            .collect();
+
+        // The filter can't hold every token, so some are rejected
+        let accepted = results.iter().filter(|accepted| **accepted).count();
+        assert!(accepted > 0);
+        assert!(accepted < tokens.len());
+
+        // Once a token is rejected because the filter is full, no token is accepted
+        let first_rejected = results.iter().position(|accepted| !accepted).unwrap();
+        assert!(results[first_rejected..].iter().all(|accepted| !accepted));
+
+        // The filter is cleared once the key is regenerated
+        clock.adjust_by(TEST_NEW_TOKEN_KEY_ROTATION_PERIOD * 3);
+        let token = generate_new_token(&mut format, &addr);
+        assert!(validate_new_token(&mut format, &addr, &token));
+    }
+
+    #[test]
+    fn test_retry_token_as_new_token() {
+        let clock = Arc::new(time::testing::MockClock::new());
+        time::testing::set_local_clock(clock);
+
+        let mut format = get_test_format();
+        let conn_id = connection::PeerId::TEST_ID;
+        let orig_conn_id = connection::InitialId::TEST_ID;
+        let addr = SocketAddress::default();
+        let mut token = [0; Format::TOKEN_LEN];
+        let mut random = random::testing::Generator(5);
+        let mut context = Context::new(&addr, &conn_id, &mut random);
+        format
+            .generate_retry_token(&mut context, &orig_conn_id, &mut token)
+            .unwrap();
+
+        assert!(!format.validate_new_token(&mut context, &token));
+    }
 }
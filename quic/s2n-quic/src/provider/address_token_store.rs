@@ -0,0 +1,34 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Provides storage for the address validation tokens a client receives from servers
+//!
+//! Servers issue tokens in NEW_TOKEN frames once the handshake is confirmed. The client
+//! includes the most recent token for a server name in the first Initial packet of the next
+//! connection to that server, which allows the server to skip the Retry round trip.
+//!
+//! The default store keeps tokens in memory for a bounded number of servers. Applications can
+//! implement [`Store`] to persist tokens across client restarts.
+
+pub use s2n_quic_core::token::store::{Bounded, Disabled, Store, DEFAULT_CAPACITY};
+
+/// Provides token storage support for a client
+pub trait Provider {
+    type Store: 'static + Send + Store;
+    type Error: 'static + core::fmt::Display + Send + Sync;
+
+    fn start(self) -> Result<Self::Store, Self::Error>;
+}
+
+impl_provider_utils!();
+
+pub type Default = Bounded;
+
+impl<T: 'static + Send + Store> Provider for T {
+    type Store = T;
+    type Error = core::convert::Infallible;
+
+    fn start(self) -> Result<Self::Store, Self::Error> {
+        Ok(self)
+    }
+}
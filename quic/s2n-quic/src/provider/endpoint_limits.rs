@@ -4,7 +4,13 @@
 //! Allows applications to limit peer's ability to open new connections
 
 pub use s2n_quic_core::endpoint::{
-    limits::{ConnectionAttempt, Outcome},
+    limits::{
+        prefix::{self, PrefixPolicy},
+        AttemptId, ConnectionAttempt, ConnectionAttemptBuilder, ConnectionCloseInfo,
+        ConnectionOpenInfo, HandshakeInfo, Outcome, ServerNameAttempt, TokenStatus,
+        DEFAULT_ATTEMPT_RATE_WINDOW, DEFAULT_MAX_DELAYED_RESPONSES, DEFAULT_MAX_PENDING_ATTEMPTS,
+        DEFAULT_PENDING_ATTEMPT_TIMEOUT,
+    },
     Limiter,
 };
 use s2n_quic_core::{event::Timestamp, path::THROTTLED_PORTS_LEN};
@@ -18,7 +24,11 @@ pub trait Provider: 'static {
 }
 
 use core::time::Duration;
-pub use default::Limits as Default;
+pub use default::{LimiterStats, Limits as Default};
+
+pub mod concurrency_limiter;
+pub mod decision_cache;
+pub mod rate_limiter;
 
 impl_provider_utils!();
 
@@ -31,9 +41,26 @@ impl<T: 'static + Limiter> Provider for T {
     }
 }
 
+/// The error returned when the configured limits are invalid
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BuildError(&'static str);
+
+impl core::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl std::error::Error for BuildError {}
+
 const THROTTLED_PORT_LIMIT: usize = 10;
 const THROTTLE_FREQUENCY: Duration = Duration::from_secs(1);
 
+/// The drop reason reported for attempts from a blocked remote port
+const BLOCKED_PORT_REASON: &str = "blocked remote port";
+/// The drop reason reported for attempts from a throttled remote port over the rate limit
+const THROTTLED_PORT_REASON: &str = "throttled remote port";
+
 #[derive(Default, Debug, Clone, Copy)]
 struct BasicRateLimiter {
     last_throttle_reset: Option<Timestamp>,
@@ -101,9 +128,16 @@ mod tests {
     #[test]
     fn first_throttle_reset() {
         let remote_address = SocketAddress::default();
+        let local_address = SocketAddress::default();
         let mock_clock = MockClock::default();
-        let info =
-            ConnectionAttempt::new(0, 0, &remote_address, mock_clock.get_time().into_event());
+        let info = ConnectionAttempt::new(
+            0,
+            0,
+            &remote_address,
+            &local_address,
+            &[],
+            mock_clock.get_time().into_event(),
+        );
 
         let mut rate_limiter = BasicRateLimiter::default();
         // The first time the throttle limit is hit the timer will be created so we expect to be
@@ -123,6 +157,7 @@ mod tests {
     #[test]
     fn throttle_timer_reset() {
         let remote_address = SocketAddress::default();
+        let local_address = SocketAddress::default();
         let mut mock_clock = MockClock::default();
 
         let mut rate_limiter = BasicRateLimiter::default();
@@ -132,8 +167,14 @@ mod tests {
         // This test should never throttle because everytime the limit is about to get hit the
         // thread sleeps long enough for the throttle reset timer to fire.
         for request in 0..(THROTTLED_PORT_LIMIT * 3) {
-            let info =
-                ConnectionAttempt::new(0, 0, &remote_address, mock_clock.get_time().into_event());
+            let info = ConnectionAttempt::new(
+                0,
+                0,
+                &remote_address,
+                &local_address,
+                &[],
+                mock_clock.get_time().into_event(),
+            );
             if request % THROTTLED_PORT_LIMIT == 0 {
                 mock_clock.inc_by(sleep_longer_than_short_freq)
             }
@@ -177,59 +218,349 @@ pub mod default {
     ///     Ok(())
     /// # }
     /// ```
+    ///
+    /// Refuse connections once the endpoint is holding 10,000 connections, and delay Retry
+    /// packets sent while more than 100 handshakes are in progress.
+    ///
+    /// ```rust
+    /// use s2n_quic::provider::endpoint_limits;
+    /// # use std::{error::Error, time::Duration};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn Error>> {
+    /// let limits = endpoint_limits::Default::builder()
+    ///     .with_inflight_handshake_limit(100)?
+    ///     .with_max_connections(10_000)?
+    ///     .with_retry_delay(Duration::from_millis(100))?
+    ///     .build()?;
+    ///
+    ///     Ok(())
+    /// # }
+    /// ```
+    ///
+    /// With the `serde` feature, the builder can be serialized and deserialized with fields
+    /// matching the `with_*` methods. Missing fields keep their defaults, and durations are
+    /// written as milliseconds or strings such as `"100ms"`.
     #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
     pub struct Builder {
+        #[cfg_attr(
+            feature = "serde",
+            serde(
+                rename = "inflight_handshake_limit",
+                skip_serializing_if = "Option::is_none"
+            )
+        )]
         max_inflight_handshake_limit: Option<usize>,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        max_connections: Option<usize>,
+        #[cfg_attr(feature = "serde", serde(with = "s2n_quic_core::time::serde"))]
+        retry_delay: Duration,
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        max_delayed_responses: Option<usize>,
+        #[cfg_attr(
+            feature = "serde",
+            serde(
+                with = "s2n_quic_core::time::serde::option",
+                skip_serializing_if = "Option::is_none"
+            )
+        )]
+        max_handshake_duration: Option<Duration>,
+        #[cfg_attr(
+            feature = "serde",
+            serde(
+                rename = "retry_above_attempt_rate",
+                skip_serializing_if = "Option::is_none"
+            )
+        )]
+        retry_attempt_rate: Option<f32>,
+        #[cfg_attr(
+            feature = "serde",
+            serde(
+                with = "s2n_quic_core::time::serde::option",
+                skip_serializing_if = "Option::is_none"
+            )
+        )]
+        attempt_rate_window: Option<Duration>,
+    }
+
+    #[cfg(feature = "serde")]
+    impl<'de> serde::Deserialize<'de> for Builder {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            use serde::de::Error as _;
+
+            #[derive(serde::Deserialize)]
+            #[serde(deny_unknown_fields)]
+            struct Settings {
+                inflight_handshake_limit: Option<usize>,
+                max_connections: Option<usize>,
+                #[serde(default, with = "s2n_quic_core::time::serde::option")]
+                retry_delay: Option<Duration>,
+                max_delayed_responses: Option<usize>,
+                #[serde(default, with = "s2n_quic_core::time::serde::option")]
+                max_handshake_duration: Option<Duration>,
+                retry_above_attempt_rate: Option<f32>,
+                #[serde(default, with = "s2n_quic_core::time::serde::option")]
+                attempt_rate_window: Option<Duration>,
+            }
+
+            // apply each setting with its `with_*` method so it is validated the same way. The
+            // settings which depend on each other are validated when the limits are built.
+            let settings = <Settings as serde::Deserialize>::deserialize(deserializer)?;
+            let mut builder = Self::default();
+            if let Some(limit) = settings.inflight_handshake_limit {
+                builder = builder
+                    .with_inflight_handshake_limit(limit)
+                    .map_err(D::Error::custom)?;
+            }
+            if let Some(limit) = settings.max_connections {
+                builder = builder
+                    .with_max_connections(limit)
+                    .map_err(D::Error::custom)?;
+            }
+            if let Some(delay) = settings.retry_delay {
+                builder = builder.with_retry_delay(delay).map_err(D::Error::custom)?;
+            }
+            if let Some(limit) = settings.max_delayed_responses {
+                builder = builder
+                    .with_max_delayed_responses(limit)
+                    .map_err(D::Error::custom)?;
+            }
+            if let Some(duration) = settings.max_handshake_duration {
+                builder = builder
+                    .with_max_handshake_duration(duration)
+                    .map_err(D::Error::custom)?;
+            }
+            if let Some(rate) = settings.retry_above_attempt_rate {
+                builder = builder
+                    .with_retry_above_attempt_rate(rate)
+                    .map_err(D::Error::custom)?;
+            }
+            if let Some(window) = settings.attempt_rate_window {
+                builder = builder
+                    .with_attempt_rate_window(window)
+                    .map_err(D::Error::custom)?;
+            }
+            Ok(builder)
+        }
     }
 
     impl Builder {
         /// Sets limit on inflight handshakes
+        ///
+        /// Attempts received while the endpoint already has `limit` handshakes in progress are
+        /// still allowed. Attempts received while more than `limit` are in progress are sent a
+        /// Retry packet.
         pub fn with_inflight_handshake_limit(mut self, limit: usize) -> Result<Self, Infallible> {
             self.max_inflight_handshake_limit = Some(limit);
             Ok(self)
         }
 
+        /// Sets limit on the number of connections held by the endpoint
+        ///
+        /// Connection attempts received while the endpoint is holding at least this many
+        /// connections, including inflight handshakes, are closed with `CONNECTION_REFUSED`.
+        pub fn with_max_connections(mut self, limit: usize) -> Result<Self, Infallible> {
+            self.max_connections = Some(limit);
+            Ok(self)
+        }
+
+        /// Sets how long to wait before sending Retry packets once the inflight handshake limit
+        /// is reached
+        ///
+        /// Defaults to sending Retry packets immediately.
+        pub fn with_retry_delay(mut self, delay: Duration) -> Result<Self, Infallible> {
+            self.retry_delay = delay;
+            Ok(self)
+        }
+
+        /// Sets the maximum number of delayed Retry or CONNECTION_CLOSE responses held at once
+        ///
+        /// Each held response uses roughly 1.3KB, and the limit applies to each type of response
+        /// separately. Defaults to [`DEFAULT_MAX_DELAYED_RESPONSES`].
+        pub fn with_max_delayed_responses(mut self, limit: usize) -> Result<Self, Infallible> {
+            self.max_delayed_responses = Some(limit);
+            Ok(self)
+        }
+
+        /// Sets how long a connection may spend completing its handshake before the endpoint
+        /// abandons it
+        ///
+        /// Abandoned connections are closed with `CONNECTION_REFUSED`, which frees them from the
+        /// inflight handshake count. If the connection limits set a shorter duration, that is used
+        /// instead.
+        pub fn with_max_handshake_duration(
+            mut self,
+            duration: Duration,
+        ) -> Result<Self, Infallible> {
+            self.max_handshake_duration = Some(duration);
+            Ok(self)
+        }
+
+        /// Sends Retry packets while the endpoint receives more than `rate` connection attempts
+        /// per second
+        ///
+        /// This defers the cost of new connections only while the endpoint is under load, rather
+        /// than adding a round trip to every handshake. Clients returning with a valid Retry token
+        /// are allowed, and the delay from [`Self::with_retry_delay`] applies. The rate is
+        /// measured over the window set with [`Self::with_attempt_rate_window`].
+        pub fn with_retry_above_attempt_rate(mut self, rate: f32) -> Result<Self, Infallible> {
+            self.retry_attempt_rate = Some(rate);
+            Ok(self)
+        }
+
+        /// Sets the length of the sliding window the endpoint measures the connection attempt
+        /// rate over
+        ///
+        /// Defaults to [`DEFAULT_ATTEMPT_RATE_WINDOW`].
+        pub fn with_attempt_rate_window(mut self, window: Duration) -> Result<Self, Infallible> {
+            self.attempt_rate_window = Some(window);
+            Ok(self)
+        }
+
         /// Build the limits
-        pub fn build(self) -> Result<Limits, Infallible> {
+        ///
+        /// Returns an error if the connection limit is lower than the inflight handshake limit,
+        /// since the inflight handshake limit could never be reached.
+        pub fn build(self) -> Result<Limits, BuildError> {
+            if let (Some(max_connections), Some(max_handshakes)) =
+                (self.max_connections, self.max_inflight_handshake_limit)
+            {
+                if max_connections < max_handshakes {
+                    return Err(BuildError(
+                        "max_connections must not be less than the inflight handshake limit",
+                    ));
+                }
+            }
+
+            if let Some(rate) = self.retry_attempt_rate {
+                if !rate.is_finite() || rate < 0.0 {
+                    return Err(BuildError(
+                        "the Retry attempt rate must be a finite, non-negative number",
+                    ));
+                }
+            }
+
+            if self.attempt_rate_window == Some(Duration::ZERO) {
+                return Err(BuildError("the attempt rate window must not be empty"));
+            }
+
             Ok(Limits {
                 max_inflight_handshake_limit: self.max_inflight_handshake_limit,
+                max_connections: self.max_connections,
+                retry_delay: self.retry_delay,
+                max_delayed_responses: self
+                    .max_delayed_responses
+                    .unwrap_or(DEFAULT_MAX_DELAYED_RESPONSES),
+                max_handshake_duration: self.max_handshake_duration,
+                retry_attempt_rate: self.retry_attempt_rate,
+                attempt_rate_window: self
+                    .attempt_rate_window
+                    .unwrap_or(DEFAULT_ATTEMPT_RATE_WINDOW),
                 rate_limiter: [BasicRateLimiter::default(); THROTTLED_PORTS_LEN],
+                stats: LimiterStats::default(),
             })
         }
     }
 
+    /// A snapshot of the outcomes decided by the [`Limits`]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    #[non_exhaustive]
+    pub struct LimiterStats {
+        /// The number of attempts allowed to continue
+        pub allowed: u64,
+        /// The number of attempts asked to validate their address with a Retry packet
+        pub retried: u64,
+        /// The number of attempts silently dropped
+        pub dropped: u64,
+        /// The number of attempts refused with a CONNECTION_CLOSE frame
+        pub closed: u64,
+        /// The highest number of inflight handshakes seen by any attempt
+        pub max_inflight_handshakes: usize,
+    }
+
+    impl LimiterStats {
+        #[inline]
+        fn on_outcome(&mut self, info: &ConnectionAttempt, outcome: &Outcome) {
+            let counter = match outcome {
+                Outcome::Allow { .. } => &mut self.allowed,
+                Outcome::Retry { .. } => &mut self.retried,
+                Outcome::Close { .. } => &mut self.closed,
+                _ => &mut self.dropped,
+            };
+            *counter += 1;
+            self.max_inflight_handshakes =
+                self.max_inflight_handshakes.max(info.inflight_handshakes);
+        }
+    }
+
+    /// The endpoint limits used by default
+    ///
+    /// Every outcome is counted, which can be read with [`Limits::snapshot`]. Once the limits are
+    /// moved into a server, the same decisions can be counted by an event subscriber with the
+    /// `EndpointConnectionAttemptDecided` event.
     #[derive(Clone, Copy, Debug)]
     pub struct Limits {
         /// Maximum number of handshakes to allow before Retry packets are queued
         max_inflight_handshake_limit: Option<usize>,
+        /// Maximum number of connections to hold before attempts are refused
+        max_connections: Option<usize>,
+        /// How long to wait before sending Retry packets
+        retry_delay: Duration,
+        /// Maximum number of delayed responses held at once
+        max_delayed_responses: usize,
+        /// How long to wait for handshakes to complete before abandoning them
+        max_handshake_duration: Option<Duration>,
+        /// The attempt rate above which Retry packets are sent
+        retry_attempt_rate: Option<f32>,
+        /// The window the endpoint measures the attempt rate over
+        attempt_rate_window: Duration,
         rate_limiter: [BasicRateLimiter; THROTTLED_PORTS_LEN],
+        stats: LimiterStats,
     }
 
     impl Limits {
         pub fn builder() -> Builder {
             Builder::default()
         }
-    }
 
-    /// Default implementation for the Limits
-    impl super::Limiter for Limits {
-        fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+        /// Returns the number of attempts decided on with each outcome
+        pub fn snapshot(&self) -> LimiterStats {
+            self.stats
+        }
+
+        fn decide(&mut self, info: &ConnectionAttempt) -> Outcome {
             let remote_port = info.remote_address.port();
             if s2n_quic_core::path::remote_port_blocked(remote_port) {
-                return Outcome::drop();
+                return Outcome::drop().with_reason(BLOCKED_PORT_REASON);
             }
 
             if let Some(port_index) = s2n_quic_core::path::remote_port_throttled_index(remote_port)
             {
                 let rate_limiter = &mut self.rate_limiter[port_index];
                 if rate_limiter.should_throttle(THROTTLED_PORT_LIMIT, THROTTLE_FREQUENCY, info) {
-                    return Outcome::drop();
+                    return Outcome::drop().with_reason(THROTTLED_PORT_REASON);
+                }
+            }
+
+            if let Some(limit) = self.max_connections {
+                if info.connection_count >= limit {
+                    return Outcome::close();
                 }
             }
 
-            if let Some(limit) = self.max_inflight_handshake_limit {
-                if info.inflight_handshakes >= limit {
-                    return Outcome::retry();
+            // clients returning with a Retry token have already been deferred once and clients
+            // with a token from a NEW_TOKEN frame proved ownership of their address previously
+            let validated = info.token_status.is_valid();
+            if !validated {
+                let over_handshake_limit = self
+                    .max_inflight_handshake_limit
+                    .map_or(false, |limit| info.inflight_handshakes > limit);
+                let over_attempt_rate = self
+                    .retry_attempt_rate
+                    .map_or(false, |rate| info.recent_attempt_rate > rate);
+
+                if over_handshake_limit || over_attempt_rate {
+                    return Outcome::retry().with_delay(self.retry_delay);
                 }
             }
 
@@ -237,12 +568,40 @@ pub mod default {
         }
     }
 
+    /// Default implementation for the Limits
+    impl super::Limiter for Limits {
+        fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+            let outcome = self.decide(info);
+            self.stats.on_outcome(info, &outcome);
+            outcome
+        }
+
+        fn max_delayed_responses(&self) -> usize {
+            self.max_delayed_responses
+        }
+
+        fn max_handshake_duration(&self) -> Option<Duration> {
+            self.max_handshake_duration
+        }
+
+        fn attempt_rate_window(&self) -> Duration {
+            self.attempt_rate_window
+        }
+    }
+
     /// Default limit values are as non-intrusive as possible
     impl std::default::Default for Limits {
         fn default() -> Self {
             Self {
                 max_inflight_handshake_limit: None,
+                max_connections: None,
+                retry_delay: Duration::ZERO,
+                max_delayed_responses: DEFAULT_MAX_DELAYED_RESPONSES,
+                max_handshake_duration: None,
+                retry_attempt_rate: None,
+                attempt_rate_window: DEFAULT_ATTEMPT_RATE_WINDOW,
                 rate_limiter: [BasicRateLimiter::default(); THROTTLED_PORTS_LEN],
+                stats: LimiterStats::default(),
             }
         }
     }
@@ -255,6 +614,14 @@ pub mod default {
             .build()
             .unwrap();
         assert_eq!(elp.max_inflight_handshake_limit, Some(100));
+        assert_eq!(elp.max_handshake_duration(), None);
+
+        let elp = Limits::builder()
+            .with_max_handshake_duration(Duration::from_secs(3))
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(elp.max_handshake_duration(), Some(Duration::from_secs(3)));
     }
 
     #[test]
@@ -266,6 +633,7 @@ pub mod default {
         };
 
         let mut remote_address = SocketAddress::default();
+        let local_address = SocketAddress::default();
         let mut limits = Limits::builder().build().unwrap();
         let mock_clock = MockClock::default();
 
@@ -273,15 +641,387 @@ pub mod default {
             let blocked_expected = s2n_quic_core::path::remote_port_blocked(port);
 
             remote_address.set_port(port);
-            let info =
-                ConnectionAttempt::new(0, 0, &remote_address, mock_clock.get_time().into_event());
+            let info = ConnectionAttempt::new(
+                0,
+                0,
+                &remote_address,
+                &local_address,
+                &[],
+                mock_clock.get_time().into_event(),
+            );
             let outcome = limits.on_connection_attempt(&info);
 
             if blocked_expected {
-                assert_eq!(Outcome::drop(), outcome);
+                assert_eq!(Outcome::drop().with_reason(BLOCKED_PORT_REASON), outcome);
             } else {
                 assert_eq!(Outcome::allow(), outcome);
             }
         }
     }
+
+    /// Returns the outcome for an attempt from an unrestricted port with the given counts
+    #[cfg(test)]
+    fn outcome(
+        limits: &mut Limits,
+        inflight_handshakes: usize,
+        connection_count: usize,
+    ) -> Outcome {
+        token_outcome(
+            limits,
+            inflight_handshakes,
+            connection_count,
+            TokenStatus::None,
+        )
+    }
+
+    /// Returns the outcome for an attempt which presented a token with the given status
+    #[cfg(test)]
+    fn token_outcome(
+        limits: &mut Limits,
+        inflight_handshakes: usize,
+        connection_count: usize,
+        token_status: TokenStatus,
+    ) -> Outcome {
+        use s2n_quic_core::{
+            event::IntoEvent,
+            inet::SocketAddress,
+            time::{testing::Clock as MockClock, Clock},
+        };
+
+        let mut remote_address = SocketAddress::default();
+        remote_address.set_port(50_000);
+        let local_address = SocketAddress::default();
+        let mock_clock = MockClock::default();
+        let info = ConnectionAttempt::new(
+            inflight_handshakes,
+            connection_count,
+            &remote_address,
+            &local_address,
+            &[],
+            mock_clock.get_time().into_event(),
+        )
+        .with_token_status(token_status);
+        limits.on_connection_attempt(&info)
+    }
+
+    #[test]
+    fn inflight_handshake_limit_test() {
+        let delay = Duration::from_millis(100);
+        let mut limits = Limits::builder()
+            .with_inflight_handshake_limit(10)
+            .unwrap()
+            .with_retry_delay(delay)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert_eq!(outcome(&mut limits, 9, 9), Outcome::allow());
+        // attempts at the limit are allowed
+        assert_eq!(outcome(&mut limits, 10, 10), Outcome::allow());
+        // one over the limit is retried
+        assert_eq!(
+            outcome(&mut limits, 11, 11),
+            Outcome::retry().with_delay(delay)
+        );
+    }
+
+    #[test]
+    fn retry_token_test() {
+        let mut limits = Limits::builder()
+            .with_inflight_handshake_limit(10)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        // clients returning with a valid Retry token aren't sent another Retry
+        let valid = TokenStatus::RetryToken { valid: true };
+        assert_eq!(token_outcome(&mut limits, 11, 11, valid), Outcome::allow());
+
+        let invalid = TokenStatus::RetryToken { valid: false };
+        assert!(token_outcome(&mut limits, 11, 11, invalid).is_retry());
+
+        // neither are clients returning with a valid token from a NEW_TOKEN frame
+        let new_token = TokenStatus::NewToken { valid: true };
+        assert_eq!(
+            token_outcome(&mut limits, 11, 11, new_token),
+            Outcome::allow()
+        );
+        let invalid = TokenStatus::NewToken { valid: false };
+        assert!(token_outcome(&mut limits, 11, 11, invalid).is_retry());
+    }
+
+    #[test]
+    fn retry_above_attempt_rate_test() {
+        let delay = Duration::from_millis(50);
+        let mut limits = Limits::builder()
+            .with_retry_above_attempt_rate(100.0)
+            .unwrap()
+            .with_retry_delay(delay)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(limits.attempt_rate_window(), DEFAULT_ATTEMPT_RATE_WINDOW);
+
+        let mut decide = |rate: f32, token_status: TokenStatus| {
+            let builder = ConnectionAttempt::builder()
+                .with_remote_address(s2n_quic_core::inet::SocketAddressV4::new(
+                    [192, 0, 2, 1],
+                    50_000,
+                ))
+                .with_recent_attempt_rate(rate)
+                .with_token_status(token_status);
+            limits.on_connection_attempt(&builder.build())
+        };
+
+        // the synthetic rate climbs past the threshold
+        for rate in 0..=300 {
+            let outcome = decide(rate as f32, TokenStatus::None);
+            if rate <= 100 {
+                assert_eq!(outcome, Outcome::allow(), "rate {rate}");
+            } else {
+                assert_eq!(outcome, Outcome::retry().with_delay(delay), "rate {rate}");
+            }
+        }
+
+        // clients returning with a valid Retry token aren't sent another Retry
+        let valid = TokenStatus::RetryToken { valid: true };
+        assert_eq!(decide(300.0, valid), Outcome::allow());
+    }
+
+    #[test]
+    fn attempt_rate_validation_test() {
+        let window = Duration::from_millis(250);
+        let limits = Limits::builder()
+            .with_attempt_rate_window(window)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(limits.attempt_rate_window(), window);
+
+        for rate in [-1.0, f32::NAN, f32::INFINITY] {
+            let result = Limits::builder()
+                .with_retry_above_attempt_rate(rate)
+                .unwrap()
+                .build();
+            assert!(result.is_err(), "rate {rate}");
+        }
+
+        let result = Limits::builder()
+            .with_attempt_rate_window(Duration::ZERO)
+            .unwrap()
+            .build();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn max_connections_test() {
+        let mut limits = Limits::builder()
+            .with_inflight_handshake_limit(10)
+            .unwrap()
+            .with_max_connections(100)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert_eq!(outcome(&mut limits, 0, 99), Outcome::allow());
+        assert_eq!(outcome(&mut limits, 0, 100), Outcome::close());
+        // the connection limit takes priority over the handshake limit
+        assert_eq!(outcome(&mut limits, 11, 100), Outcome::close());
+        assert_eq!(outcome(&mut limits, 11, 99), Outcome::retry());
+    }
+
+    #[test]
+    fn max_connections_below_handshake_limit_test() {
+        let result = Limits::builder()
+            .with_inflight_handshake_limit(10)
+            .unwrap()
+            .with_max_connections(9)
+            .unwrap()
+            .build();
+        assert!(result.is_err());
+
+        // the limits may be equal
+        assert!(Limits::builder()
+            .with_inflight_handshake_limit(10)
+            .unwrap()
+            .with_max_connections(10)
+            .unwrap()
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn stats_test() {
+        use s2n_quic_core::{
+            event::IntoEvent,
+            inet::SocketAddress,
+            time::{testing::Clock as MockClock, Clock},
+        };
+
+        let mut limits = Limits::builder()
+            .with_inflight_handshake_limit(10)
+            .unwrap()
+            .with_max_connections(100)
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(limits.snapshot(), LimiterStats::default());
+
+        for inflight_handshakes in 0..300 {
+            // stays within the handshake limit
+            outcome(&mut limits, inflight_handshakes % 11, 0);
+            // exceeds the handshake limit
+            outcome(&mut limits, 11 + inflight_handshakes % 10, 0);
+            // reaches the connection limit
+            outcome(&mut limits, inflight_handshakes, 100);
+        }
+
+        // attempts from port 0 are always dropped
+        let remote_address = SocketAddress::default();
+        let local_address = SocketAddress::default();
+        let mock_clock = MockClock::default();
+        for _ in 0..200 {
+            let info = ConnectionAttempt::new(
+                0,
+                0,
+                &remote_address,
+                &local_address,
+                &[],
+                mock_clock.get_time().into_event(),
+            );
+            assert!(limits.on_connection_attempt(&info).is_drop());
+        }
+
+        let expected = LimiterStats {
+            allowed: 300,
+            retried: 300,
+            dropped: 200,
+            closed: 300,
+            max_inflight_handshakes: 299,
+        };
+        assert_eq!(limits.snapshot(), expected);
+        // copies keep counting on their own
+        let mut copy = limits;
+        outcome(&mut copy, 0, 0);
+        assert_eq!(copy.snapshot().allowed, expected.allowed + 1);
+        assert_eq!(limits.snapshot(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_config_test() {
+        use super::{concurrency_limiter, decision_cache, rate_limiter};
+        use s2n_quic_core::inet::{IpNet, SocketAddress};
+        use serde_json::json;
+
+        #[derive(serde::Serialize, serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Config {
+            listen: SocketAddress,
+            deny: Vec<IpNet>,
+            limits: Builder,
+            rate_limiter: rate_limiter::Builder,
+            concurrency_limiter: concurrency_limiter::Builder,
+            decision_cache: decision_cache::Builder,
+        }
+
+        let snippet = json!({
+            "listen": "[::]:443",
+            "deny": ["192.0.2.0/24", "2001:db8::/32"],
+            "limits": {
+                "inflight_handshake_limit": 100,
+                "max_connections": 10000,
+                "retry_delay": "100ms",
+                "max_handshake_duration": "5s",
+                "retry_above_attempt_rate": 1000.0,
+            },
+            "rate_limiter": {
+                "refill_interval": 100,
+                "burst": 50,
+                "ipv4_prefix_len": 24,
+            },
+            "concurrency_limiter": {
+                "max_connections": 10,
+            },
+            "decision_cache": {
+                "ttl": "1m 30s",
+            },
+        });
+
+        let config: Config = serde_json::from_value(snippet).unwrap();
+        assert_eq!(config.listen, "[::]:443".parse().unwrap());
+        assert_eq!(config.deny[0], "192.0.2.0/24".parse().unwrap());
+        assert!(config.limits.build().is_ok());
+
+        // the config is serialized in its canonical form, with the defaults filled in
+        let config: Config = serde_json::from_str(
+            r#"{
+                "listen": "[::]:443",
+                "deny": ["192.0.2.0/24"],
+                "limits": { "max_connections": 10000, "retry_delay": "100ms" },
+                "rate_limiter": {},
+                "concurrency_limiter": {},
+                "decision_cache": { "ttl": "1m 30s" }
+            }"#,
+        )
+        .unwrap();
+        let expected = json!({
+            "listen": "[::]:443",
+            "deny": ["192.0.2.0/24"],
+            "limits": {
+                "max_connections": 10000,
+                "retry_delay": 100,
+            },
+            "rate_limiter": {
+                "refill_interval": 100,
+                "burst": 20,
+                "retry_burst": 20,
+                "ipv4_prefix_len": 32,
+                "ipv6_prefix_len": 56,
+                "max_entries": 65536,
+            },
+            "concurrency_limiter": {
+                "max_connections": 100,
+                "ipv4_prefix_len": 32,
+                "ipv6_prefix_len": 56,
+                "max_entries": 65536,
+            },
+            "decision_cache": {
+                "enabled": true,
+                "ttl": 90000,
+                "ipv4_prefix_len": 32,
+                "ipv6_prefix_len": 56,
+                "max_entries": 65536,
+            },
+        });
+        let value = serde_json::to_value(&config).unwrap();
+        assert_eq!(value, expected);
+
+        // the serialized form round trips
+        let config: Config = serde_json::from_value(value).unwrap();
+        assert_eq!(serde_json::to_value(&config).unwrap(), expected);
+
+        // the settings are validated the same way as the builder methods
+        for invalid in [
+            json!({ "rate_limiter": { "burst": 0 } }),
+            json!({ "rate_limiter": { "ipv4_prefix_len": 33 } }),
+            json!({ "concurrency_limiter": { "max_connections": 0 } }),
+            json!({ "decision_cache": { "ttl": 0 } }),
+            json!({ "decision_cache": { "max_entries": 1 } }),
+            json!({ "decision_cache": { "unknown": 1 } }),
+        ] {
+            let (field, value) = invalid.as_object().unwrap().iter().next().unwrap();
+            let result = match field.as_str() {
+                "rate_limiter" => {
+                    serde_json::from_value::<rate_limiter::Builder>(value.clone()).map(|_| ())
+                }
+                "concurrency_limiter" => {
+                    serde_json::from_value::<concurrency_limiter::Builder>(value.clone())
+                        .map(|_| ())
+                }
+                _ => serde_json::from_value::<decision_cache::Builder>(value.clone()).map(|_| ()),
+            };
+            assert!(result.is_err(), "{invalid}");
+        }
+    }
 }
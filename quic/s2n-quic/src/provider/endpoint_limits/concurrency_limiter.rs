@@ -0,0 +1,370 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Limits the number of open connections from each source address prefix
+//!
+//! Each connection the endpoint opens is counted against the prefix of its source address until
+//! the connection is closed, whether or not it completes its handshake. Attempts from a prefix
+//! which already holds the maximum number of connections are closed with `CONNECTION_REFUSED`.
+//! IPv4-mapped IPv6 addresses are counted against the prefix of the IPv4 address they map.
+//!
+//! ```rust
+//! use s2n_quic::provider::endpoint_limits::concurrency_limiter::ConcurrencyLimiter;
+//! # use std::error::Error;
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! // allow each address to hold at most 10 connections at a time
+//! let limiter = ConcurrencyLimiter::builder()
+//!     .with_max_connections(10)?
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{
+    rate_limiter::Prefix, BuildError, ConnectionAttempt, ConnectionCloseInfo, ConnectionOpenInfo,
+    Limiter, Outcome,
+};
+use core::convert::Infallible;
+use s2n_quic_core::event::api::SocketAddress;
+use std::collections::HashMap;
+
+/// The reason reported for attempts refused by the concurrency limiter
+const CLOSE_REASON: &str = "too many connections";
+
+/// Allows the concurrency limiter to be built with specific values
+///
+/// With the `serde` feature, the builder can be serialized and deserialized with fields matching
+/// the `with_*` methods. Missing fields keep their defaults.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Builder {
+    max_connections: usize,
+    ipv4_prefix_len: u8,
+    ipv6_prefix_len: u8,
+    max_entries: usize,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self {
+            max_connections: 100,
+            ipv4_prefix_len: 32,
+            ipv6_prefix_len: 56,
+            max_entries: 65_536,
+        }
+    }
+}
+
+impl Builder {
+    /// Sets the maximum number of connections each prefix can hold at once
+    ///
+    /// Defaults to 100.
+    pub fn with_max_connections(mut self, max_connections: usize) -> Result<Self, BuildError> {
+        if max_connections == 0 {
+            return Err(BuildError("the limit must allow at least one connection"));
+        }
+
+        self.max_connections = max_connections;
+        Ok(self)
+    }
+
+    /// Sets the length of the prefix IPv4 addresses are grouped by
+    ///
+    /// Defaults to 32, which limits each address separately.
+    pub fn with_ipv4_prefix_len(mut self, len: u8) -> Result<Self, BuildError> {
+        if len > 32 {
+            return Err(BuildError("the IPv4 prefix length must be at most 32"));
+        }
+
+        self.ipv4_prefix_len = len;
+        Ok(self)
+    }
+
+    /// Sets the length of the prefix IPv6 addresses are grouped by
+    ///
+    /// Defaults to 56, since peers are commonly assigned an entire prefix rather than a single
+    /// address.
+    pub fn with_ipv6_prefix_len(mut self, len: u8) -> Result<Self, BuildError> {
+        if len > 128 {
+            return Err(BuildError("the IPv6 prefix length must be at most 128"));
+        }
+
+        self.ipv6_prefix_len = len;
+        Ok(self)
+    }
+
+    /// Sets the maximum number of prefixes tracked at once
+    ///
+    /// Prefixes are only tracked while they hold open connections, so their counts are never
+    /// forgotten. Once the limit is reached, attempts from prefixes which aren't already tracked
+    /// are refused until other prefixes close their connections. Defaults to 65,536.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Result<Self, BuildError> {
+        if max_entries == 0 {
+            return Err(BuildError(
+                "the concurrency limiter must track at least one entry",
+            ));
+        }
+
+        self.max_entries = max_entries;
+        Ok(self)
+    }
+
+    /// Build the concurrency limiter
+    pub fn build(self) -> Result<ConcurrencyLimiter, Infallible> {
+        Ok(ConcurrencyLimiter {
+            ipv4_prefix_len: self.ipv4_prefix_len,
+            ipv6_prefix_len: self.ipv6_prefix_len,
+            counts: Counts::new(self.max_connections, self.max_entries),
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Builder {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Settings {
+            max_connections: Option<usize>,
+            ipv4_prefix_len: Option<u8>,
+            ipv6_prefix_len: Option<u8>,
+            max_entries: Option<usize>,
+        }
+
+        // apply each setting with its `with_*` method so it is validated the same way
+        let settings = <Settings as serde::Deserialize>::deserialize(deserializer)?;
+        let mut builder = Self::default();
+        if let Some(max_connections) = settings.max_connections {
+            builder = builder
+                .with_max_connections(max_connections)
+                .map_err(D::Error::custom)?;
+        }
+        if let Some(len) = settings.ipv4_prefix_len {
+            builder = builder
+                .with_ipv4_prefix_len(len)
+                .map_err(D::Error::custom)?;
+        }
+        if let Some(len) = settings.ipv6_prefix_len {
+            builder = builder
+                .with_ipv6_prefix_len(len)
+                .map_err(D::Error::custom)?;
+        }
+        if let Some(max_entries) = settings.max_entries {
+            builder = builder
+                .with_max_entries(max_entries)
+                .map_err(D::Error::custom)?;
+        }
+        Ok(builder)
+    }
+}
+
+/// A [`Limiter`] which limits the number of open connections from each source address prefix
+///
+/// Connections are counted from the time the endpoint opens them, using
+/// [`Limiter::on_connection_opened`], until they are reported to
+/// [`Limiter::on_connection_closed`]. This includes connections which never complete their
+/// handshake, such as those abandoned after the maximum handshake duration, so the counts don't
+/// leak.
+#[derive(Debug)]
+pub struct ConcurrencyLimiter {
+    ipv4_prefix_len: u8,
+    ipv6_prefix_len: u8,
+    counts: Counts,
+}
+
+impl ConcurrencyLimiter {
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+
+    /// Returns the number of open connections counted by the limiter
+    pub fn connection_count(&self) -> usize {
+        self.counts.total
+    }
+
+    fn prefix(&self, address: &SocketAddress) -> Option<Prefix> {
+        Prefix::new(address, self.ipv4_prefix_len, self.ipv6_prefix_len)
+    }
+}
+
+impl Default for ConcurrencyLimiter {
+    fn default() -> Self {
+        Self::builder().build().unwrap()
+    }
+}
+
+impl Limiter for ConcurrencyLimiter {
+    fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+        match self.prefix(&info.remote_address) {
+            Some(prefix) if !self.counts.can_open(&prefix) => {
+                Outcome::close().with_reason(CLOSE_REASON)
+            }
+            _ => Outcome::allow(),
+        }
+    }
+
+    fn on_connection_opened(&mut self, info: &ConnectionOpenInfo) {
+        if let Some(prefix) = self.prefix(&info.remote_address) {
+            self.counts.on_open(prefix);
+        }
+    }
+
+    fn on_connection_closed(&mut self, info: &ConnectionCloseInfo) {
+        if let Some(prefix) = self.prefix(&info.remote_address) {
+            self.counts.on_close(&prefix);
+        }
+    }
+}
+
+/// Tracks the number of open connections for each prefix
+///
+/// Prefixes are removed once they no longer hold any connections, so the number of entries is
+/// bounded by the number of open connections as well as `max_entries`.
+#[derive(Debug)]
+struct Counts {
+    counts: HashMap<Prefix, usize>,
+    total: usize,
+    max_connections: usize,
+    max_entries: usize,
+}
+
+impl Counts {
+    fn new(max_connections: usize, max_entries: usize) -> Self {
+        Self {
+            counts: HashMap::new(),
+            total: 0,
+            max_connections,
+            max_entries,
+        }
+    }
+
+    fn can_open(&self, prefix: &Prefix) -> bool {
+        match self.counts.get(prefix) {
+            Some(count) => *count < self.max_connections,
+            None => self.counts.len() < self.max_entries,
+        }
+    }
+
+    fn on_open(&mut self, prefix: Prefix) {
+        // connections are always counted, even if the prefix reached its limit while the outcome
+        // of the attempt was pending, so the counts balance once they close
+        *self.counts.entry(prefix).or_default() += 1;
+        self.total += 1;
+    }
+
+    fn on_close(&mut self, prefix: &Prefix) {
+        if let Some(count) = self.counts.get_mut(prefix) {
+            *count -= 1;
+            self.total -= 1;
+            if *count == 0 {
+                self.counts.remove(prefix);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use s2n_quic_core::inet::{self, SocketAddressV4, SocketAddressV6};
+
+    fn prefix(limiter: &ConcurrencyLimiter, address: inet::SocketAddress) -> Prefix {
+        use s2n_quic_core::event::IntoEvent;
+        limiter.prefix(&(&address).into_event()).unwrap()
+    }
+
+    #[test]
+    fn max_connections_test() {
+        let mut counts = Counts::new(2, 16);
+        let prefix = Prefix::IpV4([10, 0, 0, 1]);
+
+        for _ in 0..2 {
+            assert!(counts.can_open(&prefix));
+            counts.on_open(prefix);
+        }
+        assert!(!counts.can_open(&prefix));
+
+        // closing a connection makes room for another
+        counts.on_close(&prefix);
+        assert!(counts.can_open(&prefix));
+
+        counts.on_close(&prefix);
+        assert_eq!(counts.total, 0);
+        assert!(counts.counts.is_empty());
+    }
+
+    #[test]
+    fn unknown_close_test() {
+        let mut counts = Counts::new(1, 16);
+
+        // closing a connection which was never counted doesn't underflow
+        counts.on_close(&Prefix::IpV4([10, 0, 0, 1]));
+        assert_eq!(counts.total, 0);
+        assert!(counts.counts.is_empty());
+    }
+
+    #[test]
+    fn bounded_entries_test() {
+        let mut counts = Counts::new(2, 4);
+
+        for ip in 0..4 {
+            let prefix = Prefix::IpV4([10, 0, 0, ip]);
+            assert!(counts.can_open(&prefix));
+            counts.on_open(prefix);
+        }
+
+        // new prefixes are refused while the table is full, but tracked prefixes aren't
+        let new = Prefix::IpV4([10, 0, 1, 0]);
+        assert!(!counts.can_open(&new));
+        assert!(counts.can_open(&Prefix::IpV4([10, 0, 0, 0])));
+
+        counts.on_close(&Prefix::IpV4([10, 0, 0, 3]));
+        assert!(counts.can_open(&new));
+    }
+
+    #[test]
+    fn mapped_prefix_test() {
+        let limiter = ConcurrencyLimiter::default();
+
+        let ipv4 = prefix(&limiter, SocketAddressV4::new([192, 168, 1, 2], 443).into());
+        let mapped = prefix(
+            &limiter,
+            SocketAddressV4::new([192, 168, 1, 2], 4433)
+                .to_ipv6_mapped()
+                .into(),
+        );
+        assert_eq!(ipv4, mapped);
+        assert_eq!(ipv4, Prefix::IpV4([192, 168, 1, 2]));
+
+        // other IPv6 addresses are grouped by their own prefix
+        let ipv6 = prefix(
+            &limiter,
+            SocketAddressV6::new(
+                [
+                    0x20, 0x01, 0x0d, 0xb8, 0, 0, 0, 0xff, 1, 2, 3, 4, 5, 6, 7, 8,
+                ],
+                443,
+            )
+            .into(),
+        );
+        let mut expected = [0; 16];
+        expected[..7].copy_from_slice(&[0x20, 0x01, 0x0d, 0xb8, 0, 0, 0]);
+        assert_eq!(ipv6, Prefix::IpV6(expected));
+    }
+
+    #[test]
+    fn builder_test() {
+        assert!(ConcurrencyLimiter::builder()
+            .with_max_connections(0)
+            .is_err());
+        assert!(ConcurrencyLimiter::builder()
+            .with_ipv4_prefix_len(33)
+            .is_err());
+        assert!(ConcurrencyLimiter::builder()
+            .with_ipv6_prefix_len(129)
+            .is_err());
+        assert!(ConcurrencyLimiter::builder().with_max_entries(0).is_err());
+    }
+}
@@ -0,0 +1,575 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Caches `Drop` outcomes for each source address prefix in front of another limiter
+//!
+//! Under a flood of spoofed connection attempts, consulting an expensive limiter for every
+//! Initial packet is itself a cost. Once the wrapped limiter drops an attempt, further attempts
+//! from the same prefix are dropped without consulting it until the cached outcome expires.
+//! Prefixes are grouped the same way as the [`RateLimiter`](super::rate_limiter::RateLimiter).
+//!
+//! ```rust
+//! use s2n_quic::provider::endpoint_limits::{
+//!     decision_cache::DecisionCache, rate_limiter::RateLimiter,
+//! };
+//! # use std::{error::Error, time::Duration};
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! // drop attempts from prefixes the rate limiter dropped for the next 5 seconds
+//! let limiter = DecisionCache::builder()
+//!     .with_ttl(Duration::from_secs(5))?
+//!     .build(RateLimiter::default())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{
+    rate_limiter::Prefix, AttemptId, BuildError, ConnectionAttempt, ConnectionCloseInfo,
+    ConnectionOpenInfo, HandshakeInfo, Limiter, Outcome, ServerNameAttempt,
+};
+use core::{
+    convert::Infallible,
+    sync::atomic::{AtomicU64, Ordering},
+    task::{Context, Poll},
+    time::Duration,
+};
+use std::{collections::HashMap, sync::Arc};
+
+/// Allows the decision cache to be built with specific values
+///
+/// With the `serde` feature, the builder can be serialized and deserialized with fields matching
+/// the `with_*` methods. Missing fields keep their defaults.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Builder {
+    enabled: bool,
+    #[cfg_attr(feature = "serde", serde(with = "s2n_quic_core::time::serde"))]
+    ttl: Duration,
+    ipv4_prefix_len: u8,
+    ipv6_prefix_len: u8,
+    max_entries: usize,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl: Duration::from_secs(1),
+            ipv4_prefix_len: 32,
+            ipv6_prefix_len: 56,
+            max_entries: 65_536,
+        }
+    }
+}
+
+impl Builder {
+    /// Sets whether `Drop` outcomes are cached
+    ///
+    /// A disabled cache passes every attempt to the wrapped limiter. Defaults to `true`.
+    pub fn with_enabled(mut self, enabled: bool) -> Result<Self, Infallible> {
+        self.enabled = enabled;
+        Ok(self)
+    }
+
+    /// Sets how long a `Drop` outcome is reused for attempts from the same prefix
+    ///
+    /// Defaults to 1 second.
+    pub fn with_ttl(mut self, ttl: Duration) -> Result<Self, BuildError> {
+        if ttl.is_zero() {
+            return Err(BuildError("the decision cache TTL must not be zero"));
+        }
+
+        self.ttl = ttl;
+        Ok(self)
+    }
+
+    /// Sets the length of the prefix IPv4 addresses are grouped by
+    ///
+    /// Defaults to 32, which caches outcomes for each address separately.
+    pub fn with_ipv4_prefix_len(mut self, len: u8) -> Result<Self, BuildError> {
+        if len > 32 {
+            return Err(BuildError("the IPv4 prefix length must be at most 32"));
+        }
+
+        self.ipv4_prefix_len = len;
+        Ok(self)
+    }
+
+    /// Sets the length of the prefix IPv6 addresses are grouped by
+    ///
+    /// Defaults to 56, to match the rate limiter.
+    pub fn with_ipv6_prefix_len(mut self, len: u8) -> Result<Self, BuildError> {
+        if len > 128 {
+            return Err(BuildError("the IPv6 prefix length must be at most 128"));
+        }
+
+        self.ipv6_prefix_len = len;
+        Ok(self)
+    }
+
+    /// Sets the maximum number of prefixes cached at once
+    ///
+    /// Once the limit is reached, the prefixes that were least recently dropped are forgotten,
+    /// so their next attempt is passed to the wrapped limiter. Defaults to 65,536.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Result<Self, BuildError> {
+        if max_entries < 2 {
+            return Err(BuildError(
+                "the decision cache must track at least two entries",
+            ));
+        }
+
+        self.max_entries = max_entries;
+        Ok(self)
+    }
+
+    /// Build the decision cache in front of the given limiter
+    pub fn build<L: Limiter>(self, limiter: L) -> Result<DecisionCache<L>, Infallible> {
+        Ok(DecisionCache {
+            limiter,
+            enabled: self.enabled,
+            ttl: self.ttl,
+            ipv4_prefix_len: self.ipv4_prefix_len,
+            ipv6_prefix_len: self.ipv6_prefix_len,
+            entries: Entries::new(self.max_entries / 2),
+            counters: Arc::default(),
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Builder {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Settings {
+            enabled: Option<bool>,
+            #[serde(default, with = "s2n_quic_core::time::serde::option")]
+            ttl: Option<Duration>,
+            ipv4_prefix_len: Option<u8>,
+            ipv6_prefix_len: Option<u8>,
+            max_entries: Option<usize>,
+        }
+
+        // apply each setting with its `with_*` method so it is validated the same way
+        let settings = <Settings as serde::Deserialize>::deserialize(deserializer)?;
+        let mut builder = Self::default();
+        if let Some(enabled) = settings.enabled {
+            builder = builder.with_enabled(enabled).map_err(D::Error::custom)?;
+        }
+        if let Some(ttl) = settings.ttl {
+            builder = builder.with_ttl(ttl).map_err(D::Error::custom)?;
+        }
+        if let Some(len) = settings.ipv4_prefix_len {
+            builder = builder
+                .with_ipv4_prefix_len(len)
+                .map_err(D::Error::custom)?;
+        }
+        if let Some(len) = settings.ipv6_prefix_len {
+            builder = builder
+                .with_ipv6_prefix_len(len)
+                .map_err(D::Error::custom)?;
+        }
+        if let Some(max_entries) = settings.max_entries {
+            builder = builder
+                .with_max_entries(max_entries)
+                .map_err(D::Error::custom)?;
+        }
+        Ok(builder)
+    }
+}
+
+/// A snapshot of the counters of a [`DecisionCache`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CacheStats {
+    /// The number of attempts dropped from the cache without consulting the wrapped limiter
+    pub hits: u64,
+    /// The number of attempts passed to the wrapped limiter
+    pub misses: u64,
+}
+
+/// A handle to the counters of a [`DecisionCache`]
+///
+/// The handle stays valid after the cache is moved into a server, so the counters can be read
+/// while the endpoint is running.
+#[derive(Clone, Debug)]
+pub struct StatsHandle(Arc<Counters>);
+
+impl StatsHandle {
+    /// Returns the current value of the counters
+    pub fn snapshot(&self) -> CacheStats {
+        self.0.snapshot()
+    }
+}
+
+/// Counters updated on the packet path, so only relaxed ordering is used
+#[derive(Debug, Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl Counters {
+    fn snapshot(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A [`Limiter`] which reuses `Drop` outcomes from the wrapped limiter for each source address
+/// prefix
+///
+/// Cached outcomes expire using the timestamp of each attempt, so the cache follows the endpoint
+/// clock. Attempts presenting a valid address validation token have proven they own their
+/// address, so they are always passed to the wrapped limiter. Every other callback is forwarded
+/// to the wrapped limiter unchanged.
+#[derive(Debug)]
+pub struct DecisionCache<L> {
+    limiter: L,
+    enabled: bool,
+    ttl: Duration,
+    ipv4_prefix_len: u8,
+    ipv6_prefix_len: u8,
+    entries: Entries,
+    counters: Arc<Counters>,
+}
+
+impl DecisionCache<()> {
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+}
+
+impl<L: Limiter> DecisionCache<L> {
+    /// Builds a decision cache with the default values in front of the given limiter
+    pub fn new(limiter: L) -> Self {
+        Builder::default().build(limiter).unwrap()
+    }
+
+    /// Returns the number of cache hits and misses
+    pub fn snapshot(&self) -> CacheStats {
+        self.counters.snapshot()
+    }
+
+    /// Returns a handle to the counters which can be read after the cache is moved
+    pub fn stats_handle(&self) -> StatsHandle {
+        StatsHandle(self.counters.clone())
+    }
+
+    /// Returns the prefix of the attempt if its outcome can be cached
+    fn prefix(&self, info: &ConnectionAttempt) -> Option<Prefix> {
+        if !self.enabled || info.token_status.is_valid() {
+            return None;
+        }
+
+        Prefix::new(
+            &info.remote_address,
+            self.ipv4_prefix_len,
+            self.ipv6_prefix_len,
+        )
+    }
+
+    /// Returns the cached outcome for the prefix, if it hasn't expired
+    fn cached(&self, prefix: Option<Prefix>, now: Duration) -> Option<Outcome> {
+        let outcome = self.entries.get(&prefix?, now)?;
+        self.counters.hits.fetch_add(1, Ordering::Relaxed);
+        Some(outcome)
+    }
+
+    /// Caches the outcome of the wrapped limiter if it dropped the attempt
+    fn on_outcome(&mut self, prefix: Option<Prefix>, now: Duration, outcome: &Outcome) {
+        self.counters.misses.fetch_add(1, Ordering::Relaxed);
+
+        if let Some(prefix) = prefix.filter(|_| outcome.is_drop()) {
+            self.entries.insert(prefix, now + self.ttl, outcome.clone());
+        }
+    }
+}
+
+impl<L: Limiter> Limiter for DecisionCache<L> {
+    fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+        let prefix = self.prefix(info);
+        let now = info.timestamp.duration_since_start();
+
+        if let Some(outcome) = self.cached(prefix, now) {
+            return outcome;
+        }
+
+        let outcome = self.limiter.on_connection_attempt(info);
+        self.on_outcome(prefix, now, &outcome);
+        outcome
+    }
+
+    fn poll_on_connection_attempt(
+        &mut self,
+        cx: &mut Context,
+        info: &ConnectionAttempt,
+    ) -> Poll<Outcome> {
+        let prefix = self.prefix(info);
+        let now = info.timestamp.duration_since_start();
+
+        if let Some(outcome) = self.cached(prefix, now) {
+            return Poll::Ready(outcome);
+        }
+
+        let outcome = self.limiter.poll_on_connection_attempt(cx, info);
+        if let Poll::Ready(outcome) = &outcome {
+            self.on_outcome(prefix, now, outcome);
+        }
+        outcome
+    }
+
+    fn on_connection_attempt_cancelled(&mut self, attempt_id: AttemptId) {
+        self.limiter.on_connection_attempt_cancelled(attempt_id)
+    }
+
+    fn max_pending_attempts(&self) -> usize {
+        self.limiter.max_pending_attempts()
+    }
+
+    fn pending_attempt_timeout(&self) -> Duration {
+        self.limiter.pending_attempt_timeout()
+    }
+
+    fn max_delayed_responses(&self) -> usize {
+        self.limiter.max_delayed_responses()
+    }
+
+    fn max_handshake_duration(&self) -> Option<Duration> {
+        self.limiter.max_handshake_duration()
+    }
+
+    fn attempt_rate_window(&self) -> Duration {
+        self.limiter.attempt_rate_window()
+    }
+
+    fn on_server_name(&mut self, info: &ServerNameAttempt) -> Outcome {
+        self.limiter.on_server_name(info)
+    }
+
+    fn on_connection_opened(&mut self, info: &ConnectionOpenInfo) {
+        self.limiter.on_connection_opened(info)
+    }
+
+    fn on_handshake_completed(&mut self, info: &HandshakeInfo) {
+        self.limiter.on_handshake_completed(info)
+    }
+
+    fn on_connection_closed(&mut self, info: &ConnectionCloseInfo) {
+        self.limiter.on_connection_closed(info)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    expires_at: Duration,
+    outcome: Outcome,
+}
+
+/// Tracks the cached outcomes for each prefix using bounded memory
+///
+/// Entries are split into two generations, the same way as the rate limiter's buckets. Once the
+/// current generation is full, it replaces the previous generation, forgetting the prefixes that
+/// weren't dropped again since the last rotation.
+#[derive(Debug)]
+struct Entries {
+    current: HashMap<Prefix, Entry>,
+    previous: HashMap<Prefix, Entry>,
+    generation_len: usize,
+}
+
+impl Entries {
+    fn new(generation_len: usize) -> Self {
+        Self {
+            current: HashMap::new(),
+            previous: HashMap::new(),
+            generation_len,
+        }
+    }
+
+    fn get(&self, prefix: &Prefix, now: Duration) -> Option<Outcome> {
+        let entry = self
+            .current
+            .get(prefix)
+            .or_else(|| self.previous.get(prefix))?;
+
+        if entry.expires_at > now {
+            Some(entry.outcome.clone())
+        } else {
+            None
+        }
+    }
+
+    fn insert(&mut self, prefix: Prefix, expires_at: Duration, outcome: Outcome) {
+        self.previous.remove(&prefix);
+
+        if !self.current.contains_key(&prefix) && self.current.len() >= self.generation_len {
+            self.previous = core::mem::take(&mut self.current);
+        }
+
+        self.current.insert(
+            prefix,
+            Entry {
+                expires_at,
+                outcome,
+            },
+        );
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.current.len() + self.previous.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::provider::endpoint_limits::{ConnectionAttemptBuilder, TokenStatus};
+    use s2n_quic_core::{inet::SocketAddressV4, time};
+
+    const TTL: Duration = Duration::from_secs(1);
+
+    /// Drops every attempt, counting how many times it was consulted
+    #[derive(Default)]
+    struct DropAll {
+        calls: usize,
+    }
+
+    impl Limiter for DropAll {
+        fn on_connection_attempt(&mut self, _info: &ConnectionAttempt) -> Outcome {
+            self.calls += 1;
+            Outcome::drop().with_reason("flood")
+        }
+    }
+
+    fn attempt(ip: [u8; 4], timestamp: Duration) -> ConnectionAttemptBuilder {
+        ConnectionAttempt::builder()
+            .with_remote_address(SocketAddressV4::new(ip, 443))
+            .with_timestamp(time::testing::now() + timestamp)
+    }
+
+    fn cache(enabled: bool) -> DecisionCache<DropAll> {
+        DecisionCache::builder()
+            .with_enabled(enabled)
+            .unwrap()
+            .with_ttl(TTL)
+            .unwrap()
+            .build(DropAll::default())
+            .unwrap()
+    }
+
+    #[test]
+    fn ttl_test() {
+        let mut cache = cache(true);
+        let start = Duration::from_secs(1);
+
+        // the wrapped limiter is only consulted once per TTL for the dropped source
+        for windows in 1..=3 {
+            let window_start = start + TTL * (windows - 1);
+            for step in 0..10 {
+                let now = window_start + TTL / 10 * step;
+                let outcome = cache.on_connection_attempt(&attempt([10, 0, 0, 1], now).build());
+                assert!(outcome.is_drop());
+                // the reason from the wrapped limiter is kept
+                assert_eq!(outcome.reason(), Some("flood"));
+            }
+            assert_eq!(cache.limiter.calls, windows as usize);
+        }
+
+        assert_eq!(
+            cache.snapshot(),
+            CacheStats {
+                hits: 27,
+                misses: 3
+            }
+        );
+
+        // other sources aren't affected
+        cache.on_connection_attempt(&attempt([10, 0, 0, 2], start).build());
+        assert_eq!(cache.limiter.calls, 4);
+    }
+
+    #[test]
+    fn disabled_test() {
+        let mut cache = cache(false);
+
+        for _ in 0..10 {
+            cache.on_connection_attempt(&attempt([10, 0, 0, 1], Duration::ZERO).build());
+        }
+
+        assert_eq!(cache.limiter.calls, 10);
+        assert_eq!(
+            cache.snapshot(),
+            CacheStats {
+                hits: 0,
+                misses: 10
+            }
+        );
+    }
+
+    #[test]
+    fn valid_token_test() {
+        let mut cache = cache(true);
+        let now = Duration::from_secs(1);
+
+        cache.on_connection_attempt(&attempt([10, 0, 0, 1], now).build());
+
+        // attempts which validated their address bypass the cache
+        let info = attempt([10, 0, 0, 1], now)
+            .with_token_status(TokenStatus::RetryToken { valid: true })
+            .build();
+        cache.on_connection_attempt(&info);
+        assert_eq!(cache.limiter.calls, 2);
+    }
+
+    #[test]
+    fn allowed_outcomes_test() {
+        struct AllowAll;
+
+        impl Limiter for AllowAll {
+            fn on_connection_attempt(&mut self, _info: &ConnectionAttempt) -> Outcome {
+                Outcome::allow()
+            }
+        }
+
+        let mut cache = DecisionCache::new(AllowAll);
+        let now = Duration::from_secs(1);
+
+        // only dropped attempts are cached
+        for _ in 0..10 {
+            assert!(cache
+                .on_connection_attempt(&attempt([10, 0, 0, 1], now).build())
+                .is_allow());
+        }
+        assert_eq!(cache.entries.len(), 0);
+        assert_eq!(cache.snapshot().misses, 10);
+    }
+
+    #[test]
+    fn bounded_entries_test() {
+        let mut entries = Entries::new(4);
+        let expires_at = Duration::from_secs(10);
+        let now = Duration::from_secs(1);
+
+        for ip in 0..=255 {
+            entries.insert(Prefix::IpV4([10, 0, 0, ip]), expires_at, Outcome::drop());
+            assert!(entries.len() <= 8);
+        }
+
+        // the most recently inserted prefixes are retained
+        assert!(entries.get(&Prefix::IpV4([10, 0, 0, 255]), now).is_some());
+        assert!(entries.get(&Prefix::IpV4([10, 0, 0, 0]), now).is_none());
+    }
+
+    #[test]
+    fn builder_test() {
+        assert!(DecisionCache::builder().with_ttl(Duration::ZERO).is_err());
+        assert!(DecisionCache::builder().with_ipv4_prefix_len(33).is_err());
+        assert!(DecisionCache::builder().with_ipv6_prefix_len(129).is_err());
+        assert!(DecisionCache::builder().with_max_entries(1).is_err());
+    }
+}
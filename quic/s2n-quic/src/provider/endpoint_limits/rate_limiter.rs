@@ -0,0 +1,581 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Limits the rate of connection attempts from each source address prefix
+//!
+//! Each prefix is given a token bucket which refills at a configurable rate up to a burst size.
+//! Attempts are allowed while the bucket has tokens. Once the bucket is empty, attempts are sent
+//! a Retry packet, which requires the peer to prove that it owns the address before the
+//! connection proceeds. Attempts beyond a second threshold are dropped. Attempts which present a
+//! valid address validation token aren't limited.
+//!
+//! ```rust
+//! use s2n_quic::provider::endpoint_limits::rate_limiter::RateLimiter;
+//! # use std::{error::Error, time::Duration};
+//! # fn main() -> Result<(), Box<dyn Error>> {
+//! // allow 10 attempts per second from each /24 prefix after an initial burst of 20 attempts
+//! let limiter = RateLimiter::builder()
+//!     .with_rate(10, Duration::from_secs(1))?
+//!     .with_burst(20)?
+//!     .with_ipv4_prefix_len(24)?
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::{BuildError, ConnectionAttempt, Limiter, Outcome};
+use core::time::Duration;
+use s2n_quic_core::event::api::SocketAddress;
+use std::collections::HashMap;
+
+/// The reason reported for attempts dropped by the rate limiter
+const DROP_REASON: &str = "rate limited";
+
+/// Allows the rate limiter to be built with specific values
+///
+/// With the `serde` feature, the builder can be serialized and deserialized. The rate is set with
+/// `refill_interval`, the time it takes for a bucket to gain a token, and the other fields match
+/// the `with_*` methods. Missing fields keep their defaults.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Builder {
+    #[cfg_attr(feature = "serde", serde(with = "s2n_quic_core::time::serde"))]
+    refill_interval: Duration,
+    burst: u32,
+    retry_burst: u32,
+    ipv4_prefix_len: u8,
+    ipv6_prefix_len: u8,
+    max_entries: usize,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self {
+            refill_interval: Duration::from_millis(100),
+            burst: 20,
+            retry_burst: 20,
+            ipv4_prefix_len: 32,
+            ipv6_prefix_len: 56,
+            max_entries: 65_536,
+        }
+    }
+}
+
+impl Builder {
+    /// Sets the rate at which each bucket is refilled to `attempts` per `period`
+    ///
+    /// Defaults to 10 attempts per second.
+    pub fn with_rate(mut self, attempts: u32, period: Duration) -> Result<Self, BuildError> {
+        if attempts == 0 {
+            return Err(BuildError("the rate must allow at least one attempt"));
+        }
+
+        let refill_interval = period / attempts;
+        if refill_interval.is_zero() {
+            return Err(BuildError(
+                "the rate must be less than one attempt per nanosecond",
+            ));
+        }
+
+        self.refill_interval = refill_interval;
+        Ok(self)
+    }
+
+    /// Sets the number of attempts allowed from a prefix before the rate applies
+    ///
+    /// Defaults to 20.
+    pub fn with_burst(mut self, burst: u32) -> Result<Self, BuildError> {
+        if burst == 0 {
+            return Err(BuildError("the burst must be at least one attempt"));
+        }
+
+        self.burst = burst;
+        Ok(self)
+    }
+
+    /// Sets the number of attempts over the rate that are sent a Retry packet before further
+    /// attempts are dropped
+    ///
+    /// Defaults to 20.
+    pub fn with_retry_burst(mut self, retry_burst: u32) -> Result<Self, BuildError> {
+        self.retry_burst = retry_burst;
+        Ok(self)
+    }
+
+    /// Sets the length of the prefix IPv4 addresses are grouped by
+    ///
+    /// Defaults to 32, which limits each address separately.
+    pub fn with_ipv4_prefix_len(mut self, len: u8) -> Result<Self, BuildError> {
+        if len > 32 {
+            return Err(BuildError("the IPv4 prefix length must be at most 32"));
+        }
+
+        self.ipv4_prefix_len = len;
+        Ok(self)
+    }
+
+    /// Sets the length of the prefix IPv6 addresses are grouped by
+    ///
+    /// Defaults to 56, since peers are commonly assigned an entire prefix rather than a single
+    /// address.
+    pub fn with_ipv6_prefix_len(mut self, len: u8) -> Result<Self, BuildError> {
+        if len > 128 {
+            return Err(BuildError("the IPv6 prefix length must be at most 128"));
+        }
+
+        self.ipv6_prefix_len = len;
+        Ok(self)
+    }
+
+    /// Sets the maximum number of prefixes tracked at once
+    ///
+    /// Once the limit is reached, the prefixes that were least recently seen are forgotten and
+    /// start with a full bucket the next time they are seen. Defaults to 65,536.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Result<Self, BuildError> {
+        if max_entries < 2 {
+            return Err(BuildError(
+                "the rate limiter must track at least two entries",
+            ));
+        }
+
+        self.max_entries = max_entries;
+        Ok(self)
+    }
+
+    /// Build the rate limiter
+    pub fn build(self) -> Result<RateLimiter, BuildError> {
+        Ok(RateLimiter {
+            config: Config {
+                refill_interval: self.refill_interval,
+                burst: self.burst,
+                retry_burst: self.retry_burst,
+            },
+            ipv4_prefix_len: self.ipv4_prefix_len,
+            ipv6_prefix_len: self.ipv6_prefix_len,
+            buckets: Buckets::new(self.max_entries / 2),
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Builder {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error as _;
+
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Settings {
+            #[serde(default, with = "s2n_quic_core::time::serde::option")]
+            refill_interval: Option<Duration>,
+            burst: Option<u32>,
+            retry_burst: Option<u32>,
+            ipv4_prefix_len: Option<u8>,
+            ipv6_prefix_len: Option<u8>,
+            max_entries: Option<usize>,
+        }
+
+        // apply each setting with its `with_*` method so it is validated the same way
+        let settings = <Settings as serde::Deserialize>::deserialize(deserializer)?;
+        let mut builder = Self::default();
+        if let Some(refill_interval) = settings.refill_interval {
+            builder = builder
+                .with_rate(1, refill_interval)
+                .map_err(D::Error::custom)?;
+        }
+        if let Some(burst) = settings.burst {
+            builder = builder.with_burst(burst).map_err(D::Error::custom)?;
+        }
+        if let Some(retry_burst) = settings.retry_burst {
+            builder = builder
+                .with_retry_burst(retry_burst)
+                .map_err(D::Error::custom)?;
+        }
+        if let Some(len) = settings.ipv4_prefix_len {
+            builder = builder
+                .with_ipv4_prefix_len(len)
+                .map_err(D::Error::custom)?;
+        }
+        if let Some(len) = settings.ipv6_prefix_len {
+            builder = builder
+                .with_ipv6_prefix_len(len)
+                .map_err(D::Error::custom)?;
+        }
+        if let Some(max_entries) = settings.max_entries {
+            builder = builder
+                .with_max_entries(max_entries)
+                .map_err(D::Error::custom)?;
+        }
+        Ok(builder)
+    }
+}
+
+/// A [`Limiter`] which limits the rate of connection attempts from each source address prefix
+///
+/// Buckets are refilled using the timestamp of each attempt, rather than the system time, so the
+/// limiter behaves the same when the endpoint is driven by a simulated clock.
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: Config,
+    ipv4_prefix_len: u8,
+    ipv6_prefix_len: u8,
+    buckets: Buckets,
+}
+
+impl RateLimiter {
+    pub fn builder() -> Builder {
+        Builder::default()
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::builder().build().unwrap()
+    }
+}
+
+impl Limiter for RateLimiter {
+    fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+        // clients returning with a valid address validation token have proven they own their
+        // address, and were already charged for the attempt which was sent a Retry packet
+        if info.token_status.is_valid() {
+            return Outcome::allow();
+        }
+
+        let prefix = match Prefix::new(
+            &info.remote_address,
+            self.ipv4_prefix_len,
+            self.ipv6_prefix_len,
+        ) {
+            Some(prefix) => prefix,
+            // don't limit address families we don't understand
+            None => return Outcome::allow(),
+        };
+
+        let now = info.timestamp.duration_since_start();
+        let bucket = self.buckets.get_or_insert(prefix, now);
+
+        match bucket.on_attempt(&self.config, now) {
+            Decision::Allow => Outcome::allow(),
+            Decision::Retry => Outcome::retry(),
+            Decision::Drop => Outcome::drop().with_reason(DROP_REASON),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(super) enum Prefix {
+    IpV4([u8; 4]),
+    IpV6([u8; 16]),
+}
+
+impl Prefix {
+    /// Returns the prefix of the address, or `None` if the address family isn't understood
+    ///
+    /// IPv4-mapped IPv6 addresses, which dual-stack sockets report for IPv4 peers, use the prefix
+    /// of the IPv4 address they map.
+    pub(super) fn new(
+        address: &SocketAddress,
+        ipv4_prefix_len: u8,
+        ipv6_prefix_len: u8,
+    ) -> Option<Self> {
+        let prefix = match address.unmap() {
+            SocketAddress::IpV4 { ip, .. } => Self::IpV4(mask(*ip, ipv4_prefix_len)),
+            SocketAddress::IpV6 { ip, .. } => Self::IpV6(mask(*ip, ipv6_prefix_len)),
+            _ => return None,
+        };
+
+        Some(prefix)
+    }
+}
+
+/// Clears all of the bits of the address after the first `prefix_len` bits
+pub(super) fn mask<const N: usize>(mut ip: [u8; N], prefix_len: u8) -> [u8; N] {
+    let prefix_len = prefix_len as usize;
+
+    for (index, byte) in ip.iter_mut().enumerate() {
+        let start = index * 8;
+        if prefix_len <= start {
+            *byte = 0;
+        } else if prefix_len < start + 8 {
+            *byte &= !(0xff >> (prefix_len - start));
+        }
+    }
+
+    ip
+}
+
+/// Tracks the buckets for each prefix using bounded memory
+///
+/// Buckets are split into two generations. Once the current generation is full, it replaces the
+/// previous generation, forgetting any prefixes that haven't been seen since the last rotation.
+/// Prefixes seen in the previous generation are moved back into the current generation, so
+/// active prefixes are retained.
+#[derive(Debug)]
+struct Buckets {
+    current: HashMap<Prefix, Bucket>,
+    previous: HashMap<Prefix, Bucket>,
+    generation_len: usize,
+}
+
+impl Buckets {
+    fn new(generation_len: usize) -> Self {
+        Self {
+            current: HashMap::new(),
+            previous: HashMap::new(),
+            generation_len,
+        }
+    }
+
+    fn get_or_insert(&mut self, prefix: Prefix, now: Duration) -> &mut Bucket {
+        if !self.current.contains_key(&prefix) {
+            let bucket = self
+                .previous
+                .remove(&prefix)
+                .unwrap_or_else(|| Bucket::new(now));
+
+            if self.current.len() >= self.generation_len {
+                self.previous = core::mem::take(&mut self.current);
+            }
+
+            self.current.insert(prefix, bucket);
+        }
+
+        self.current.get_mut(&prefix).unwrap()
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.current.len() + self.previous.len()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Config {
+    /// The time it takes to refill a single token
+    refill_interval: Duration,
+    burst: u32,
+    retry_burst: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Decision {
+    Allow,
+    Retry,
+    Drop,
+}
+
+/// A token bucket for a single prefix
+///
+/// Rather than storing the number of tokens, the bucket stores the time at which it will be full
+/// again. This avoids needing to refill the bucket on a timer, and allows the bucket to go into
+/// debt by up to `retry_burst` tokens for attempts that are sent a Retry packet.
+#[derive(Clone, Copy, Debug)]
+struct Bucket {
+    full_at: Duration,
+}
+
+impl Bucket {
+    fn new(now: Duration) -> Self {
+        Self { full_at: now }
+    }
+
+    fn on_attempt(&mut self, config: &Config, now: Duration) -> Decision {
+        // the amount of time before the bucket is full, which is proportional to the number of
+        // tokens taken
+        let deficit = self.full_at.saturating_sub(now);
+
+        let allowed = config.burst - 1;
+        let retried = allowed.saturating_add(config.retry_burst);
+
+        let decision = if deficit <= config.refill_interval.saturating_mul(allowed) {
+            Decision::Allow
+        } else if deficit <= config.refill_interval.saturating_mul(retried) {
+            Decision::Retry
+        } else {
+            // dropped attempts don't take a token, so the bucket still refills during a flood
+            return Decision::Drop;
+        };
+
+        self.full_at = self.full_at.max(now) + config.refill_interval;
+
+        decision
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(refill_interval: Duration, burst: u32, retry_burst: u32) -> Config {
+        Config {
+            refill_interval,
+            burst,
+            retry_burst,
+        }
+    }
+
+    /// Returns the number of consecutive attempts with the given decision
+    fn count(bucket: &mut Bucket, config: &Config, now: Duration, decision: Decision) -> u32 {
+        let mut count = 0;
+        // bound the loop in case the bucket never changes its decision
+        while count <= config.burst.saturating_add(config.retry_burst) {
+            if bucket.on_attempt(config, now) != decision {
+                break;
+            }
+            count += 1;
+        }
+        count
+    }
+
+    #[test]
+    fn bucket_burst_test() {
+        bolero::check!()
+            .with_type::<(u16, u8, u8)>()
+            .cloned()
+            .for_each(|(interval, burst, retry_burst)| {
+                let interval = Duration::from_millis(interval as u64 + 1);
+                let burst = burst as u32 + 1;
+                let retry_burst = retry_burst as u32;
+                let config = config(interval, burst, retry_burst);
+                let now = Duration::from_secs(1);
+                let mut bucket = Bucket::new(now);
+
+                assert_eq!(count(&mut bucket, &config, now, Decision::Allow), burst);
+
+                // the first attempt over the burst was already made by the previous `count`
+                let retries = count(&mut bucket, &config, now, Decision::Retry);
+                let expected = if retry_burst == 0 { 0 } else { retry_burst - 1 };
+                assert_eq!(retries, expected);
+
+                assert_eq!(bucket.on_attempt(&config, now), Decision::Drop);
+            });
+    }
+
+    #[test]
+    fn bucket_refill_test() {
+        bolero::check!()
+            .with_type::<(u16, u8, u32)>()
+            .cloned()
+            .for_each(|(interval, burst, idle)| {
+                let interval = Duration::from_millis(interval as u64 + 1);
+                let burst = burst as u32 + 1;
+                let idle = Duration::from_millis(idle as u64);
+                let config = config(interval, burst, 0);
+                let start = Duration::from_secs(1);
+                let mut bucket = Bucket::new(start);
+
+                // empty the bucket
+                for _ in 0..burst {
+                    assert_eq!(bucket.on_attempt(&config, start), Decision::Allow);
+                }
+
+                // a token is refilled for each interval that elapsed, up to the burst
+                let now = start + idle;
+                let refilled = (idle.as_nanos() / interval.as_nanos()).min(burst as u128) as u32;
+                assert_eq!(count(&mut bucket, &config, now, Decision::Allow), refilled);
+            });
+    }
+
+    #[test]
+    fn dropped_attempts_test() {
+        let interval = Duration::from_secs(1);
+        let config = config(interval, 1, 0);
+        let start = Duration::from_secs(1);
+        let mut bucket = Bucket::new(start);
+
+        assert_eq!(bucket.on_attempt(&config, start), Decision::Allow);
+
+        // dropped attempts don't delay the bucket from refilling
+        for _ in 0..100 {
+            assert_eq!(bucket.on_attempt(&config, start), Decision::Drop);
+        }
+        assert_eq!(
+            bucket.on_attempt(&config, start + interval),
+            Decision::Allow
+        );
+    }
+
+    #[test]
+    fn mask_test() {
+        assert_eq!(mask([192, 168, 1, 255], 32), [192, 168, 1, 255]);
+        assert_eq!(mask([192, 168, 1, 255], 24), [192, 168, 1, 0]);
+        assert_eq!(mask([192, 168, 1, 255], 20), [192, 168, 0, 0]);
+        assert_eq!(mask([192, 168, 1, 255], 0), [0, 0, 0, 0]);
+
+        let ip = [0xff; 16];
+        let mut expected = [0; 16];
+        expected[..7].copy_from_slice(&[0xff; 7]);
+        assert_eq!(mask(ip, 56), expected);
+        expected[7] = 0xe0;
+        assert_eq!(mask(ip, 59), expected);
+        assert_eq!(mask(ip, 128), ip);
+    }
+
+    #[test]
+    fn bounded_entries_test() {
+        let mut buckets = Buckets::new(4);
+        let now = Duration::from_secs(1);
+
+        for ip in 0..=255 {
+            buckets.get_or_insert(Prefix::IpV4([10, 0, 0, ip]), now);
+            assert!(buckets.len() <= 8);
+        }
+
+        // recently seen prefixes are retained
+        let config = config(Duration::from_secs(1), 1, 0);
+        let active = Prefix::IpV4([192, 168, 0, 1]);
+        assert_eq!(
+            buckets.get_or_insert(active, now).on_attempt(&config, now),
+            Decision::Allow
+        );
+        for ip in 0..=255 {
+            buckets.get_or_insert(Prefix::IpV4([10, 0, 1, ip]), now);
+            assert_eq!(
+                buckets.get_or_insert(active, now).on_attempt(&config, now),
+                Decision::Drop
+            );
+        }
+    }
+
+    #[test]
+    fn mapped_address_test() {
+        use s2n_quic_core::inet::SocketAddressV4;
+
+        let mut limiter = RateLimiter::builder()
+            .with_burst(1)
+            .unwrap()
+            .with_retry_burst(0)
+            .unwrap()
+            .build()
+            .unwrap();
+        let address = SocketAddressV4::new([192, 168, 1, 2], 443);
+
+        let attempt = ConnectionAttempt::builder()
+            .with_remote_address(address)
+            .with_timestamp(s2n_quic_core::time::testing::now() + Duration::from_secs(1));
+        assert!(limiter.on_connection_attempt(&attempt.build()).is_allow());
+
+        // the IPv4-mapped form of the address shares its bucket
+        let attempt = attempt.with_remote_address(address.to_ipv6_mapped());
+        assert!(limiter.on_connection_attempt(&attempt.build()).is_drop());
+    }
+
+    #[test]
+    fn builder_test() {
+        assert!(RateLimiter::builder()
+            .with_rate(0, Duration::from_secs(1))
+            .is_err());
+        assert!(RateLimiter::builder()
+            .with_rate(10, Duration::ZERO)
+            .is_err());
+        assert!(RateLimiter::builder().with_burst(0).is_err());
+        assert!(RateLimiter::builder().with_ipv4_prefix_len(33).is_err());
+        assert!(RateLimiter::builder().with_ipv6_prefix_len(129).is_err());
+        assert!(RateLimiter::builder().with_max_entries(1).is_err());
+
+        let builder = RateLimiter::builder()
+            .with_rate(4, Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(builder.refill_interval, Duration::from_millis(250));
+    }
+}
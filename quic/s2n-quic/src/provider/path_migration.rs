@@ -1,15 +1,20 @@
 // Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
 // SPDX-License-Identifier: Apache-2.0
 
-// this functionality isn't public but should be assumed that it
-// eventually will be
+//! Provides support for validating connection migration attempts
+//!
+//! Each time a peer attempts to migrate to a new address, the validator is called with the
+//! addresses involved and the number of paths already being validated. The validator can allow
+//! the migration to proceed with path validation, deny it by dropping the packet, or close the
+//! connection.
+
 #[allow(unused_imports)]
 pub use s2n_quic_core::path::migration::{
     default::{self, Validator as Default},
-    disabled, Attempt, Outcome, Validator,
+    disabled, Attempt, DenyReason, Outcome, PacketInfo, Validator,
 };
 
-/// Provides limits support for an endpoint
+/// Provides path migration validation support for an endpoint
 pub trait Provider {
     type Validator: 'static + Send + Validator;
     type Error: 'static + core::fmt::Display + Send + Sync;
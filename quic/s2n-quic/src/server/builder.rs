@@ -146,6 +146,60 @@ impl<Providers: ServerProviders> Builder<Providers> {
         /// #    Ok(())
         /// # }
         /// ```
+        ///
+        /// Sends Retry packets while more than 100 handshakes are in progress, using a closure
+        ///
+        /// ```rust,no_run
+        /// # use std::error::Error;
+        /// use s2n_quic::{
+        ///     provider::endpoint_limits::{ConnectionAttempt, Outcome},
+        ///     Server,
+        /// };
+        ///
+        /// # #[tokio::main]
+        /// # async fn main() -> Result<(), Box<dyn Error>> {
+        /// let server = Server::builder()
+        ///     .with_endpoint_limits(|attempt: &ConnectionAttempt| {
+        ///         if attempt.inflight_handshakes > 100 {
+        ///             Outcome::retry()
+        ///         } else {
+        ///             Outcome::allow()
+        ///         }
+        ///     })?
+        ///     .start()?;
+        /// #
+        /// #    Ok(())
+        /// # }
+        /// ```
+        ///
+        /// Chooses the limiter at runtime with a boxed limiter
+        ///
+        /// ```rust,no_run
+        /// # use std::error::Error;
+        /// use s2n_quic::{
+        ///     provider::endpoint_limits::{self, Limiter},
+        ///     Server,
+        /// };
+        ///
+        /// # #[tokio::main]
+        /// # async fn main() -> Result<(), Box<dyn Error>> {
+        /// # let max_connections: Option<usize> = None;
+        /// let limiter: Box<dyn Limiter> = match max_connections {
+        ///     Some(limit) => Box::new(
+        ///         endpoint_limits::Default::builder()
+        ///             .with_max_connections(limit)?
+        ///             .build()?,
+        ///     ),
+        ///     None => Box::new(endpoint_limits::Default::default()),
+        /// };
+        ///
+        /// let server = Server::builder()
+        ///     .with_endpoint_limits(limiter)?
+        ///     .start()?;
+        /// #
+        /// #    Ok(())
+        /// # }
+        /// ```
         with_endpoint_limits,
         endpoint_limits,
         ServerProviders
@@ -303,6 +357,17 @@ impl<Providers: ServerProviders> Builder<Providers> {
         ServerProviders
     );
 
+    #[cfg(any(test, feature = "unstable-provider-path-migration"))]
+    impl_provider_method!(
+        /// Sets the path migration provider for the [`Server`]
+        ///
+        /// The provider is called on each connection migration attempt and can allow or deny
+        /// the attempt, or close the connection.
+        with_path_migration,
+        path_migration,
+        ServerProviders
+    );
+
     #[cfg(any(test, feature = "unstable-provider-random"))]
     impl_provider_method!(
         /// Sets the random provider for the [`Server`]
@@ -126,6 +126,7 @@ impl<
             sync,
             tls,
             address_token,
+            token_store: Default::default(),
             path_handle: PhantomData,
             path_migration,
             datagram,
@@ -173,6 +174,8 @@ struct EndpointConfig<
     sync: Sync,
     tls: Tls,
     address_token: AddressToken,
+    // servers issue tokens but never receive them
+    token_store: address_token_store::Disabled,
     path_handle: PhantomData<PathHandle>,
     path_migration: PathMigration,
     datagram: Datagram,
@@ -266,6 +269,7 @@ impl<
     type EventSubscriber = Event;
     type TLSEndpoint = Tls;
     type TokenFormat = AddressToken;
+    type TokenStore = address_token_store::Disabled;
     type ConnectionLimits = Limits;
     type StreamManager = stream::DefaultStreamManager;
     type PathMigrationValidator = PathMigration;
@@ -285,6 +289,7 @@ impl<
             tls: &mut self.tls,
             endpoint_limits: &mut self.endpoint_limits,
             token: &mut self.address_token,
+            token_store: &mut self.token_store,
             connection_limits: &mut self.limits,
             event_subscriber: &mut self.event,
             path_migration: &mut self.path_migration,
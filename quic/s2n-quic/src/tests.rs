@@ -28,11 +28,16 @@ mod resumption;
 mod setup;
 use setup::*;
 
+mod ack_frequency;
 mod blackhole;
+mod connection_limits;
 mod connection_migration;
+mod datagram;
+mod grease;
 mod handshake_cid_rotation;
 mod interceptor;
 mod mtu;
+mod new_token;
 mod no_tls;
 mod pto;
 mod self_test;
@@ -55,3 +60,4 @@ mod issue_1427;
 mod issue_1464;
 mod issue_1717;
 mod issue_954;
+mod server_name;
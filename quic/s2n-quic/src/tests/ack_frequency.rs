@@ -0,0 +1,131 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::provider::limits::Limits;
+use s2n_quic_core::event::api::{Frame, FrameSent};
+
+// Transfers data from the client to the server, with the client requesting the given
+// ACK frequency. The frames sent by the server and client are returned.
+fn ack_frequency_transfer<I>(
+    server_limits: Limits,
+    client_limits: Limits,
+    client_interceptor: I,
+) -> (Vec<events::FrameSent>, Vec<events::FrameSent>)
+where
+    I: 'static + provider::packet_interceptor::PacketInterceptor + Send,
+{
+    let model = Model::default();
+
+    let server_subscriber = recorder::FrameSent::new();
+    let server_events = server_subscriber.events();
+    let client_subscriber = recorder::FrameSent::new();
+    let client_events = client_subscriber.events();
+
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event((tracing_events(), server_subscriber))?
+            .with_random(Random::with_seed(456))?
+            .with_limits(server_limits)?
+            .start()?;
+        let client = Client::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event((tracing_events(), client_subscriber))?
+            .with_random(Random::with_seed(456))?
+            .with_limits(client_limits)?
+            .with_packet_interceptor(client_interceptor)?
+            .start()?;
+        let addr = start_server(server)?;
+        start_client(client, addr, Data::new(200_000))?;
+        Ok(addr)
+    })
+    .unwrap();
+
+    let server_events = server_events.lock().unwrap();
+    let client_events = client_events.lock().unwrap();
+    (server_events.clone(), client_events.clone())
+}
+
+fn server_limits() -> Limits {
+    Limits::default()
+        .with_min_ack_delay(Duration::from_millis(1))
+        .unwrap()
+}
+
+fn client_limits() -> Limits {
+    Limits::default()
+        .with_requested_ack_eliciting_threshold(20)
+        .unwrap()
+        .with_requested_max_ack_delay(Duration::from_millis(50))
+        .unwrap()
+}
+
+fn ack_count(events: &[FrameSent]) -> usize {
+    events
+        .iter()
+        .filter(|frame_sent| matches!(frame_sent.frame, Frame::Ack { .. }))
+        .count()
+}
+
+fn ack_frequency_requests(events: &[FrameSent]) -> Vec<(u64, u64)> {
+    events
+        .iter()
+        .filter_map(|frame_sent| match frame_sent.frame {
+            Frame::AckFrequency {
+                sequence_number,
+                ack_eliciting_threshold,
+                ..
+            } => Some((sequence_number, ack_eliciting_threshold)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn ack_frequency_test() {
+    let (default_server_events, _) = ack_frequency_transfer(
+        Limits::default(),
+        Limits::default(),
+        provider::packet_interceptor::Disabled,
+    );
+    let (server_events, client_events) = ack_frequency_transfer(
+        server_limits(),
+        client_limits(),
+        provider::packet_interceptor::Disabled,
+    );
+
+    // The client requests the configured threshold once
+    assert_eq!(ack_frequency_requests(&client_events), vec![(0, 20)]);
+
+    // The server sends fewer ACKs after the request is received
+    assert!(ack_count(&server_events) < ack_count(&default_server_events));
+}
+
+// The request is only sent when the peer advertises support for the extension
+#[test]
+fn ack_frequency_unsupported_test() {
+    let (_, client_events) = ack_frequency_transfer(
+        Limits::default(),
+        client_limits(),
+        provider::packet_interceptor::Disabled,
+    );
+
+    assert!(ack_frequency_requests(&client_events).is_empty());
+}
+
+// Loss detection still recovers lost packets while ACKs are delayed
+#[test]
+fn ack_frequency_loss_test() {
+    let loss = Loss::builder(Random::with_seed(123))
+        .with_tx_loss(0..2)
+        .with_tx_pass(1..20)
+        .build();
+    let (server_events, client_events) =
+        ack_frequency_transfer(server_limits(), client_limits(), loss);
+
+    assert!(!ack_frequency_requests(&client_events).is_empty());
+    assert!(ack_count(&server_events) > 0);
+}
@@ -0,0 +1,1619 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::provider::{
+    endpoint_limits::{
+        self, concurrency_limiter::ConcurrencyLimiter, rate_limiter::RateLimiter, AttemptId,
+        ConnectionAttempt, ConnectionCloseInfo, ConnectionOpenInfo, HandshakeInfo, Limiter,
+        Outcome, ServerNameAttempt, TokenStatus,
+    },
+    event::{
+        events::{self, ConnectionInfo, ConnectionMeta, EndpointMeta, Subscriber},
+        Timestamp,
+    },
+};
+use s2n_codec::{DecoderBuffer, DecoderBufferMut, EncoderBuffer};
+use s2n_quic_core::{
+    connection,
+    event::api::Subject,
+    frame::{Frame, FrameMut},
+    packet::interceptor::{Datagram, Interceptor, Packet},
+    transport,
+    varint::VarInt,
+};
+use std::{
+    collections::HashMap,
+    task::{Context, Poll, Waker},
+};
+
+const MAX_CONNECTIONS: usize = 3;
+
+struct MaxConnections(usize);
+
+impl Limiter for MaxConnections {
+    fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+        if info.connection_count >= self.0 {
+            Outcome::close()
+        } else {
+            Outcome::allow()
+        }
+    }
+}
+
+/// Ensures a limiter can cap the number of open connections using the connection count
+#[test]
+fn max_connections_test() {
+    let model = Model::default();
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(MaxConnections(MAX_CONNECTIONS))?
+            .start()?;
+        let server_addr = start_server(server)?;
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+
+            let mut connections = vec![];
+            for _ in 0..MAX_CONNECTIONS {
+                connections.push(client.connect(connect.clone()).await.unwrap());
+            }
+
+            // the endpoint is at its limit while the existing connections are held open
+            assert!(client.connect(connect.clone()).await.is_err());
+
+            // closed connections stop being counted once the server has finalized them
+            drop(connections.pop());
+            delay(Duration::from_secs(5)).await;
+
+            connections.push(client.connect(connect).await.unwrap());
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+}
+
+struct Refuse(Outcome);
+
+impl Limiter for Refuse {
+    fn on_connection_attempt(&mut self, _info: &ConnectionAttempt) -> Outcome {
+        self.0.clone()
+    }
+}
+
+/// Records the error code and reason of each CONNECTION_CLOSE frame received
+#[derive(Clone, Default)]
+struct CloseRecorder(Arc<Mutex<Vec<(u64, Vec<u8>)>>>);
+
+impl Interceptor for CloseRecorder {
+    fn intercept_rx_payload<'a>(
+        &mut self,
+        _subject: &Subject,
+        _packet: &Packet,
+        payload: DecoderBufferMut<'a>,
+    ) -> DecoderBufferMut<'a> {
+        let payload = payload.into_less_safe_slice();
+
+        let mut frames = payload.to_vec();
+        let mut buffer = DecoderBufferMut::new(&mut frames);
+        while let Ok((frame, remaining)) = buffer.decode::<FrameMut>() {
+            if let Frame::ConnectionClose(frame) = frame {
+                let reason = frame.reason.unwrap_or_default().to_vec();
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((frame.error_code.as_u64(), reason));
+            }
+            buffer = remaining;
+        }
+
+        DecoderBufferMut::new(payload)
+    }
+}
+
+fn refused_connection(outcome: Outcome) -> (connection::Error, Vec<(u64, Vec<u8>)>) {
+    let model = Model::default();
+    let recorder = CloseRecorder::default();
+    let closes = recorder.0.clone();
+    let error = Arc::new(Mutex::new(None));
+    let client_error = error.clone();
+
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(Refuse(outcome))?
+            .start()?;
+        let server_addr = start_server(server)?;
+
+        let client = Client::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_packet_interceptor(recorder)?
+            .start()?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let error = client.connect(connect).await.unwrap_err();
+            *client_error.lock().unwrap() = Some(error);
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    let error = error.lock().unwrap().take().unwrap();
+    let closes = closes.lock().unwrap().clone();
+    (error, closes)
+}
+
+/// Ensures refused connections are closed with CONNECTION_REFUSED by default
+#[test]
+fn close_connection_refused_test() {
+    let (error, closes) = refused_connection(Outcome::close());
+
+    assert!(
+        matches!(
+            error,
+            connection::Error::Transport { code, .. } if code == transport::Error::CONNECTION_REFUSED.code
+        ),
+        "{error:?}"
+    );
+    assert_eq!(
+        closes,
+        [(transport::Error::CONNECTION_REFUSED.code.as_u64(), vec![])]
+    );
+}
+
+/// Ensures the error code and reason chosen by the limiter are sent to the client
+#[test]
+fn close_error_code_and_reason_test() {
+    let code = transport::Error::INTERNAL_ERROR.code;
+    let outcome = Outcome::close()
+        .with_error_code(VarInt::new(code.as_u64()).unwrap())
+        .with_reason("server busy");
+    let (error, closes) = refused_connection(outcome);
+
+    assert!(
+        matches!(error, connection::Error::Transport { code: actual, .. } if actual == code),
+        "{error:?}"
+    );
+    assert_eq!(closes, [(code.as_u64(), b"server busy".to_vec())]);
+}
+
+/// Records when the first Initial datagram was received and when each Retry packet was sent
+#[derive(Clone, Default)]
+struct RetryRecorder {
+    first_received: Arc<Mutex<Option<Duration>>>,
+    retries_sent: Arc<Mutex<Vec<Duration>>>,
+}
+
+impl Subscriber for RetryRecorder {
+    type ConnectionContext = ();
+
+    fn create_connection_context(
+        &mut self,
+        _meta: &ConnectionMeta,
+        _info: &ConnectionInfo,
+    ) -> Self::ConnectionContext {
+    }
+
+    fn on_endpoint_datagram_received(
+        &mut self,
+        meta: &EndpointMeta,
+        _event: &events::EndpointDatagramReceived,
+    ) {
+        self.first_received
+            .lock()
+            .unwrap()
+            .get_or_insert(meta.timestamp.duration_since_start());
+    }
+
+    fn on_endpoint_packet_sent(&mut self, meta: &EndpointMeta, event: &events::EndpointPacketSent) {
+        if let events::PacketHeader::Retry { .. } = event.packet_header {
+            self.retries_sent
+                .lock()
+                .unwrap()
+                .push(meta.timestamp.duration_since_start());
+        }
+    }
+}
+
+/// Ensures Retry packets are held for the delay chosen by the limiter, and only sent once even
+/// if the client retransmits its Initial packet in the meantime
+#[test]
+fn delayed_retry_test() {
+    let delay = Duration::from_secs(3);
+    let model = Model::default();
+    let recorder = RetryRecorder::default();
+    let first_received = recorder.first_received.clone();
+    let retries_sent = recorder.retries_sent.clone();
+
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event((tracing_events(), recorder))?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(Refuse(Outcome::retry().with_delay(delay)))?
+            .start()?;
+        let server_addr = start_server(server)?;
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            client.connect(connect).await.unwrap();
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    let first_received = first_received.lock().unwrap().unwrap();
+    let retries_sent = retries_sent.lock().unwrap().clone();
+
+    assert_eq!(retries_sent.len(), 1, "{retries_sent:?}");
+    assert!(retries_sent[0] - first_received >= delay);
+}
+
+/// Delays every Retry packet, without room to hold any of them
+struct NoDelayedResponses;
+
+impl Limiter for NoDelayedResponses {
+    fn on_connection_attempt(&mut self, _info: &ConnectionAttempt) -> Outcome {
+        Outcome::retry().with_delay(Duration::from_secs(1))
+    }
+
+    fn max_delayed_responses(&self) -> usize {
+        0
+    }
+}
+
+/// Records the outcome of each response the endpoint dropped
+#[derive(Clone, Default)]
+struct ResponseDropRecorder(Arc<Mutex<Vec<events::ConnectionAttemptOutcome>>>);
+
+impl Subscriber for ResponseDropRecorder {
+    type ConnectionContext = ();
+
+    fn create_connection_context(
+        &mut self,
+        _meta: &ConnectionMeta,
+        _info: &ConnectionInfo,
+    ) -> Self::ConnectionContext {
+    }
+
+    fn on_endpoint_response_dropped(
+        &mut self,
+        _meta: &EndpointMeta,
+        event: &events::EndpointResponseDropped,
+    ) {
+        self.0.lock().unwrap().push(event.outcome.clone());
+    }
+}
+
+/// Ensures delayed responses which don't fit in the endpoint are dropped and reported
+#[test]
+fn dropped_delayed_response_test() {
+    let model = Model::default();
+    let recorder = RetryRecorder::default();
+    let retries_sent = recorder.retries_sent.clone();
+    let drop_recorder = ResponseDropRecorder::default();
+    let dropped = drop_recorder.0.clone();
+
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event((tracing_events(), (recorder, drop_recorder)))?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(NoDelayedResponses)?
+            .start()?;
+        let server_addr = start_server(server)?;
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            assert!(client.connect(connect).await.is_err());
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    assert!(retries_sent.lock().unwrap().is_empty());
+
+    let dropped = dropped.lock().unwrap();
+    assert!(!dropped.is_empty());
+    assert!(dropped
+        .iter()
+        .all(|outcome| matches!(outcome, events::ConnectionAttemptOutcome::Retry { .. })));
+}
+
+/// Records the reason of each rejected connection attempt reported by the endpoint
+#[derive(Clone, Default)]
+struct DropRecorder(Arc<Mutex<Vec<Option<&'static str>>>>);
+
+impl Subscriber for DropRecorder {
+    type ConnectionContext = ();
+
+    fn create_connection_context(
+        &mut self,
+        _meta: &ConnectionMeta,
+        _info: &ConnectionInfo,
+    ) -> Self::ConnectionContext {
+    }
+
+    fn on_endpoint_datagram_dropped(
+        &mut self,
+        _meta: &EndpointMeta,
+        event: &events::EndpointDatagramDropped,
+    ) {
+        if let events::DatagramDropReason::RejectedConnectionAttempt { reason, .. } = event.reason {
+            self.0.lock().unwrap().push(reason);
+        }
+    }
+}
+
+/// Ensures the reason for dropping a connection attempt is reported to event subscribers
+#[test]
+fn drop_reason_test() {
+    let model = Model::default();
+    let recorder = DropRecorder::default();
+    let reasons = recorder.0.clone();
+
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event((tracing_events(), recorder))?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(Refuse(Outcome::drop().with_reason("denylisted prefix")))?
+            .start()?;
+        let server_addr = start_server(server)?;
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            assert!(client.connect(connect).await.is_err());
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    let reasons = reasons.lock().unwrap();
+    assert!(!reasons.is_empty());
+    assert!(reasons
+        .iter()
+        .all(|reason| *reason == Some("denylisted prefix")));
+}
+
+/// Ensures a peer flooding the endpoint with connection attempts is rate limited without
+/// affecting peers on other addresses
+#[test]
+fn rate_limiter_test() {
+    const ATTEMPTS: usize = 10;
+
+    let model = Model::default();
+    let connected = Arc::new(Mutex::new(0));
+    let attacker_connected = connected.clone();
+
+    test(model, |handle| {
+        // the bucket doesn't refill during the test, so the attacker is allowed a single
+        // attempt, is sent a Retry for the next, and is dropped after that
+        let limiter = RateLimiter::builder()
+            .with_rate(1, Duration::from_secs(1000))?
+            .with_burst(1)?
+            .with_retry_burst(1)?
+            .build()?;
+
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(limiter)?
+            .start()?;
+        let server_addr = start_server(server)?;
+
+        // each client is bound to a separate address
+        let attacker = build_client(handle)?;
+        let client = build_client(handle)?;
+        let connect = Connect::new(server_addr).with_server_name("localhost");
+
+        for _ in 0..ATTEMPTS {
+            let attacker = attacker.clone();
+            let connect = connect.clone();
+            let connected = attacker_connected.clone();
+            primary::spawn(async move {
+                if attacker.connect(connect).await.is_ok() {
+                    *connected.lock().unwrap() += 1;
+                }
+            });
+        }
+
+        primary::spawn(async move {
+            delay(Duration::from_secs(1)).await;
+            client.connect(connect).await.unwrap();
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    assert_eq!(*connected.lock().unwrap(), 2);
+}
+
+/// Caps the number of established connections from each peer address using the lifecycle hooks
+struct PerIpConcurrencyLimiter {
+    max_connections: usize,
+    connections: Arc<Mutex<HashMap<Vec<u8>, usize>>>,
+}
+
+impl PerIpConcurrencyLimiter {
+    fn new(max_connections: usize) -> Self {
+        Self {
+            max_connections,
+            connections: Default::default(),
+        }
+    }
+}
+
+fn ip_key(address: &events::SocketAddress) -> Vec<u8> {
+    match address {
+        events::SocketAddress::IpV4 { ip, .. } => ip.to_vec(),
+        events::SocketAddress::IpV6 { ip, .. } => ip.to_vec(),
+        _ => vec![],
+    }
+}
+
+impl Limiter for PerIpConcurrencyLimiter {
+    fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+        let connections = self.connections.lock().unwrap();
+        let count = connections
+            .get(&ip_key(&info.remote_address))
+            .copied()
+            .unwrap_or(0);
+
+        if count >= self.max_connections {
+            Outcome::close()
+        } else {
+            Outcome::allow()
+        }
+    }
+
+    fn on_handshake_completed(&mut self, info: &HandshakeInfo) {
+        let mut connections = self.connections.lock().unwrap();
+        *connections.entry(ip_key(&info.remote_address)).or_default() += 1;
+    }
+
+    fn on_connection_closed(&mut self, info: &ConnectionCloseInfo) {
+        // only connections that completed the handshake were counted
+        if !info.handshake_completed {
+            return;
+        }
+
+        let mut connections = self.connections.lock().unwrap();
+        let key = ip_key(&info.remote_address);
+        if let Some(count) = connections.get_mut(&key) {
+            *count -= 1;
+            if *count == 0 {
+                connections.remove(&key);
+            }
+        }
+    }
+}
+
+/// Ensures the limiter is notified when connections complete their handshake and close, so
+/// per-peer counters balance
+#[test]
+fn lifecycle_hooks_test() {
+    let model = Model::default();
+    let limiter = PerIpConcurrencyLimiter::new(1);
+    let connections = limiter.connections.clone();
+
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(limiter)?
+            .start()?;
+        let server_addr = start_server(server)?;
+        let client = build_client(handle)?;
+        let other_client = build_client(handle)?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+
+            for _ in 0..3 {
+                let connection = client.connect(connect.clone()).await.unwrap();
+
+                // the peer is at its limit while the connection is open
+                assert!(client.connect(connect.clone()).await.is_err());
+                // other peers aren't affected
+                drop(other_client.connect(connect.clone()).await.unwrap());
+
+                // closing the connection makes room for the peer to reconnect
+                drop(connection);
+                delay(Duration::from_secs(5)).await;
+            }
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    assert!(connections.lock().unwrap().is_empty());
+}
+
+/// The state shared between [`ExternalDecision`] and the simulated service deciding on attempts
+#[derive(Default)]
+struct DecisionState {
+    outcome: Option<Outcome>,
+    waker: Option<Waker>,
+    /// The id of each attempt the service was queried for
+    queries: Vec<AttemptId>,
+    /// The id of each attempt the endpoint stopped waiting for
+    cancelled: Vec<AttemptId>,
+    polls: usize,
+}
+
+/// A limiter which waits for an external service to decide on each connection attempt
+#[derive(Clone)]
+struct ExternalDecision {
+    state: Arc<Mutex<DecisionState>>,
+    timeout: Duration,
+}
+
+impl ExternalDecision {
+    fn new(timeout: Duration) -> Self {
+        Self {
+            state: Default::default(),
+            timeout,
+        }
+    }
+
+    fn decide(&self, outcome: Outcome) {
+        let waker = {
+            let mut state = self.state.lock().unwrap();
+            state.outcome = Some(outcome);
+            state.waker.take()
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl Limiter for ExternalDecision {
+    fn on_connection_attempt(&mut self, _info: &ConnectionAttempt) -> Outcome {
+        unreachable!("the endpoint should poll for the outcome")
+    }
+
+    fn poll_on_connection_attempt(
+        &mut self,
+        cx: &mut Context,
+        info: &ConnectionAttempt,
+    ) -> Poll<Outcome> {
+        let mut state = self.state.lock().unwrap();
+        state.polls += 1;
+
+        if !state.queries.contains(&info.attempt_id) {
+            state.queries.push(info.attempt_id);
+        }
+
+        if let Some(outcome) = state.outcome.clone() {
+            return Poll::Ready(outcome);
+        }
+
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    fn on_connection_attempt_cancelled(&mut self, attempt_id: AttemptId) {
+        self.state.lock().unwrap().cancelled.push(attempt_id);
+    }
+
+    fn pending_attempt_timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+/// Ensures the handshake only proceeds once a pending decision arrives, and that retransmitted
+/// Initial packets don't query the limiter again
+#[test]
+fn pending_outcome_test() {
+    let decision_delay = Duration::from_secs(3);
+    let model = Model::default();
+    let limiter = ExternalDecision::new(Duration::from_secs(10));
+    let service = limiter.clone();
+    let state = limiter.state.clone();
+
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(limiter)?
+            .start()?;
+        let server_addr = start_server(server)?;
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            delay(decision_delay).await;
+            service.decide(Outcome::allow());
+        });
+
+        primary::spawn(async move {
+            let start = io::now();
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            client.connect(connect).await.unwrap();
+            assert!(io::now().saturating_duration_since(start) >= decision_delay);
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    let state = state.lock().unwrap();
+    assert_eq!(state.queries.len(), 1, "{:?}", state.queries);
+    assert!(state.polls > 1);
+    assert!(state.cancelled.is_empty(), "{:?}", state.cancelled);
+}
+
+/// Ensures attempts are dropped and reported as cancelled if the limiter takes longer than its
+/// timeout to decide
+#[test]
+fn pending_outcome_timeout_test() {
+    let model = Model::default();
+    let limiter = ExternalDecision::new(Duration::from_millis(500));
+    let state = limiter.state.clone();
+
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(limiter)?
+            .start()?;
+        let server_addr = start_server(server)?;
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            assert!(client.connect(connect).await.is_err());
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    let state = state.lock().unwrap();
+
+    // each retransmission after the timeout is a new query
+    assert!(state.queries.len() > 1);
+
+    // every attempt the endpoint stopped waiting for was reported once
+    let mut cancelled = state.cancelled.clone();
+    cancelled.dedup();
+    assert_eq!(cancelled, state.cancelled);
+    assert!(!cancelled.is_empty());
+    assert!(cancelled.iter().all(|id| state.queries.contains(id)));
+}
+
+/// Records the original destination connection ID of each attempt
+#[derive(Clone, Default)]
+struct DcidLimiter(Arc<Mutex<Vec<Vec<u8>>>>);
+
+impl Limiter for DcidLimiter {
+    fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+        self.0
+            .lock()
+            .unwrap()
+            .push(info.original_destination_connection_id.to_vec());
+        Outcome::allow()
+    }
+}
+
+/// Records the destination connection ID of the first Initial packet sent by the client
+#[derive(Clone, Default)]
+struct DcidRecorder(Arc<Mutex<Option<Vec<u8>>>>);
+
+impl Interceptor for DcidRecorder {
+    fn intercept_tx_datagram(
+        &mut self,
+        _subject: &Subject,
+        _datagram: &Datagram,
+        payload: &mut EncoderBuffer,
+    ) {
+        let bytes = payload.as_mut_slice();
+
+        // the first byte, 4-byte version and connection ID length precede the connection ID
+        if bytes.len() < 6 || bytes[0] & 0x80 == 0 {
+            return;
+        }
+
+        let len = bytes[5] as usize;
+        if let Some(dcid) = bytes.get(6..6 + len) {
+            self.0.lock().unwrap().get_or_insert_with(|| dcid.to_vec());
+        }
+    }
+}
+
+/// Ensures the limiter sees the destination connection ID chosen by the client
+#[test]
+fn original_destination_connection_id_test() {
+    let model = Model::default();
+    let limiter = DcidLimiter::default();
+    let seen = limiter.0.clone();
+    let recorder = DcidRecorder::default();
+    let sent = recorder.0.clone();
+
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(limiter)?
+            .start()?;
+        let server_addr = start_server(server)?;
+
+        let client = Client::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_packet_interceptor(recorder)?
+            .start()?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            client.connect(connect).await.unwrap();
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    let sent = sent.lock().unwrap().clone().unwrap();
+    let seen = seen.lock().unwrap().clone();
+    assert!(!sent.is_empty());
+    assert_eq!(seen, vec![sent]);
+}
+
+/// Sends a Retry packet to attempts without a token and records the status of each token
+#[derive(Clone, Default)]
+struct TokenRecorder(Arc<Mutex<Vec<TokenStatus>>>);
+
+impl Limiter for TokenRecorder {
+    fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+        self.0.lock().unwrap().push(info.token_status);
+
+        if info.token_status == TokenStatus::None {
+            Outcome::retry()
+        } else {
+            Outcome::allow()
+        }
+    }
+}
+
+/// Corrupts the token in each Initial packet sent by the client
+#[derive(Default)]
+struct ForgeToken;
+
+impl Interceptor for ForgeToken {
+    fn intercept_tx_datagram(
+        &mut self,
+        _subject: &Subject,
+        _datagram: &Datagram,
+        payload: &mut EncoderBuffer,
+    ) {
+        let bytes = payload.as_mut_slice();
+
+        // only Initial packets carry a token
+        if bytes.len() < 6 || bytes[0] & 0xf0 != 0xc0 {
+            return;
+        }
+
+        // skip the first byte, version and connection IDs
+        let dcid_len = bytes[5] as usize;
+        let scid_len_index = 6 + dcid_len;
+        let token_len_index = match bytes.get(scid_len_index) {
+            Some(scid_len) => scid_len_index + 1 + *scid_len as usize,
+            None => return,
+        };
+
+        let buffer = DecoderBuffer::new(bytes.get(token_len_index..).unwrap_or(&[]));
+        let (token_len, remaining) = match buffer.decode::<VarInt>() {
+            Ok(value) => value,
+            Err(_) => return,
+        };
+
+        if token_len == VarInt::from_u8(0) {
+            return;
+        }
+
+        let token_index = bytes.len() - remaining.len();
+        bytes[token_index] ^= 0xff;
+    }
+}
+
+/// Returns the token status of each attempt and whether the client connected
+fn token_statuses(forge_token: bool) -> (Vec<TokenStatus>, bool) {
+    let model = Model::default();
+    let limiter = TokenRecorder::default();
+    let statuses = limiter.0.clone();
+    let connected = Arc::new(Mutex::new(false));
+    let client_connected = connected.clone();
+
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(limiter)?
+            .start()?;
+        let server_addr = start_server(server)?;
+
+        let client = Client::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?;
+        let client = if forge_token {
+            client.with_packet_interceptor(ForgeToken)?.start()?
+        } else {
+            client.start()?
+        };
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            *client_connected.lock().unwrap() = client.connect(connect).await.is_ok();
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    let statuses = statuses.lock().unwrap().clone();
+    let connected = *connected.lock().unwrap();
+    (statuses, connected)
+}
+
+/// Ensures the limiter is told when a client returns with the token from a Retry packet
+#[test]
+fn retry_token_status_test() {
+    let (statuses, connected) = token_statuses(false);
+
+    assert!(connected);
+    assert_eq!(
+        statuses,
+        vec![TokenStatus::None, TokenStatus::RetryToken { valid: true }]
+    );
+}
+
+/// Ensures forged tokens are reported to the limiter as invalid
+#[test]
+fn forged_token_status_test() {
+    let (statuses, connected) = token_statuses(true);
+
+    assert!(!connected);
+    assert_eq!(statuses[0], TokenStatus::None);
+    assert!(statuses[1..].iter().all(TokenStatus::is_invalid));
+    assert!(statuses.len() > 1);
+}
+
+/// Records the datagram length of each attempt and closes it, so the client doesn't retransmit
+#[derive(Clone, Default)]
+struct DatagramLenLimiter(Arc<Mutex<Vec<usize>>>);
+
+impl Limiter for DatagramLenLimiter {
+    fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+        self.0.lock().unwrap().push(info.datagram_len);
+        Outcome::close()
+    }
+}
+
+/// Records the length of the first datagram sent by the client
+#[derive(Clone, Default)]
+struct DatagramLenRecorder(Arc<Mutex<Option<usize>>>);
+
+impl Interceptor for DatagramLenRecorder {
+    fn intercept_tx_datagram(
+        &mut self,
+        _subject: &Subject,
+        _datagram: &Datagram,
+        payload: &mut EncoderBuffer,
+    ) {
+        let len = payload.as_mut_slice().len();
+        self.0.lock().unwrap().get_or_insert(len);
+    }
+}
+
+/// Ensures the limiter sees the length of the datagram sent by the client
+#[test]
+fn datagram_len_test() {
+    let model = Model::default();
+    let limiter = DatagramLenLimiter::default();
+    let seen = limiter.0.clone();
+    let recorder = DatagramLenRecorder::default();
+    let sent = recorder.0.clone();
+
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(limiter)?
+            .start()?;
+        let server_addr = start_server(server)?;
+
+        let client = Client::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_packet_interceptor(recorder)?
+            .start()?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            client.connect(connect).await.unwrap_err();
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    let sent = sent.lock().unwrap().unwrap();
+    let seen = seen.lock().unwrap().clone();
+
+    // the client pads its first Initial to fill the largest datagram allowed before the path
+    // MTU is probed
+    assert!(sent >= 1200);
+    assert_eq!(seen, vec![sent]);
+}
+
+/// Records the inflight handshakes seen by each attempt and how each connection was closed
+#[derive(Clone, Default)]
+struct HandshakeTracker {
+    attempts: Arc<Mutex<Vec<(usize, Timestamp)>>>,
+    closes: Arc<Mutex<Vec<(bool, Option<connection::Error>, Timestamp)>>>,
+}
+
+impl Limiter for HandshakeTracker {
+    fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+        self.attempts
+            .lock()
+            .unwrap()
+            .push((info.inflight_handshakes, info.timestamp));
+        Outcome::allow()
+    }
+
+    fn on_connection_closed(&mut self, info: &ConnectionCloseInfo) {
+        self.closes
+            .lock()
+            .unwrap()
+            .push((info.handshake_completed, info.error, info.timestamp));
+    }
+}
+
+/// Stops the handshake from completing by corrupting every datagram after the first
+#[derive(Default)]
+struct StallHandshake {
+    sent: usize,
+}
+
+impl Interceptor for StallHandshake {
+    fn intercept_tx_datagram(
+        &mut self,
+        _subject: &Subject,
+        _datagram: &Datagram,
+        payload: &mut EncoderBuffer,
+    ) {
+        self.sent += 1;
+        if self.sent > 1 {
+            // a zero first byte doesn't have the fixed bit set so the server discards it
+            payload.as_mut_slice().fill(0);
+        }
+    }
+}
+
+/// Ensures stalled handshakes are abandoned after the maximum handshake duration, without
+/// affecting handshakes which complete in time
+#[test]
+fn max_handshake_duration_test() {
+    let max_handshake_duration = Duration::from_secs(1);
+    let model = Model::default();
+    let tracker = HandshakeTracker::default();
+    let attempts = tracker.attempts.clone();
+    let closes = tracker.closes.clone();
+    let stalled_error = Arc::new(Mutex::new(None));
+    let client_error = stalled_error.clone();
+
+    test(model, |handle| {
+        let limits = endpoint_limits::Default::builder()
+            .with_max_handshake_duration(max_handshake_duration)?
+            .build()?
+            .and_then(tracker);
+
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(limits)?
+            .start()?;
+        let server_addr = start_server(server)?;
+
+        let stalled = Client::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_packet_interceptor(StallHandshake::default())?
+            .start()?;
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let error = stalled.connect(connect.clone()).await.unwrap_err();
+            *client_error.lock().unwrap() = Some(error);
+
+            // wait for the abandoned connection to be removed from the endpoint
+            delay(Duration::from_secs(10)).await;
+
+            let start = io::now();
+            client.connect(connect).await.unwrap();
+            assert!(io::now().saturating_duration_since(start) < max_handshake_duration);
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    // the stalled client is told the handshake was abandoned
+    let error = stalled_error.lock().unwrap().take().unwrap();
+    assert!(
+        matches!(
+            error,
+            connection::Error::Transport { code, .. } if code == transport::Error::CONNECTION_REFUSED.code
+        ),
+        "{error:?}"
+    );
+
+    let attempts = attempts.lock().unwrap().clone();
+    let closes = closes.lock().unwrap().clone();
+
+    // the abandoned handshake no longer counts towards the inflight handshakes
+    assert_eq!(attempts.len(), 2);
+    assert_eq!(attempts[1].0, 0);
+
+    let (handshake_completed, error, closed_at) = closes[0];
+    assert!(!handshake_completed);
+    assert!(
+        matches!(
+            error,
+            Some(connection::Error::MaxHandshakeDurationExceeded { max_handshake_duration: duration, .. })
+                if duration == max_handshake_duration
+        ),
+        "{error:?}"
+    );
+    assert!(closed_at.saturating_duration_since(attempts[0].1) >= max_handshake_duration);
+}
+
+/// Ensures clients which exceed their maximum handshake duration close silently, since only
+/// servers refuse handshakes which took too long
+#[test]
+fn client_max_handshake_duration_test() {
+    let max_handshake_duration = Duration::from_secs(1);
+    let model = Model::default();
+    let frames = recorder::FrameSent::new();
+    let sent = frames.events();
+    let error = Arc::new(Mutex::new(None));
+    let client_error = error.clone();
+
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            // corrupt every datagram so the client's handshake can't complete
+            .with_packet_interceptor(StallHandshake { sent: 1 })?
+            .start()?;
+        let server_addr = start_server(server)?;
+
+        let limits = provider::limits::Limits::default()
+            .with_max_handshake_duration(max_handshake_duration)?;
+        let client = Client::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event((tracing_events(), frames))?
+            .with_random(Random::with_seed(123))?
+            .with_limits(limits)?
+            .start()?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let error = client.connect(connect).await.unwrap_err();
+            *client_error.lock().unwrap() = Some(error);
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    let error = error.lock().unwrap().take().unwrap();
+    assert!(
+        matches!(
+            error,
+            connection::Error::MaxHandshakeDurationExceeded { max_handshake_duration: duration, .. }
+                if duration == max_handshake_duration
+        ),
+        "{error:?}"
+    );
+
+    // the client didn't send a CONNECTION_CLOSE frame
+    let sent = sent.lock().unwrap();
+    assert!(!sent.is_empty());
+    assert!(!sent
+        .iter()
+        .any(|event| matches!(event.frame, events::Frame::ConnectionClose { .. })));
+}
+
+/// A version number reserved for private use, which the server doesn't support
+const BOGUS_VERSION: u32 = 0xff0a_0b0c;
+
+/// Records the version of each attempt and returns a fixed outcome
+struct VersionRecorder {
+    outcome: Outcome,
+    versions: Arc<Mutex<Vec<u32>>>,
+}
+
+impl Limiter for VersionRecorder {
+    fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+        self.versions.lock().unwrap().push(info.quic_version);
+        self.outcome.clone()
+    }
+}
+
+/// Rewrites the version of each long header packet sent by the client and records whether a
+/// Version Negotiation packet was received
+#[derive(Clone, Default)]
+struct BogusVersion(Arc<Mutex<bool>>);
+
+impl Interceptor for BogusVersion {
+    fn intercept_tx_datagram(
+        &mut self,
+        _subject: &Subject,
+        _datagram: &Datagram,
+        payload: &mut EncoderBuffer,
+    ) {
+        let bytes = payload.as_mut_slice();
+        if bytes.len() >= 5 && bytes[0] & 0x80 != 0 {
+            bytes[1..5].copy_from_slice(&BOGUS_VERSION.to_be_bytes());
+        }
+    }
+
+    fn intercept_rx_datagram<'a>(
+        &mut self,
+        _subject: &Subject,
+        _datagram: &Datagram,
+        payload: DecoderBufferMut<'a>,
+    ) -> DecoderBufferMut<'a> {
+        let bytes = payload.into_less_safe_slice();
+
+        // Version Negotiation packets are long header packets with a zero version
+        if bytes.len() >= 5 && bytes[0] & 0x80 != 0 && bytes[1..5] == [0; 4] {
+            *self.0.lock().unwrap() = true;
+        }
+
+        DecoderBufferMut::new(bytes)
+    }
+}
+
+/// Returns the versions seen by the limiter and whether the client received a Version
+/// Negotiation packet
+fn bogus_version(outcome: Outcome) -> (Vec<u32>, bool) {
+    let model = Model::default();
+    let versions = Arc::new(Mutex::new(vec![]));
+    let limiter = VersionRecorder {
+        outcome,
+        versions: versions.clone(),
+    };
+    let interceptor = BogusVersion::default();
+    let negotiated = interceptor.0.clone();
+
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(limiter)?
+            .start()?;
+        let server_addr = start_server(server)?;
+
+        let client = Client::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_packet_interceptor(interceptor)?
+            .start()?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            client.connect(connect).await.unwrap_err();
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    let versions = versions.lock().unwrap().clone();
+    let negotiated = *negotiated.lock().unwrap();
+    (versions, negotiated)
+}
+
+/// Ensures the limiter sees unsupported versions and can drop them without a Version
+/// Negotiation packet being sent
+#[test]
+fn unsupported_version_drop_test() {
+    let (versions, negotiated) = bogus_version(Outcome::drop());
+
+    assert!(!versions.is_empty());
+    assert!(versions.iter().all(|version| *version == BOGUS_VERSION));
+    assert!(!negotiated);
+}
+
+/// Ensures the endpoint still negotiates the version when the limiter doesn't drop the attempt
+#[test]
+fn unsupported_version_negotiation_test() {
+    let (versions, negotiated) = bogus_version(Outcome::allow());
+
+    assert!(!versions.is_empty());
+    assert!(versions.iter().all(|version| *version == BOGUS_VERSION));
+    assert!(negotiated);
+}
+
+/// Counts the connection attempt outcomes reported by the endpoint
+#[derive(Clone, Default)]
+struct OutcomeRecorder(Arc<Mutex<endpoint_limits::LimiterStats>>);
+
+impl Subscriber for OutcomeRecorder {
+    type ConnectionContext = ();
+
+    fn create_connection_context(
+        &mut self,
+        _meta: &ConnectionMeta,
+        _info: &ConnectionInfo,
+    ) -> Self::ConnectionContext {
+    }
+
+    fn on_endpoint_connection_attempt_decided(
+        &mut self,
+        _meta: &EndpointMeta,
+        event: &events::EndpointConnectionAttemptDecided,
+    ) {
+        let mut stats = self.0.lock().unwrap();
+        match event.outcome {
+            events::ConnectionAttemptOutcome::Allow { .. } => stats.allowed += 1,
+            events::ConnectionAttemptOutcome::Retry { .. } => stats.retried += 1,
+            events::ConnectionAttemptOutcome::Drop { .. } => stats.dropped += 1,
+            events::ConnectionAttemptOutcome::Close { .. } => stats.closed += 1,
+            _ => {}
+        }
+        stats.max_inflight_handshakes =
+            stats.max_inflight_handshakes.max(event.inflight_handshakes);
+    }
+}
+
+/// Ensures the outcomes decided by the default limits are reported to the event subscriber once
+/// the limits are moved into the server
+#[test]
+fn outcome_stats_test() {
+    let model = Model::default();
+    let limits = endpoint_limits::Default::builder()
+        .with_max_connections(1)
+        .unwrap()
+        .build()
+        .unwrap();
+    let recorder = OutcomeRecorder::default();
+    let events = recorder.0.clone();
+
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event((tracing_events(), recorder))?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(limits)?
+            .start()?;
+        let server_addr = start_server(server)?;
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let connection = client.connect(connect.clone()).await.unwrap();
+
+            // the endpoint is at its connection limit while the connection is open
+            for _ in 0..3 {
+                assert!(client.connect(connect.clone()).await.is_err());
+            }
+
+            drop(connection);
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    let stats = *events.lock().unwrap();
+    assert_eq!(stats.allowed, 1);
+    assert!(stats.closed >= 3, "{stats:?}");
+}
+
+/// Ensures the default limits only send Retry packets once the attempt rate measured by the
+/// endpoint crosses the threshold
+#[test]
+fn retry_above_attempt_rate_test() {
+    const BURST: usize = 20;
+
+    let model = Model::default();
+    let recorder = RetryRecorder::default();
+    let retries_sent = recorder.retries_sent.clone();
+    let retries_before_burst = retries_sent.clone();
+    let connected = Arc::new(Mutex::new(0));
+    let burst_connected = connected.clone();
+
+    test(model, |handle| {
+        let limits = endpoint_limits::Default::builder()
+            .with_retry_above_attempt_rate(5.0)?
+            .build()?;
+
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event((tracing_events(), recorder))?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(limits)?
+            .start()?;
+        let server_addr = start_server(server)?;
+        let client = build_client(handle)?;
+        let connect = Connect::new(server_addr).with_server_name("localhost");
+
+        primary::spawn(async move {
+            // a few attempts spread out in time stay below the threshold
+            for _ in 0..3 {
+                drop(client.connect(connect.clone()).await.unwrap());
+                delay(Duration::from_secs(2)).await;
+            }
+            assert!(retries_before_burst.lock().unwrap().is_empty());
+
+            for _ in 0..BURST {
+                let client = client.clone();
+                let connect = connect.clone();
+                let connected = burst_connected.clone();
+                primary::spawn(async move {
+                    // clients sent a Retry still connect with the token
+                    drop(client.connect(connect).await.unwrap());
+                    *connected.lock().unwrap() += 1;
+                });
+            }
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    assert_eq!(*connected.lock().unwrap(), BURST);
+    let retries_sent = retries_sent.lock().unwrap().len();
+    assert!(retries_sent > 0 && retries_sent < BURST, "{retries_sent}");
+}
+
+/// Closes connections to a single server name once the client's hello is received
+#[derive(Default)]
+struct ServerNameLimiter {
+    names: Arc<Mutex<Vec<String>>>,
+}
+
+impl Limiter for ServerNameLimiter {
+    fn on_connection_attempt(&mut self, _info: &ConnectionAttempt) -> Outcome {
+        Outcome::allow()
+    }
+
+    fn on_server_name(&mut self, info: &ServerNameAttempt) -> Outcome {
+        self.names
+            .lock()
+            .unwrap()
+            .push(info.server_name.to_string());
+
+        if info.server_name == "victim.example.com" {
+            Outcome::close()
+        } else {
+            Outcome::allow()
+        }
+    }
+}
+
+/// Ensures the limiter can close connections based on the server name the client requested
+#[test]
+fn server_name_limit_test() {
+    let model = Model::default();
+    let limiter = ServerNameLimiter::default();
+    let names = limiter.names.clone();
+    let error = Arc::new(Mutex::new(None));
+    let client_error = error.clone();
+
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(limiter)?
+            .start()?;
+        let server_addr = start_server(server)?;
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            drop(client.connect(connect).await.unwrap());
+
+            let connect = Connect::new(server_addr).with_server_name("victim.example.com");
+            let error = client.connect(connect).await.unwrap_err();
+            *client_error.lock().unwrap() = Some(error);
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    let error = error.lock().unwrap().take().unwrap();
+    assert!(
+        matches!(
+            error,
+            connection::Error::Transport { code, .. } if code == transport::Error::CONNECTION_REFUSED.code
+        ),
+        "{error:?}"
+    );
+    assert_eq!(
+        *names.lock().unwrap(),
+        vec!["localhost".to_string(), "victim.example.com".to_string()]
+    );
+}
+
+/// Records the number of connections counted by a [`ConcurrencyLimiter`] as it changes
+struct ConcurrencyRecorder {
+    limiter: ConcurrencyLimiter,
+    /// The current and maximum number of connections counted
+    counts: Arc<Mutex<(usize, usize)>>,
+}
+
+impl ConcurrencyRecorder {
+    fn record(&self) {
+        let mut counts = self.counts.lock().unwrap();
+        counts.0 = self.limiter.connection_count();
+        counts.1 = counts.1.max(counts.0);
+    }
+}
+
+impl Limiter for ConcurrencyRecorder {
+    fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+        self.limiter.on_connection_attempt(info)
+    }
+
+    fn on_connection_opened(&mut self, info: &ConnectionOpenInfo) {
+        self.limiter.on_connection_opened(info);
+        self.record();
+    }
+
+    fn on_connection_closed(&mut self, info: &ConnectionCloseInfo) {
+        self.limiter.on_connection_closed(info);
+        self.record();
+    }
+}
+
+/// Opens and closes many connections from a few addresses, including handshakes which fail, and
+/// ensures the concurrency limiter's counts return to zero
+#[test]
+fn concurrency_limiter_soak_test() {
+    const CLIENTS: usize = 3;
+    const MAX_CONNECTIONS: usize = 2;
+    const ROUNDS: usize = 10;
+
+    let model = Model::default();
+    let limiter = ConcurrencyLimiter::builder()
+        .with_max_connections(MAX_CONNECTIONS)
+        .unwrap()
+        .build()
+        .unwrap();
+    let recorder = ConcurrencyRecorder {
+        limiter,
+        counts: Default::default(),
+    };
+    let counts = recorder.counts.clone();
+    let outcomes = Arc::new(Mutex::new((0, 0)));
+    let client_outcomes = outcomes.clone();
+
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(recorder)?
+            .start()?;
+        let server_addr = start_server(server)?;
+
+        for _ in 0..CLIENTS {
+            let client = build_client(handle)?;
+            let outcomes = client_outcomes.clone();
+
+            primary::spawn(async move {
+                for round in 0..ROUNDS {
+                    for attempt in 0..=MAX_CONNECTIONS {
+                        // the client rejects the server's certificate for this name, so every
+                        // round includes a handshake which never completes
+                        let server_name = if attempt == 0 && round % 2 == 0 {
+                            "wrong.example.com"
+                        } else {
+                            "localhost"
+                        };
+                        let connect = Connect::new(server_addr).with_server_name(server_name);
+                        let client = client.clone();
+                        let outcomes = outcomes.clone();
+
+                        primary::spawn(async move {
+                            match client.connect(connect).await {
+                                Ok(connection) => {
+                                    outcomes.lock().unwrap().0 += 1;
+                                    delay(Duration::from_millis(100)).await;
+                                    drop(connection);
+                                }
+                                Err(_) => outcomes.lock().unwrap().1 += 1,
+                            }
+                        });
+                    }
+
+                    delay(Duration::from_millis(50)).await;
+                }
+
+                // give the server time to finalize the closed connections
+                delay(Duration::from_secs(5)).await;
+            });
+        }
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    let (connected, failed) = *outcomes.lock().unwrap();
+    assert_eq!(connected + failed, CLIENTS * ROUNDS * (MAX_CONNECTIONS + 1));
+    assert!(connected > 0 && failed > 0, "{connected} {failed}");
+
+    let (current, max) = *counts.lock().unwrap();
+    assert_eq!(current, 0);
+    assert!(max <= CLIENTS * MAX_CONNECTIONS, "{max}");
+}
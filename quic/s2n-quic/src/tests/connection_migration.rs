@@ -218,3 +218,345 @@ fn port_rebind_test() {
 fn ip_and_port_rebind_test() {
     run_test(|addr| rebind_ip(rebind_port(addr)));
 }
+
+/// Denies migration attempts while too many paths are being validated
+struct ValidationLimit {
+    max_inflight_path_validations: usize,
+    /// Records the inflight path validations and whether the attempt was allowed
+    attempts: Arc<Mutex<Vec<(usize, bool)>>>,
+}
+
+impl provider::path_migration::Validator for ValidationLimit {
+    fn on_migration_attempt(
+        &mut self,
+        attempt: &provider::path_migration::Attempt,
+    ) -> provider::path_migration::Outcome {
+        use provider::path_migration::{DenyReason, Outcome, Validator as _};
+
+        let outcome = if attempt.inflight_path_validations >= self.max_inflight_path_validations {
+            Outcome::Deny(DenyReason::Limited)
+        } else {
+            provider::path_migration::Default::default().on_migration_attempt(attempt)
+        };
+
+        self.attempts
+            .lock()
+            .unwrap()
+            .push((attempt.inflight_path_validations, outcome == Outcome::Allow));
+
+        outcome
+    }
+}
+
+/// Ensures the path migration validator can cap the number of paths being validated
+/// for a client that rebinds faster than its paths can be validated
+#[test]
+fn path_validation_limit_test() {
+    let model = Model::default();
+    let rtt = Duration::from_millis(10);
+    // rebind before the previous path has a chance to be validated
+    let rebind_rate = rtt / 4;
+    let rebind_count = 4;
+
+    model.set_delay(rtt / 2);
+
+    let attempts = Arc::new(Mutex::new(vec![]));
+    let validator = ValidationLimit {
+        max_inflight_path_validations: 1,
+        attempts: attempts.clone(),
+    };
+
+    let on_socket = move |socket: io::Socket| {
+        spawn(async move {
+            let mut local_addr = socket.local_addr().unwrap();
+            for _ in 0..rebind_count {
+                local_addr = rebind_port(local_addr);
+                delay(rebind_rate).await;
+                socket.rebind(local_addr);
+            }
+        });
+    };
+
+    test(model, move |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(456))?
+            .with_path_migration(validator)?
+            .start()?;
+
+        let client_io = handle.builder().on_socket(on_socket).build()?;
+
+        let client = Client::builder()
+            .with_io(client_io)?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(456))?
+            .start()?;
+
+        let addr = start_server(server)?;
+        primary::spawn(async move {
+            let connect = Connect::new(addr).with_server_name("localhost");
+            let mut conn = client.connect(connect).await.unwrap();
+            let mut stream = conn.open_bidirectional_stream().await.unwrap();
+
+            stream.send(Bytes::from_static(b"A")).await.unwrap();
+
+            for _ in 0..rebind_count {
+                delay(rebind_rate).await;
+                stream.send(Bytes::from_static(b"B")).await.unwrap();
+            }
+
+            stream.finish().unwrap();
+
+            // the connection should continue once the pending validations complete
+            let mut received = vec![];
+            while let Some(chunk) = stream.receive().await.unwrap() {
+                received.extend_from_slice(&chunk);
+            }
+            assert_eq!(&received[..], &b"ABBBB"[..]);
+        });
+
+        Ok(addr)
+    })
+    .unwrap();
+
+    let attempts = attempts.lock().unwrap();
+    assert!(
+        attempts.iter().any(|(_, allowed)| !allowed),
+        "rebinding faster than paths are validated should exceed the limit"
+    );
+    for (inflight_path_validations, allowed) in attempts.iter() {
+        if *allowed {
+            assert!(*inflight_path_validations < 1);
+        }
+    }
+}
+
+/// Ensures a client can migrate the connection to a new local address in the
+/// middle of a transfer
+#[test]
+fn client_initiated_migration_test() {
+    let model = Model::default();
+    let rtt = Duration::from_millis(10);
+
+    model.set_delay(rtt / 2);
+
+    let client_socket = Arc::new(Mutex::new(None));
+    let client_socket_pub = client_socket.clone();
+    let on_socket = move |socket: io::Socket| {
+        *client_socket_pub.lock().unwrap() = Some(socket);
+    };
+
+    let migrated_addr = Arc::new(Mutex::new(None));
+    let migrated_addr_pub = migrated_addr.clone();
+
+    let active_paths = recorder::ActivePathUpdated::new();
+    let active_path_sub = active_paths.clone();
+
+    test(model, move |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event((tracing_events(), active_path_sub))?
+            .with_random(Random::with_seed(456))?
+            .start()?;
+
+        let client_io = handle.builder().on_socket(on_socket).build()?;
+
+        let client = Client::builder()
+            .with_io(client_io)?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(456))?
+            .start()?;
+
+        let addr = start_server(server)?;
+        primary::spawn(async move {
+            let connect = Connect::new(addr).with_server_name("localhost");
+            let mut conn = client.connect(connect).await.unwrap();
+            let stream = conn.open_bidirectional_stream().await.unwrap();
+            let (mut recv, mut send) = stream.split();
+
+            let data = Data::new(1_000_000);
+
+            primary::spawn(async move {
+                let mut send_data = data;
+                while let Some(chunk) = send_data.send_one(usize::MAX) {
+                    send.send(chunk).await.unwrap();
+                }
+                send.finish().unwrap();
+            });
+
+            primary::spawn(async move {
+                let mut recv_data = data;
+                while let Some(chunk) = recv.receive().await.unwrap() {
+                    recv_data.receive(&[chunk]);
+                }
+                assert!(recv_data.is_finished());
+            });
+
+            // wait for the handshake to be confirmed and the transfer to start
+            delay(rtt * 5).await;
+
+            let socket = client_socket.lock().unwrap().take().unwrap();
+            let local_addr = rebind_ip(socket.local_addr().unwrap());
+            socket.rebind(local_addr);
+
+            conn.migrate(local_addr).await.unwrap();
+            assert_eq!(conn.local_addr().unwrap(), local_addr);
+
+            *migrated_addr_pub.lock().unwrap() = Some(local_addr);
+        });
+
+        Ok(addr)
+    })
+    .unwrap();
+
+    let migrated_addr = migrated_addr
+        .lock()
+        .unwrap()
+        .expect("the client should complete the migration");
+    assert_eq!(
+        active_paths.events().lock().unwrap().last(),
+        Some(&migrated_addr),
+        "the server should switch to the migrated path"
+    );
+}
+
+/// Ensures servers are not able to initiate a connection migration
+#[test]
+fn server_initiated_migration_test() {
+    let model = Model::default();
+
+    test(model, |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(456))?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        spawn(async move {
+            while let Some(mut conn) = server.accept().await {
+                let local_addr = rebind_ip(conn.local_addr().unwrap());
+                assert_eq!(
+                    conn.migrate(local_addr).await,
+                    Err(crate::connection::MigrationError::InvalidEndpointType)
+                );
+            }
+        });
+
+        let client = build_client(handle)?;
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            client.connect(connect).await.unwrap();
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+}
+
+/// Ensures a transfer doesn't stall when the client's NAT rebinds its port in the
+/// middle of a stream
+#[test]
+fn nat_rebinding_stall_test() {
+    let model = Model::default();
+    let rtt = Duration::from_millis(10);
+
+    model.set_delay(rtt / 2);
+
+    let rebind_addr = Arc::new(Mutex::new(None));
+    let rebind_addr_pub = rebind_addr.clone();
+    let on_socket = move |socket: io::Socket| {
+        spawn(async move {
+            // wait for the transfer to be in progress
+            delay(rtt * 10).await;
+            let local_addr = rebind_port(socket.local_addr().unwrap());
+            *rebind_addr_pub.lock().unwrap() = Some(local_addr);
+            socket.rebind(local_addr);
+        });
+    };
+
+    let path_challenges = recorder::PathChallengeUpdated::new();
+    let path_challenges_sub = path_challenges.clone();
+
+    let max_stall = Arc::new(Mutex::new(Duration::ZERO));
+    let max_stall_pub = max_stall.clone();
+
+    test(model, move |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event((tracing_events(), path_challenges_sub))?
+            .with_random(Random::with_seed(456))?
+            .start()?;
+
+        let client_io = handle.builder().on_socket(on_socket).build()?;
+
+        let client = Client::builder()
+            .with_io(client_io)?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(456))?
+            .start()?;
+
+        let addr = start_server(server)?;
+        primary::spawn(async move {
+            let connect = Connect::new(addr).with_server_name("localhost");
+            let mut conn = client.connect(connect).await.unwrap();
+            let stream = conn.open_bidirectional_stream().await.unwrap();
+            let (mut recv, mut send) = stream.split();
+
+            let data = Data::new(10_000_000);
+
+            spawn(async move {
+                let mut send_data = data;
+                while let Some(chunk) = send_data.send_one(usize::MAX) {
+                    send.send(chunk).await.unwrap();
+                }
+                send.finish().unwrap();
+            });
+
+            let mut recv_data = data;
+            let mut last_received = None;
+            while let Some(chunk) = recv.receive().await.unwrap() {
+                let now = io::time::now();
+                if let Some(prev) = last_received.replace(now) {
+                    let stall = now.saturating_duration_since(prev);
+                    let mut max_stall = max_stall_pub.lock().unwrap();
+                    *max_stall = (*max_stall).max(stall);
+                }
+                recv_data.receive(&[chunk]);
+            }
+            assert!(recv_data.is_finished());
+        });
+
+        Ok(addr)
+    })
+    .unwrap();
+
+    let rebind_addr = rebind_addr
+        .lock()
+        .unwrap()
+        .expect("the client should rebind during the transfer");
+
+    // The server should validate the new address
+    let path_challenges = path_challenges.events();
+    let path_challenges = path_challenges.lock().unwrap();
+    assert!(path_challenges.contains(&(rebind_addr, recorder::PathChallengeStatus::Pending)));
+    assert!(path_challenges.contains(&(rebind_addr, recorder::PathChallengeStatus::Validated)));
+
+    // Data the server sent to the previous address while the client was rebinding is lost,
+    // which takes a round trip to recover once the server has observed the new address.
+    // The new path is validated in parallel, so the stall should be bounded by a round trip
+    // plus the validation round trip, with some allowance for delayed ACKs.
+    let max_stall = *max_stall.lock().unwrap();
+    assert!(
+        max_stall <= rtt * 3,
+        "the transfer stalled for {max_stall:?} after a NAT rebinding"
+    );
+}
@@ -0,0 +1,169 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::provider::datagram::default::{DatagramError, Endpoint, Receiver, Sender};
+
+const DATAGRAM_COUNT: u32 = 100;
+const DATAGRAM_LEN: usize = 100;
+
+fn datagram(index: u32) -> Bytes {
+    let mut data = vec![0; DATAGRAM_LEN];
+    data[..4].copy_from_slice(&index.to_be_bytes());
+    Bytes::from(data)
+}
+
+fn datagram_index(data: &[u8]) -> u32 {
+    assert_eq!(data.len(), DATAGRAM_LEN);
+    u32::from_be_bytes([data[0], data[1], data[2], data[3]])
+}
+
+fn datagram_endpoint() -> Endpoint {
+    Endpoint::builder()
+        .with_send_capacity(200)
+        .unwrap()
+        .with_recv_capacity(DATAGRAM_COUNT as usize)
+        .unwrap()
+        .build()
+        .unwrap()
+}
+
+// Construct a simulation where the server sends datagrams to the client at a fixed
+// interval. The indices of the datagrams the client receives are returned at the
+// end of the simulation.
+fn datagram_transfer<I>(client_interceptor: I) -> Vec<u32>
+where
+    I: 'static + provider::packet_interceptor::PacketInterceptor + Send,
+{
+    let model = Model::default();
+    let received = Arc::new(Mutex::new(vec![]));
+    let client_received = received.clone();
+
+    test(model, |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(456))?
+            .with_datagram(datagram_endpoint())?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        spawn(async move {
+            let connection = server.accept().await.unwrap();
+
+            for index in 0..DATAGRAM_COUNT {
+                connection
+                    .datagram_mut(|sender: &mut Sender| sender.send_datagram(datagram(index)))
+                    .unwrap()
+                    .unwrap();
+                delay(Duration::from_millis(10)).await;
+            }
+
+            // The client advertises the recommended frame size so larger datagrams are rejected
+            let oversized = Bytes::from(vec![0; u16::MAX as usize + 1]);
+            let result = connection
+                .datagram_mut(|sender: &mut Sender| sender.send_datagram(oversized))
+                .unwrap();
+            assert_eq!(result, Err(DatagramError::ExceedsPeerTransportLimits));
+
+            // The max datagram size reflects the packet space on the current path
+            let max_datagram_size = connection
+                .datagram_mut(|sender: &mut Sender| sender.max_datagram_size())
+                .unwrap();
+            assert!(max_datagram_size >= DATAGRAM_LEN);
+            assert!(max_datagram_size < 1500);
+
+            // keep the connection open while the client drains its queue
+            delay(Duration::from_secs(10)).await;
+        });
+
+        let client = Client::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(456))?
+            .with_datagram(datagram_endpoint())?
+            .with_packet_interceptor(client_interceptor)?
+            .start()?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let connection = client.connect(connect).await.unwrap();
+
+            // Give the server enough time to send all of the datagrams and for any
+            // lost packets to be declared lost and recovered
+            delay(Duration::from_secs(5)).await;
+
+            let mut received = client_received.lock().unwrap();
+            while let Some(data) = connection
+                .datagram_mut(|receiver: &mut Receiver| receiver.recv_datagram())
+                .unwrap()
+            {
+                received.push(datagram_index(&data));
+            }
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    let received = received.lock().unwrap();
+    received.clone()
+}
+
+#[test]
+fn datagram_test() {
+    let received = datagram_transfer(provider::packet_interceptor::Disabled);
+    let expected: Vec<_> = (0..DATAGRAM_COUNT).collect();
+    assert_eq!(received, expected);
+}
+
+// Datagrams in lost packets are never retransmitted, so the client only receives a subset
+// of the datagrams, each at most once and in the order they were sent.
+#[test]
+fn datagram_loss_test() {
+    let loss = Loss::builder(Random::with_seed(123))
+        .with_rx_loss(0..2)
+        .with_rx_pass(1..4)
+        .build();
+    let received = datagram_transfer(loss);
+
+    assert!(!received.is_empty());
+    assert!(received.len() < DATAGRAM_COUNT as usize);
+    assert!(received.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+// Datagrams are rejected when the peer does not advertise support for them
+#[test]
+fn datagram_unsupported_test() {
+    let model = Model::default();
+    test(model, |handle| {
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(456))?
+            .with_datagram(datagram_endpoint())?
+            .start()?;
+        let server_addr = server.local_addr()?;
+
+        primary::spawn(async move {
+            let connection = server.accept().await.unwrap();
+            let result = connection
+                .datagram_mut(|sender: &mut Sender| sender.send_datagram(datagram(0)))
+                .unwrap();
+            assert_eq!(result, Err(DatagramError::ExceedsPeerTransportLimits));
+        });
+
+        let client = build_client(handle)?;
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            let _connection = client.connect(connect).await.unwrap();
+            delay(Duration::from_secs(1)).await;
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+}
@@ -0,0 +1,60 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::provider::limits::Limits;
+
+fn limits(grease: bool) -> Limits {
+    Limits::default().with_grease(grease).unwrap()
+}
+
+// Connects a client to a server with greasing enabled or disabled on each endpoint and
+// transfers data over a stream
+fn grease_test(server_grease: bool, client_grease: bool, seed: u64) {
+    let model = Model::default();
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(seed))?
+            .with_limits(limits(server_grease))?
+            .start()?;
+        let server_addr = start_server(server)?;
+
+        let client = Client::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(certificates::CERT_PEM)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(seed))?
+            .with_limits(limits(client_grease))?
+            .start()?;
+        start_client(client, server_addr, Data::new(10_000))?;
+
+        Ok(server_addr)
+    })
+    .unwrap();
+}
+
+#[test]
+fn grease_enabled_test() {
+    // each seed generates different reserved transport parameters and versions
+    for seed in 0..10 {
+        grease_test(true, true, seed);
+    }
+}
+
+#[test]
+fn grease_disabled_test() {
+    grease_test(false, false, 123);
+}
+
+#[test]
+fn grease_client_only_test() {
+    grease_test(false, true, 123);
+}
+
+#[test]
+fn grease_server_only_test() {
+    grease_test(true, false, 123);
+}
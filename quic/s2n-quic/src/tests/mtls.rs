@@ -153,3 +153,98 @@ fn mtls_auth_failure() {
     // confirm server connection was attempted but failed
     assert!(server_connection_closed.load(Ordering::SeqCst));
 }
+
+/// Records the certificate chain presented by the peer
+struct PeerCertChain;
+
+/// Returns the DER encoding of the first certificate in a PEM file
+fn leaf_der(pem: &str) -> Vec<u8> {
+    rustls_pemfile::certs(&mut pem.as_bytes())
+        .unwrap()
+        .into_iter()
+        .next()
+        .unwrap()
+}
+
+impl events::Subscriber for PeerCertChain {
+    type ConnectionContext = Option<Vec<Vec<u8>>>;
+
+    fn create_connection_context(
+        &mut self,
+        _: &events::ConnectionMeta,
+        _info: &events::ConnectionInfo,
+    ) -> Self::ConnectionContext {
+        None
+    }
+
+    fn on_tls_exporter_ready(
+        &mut self,
+        context: &mut Self::ConnectionContext,
+        _meta: &events::ConnectionMeta,
+        event: &events::TlsExporterReady,
+    ) {
+        *context = event.session.peer_cert_chain_der().ok();
+    }
+}
+
+#[test]
+fn mtls_peer_cert_chain() {
+    let model = Model::default();
+    model.set_delay(Duration::from_millis(50));
+
+    test(model, |handle| {
+        let server_tls = build_server_mtls_provider(certificates::MTLS_CA_CERT)?;
+        let mut server = Server::builder()
+            .with_io(handle.builder().build()?)?
+            .with_tls(server_tls)?
+            .with_event((tracing_events(), PeerCertChain))?
+            .with_random(Random::with_seed(456))?
+            .start()?;
+
+        let addr = server.local_addr()?;
+        spawn(async move {
+            let mut conn = server.accept().await.unwrap();
+            let chain = conn
+                .query_event_context(|chain: &Option<Vec<Vec<u8>>>| chain.clone())
+                .unwrap()
+                .expect("the chain should be available once the handshake completes");
+            assert_eq!(
+                chain.first(),
+                Some(&leaf_der(certificates::MTLS_CLIENT_CERT)),
+                "the chain should start with the client's certificate"
+            );
+
+            let mut stream = conn.open_bidirectional_stream().await.unwrap();
+            stream.send(vec![42; 10].into()).await.unwrap();
+            stream.flush().await.unwrap();
+        });
+
+        let client_tls = build_client_mtls_provider(certificates::MTLS_CA_CERT)?;
+        let client = Client::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(client_tls)?
+            .with_event((tracing_events(), PeerCertChain))?
+            .with_random(Random::with_seed(456))?
+            .start()?;
+
+        primary::spawn(async move {
+            let connect = Connect::new(addr).with_server_name("localhost");
+            let mut conn = client.connect(connect).await.unwrap();
+            let chain = conn
+                .query_event_context(|chain: &Option<Vec<Vec<u8>>>| chain.clone())
+                .unwrap()
+                .expect("the chain should be available once the handshake completes");
+            assert_eq!(
+                chain.first(),
+                Some(&leaf_der(certificates::MTLS_SERVER_CERT)),
+                "the chain should start with the server's certificate"
+            );
+
+            let mut stream = conn.accept_bidirectional_stream().await.unwrap().unwrap();
+            while stream.receive().await.unwrap().is_some() {}
+        });
+
+        Ok(addr)
+    })
+    .unwrap();
+}
@@ -0,0 +1,165 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::provider::{
+    address_token_store::{Bounded, Store},
+    endpoint_limits::{self, ConnectionAttempt, Limiter, Outcome, TokenStatus},
+};
+use s2n_quic_core::application::ServerName;
+
+/// Retries every attempt which didn't validate its address, as the default limits do under load,
+/// and records the token status of each attempt along with whether it was retried
+struct RetryUnderLoad {
+    limits: endpoint_limits::Default,
+    attempts: Arc<Mutex<Vec<(TokenStatus, bool)>>>,
+}
+
+impl RetryUnderLoad {
+    fn new(attempts: Arc<Mutex<Vec<(TokenStatus, bool)>>>) -> Self {
+        let limits = endpoint_limits::Default::builder()
+            .with_retry_above_attempt_rate(0.0)
+            .unwrap()
+            .build()
+            .unwrap();
+        Self { limits, attempts }
+    }
+}
+
+impl Limiter for RetryUnderLoad {
+    fn on_connection_attempt(&mut self, info: &ConnectionAttempt) -> Outcome {
+        let outcome = self.limits.on_connection_attempt(info);
+        self.attempts
+            .lock()
+            .unwrap()
+            .push((info.token_status, outcome.is_retry()));
+        outcome
+    }
+}
+
+/// A token store shared between client endpoints, to replay tokens from another address
+#[derive(Clone, Default)]
+struct SharedStore(Arc<Mutex<Bounded>>);
+
+impl Store for SharedStore {
+    fn on_new_token(&mut self, server_name: &ServerName, token: &[u8]) {
+        self.0.lock().unwrap().on_new_token(server_name, token)
+    }
+
+    fn take_token(&mut self, server_name: &ServerName) -> Option<Vec<u8>> {
+        self.0.lock().unwrap().take_token(server_name)
+    }
+}
+
+fn build_client_with_store(handle: &io::Handle, store: SharedStore) -> io::Result<Client> {
+    Ok(Client::builder()
+        .with_io(handle.builder().build().unwrap())?
+        .with_tls(certificates::CERT_PEM)?
+        .with_event(tracing_events())?
+        .with_random(Random::with_seed(123))?
+        .with_address_token_store(store)?
+        .start()?)
+}
+
+/// Connects to the server and keeps the connection open long enough to receive a NEW_TOKEN frame
+async fn connect_and_wait(client: &Client, server_addr: SocketAddr) {
+    let connect = Connect::new(server_addr).with_server_name("localhost");
+    let connection = client.connect(connect).await.unwrap();
+    delay(Duration::from_secs(1)).await;
+    drop(connection);
+}
+
+/// Ensures a client returning to a server with the token from a NEW_TOKEN frame is admitted
+/// without a Retry, even while the server retries every other attempt
+#[test]
+fn new_token_skips_retry_test() {
+    let model = Model::default();
+    let attempts = Arc::new(Mutex::new(vec![]));
+    let limiter = RetryUnderLoad::new(attempts.clone());
+    let connected = Arc::new(Mutex::new(0));
+    let client_connected = connected.clone();
+
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(limiter)?
+            .start()?;
+        let server_addr = start_server(server)?;
+        let client = build_client_with_store(handle, SharedStore::default())?;
+
+        primary::spawn(async move {
+            for _ in 0..2 {
+                connect_and_wait(&client, server_addr).await;
+                *client_connected.lock().unwrap() += 1;
+            }
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    assert_eq!(*connected.lock().unwrap(), 2);
+    assert_eq!(
+        *attempts.lock().unwrap(),
+        vec![
+            // the first connection is sent a Retry packet
+            (TokenStatus::None, true),
+            (TokenStatus::RetryToken { valid: true }, false),
+            // the second connection is admitted with the token from the first
+            (TokenStatus::NewToken { valid: true }, false),
+        ]
+    );
+}
+
+/// Ensures a token from a NEW_TOKEN frame isn't accepted when it's replayed from a different
+/// address, and that the client falls back to the Retry round trip
+#[test]
+fn new_token_replay_test() {
+    let model = Model::default();
+    let attempts = Arc::new(Mutex::new(vec![]));
+    let limiter = RetryUnderLoad::new(attempts.clone());
+    let connected = Arc::new(Mutex::new(0));
+    let client_connected = connected.clone();
+
+    test(model, |handle| {
+        let server = Server::builder()
+            .with_io(handle.builder().build().unwrap())?
+            .with_tls(SERVER_CERTS)?
+            .with_event(tracing_events())?
+            .with_random(Random::with_seed(123))?
+            .with_endpoint_limits(limiter)?
+            .start()?;
+        let server_addr = start_server(server)?;
+
+        // each client is bound to a separate address but they share the stored tokens
+        let store = SharedStore::default();
+        let client = build_client_with_store(handle, store.clone())?;
+        let other_client = build_client_with_store(handle, store)?;
+
+        primary::spawn(async move {
+            connect_and_wait(&client, server_addr).await;
+            *client_connected.lock().unwrap() += 1;
+
+            connect_and_wait(&other_client, server_addr).await;
+            *client_connected.lock().unwrap() += 1;
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    assert_eq!(*connected.lock().unwrap(), 2);
+    assert_eq!(
+        *attempts.lock().unwrap(),
+        vec![
+            (TokenStatus::None, true),
+            (TokenStatus::RetryToken { valid: true }, false),
+            // the token issued to the first client is rejected
+            (TokenStatus::NewToken { valid: false }, true),
+            (TokenStatus::RetryToken { valid: true }, false),
+        ]
+    );
+}
@@ -97,6 +97,41 @@ event_recorder!(
     }
 );
 
+event_recorder!(
+    PathChallengeUpdated,
+    PathChallengeUpdated,
+    on_path_challenge_updated,
+    (SocketAddr, PathChallengeStatus),
+    |event: &events::PathChallengeUpdated, storage: &mut Vec<(SocketAddr, PathChallengeStatus)>| {
+        let addr = (&event.path.remote_addr).into();
+        if let Ok(status) = (&event.path_challenge_status).try_into() {
+            storage.push((addr, status));
+        }
+    }
+);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathChallengeStatus {
+    Pending,
+    Validated,
+    Abandoned,
+}
+
+impl TryFrom<&events::PathChallengeStatus> for PathChallengeStatus {
+    type Error = ();
+
+    fn try_from(status: &events::PathChallengeStatus) -> Result<Self, ()> {
+        use events::PathChallengeStatus::*;
+
+        Ok(match status {
+            Pending { .. } => Self::Pending,
+            Validated { .. } => Self::Validated,
+            Abandoned { .. } => Self::Abandoned,
+            _ => return Err(()),
+        })
+    }
+}
+
 event_recorder!(
     PacketDropped,
     PacketDropped,
@@ -0,0 +1,100 @@
+// Copyright Amazon.com, Inc. or its affiliates. All Rights Reserved.
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::connection;
+
+/// Ensures connection attempts with a server name which is neither a DNS host name nor an IP
+/// literal are rejected before sending any packets
+#[test]
+fn invalid_server_name_test() {
+    let model = Model::default();
+    test(model, |handle| {
+        let server_addr = server(handle)?;
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            for server_name in ["localhost.", "", "exa mple.com", "[::1]"] {
+                let connect = Connect::new(server_addr).with_server_name(server_name);
+                let error = client.connect(connect).await.unwrap_err();
+                assert!(
+                    matches!(error, connection::Error::ImmediateClose { .. }),
+                    "{server_name:?} should be rejected: {error:?}"
+                );
+            }
+
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            client.connect(connect).await.unwrap();
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+}
+
+/// Ensures IP literals, including the remote address when no server name is specified, are
+/// accepted and used to verify the server's certificate
+#[test]
+fn ip_server_name_test() {
+    let model = Model::default();
+    test(model, |handle| {
+        let server_addr = server(handle)?;
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            let attempts = [
+                Connect::new(server_addr),
+                Connect::new(server_addr).with_server_name("127.0.0.1"),
+                Connect::new(server_addr).with_server_name("::1"),
+            ];
+
+            for connect in attempts {
+                // the test certificate is only issued for DNS names, so the server is contacted
+                // and the handshake fails verification instead of being rejected by the endpoint
+                let error = client.connect(connect.clone()).await.unwrap_err();
+                assert!(
+                    matches!(error, connection::Error::Transport { .. }),
+                    "{connect:#}: {error:?}"
+                );
+            }
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+}
+
+/// Ensures the server name specified with `with_server_name` takes precedence over the remote
+/// address being connected to
+#[test]
+fn server_name_override_test() {
+    let model = Model::default();
+    let names = Arc::new(Mutex::new(vec![]));
+    let server_names = names.clone();
+
+    test(model, |handle| {
+        let mut server = build_server(handle)?;
+        let server_addr = server.local_addr()?;
+        let client = build_client(handle)?;
+
+        primary::spawn(async move {
+            let connection = server.accept().await.unwrap();
+            let name = connection
+                .server_name()
+                .unwrap()
+                .map(|name| name.to_string());
+            server_names.lock().unwrap().push(name);
+        });
+
+        primary::spawn(async move {
+            let connect = Connect::new(server_addr).with_server_name("localhost");
+            assert_eq!(connect.server_name().map(|name| &**name), Some("localhost"));
+            client.connect(connect).await.unwrap();
+        });
+
+        Ok(server_addr)
+    })
+    .unwrap();
+
+    assert_eq!(*names.lock().unwrap(), [Some("localhost".to_string())]);
+}